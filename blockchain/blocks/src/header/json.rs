@@ -31,7 +31,8 @@ where
     #[derive(Serialize)]
     #[serde(rename_all = "PascalCase")]
     struct BlockHeaderSer<'a> {
-        miner: String,
+        #[serde(with = "address::json")]
+        miner: &'a Address,
         #[serde(with = "ticket::json")]
         ticket: &'a Ticket,
         #[serde(with = "election_proof::json::opt")]
@@ -61,7 +62,7 @@ where
     }
 
     BlockHeaderSer {
-        miner: m.miner_address.to_string(),
+        miner: &m.miner_address,
         ticket: &m.ticket,
         election_proof: &m.election_proof,
         win_post_proof: &m.win_post_proof,
@@ -87,7 +88,8 @@ where
     #[derive(Deserialize)]
     #[serde(rename_all = "PascalCase")]
     struct BlockHeaderDe {
-        miner: String,
+        #[serde(with = "address::json")]
+        miner: Address,
         #[serde(with = "ticket::json")]
         ticket: Ticket,
         #[serde(default, with = "election_proof::json::opt")]
@@ -119,7 +121,7 @@ where
     let v: BlockHeaderDe = Deserialize::deserialize(deserializer)?;
 
     Ok(BlockHeader::builder()
-        .miner_address(v.miner.parse().map_err(de::Error::custom)?)
+        .miner_address(v.miner)
         .ticket(v.ticket)
         .beacon_entries(v.beacon_entries)
         .epoch(v.height)
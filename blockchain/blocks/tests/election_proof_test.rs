@@ -0,0 +1,27 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crypto::{election_proof::ElectionProof, VRFProof};
+use encoding::{from_slice, to_vec};
+
+#[test]
+fn election_proof_encoding() {
+    let vrfproof = VRFProof::new(b"vrf proof".to_vec());
+
+    // Proofs without a win count keep the encoding from before multi-win blocks
+    let proof = ElectionProof {
+        win_count: None,
+        vrfproof: vrfproof.clone(),
+    };
+    let encoded = to_vec(&proof).unwrap();
+    assert_eq!(encoded, to_vec(&(&vrfproof,)).unwrap());
+    assert_eq!(from_slice::<ElectionProof>(&encoded).unwrap(), proof);
+
+    let proof = ElectionProof {
+        win_count: Some(2),
+        vrfproof: vrfproof.clone(),
+    };
+    let encoded = to_vec(&proof).unwrap();
+    assert_eq!(encoded, to_vec(&(2u64, &vrfproof)).unwrap());
+    assert_eq!(from_slice::<ElectionProof>(&encoded).unwrap(), proof);
+}
@@ -0,0 +1,107 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::Error;
+use actor::{
+    init, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR,
+    STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
+};
+use address::Address;
+use blocks::BlockHeader;
+use cid::Cid;
+use forest_car::load_car;
+use ipld_blockstore::BlockStore;
+use state_tree::StateTree;
+use std::io::{BufReader, Read};
+
+/// Loads a genesis CAR into the store and returns the store, the genesis state root and
+/// the genesis block header. Errors if the genesis state is missing any of the singleton
+/// actors or does not have a network name set.
+pub fn load_genesis<BS, R>(store: BS, reader: BufReader<R>) -> Result<(BS, Cid, BlockHeader), Error>
+where
+    BS: BlockStore,
+    R: Read,
+{
+    let roots = load_car(&store, reader).map_err(|e| Error::Other(e.to_string()))?;
+    if roots.len() != 1 {
+        return Err(Error::Other(format!(
+            "Genesis CAR must have exactly one root, has {}",
+            roots.len()
+        )));
+    }
+
+    let genesis: BlockHeader = store
+        .get(&roots[0])
+        .map_err(|e| Error::Other(e.to_string()))?
+        .ok_or_else(|| Error::NotFound("Genesis block"))?;
+    if genesis.epoch() != 0 {
+        return Err(Error::Other(format!(
+            "Genesis block must be at epoch 0, is at {}",
+            genesis.epoch()
+        )));
+    }
+
+    verify_state(&store, genesis.state_root())?;
+
+    let state_root = genesis.state_root().clone();
+    Ok((store, state_root, genesis))
+}
+
+/// Checks that all singleton actors exist in the state and the network name is set.
+pub(crate) fn verify_state<BS: BlockStore>(store: &BS, state_root: &Cid) -> Result<(), Error> {
+    let state = StateTree::new_from_root(store, state_root)?;
+
+    let singletons: [&Address; 8] = [
+        &SYSTEM_ACTOR_ADDR,
+        &INIT_ACTOR_ADDR,
+        &REWARD_ACTOR_ADDR,
+        &CRON_ACTOR_ADDR,
+        &STORAGE_POWER_ACTOR_ADDR,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &VERIFIED_REGISTRY_ACTOR_ADDR,
+        &BURNT_FUNDS_ACTOR_ADDR,
+    ];
+    for addr in singletons.iter() {
+        if state.get_actor(addr)?.is_none() {
+            return Err(Error::Other(format!(
+                "State is missing singleton actor {}",
+                addr
+            )));
+        }
+    }
+
+    let init_actor = state
+        .get_actor(&INIT_ACTOR_ADDR)?
+        .ok_or_else(|| Error::NotFound("Init actor"))?;
+    let init_state: init::State = store
+        .get(&init_actor.state)
+        .map_err(|e| Error::Other(e.to_string()))?
+        .ok_or_else(|| Error::NotFound("Init actor state"))?;
+    if init_state.network_name.is_empty() {
+        return Err(Error::Other("State has no network name".to_owned()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::MemoryDB;
+
+    #[test]
+    fn load_devnet_genesis() {
+        let bz = include_bytes!("../../../forest/src/cli/devnet.car");
+        let reader = BufReader::<&[u8]>::new(bz.as_ref());
+
+        let (store, state_root, genesis) = load_genesis(MemoryDB::default(), reader).unwrap();
+        assert_eq!(genesis.epoch(), 0);
+        assert_eq!(&state_root, genesis.state_root());
+
+        let state = StateTree::new_from_root(&store, &state_root).unwrap();
+        let init_actor = state.get_actor(&INIT_ACTOR_ADDR).unwrap().unwrap();
+        let init_state: init::State = store.get(&init_actor.state).unwrap().unwrap();
+        assert!(!init_state.network_name.is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod genesis;
+mod snapshot;
 mod store;
 
+pub use self::genesis::*;
+pub use self::snapshot::*;
 pub use self::store::*;
@@ -0,0 +1,210 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::genesis::verify_state;
+use super::{tipset_from_keys, Error};
+use blocks::{Tipset, TipsetKeys};
+use cid::{Cid, Codec};
+use clock::ChainEpoch;
+use forest_car::{load_car, CarWriter};
+use ipld_blockstore::{links_to_block, scan_for_links, BlockStore};
+use std::collections::HashSet;
+use std::io::{BufReader, Read, Write};
+
+/// Exports a pruned snapshot of the chain at the head tipset as a CAR, with the keys of the head
+/// as its roots. The snapshot contains the headers, messages and receipts of the tipsets within
+/// `recent_roots` epochs of the head, and the state tree at the parent state root of the head.
+/// Older headers and states are left out, so the snapshot only links to its oldest parents.
+pub fn export_snapshot<DB, W>(
+    db: &DB,
+    head: &Tipset,
+    recent_roots: ChainEpoch,
+    writer: &mut W,
+) -> Result<(), Error>
+where
+    DB: BlockStore,
+    W: Write,
+{
+    if recent_roots < 0 {
+        return Err(Error::Other(format!(
+            "Snapshot recent roots must not be negative, is {}",
+            recent_roots
+        )));
+    }
+    let car_err = |e: forest_car::Error| Error::Other(e.to_string());
+
+    let mut car_writer = CarWriter::new(writer, head.cids().to_vec()).map_err(car_err)?;
+    let min_epoch = head.epoch() - recent_roots;
+    let mut ts = head.clone();
+    loop {
+        for header in ts.blocks() {
+            car_writer.write_block(db, header.cid()).map_err(car_err)?;
+            car_writer
+                .write_dag(db, header.messages())
+                .map_err(car_err)?;
+            car_writer
+                .write_dag(db, header.message_receipts())
+                .map_err(car_err)?;
+        }
+        if ts.epoch() == 0 {
+            break;
+        }
+        let parent = tipset_from_keys(db, ts.parents())?;
+        if parent.epoch() < min_epoch {
+            break;
+        }
+        ts = parent;
+    }
+
+    car_writer
+        .write_dag(db, head.parent_state())
+        .map_err(car_err)
+}
+
+/// Loads a snapshot CAR into the store and returns the head tipset of the snapshot. Errors if
+/// the state tree at the parent state root of the head is incomplete or is missing any of the
+/// singleton actors, or if the messages or receipts of a tipset of the snapshot are incomplete.
+pub fn import_snapshot<DB, R>(db: &DB, reader: BufReader<R>) -> Result<Tipset, Error>
+where
+    DB: BlockStore,
+    R: Read,
+{
+    let roots = load_car(db, reader).map_err(|e| Error::Other(e.to_string()))?;
+    let head = tipset_from_keys(db, &TipsetKeys::new(roots))?;
+
+    verify_dag(db, head.parent_state())?;
+    verify_state(db, head.parent_state())?;
+
+    let mut ts = head.clone();
+    loop {
+        for header in ts.blocks() {
+            verify_dag(db, header.messages())?;
+            verify_dag(db, header.message_receipts())?;
+        }
+        if ts.epoch() == 0 {
+            break;
+        }
+        // The snapshot ends at the first tipset with pruned parents
+        ts = match tipset_from_keys(db, ts.parents()) {
+            Ok(parent) => parent,
+            Err(Error::NotFound(_)) => break,
+            Err(e) => return Err(e),
+        };
+    }
+
+    Ok(head)
+}
+
+/// Checks that all blocks reachable from the root are in the store.
+fn verify_dag<DB: BlockStore>(db: &DB, root: &Cid) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![root.clone()];
+    while let Some(cid) = stack.pop() {
+        if !links_to_block(&cid) || !seen.insert(cid.clone()) {
+            continue;
+        }
+        let data = db
+            .get_bytes(&cid)
+            .map_err(|e| Error::Other(e.to_string()))?
+            .ok_or_else(|| Error::Other(format!("Block {} is missing from snapshot", cid)))?;
+        if cid.codec == Codec::DagCBOR {
+            scan_for_links(&data, |c| {
+                stack.push(c);
+                Ok(())
+            })
+            .map_err(|e| Error::Other(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_genesis, persist_objects};
+    use address::Address;
+    use blocks::{BlockHeader, TxMeta};
+    use cid::multihash::Blake2b256;
+    use db::{MemoryDB, Store};
+    use ipld_amt::Amt;
+
+    /// Returns a store with the devnet genesis and a chain of empty tipsets on top of it at the
+    /// given epochs, and the tipsets of the chain starting with genesis.
+    fn chain(epochs: &[ChainEpoch]) -> (MemoryDB, Vec<Tipset>) {
+        let bz = include_bytes!("../../../forest/src/cli/devnet.car");
+        let reader = BufReader::<&[u8]>::new(bz.as_ref());
+        let (db, state_root, genesis) = load_genesis(MemoryDB::default(), reader).unwrap();
+
+        let empty_amt = Amt::<Cid, _>::new_from_slice(&db, &[]).unwrap();
+        let meta = TxMeta {
+            bls_message_root: empty_amt.clone(),
+            secp_message_root: empty_amt.clone(),
+        };
+        let messages = db.put(&meta, Blake2b256).unwrap();
+
+        let mut tipsets = vec![Tipset::new(vec![genesis]).unwrap()];
+        for &epoch in epochs {
+            let header = BlockHeader::builder()
+                .epoch(epoch)
+                .parents(tipsets.last().unwrap().key().clone())
+                .messages(messages.clone())
+                .message_receipts(empty_amt.clone())
+                .state_root(state_root.clone())
+                .miner_address(Address::new_id(0))
+                .build_and_validate()
+                .unwrap();
+            persist_objects(&db, &[header.clone()]).unwrap();
+            tipsets.push(Tipset::new(vec![header]).unwrap());
+        }
+        (db, tipsets)
+    }
+
+    #[test]
+    fn export_import_snapshot() {
+        let (db, tipsets) = chain(&[1, 2, 4]);
+        let head = tipsets.last().unwrap();
+
+        let mut car = Vec::new();
+        export_snapshot(&db, head, 2, &mut car).unwrap();
+
+        let store = MemoryDB::default();
+        let imported = import_snapshot(&store, BufReader::new(car.as_slice())).unwrap();
+        assert_eq!(&imported, head);
+
+        // Only the tipsets within the recent epochs of the head are included
+        let has_header = |ts: &Tipset| store.exists(ts.cids()[0].key()).unwrap();
+        assert!(has_header(&tipsets[3]));
+        assert!(has_header(&tipsets[2]));
+        assert!(!has_header(&tipsets[1]));
+        assert!(!has_header(&tipsets[0]));
+    }
+
+    #[test]
+    fn export_snapshot_to_genesis() {
+        let (db, tipsets) = chain(&[1]);
+        let head = tipsets.last().unwrap();
+
+        let mut car = Vec::new();
+        export_snapshot(&db, head, 10, &mut car).unwrap();
+
+        let store = MemoryDB::default();
+        import_snapshot(&store, BufReader::new(car.as_slice())).unwrap();
+        assert!(store.exists(tipsets[0].cids()[0].key()).unwrap());
+
+        assert!(export_snapshot(&db, head, -1, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn import_incomplete_snapshot() {
+        let (db, tipsets) = chain(&[1]);
+        let head = tipsets.last().unwrap();
+
+        // A snapshot of only the head headers is missing the state tree
+        let mut car = Vec::new();
+        let mut car_writer = CarWriter::new(&mut car, head.cids().to_vec()).unwrap();
+        car_writer.write_block(&db, &head.cids()[0]).unwrap();
+
+        let store = MemoryDB::default();
+        assert!(import_snapshot(&store, BufReader::new(car.as_slice())).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{Error, TipIndex, TipsetMetadata};
+use super::{Error, MsgIndex, TipIndex, TipsetMetadata};
 use actor::{power::State as PowerState, STORAGE_POWER_ACTOR_ADDR};
 use address::Address;
 use beacon::BeaconEntry;
@@ -58,6 +58,9 @@ pub struct ChainStore<DB> {
 
     // tip_index tracks tipsets by epoch/parentset for use by expected consensus.
     tip_index: TipIndex,
+
+    // msg_index tracks the receipts of the messages executed on the heaviest chain.
+    msg_index: MsgIndex,
 }
 
 impl<DB> ChainStore<DB>
@@ -73,13 +76,22 @@ where
             db,
             publisher: Publisher::new(SINK_CAP),
             tip_index: TipIndex::new(),
+            msg_index: MsgIndex::new(),
             heaviest,
         }
     }
 
-    /// Sets heaviest tipset within ChainStore and store its tipset cids under HEAD_KEY
+    /// Sets heaviest tipset within ChainStore and store its tipset cids under HEAD_KEY, and
+    /// indexes the receipts of the messages executed in its parent
     pub async fn set_heaviest_tipset(&mut self, ts: Arc<Tipset>) -> Result<(), Error> {
         self.db.write(HEAD_KEY, ts.key().marshal_cbor()?)?;
+        if let Err(e) = self.msg_index.put(self.db.as_ref(), &ts) {
+            warn!(
+                "Failed to index messages of tipset at {}: {}",
+                ts.epoch(),
+                e
+            );
+        }
         self.heaviest = Some(ts.clone());
         self.publisher.publish(HeadChange::Current(ts)).await;
         Ok(())
@@ -143,22 +155,7 @@ where
 
     /// Finds the latest beacon entry given a tipset up to 20 blocks behind
     pub fn latest_beacon_entry(&self, ts: &Tipset) -> Result<BeaconEntry, Error> {
-        let mut cur = ts.clone();
-        for _ in 1..20 {
-            let cbe = ts.blocks()[0].beacon_entries();
-            if let Some(entry) = cbe.last() {
-                return Ok(entry.clone());
-            }
-            if cur.epoch() == 0 {
-                return Err(Error::Other(
-                    "made it back to genesis block without finding beacon entry".to_owned(),
-                ));
-            }
-            cur = self.tipset_from_keys(cur.parents())?;
-        }
-        Err(Error::Other(
-            "Found no beacon entries in the 20 blocks prior to the given tipset".to_owned(),
-        ))
+        latest_beacon_entry(self.blockstore(), ts)
     }
 
     /// Returns heaviest tipset from blockstore
@@ -176,6 +173,24 @@ where
         tipset_from_keys(self.blockstore(), tsk)
     }
 
+    /// Returns the tipset a message was executed in and the message's receipt, if the message
+    /// was executed in the parent of a tipset set as heaviest
+    pub fn get_message_receipt(
+        &self,
+        msg: &Cid,
+    ) -> Result<Option<(Tipset, MessageReceipt)>, Error> {
+        let lookup = match self.msg_index.get(msg) {
+            Some(lookup) => lookup,
+            None => return Ok(None),
+        };
+        let ts = self.tipset_from_keys(&lookup.tipset)?;
+        let receipts = Amt::load(&lookup.receipts_root, self.blockstore())?;
+        let receipt = receipts
+            .get(lookup.index)?
+            .ok_or_else(|| Error::UndefinedKey(format!("receipt of message {}", msg)))?;
+        Ok(Some((ts, receipt)))
+    }
+
     /// Constructs and returns a full tipset if messages from storage exists
     pub fn fill_tipsets(&self, ts: Tipset) -> Result<FullTipset, Error> {
         let mut blocks: Vec<Block> = Vec::with_capacity(ts.blocks().len());
@@ -300,23 +315,76 @@ where
     Ok(())
 }
 
-/// Gets 32 bytes of randomness for ChainRand paramaterized by the DomainSeparationTag, ChainEpoch, Entropy
-pub fn get_randomness<DB: BlockStore>(
+/// Finds the latest beacon entry given a tipset up to 20 blocks behind
+pub fn latest_beacon_entry<DB: BlockStore>(db: &DB, ts: &Tipset) -> Result<BeaconEntry, Error> {
+    let mut cur = ts.clone();
+    for _ in 1..20 {
+        let cbe = cur.blocks()[0].beacon_entries();
+        if let Some(entry) = cbe.last() {
+            return Ok(entry.clone());
+        }
+        if cur.epoch() == 0 {
+            return Err(Error::Other(
+                "made it back to genesis block without finding beacon entry".to_owned(),
+            ));
+        }
+        cur = tipset_from_keys(db, cur.parents())?;
+    }
+    Err(Error::Other(
+        "Found no beacon entries in the 20 blocks prior to the given tipset".to_owned(),
+    ))
+}
+
+/// Returns the tipset at or before the round, walking back from the tipset of the given keys.
+/// Rounds before genesis resolve to the genesis tipset. Errors if the round is after the tipset.
+fn randomness_tipset<DB: BlockStore>(
+    db: &DB,
+    blocks: &TipsetKeys,
+    round: ChainEpoch,
+) -> Result<Tipset, Box<dyn std::error::Error>> {
+    let mut ts = tipset_from_keys(db, blocks)?;
+    if round > ts.epoch() {
+        return Err(format!(
+            "cannot draw randomness from the future, round {} is after tipset at {}",
+            round,
+            ts.epoch()
+        )
+        .into());
+    }
+    while ts.epoch() > round && ts.epoch() > 0 {
+        ts = tipset_from_keys(db, ts.parents())?;
+    }
+    Ok(ts)
+}
+
+/// Gets 32 bytes of randomness from the tickets of the chain, paramaterized by the
+/// DomainSeparationTag, ChainEpoch and Entropy. Randomness can't be drawn for rounds after the
+/// tipset, and rounds before genesis draw from the genesis ticket.
+pub fn get_chain_randomness<DB: BlockStore>(
     db: &DB,
     blocks: &TipsetKeys,
     pers: DomainSeparationTag,
     round: ChainEpoch,
     entropy: &[u8],
 ) -> Result<[u8; 32], Box<dyn std::error::Error>> {
-    let mut blks = blocks.clone();
-    loop {
-        let nts = tipset_from_keys(db, &blks)?;
-        let mtb = nts.min_ticket_block();
-        if nts.epoch() <= round || mtb.epoch() == 0 {
-            return draw_randomness(mtb.ticket().vrfproof.as_bytes(), pers, round, entropy);
-        }
-        blks = mtb.parents().clone();
-    }
+    let ts = randomness_tipset(db, blocks, round)?;
+    let mtb = ts.min_ticket_block();
+    draw_randomness(mtb.ticket().vrfproof.as_bytes(), pers, round, entropy)
+}
+
+/// Gets 32 bytes of randomness from the latest beacon entry of the chain at the round,
+/// paramaterized by the DomainSeparationTag, ChainEpoch and Entropy. Randomness can't be drawn
+/// for rounds after the tipset, and rounds before genesis draw from the genesis beacon entry.
+pub fn get_beacon_randomness<DB: BlockStore>(
+    db: &DB,
+    blocks: &TipsetKeys,
+    pers: DomainSeparationTag,
+    round: ChainEpoch,
+    entropy: &[u8],
+) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let ts = randomness_tipset(db, blocks, round)?;
+    let be = latest_beacon_entry(db, &ts)?;
+    draw_randomness(be.data(), pers, round, entropy)
 }
 
 /// Computes a pseudorandom 32 byte Vec
@@ -436,7 +504,7 @@ where
     })
 }
 
-/// Attempts to deserialize to unsigend message or signed message and then returns it at as a message trait object
+/// Loads an unsigned or signed message from the store, decoded as the type of the stored block
 pub fn get_chain_message<DB>(db: &DB, key: &Cid) -> Result<ChainMessage, Error>
 where
     DB: BlockStore,
@@ -444,12 +512,7 @@ where
     let value = db
         .read(key.key())?
         .ok_or_else(|| Error::UndefinedKey(key.to_string()))?;
-    if let Ok(message) = from_slice::<UnsignedMessage>(&value) {
-        Ok(ChainMessage::Unsigned(message))
-    } else {
-        let signed_message: SignedMessage = from_slice(&value)?;
-        Ok(ChainMessage::Signed(signed_message))
-    }
+    Ok(ChainMessage::unmarshal_block(&value)?)
 }
 
 /// given a tipset this function will return all messages
@@ -458,7 +521,7 @@ where
     DB: BlockStore,
 {
     let mut applied: HashMap<Address, u64> = HashMap::new();
-    let mut balances: HashMap<Address, BigInt> = HashMap::new();
+    let mut balances: HashMap<Address, _> = HashMap::new();
     let state = StateTree::new_from_root(db, ts.parent_state())?;
 
     // message to get all messages for block_header into a single iterator
@@ -470,7 +533,7 @@ where
 
         for message in unsigned_box.chain(signed_box) {
             let from_address = message.from();
-            if applied.contains_key(&from_address) {
+            if !applied.contains_key(from_address) {
                 let actor_state = state
                     .get_actor(from_address)?
                     .ok_or_else(|| Error::Other("Actor state not found".to_string()))?;
@@ -486,10 +549,14 @@ where
                 continue;
             }
             if let Some(bal) = balances.get_mut(from_address) {
-                if *bal < message.required_funds() {
+                let required = match message.required_funds() {
+                    Ok(required) => required,
+                    Err(_) => continue,
+                };
+                if &*bal < required.as_token_amount() {
                     continue;
                 }
-                *bal -= message.required_funds();
+                *bal -= required.as_token_amount();
             } else {
                 continue;
             }
@@ -594,4 +661,221 @@ mod tests {
         cs.set_genesis(gen_block.clone()).unwrap();
         assert_eq!(cs.genesis().unwrap(), Some(gen_block));
     }
+
+    #[test]
+    fn randomness_sources() {
+        use blocks::Ticket;
+        use crypto::VRFProof;
+
+        let db = db::MemoryDB::default();
+        let header = |epoch: ChainEpoch,
+                      parents: TipsetKeys,
+                      ticket: u8,
+                      beacon_entries: Vec<BeaconEntry>| {
+            BlockHeader::builder()
+                .epoch(epoch)
+                .parents(parents)
+                .ticket(Ticket::new(VRFProof::new(vec![ticket; 32])))
+                .beacon_entries(beacon_entries)
+                .miner_address(Address::new_id(0))
+                .build_and_validate()
+                .unwrap()
+        };
+        let entry = BeaconEntry::new(1, vec![7; 32]);
+        let genesis = header(0, TipsetKeys::default(), 1, vec![entry.clone()]);
+        // Epoch 1 is a null round, and the block at epoch 2 has no beacon entries
+        let child = header(2, TipsetKeys::new(vec![genesis.cid().clone()]), 2, vec![]);
+        persist_objects(&db, &[genesis, child.clone()]).unwrap();
+        let head = TipsetKeys::new(vec![child.cid().clone()]);
+
+        let pers = DomainSeparationTag::SealRandomness;
+        let ticket_rand = |ticket: u8, round: ChainEpoch| {
+            draw_randomness(&[ticket; 32], pers, round, &[3]).unwrap()
+        };
+        assert_eq!(
+            get_chain_randomness(&db, &head, pers, 2, &[3]).unwrap(),
+            ticket_rand(2, 2)
+        );
+        assert_eq!(
+            get_chain_randomness(&db, &head, pers, 1, &[3]).unwrap(),
+            ticket_rand(1, 1)
+        );
+        // Rounds before genesis draw from the genesis ticket
+        assert_eq!(
+            get_chain_randomness(&db, &head, pers, -5, &[3]).unwrap(),
+            ticket_rand(1, -5)
+        );
+        assert!(get_chain_randomness(&db, &head, pers, 3, &[3]).is_err());
+
+        // The latest beacon entry is used, which is in the genesis block
+        assert_eq!(
+            get_beacon_randomness(&db, &head, pers, 2, &[3]).unwrap(),
+            draw_randomness(entry.data(), pers, 2, &[3]).unwrap()
+        );
+        assert!(get_beacon_randomness(&db, &head, pers, 3, &[3]).is_err());
+    }
+
+    /// Stores the messages and returns the Cid of the block messages meta containing them
+    fn put_bls_messages(db: &db::MemoryDB, msgs: &[UnsignedMessage]) -> Cid {
+        let msg_cids: Vec<Cid> = msgs.iter().map(|m| m.cid().unwrap()).collect();
+        persist_objects(db, msgs).unwrap();
+        let meta = TxMeta {
+            bls_message_root: Amt::new_from_slice(db, &msg_cids).unwrap(),
+            secp_message_root: Amt::<Cid, _>::new_from_slice(db, &[]).unwrap(),
+        };
+        db.put(&meta, Blake2b256).unwrap()
+    }
+
+    fn message(from: Address, sequence: u64, value: u64) -> UnsignedMessage {
+        UnsignedMessage::builder()
+            .from(from)
+            .to(Address::new_id(101))
+            .sequence(sequence)
+            .value(value.into())
+            .build()
+            .unwrap()
+    }
+
+    /// Returns the state root of a state tree with account actors of the given balances
+    fn accounts_state(db: &db::MemoryDB, accounts: &[(Address, u64)]) -> Cid {
+        use actor::{ActorState, ACCOUNT_ACTOR_CODE_ID};
+
+        let mut state = StateTree::new(db);
+        for (addr, balance) in accounts {
+            let actor = ActorState::new(
+                ACCOUNT_ACTOR_CODE_ID.clone(),
+                Cid::new_from_cbor(&[], Identity),
+                (*balance).into(),
+                0,
+            );
+            state.set_actor(addr, actor).unwrap();
+        }
+        state.flush().unwrap()
+    }
+
+    #[test]
+    fn messages_for_tipset_skips_invalid() {
+        let db = db::MemoryDB::default();
+        let sender = Address::new_id(100);
+        let poor = Address::new_id(102);
+        let state_root = accounts_state(&db, &[(sender, 1000), (poor, 10)]);
+
+        let msgs = vec![
+            message(sender, 0, 1),
+            message(sender, 1, 1),
+            // Sequence already used
+            message(sender, 1, 2),
+            // Sequence gap
+            message(sender, 3, 1),
+            // Insufficient funds
+            message(poor, 0, 20),
+        ];
+        let header = BlockHeader::builder()
+            .epoch(1)
+            .messages(put_bls_messages(&db, &msgs))
+            .message_receipts(Cid::new_from_cbor(&[], Identity))
+            .state_root(state_root)
+            .miner_address(Address::new_id(0))
+            .build_and_validate()
+            .unwrap();
+        let ts = Tipset::new(vec![header]).unwrap();
+
+        let applied: Vec<Cid> = messages_for_tipset(&db, &ts)
+            .unwrap()
+            .iter()
+            .map(|m| m.cid().unwrap())
+            .collect();
+        assert_eq!(
+            applied,
+            vec![msgs[0].cid().unwrap(), msgs[1].cid().unwrap()]
+        );
+    }
+
+    #[test]
+    fn message_receipts_index() {
+        use actor::{ExitCode, Serialized};
+        use async_std::task;
+
+        let db = db::MemoryDB::default();
+        let sender = Address::new_id(100);
+        let state_root = accounts_state(&db, &[(sender, 1000)]);
+
+        let msgs: Vec<UnsignedMessage> = (0..2).map(|seq| message(sender, seq, 0)).collect();
+        let msg_cids: Vec<Cid> = msgs.iter().map(|m| m.cid().unwrap()).collect();
+        let messages = put_bls_messages(&db, &msgs);
+        let no_messages = put_bls_messages(&db, &[]);
+
+        let receipts: Vec<MessageReceipt> = (0..2)
+            .map(|gas_used| MessageReceipt {
+                exit_code: ExitCode::Ok,
+                return_data: Serialized::default(),
+                gas_used,
+            })
+            .collect();
+        let receipts_root = Amt::new_from_slice(&db, &receipts).unwrap();
+        let no_receipts = Amt::<MessageReceipt, _>::new_from_slice(&db, &[]).unwrap();
+
+        let header = |epoch: ChainEpoch, parents: TipsetKeys, messages: &Cid, receipts: &Cid| {
+            BlockHeader::builder()
+                .epoch(epoch)
+                .parents(parents)
+                .messages(messages.clone())
+                .message_receipts(receipts.clone())
+                .state_root(state_root.clone())
+                .miner_address(Address::new_id(epoch as u64))
+                .build_and_validate()
+                .unwrap()
+        };
+        let genesis = header(0, TipsetKeys::default(), &no_messages, &no_receipts);
+        let genesis_key = TipsetKeys::new(vec![genesis.cid().clone()]);
+        let parent = header(1, genesis_key.clone(), &messages, &no_receipts);
+        let child = header(
+            2,
+            TipsetKeys::new(vec![parent.cid().clone()]),
+            &no_messages,
+            &receipts_root,
+        );
+        // Sibling of the parent without messages, and a child of it
+        let fork = header(1, genesis_key.clone(), &no_messages, &no_receipts);
+        let fork_child = header(
+            2,
+            TipsetKeys::new(vec![fork.cid().clone()]),
+            &no_messages,
+            &no_receipts,
+        );
+        persist_objects(
+            &db,
+            &[
+                genesis.clone(),
+                parent.clone(),
+                child.clone(),
+                fork.clone(),
+                fork_child.clone(),
+            ],
+        )
+        .unwrap();
+        let tipset = |header: &BlockHeader| Arc::new(Tipset::new(vec![header.clone()]).unwrap());
+
+        let mut cs = ChainStore::new(Arc::new(db));
+        assert!(cs.get_message_receipt(&msg_cids[1]).unwrap().is_none());
+
+        for head in &[&genesis, &parent, &child] {
+            task::block_on(cs.set_heaviest_tipset(tipset(head))).unwrap();
+        }
+        for (i, cid) in msg_cids.iter().enumerate() {
+            let (ts, receipt) = cs.get_message_receipt(cid).unwrap().unwrap();
+            assert_eq!(ts, *tipset(&parent));
+            assert_eq!(receipt.gas_used, i as i64);
+        }
+        assert!(cs
+            .get_message_receipt(&Cid::new_from_cbor(&[], Identity))
+            .unwrap()
+            .is_none());
+
+        // Switching to the fork removes the messages only executed on the previous chain
+        task::block_on(cs.set_heaviest_tipset(tipset(&fork_child))).unwrap();
+        for cid in &msg_cids {
+            assert!(cs.get_message_receipt(cid).unwrap().is_none());
+        }
+    }
 }
@@ -3,8 +3,10 @@
 
 mod chain_store;
 mod errors;
+mod msg_index;
 mod tip_index;
 
 pub use self::chain_store::*;
 pub use self::errors::*;
+pub use self::msg_index::*;
 pub use self::tip_index::*;
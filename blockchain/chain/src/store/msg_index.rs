@@ -0,0 +1,106 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::{messages_for_tipset, tipset_from_keys, Error};
+use blocks::{Tipset, TipsetKeys};
+use cid::Cid;
+use clock::ChainEpoch;
+use encoding::Cbor;
+use ipld_blockstore::BlockStore;
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum number of tipsets whose messages are indexed, the oldest tipsets are evicted first.
+const MSG_INDEX_CAPACITY: usize = 900;
+
+/// Location of the receipt of an executed message.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MsgLookup {
+    /// Keys of the tipset the message was executed in.
+    pub tipset: TipsetKeys,
+    /// Epoch of the tipset the message was executed in.
+    pub epoch: ChainEpoch,
+    /// Root of the receipts of the tipset's messages, which is in the headers of its child.
+    pub receipts_root: Cid,
+    /// Index of the message's receipt.
+    pub index: u64,
+}
+
+/// Tracks the tipsets messages were executed in and the index of their receipts by message
+/// Cid, so that executed messages can be looked up without scanning the chain.
+///
+/// Only the most recent tipsets of the current head's chain are indexed. The index is cleared
+/// when the head switches to another chain.
+#[derive(Default)]
+pub struct MsgIndex {
+    lookups: HashMap<Cid, MsgLookup>,
+    // Indexed tipsets from oldest to newest, with the Cids of the messages executed in them
+    tipsets: VecDeque<(TipsetKeys, Vec<Cid>)>,
+    // Last tipset put in the index
+    head: Option<TipsetKeys>,
+}
+
+impl MsgIndex {
+    /// Creates an empty MsgIndex
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the messages executed in the parent of the tipset, whose receipts are in the
+    /// headers of the tipset. The tipset is the new head, if it doesn't extend the previous
+    /// head the messages indexed for the previous chain are removed first.
+    pub fn put<DB: BlockStore>(&mut self, db: &DB, ts: &Tipset) -> Result<(), Error> {
+        if let Some(head) = &self.head {
+            if head == ts.key() {
+                return Ok(());
+            }
+            if head != ts.parents() {
+                self.clear();
+            }
+        }
+        self.head = Some(ts.key().clone());
+        if ts.epoch() == 0 {
+            return Ok(());
+        }
+
+        let parent = tipset_from_keys(db, ts.parents())?;
+        let receipts_root = ts.blocks()[0].message_receipts();
+        let mut cids = Vec::new();
+        for (index, msg) in messages_for_tipset(db, &parent)?.iter().enumerate() {
+            let cid = msg.cid()?;
+            self.lookups.insert(
+                cid.clone(),
+                MsgLookup {
+                    tipset: parent.key().clone(),
+                    epoch: parent.epoch(),
+                    receipts_root: receipts_root.clone(),
+                    index: index as u64,
+                },
+            );
+            cids.push(cid);
+        }
+        self.tipsets.push_back((parent.key().clone(), cids));
+
+        while self.tipsets.len() > MSG_INDEX_CAPACITY {
+            if let Some((key, cids)) = self.tipsets.pop_front() {
+                for cid in cids {
+                    if self.lookups.get(&cid).map_or(false, |l| l.tipset == key) {
+                        self.lookups.remove(&cid);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the location of the receipt of the message, if it was indexed.
+    pub fn get(&self, msg: &Cid) -> Option<&MsgLookup> {
+        self.lookups.get(msg)
+    }
+
+    /// Removes all indexed messages.
+    pub fn clear(&mut self) {
+        self.lookups.clear();
+        self.tipsets.clear();
+        self.head = None;
+    }
+}
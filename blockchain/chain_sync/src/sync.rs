@@ -20,8 +20,8 @@ use chain::{persist_objects, ChainStore};
 use cid::{multihash::Blake2b256, Cid};
 use commcid::cid_to_replica_commitment_v1;
 use core::time::Duration;
-use crypto::verify_bls_aggregate;
 use crypto::DomainSeparationTag;
+use crypto::{verify_batch, verify_bls_aggregate};
 use encoding::{Cbor, Error as EncodingError};
 use fil_types::SectorInfo;
 use filecoin_proofs_api::{post::verify_winning_post, ProverId, PublicReplicaInfo, SectorId};
@@ -531,14 +531,17 @@ where
                     }
 
                     // sufficient funds check
-                    if *balance < msg.required_funds() {
+                    let required = msg
+                        .required_funds()
+                        .map_err(|e| Error::Validation(e.to_string()))?;
+                    if balance < required.as_token_amount() {
                         return Err(Error::Validation(
                             "Insufficient funds for message execution".to_owned(),
                         ));
                     }
                     // update balance and increment sequence by 1
                     MsgMetaData {
-                        balance: balance - &msg.required_funds(),
+                        balance: balance - required.as_token_amount(),
                         sequence: sequence + 1,
                     }
                 }
@@ -572,13 +575,24 @@ where
         for m in block.bls_msgs() {
             check_msg(m, &mut msg_meta_data, &tree)?;
         }
-        // loop through secp messages and check msg validity and signature
+        // loop through secp messages and check msg validity
+        let mut secp_cids = Vec::with_capacity(block.secp_msgs().len());
         for m in block.secp_msgs() {
             check_msg(m, &mut msg_meta_data, &tree)?;
-            // signature validation
-            m.signature()
-                .verify(&m.cid()?.to_bytes(), m.from())
-                .map_err(|e| Error::Validation(format!("Message signature invalid: {}", e)))?;
+            secp_cids.push(m.cid()?.to_bytes());
+        }
+        // signature validation, secp signatures are independent so are verified as a batch
+        let sigs: Vec<_> = block
+            .secp_msgs()
+            .iter()
+            .zip(secp_cids.iter())
+            .map(|(m, cid)| (m.signature(), cid.as_slice(), m.from()))
+            .collect();
+        if let Some(Err(e)) = verify_batch(&sigs).into_iter().find(Result::is_err) {
+            return Err(Error::Validation(format!(
+                "Message signature invalid: {}",
+                e
+            )));
         }
         // validate message root from header matches message root
         let sm_root = compute_msg_meta(db.as_ref(), block.bls_msgs(), block.secp_msgs())?;
@@ -637,7 +651,7 @@ where
                         ))
                     })?
                     .verify(&cid_bytes, &work_addr_result.unwrap())
-                    .map_err(|e| Error::Blockchain(blocks::Error::InvalidSignature(e)))
+                    .map_err(|e| Error::Blockchain(blocks::Error::InvalidSignature(e.to_string())))
             })),
             Err(err) => error_vec.push(err.to_string()),
         }
@@ -765,7 +779,10 @@ where
                         })?,
                     commr,
                 );
-                Ok((SectorId::from(sector_info.sector_number), replica))
+                Ok((
+                    SectorId::from(u64::from(sector_info.sector_number)),
+                    replica,
+                ))
             })
             .collect::<Result<BTreeMap<SectorId, PublicReplicaInfo>, _>>()?;
 
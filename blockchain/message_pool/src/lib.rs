@@ -3,6 +3,8 @@
 
 mod errors;
 mod msgpool;
+mod selection;
 
 pub use self::errors::*;
 pub use self::msgpool::*;
+pub use self::selection::*;
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::errors::Error;
+use super::selection::{select_messages, SenderState};
 use address::Address;
 use async_std::sync::{Arc, RwLock};
 use async_std::task;
@@ -17,11 +18,10 @@ use futures::StreamExt;
 use log::{error, warn};
 use lru::LruCache;
 use message::{Message, SignedMessage, UnsignedMessage};
-use num_bigint::BigInt;
 use state_tree::StateTree;
 use std::borrow::BorrowMut;
 use std::collections::{HashMap, HashSet};
-use vm::ActorState;
+use vm::{ActorState, NonNegTokenAmount, TokenAmount};
 
 const REPLACE_BY_FEE_RATIO: f32 = 1.25;
 const RBF_NUM: u64 = ((REPLACE_BY_FEE_RATIO - 1f32) * 256f32) as u64;
@@ -51,11 +51,15 @@ impl MsgSet {
             self.next_sequence = m.sequence() + 1;
         }
         if let Some(exms) = self.msgs.get(&m.sequence()) {
-            if m.cid()? != exms.cid()? {
+            // The cached quick hashes tell most replacements apart, the messages are only
+            // compared in full when the hashes collide
+            let duplicate = m.message().quick_hash()? == exms.message().quick_hash()?
+                && m.message() == exms.message();
+            if !duplicate {
                 let gas_price = exms.message().gas_price();
-                let rbf_num = BigInt::from(RBF_NUM);
-                let rbf_denom = BigInt::from(RBF_DENOM);
-                let min_price = gas_price.clone() + ((gas_price * &rbf_num) / rbf_denom) + 1u8;
+                let min_price = gas_price.clone()
+                    + ((gas_price * RBF_NUM) / RBF_DENOM)
+                    + TokenAmount::from(1u8);
                 if m.message().gas_price() <= &min_price {
                     warn!("mesage gas price is below min gas price");
                     return Err(Error::GasPriceTooLow);
@@ -227,7 +231,7 @@ pub struct MessagePool<T: 'static> {
     pending: Arc<RwLock<HashMap<Address, MsgSet>>>,
     pub cur_tipset: Arc<RwLock<Tipset>>,
     api: Arc<RwLock<T>>,
-    pub min_gas_price: BigInt,
+    pub min_gas_price: TokenAmount,
     pub max_tx_pool_size: i64,
     pub network_name: String,
     bls_sig_cache: Arc<RwLock<LruCache<Cid, Signature>>>,
@@ -324,7 +328,7 @@ where
         if size > 32 * 1024 {
             return Err(Error::MessageTooBig);
         }
-        if msg.value() > &BigInt::from(2_000_000_000u64) {
+        if msg.value() > &TokenAmount::from(2_000_000_000u64) {
             return Err(Error::MessageValueTooHigh);
         }
 
@@ -353,7 +357,7 @@ where
         let umsg = msg.message().marshal_cbor()?;
         msg.signature()
             .verify(umsg.as_slice(), msg.from())
-            .map_err(Error::Other)?;
+            .map_err(|e| Error::Other(e.to_string()))?;
 
         self.sig_val_cache.write().await.put(cid, ());
 
@@ -371,8 +375,11 @@ where
 
         let balance = self.get_state_balance(msg.from(), cur_ts).await?;
 
-        let msg_balance = msg.message().required_funds();
-        if balance < msg_balance {
+        let msg_balance = msg
+            .message()
+            .required_funds()
+            .map_err(|e| Error::Other(e.to_string()))?;
+        if &balance < msg_balance.as_token_amount() {
             return Err(Error::NotEnoughFunds);
         }
         self.add_helper(msg).await
@@ -435,7 +442,7 @@ where
 
     /// Get the state balance for the actor that corresponds to the supplied address and tipset,
     /// if this actor does not exist, return an error
-    async fn get_state_balance(&self, addr: &Address, ts: &Tipset) -> Result<BigInt, Error> {
+    async fn get_state_balance(&self, addr: &Address, ts: &Tipset) -> Result<TokenAmount, Error> {
         let actor = self.api.read().await.state_get_actor(&addr, &ts)?;
         Ok(actor.balance)
     }
@@ -482,6 +489,31 @@ where
         Some(msg_vec)
     }
 
+    /// Selects the pending messages to include in a block on top of the given tipset, which
+    /// maximize the gas reward of the block within the gas limit.
+    pub async fn select_messages(
+        &self,
+        ts: &Tipset,
+        gas_limit: i64,
+    ) -> Result<Vec<SignedMessage>, Error> {
+        let pending = self.pending.read().await.clone();
+
+        let mut msgs = HashMap::with_capacity(pending.len());
+        let mut states = HashMap::with_capacity(pending.len());
+        for (addr, mset) in pending {
+            let state = SenderState {
+                sequence: self.get_state_sequence(&addr, ts).await?,
+                balance: NonNegTokenAmount::new_saturating(
+                    self.get_state_balance(&addr, ts).await?,
+                ),
+            };
+            states.insert(addr, state);
+            msgs.insert(addr, mset.msgs.into_iter().map(|(_, m)| m).collect());
+        }
+
+        Ok(select_messages(&msgs, &states, gas_limit))
+    }
+
     /// Return Vector of signed messages given a block header for self
     pub async fn messages_for_blocks(
         &self,
@@ -510,13 +542,13 @@ where
         _sender: Address,
         _gas_limit: u64,
         _tsk: TipsetKeys,
-    ) -> Result<BigInt, Error> {
+    ) -> Result<TokenAmount, Error> {
         // TODO possibly come up with a smarter way to estimate the gas price
         let min_gas_price = 0;
         match nblocksincl {
-            0 => Ok(BigInt::from(min_gas_price + 2)),
-            1 => Ok(BigInt::from(min_gas_price + 1)),
-            _ => Ok(BigInt::from(min_gas_price)),
+            0 => Ok(TokenAmount::from(min_gas_price + 2)),
+            1 => Ok(TokenAmount::from(min_gas_price + 1)),
+            _ => Ok(TokenAmount::from(min_gas_price)),
         }
     }
 
@@ -785,7 +817,7 @@ pub mod test_provider {
             let actor = ActorState::new(
                 Cid::default(),
                 Cid::default(),
-                BigInt::from(9_000_000 as u64),
+                TokenAmount::from(9_000_000 as u64),
                 sequence,
             );
             Ok(actor)
@@ -884,6 +916,7 @@ pub mod tests {
         let fmt_str = format!("===={}=====", ticket_sequence);
         let ticket = Ticket::new(VRFProof::new(fmt_str.clone().into_bytes()));
         let election_proof = ElectionProof {
+            win_count: None,
             vrfproof: VRFProof::new(fmt_str.into_bytes()),
         };
         let weight_inc = BigUint::from(weight);
@@ -911,6 +944,7 @@ pub mod tests {
         let fmt_str = format!("===={}=====", ticket_sequence);
         let ticket = Ticket::new(VRFProof::new(fmt_str.clone().into_bytes()));
         let election_proof = ElectionProof {
+            win_count: None,
             vrfproof: VRFProof::new(fmt_str.into_bytes()),
         };
         BlockHeader::builder()
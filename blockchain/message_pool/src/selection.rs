@@ -0,0 +1,388 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use address::Address;
+use message::{Message, SignedMessage};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use vm::{NonNegTokenAmount, TokenAmount};
+
+/// Maximum sum of the gas limits of the messages included in a block.
+pub const BLOCK_GAS_LIMIT: i64 = 10_000_000_000;
+
+/// State of a sender at the base tipset, which its pending messages are selected on top of.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SenderState {
+    /// Sequence of the next message of the sender to be included.
+    pub sequence: u64,
+    /// Balance available to pay for the value and gas of the sender's messages.
+    pub balance: NonNegTokenAmount,
+}
+
+/// Consecutive messages of a sender which are selected together. Messages of a chain can
+/// only be included after all messages of the sender's previous chains.
+#[derive(Debug)]
+struct MsgChain {
+    sender: Address,
+    /// Position of the chain among the chains of its sender.
+    index: usize,
+    msgs: Vec<SignedMessage>,
+    gas_reward: TokenAmount,
+    gas_limit: i64,
+    valid: bool,
+}
+
+impl MsgChain {
+    fn new(sender: Address, msg: SignedMessage) -> Self {
+        let mut chain = Self {
+            sender,
+            index: 0,
+            msgs: Vec::new(),
+            gas_reward: TokenAmount::default(),
+            gas_limit: 0,
+            valid: true,
+        };
+        chain.push(msg);
+        chain
+    }
+
+    fn push(&mut self, msg: SignedMessage) {
+        self.gas_reward += msg.gas_price() * msg.gas_limit();
+        self.gas_limit += msg.gas_limit();
+        self.msgs.push(msg);
+    }
+
+    fn append(&mut self, other: MsgChain) {
+        for msg in other.msgs {
+            self.push(msg);
+        }
+    }
+
+    /// Removes the last message of the chain.
+    fn pop(&mut self) {
+        if let Some(msg) = self.msgs.pop() {
+            self.gas_reward -= msg.gas_price() * msg.gas_limit();
+            self.gas_limit -= msg.gas_limit();
+        }
+    }
+
+    /// Compares the gas reward per unit of gas of the chains. Chains without messages have
+    /// the lowest performance.
+    fn cmp_perf(&self, other: &Self) -> Ordering {
+        match (self.gas_limit, other.gas_limit) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            (a, b) => (&self.gas_reward * b).cmp(&(&other.gas_reward * a)),
+        }
+    }
+}
+
+/// Selects the pending messages to include in a block, maximizing the gas reward of the
+/// block within the gas limit.
+///
+/// The messages of each sender are grouped into chains of consecutive sequences starting at
+/// the sequence of the sender's state, which its balance can pay for. Chains are packed
+/// greedily in order of gas reward per unit of gas, and a chain which doesn't fit in the
+/// remaining gas is trimmed. Senders without a state are skipped.
+pub fn select_messages(
+    pending: &HashMap<Address, Vec<SignedMessage>>,
+    states: &HashMap<Address, SenderState>,
+    gas_limit: i64,
+) -> Vec<SignedMessage> {
+    let mut chains = Vec::new();
+    for (sender, msgs) in pending {
+        if let Some(state) = states.get(sender) {
+            chains.extend(create_chains(*sender, state, msgs, gas_limit));
+        }
+    }
+    chains.sort_by(|a, b| b.cmp_perf(a));
+
+    let mut selected = Vec::new();
+    let mut gas_left = gas_limit;
+    // Number of chains included for each sender
+    let mut included: HashMap<Address, usize> = HashMap::new();
+    let mut i = 0;
+    while i < chains.len() {
+        let chain = &mut chains[i];
+        let sender = chain.sender;
+        let sender_included = included.entry(sender).or_default();
+        if !chain.valid || chain.index != *sender_included {
+            i += 1;
+            continue;
+        }
+
+        if chain.gas_limit <= gas_left {
+            gas_left -= chain.gas_limit;
+            *sender_included += 1;
+            selected.append(&mut chain.msgs);
+            i += 1;
+            continue;
+        }
+
+        // The chain doesn't fit, so the last messages are dropped until it does. The later
+        // chains of the sender can't be included without the dropped messages.
+        while chain.gas_limit > gas_left {
+            chain.pop();
+        }
+        if chain.msgs.is_empty() {
+            chain.valid = false;
+        }
+        for later in chains[i + 1..].iter_mut() {
+            if later.sender == sender {
+                later.valid = false;
+            }
+        }
+        chains[i..].sort_by(|a, b| b.cmp_perf(a));
+    }
+
+    selected
+}
+
+/// Creates the chains of the messages of a sender, such that the gas performance of the
+/// chains is non increasing.
+fn create_chains(
+    sender: Address,
+    state: &SenderState,
+    msgs: &[SignedMessage],
+    gas_limit: i64,
+) -> Vec<MsgChain> {
+    let mut msgs: Vec<&SignedMessage> = msgs.iter().collect();
+    msgs.sort_by_key(|m| m.sequence());
+
+    let mut next_sequence = state.sequence;
+    let mut balance = state.balance.clone();
+    let mut total_gas = 0;
+    let mut chains: Vec<MsgChain> = Vec::new();
+    for msg in msgs {
+        if msg.sequence() < next_sequence {
+            // Already included in the base tipset
+            continue;
+        }
+        if msg.sequence() != next_sequence {
+            // Messages after a gap in sequences can't be included
+            break;
+        }
+        if msg.gas_limit() <= 0 || total_gas + msg.gas_limit() > gas_limit {
+            break;
+        }
+        let required = match msg.message().required_funds() {
+            Ok(required) => required,
+            Err(_) => break,
+        };
+        balance = match balance.checked_sub(&required) {
+            Some(balance) => balance,
+            None => break,
+        };
+        total_gas += msg.gas_limit();
+        next_sequence += 1;
+
+        let chain = MsgChain::new(sender, msg.clone());
+        chains.push(chain);
+        // A chain which performs better than the previous one is merged into it, as it
+        // can only be included after the previous one
+        while chains.len() > 1
+            && chains[chains.len() - 1].cmp_perf(&chains[chains.len() - 2]) == Ordering::Greater
+        {
+            let last = chains.pop().unwrap();
+            chains.last_mut().unwrap().append(last);
+        }
+    }
+
+    for (index, chain) in chains.iter_mut().enumerate() {
+        chain.index = index;
+    }
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{Signature, Signer};
+    use message::UnsignedMessage;
+    use rand::{Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use std::error::Error;
+
+    struct DummySigner;
+    impl Signer for DummySigner {
+        fn sign_bytes(&self, _: Vec<u8>, _: &Address) -> Result<Signature, Box<dyn Error>> {
+            Ok(Signature::new_secp256k1(vec![0]))
+        }
+    }
+
+    fn msg(from: u64, sequence: u64, gas_price: u64, gas_limit: i64) -> SignedMessage {
+        let umsg = UnsignedMessage::builder()
+            .from(Address::new_id(from))
+            .to(Address::new_id(1))
+            .sequence(sequence)
+            .gas_price(gas_price.into())
+            .gas_limit(gas_limit)
+            .build()
+            .unwrap();
+        SignedMessage::new(umsg, &DummySigner).unwrap()
+    }
+
+    fn state(sequence: u64, balance: u64) -> SenderState {
+        SenderState {
+            sequence,
+            balance: balance.into(),
+        }
+    }
+
+    fn sequences(selected: &[SignedMessage], from: u64) -> Vec<u64> {
+        selected
+            .iter()
+            .filter(|m| m.from() == &Address::new_id(from))
+            .map(|m| m.sequence())
+            .collect()
+    }
+
+    #[test]
+    fn selects_by_gas_performance() {
+        let pending: HashMap<_, _> = vec![
+            (
+                Address::new_id(100),
+                vec![msg(100, 0, 1, 100), msg(100, 1, 1, 100)],
+            ),
+            (
+                Address::new_id(101),
+                vec![msg(101, 0, 3, 100), msg(101, 1, 2, 100)],
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let states: HashMap<_, _> = vec![
+            (Address::new_id(100), state(0, 1_000_000)),
+            (Address::new_id(101), state(0, 1_000_000)),
+        ]
+        .into_iter()
+        .collect();
+
+        let selected = select_messages(&pending, &states, 300);
+        assert_eq!(sequences(&selected, 101), vec![0, 1]);
+        assert_eq!(sequences(&selected, 100), vec![0]);
+    }
+
+    #[test]
+    fn merges_dependent_messages() {
+        // The cheap first message of the sender is included to get to its expensive
+        // second message
+        let pending: HashMap<_, _> = vec![
+            (
+                Address::new_id(100),
+                vec![msg(100, 0, 1, 100), msg(100, 1, 10, 100)],
+            ),
+            (Address::new_id(101), vec![msg(101, 0, 4, 200)]),
+        ]
+        .into_iter()
+        .collect();
+        let states: HashMap<_, _> = vec![
+            (Address::new_id(100), state(0, 1_000_000)),
+            (Address::new_id(101), state(0, 1_000_000)),
+        ]
+        .into_iter()
+        .collect();
+
+        let selected = select_messages(&pending, &states, 200);
+        assert_eq!(sequences(&selected, 100), vec![0, 1]);
+        assert!(sequences(&selected, 101).is_empty());
+    }
+
+    #[test]
+    fn skips_gaps_and_unfunded_messages() {
+        let pending: HashMap<_, _> = vec![
+            (
+                Address::new_id(100),
+                vec![
+                    msg(100, 3, 1, 100),
+                    msg(100, 4, 1, 100),
+                    msg(100, 6, 1, 100),
+                ],
+            ),
+            (
+                Address::new_id(101),
+                vec![msg(101, 0, 2, 100), msg(101, 1, 2, 100)],
+            ),
+            (Address::new_id(102), vec![msg(102, 0, 5, 100)]),
+        ]
+        .into_iter()
+        .collect();
+        let states: HashMap<_, _> = vec![
+            (Address::new_id(100), state(4, 1_000_000)),
+            (Address::new_id(101), state(0, 300)),
+        ]
+        .into_iter()
+        .collect();
+
+        let selected = select_messages(&pending, &states, BLOCK_GAS_LIMIT);
+        assert_eq!(sequences(&selected, 100), vec![4]);
+        assert_eq!(sequences(&selected, 101), vec![0]);
+        assert!(sequences(&selected, 102).is_empty());
+    }
+
+    #[test]
+    fn trims_chains_to_gas_limit() {
+        let pending: HashMap<_, _> = vec![(
+            Address::new_id(100),
+            vec![
+                msg(100, 0, 5, 100),
+                msg(100, 1, 5, 100),
+                msg(100, 2, 5, 100),
+            ],
+        )]
+        .into_iter()
+        .collect();
+        let states: HashMap<_, _> = vec![(Address::new_id(100), state(0, 1_000_000))]
+            .into_iter()
+            .collect();
+
+        let selected = select_messages(&pending, &states, 250);
+        assert_eq!(sequences(&selected, 100), vec![0, 1]);
+    }
+
+    #[test]
+    fn random_selection_properties() {
+        let mut rng = XorShiftRng::seed_from_u64(5);
+        for _ in 0..200 {
+            let num_senders = rng.gen_range(1, 8);
+            let gas_limit = rng.gen_range(100, 5_000);
+            let mut pending = HashMap::new();
+            let mut states = HashMap::new();
+            for s in 0..num_senders {
+                let from = 100 + s;
+                let base = rng.gen_range(0, 3);
+                let msgs: Vec<_> = (0..rng.gen_range(0, 10))
+                    .map(|seq| msg(from, seq, rng.gen_range(0, 10), rng.gen_range(1, 1_000)))
+                    .collect();
+                pending.insert(Address::new_id(from), msgs);
+                states.insert(Address::new_id(from), state(base, rng.gen_range(0, 20_000)));
+            }
+
+            let selected = select_messages(&pending, &states, gas_limit);
+
+            // The block gas limit is respected
+            let total_gas: i64 = selected.iter().map(|m| m.gas_limit()).sum();
+            assert!(total_gas <= gas_limit);
+
+            for s in 0..num_senders {
+                let from = 100 + s;
+                let state = &states[&Address::new_id(from)];
+
+                // Each sender's messages are consecutive from its state's sequence
+                let seqs = sequences(&selected, from);
+                let expected: Vec<u64> =
+                    (state.sequence..state.sequence + seqs.len() as u64).collect();
+                assert_eq!(seqs, expected);
+
+                // The sender can pay for all of its selected messages
+                let required: TokenAmount = selected
+                    .iter()
+                    .filter(|m| m.from() == &Address::new_id(from))
+                    .map(|m| m.message().required_funds().unwrap().into_token_amount())
+                    .sum();
+                assert!(&required <= state.balance.as_token_amount());
+            }
+        }
+    }
+}
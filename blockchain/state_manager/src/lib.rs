@@ -5,7 +5,7 @@ mod errors;
 pub mod utils;
 pub use self::errors::*;
 use actor::{
-    init, market, miner, power, ActorState, BalanceTable, INIT_ACTOR_ADDR,
+    cron, init, market, miner, power, ActorState, BalanceTable, CRON_ACTOR_ADDR, INIT_ACTOR_ADDR,
     STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
 };
 use address::{Address, BLSPublicKey, Payload, BLS_PUB_LEN};
@@ -23,8 +23,9 @@ use flo_stream::Subscriber;
 use forest_blocks::{Block, BlockHeader, FullTipset, Tipset, TipsetKeys};
 use futures::channel::oneshot;
 use futures::stream::{FuturesUnordered, StreamExt};
-use interpreter::{resolve_to_key_addr, ApplyRet, ChainRand, DefaultSyscalls, VM};
-use ipld_amt::Amt;
+use interpreter::{
+    apply_block_messages, resolve_to_key_addr, ApplyRet, ChainRand, DefaultSyscalls, VMConfig, VM,
+};
 use log::{trace, warn};
 use message::{Message, MessageReceipt, UnsignedMessage};
 use num_bigint::BigInt;
@@ -32,6 +33,7 @@ use state_tree::StateTree;
 use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::sync::Arc;
+use vm::TokenAmount;
 
 /// Intermediary for retrieving state objects and updating actor states
 pub type CidPair = (Cid, Cid);
@@ -52,8 +54,8 @@ pub type StateCallResult<T> = Result<InvocResult<T>, Error>;
 #[allow(dead_code)]
 #[derive(Default)]
 pub struct MarketBalance {
-    escrow: BigInt,
-    locked: BigInt,
+    escrow: TokenAmount,
+    locked: TokenAmount,
 }
 
 pub struct StateManager<DB> {
@@ -116,6 +118,11 @@ where
         let state: init::State = self.load_actor_state(&*INIT_ACTOR_ADDR, st)?;
         Ok(state.network_name)
     }
+    /// Returns the entries the cron actor calls at the end of every epoch
+    pub fn get_cron_entries(&self, st: &Cid) -> Result<Vec<cron::Entry>, Error> {
+        let state: cron::State = self.load_actor_state(&*CRON_ACTOR_ADDR, st)?;
+        Ok(state.entries)
+    }
     /// Returns true if miner has been slashed or is considered invalid
     // TODO update
     pub fn is_miner_slashed(&self, addr: &Address, state_cid: &Cid) -> Result<bool, Error> {
@@ -162,27 +169,10 @@ where
         rand: &ChainRand,
         callback: Option<impl FnMut(Cid, UnsignedMessage, ApplyRet) -> Result<(), String>>,
     ) -> Result<(Cid, Cid), Box<dyn StdError>> {
-        let mut buf_store = BufferedBlockStore::new(self.bs.as_ref());
         // TODO possibly switch out syscalls to be saved at state manager level
         // TODO change from statically using devnet params when needed
-        let mut vm = VM::<_, _, DevnetParams>::new(
-            ts.parent_state(),
-            &buf_store,
-            ts.epoch(),
-            DefaultSyscalls::new(&buf_store),
-            rand,
-        )?;
-
-        // Apply tipset messages
-        let receipts = vm.apply_tipset_messages(ts, callback)?;
-
-        // Construct receipt root from receipts
-        let rect_root = Amt::new_from_slice(self.bs.as_ref(), &receipts)?;
-
-        // Flush changes to blockstore
-        let state_root = vm.flush()?;
-        // Persist changes connected to root
-        buf_store.flush(&state_root)?;
+        let (state_root, rect_root, _) =
+            apply_block_messages::<_, DevnetParams>(self.bs.as_ref(), ts, rand, callback)?;
 
         Ok((state_root, rect_root))
     }
@@ -246,6 +236,7 @@ where
                 *bheight,
                 DefaultSyscalls::new(&buf_store),
                 rand,
+                VMConfig::default(),
             )?;
 
             if msg.gas_limit() == 0 {
@@ -659,7 +650,7 @@ where
     }
 
     /// Return the heaviest tipset's balance from self.db for a given address
-    pub fn get_heaviest_balance(&self, addr: &Address) -> Result<BigInt, Error> {
+    pub fn get_heaviest_balance(&self, addr: &Address) -> Result<TokenAmount, Error> {
         let ts = get_heaviest_tipset(self.bs.as_ref())
             .map_err(|err| Error::Other(err.to_string()))?
             .ok_or_else(|| Error::Other("could not get bs heaviest ts".to_owned()))?;
@@ -668,7 +659,7 @@ where
     }
 
     /// Return the balance of a given address and state_cid
-    pub fn get_balance(&self, addr: &Address, cid: &Cid) -> Result<BigInt, Error> {
+    pub fn get_balance(&self, addr: &Address, cid: &Cid) -> Result<TokenAmount, Error> {
         let act = self.get_actor(addr, cid)?;
         let actor = act.ok_or_else(|| "could not find actor".to_owned())?;
         Ok(actor.balance)
@@ -158,7 +158,7 @@ where
         }
         sset.push(ChainSectorInfo {
             info: sector_chain.info.to_owned(),
-            id: i,
+            id: SectorNumber::new(i)?,
         });
         Ok(())
     };
@@ -2,17 +2,84 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::VRFProof;
-use encoding::tuple::*;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 
 /// Proofs generated by a miner which determines the reward they earn.
 /// This is generated from hashing a partial ticket and using the hash to generate a value.
-#[derive(
-    Clone, Debug, PartialEq, PartialOrd, Eq, Default, Ord, Serialize_tuple, Deserialize_tuple,
-)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Default, Ord)]
 pub struct ElectionProof {
+    /// Number of times the miner won the election of the epoch, each win earns a share of the
+    /// block reward. Set from `NetworkVersion::V2`, proofs without it are encoded as the one
+    /// field tuple of earlier network versions, so that block headers keep their CIDs.
+    pub win_count: Option<u64>,
     pub vrfproof: VRFProof,
 }
 
+impl Serialize for ElectionProof {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.win_count {
+            Some(win_count) => (win_count, &self.vrfproof).serialize(s),
+            None => (&self.vrfproof,).serialize(s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ElectionProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        /// First field of the tuple, which is the proof in the encoding without a win count.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum First {
+            WinCount(u64),
+            Proof(VRFProof),
+        }
+
+        struct ElectionProofVisitor;
+
+        impl<'de> de::Visitor<'de> for ElectionProofVisitor {
+            type Value = ElectionProof;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a tuple of an optional win count and a VRF proof")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<ElectionProof, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let first = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let proof = match first {
+                    First::WinCount(win_count) => ElectionProof {
+                        win_count: Some(win_count),
+                        vrfproof: seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    },
+                    First::Proof(vrfproof) => ElectionProof {
+                        win_count: None,
+                        vrfproof,
+                    },
+                };
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(3, &self));
+                }
+                Ok(proof)
+            }
+        }
+
+        deserializer.deserialize_seq(ElectionProofVisitor)
+    }
+}
+
 #[cfg(feature = "json")]
 pub mod json {
     use super::*;
@@ -35,10 +102,13 @@ pub mod json {
     {
         #[derive(Serialize)]
         struct ElectionProofSer<'a> {
+            #[serde(rename = "WinCount", skip_serializing_if = "Option::is_none")]
+            win_count: Option<u64>,
             #[serde(rename = "VRFProof", with = "vrf::json")]
             vrfproof: &'a VRFProof,
         }
         ElectionProofSer {
+            win_count: m.win_count,
             vrfproof: &m.vrfproof,
         }
         .serialize(serializer)
@@ -50,11 +120,19 @@ pub mod json {
     {
         #[derive(Serialize, Deserialize)]
         struct ElectionProofDe {
+            #[serde(rename = "WinCount", default)]
+            win_count: Option<u64>,
             #[serde(rename = "VRFProof", with = "vrf::json")]
             vrfproof: VRFProof,
         }
-        let ElectionProofDe { vrfproof } = Deserialize::deserialize(deserializer)?;
-        Ok(ElectionProof { vrfproof })
+        let ElectionProofDe {
+            win_count,
+            vrfproof,
+        } = Deserialize::deserialize(deserializer)?;
+        Ok(ElectionProof {
+            win_count,
+            vrfproof,
+        })
     }
 
     pub mod opt {
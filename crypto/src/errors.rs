@@ -3,6 +3,7 @@
 
 use address::Error as AddressError;
 use encoding::Error as EncodingError;
+#[cfg(feature = "verify")]
 use secp256k1::Error as SecpError;
 use std::error;
 use thiserror::Error;
@@ -21,6 +22,23 @@ pub enum Error {
     InvalidPubKey(#[from] AddressError),
 }
 
+/// Reason a signature failed verification
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SignatureError {
+    /// Signature is well formed, but was not made by the signer over the data
+    #[error("signature was not made by the signer over the data")]
+    WrongSigner,
+    /// Signature or signer public key bytes can't be decoded
+    #[error("invalid signature bytes: {0}")]
+    InvalidSignatureBytes(String),
+    /// Signer address is not of a protocol which can sign
+    #[error("unsupported signer type: {0}")]
+    UnsupportedType(String),
+    /// Public key could not be recovered from a secp256k1 signature
+    #[error("could not recover public key from signature: {0}")]
+    PubkeyRecoveryFailed(String),
+}
+
 impl From<Box<dyn error::Error>> for Error {
     fn from(err: Box<dyn error::Error>) -> Error {
         // Pass error encountered in signer trait as module error type
@@ -28,6 +46,7 @@ impl From<Box<dyn error::Error>> for Error {
     }
 }
 
+#[cfg(feature = "verify")]
 impl From<SecpError> for Error {
     fn from(err: SecpError) -> Error {
         match err {
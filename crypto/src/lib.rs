@@ -4,12 +4,14 @@
 pub mod election_proof;
 mod errors;
 mod randomness;
+mod secret;
 pub mod signature;
 mod signer;
 pub mod vrf;
 
-pub use self::errors::Error;
+pub use self::errors::{Error, SignatureError};
 pub use self::randomness::DomainSeparationTag;
+pub use self::secret::*;
 pub use self::signature::*;
 pub use self::signer::*;
 pub use self::vrf::*;
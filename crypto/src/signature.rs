@@ -1,15 +1,23 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::errors::Error;
+#[cfg(feature = "verify")]
+use super::errors::{Error, SignatureError};
+#[cfg(feature = "verify")]
 use address::{Address, Protocol};
+#[cfg(feature = "verify")]
 use bls_signatures::{
     hash as bls_hash, paired::bls12_381::G2, verify, PublicKey as BlsPubKey, Serialize,
     Signature as BlsSignature,
 };
-use encoding::{blake2b_256, de, repr::*, ser, serde_bytes};
+#[cfg(feature = "verify")]
+use encoding::blake2b_256;
+use encoding::{de, repr::*, ser, serde_bytes};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "verify")]
 use secp256k1::{recover, Message, RecoveryId, Signature as EcsdaSignature};
 
 /// BLS signature length in bytes
@@ -107,54 +115,71 @@ impl Signature {
     }
 
     /// Checks if a signature is valid given data and address
-    pub fn verify(&self, data: &[u8], addr: &Address) -> Result<(), String> {
+    #[cfg(feature = "verify")]
+    pub fn verify(&self, data: &[u8], addr: &Address) -> Result<(), SignatureError> {
         match addr.protocol() {
             Protocol::BLS => self.verify_bls_sig(data, addr),
             Protocol::Secp256k1 => self.verify_secp256k1_sig(data, addr),
-            _ => Err("Address must be resolved to verify a signature".to_owned()),
+            protocol => Err(SignatureError::UnsupportedType(format!(
+                "address of protocol {:?} must be resolved to verify a signature",
+                protocol
+            ))),
         }
     }
 
-    /// Returns `String` error if a bls signature is invalid
-    pub(crate) fn verify_bls_sig(&self, data: &[u8], addr: &Address) -> Result<(), String> {
+    /// Returns `SignatureError` if a bls signature is invalid
+    #[cfg(feature = "verify")]
+    pub(crate) fn verify_bls_sig(&self, data: &[u8], addr: &Address) -> Result<(), SignatureError> {
         let pub_k = addr.payload_bytes();
 
         // hash data to be verified
         let hashed = bls_hash(data);
 
         // generate public key object from bytes
-        let pk = BlsPubKey::from_bytes(&pub_k).map_err(|e| e.to_string())?;
+        let pk = BlsPubKey::from_bytes(&pub_k).map_err(|e| {
+            SignatureError::InvalidSignatureBytes(format!("invalid bls public key: {}", e))
+        })?;
 
         // generate signature struct from bytes
-        let sig = BlsSignature::from_bytes(self.bytes()).map_err(|e| e.to_string())?;
+        let sig = BlsSignature::from_bytes(self.bytes())
+            .map_err(|e| SignatureError::InvalidSignatureBytes(e.to_string()))?;
 
         // BLS verify hash against key
         if verify(&sig, &[hashed], &[pk]) {
             Ok(())
         } else {
-            Err("bls signature verification failed".to_owned())
+            Err(SignatureError::WrongSigner)
         }
     }
 
-    /// Returns `String` error if a secp256k1 signature is invalid
-    fn verify_secp256k1_sig(&self, data: &[u8], addr: &Address) -> Result<(), String> {
+    /// Returns `SignatureError` if a secp256k1 signature is invalid
+    #[cfg(feature = "verify")]
+    fn verify_secp256k1_sig(&self, data: &[u8], addr: &Address) -> Result<(), SignatureError> {
         // blake2b 256 hash
         let hash = blake2b_256(data);
 
         // Ecrecover with hash and signature
+        if self.bytes().len() != 65 {
+            return Err(SignatureError::InvalidSignatureBytes(format!(
+                "secp256k1 signature must be 65 bytes, was {}",
+                self.bytes().len()
+            )));
+        }
         let mut signature = [0u8; 65];
         signature[..].clone_from_slice(self.bytes());
-        let rec_addr = ecrecover(&hash, &signature).map_err(|e| e.to_string())?;
+        let rec_addr = ecrecover(&hash, &signature)
+            .map_err(|e| SignatureError::PubkeyRecoveryFailed(e.to_string()))?;
 
         // check address against recovered address
         if &rec_addr == addr {
             Ok(())
         } else {
-            Err("Secp signature verification failed".to_owned())
+            Err(SignatureError::WrongSigner)
         }
     }
 }
 /// Aggregates and verifies bls signatures collectively
+#[cfg(feature = "verify")]
 pub fn verify_bls_aggregate(data: &[&[u8]], pub_keys: &[&[u8]], aggregate_sig: &Signature) -> bool {
     // If the number of public keys and data does not match, then return false
     if data.len() != pub_keys.len() {
@@ -166,21 +191,38 @@ pub fn verify_bls_aggregate(data: &[&[u8]], pub_keys: &[&[u8]], aggregate_sig: &
         Err(_) => return false,
     };
 
-    let pk_map_results: Result<Vec<_>, _> =
-        pub_keys.iter().map(|x| BlsPubKey::from_bytes(x)).collect();
+    #[cfg(feature = "parallel")]
+    let (pk_iter, data_iter) = (pub_keys.par_iter(), data.par_iter());
+    #[cfg(not(feature = "parallel"))]
+    let (pk_iter, data_iter) = (pub_keys.iter(), data.iter());
 
-    let pks = match pk_map_results {
-        Ok(v) => v,
-        Err(_) => return false,
+    let pks: Vec<BlsPubKey> = match pk_iter.map(|x| BlsPubKey::from_bytes(x).ok()).collect() {
+        Some(v) => v,
+        None => return false,
     };
 
-    let hashed_data: Vec<G2> = data.iter().map(|x| bls_hash(x)).collect();
+    // Hashing to the curve is the expensive part, done for each message independently
+    let hashed_data: Vec<G2> = data_iter.map(|x| bls_hash(x)).collect();
 
     // DOes the aggregate verification
     verify(&sig, &hashed_data[..], &pks[..])
 }
 
+/// Verifies a batch of independent signatures, each over its own data and signed by the
+/// given address. Returns the result of each verification, in the order of the input.
+#[cfg(feature = "verify")]
+pub fn verify_batch(sigs: &[(&Signature, &[u8], &Address)]) -> Vec<Result<(), SignatureError>> {
+    #[cfg(feature = "parallel")]
+    let iter = sigs.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let iter = sigs.iter();
+
+    iter.map(|(sig, data, addr)| sig.verify(data, addr))
+        .collect()
+}
+
 /// Return Address for a message given it's hash and signature
+#[cfg(feature = "verify")]
 pub fn ecrecover(hash: &[u8; 32], signature: &[u8; 65]) -> Result<Address, Error> {
     // generate types to recover key from
     let rec_id = RecoveryId::parse(signature[64])?;
@@ -198,7 +240,7 @@ pub fn ecrecover(hash: &[u8; 32], signature: &[u8; 65]) -> Result<Address, Error
     Ok(addr)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "verify"))]
 mod tests {
     use super::*;
     use bls_signatures::{PrivateKey, Serialize, Signature as BlsSignature};
@@ -239,6 +281,86 @@ mod tests {
             true
         );
     }
+
+    #[test]
+    fn bls_batch_verify() {
+        let num_sigs = 4;
+        let rng = &mut ChaCha8Rng::seed_from_u64(8);
+
+        let data: Vec<Vec<u8>> = (0..num_sigs)
+            .map(|_| (0..64).map(|_| rng.gen()).collect())
+            .collect();
+        let private_keys: Vec<PrivateKey> =
+            (0..num_sigs).map(|_| PrivateKey::generate(rng)).collect();
+        let addrs: Vec<Address> = private_keys
+            .iter()
+            .map(|x| Address::new_bls(&x.public_key().as_bytes()).unwrap())
+            .collect();
+        let mut signatures: Vec<Signature> = (0..num_sigs)
+            .map(|x| Signature::new_bls(private_keys[x].sign(data[x].as_slice()).as_bytes()))
+            .collect();
+        // Invalidate the last signature by signing other data
+        signatures[num_sigs - 1] = Signature::new_bls(
+            private_keys[num_sigs - 1]
+                .sign(data[0].as_slice())
+                .as_bytes(),
+        );
+
+        let batch: Vec<_> = (0..num_sigs)
+            .map(|x| (&signatures[x], data[x].as_slice(), &addrs[x]))
+            .collect();
+        let results = verify_batch(&batch);
+        assert_eq!(results.len(), num_sigs);
+        assert!(results[..num_sigs - 1].iter().all(Result::is_ok));
+        assert_eq!(results[num_sigs - 1], Err(SignatureError::WrongSigner));
+    }
+
+    #[test]
+    fn verify_errors() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(5);
+        let data = b"data";
+
+        let bls_key = PrivateKey::generate(rng);
+        let bls_addr = Address::new_bls(&bls_key.public_key().as_bytes()).unwrap();
+        let bls_sig = Signature::new_bls(bls_key.sign(&data[..]).as_bytes());
+        bls_sig.verify(data, &bls_addr).unwrap();
+        assert_eq!(
+            bls_sig.verify(b"other data", &bls_addr),
+            Err(SignatureError::WrongSigner)
+        );
+        assert!(matches!(
+            Signature::new_bls(vec![1; BLS_SIG_LEN]).verify(data, &bls_addr),
+            Err(SignatureError::InvalidSignatureBytes(_))
+        ));
+
+        let secp_key = secp256k1::SecretKey::random(rng);
+        let secp_pub = secp256k1::PublicKey::from_secret_key(&secp_key);
+        let secp_addr = Address::new_secp256k1(&secp_pub.serialize()).unwrap();
+        let (sig, rec_id) = secp256k1::sign(&Message::parse(&blake2b_256(data)), &secp_key);
+        let mut sig_bytes = sig.serialize().to_vec();
+        sig_bytes.push(rec_id.serialize());
+        let secp_sig = Signature::new_secp256k1(sig_bytes.clone());
+        secp_sig.verify(data, &secp_addr).unwrap();
+        assert_eq!(
+            secp_sig.verify(b"other data", &secp_addr),
+            Err(SignatureError::WrongSigner)
+        );
+        assert!(matches!(
+            Signature::new_secp256k1(sig_bytes[..64].to_vec()).verify(data, &secp_addr),
+            Err(SignatureError::InvalidSignatureBytes(_))
+        ));
+        // Recovery ids are at most 3
+        sig_bytes[64] = 4;
+        assert!(matches!(
+            Signature::new_secp256k1(sig_bytes).verify(data, &secp_addr),
+            Err(SignatureError::PubkeyRecoveryFailed(_))
+        ));
+
+        assert!(matches!(
+            secp_sig.verify(data, &Address::new_id(1)),
+            Err(SignatureError::UnsupportedType(_))
+        ));
+    }
 }
 
 #[cfg(feature = "json")]
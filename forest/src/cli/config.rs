@@ -14,6 +14,8 @@ pub struct Config {
     pub drand_public: DrandPublic,
     pub enable_rpc: bool,
     pub rpc_port: String,
+    /// Encodes addresses for mainnet instead of testnet.
+    pub mainnet: bool,
 }
 
 impl Default for Config {
@@ -24,7 +26,8 @@ impl Default for Config {
             genesis_file: None,
             drand_public: DrandPublic{coefficient: hex::decode("922a2e93828ff83345bae533f5172669a26c02dc76d6bf59c80892e12ab1455c229211886f35bb56af6d5bea981024df").unwrap()},
             enable_rpc : true,
-            rpc_port: "1234".to_string()
+            rpc_port: "1234".to_string(),
+            mainnet: false,
         }
     }
 }
@@ -60,6 +60,8 @@ pub struct DaemonOpts {
     pub rpc: Option<bool>,
     #[structopt(short, long, help = "The port used for communication")]
     pub port: Option<String>,
+    #[structopt(long, help = "Encode addresses for mainnet instead of testnet")]
+    pub mainnet: bool,
 }
 
 impl DaemonOpts {
@@ -82,6 +84,9 @@ impl DaemonOpts {
         } else {
             cfg.enable_rpc = false;
         }
+        if self.mainnet {
+            cfg.mainnet = true;
+        }
         // (where to find these flags, should be easy to do with structops)
 
         Ok(cfg)
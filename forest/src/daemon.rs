@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::cli::{block_until_sigint, initialize_genesis, Config};
+use address::{set_current_network, Network};
 use async_std::sync::RwLock;
 use async_std::task;
 use beacon::DrandBeacon;
@@ -20,6 +21,13 @@ use wallet::MemKeyStore;
 /// Starts daemon process
 pub(super) async fn start(config: Config) {
     info!("Starting Forest daemon");
+    // Set before any addresses are created, as addresses on different networks are not equal
+    set_current_network(if config.mainnet {
+        Network::Mainnet
+    } else {
+        Network::Testnet
+    });
+
     let net_keypair = get_keypair(&format!("{}{}", &config.data_dir, "/libp2p/keypair"))
         .unwrap_or_else(|| {
             // Keypair not found, generate and save generated keypair
@@ -1,7 +1,10 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::{node::Link, nodes_for_height, BitMap, Error, Node, Root, MAX_INDEX, WIDTH};
+use crate::{
+    node::{load_node, Link},
+    nodes_for_height, BitMap, Error, Node, Root, MAX_INDEX, WIDTH,
+};
 use cid::{multihash::Blake2b256, Cid};
 use encoding::{de::DeserializeOwned, ser::Serialize};
 use ipld_blockstore::BlockStore;
@@ -109,7 +112,7 @@ where
 
                 // Set links node with first index as cid
                 let mut new_links: [Option<Link<V>>; WIDTH] = Default::default();
-                new_links[0] = Some(Link::Cid(cid));
+                new_links[0] = Some(Link::from(cid));
 
                 self.root.node = Node::Link {
                     bmap: BitMap::new(0x01),
@@ -169,11 +172,11 @@ where
         while *self.root.node.bitmap() == 0x01 && self.height() > 0 {
             let sub_node: Node<V> = match &self.root.node {
                 Node::Link { links, .. } => match &links[0] {
-                    Some(Link::Cached(node)) => *node.clone(),
-                    Some(Link::Cid(cid)) => self
-                        .block_store
-                        .get(cid)?
-                        .ok_or_else(|| Error::CidNotFound(cid.to_string()))?,
+                    Some(Link::Dirty(node)) => *node.clone(),
+                    Some(Link::Cid { cid, cache }) => match cache.get() {
+                        Some(node) => *node.clone(),
+                        None => *load_node(self.block_store, cid)?,
+                    },
                     _ => unreachable!("Link index should match bitmap"),
                 },
                 Node::Leaf { .. } => unreachable!("Non zero height cannot be a leaf node"),
@@ -8,17 +8,38 @@ use encoding::{
     ser::{self, Serialize},
 };
 use ipld_blockstore::BlockStore;
+use once_cell::unsync::OnceCell;
 
 /// This represents a link to another Node
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub(super) enum Link<V> {
-    Cid(Cid),
-    Cached(Box<Node<V>>),
+    /// Unchanged link to data, with a cache of the node once it has been loaded
+    Cid {
+        cid: Cid,
+        cache: OnceCell<Box<Node<V>>>,
+    },
+    /// Modifications have been made to the link, requires flush to clear
+    Dirty(Box<Node<V>>),
 }
 
+impl<V: PartialEq> PartialEq for Link<V> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Link::Cid { cid: a, .. }, Link::Cid { cid: b, .. }) => a == b,
+            (Link::Dirty(a), Link::Dirty(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<V: Eq> Eq for Link<V> {}
+
 impl<V> From<Cid> for Link<V> {
-    fn from(c: Cid) -> Link<V> {
-        Link::Cid(c)
+    fn from(cid: Cid) -> Link<V> {
+        Link::Cid {
+            cid,
+            cache: Default::default(),
+        }
     }
 }
 
@@ -85,8 +106,8 @@ fn cids_from_links<V>(links: &[Option<Link<V>>; WIDTH]) -> Result<Vec<Cid>, Erro
     links
         .iter()
         .filter_map(|c| match c {
-            Some(Link::Cid(cid)) => Some(Ok(cid.clone())),
-            Some(Link::Cached(_)) => Some(Err(Error::Cached)),
+            Some(Link::Cid { cid, .. }) => Some(Ok(cid.clone())),
+            Some(Link::Dirty(_)) => Some(Err(Error::Cached)),
             None => None,
         })
         .collect()
@@ -143,15 +164,19 @@ where
     pub(super) fn flush<DB: BlockStore>(&mut self, bs: &DB) -> Result<(), Error> {
         if let Node::Link { links, .. } = self {
             for link in &mut links.iter_mut() {
-                if let Some(Link::Cached(n)) = link {
+                if let Some(Link::Dirty(n)) = link {
                     // flush sub node to clear caches
                     n.flush(bs)?;
 
                     // Puts node in blockstore and and retrieves it's CID
                     let cid = bs.put(n, Blake2b256)?;
 
-                    // Turn cached node into a Cid link
-                    *link = Some(Link::Cid(cid));
+                    // Turn dirty node into a Cid link, keeping the node cached for reads
+                    let cache = OnceCell::new();
+                    if let Some(Link::Dirty(n)) = link.take() {
+                        let _ = cache.set(n);
+                    }
+                    *link = Some(Link::Cid { cid, cache });
                 }
             }
         }
@@ -186,16 +211,13 @@ where
         match self {
             Node::Leaf { vals, .. } => Ok(vals[i as usize].clone()),
             Node::Link { links, .. } => match &links[sub_i as usize] {
-                Some(Link::Cid(cid)) => {
-                    // TODO after benchmarking check if cache should be updated from get
-                    let node: Node<V> = bs
-                        .get::<Node<V>>(cid)?
-                        .ok_or_else(|| Error::CidNotFound(cid.to_string()))?;
+                Some(Link::Cid { cid, cache }) => {
+                    // Load node into cache on first access, subsequent gets are served from memory
+                    let node = cache.get_or_try_init(|| load_node(bs, cid))?;
 
-                    // Get from node pulled into memory from Cid
                     node.get(bs, height - 1, i % nodes_for_height(height))
                 }
-                Some(Link::Cached(n)) => n.get(bs, height - 1, i % nodes_for_height(height)),
+                Some(Link::Dirty(n)) => n.get(bs, height - 1, i % nodes_for_height(height)),
                 None => Ok(None),
             },
         }
@@ -221,10 +243,14 @@ where
 
         if let Node::Link { links, bmap } = self {
             links[idx] = match &mut links[idx] {
-                Some(Link::Cid(cid)) => {
-                    let node = bs.get::<Node<V>>(cid)?.ok_or_else(|| Error::RootNotFound)?;
+                Some(Link::Cid { cid, cache }) => {
+                    // Reuse cached node if it was already loaded, node becomes dirty on write
+                    let node = match std::mem::take(cache).into_inner() {
+                        Some(node) => node,
+                        None => load_node(bs, cid)?,
+                    };
 
-                    Some(Link::Cached(Box::new(node)))
+                    Some(Link::Dirty(node))
                 }
                 None => {
                     let node = match height {
@@ -238,12 +264,12 @@ where
                         },
                     };
                     bmap.set_bit(idx as u64);
-                    Some(Link::Cached(Box::new(node)))
+                    Some(Link::Dirty(Box::new(node)))
                 }
-                Some(Link::Cached(node)) => return node.set(bs, height - 1, i % nfh, val),
+                Some(Link::Dirty(node)) => return node.set(bs, height - 1, i % nfh, val),
             };
 
-            if let Some(Link::Cached(n)) = &mut links[idx] {
+            if let Some(Link::Dirty(n)) = &mut links[idx] {
                 n.set(bs, height - 1, i % nfh, val)
             } else {
                 unreachable!("Value is set as cached")
@@ -293,8 +319,11 @@ where
             }
             Self::Link { links, bmap } => {
                 let mut sub_node: Node<V> = match &links[sub_i as usize] {
-                    Some(Link::Cached(n)) => *n.clone(),
-                    Some(Link::Cid(cid)) => bs.get(cid)?.ok_or_else(|| Error::RootNotFound)?,
+                    Some(Link::Dirty(n)) => *n.clone(),
+                    Some(Link::Cid { cid, cache }) => match cache.get() {
+                        Some(n) => *n.clone(),
+                        None => *load_node(bs, cid)?,
+                    },
                     None => unreachable!("Bitmap value for index is set"),
                 };
 
@@ -309,7 +338,7 @@ where
                     bmap.clear_bit(sub_i);
                     None
                 } else {
-                    Some(Link::Cached(Box::new(sub_node)))
+                    Some(Link::Dirty(Box::new(sub_node)))
                 };
 
                 Ok(true)
@@ -344,12 +373,9 @@ where
                     if bmap.get_bit(i as u64) {
                         let offs = offset + (i as u64 * nodes_for_height(height));
                         match l.as_ref().expect("bit set at index") {
-                            Link::Cached(sub) => sub.for_each(store, height - 1, offs, f)?,
-                            Link::Cid(cid) => {
-                                let node = store
-                                    .get::<Node<V>>(cid)
-                                    .map_err(|e| e.to_string())?
-                                    .ok_or_else(|| Error::RootNotFound)?;
+                            Link::Dirty(sub) => sub.for_each(store, height - 1, offs, f)?,
+                            Link::Cid { cid, cache } => {
+                                let node = cache.get_or_try_init(|| load_node(store, cid))?;
 
                                 node.for_each(store, height - 1, offs, f)?;
                             }
@@ -362,6 +388,18 @@ where
     }
 }
 
+/// Loads a node from the blockstore by its Cid
+pub(super) fn load_node<V, DB>(bs: &DB, cid: &Cid) -> Result<Box<Node<V>>, Error>
+where
+    V: Clone + DeserializeOwned,
+    DB: BlockStore,
+{
+    let node = bs
+        .get::<Node<V>>(cid)?
+        .ok_or_else(|| Error::CidNotFound(cid.to_string()))?;
+    Ok(Box::new(node))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
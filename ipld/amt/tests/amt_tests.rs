@@ -1,9 +1,11 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use db::Store;
 use encoding::{de::DeserializeOwned, ser::Serialize};
 use ipld_amt::{Amt, Error, MAX_INDEX};
 use ipld_blockstore::BlockStore;
+use std::cell::Cell;
 use std::fmt::Debug;
 
 fn assert_get<V, BS>(a: &mut Amt<V, BS>, i: u64, v: &V)
@@ -220,3 +222,75 @@ fn delete_reduce_height() {
             .unwrap()
     );
 }
+
+/// Blockstore wrapper to count the number of reads from the underlying store
+#[derive(Default)]
+struct ReadCountStore {
+    db: db::MemoryDB,
+    reads: Cell<usize>,
+}
+
+impl Store for ReadCountStore {
+    fn read<K>(&self, key: K) -> Result<Option<Vec<u8>>, db::Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.reads.set(self.reads.get() + 1);
+        self.db.read(key)
+    }
+    fn write<K, V>(&self, key: K, value: V) -> Result<(), db::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.db.write(key, value)
+    }
+    fn delete<K>(&self, key: K) -> Result<(), db::Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.db.delete(key)
+    }
+    fn exists<K>(&self, key: K) -> Result<bool, db::Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.db.exists(key)
+    }
+}
+
+impl BlockStore for ReadCountStore {}
+
+#[test]
+fn cached_get() {
+    let store = ReadCountStore::default();
+    let mut a = Amt::new(&store);
+    for i in 0..100 {
+        a.set(i, i).unwrap();
+    }
+    let c = a.flush().unwrap();
+
+    let new_amt: Amt<u64, _> = Amt::load(&c, &store).unwrap();
+    let reads = store.reads.get();
+    for i in 0..100 {
+        assert_eq!(new_amt.get(i).unwrap(), Some(i));
+    }
+    // Nodes are only loaded from the store on first access
+    let loaded = store.reads.get() - reads;
+    assert!(loaded > 0);
+    for i in 0..100 {
+        assert_eq!(new_amt.get(i).unwrap(), Some(i));
+    }
+    assert_eq!(store.reads.get() - reads, loaded);
+
+    // Writing through a cached node and flushing yields the same root as a fresh build
+    let mut new_amt = new_amt;
+    new_amt.set(3, 7).unwrap();
+    let c2 = new_amt.flush().unwrap();
+
+    let mut fresh = Amt::new(&store);
+    for i in 0..100 {
+        fresh.set(i, if i == 3 { 7 } else { i }).unwrap();
+    }
+    assert_eq!(fresh.flush().unwrap(), c2);
+}
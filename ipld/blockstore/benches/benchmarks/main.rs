@@ -0,0 +1,68 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::{multihash::Blake2b256, Cid};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use encoding::{from_slice, to_vec};
+use forest_ipld::Ipld;
+use ipld_blockstore::scan_for_links;
+
+/// Block shaped like a full Hamt node, with buckets of key value pairs and links to children.
+fn example_block() -> Vec<u8> {
+    let pointers: Vec<Ipld> = (0..32u64)
+        .map(|i| {
+            if i % 2 == 0 {
+                Ipld::Link(Cid::new_from_cbor(&i.to_be_bytes(), Blake2b256))
+            } else {
+                let kvs = (0..3u64)
+                    .map(|j| {
+                        Ipld::List(vec![
+                            Ipld::Bytes((i * 3 + j).to_be_bytes().to_vec()),
+                            Ipld::List(vec![Ipld::Integer(j.into()), Ipld::Bytes(vec![7; 64])]),
+                        ])
+                    })
+                    .collect();
+                Ipld::List(kvs)
+            }
+        })
+        .collect();
+    to_vec(&Ipld::List(vec![
+        Ipld::Bytes(vec![0xff; 4]),
+        Ipld::List(pointers),
+    ]))
+    .unwrap()
+}
+
+fn count_links(ipld: &Ipld) -> usize {
+    match ipld {
+        Ipld::Link(_) => 1,
+        Ipld::List(arr) => arr.iter().map(count_links).sum(),
+        Ipld::Map(map) => map.values().map(count_links).sum(),
+        _ => 0,
+    }
+}
+
+fn scan_links(c: &mut Criterion) {
+    let bz = example_block();
+    c.bench_function("scan_links", |b| {
+        b.iter(|| {
+            let mut count = 0;
+            scan_for_links(black_box(&bz), |_| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+            count
+        })
+    });
+}
+
+fn deserialize_links(c: &mut Criterion) {
+    let bz = example_block();
+    c.bench_function("deserialize_links", |b| {
+        b.iter(|| count_links(&from_slice::<Ipld>(black_box(&bz)).unwrap()))
+    });
+}
+
+criterion_group!(benches, scan_links, deserialize_links);
+criterion_main!(benches);
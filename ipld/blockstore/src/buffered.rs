@@ -1,15 +1,10 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::BlockStore;
-use cid::{
-    multihash::{Code, MultihashDigest},
-    Cid,
-};
-use commcid::FilecoinMultihashCode;
+use super::{links_to_block, scan_for_links, BlockStore};
+use cid::{multihash::MultihashDigest, Cid};
 use db::{Error, Store};
-use encoding::{from_slice, ser::Serialize, to_vec};
-use forest_ipld::Ipld;
+use encoding::{ser::Serialize, to_vec};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::error::Error as StdError;
@@ -53,11 +48,7 @@ where
     BS: BlockStore,
 {
     // Skip identity and Filecoin commitment Cids
-    let ch = cid.hash.algorithm();
-    if ch == Code::Identity
-        || ch == Code::Custom(FilecoinMultihashCode::SealedV1 as u64)
-        || ch == Code::Custom(FilecoinMultihashCode::UnsealedV1 as u64)
-    {
+    if !links_to_block(cid) {
         return Ok(());
     }
 
@@ -71,41 +62,14 @@ where
         return Ok(());
     }
 
-    // Deserialize the bytes to Ipld to traverse links.
-    // This is safer than finding links in place,
-    // but slightly slower to copy and potentially allocate non Cid data.
-    let block: Ipld = from_slice(raw_bz)?;
-
     // Traverse and write linked data recursively
-    for_each_link(&block, &|c| write_recursive(base, cache, c))?;
+    scan_for_links(raw_bz, |c| write_recursive(base, cache, &c))?;
 
     // Write the root node to base storage
     base.write(&raw_cid_bz, raw_bz)?;
     Ok(())
 }
 
-/// Recursively explores Ipld for links and calls a function with a reference to the Cid.
-fn for_each_link<F>(ipld: &Ipld, cb: &F) -> Result<(), Box<dyn StdError>>
-where
-    F: Fn(&Cid) -> Result<(), Box<dyn StdError>>,
-{
-    match ipld {
-        Ipld::Link(c) => cb(&c)?,
-        Ipld::List(arr) => {
-            for item in arr {
-                for_each_link(item, cb)?
-            }
-        }
-        Ipld::Map(map) => {
-            for v in map.values() {
-                for_each_link(v, cb)?
-            }
-        }
-        _ => (),
-    }
-    Ok(())
-}
-
 impl<BS> BlockStore for BufferedBlockStore<'_, BS>
 where
     BS: BlockStore,
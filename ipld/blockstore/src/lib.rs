@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod buffered;
+mod links;
+mod tracking;
 
 pub use self::buffered::BufferedBlockStore;
+pub use self::links::{links_to_block, scan_for_links};
+pub use self::tracking::{StoreStats, TrackingBlockStore};
 
 use cid::{multihash::MultihashDigest, Cid};
 use db::{MemoryDB, Store};
@@ -0,0 +1,199 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::{multihash::Code, Cid};
+use commcid::FilecoinMultihashCode;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+
+const CBOR_TAG_CID: u64 = 42;
+const MULTIBASE_IDENTITY: u8 = 0;
+
+/// Returns whether the Cid links to a block of a store. Identity Cids contain their data
+/// inline, and the data of Filecoin commitment Cids is never stored.
+pub fn links_to_block(cid: &Cid) -> bool {
+    let ch = cid.hash.algorithm();
+    ch != Code::Identity
+        && ch != Code::Custom(FilecoinMultihashCode::SealedV1 as u64)
+        && ch != Code::Custom(FilecoinMultihashCode::UnsealedV1 as u64)
+}
+
+/// Calls the callback with each Cid linked to by the DAG-CBOR encoded bytes, in order of
+/// appearance. The links are read in place, which avoids deserializing the whole block into
+/// `Ipld` just to traverse it.
+pub fn scan_for_links<F>(bz: &[u8], mut cb: F) -> Result<(), Box<dyn StdError>>
+where
+    F: FnMut(Cid) -> Result<(), Box<dyn StdError>>,
+{
+    let mut reader = Reader { bz, pos: 0 };
+    // Number of items left to read, including the items nested in lists and maps
+    let mut remaining: u64 = 1;
+    while remaining > 0 {
+        let (major, value) = reader.header()?;
+        match major {
+            // Byte and text strings
+            2 | 3 => reader.skip(value)?,
+            // Lists
+            4 => remaining = reader.add_items(remaining, value)?,
+            // Maps, which contain a key and a value per entry
+            5 => {
+                let items = value.checked_mul(2).ok_or("CBOR map length overflow")?;
+                remaining = reader.add_items(remaining, items)?;
+            }
+            6 if value == CBOR_TAG_CID => cb(reader.link()?)?,
+            // The tagged item is read next, as part of this item
+            6 => continue,
+            // Integers, simple values and floats have no content after the header
+            _ => (),
+        }
+        remaining -= 1;
+    }
+
+    if reader.pos != bz.len() {
+        return Err("trailing bytes after CBOR item".into());
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    bz: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Box<dyn StdError>> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.bz.len())
+            .ok_or("unexpected end of CBOR bytes")?;
+        let bz = &self.bz[self.pos..end];
+        self.pos = end;
+        Ok(bz)
+    }
+
+    fn skip(&mut self, n: u64) -> Result<(), Box<dyn StdError>> {
+        self.take(usize::try_from(n)?)?;
+        Ok(())
+    }
+
+    /// Reads the major type and the argument of an item header. The argument of floats is
+    /// their value, so floats are fully read.
+    fn header(&mut self) -> Result<(u8, u64), Box<dyn StdError>> {
+        let b = self.take(1)?[0];
+        let value = match b & 0x1f {
+            info @ 0..=23 => u64::from(info),
+            info @ 24..=27 => self
+                .take(1 << (info - 24))?
+                .iter()
+                .fold(0, |acc, b| acc << 8 | u64::from(*b)),
+            31 => return Err("indefinite length items are not valid DAG-CBOR".into()),
+            info => return Err(format!("invalid CBOR additional info {}", info).into()),
+        };
+        Ok((b >> 5, value))
+    }
+
+    /// Adds the items of a list or map to the items left to read. Every item takes at least a
+    /// byte, which bounds the lengths of lists and maps by the bytes left.
+    fn add_items(&self, remaining: u64, items: u64) -> Result<u64, Box<dyn StdError>> {
+        if items > (self.bz.len() - self.pos) as u64 {
+            return Err("CBOR length exceeds the bytes left".into());
+        }
+        Ok(remaining + items)
+    }
+
+    /// Reads the byte string of a Cid tag.
+    fn link(&mut self) -> Result<Cid, Box<dyn StdError>> {
+        let (major, len) = self.header()?;
+        if major != 2 {
+            return Err("Cid tag must contain a byte string".into());
+        }
+        let mut bz = self.take(usize::try_from(len)?)?;
+        if bz.first() == Some(&MULTIBASE_IDENTITY) {
+            bz = &bz[1..];
+        }
+        Ok(Cid::try_from(bz)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cid::multihash::{Blake2b256, Identity};
+    use commcid::commitment_to_cid;
+    use encoding::to_vec;
+    use forest_ipld::{ipld, Ipld};
+
+    fn ipld_links(ipld: &Ipld, links: &mut Vec<Cid>) {
+        match ipld {
+            Ipld::Link(c) => links.push(c.clone()),
+            Ipld::List(arr) => arr.iter().for_each(|item| ipld_links(item, links)),
+            Ipld::Map(map) => map.values().for_each(|v| ipld_links(v, links)),
+            _ => (),
+        }
+    }
+
+    fn links(bz: &[u8]) -> Result<Vec<Cid>, Box<dyn StdError>> {
+        let mut links = Vec::new();
+        scan_for_links(bz, |c| {
+            links.push(c);
+            Ok(())
+        })?;
+        Ok(links)
+    }
+
+    #[test]
+    fn scans_nested_links() {
+        let c1 = Cid::new_from_cbor(&[1], Blake2b256);
+        let c2 = Cid::new_from_cbor(&[2], Identity);
+        let c3 = Cid::new_from_cbor(&[3], Blake2b256);
+        let block = ipld!({
+            "a": [Link(c1.clone()), 1, -5, "str", Bytes(vec![0u8; 40])],
+            "b": { "nested": Link(c2.clone()), "n": null, "f": 1.5 },
+            "c": [[[Link(c3.clone())]], true],
+        });
+        let bz = to_vec(&block).unwrap();
+
+        // Same links in the same order as when traversing the deserialized block
+        let mut expected = Vec::new();
+        ipld_links(&block, &mut expected);
+        assert_eq!(expected, vec![c1, c2, c3]);
+        assert_eq!(links(&bz).unwrap(), expected);
+    }
+
+    #[test]
+    fn scans_blocks_without_links() {
+        assert!(links(&to_vec(&8u8).unwrap()).unwrap().is_empty());
+        assert!(links(&to_vec(&("value", 8u8)).unwrap()).unwrap().is_empty());
+        assert!(links(&to_vec(&Ipld::Null).unwrap()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_cbor() {
+        let c = Cid::new_from_cbor(&[1], Blake2b256);
+        let bz = to_vec(&ipld!([Link(c), 2])).unwrap();
+
+        // Truncated and trailing bytes
+        assert!(links(&bz[..bz.len() - 1]).is_err());
+        let mut trailing = bz.clone();
+        trailing.push(0);
+        assert!(links(&trailing).is_err());
+
+        // Indefinite length list
+        assert!(links(&[0x9f, 0x01, 0xff]).is_err());
+        // List longer than the bytes left
+        assert!(links(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+        // Cid tag of an integer
+        assert!(links(&[0xd8, 0x2a, 0x01]).is_err());
+    }
+
+    #[test]
+    fn block_links() {
+        assert!(links_to_block(&Cid::new_from_cbor(&[1], Blake2b256)));
+        assert!(!links_to_block(&Cid::new_from_cbor(&[1], Identity)));
+        assert!(!links_to_block(&commitment_to_cid(
+            &[7u8; 32],
+            FilecoinMultihashCode::SealedV1
+        )));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::BlockStore;
+use db::{Error, Store};
+use std::cell::RefCell;
+
+/// Number of reads and writes, and the bytes of each, made to a `TrackingBlockStore`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StoreStats {
+    pub reads: usize,
+    pub writes: usize,
+    pub bytes_read: usize,
+    pub bytes_written: usize,
+}
+
+/// Wrapper around `BlockStore` which tracks the IO made to the underlying store.
+/// This type is not threadsafe and can only be used in synchronous contexts.
+#[derive(Debug)]
+pub struct TrackingBlockStore<'bs, BS> {
+    base: &'bs BS,
+    stats: RefCell<StoreStats>,
+}
+
+impl<'bs, BS> TrackingBlockStore<'bs, BS>
+where
+    BS: BlockStore,
+{
+    pub fn new(base: &'bs BS) -> Self {
+        Self {
+            base,
+            stats: Default::default(),
+        }
+    }
+
+    /// Returns the IO made to the store since creation or the last reset.
+    pub fn stats(&self) -> StoreStats {
+        *self.stats.borrow()
+    }
+
+    /// Resets the tracked stats, used to measure the IO of each execution separately.
+    pub fn reset_stats(&self) {
+        *self.stats.borrow_mut() = Default::default();
+    }
+}
+
+impl<BS> BlockStore for TrackingBlockStore<'_, BS> where BS: BlockStore {}
+
+impl<BS> Store for TrackingBlockStore<'_, BS>
+where
+    BS: Store,
+{
+    fn read<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let res = self.base.read(key)?;
+        let mut stats = self.stats.borrow_mut();
+        stats.reads += 1;
+        if let Some(bz) = &res {
+            stats.bytes_read += bz.len();
+        }
+        Ok(res)
+    }
+    fn write<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let len = value.as_ref().len();
+        self.base.write(key, value)?;
+        let mut stats = self.stats.borrow_mut();
+        stats.writes += 1;
+        stats.bytes_written += len;
+        Ok(())
+    }
+    fn delete<K>(&self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.base.delete(key)
+    }
+    fn exists<K>(&self, key: K) -> Result<bool, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.base.exists(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cid::multihash::Blake2b256;
+    use encoding::to_vec;
+
+    #[test]
+    fn tracks_reads_and_writes() {
+        let mem = db::MemoryDB::default();
+        let store = TrackingBlockStore::new(&mem);
+
+        let cid = store.put(&"value", Blake2b256).unwrap();
+        let size = to_vec(&"value").unwrap().len();
+        assert_eq!(store.get::<String>(&cid).unwrap(), Some("value".to_owned()));
+        assert_eq!(
+            store.stats(),
+            StoreStats {
+                reads: 1,
+                writes: 1,
+                bytes_read: size,
+                bytes_written: size,
+            }
+        );
+
+        store.reset_stats();
+        assert_eq!(
+            store.get_bytes(&cid).unwrap().map(|bz| bz.len()),
+            Some(size)
+        );
+        assert_eq!(mem.get_bytes(&cid).unwrap().map(|bz| bz.len()), Some(size));
+        assert_eq!(
+            store.stats(),
+            StoreStats {
+                reads: 1,
+                bytes_read: size,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -4,13 +4,14 @@
 mod error;
 mod util;
 
-use blockstore::BlockStore;
-use cid::Cid;
-use error::*;
-use forest_encoding::from_slice;
+use blockstore::{links_to_block, scan_for_links, BlockStore};
+use cid::{Cid, Codec};
+pub use error::*;
+use forest_encoding::{from_slice, to_vec};
 use serde::{Deserialize, Serialize};
-use std::io::{BufReader, Read};
-use util::{ld_read, read_node};
+use std::collections::HashSet;
+use std::io::{BufReader, Read, Write};
+use util::{ld_read, ld_write, read_node};
 
 /// CAR file header
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -78,3 +79,83 @@ pub fn load_car<R: Read, B: BlockStore>(
     }
     Ok(car_reader.header.roots)
 }
+
+/// Writes the blocks of the BlockStore reachable from the roots as a CAR file. Each block is
+/// written once, in depth first order of the links from the roots.
+pub fn write_car<W: Write, B: BlockStore>(
+    s: &B,
+    roots: Vec<Cid>,
+    writer: &mut W,
+) -> Result<(), Error> {
+    let mut car_writer = CarWriter::new(writer, roots.clone())?;
+    for root in &roots {
+        car_writer.write_dag(s, root)?;
+    }
+    Ok(())
+}
+
+/// Writes blocks to a CAR file, skipping blocks which have already been written. Allows
+/// writing a selection of blocks and DAGs, rather than everything reachable from the roots.
+pub struct CarWriter<'a, W> {
+    writer: &'a mut W,
+    written: HashSet<Cid>,
+}
+
+impl<'a, W> CarWriter<'a, W>
+where
+    W: Write,
+{
+    /// Creates a new CarWriter and writes the CarHeader with the given roots
+    pub fn new(writer: &'a mut W, roots: Vec<Cid>) -> Result<Self, Error> {
+        let header = CarHeader::new(roots, 1);
+        let header_bz = to_vec(&header).map_err(|e| Error::Other(e.to_string()))?;
+        ld_write(writer, &header_bz)?;
+        Ok(Self {
+            writer,
+            written: HashSet::new(),
+        })
+    }
+
+    /// Writes a single block of the BlockStore without following its links. Returns the bytes
+    /// of the block, or `None` if the block was already written or has no data to write.
+    pub fn write_block<B: BlockStore>(
+        &mut self,
+        s: &B,
+        cid: &Cid,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        // Identity and commitment Cids have no block to write
+        if !links_to_block(cid) || !self.written.insert(cid.clone()) {
+            return Ok(None);
+        }
+        let data = s
+            .get_bytes(cid)
+            .map_err(|e| Error::Other(e.to_string()))?
+            .ok_or_else(|| Error::Other(format!("block {} not found in store", cid)))?;
+
+        let mut node = cid.to_bytes();
+        node.extend_from_slice(&data);
+        ld_write(self.writer, &node)?;
+        Ok(Some(data))
+    }
+
+    /// Writes all blocks of the BlockStore reachable from the root, in depth first order.
+    pub fn write_dag<B: BlockStore>(&mut self, s: &B, root: &Cid) -> Result<(), Error> {
+        let mut stack = vec![root.clone()];
+        while let Some(cid) = stack.pop() {
+            let data = match self.write_block(s, &cid)? {
+                Some(data) => data,
+                None => continue,
+            };
+            if cid.codec == Codec::DagCBOR {
+                let mut links = Vec::new();
+                scan_for_links(&data, |c| {
+                    links.push(c);
+                    Ok(())
+                })
+                .map_err(|e| Error::ParsingError(e.to_string()))?;
+                stack.extend(links.into_iter().rev());
+            }
+        }
+        Ok(())
+    }
+}
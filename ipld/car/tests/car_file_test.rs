@@ -1,6 +1,8 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use blockstore::BlockStore;
+use cid::multihash::{Blake2b256, Identity};
 use db::MemoryDB;
 use forest_car::*;
 use std::fs::File;
@@ -14,3 +16,39 @@ fn load_into_blockstore() {
 
     let _ = load_car(&mut bs, buf_reader).unwrap();
 }
+
+#[test]
+fn write_reachable_blocks() {
+    let bs = MemoryDB::default();
+    let leaf = bs.put(&("leaf", 1u8), Blake2b256).unwrap();
+    let identity = bs.put(&2u8, Identity).unwrap();
+    let node = bs
+        .put(&(leaf.clone(), identity, leaf.clone()), Blake2b256)
+        .unwrap();
+    let root = bs.put(&(node.clone(), "root"), Blake2b256).unwrap();
+    let unreachable = bs.put(&3u8, Blake2b256).unwrap();
+
+    let mut buf = Vec::new();
+    write_car(&bs, vec![root.clone()], &mut buf).unwrap();
+
+    let mut reader = CarReader::new(BufReader::new(buf.as_slice())).unwrap();
+    assert_eq!(reader.header.roots, vec![root.clone()]);
+    let mut blocks = 0;
+    while !reader.buf_reader.buffer().is_empty() {
+        reader.next_block().unwrap();
+        blocks += 1;
+    }
+    // The leaf linked twice is written once
+    assert_eq!(blocks, 3);
+
+    let loaded = MemoryDB::default();
+    let roots = load_car(&loaded, BufReader::new(buf.as_slice())).unwrap();
+    assert_eq!(roots, vec![root.clone()]);
+    for cid in &[root, node, leaf] {
+        assert_eq!(loaded.get_bytes(cid).unwrap(), bs.get_bytes(cid).unwrap());
+    }
+    assert_eq!(loaded.get_bytes(&unreachable).unwrap(), None);
+
+    // Blocks missing from the store can't be written
+    assert!(write_car(&loaded, vec![unreachable], &mut Vec::new()).is_err());
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ipld_hamt::{BytesKey, Entry, Hamt};
+
+const ITEMS: u64 = 1000;
+
+fn key(i: u64) -> BytesKey {
+    format!("key-{}", i).into_bytes().into()
+}
+
+/// Hamt of `ITEMS` entries, flushed so updates have to load the nodes from the store.
+fn setup(store: &db::MemoryDB) -> cid::Cid {
+    let mut hamt: Hamt<BytesKey, _> = Hamt::new(store);
+    for i in 0..ITEMS {
+        hamt.set(key(i), i).unwrap();
+    }
+    hamt.flush().unwrap()
+}
+
+/// Increments every value with a `get` followed by a `set`, which hashes each key and walks
+/// the Hamt twice, and clones the key value pair that is read.
+fn get_then_set(c: &mut Criterion) {
+    let store = db::MemoryDB::default();
+    let root = setup(&store);
+    c.bench_function("HAMT get then set", |b| {
+        b.iter(|| {
+            let mut hamt: Hamt<BytesKey, _> = Hamt::load(&root, &store).unwrap();
+            for i in 0..ITEMS {
+                let k = key(i);
+                let value: u64 = hamt.get(&k).unwrap().unwrap();
+                hamt.set(k, black_box(value + 1)).unwrap();
+            }
+            hamt
+        })
+    });
+}
+
+/// Increments every value through its entry, which hashes each key and walks the Hamt once.
+fn entry(c: &mut Criterion) {
+    let store = db::MemoryDB::default();
+    let root = setup(&store);
+    c.bench_function("HAMT entry", |b| {
+        b.iter(|| {
+            let mut hamt: Hamt<BytesKey, _> = Hamt::load(&root, &store).unwrap();
+            for i in 0..ITEMS {
+                if let Entry::Occupied(mut entry) = hamt.entry(key(i)).unwrap() {
+                    let value: u64 = entry.get().unwrap();
+                    entry.insert(black_box(value + 1)).unwrap();
+                }
+            }
+            hamt
+        })
+    });
+}
+
+criterion_group!(benches, get_then_set, entry);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::hash_bits::HashBits;
+use crate::node::Node;
+use crate::{Error, Hash, HashedKey};
+use forest_ipld::{from_ipld, to_ipld, Ipld};
+use ipld_blockstore::BlockStore;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A view into a single entry of the Hamt, which may either be occupied or vacant.
+///
+/// The entry is located with a single traversal of the Hamt, which allows reading and updating
+/// a value without hashing the key and walking the nodes twice.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_hamt::{Entry, Hamt};
+///
+/// let store = db::MemoryDB::default();
+///
+/// let mut map: Hamt<usize, _> = Hamt::new(&store);
+/// match map.entry(1).unwrap() {
+///     Entry::Occupied(_) => unreachable!(),
+///     Entry::Vacant(entry) => entry.insert(10u64).unwrap(),
+/// }
+/// if let Entry::Occupied(mut entry) = map.entry(1).unwrap() {
+///     let prev: u64 = entry.get().unwrap();
+///     entry.insert(prev + 1).unwrap();
+/// }
+/// assert_eq!(map.get(&1).unwrap(), Some(11u64));
+/// ```
+#[derive(Debug)]
+pub enum Entry<'a, K, BS> {
+    /// The key is set in the Hamt.
+    Occupied(OccupiedEntry<'a>),
+    /// The key is not set in the Hamt.
+    Vacant(VacantEntry<'a, K, BS>),
+}
+
+impl<'a, K, BS> Entry<'a, K, BS>
+where
+    K: Hash + Eq + PartialOrd + Serialize + DeserializeOwned + Clone,
+    BS: BlockStore,
+{
+    /// Returns the value of the entry, inserting the value returned by `f` if the entry is
+    /// vacant.
+    pub fn or_insert_with<V, F>(self, f: F) -> Result<V, Error>
+    where
+        V: Serialize + DeserializeOwned,
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.get(),
+            Entry::Vacant(entry) => {
+                let value = f();
+                entry.insert(&value)?;
+                Ok(value)
+            }
+        }
+    }
+}
+
+/// A view into an occupied entry of the Hamt.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    value: &'a mut Ipld,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    pub(crate) fn new(value: &'a mut Ipld) -> Self {
+        Self { value }
+    }
+
+    /// Returns the value of the entry.
+    pub fn get<V: DeserializeOwned>(&self) -> Result<V, Error> {
+        from_ipld(self.value).map_err(Error::Encoding)
+    }
+
+    /// Replaces the value of the entry.
+    pub fn insert<V: Serialize>(&mut self, value: V) -> Result<(), Error> {
+        *self.value = to_ipld(value)?;
+        Ok(())
+    }
+}
+
+/// A view into a vacant entry of the Hamt. Holds the node the key would be inserted into.
+#[derive(Debug)]
+pub struct VacantEntry<'a, K, BS> {
+    node: &'a mut Node<K>,
+    key: K,
+    hash: HashedKey,
+    /// Bits of the hash consumed before reaching the node.
+    consumed: u8,
+    depth: usize,
+    store: &'a BS,
+    bit_width: u8,
+}
+
+impl<'a, K, BS> VacantEntry<'a, K, BS>
+where
+    K: Hash + Eq + PartialOrd + Serialize + DeserializeOwned + Clone,
+    BS: BlockStore,
+{
+    pub(crate) fn new(
+        node: &'a mut Node<K>,
+        key: K,
+        hash: HashedKey,
+        consumed: u8,
+        depth: usize,
+        store: &'a BS,
+        bit_width: u8,
+    ) -> Self {
+        Self {
+            node,
+            key,
+            hash,
+            consumed,
+            depth,
+            store,
+            bit_width,
+        }
+    }
+
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Inserts the value into the Hamt, starting from the node the entry was located at.
+    pub fn insert<V: Serialize>(self, value: V) -> Result<(), Error> {
+        let value = to_ipld(value)?;
+        self.node.modify_value(
+            &mut HashBits::new_at_index(&self.hash, self.consumed),
+            self.bit_width,
+            self.depth,
+            self.key,
+            value,
+            self.store,
+        )
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::node::Node;
-use crate::{Error, Hash, DEFAULT_BIT_WIDTH};
+use crate::{Entry, Error, Hash, DEFAULT_BIT_WIDTH};
 use cid::{multihash::Blake2b256, Cid};
 use forest_ipld::{from_ipld, to_ipld, Ipld};
 use ipld_blockstore::BlockStore;
@@ -161,6 +161,50 @@ where
         }
     }
 
+    /// Gets the entry of the key in the HAMT for in place reading and modification, locating
+    /// it with a single traversal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ipld_hamt::{Entry, Hamt};
+    ///
+    /// let store = db::MemoryDB::default();
+    ///
+    /// let mut map: Hamt<usize, _> = Hamt::new(&store);
+    /// map.set(1, 5u64).unwrap();
+    /// if let Entry::Occupied(mut entry) = map.entry(1).unwrap() {
+    ///     let value: u64 = entry.get().unwrap();
+    ///     entry.insert(value * 2).unwrap();
+    /// }
+    /// assert_eq!(map.get(&1).unwrap(), Some(10u64));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Result<Entry<'_, K, BS>, Error> {
+        self.root.entry(key, self.store, self.bit_width)
+    }
+
+    /// Returns the value corresponding to the key, inserting the value returned by `f` if the
+    /// key is not set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ipld_hamt::Hamt;
+    ///
+    /// let store = db::MemoryDB::default();
+    ///
+    /// let mut map: Hamt<usize, _> = Hamt::new(&store);
+    /// assert_eq!(map.get_or_insert_with(1, || "a".to_string()).unwrap(), "a");
+    /// assert_eq!(map.get_or_insert_with(1, || "b".to_string()).unwrap(), "a");
+    /// ```
+    pub fn get_or_insert_with<V, F>(&mut self, key: K, f: F) -> Result<V, Error>
+    where
+        V: Serialize + DeserializeOwned,
+        F: FnOnce() -> V,
+    {
+        self.entry(key)?.or_insert_with(f)
+    }
+
     /// Removes a key from the HAMT, returning the value at the key if the key
     /// was previously in the HAMT.
     ///
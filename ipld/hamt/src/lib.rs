@@ -10,6 +10,7 @@
 //! The Hamt is a data structure that mimmics a HashMap which has the features of being sharded, persisted, and indexable by a Cid. The Hamt supports a variable bit width to adjust the amount of possible pointers that can exist at each height of the tree. Hamt can be modified at any point, but the underlying values are only persisted to the store when the [flush](struct.Hamt.html#method.flush) is called.
 
 mod bitfield;
+mod entry;
 mod error;
 mod hamt;
 mod hash;
@@ -17,6 +18,7 @@ mod hash_bits;
 mod node;
 mod pointer;
 
+pub use self::entry::{Entry, OccupiedEntry, VacantEntry};
 pub use self::error::Error;
 pub use self::hamt::Hamt;
 pub use self::hash::*;
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::bitfield::Bitfield;
+use super::entry::{Entry, OccupiedEntry, VacantEntry};
 use super::hash_bits::HashBits;
 use super::pointer::Pointer;
 use super::{Error, Hash, HashedKey, KeyValuePair, MAX_ARRAY_WIDTH};
@@ -94,6 +95,18 @@ where
         self.modify_value(&mut HashBits::new(&hash), bit_width, 0, key, value, store)
     }
 
+    /// Locates the entry of the key, pulling the nodes on its path from the store into the
+    /// cache so that the entry can be modified.
+    pub fn entry<'a, S: BlockStore>(
+        &'a mut self,
+        key: K,
+        store: &'a S,
+        bit_width: u8,
+    ) -> Result<Entry<'a, K, S>, Error> {
+        let hash = Self::hash(&key);
+        self.find_entry(&mut HashBits::new(&hash), bit_width, 0, key, hash, store)
+    }
+
     #[inline]
     pub fn get<Q: ?Sized, S: BlockStore>(
         &self,
@@ -196,6 +209,58 @@ where
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn find_entry<'a, S: BlockStore>(
+        &'a mut self,
+        hashed_key: &mut HashBits,
+        bit_width: u8,
+        depth: usize,
+        key: K,
+        hash: HashedKey,
+        store: &'a S,
+    ) -> Result<Entry<'a, K, S>, Error> {
+        let consumed = hashed_key.consumed;
+        let idx = hashed_key.next(bit_width)?;
+
+        if !self.bitfield.test_bit(idx) {
+            return Ok(Entry::Vacant(VacantEntry::new(
+                self, key, hash, consumed, depth, store, bit_width,
+            )));
+        }
+
+        let cindex = self.index_for_bit_pos(idx);
+        if let Pointer::Link(cid) = self.get_child(cindex) {
+            // Pull node from store into the cache, as the entry may be modified
+            let node = match store.get::<Node<K>>(cid)? {
+                Some(node) => node,
+                None => return Err(Error::CidNotFound(cid.to_string())),
+            };
+            *self.get_child_mut(cindex) = Pointer::Cache(Box::new(node));
+        }
+
+        // Position of the key in the values of the child, if the child holds values
+        let position = match self.get_child(cindex) {
+            Pointer::Values(vals) => Some(vals.iter().position(|kv| kv.key() == &key)),
+            _ => None,
+        };
+
+        match position {
+            Some(Some(i)) => match self.get_child_mut(cindex) {
+                Pointer::Values(vals) => Ok(Entry::Occupied(OccupiedEntry::new(&mut vals[i].1))),
+                _ => unreachable!("child was matched as values"),
+            },
+            Some(None) => Ok(Entry::Vacant(VacantEntry::new(
+                self, key, hash, consumed, depth, store, bit_width,
+            ))),
+            None => match self.get_child_mut(cindex) {
+                Pointer::Cache(n) => {
+                    n.find_entry(hashed_key, bit_width, depth + 1, key, hash, store)
+                }
+                _ => unreachable!("links are replaced by cached nodes"),
+            },
+        }
+    }
+
     /// The hash function used to hash keys.
     #[cfg(not(feature = "identity-hash"))]
     fn hash<X: ?Sized>(key: &X) -> HashedKey
@@ -219,7 +284,7 @@ where
     }
 
     /// Internal method to modify values.
-    fn modify_value<S: BlockStore>(
+    pub(crate) fn modify_value<S: BlockStore>(
         &mut self,
         hashed_key: &mut HashBits,
         bit_width: u8,
@@ -8,6 +8,8 @@ use cid::multihash::Blake2b256;
 #[cfg(not(feature = "identity-hash"))]
 use ipld_blockstore::BlockStore;
 #[cfg(not(feature = "identity-hash"))]
+use ipld_hamt::Entry;
+#[cfg(not(feature = "identity-hash"))]
 use serde_bytes::ByteBuf;
 
 #[cfg(feature = "identity-hash")]
@@ -155,6 +157,47 @@ fn set_delete_many() {
     );
 }
 
+#[test]
+#[cfg(not(feature = "identity-hash"))]
+fn entry_many() {
+    let store = db::MemoryDB::default();
+
+    // Inserting through vacant entries builds the same Hamt as setting the keys
+    let mut hamt: Hamt<BytesKey, _> = Hamt::new_with_bit_width(&store, 5);
+    for i in 0..200 {
+        let value = hamt
+            .get_or_insert_with(format!("{}", i).into_bytes().into(), || i)
+            .unwrap();
+        assert_eq!(value, i);
+    }
+    let c1 = hamt.flush().unwrap();
+    assert_eq!(
+        hex::encode(c1.to_bytes()),
+        "0171a0e402207c660382de99c174ce39517bdbd28f3967801aebbd9795f0591e226d93e2f010"
+    );
+
+    // Entries of a loaded Hamt are found through the links to its nodes
+    let mut loaded: Hamt<BytesKey, _> = Hamt::load_with_bit_width(&c1, &store, 5).unwrap();
+    let mut expected: Hamt<BytesKey, _> = Hamt::load_with_bit_width(&c1, &store, 5).unwrap();
+    for i in 0..200 {
+        let key: BytesKey = format!("{}", i).into_bytes().into();
+        assert_eq!(loaded.get_or_insert_with(key.clone(), || 0).unwrap(), i);
+        match loaded.entry(key.clone()).unwrap() {
+            Entry::Occupied(mut entry) => {
+                let value: u64 = entry.get().unwrap();
+                entry.insert(value * 2).unwrap();
+            }
+            Entry::Vacant(_) => panic!("key {} should be set", i),
+        }
+        expected.set(key, i * 2).unwrap();
+    }
+    match loaded.entry(b"missing".to_vec().into()).unwrap() {
+        Entry::Occupied(_) => panic!("key should not be set"),
+        Entry::Vacant(entry) => assert_eq!(entry.key(), &BytesKey::from("missing")),
+    }
+    assert_eq!(loaded.flush().unwrap(), expected.flush().unwrap());
+}
+
 #[cfg(feature = "identity-hash")]
 fn add_and_remove_keys(
     bit_width: u8,
@@ -1,11 +1,14 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::Ipld;
+use super::{Error, Ipld};
 use cid::Cid;
 use encoding::tags::current_cbor_tag;
-use serde::de::{self, Deserialize};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserialize, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fmt;
 
 /// Struct used in deserialization to decode cbor encoded data (including Cid tagged)
@@ -132,7 +135,8 @@ impl<'de> de::Visitor<'de> for IpldVisitor {
                 Ok(Ipld::Link(cid))
             }
             Some(tag) => Err(de::Error::custom(format!("unexpected tag ({})", tag))),
-            _ => Err(de::Error::custom("tag expected")),
+            // Links are visited without a tag when deserializing from an Ipld value
+            None => Ok(Ipld::Link(Cid::deserialize(deserializer)?)),
         }
     }
 }
@@ -145,3 +149,193 @@ impl<'de> de::Deserialize<'de> for Ipld {
         deserializer.deserialize_any(IpldVisitor)
     }
 }
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error::Encoding(msg.to_string())
+    }
+}
+
+/// Deserializes a type directly from an Ipld value, borrowing strings and bytes from it.
+impl<'de> de::Deserializer<'de> for &'de Ipld {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Ipld::Null => visitor.visit_unit(),
+            Ipld::Bool(v) => visitor.visit_bool(*v),
+            Ipld::Integer(v) => {
+                if let Ok(v) = u64::try_from(*v) {
+                    visitor.visit_u64(v)
+                } else if let Ok(v) = i64::try_from(*v) {
+                    visitor.visit_i64(v)
+                } else {
+                    visitor.visit_i128(*v)
+                }
+            }
+            Ipld::Float(v) => visitor.visit_f64(*v),
+            Ipld::String(v) => visitor.visit_borrowed_str(v),
+            Ipld::Bytes(v) => visitor.visit_borrowed_bytes(v),
+            Ipld::List(v) => {
+                let mut seq = SeqDeserializer::new(v.iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Ipld::Map(v) => {
+                let mut map = MapDeserializer::new(v.iter().map(|(k, v)| (k.as_str(), v)));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            // Cids deserialize through a newtype, the same way as when tagged in cbor
+            Ipld::Link(cid) => visitor.visit_newtype_struct(LinkDeserializer(cid)),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Ipld::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Enums are encoded as the variant name, or a map of the name to the variant value
+        match self {
+            Ipld::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: None,
+            }),
+            Ipld::Map(map) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            _ => Err(de::Error::custom(
+                "expected a string or single entry map for enum",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Ipld {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializer for the bytes of a linked Cid.
+struct LinkDeserializer<'de>(&'de Cid);
+
+impl<'de> de::Deserializer<'de> for LinkDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(&self.0.to_bytes())
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Access to the variant of an enum encoded in an Ipld value.
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    value: Option<&'de Ipld>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some(Ipld::Null) => Ok(()),
+            Some(_) => Err(de::Error::custom("expected unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::custom("expected newtype variant")),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(value @ Ipld::List(_)) => de::Deserializer::deserialize_any(value, visitor),
+            _ => Err(de::Error::custom("expected tuple variant")),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(value @ Ipld::Map(_)) => de::Deserializer::deserialize_any(value, visitor),
+            _ => Err(de::Error::custom("expected struct variant")),
+        }
+    }
+}
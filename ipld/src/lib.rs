@@ -19,7 +19,7 @@ pub use path::Path;
 pub use path_segment::PathSegment;
 
 use cid::Cid;
-use encoding::{from_slice, to_vec, Cbor};
+use encoding::Cbor;
 use ser::Serializer;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -129,16 +129,10 @@ where
     ipld.serialize(Serializer)
 }
 
-/// Convert a `Ipld` structure into a type `T`
-/// Currently converts using a byte buffer with serde_cbor
+/// Convert a `Ipld` structure into a type `T`, without encoding the value to bytes.
 pub fn from_ipld<T>(value: &Ipld) -> Result<T, String>
 where
     T: DeserializeOwned,
 {
-    // TODO update to not go through byte buffer to convert
-    // There is a good amount of overhead for this (having to implement serde::Deserializer)
-    // for Ipld, but possible. The benefit isn't worth changing yet since if the value is not
-    // passed by reference as needed by HAMT, then the values will have to be cloned.
-    let buf = to_vec(value).map_err(|e| e.to_string())?;
-    from_slice(buf.as_slice()).map_err(|e| e.to_string())
+    T::deserialize(value).map_err(|e| e.to_string())
 }
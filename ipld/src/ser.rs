@@ -13,7 +13,6 @@ use std::collections::BTreeMap;
 
 use super::{to_ipld, Error, Ipld};
 use cid::Cid;
-use encoding::to_vec;
 use serde::{self, Serialize};
 use std::convert::TryFrom;
 
@@ -155,30 +154,15 @@ impl serde::Serializer for Serializer {
     where
         T: Serialize,
     {
-        // TODO revisit this, necessary workaround to allow Cids to be converted to Ipld
-        // but is not very clean to use and requires the bytes buffer. The reason this is
-        // necessary is because Cids serialize through newtype_struct.
+        // Cids serialize through a tagged newtype of their bytes, prefixed with the
+        // multibase identity byte.
         if name == "\0cbor_tag" {
-            let bz = to_vec(&ipld)?;
-            let mut sl = &bz[..];
-
-            if bz.len() < 3 {
-                return Err(Error::Encoding("Invalid tag for Ipld".to_owned()));
-            }
-
-            // Index past the cbor and multibase prefix for Cid deserialization
-            match sl[0] {
-                0x40..=0x57 => sl = &sl[2..],
-                0x58 => sl = &sl[3..],  // extra u8
-                0x59 => sl = &sl[4..],  // extra u16
-                0x5a => sl = &sl[6..],  // extra u32
-                0x5b => sl = &sl[10..], // extra u64
-                _ => return Err(Error::Encoding("Invalid cbor tag".to_owned())),
-            }
-
-            return Ok(Ipld::Link(
-                Cid::try_from(sl).map_err(|e| Error::Encoding(e.to_string()))?,
-            ));
+            return match ipld.serialize(self)? {
+                Ipld::Bytes(bz) if !bz.is_empty() => Ok(Ipld::Link(
+                    Cid::try_from(&bz[1..]).map_err(|e| Error::Encoding(e.to_string()))?,
+                )),
+                _ => Err(Error::Encoding("Invalid tag for Ipld".to_owned())),
+            };
         }
         ipld.serialize(self)
     }
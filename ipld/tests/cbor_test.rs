@@ -6,10 +6,10 @@ use cid::{
     Cid,
 };
 use encoding::{from_slice, to_vec};
-use forest_ipld::{ipld, to_ipld, Ipld};
+use forest_ipld::{from_ipld, ipld, to_ipld, Ipld};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 struct TestStruct {
     name: String,
     details: Cid,
@@ -62,3 +62,58 @@ fn cid_conversions_ipld() {
     let ipld2 = to_ipld(&cid).unwrap();
     assert_eq!(ipld, ipld2);
 }
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum TestEnum {
+    Unit,
+    Newtype(u64),
+    Struct { value: i64 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct NestedStruct {
+    inner: Vec<TestStruct>,
+    optional: Option<Cid>,
+    #[serde(with = "encoding::serde_bytes")]
+    bytes: Vec<u8>,
+    tuple: (i64, bool),
+    variants: Vec<TestEnum>,
+    raw: Ipld,
+}
+
+#[test]
+fn ipld_typed_conversions() {
+    let cid = Cid::new_from_cbor(&[1, 2, 3], Blake2b256);
+    let value = NestedStruct {
+        inner: vec![TestStruct {
+            name: "s".to_owned(),
+            details: cid.clone(),
+        }],
+        optional: None,
+        bytes: vec![0, 1, 2],
+        tuple: (-8, true),
+        variants: vec![
+            TestEnum::Unit,
+            TestEnum::Newtype(3),
+            TestEnum::Struct { value: -1 },
+        ],
+        raw: ipld!([Link(cid.clone()), "string", null]),
+    };
+
+    let ipld = to_ipld(&value).unwrap();
+    let decoded: NestedStruct = from_ipld(&ipld).unwrap();
+    assert_eq!(decoded, value);
+
+    // Conversion must match decoding from the cbor encoding of the value
+    let bz = to_vec(&ipld).unwrap();
+    assert_eq!(from_slice::<NestedStruct>(&bz).unwrap(), decoded);
+
+    // Links are preserved when converting to Ipld
+    assert_eq!(from_ipld::<Ipld>(&ipld).unwrap(), ipld);
+    assert_eq!(from_ipld::<Cid>(&Ipld::Link(cid.clone())).unwrap(), cid);
+
+    // Type mismatches are errors
+    assert!(from_ipld::<u8>(&ipld!(300)).is_err());
+    assert!(from_ipld::<String>(&ipld!(1)).is_err());
+    assert!(from_ipld::<(u8,)>(&ipld!([1, 2])).is_err());
+}
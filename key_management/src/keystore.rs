@@ -2,17 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::errors::Error;
-use crypto::SignatureType;
+use crypto::{PrivateKey, SignatureType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// KeyInfo struct, this contains the type of key (stored as a string) and the private key.
-/// note how the private key is stored as a byte vector
+/// note how the private key is stored as bytes, which are zeroed when the KeyInfo is dropped
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct KeyInfo {
     key_type: SignatureType,
-    // Vec<u8> is used because The private keys for BLS and SECP256K1 are not of the same type
-    private_key: Vec<u8>,
+    // Bytes are used because The private keys for BLS and SECP256K1 are not of the same type
+    private_key: PrivateKey,
 }
 
 impl KeyInfo {
@@ -20,7 +20,7 @@ impl KeyInfo {
     pub fn new(key_type: SignatureType, private_key: Vec<u8>) -> Self {
         KeyInfo {
             key_type,
-            private_key,
+            private_key: PrivateKey::new(private_key),
         }
     }
 
@@ -29,9 +29,9 @@ impl KeyInfo {
         &self.key_type
     }
 
-    /// Return a clone of the private_key
-    pub fn private_key(&self) -> &Vec<u8> {
-        &self.private_key
+    /// Return the bytes of the private_key
+    pub fn private_key(&self) -> &[u8] {
+        self.private_key.as_bytes()
     }
 }
 
@@ -64,7 +64,7 @@ pub mod json {
     {
         JsonHelper {
             sig_type: k.key_type,
-            private_key: base64::encode(&k.private_key),
+            private_key: base64::encode(k.private_key.as_bytes()),
         }
         .serialize(serializer)
     }
@@ -79,7 +79,7 @@ pub mod json {
         } = Deserialize::deserialize(deserializer)?;
         Ok(KeyInfo {
             key_type: sig_type,
-            private_key: base64::decode(private_key).map_err(de::Error::custom)?,
+            private_key: PrivateKey::new(base64::decode(private_key).map_err(de::Error::custom)?),
         })
     }
 }
@@ -260,7 +260,7 @@ mod tests {
     #[test]
     fn sign() {
         let key_vec = construct_priv_keys();
-        let priv_key_bytes = key_vec[2].key_info.private_key().clone();
+        let priv_key_bytes = key_vec[2].key_info.private_key().to_vec();
         let addr = key_vec[2].address.clone();
         let mut wallet = Wallet::new_from_keys(MemKeyStore::new(), key_vec);
         let msg = [0u8; 64];
@@ -412,4 +412,19 @@ mod tests {
         let invalid_addr = wallet.generate_addr(SignatureType::BLS).unwrap();
         assert!(sig.verify(&msg, &invalid_addr).is_err())
     }
+
+    #[test]
+    fn key_info_hides_private_key() {
+        let key_info = KeyInfo::new(SignatureType::Secp256k1, vec![0xab; 32]);
+        let debug = format!("{:?}", key_info);
+        assert!(!debug.contains("171"), "{}", debug);
+        assert!(debug.contains("32 bytes redacted"), "{}", debug);
+
+        assert_eq!(key_info.private_key(), &[0xab; 32][..]);
+        assert_eq!(key_info, key_info.clone());
+        assert_ne!(
+            key_info,
+            KeyInfo::new(SignatureType::Secp256k1, vec![0xab; 31])
+        );
+    }
 }
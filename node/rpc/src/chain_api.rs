@@ -203,7 +203,7 @@ where
     KS: KeyStore + Send + Sync + 'static,
 {
     let (tsk, pers, epoch, entropy) = params;
-    Ok(chain::get_randomness(
+    Ok(chain::get_chain_randomness(
         data.store.as_ref(),
         &tsk,
         DomainSeparationTag::from_i64(pers).ok_or("invalid DomainSeparationTag")?,
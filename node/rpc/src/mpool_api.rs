@@ -32,7 +32,7 @@ where
     let price = data
         .mpool
         .estimate_gas_price(nblocks, sender, gas_limit, tsk)?;
-    Ok(price.to_string())
+    Ok(price.atto().to_string())
 }
 
 /// get the sequence of given address in mpool
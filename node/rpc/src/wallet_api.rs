@@ -19,7 +19,7 @@ use std::convert::TryFrom;
 use std::str::FromStr;
 use wallet::{json::KeyInfoJson, Key, KeyStore};
 
-/// Return the balance from StateManager for a given Address
+/// Return the balance in attoFIL from StateManager for a given Address
 pub(crate) async fn wallet_balance<DB, KS>(
     data: Data<RpcState<DB, KS>>,
     Params(params): Params<(String,)>,
@@ -39,7 +39,7 @@ where
         Ok(act) => {
             let actor = act.ok_or("Could not find actor")?;
             let actor_balance = actor.balance;
-            Ok(actor_balance.to_string())
+            Ok(actor_balance.atto().to_string())
         }
         Err(e) => {
             if e == "Address not found" {
@@ -7,7 +7,38 @@ pub mod sector;
 pub use self::piece::*;
 pub use self::sector::*;
 
-use num_bigint::BigInt;
+use clock::ChainEpoch;
+use vm::TokenAmount;
+
+/// Version of the network protocol, which determines the actor policies in effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NetworkVersion {
+    V0,
+    /// Market `AddBalance` returns the updated escrow balance.
+    V1,
+    /// Block rewards are paid for each win of the block producer in the epoch's election.
+    V2,
+    /// Market actor indexes deals by provider.
+    V3,
+    /// Payment channel voucher secrets can be prefixed with the hash function of their digest.
+    V4,
+    /// Market actor tracks the pending deal proposals to reject duplicates.
+    V5,
+    /// Executed messages burn part of the unused gas when their gas limit overestimates the gas
+    /// used.
+    V6,
+    /// Miner actor activates the deals of its proven sectors through the market's
+    /// `VerifyDealsForActivation`.
+    V7,
+    /// Multisig signer swaps take effect once the new signer confirms them.
+    V8,
+    /// Power actor tracks the power committed by all miners, including those below the
+    /// consensus minimum power.
+    V9,
+    /// Reward actor pays a reward for the genesis epoch, and the simple supply of an epoch
+    /// includes the epoch being paid for.
+    V10,
+}
 
 /// Config trait which handles different network configurations.
 pub trait NetworkParams {
@@ -18,13 +49,19 @@ pub trait NetworkParams {
     const MINING_REWARD_TOTAL: i64;
 
     /// Initial reward actor balance. This function is only called in genesis setting up state.
-    fn initial_reward_balance() -> BigInt {
-        BigInt::from(Self::MINING_REWARD_TOTAL) * Self::TOTAL_FILECOIN
+    fn initial_reward_balance() -> TokenAmount {
+        TokenAmount::from(Self::MINING_REWARD_TOTAL) * Self::TOTAL_FILECOIN
     }
 
-    /// Convert integer value of tokens into BigInt based on the token precision.
-    fn from_fil(i: i64) -> BigInt {
-        BigInt::from(i) * FILECOIN_PRECISION
+    /// Convert integer value of tokens into a token amount based on the token precision.
+    fn from_fil(i: i64) -> TokenAmount {
+        TokenAmount::from_whole(i)
+    }
+
+    /// Network version in effect at the given epoch, networks with upgrades override this
+    /// with their upgrade schedule.
+    fn network_version(_epoch: ChainEpoch) -> NetworkVersion {
+        NetworkVersion::V0
     }
 }
 
@@ -34,6 +71,3 @@ impl NetworkParams for DevnetParams {
     const TOTAL_FILECOIN: i64 = 2_000_000_000;
     const MINING_REWARD_TOTAL: i64 = 1_400_000_000;
 }
-
-/// Ratio of integer values to token value.
-pub const FILECOIN_PRECISION: i64 = 1_000_000_000_000_000_000;
@@ -3,11 +3,14 @@
 
 mod zero;
 
+use crate::RegisteredSealProof;
 use cid::Cid;
-use commcid::cid_to_piece_commitment_v1;
+use commcid::{cid_to_piece_commitment_v1, piece_commitment_v1_to_cid};
 use encoding::tuple::*;
+use filecoin_proofs_api::seal::generate_piece_commitment;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
+use std::io::Read;
 pub use zero::zero_piece_commitment;
 
 /// Size of a piece in bytes
@@ -69,6 +72,24 @@ pub struct PieceInfo {
     pub cid: Cid,
 }
 
+impl PieceInfo {
+    /// Computes the piece commitment of the data read from the source, which must contain
+    /// exactly `size` bytes, and returns the info of the piece.
+    pub fn generate<R: Read>(
+        proof_type: RegisteredSealProof,
+        source: R,
+        size: UnpaddedPieceSize,
+    ) -> Result<Self, String> {
+        size.validate()?;
+        let info = generate_piece_commitment(proof_type.try_into()?, source, size.into())
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            size: size.padded(),
+            cid: piece_commitment_v1_to_cid(&info.commitment),
+        })
+    }
+}
+
 impl TryFrom<&PieceInfo> for filecoin_proofs_api::PieceInfo {
     type Error = &'static str;
 
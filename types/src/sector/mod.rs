@@ -9,14 +9,95 @@ pub use self::post::*;
 pub use self::registered_proof::*;
 pub use self::seal::*;
 
-use encoding::{repr::*, tuple::*};
+use encoding::{de, repr::*, ser, tuple::*};
 use num_bigint::BigInt;
 use num_bigint::BigUint;
 use num_derive::FromPrimitive;
+use std::convert::TryFrom;
 use std::fmt;
 use vm::ActorID;
 
-pub type SectorNumber = u64;
+/// Maximum sector number, sector numbers must fit within a signed 64 bit integer.
+pub const MAX_SECTOR_NUMBER: u64 = i64::MAX as u64;
+
+/// Maximum partition number, partition numbers must fit within a signed 64 bit integer.
+pub const MAX_PARTITION_NUMBER: u64 = i64::MAX as u64;
+
+/// Defines a `u64` newtype which is validated against a maximum value on creation
+/// and when decoded.
+macro_rules! bounded_number {
+    ($(#[$attr:meta])* $name:ident, $max:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(u64);
+
+        impl $name {
+            /// Creates a new identifier, returns an error if it exceeds the maximum value.
+            pub fn new(n: u64) -> Result<Self, String> {
+                if n > $max {
+                    return Err(format!(
+                        "{} {} out of range, max {}",
+                        stringify!($name),
+                        n,
+                        $max
+                    ));
+                }
+                Ok(Self(n))
+            }
+        }
+
+        impl TryFrom<u64> for $name {
+            type Error = String;
+
+            fn try_from(n: u64) -> Result<Self, Self::Error> {
+                Self::new(n)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(n: $name) -> u64 {
+                n.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl ser::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                ser::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> de::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                let n: u64 = de::Deserialize::deserialize(deserializer)?;
+                Self::new(n).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+bounded_number!(
+    /// Number of a sector, unique within a miner.
+    SectorNumber,
+    MAX_SECTOR_NUMBER
+);
+
+bounded_number!(
+    /// Index of a Window PoSt partition, counted across all deadlines of a proving period.
+    PartitionNumber,
+    MAX_PARTITION_NUMBER
+);
 
 /// Unit of storage power (measured in bytes)
 pub type StoragePower = BigInt;
@@ -0,0 +1,30 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use encoding::{from_slice, to_vec};
+use fil_types::{PartitionNumber, SectorNumber, MAX_SECTOR_NUMBER};
+use std::convert::TryFrom;
+
+#[test]
+fn sector_number_bounds() {
+    assert!(SectorNumber::new(0).is_ok());
+    assert_eq!(
+        u64::from(SectorNumber::new(MAX_SECTOR_NUMBER).unwrap()),
+        MAX_SECTOR_NUMBER
+    );
+    assert!(SectorNumber::new(MAX_SECTOR_NUMBER + 1).is_err());
+    assert!(PartitionNumber::try_from(std::u64::MAX).is_err());
+}
+
+#[test]
+fn sector_number_decode() {
+    let num = SectorNumber::new(1234).unwrap();
+    let bz = to_vec(&num).unwrap();
+    // Encoded the same as the underlying integer
+    assert_eq!(bz, to_vec(&1234u64).unwrap());
+    assert_eq!(from_slice::<SectorNumber>(&bz).unwrap(), num);
+
+    // Invalid sector numbers are rejected when decoded
+    let bz = to_vec(&(MAX_SECTOR_NUMBER + 1)).unwrap();
+    assert!(from_slice::<SectorNumber>(&bz).is_err());
+}
@@ -1,6 +1,7 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::{check_serialized_len, deserialize_bytes};
 use num_bigint::{BigInt, Sign};
 use serde::{Deserialize, Serialize};
 
@@ -14,7 +15,8 @@ pub struct BigIntSer<'a>(#[serde(with = "self")] pub &'a BigInt);
 #[serde(transparent)]
 pub struct BigIntDe(#[serde(with = "self")] pub BigInt);
 
-/// Serializes big int as bytes following Filecoin spec.
+/// Serializes big int as bytes following Filecoin spec. Fails if the bytes exceed
+/// `MAX_BIGINT_SIZE`.
 pub fn serialize<S>(int: &BigInt, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -28,28 +30,26 @@ where
         Sign::NoSign => bz = Vec::new(),
     }
 
+    check_serialized_len::<S::Error>(&bz)?;
+
     // Serialize as bytes
     serde_bytes::Serialize::serialize(&bz, serializer)
 }
 
-/// Deserializes bytes into big int.
+/// Deserializes bytes into big int. Fails if the bytes exceed `MAX_BIGINT_SIZE`.
 pub fn deserialize<'de, D>(deserializer: D) -> Result<BigInt, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let mut bz: Vec<u8> = serde_bytes::Deserialize::deserialize(deserializer)?;
-    if bz.is_empty() {
-        return Ok(BigInt::default());
-    }
-    let sign_byte = bz.remove(0);
-    let sign: Sign = match sign_byte {
-        1 => Sign::Minus,
-        0 => Sign::Plus,
-        _ => {
-            return Err(serde::de::Error::custom(
-                "First byte must be valid sign (0, 1)",
-            ));
+    deserialize_bytes(deserializer, |bz| {
+        if bz.is_empty() {
+            return Ok(BigInt::default());
         }
-    };
-    Ok(BigInt::from_bytes_be(sign, &bz))
+        let sign: Sign = match bz[0] {
+            1 => Sign::Minus,
+            0 => Sign::Plus,
+            _ => return Err("First byte must be valid sign (0, 1)"),
+        };
+        Ok(BigInt::from_bytes_be(sign, &bz[1..]))
+    })
 }
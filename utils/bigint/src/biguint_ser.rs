@@ -1,6 +1,7 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::{check_serialized_len, deserialize_bytes};
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +28,8 @@ where
         bz.insert(0, 0);
     }
 
+    check_serialized_len::<S::Error>(&bz)?;
+
     // Serialize as bytes
     serde_bytes::Serialize::serialize(&bz, serializer)
 }
@@ -35,16 +38,15 @@ pub fn deserialize<'de, D>(deserializer: D) -> Result<BigUint, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let mut bz: Vec<u8> = serde_bytes::Deserialize::deserialize(deserializer)?;
-    if bz.is_empty() {
-        return Ok(BigUint::default());
-    }
+    deserialize_bytes(deserializer, |bz| {
+        if bz.is_empty() {
+            return Ok(BigUint::default());
+        }
 
-    if bz.remove(0) != 0 {
-        return Err(serde::de::Error::custom(
-            "First byte must be 0 to decode as BigUint",
-        ));
-    }
+        if bz[0] != 0 {
+            return Err("First byte must be 0 to decode as BigUint");
+        }
 
-    Ok(BigUint::from_bytes_be(&bz))
+        Ok(BigUint::from_bytes_be(&bz[1..]))
+    })
 }
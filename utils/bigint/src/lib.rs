@@ -5,3 +5,77 @@ pub mod bigint_ser;
 pub mod biguint_ser;
 
 pub use num_bigint::*;
+
+use serde::de;
+use std::fmt;
+
+/// Maximum length of the serialized bytes of a big integer, including the sign byte. Big
+/// integers which exceed it are rejected by consensus, so they fail to serialize and
+/// deserialize.
+pub const MAX_BIGINT_SIZE: usize = 128;
+
+/// Deserializes the bytes of a big integer and decodes them with `decode`. Fails if the bytes
+/// exceed `MAX_BIGINT_SIZE`, which is checked before buffering them where possible.
+fn deserialize_bytes<'de, D, T, F>(deserializer: D, decode: F) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnOnce(&[u8]) -> Result<T, &'static str>,
+{
+    deserializer.deserialize_bytes(BytesVisitor(decode))
+}
+
+struct BytesVisitor<F>(F);
+
+impl<'de, T, F> de::Visitor<'de> for BytesVisitor<F>
+where
+    F: FnOnce(&[u8]) -> Result<T, &'static str>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "at most {} bytes", MAX_BIGINT_SIZE)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        if v.len() > MAX_BIGINT_SIZE {
+            return Err(de::Error::invalid_length(v.len(), &self));
+        }
+        (self.0)(v).map_err(de::Error::custom)
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(&v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<T, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut bz: Vec<u8> = Vec::new();
+        while let Some(b) = seq.next_element()? {
+            if bz.len() == MAX_BIGINT_SIZE {
+                return Err(de::Error::invalid_length(bz.len() + 1, &self));
+            }
+            bz.push(b);
+        }
+        self.visit_bytes(&bz)
+    }
+}
+
+/// Checks the length of the serialized bytes of a big integer.
+fn check_serialized_len<E: serde::ser::Error>(bz: &[u8]) -> Result<(), E> {
+    if bz.len() > MAX_BIGINT_SIZE {
+        return Err(E::custom(format!(
+            "big integer of {} bytes exceeds the maximum of {} bytes",
+            bz.len(),
+            MAX_BIGINT_SIZE
+        )));
+    }
+    Ok(())
+}
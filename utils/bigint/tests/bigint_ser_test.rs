@@ -0,0 +1,67 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_bigint::{
+    bigint_ser::{BigIntDe, BigIntSer},
+    biguint_ser::{BigUintDe, BigUintSer},
+    BigInt, BigUint, MAX_BIGINT_SIZE,
+};
+use serde_cbor::{from_slice, to_vec, Value};
+
+/// Returns the CBOR encoding of the bytes.
+fn cbor_bytes(bz: Vec<u8>) -> Vec<u8> {
+    to_vec(&Value::Bytes(bz)).unwrap()
+}
+
+#[test]
+fn bigint_round_trip() {
+    for int in &[
+        BigInt::from(0),
+        BigInt::from(1),
+        BigInt::from(-1),
+        BigInt::from(u64::MAX) * 1000,
+    ] {
+        let bz = to_vec(&BigIntSer(int)).unwrap();
+        let BigIntDe(decoded) = from_slice(&bz).unwrap();
+        assert_eq!(&decoded, int);
+    }
+    assert_eq!(to_vec(&BigIntSer(&BigInt::from(-1))).unwrap(), [0x42, 1, 1]);
+
+    let uint = BigUint::from(u64::MAX) * 1000u32;
+    let bz = to_vec(&BigUintSer(&uint)).unwrap();
+    let BigUintDe(decoded) = from_slice(&bz).unwrap();
+    assert_eq!(decoded, uint);
+}
+
+#[test]
+fn bigint_max_size() {
+    // The largest big integers have a sign byte and 127 bytes of magnitude
+    let max = BigInt::from_bytes_be(forest_bigint::Sign::Minus, &[0xff; MAX_BIGINT_SIZE - 1]);
+    let bz = to_vec(&BigIntSer(&max)).unwrap();
+    let BigIntDe(decoded) = from_slice(&bz).unwrap();
+    assert_eq!(decoded, max);
+
+    let too_large: BigInt = max - 1;
+    assert!(to_vec(&BigIntSer(&too_large)).is_err());
+    let mut bz = vec![1];
+    bz.extend_from_slice(&[0xff; MAX_BIGINT_SIZE]);
+    assert!(from_slice::<BigIntDe>(&cbor_bytes(bz)).is_err());
+
+    let max = BigUint::from_bytes_be(&[0xff; MAX_BIGINT_SIZE - 1]);
+    let bz = to_vec(&BigUintSer(&max)).unwrap();
+    let BigUintDe(decoded) = from_slice(&bz).unwrap();
+    assert_eq!(decoded, max);
+
+    assert!(to_vec(&BigUintSer(&(max + 1u32))).is_err());
+    let mut bz = vec![0];
+    bz.extend_from_slice(&[0xff; MAX_BIGINT_SIZE]);
+    assert!(from_slice::<BigUintDe>(&cbor_bytes(bz)).is_err());
+}
+
+#[test]
+fn bigint_invalid_sign() {
+    assert!(from_slice::<BigIntDe>(&cbor_bytes(vec![2, 1])).is_err());
+    assert!(from_slice::<BigUintDe>(&cbor_bytes(vec![1, 1])).is_err());
+    let BigIntDe(zero) = from_slice(&cbor_bytes(vec![])).unwrap();
+    assert_eq!(zero, BigInt::from(0));
+}
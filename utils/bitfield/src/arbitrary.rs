@@ -0,0 +1,84 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Generation of random bit fields for property tests, with `quickcheck` and `proptest`. Bit
+//! fields are generated either from random runs of 0s and 1s, or as alternating single set and
+//! unset bits, which is the worst case for the size of the RLE+ encoding.
+
+use super::{iter::Ranges, BitField};
+use alloc::{boxed::Box, vec::Vec};
+use core::ops::Range;
+use proptest::collection::vec;
+use proptest::prop_oneof;
+use proptest::strategy::{BoxedStrategy, Strategy};
+use quickcheck::{Arbitrary, Gen};
+
+/// Creates a bit field from the lengths of its alternating runs of 0s and 1s, starting with 0s.
+/// Empty runs are allowed, in which case the runs around them are joined.
+fn from_runs(runs: &[usize]) -> BitField {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut index = 0;
+    for (i, &len) in runs.iter().enumerate() {
+        let end = index + len;
+        if i % 2 == 1 && len > 0 {
+            match ranges.last_mut() {
+                Some(last) if last.end == index => last.end = end,
+                _ => ranges.push(index..end),
+            }
+        }
+        index = end;
+    }
+    BitField::from_ranges(Ranges::new(ranges))
+}
+
+/// Returns the lengths of the alternating runs of 0s and 1s of the bit field, starting with 0s.
+fn to_runs(bf: &BitField) -> Vec<usize> {
+    let mut runs = Vec::new();
+    let mut index = 0;
+    for range in bf.ranges() {
+        runs.push(range.start - index);
+        runs.push(range.len());
+        index = range.end;
+    }
+    runs
+}
+
+/// Creates a bit field with `len` bits set at every other bit from `offset`.
+fn alternating_bits(offset: usize, len: usize) -> BitField {
+    (0..len).map(|i| offset + 2 * i).collect()
+}
+
+impl Arbitrary for BitField {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        if bool::arbitrary(g) {
+            from_runs(&Vec::arbitrary(g))
+        } else {
+            alternating_bits(usize::arbitrary(g), usize::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(to_runs(self).shrink().map(|runs| from_runs(&runs)))
+    }
+}
+
+/// Strategy generating bit fields of up to `max_runs` runs of 0s and 1s, each of 1 to
+/// `max_run_len` bits.
+pub fn runs(max_runs: usize, max_run_len: usize) -> impl Strategy<Value = BitField> {
+    vec(1..=max_run_len, 0..=max_runs).prop_map(|runs| from_runs(&runs))
+}
+
+/// Strategy generating bit fields of up to `max_len` set bits, alternating with unset bits
+/// after a run of up to `max_len` unset bits.
+pub fn alternating(max_len: usize) -> impl Strategy<Value = BitField> {
+    (0..=max_len, 0..=max_len).prop_map(|(offset, len)| alternating_bits(offset, len))
+}
+
+impl proptest::arbitrary::Arbitrary for BitField {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![runs(64, 1 << 12), alternating(1 << 10)].boxed()
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::{rleplus::MAX_ENCODED_SIZE, BitField, Result};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::Deref;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A bit field with at most `MAX` set bits. Deserializing fails if the decoded bit field has
+/// more set bits, which validates bit fields received as parameters when they are decoded.
+///
+/// The bit field can be read through `Deref`, but can only be modified by taking it out with
+/// `into_inner`, so that the bound always holds.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BoundedBitField<const MAX: usize>(BitField);
+
+impl<const MAX: usize> BoundedBitField<MAX> {
+    /// Creates a bounded bit field, failing if the bit field has more than `MAX` set bits.
+    pub fn new(bf: BitField) -> Result<Self> {
+        if bf.len() > MAX {
+            return Err("bit field exceeds the maximum number of set bits");
+        }
+        Ok(Self(bf))
+    }
+
+    /// Returns the underlying bit field.
+    pub fn into_inner(self) -> BitField {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Deref for BoundedBitField<MAX> {
+    type Target = BitField;
+
+    fn deref(&self) -> &BitField {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> TryFrom<BitField> for BoundedBitField<MAX> {
+    type Error = &'static str;
+
+    fn try_from(bf: BitField) -> Result<Self> {
+        Self::new(bf)
+    }
+}
+
+impl<const MAX: usize> From<BoundedBitField<MAX>> for BitField {
+    fn from(bf: BoundedBitField<MAX>) -> Self {
+        bf.0
+    }
+}
+
+impl<const MAX: usize> Serialize for BoundedBitField<MAX> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, const MAX: usize> Deserialize<'de> for BoundedBitField<MAX> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes: Vec<u8> = serde_bytes::deserialize(deserializer)?;
+        // Every run of 1s has at least one set bit, so decoding stops early on long inputs
+        let bf = BitField::from_bytes_with_limits(&bytes, MAX, MAX_ENCODED_SIZE)
+            .map_err(serde::de::Error::custom)?;
+        Self::new(bf).map_err(serde::de::Error::custom)
+    }
+}
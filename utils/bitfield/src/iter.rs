@@ -1,7 +1,9 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::{iter, ops::Range};
+use super::Result;
+use alloc::vec::Vec;
+use core::{iter, ops::Range};
 
 /// A trait for iterators over `Range<usize>`.
 ///
@@ -34,6 +36,27 @@ pub trait RangeIterator: Iterator<Item = Range<usize>> + Sized {
         }
     }
 
+    /// Returns a new `RangeIterator` over the bits that are in `self` or in `other`, but not in
+    /// both.
+    fn symmetric_difference<R: RangeIterator>(self, other: R) -> SymmetricDifference<Self, R> {
+        SymmetricDifference {
+            a: Peekable::new(self),
+            b: Peekable::new(other),
+            pending: None,
+        }
+    }
+
+    /// Returns a new `RangeIterator` over the bits in `self` that are not in `other`, with the
+    /// positions of the bits in `other` removed, shifting the bits after them down.
+    fn cut<R: RangeIterator>(self, other: R) -> Cut<Self, R> {
+        Cut {
+            a: Peekable::new(self),
+            b: Peekable::new(other),
+            offset: 0,
+            pending: None,
+        }
+    }
+
     /// Returns a new `RangeIterator` over the bits in `self` after skipping the first `n` bits.
     fn skip_bits(self, n: usize) -> Skip<Self> {
         Skip {
@@ -319,6 +342,169 @@ impl<A: RangeIterator, B: RangeIterator> Iterator for Difference<A, B> {
 
 impl<A: RangeIterator, B: RangeIterator> RangeIterator for Difference<A, B> {}
 
+/// A `RangeIterator` over the bits that are in exactly one of two other `RangeIterator`s.
+pub struct SymmetricDifference<A, B> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+    /// A range that was read ahead to check whether it touches the previous range.
+    pending: Option<Range<usize>>,
+}
+
+impl<A: RangeIterator, B: RangeIterator> SymmetricDifference<A, B> {
+    /// Returns the next range of bits that are in only one of `a` and `b`. Consecutive ranges
+    /// can touch, when a range of `a` ends where a range of `b` starts.
+    fn next_unmerged(&mut self) -> Option<Range<usize>> {
+        loop {
+            let (a, b) = match (self.a.peek(), self.b.peek()) {
+                (Some(a), Some(b)) => (a, b),
+                (Some(_), None) => return self.a.next(),
+                (None, _) => return self.b.next(),
+            };
+
+            // x is the range that starts first
+            let (x, y) = if a.start <= b.start { (a, b) } else { (b, a) };
+            let range = if x.end <= y.start {
+                // x: -xx----
+                // y: ----xx-
+                let range = x.clone();
+                x.start = x.end;
+                Some(range)
+            } else if x.start < y.start {
+                // x: -xxxx-
+                // y: ---xxx
+                let range = x.start..y.start;
+                x.start = y.start;
+                Some(range)
+            } else {
+                // x: -xxxx- or -xx---
+                // y: -xx---    -xxxx-
+                let end = core::cmp::min(x.end, y.end);
+                x.start = end;
+                y.start = end;
+                None
+            };
+
+            // drop the ranges that have been fully read
+            if self.a.peek().is_some_and(|a| a.is_empty()) {
+                self.a.next();
+            }
+            if self.b.peek().is_some_and(|b| b.is_empty()) {
+                self.b.next();
+            }
+
+            if range.is_some() {
+                return range;
+            }
+        }
+    }
+}
+
+impl<A: RangeIterator, B: RangeIterator> Iterator for SymmetricDifference<A, B> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut range = match self.pending.take() {
+            Some(range) => range,
+            None => self.next_unmerged()?,
+        };
+
+        // merge the following ranges that touch this one
+        while let Some(next) = self.next_unmerged() {
+            if next.start == range.end {
+                range.end = next.end;
+            } else {
+                self.pending = Some(next);
+                break;
+            }
+        }
+
+        Some(range)
+    }
+}
+
+impl<A: RangeIterator, B: RangeIterator> RangeIterator for SymmetricDifference<A, B> {}
+
+/// A `RangeIterator` over the bits of one `RangeIterator` with the positions of the bits of
+/// another `RangeIterator` cut out.
+pub struct Cut<A, B> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+    /// The number of bits of `b` before the current position of `a`.
+    offset: usize,
+    /// A range that was read ahead to check whether it touches the previous range.
+    pending: Option<Range<usize>>,
+}
+
+impl<A: RangeIterator, B: RangeIterator> Cut<A, B> {
+    /// Returns the next range of bits of `a` that aren't in `b`, shifted down by the bits of `b`
+    /// before it. Consecutive ranges can touch, as the bits between them may have been cut.
+    fn next_shifted(&mut self) -> Option<Range<usize>> {
+        loop {
+            let a = self.a.peek()?;
+            let b = match self.b.peek() {
+                Some(b) => b,
+                None => {
+                    let range = self.a.next()?;
+                    return Some(range.start - self.offset..range.end - self.offset);
+                }
+            };
+
+            if b.end <= a.start {
+                // b: -xx----
+                // a: ----xx-
+                self.offset += b.len();
+                self.b.next();
+            } else if a.end <= b.start {
+                // a: -xx----
+                // b: ----xx-
+                let range = self.a.next()?;
+                return Some(range.start - self.offset..range.end - self.offset);
+            } else if a.start < b.start {
+                // a: -xxxx-
+                // b: ---xx-
+                let range = a.start - self.offset..b.start - self.offset;
+                a.start = b.start;
+                return Some(range);
+            } else if a.end <= b.end {
+                // a: ---xx-
+                // b: -xxxx-
+                self.a.next();
+            } else {
+                // a: -xxxx-
+                // b: -xx---
+                a.start = b.end;
+                self.offset += b.len();
+                self.b.next();
+            }
+        }
+    }
+}
+
+impl<A: RangeIterator, B: RangeIterator> Iterator for Cut<A, B> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut range = match self.pending.take() {
+            Some(range) => range,
+            None => self.next_shifted()?,
+        };
+
+        // merge the following ranges that touch this one
+        while let Some(next) = self.next_shifted() {
+            if next.start == range.end {
+                range.end = next.end;
+            } else {
+                self.pending = Some(next);
+                break;
+            }
+        }
+
+        Some(range)
+    }
+}
+
+impl<A: RangeIterator, B: RangeIterator> RangeIterator for Cut<A, B> {}
+
 /// A `RangeIterator` that skips over `n` bits of antoher `RangeIterator`.
 pub struct Skip<I> {
     iter: I,
@@ -404,7 +590,7 @@ impl<I> RangeIterator for Ranges<I> where I: Iterator<Item = Range<usize>> {}
 
 /// Returns a `RangeIterator` which ranges contain the values from the provided iterator.
 /// The values need to be in ascending order — if not, the returned iterator may not satisfy
-/// all `RangeIterator` requirements.
+/// all `RangeIterator` requirements. Use `ranges_from_sorted_bits` for untrusted values.
 pub fn ranges_from_bits(bits: impl IntoIterator<Item = usize>) -> impl RangeIterator {
     let mut iter = bits.into_iter().peekable();
 
@@ -419,6 +605,26 @@ pub fn ranges_from_bits(bits: impl IntoIterator<Item = usize>) -> impl RangeIter
     }))
 }
 
+/// Returns a `RangeIterator` which ranges contain the values from the provided iterator, or
+/// an error if the values are not in strictly ascending order or contain `usize::MAX`, which
+/// can't be the start of a range.
+pub fn ranges_from_sorted_bits(
+    bits: impl IntoIterator<Item = usize>,
+) -> Result<impl RangeIterator> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for bit in bits {
+        let end = bit.checked_add(1).ok_or("bit is too large")?;
+        match ranges.last_mut() {
+            Some(range) if bit == range.end => range.end = end,
+            Some(range) if bit < range.end => {
+                return Err("bits are not in strictly ascending order")
+            }
+            _ => ranges.push(bit..end),
+        }
+    }
+    Ok(Ranges::new(ranges))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -435,6 +641,7 @@ mod tests {
             union: &'a [Range<usize>],
             intersection: &'a [Range<usize>],
             difference: &'a [Range<usize>],
+            symmetric_difference: &'a [Range<usize>],
         }
 
         for &Case {
@@ -443,6 +650,7 @@ mod tests {
             union,
             intersection,
             difference,
+            symmetric_difference,
         } in &[
             Case {
                 lhs: &[2..5],
@@ -450,6 +658,7 @@ mod tests {
                 union: &[2..5],
                 intersection: &[],
                 difference: &[2..5],
+                symmetric_difference: &[2..5],
             },
             Case {
                 lhs: &[0..3, 10..13],
@@ -457,6 +666,7 @@ mod tests {
                 union: &[0..3, 5..8, 10..13],
                 intersection: &[],
                 difference: &[0..3, 10..13],
+                symmetric_difference: &[0..3, 5..8, 10..13],
             },
             Case {
                 lhs: &[0..3, 8..11],
@@ -464,6 +674,7 @@ mod tests {
                 union: &[0..5, 8..11],
                 intersection: &[2..3],
                 difference: &[0..2, 8..11],
+                symmetric_difference: &[0..2, 3..5, 8..11],
             },
             Case {
                 lhs: &[0..3, 4..7, 8..11],
@@ -471,6 +682,7 @@ mod tests {
                 union: &[0..13],
                 intersection: &[2..3, 4..5, 6..7, 8..9, 10..11],
                 difference: &[0..2, 5..6, 9..10],
+                symmetric_difference: &[0..2, 3..4, 5..6, 7..8, 9..10, 11..13],
             },
             Case {
                 lhs: &[0..6],
@@ -478,6 +690,7 @@ mod tests {
                 union: &[0..6],
                 intersection: &[1..3],
                 difference: &[0..1, 3..6],
+                symmetric_difference: &[0..1, 3..6],
             },
             Case {
                 lhs: &[0..6],
@@ -485,6 +698,7 @@ mod tests {
                 union: &[0..7, 9..11],
                 intersection: &[1..3, 5..6],
                 difference: &[0..1, 3..5],
+                symmetric_difference: &[0..1, 3..5, 6..7, 9..11],
             },
             Case {
                 lhs: &[3..6],
@@ -492,6 +706,7 @@ mod tests {
                 union: &[0..2, 3..6, 8..10],
                 intersection: &[4..5],
                 difference: &[3..4, 5..6],
+                symmetric_difference: &[0..2, 3..4, 5..6, 8..10],
             },
             Case {
                 lhs: &[3..6, 8..10],
@@ -499,6 +714,7 @@ mod tests {
                 union: &[2..7, 8..11],
                 intersection: &[3..6, 8..10],
                 difference: &[],
+                symmetric_difference: &[2..3, 6..7, 10..11],
             },
             Case {
                 lhs: &[3..6, 8..10],
@@ -506,6 +722,15 @@ mod tests {
                 union: &[2..6, 8..10],
                 intersection: &[3..4],
                 difference: &[4..6, 8..10],
+                symmetric_difference: &[2..3, 4..6, 8..10],
+            },
+            Case {
+                lhs: &[0..2, 4..6],
+                rhs: &[2..4],
+                union: &[0..6],
+                intersection: &[],
+                difference: &[0..2, 4..6],
+                symmetric_difference: &[0..6],
             },
         ] {
             assert_eq!(ranges(lhs).merge(ranges(rhs)).collect::<Vec<_>>(), union);
@@ -524,6 +749,38 @@ mod tests {
                 ranges(lhs).difference(ranges(rhs)).collect::<Vec<_>>(),
                 difference
             );
+
+            assert_eq!(
+                ranges(lhs)
+                    .symmetric_difference(ranges(rhs))
+                    .collect::<Vec<_>>(),
+                symmetric_difference
+            );
+            assert_eq!(
+                ranges(rhs)
+                    .symmetric_difference(ranges(lhs))
+                    .collect::<Vec<_>>(),
+                symmetric_difference
+            );
+        }
+    }
+
+    #[test]
+    fn test_cut() {
+        for &(lhs, rhs, cut) in &[
+            (&[0..5][..], &[][..], &[0..5][..]),
+            (&[], &[0..5], &[]),
+            (&[0..10], &[3..5], &[0..8]),
+            (&[0..3, 6..9], &[3..6], &[0..6]),
+            (&[2..4, 10..12], &[0..1, 5..8], &[1..3, 6..8]),
+            // Bits 0, 1, 5 and 9 remain, and are shifted down to be contiguous
+            (&[0..3, 4..7, 8..11], &[2..5, 6..9, 10..13], &[0..4]),
+            // Case from the cut tests of go-bitfield
+            (&[5..9, 15..18], &[1..2, 5..9, 16..17], &[10..12]),
+            (&[5..6], &[0..5], &[0..1]),
+            (&[1..2, 3..4], &[2..3], &[1..3]),
+        ] {
+            assert_eq!(ranges(lhs).cut(ranges(rhs)).collect::<Vec<_>>(), cut);
         }
     }
 
@@ -610,4 +867,28 @@ mod tests {
             assert_eq!(ranges(input).take_bits(n).collect::<Vec<_>>(), take);
         }
     }
+
+    #[test]
+    fn test_ranges_from_sorted_bits() {
+        let bits = [1, 2, 3, 5, 8, 9];
+        assert_eq!(
+            ranges_from_sorted_bits(bits.iter().copied())
+                .unwrap()
+                .collect::<Vec<_>>(),
+            ranges_from_bits(bits.iter().copied()).collect::<Vec<_>>()
+        );
+        assert_eq!(ranges_from_sorted_bits(iter::empty()).unwrap().next(), None);
+
+        assert!(ranges_from_sorted_bits(vec![1, 2, 2, 3]).is_err());
+        assert!(ranges_from_sorted_bits(vec![1, 5, 3]).is_err());
+        assert!(ranges_from_sorted_bits(vec![4, 5, 6, 5]).is_err());
+        assert!(ranges_from_sorted_bits(vec![usize::MAX]).is_err());
+        assert!(ranges_from_sorted_bits(vec![usize::MAX - 1, usize::MAX]).is_err());
+        assert_eq!(
+            ranges_from_sorted_bits(vec![usize::MAX - 1])
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![usize::MAX - 1..usize::MAX]
+        );
+    }
 }
@@ -0,0 +1,60 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! JSON representation of a bit field compatible with Lotus, which is the array of the lengths
+//! of its alternating runs of 0s and 1s, starting with a run of 0s. Only the first run may be
+//! empty, so a bit field starting with a set bit starts with a 0.
+
+use super::{iter::Ranges, BitField};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Wrapper for serializing and deserializing a BitField from JSON.
+#[derive(Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct BitFieldJson(#[serde(with = "self")] pub BitField);
+
+/// Wrapper for serializing a BitField reference to JSON.
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct BitFieldJsonRef<'a>(#[serde(with = "self")] pub &'a BitField);
+
+pub fn serialize<S>(m: &BitField, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut runs = Vec::new();
+    let mut index = 0;
+    for range in m.ranges() {
+        runs.push((range.start - index) as u64);
+        runs.push(range.len() as u64);
+        index = range.end;
+    }
+    runs.serialize(serializer)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<BitField, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let runs: Vec<u64> = Deserialize::deserialize(deserializer)?;
+
+    let mut ranges = Vec::new();
+    let mut index: usize = 0;
+    for (i, &run) in runs.iter().enumerate() {
+        if run == 0 && i != 0 {
+            return Err(de::Error::custom("only the first run can be empty"));
+        }
+        let end = usize::try_from(run)
+            .ok()
+            .and_then(|len| index.checked_add(len))
+            .ok_or_else(|| de::Error::custom("bit field length overflow"))?;
+        // Runs of 1s are at odd positions
+        if i % 2 == 1 {
+            ranges.push(index..end);
+        }
+        index = end;
+    }
+    Ok(BitField::from_ranges(Ranges::new(ranges)))
+}
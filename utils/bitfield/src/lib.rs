@@ -1,17 +1,52 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-pub mod iter;
-mod rleplus;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-use ahash::AHashSet;
-use iter::{ranges_from_bits, RangeIterator};
-use std::{
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+mod bounded;
+pub mod iter;
+#[cfg(feature = "json")]
+pub mod json;
+mod rank;
+pub mod rleplus;
+mod unvalidated;
+
+use alloc::vec::Vec;
+pub use bounded::BoundedBitField;
+use core::{
     iter::FromIterator,
-    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Range, Sub, SubAssign},
+    mem::size_of,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Range, Sub, SubAssign},
 };
-
-type Result<T> = std::result::Result<T, &'static str>;
+use iter::{ranges_from_bits, ranges_from_sorted_bits, RangeIterator};
+pub use rank::RankSelect;
+pub use unvalidated::UnvalidatedBitField;
+
+type Result<T> = core::result::Result<T, &'static str>;
+
+/// Set of buffered bits, hashed when std is available.
+#[cfg(feature = "std")]
+type BitSet = ahash::AHashSet<usize>;
+#[cfg(not(feature = "std"))]
+type BitSet = alloc::collections::BTreeSet<usize>;
+
+/// Number of buffered bits above which `set` and `unset` fold the buffered bits into the
+/// ranges, unless the bit field has more ranges than that.
+const MAX_BUFFERED_BITS: usize = 1024;
+
+/// Approximate number of heap bytes used by a set of buffered bits.
+#[cfg(feature = "std")]
+fn bit_set_bytes(set: &BitSet) -> usize {
+    set.capacity() * size_of::<usize>()
+}
+#[cfg(not(feature = "std"))]
+fn bit_set_bytes(set: &BitSet) -> usize {
+    set.len() * size_of::<usize>()
+}
 
 /// An bit field with buffered insertion/removal that serializes to/from RLE+. Similar to
 /// `HashSet<usize>`, but more memory-efficient when long runs of 1s and 0s are present.
@@ -20,9 +55,9 @@ pub struct BitField {
     /// The underlying ranges of 1s.
     ranges: Vec<Range<usize>>,
     /// Bits set to 1. Never overlaps with `unset`.
-    set: AHashSet<usize>,
+    set: BitSet,
     /// Bits set to 0. Never overlaps with `set`.
-    unset: AHashSet<usize>,
+    unset: BitSet,
 }
 
 impl PartialEq for BitField {
@@ -35,6 +70,7 @@ impl FromIterator<usize> for BitField {
     fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
         let mut vec: Vec<_> = iter.into_iter().collect();
         vec.sort_unstable();
+        vec.dedup();
         Self::from_ranges(ranges_from_bits(vec))
     }
 }
@@ -64,16 +100,24 @@ impl BitField {
         }
     }
 
+    /// Creates a new bit field from bits in strictly ascending order. Returns an error if the
+    /// bits are out of order, contain duplicates or contain `usize::MAX`.
+    pub fn try_from_bits(bits: impl IntoIterator<Item = usize>) -> Result<Self> {
+        Ok(Self::from_ranges(ranges_from_sorted_bits(bits)?))
+    }
+
     /// Adds the bit at a given index to the bit field.
     pub fn set(&mut self, bit: usize) {
         self.unset.remove(&bit);
         self.set.insert(bit);
+        self.normalize_if_full();
     }
 
     /// Removes the bit at a given index from the bit field.
     pub fn unset(&mut self, bit: usize) {
         self.set.remove(&bit);
         self.unset.insert(bit);
+        self.normalize_if_full();
     }
 
     /// Returns `true` if the bit field contains the bit at a given index.
@@ -85,7 +129,7 @@ impl BitField {
         } else {
             // since `self.ranges` is ordered, we can use a binary search to find out if
             // any range in `self.ranges` contains `index`
-            use std::cmp::Ordering;
+            use core::cmp::Ordering;
             self.ranges
                 .binary_search_by(|range| {
                     if index < range.start {
@@ -124,6 +168,54 @@ impl BitField {
             .find(|i| !self.unset.contains(i))
     }
 
+    /// Returns the index of the highest bit present in the bit field.
+    pub fn last(&self) -> Option<usize> {
+        self.ranges().last().map(|range| range.end - 1)
+    }
+
+    /// Returns the number of set bits within the given range of indices.
+    pub fn count_in_range(&self, range: Range<usize>) -> usize {
+        if range.is_empty() {
+            return 0;
+        }
+        self.ranges()
+            .intersection(iter::Ranges::new(Some(range)))
+            .map(|range| range.len())
+            .sum()
+    }
+
+    /// Returns `true` if all set bits are within the given range of indices.
+    pub fn is_subset_of_range(&self, range: Range<usize>) -> bool {
+        match (self.first(), self.last()) {
+            (Some(first), Some(last)) => range.start <= first && last < range.end,
+            _ => true,
+        }
+    }
+
+    /// Returns the number of set bits below the given index.
+    ///
+    /// Each call traverses the bit field, use `rank_select` to answer multiple queries.
+    pub fn rank(&self, bit: usize) -> usize {
+        self.ranges()
+            .take_while(|range| range.start < bit)
+            .map(|range| range.end.min(bit) - range.start)
+            .sum()
+    }
+
+    /// Returns the index of the `n`th set bit (zero-based), or `None` if the bit field
+    /// contains no more than `n` set bits.
+    ///
+    /// Each call traverses the bit field, use `rank_select` to answer multiple queries.
+    pub fn select(&self, n: usize) -> Option<usize> {
+        self.ranges().skip_bits(n).next().map(|range| range.start)
+    }
+
+    /// Returns an index over the set bits of the bit field which answers `rank` and
+    /// `select` queries in `O(log n)` of the number of ranges.
+    pub fn rank_select(&self) -> RankSelect {
+        RankSelect::new(self.ranges())
+    }
+
     /// Returns an iterator over the indices of the bit field's set bits.
     pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
         // this code results in the same values as `self.ranges().flatten()`, but there's
@@ -166,7 +258,7 @@ impl BitField {
     /// Returns an iterator over the ranges of set bits that make up the bit field. The
     /// ranges are in ascending order, are non-empty, and don't overlap.
     pub fn ranges(&self) -> impl RangeIterator + '_ {
-        let ranges = |set: &AHashSet<usize>| {
+        let ranges = |set: &BitSet| {
             let mut vec: Vec<_> = set.iter().copied().collect();
             vec.sort_unstable();
             ranges_from_bits(vec)
@@ -177,6 +269,47 @@ impl BitField {
             .difference(ranges(&self.unset))
     }
 
+    /// Returns the approximate number of heap bytes used by the bit field, including the
+    /// buffered bits which haven't been folded into ranges.
+    pub fn allocated_bytes(&self) -> usize {
+        self.ranges.capacity() * size_of::<Range<usize>>()
+            + bit_set_bytes(&self.set)
+            + bit_set_bytes(&self.unset)
+    }
+
+    /// Folds the buffered set and unset bits into the ranges, so that they no longer need to
+    /// be sorted by every query. The bits of the bit field are unchanged.
+    ///
+    /// This is done by `set` and `unset` once enough bits are buffered, so it's only needed
+    /// before many queries of a bit field with buffered bits.
+    pub fn normalize(&mut self) {
+        if self.set.is_empty() && self.unset.is_empty() {
+            return;
+        }
+        let ranges: Vec<_> = self.ranges().collect();
+        self.ranges = ranges;
+        self.set.clear();
+        self.unset.clear();
+    }
+
+    /// Normalizes the bit field if the buffered bits outnumber the ranges, which bounds the
+    /// amortized cost of folding them per buffered bit.
+    fn normalize_if_full(&mut self) {
+        let buffered = self.set.len() + self.unset.len();
+        if buffered > MAX_BUFFERED_BITS.max(self.ranges.len()) {
+            self.normalize();
+        }
+    }
+
+    /// Folds the buffered set and unset bits into the ranges and releases unused memory.
+    /// The bits of the bit field are unchanged.
+    pub fn compact(&mut self) {
+        self.normalize();
+        self.ranges.shrink_to_fit();
+        self.set = BitSet::default();
+        self.unset = BitSet::default();
+    }
+
     /// Returns `true` if the bit field is empty.
     pub fn is_empty(&self) -> bool {
         self.set.is_empty()
@@ -190,10 +323,24 @@ impl BitField {
     /// and number of bits to include in the slice. Returns an error if the
     /// bit field contains fewer than `start + len` set bits.
     pub fn slice(&self, start: usize, len: usize) -> Result<Self> {
-        let slice = BitField::from_ranges(self.ranges().skip_bits(start).take_bits(len));
+        Ok(BitField::from_ranges(self.try_slice_iter(start, len)?))
+    }
 
-        if slice.len() == len {
-            Ok(slice)
+    /// Returns an iterator over the ranges of a slice of the bit field, like `slice`, without
+    /// collecting them into a new bit field. Returns an error if the bit field contains fewer
+    /// than `start + len` set bits, which is checked before returning the iterator by counting
+    /// only up to the end of the slice.
+    pub fn try_slice_iter(&self, start: usize, len: usize) -> Result<impl RangeIterator + '_> {
+        let end = start.checked_add(len).ok_or("Not enough bits")?;
+        let mut count = 0;
+        let enough = end == 0
+            || self.ranges().any(|range| {
+                count += range.len();
+                count >= end
+            });
+
+        if enough {
+            Ok(self.ranges().skip_bits(start).take_bits(len))
         } else {
             Err("Not enough bits")
         }
@@ -225,6 +372,32 @@ impl BitField {
         self.ranges().difference(other.ranges())
     }
 
+    /// Returns a new `RangeIterator` over the bits that are in `self` or in `other`, but not in
+    /// both.
+    ///
+    /// The `^` operator is the eager version of this.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> impl RangeIterator + 'a {
+        self.ranges().symmetric_difference(other.ranges())
+    }
+
+    /// Returns a new bit field with the positions of the bits in `other` removed, and the
+    /// remaining bits of `self` shifted down to fill the gaps.
+    pub fn cut(&self, other: &Self) -> Self {
+        Self::from_ranges(self.ranges().cut(other.ranges()))
+    }
+
+    /// Returns the number of bits that are in both `self` and `other`, without building a
+    /// new bit field.
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        self.intersection(other).map(|range| range.len()).sum()
+    }
+
+    /// Returns the number of bits that are in `self` but not in `other`, without building a
+    /// new bit field.
+    pub fn difference_len(&self, other: &Self) -> usize {
+        self.difference(other).map(|range| range.len()).sum()
+    }
+
     /// Returns the union of the given bit fields as a new bit field.
     pub fn union<'a>(bitfields: impl IntoIterator<Item = &'a Self>) -> Self {
         bitfields.into_iter().fold(Self::new(), |a, b| &a | b)
@@ -289,6 +462,22 @@ impl SubAssign<&BitField> for BitField {
     }
 }
 
+impl BitXor<&BitField> for &BitField {
+    type Output = BitField;
+
+    #[inline]
+    fn bitxor(self, rhs: &BitField) -> Self::Output {
+        BitField::from_ranges(self.symmetric_difference(rhs))
+    }
+}
+
+impl BitXorAssign<&BitField> for BitField {
+    #[inline]
+    fn bitxor_assign(&mut self, rhs: &BitField) {
+        *self = &*self ^ rhs;
+    }
+}
+
 /// Constructs a `BitField` from a given list of 1s and 0s.
 ///
 /// # Examples
@@ -306,10 +495,10 @@ impl SubAssign<&BitField> for BitField {
 #[macro_export]
 macro_rules! bitfield {
     (@iter) => {
-        std::iter::empty::<bool>()
+        core::iter::empty::<bool>()
     };
     (@iter $head:literal $(, $tail:literal)*) => {
-        std::iter::once($head != 0_u32).chain(bitfield!(@iter $($tail),*))
+        core::iter::once($head != 0_u32).chain(bitfield!(@iter $($tail),*))
     };
     ($($val:literal),* $(,)?) => {
         bitfield!(@iter $($val),*).collect::<$crate::BitField>()
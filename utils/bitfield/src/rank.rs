@@ -0,0 +1,94 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::iter::RangeIterator;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::Range;
+
+/// Index over the ranges of a bit field which answers rank and select queries in
+/// logarithmic time, by keeping the number of set bits preceding each range.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RankSelect {
+    /// The ranges of 1s, in ascending order.
+    ranges: Vec<Range<usize>>,
+    /// Number of set bits before the range at the same position in `ranges`.
+    prefix: Vec<usize>,
+    /// Total number of set bits.
+    len: usize,
+}
+
+impl RankSelect {
+    /// Builds the index from a `RangeIterator`.
+    pub fn new(iter: impl RangeIterator) -> Self {
+        let mut len = 0;
+        let mut ranges = Vec::new();
+        let mut prefix = Vec::new();
+        for range in iter {
+            prefix.push(len);
+            len += range.len();
+            ranges.push(range);
+        }
+        Self {
+            ranges,
+            prefix,
+            len,
+        }
+    }
+
+    /// Returns the number of set bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no set bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of set bits strictly below the given index.
+    pub fn rank(&self, bit: usize) -> usize {
+        // number of ranges starting before `bit`, the last of which may contain it
+        let idx = self
+            .ranges
+            .binary_search_by(|range| {
+                if range.start < bit {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_err();
+
+        match idx.checked_sub(1) {
+            Some(i) => {
+                let range = &self.ranges[i];
+                self.prefix[i] + range.end.min(bit) - range.start
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the index of the `n`th set bit (zero-based), or `None` if there are
+    /// no more than `n` set bits.
+    pub fn select(&self, n: usize) -> Option<usize> {
+        if n >= self.len {
+            return None;
+        }
+        // number of ranges with at most `n` set bits before them, the last of which
+        // contains the bit
+        let idx = self
+            .prefix
+            .binary_search_by(|&count| {
+                if count <= n {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_err();
+
+        let i = idx - 1;
+        Some(self.ranges[i].start + n - self.prefix[i])
+    }
+}
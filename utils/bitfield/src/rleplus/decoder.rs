@@ -0,0 +1,162 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::{BitReader, Result};
+use core::ops::Range;
+
+/// A `Decoder` lazily decodes the ranges of 1s of an RLE+ encoded bit field, reading the runs
+/// from the bytes only as far as needed. This allows querying an encoded bit field without
+/// allocating all of its ranges.
+pub struct Decoder<'a> {
+    reader: BitReader<'a>,
+    /// Whether the next run is a run of 1s.
+    next_value: bool,
+    /// The index of the first bit of the next run.
+    index: usize,
+    /// Set once the last run has been read, or decoding failed.
+    done: bool,
+}
+
+impl<'a> Decoder<'a> {
+    /// Creates a new `Decoder`, reading the header of the encoded bytes.
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        let mut reader = BitReader::new(bytes);
+
+        let version = reader.read(2);
+        if version != 0 {
+            return Err("incorrect version");
+        }
+
+        let next_value = reader.read(1) == 1;
+        Ok(Self {
+            reader,
+            next_value,
+            index: 0,
+            done: false,
+        })
+    }
+
+    /// Returns `true` if the bit at the given index is set.
+    pub fn get(self, index: usize) -> Result<bool> {
+        for range in self {
+            let range = range?;
+            if range.start > index {
+                break;
+            }
+            if range.end > index {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the index of the lowest set bit.
+    pub fn first(mut self) -> Result<Option<usize>> {
+        Ok(self.next().transpose()?.map(|range| range.start))
+    }
+
+    /// Returns `true` if no bits are set.
+    pub fn is_empty(self) -> Result<bool> {
+        Ok(self.first()?.is_none())
+    }
+
+    /// Returns the number of set bits.
+    pub fn len(self) -> Result<usize> {
+        let mut len = 0;
+        for range in self {
+            len += range?.len();
+        }
+        Ok(len)
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = Result<Range<usize>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let len = match self.reader.read_len() {
+                Ok(Some(len)) => len,
+                Ok(None) => break,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            let start = self.index;
+            self.index = match start.checked_add(len) {
+                Some(end) => end,
+                None => {
+                    self.done = true;
+                    return Some(Err("bit field length overflow"));
+                }
+            };
+
+            let value = self.next_value;
+            self.next_value = !value;
+            if value {
+                return Some(Ok(start..self.index));
+            }
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::BitWriter, Decoder};
+    use crate::{bitfield, BitField};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn decode_ranges() {
+        let bf: BitField = vec![0usize, 1, 2, 7, 20, 21, 100].into_iter().collect();
+        let bytes = bf.to_bytes().unwrap();
+
+        let ranges: Vec<_> = Decoder::new(&bytes)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ranges, vec![0..3, 7..8, 20..22, 100..101]);
+
+        assert_eq!(Decoder::new(&bytes).unwrap().len(), Ok(7));
+        assert_eq!(Decoder::new(&bytes).unwrap().first(), Ok(Some(0)));
+        assert_eq!(Decoder::new(&bytes).unwrap().is_empty(), Ok(false));
+        for i in 0..110 {
+            assert_eq!(Decoder::new(&bytes).unwrap().get(i), Ok(bf.get(i)));
+        }
+
+        let empty = bitfield![].to_bytes().unwrap();
+        assert_eq!(Decoder::new(&empty).unwrap().len(), Ok(0));
+        assert_eq!(Decoder::new(&empty).unwrap().first(), Ok(None));
+        assert_eq!(Decoder::new(&empty).unwrap().is_empty(), Ok(true));
+    }
+
+    #[test]
+    fn reads_only_needed_runs() {
+        // The first range can be read even though the run after it is an invalid varint
+        let mut writer = BitWriter::new();
+        writer.write(0, 2); // version 00
+        writer.write(0, 1); // the first bit is a 0
+        writer.write_len(2);
+        writer.write_len(2);
+        writer.write(0, 2); // block long
+        for _ in 0..10 {
+            writer.write(0xff, 8);
+        }
+        let bytes = writer.finish();
+
+        assert_eq!(Decoder::new(&bytes).unwrap().first(), Ok(Some(2)));
+        assert_eq!(Decoder::new(&bytes).unwrap().get(3), Ok(true));
+        assert!(Decoder::new(&bytes).unwrap().len().is_err());
+        assert!(BitField::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn invalid_version() {
+        assert!(Decoder::new(&[0b0000_0001]).is_err());
+    }
+}
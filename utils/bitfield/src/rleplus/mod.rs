@@ -61,59 +61,75 @@
 //! > the same encoding, given the same input.
 //!
 
+mod decoder;
 mod reader;
 mod writer;
 
+pub use decoder::Decoder;
 pub use reader::BitReader;
 pub use writer::BitWriter;
 
 use super::{BitField, Result};
+use alloc::vec::Vec;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+/// Maximum length of the RLE+ encoding of a bit field accepted when deserializing, which
+/// also bounds the number of runs allocated while decoding.
+pub const MAX_ENCODED_SIZE: usize = 32 << 10;
+
+/// Maximum length of a run, which is the largest length that fits in the 9 byte varint of a
+/// long block. Longer runs can't be decoded, so encoding a bit field with one fails.
+pub const MAX_RUN_LEN: u64 = (1 << 63) - 1;
+
 impl Serialize for BitField {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let bytes = self.to_bytes();
+        let bytes = self.to_bytes().map_err(serde::ser::Error::custom)?;
         serde_bytes::serialize(&bytes, serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for BitField {
-    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         let bytes: Vec<u8> = serde_bytes::deserialize(deserializer)?;
-        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+        Self::from_bytes_with_limits(&bytes, usize::MAX, MAX_ENCODED_SIZE)
+            .map_err(serde::de::Error::custom)
     }
 }
 
 impl BitField {
     /// Decodes RLE+ encoded bytes into a bit field.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        let mut reader = BitReader::new(bytes);
+        let ranges = Decoder::new(bytes)?.collect::<Result<_>>()?;
+
+        Ok(Self {
+            ranges,
+            ..Default::default()
+        })
+    }
 
-        let version = reader.read(2);
-        if version != 0 {
-            return Err("incorrect version");
+    /// Decodes RLE+ encoded bytes into a bit field, failing before decoding if the bytes are
+    /// longer than `max_encoded_len`, or as soon as there are more than `max_runs` runs of 1s.
+    pub fn from_bytes_with_limits(
+        bytes: &[u8],
+        max_runs: usize,
+        max_encoded_len: usize,
+    ) -> Result<Self> {
+        if bytes.len() > max_encoded_len {
+            return Err("encoded bit field exceeds the maximum length");
         }
 
-        let mut next_value = reader.read(1) == 1;
         let mut ranges = Vec::new();
-        let mut index = 0;
-
-        while let Some(len) = reader.read_len()? {
-            let start = index;
-            index += len;
-            let end = index;
-
-            if next_value {
-                ranges.push(start..end);
+        for range in Decoder::new(bytes)? {
+            if ranges.len() == max_runs {
+                return Err("bit field exceeds the maximum number of runs");
             }
-
-            next_value = !next_value;
+            ranges.push(range?);
         }
 
         Ok(Self {
@@ -122,46 +138,115 @@ impl BitField {
         })
     }
 
-    /// Turns a bit field into its RLE+ encoded form.
-    pub fn to_bytes(&self) -> Vec<u8> {
+    /// Turns a bit field into its RLE+ encoded form. Fails if a run of 0s or 1s of the bit
+    /// field is longer than `MAX_RUN_LEN`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.encode_into(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Appends the RLE+ encoded form of the bit field to the buffer, reusing its allocation.
+    /// Fails like `to_bytes`, in which case the buffer is left unchanged.
+    pub fn encode_into(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let start = buf.len();
+        let mut writer = BitWriter::with_buffer(core::mem::take(buf));
+        let res = self.write_runs(&mut writer);
+        *buf = writer.finish();
+        if res.is_err() {
+            buf.truncate(start);
+        }
+        res
+    }
+
+    /// Returns the number of bytes of the RLE+ encoded form of the bit field, without
+    /// encoding it. Fails like `to_bytes`.
+    pub fn encoded_len(&self) -> Result<usize> {
+        let mut iter = self.ranges();
+
+        let first_range = match iter.next() {
+            Some(range) => range,
+            None => return Ok(0),
+        };
+
+        // version and first bit
+        let mut bits: usize = 3;
+        if first_range.start != 0 {
+            bits += run_bits(first_range.start)?;
+        }
+        bits += run_bits(first_range.len())?;
+        let mut index = first_range.end;
+
+        for range in iter {
+            bits += run_bits(range.start - index)?;
+            bits += run_bits(range.len())?;
+            index = range.end;
+        }
+
+        Ok(bits.div_ceil(8))
+    }
+
+    /// Writes the encoded runs of the bit field, which is nothing for an empty bit field.
+    fn write_runs(&self, writer: &mut BitWriter) -> Result<()> {
         let mut iter = self.ranges();
 
         let first_range = match iter.next() {
             Some(range) => range,
-            None => return Default::default(),
+            None => return Ok(()),
         };
 
-        let mut writer = BitWriter::new();
         writer.write(0, 2); // version 00
 
         if first_range.start == 0 {
             writer.write(1, 1); // the first bit is a 1
         } else {
             writer.write(0, 1); // the first bit is a 0
-            writer.write_len(first_range.start); // the number of leading 0s
+            write_run(writer, first_range.start)?; // the number of leading 0s
         }
 
-        writer.write_len(first_range.len());
+        write_run(writer, first_range.len())?;
         let mut index = first_range.end;
 
         // for each range of 1s we first encode the number of 0s that came prior
         // before encoding the number of 1s
         for range in iter {
-            writer.write_len(range.start - index); // zeros
-            writer.write_len(range.len()); // ones
+            write_run(writer, range.start - index)?; // zeros
+            write_run(writer, range.len())?; // ones
             index = range.end;
         }
 
-        writer.finish()
+        Ok(())
+    }
+}
+
+/// Checks that the length of a run is at most `MAX_RUN_LEN`.
+fn check_run(len: usize) -> Result<()> {
+    if len as u64 > MAX_RUN_LEN {
+        return Err("run exceeds the maximum length");
     }
+    Ok(())
+}
+
+/// Returns the number of bits of the encoded length of a run.
+fn run_bits(len: usize) -> Result<usize> {
+    check_run(len)?;
+    Ok(BitWriter::len_bits(len))
+}
+
+/// Writes the length of a run, failing if it's longer than `MAX_RUN_LEN`.
+fn write_run(writer: &mut BitWriter, len: usize) -> Result<()> {
+    check_run(len)?;
+    writer.write_len(len);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        super::{bitfield, ranges_from_bits},
-        BitField, BitWriter,
+        super::{bitfield, iter::Ranges, ranges_from_bits},
+        BitField, BitWriter, MAX_RUN_LEN,
     };
+    use core::convert::TryFrom;
 
     use rand::{Rng, SeedableRng};
     use rand_xorshift::XorShiftRng;
@@ -238,4 +323,42 @@ mod tests {
             assert_eq!(bf.ranges().collect::<Vec<_>>(), ranges);
         }
     }
+
+    #[test]
+    fn encode_into_buffer() {
+        let mut rng = XorShiftRng::seed_from_u64(2);
+        let mut buf = Vec::new();
+
+        for _i in 0..1000 {
+            // Runs of up to 2^16 bits cover every block type and varints of up to 3 bytes
+            let mut ranges = Vec::new();
+            let mut index = 0;
+            for _ in 0..rng.gen_range(0, 10) {
+                let shift = rng.gen_range(0, 16);
+                index += rng.gen_range(0, 1 << shift);
+                let shift = rng.gen_range(0, 16);
+                let len = rng.gen_range(1, 2 << shift);
+                ranges.push(index..index + len);
+                index += len + 1;
+            }
+            let bf = BitField::from_ranges(Ranges::new(ranges));
+
+            buf.clear();
+            bf.encode_into(&mut buf).unwrap();
+            assert_eq!(buf, bf.to_bytes().unwrap());
+            assert_eq!(bf.encoded_len().unwrap(), buf.len());
+            assert_eq!(BitField::from_bytes(&buf).unwrap(), bf);
+        }
+
+        // Encoding appends to the buffer, and leaves it unchanged on failure
+        let mut buf = vec![7];
+        bitfield![1].encode_into(&mut buf).unwrap();
+        assert_eq!(buf, [7, 0b0000_1100]);
+        if let Ok(max) = usize::try_from(MAX_RUN_LEN) {
+            let bf = BitField::from_ranges(ranges_from_bits(vec![max + 1]));
+            assert!(bf.encoded_len().is_err());
+            assert!(bf.encode_into(&mut buf).is_err());
+            assert_eq!(buf, [7, 0b0000_1100]);
+        }
+    }
 }
@@ -89,6 +89,10 @@ impl<'a> BitReader<'a> {
             // if the most significant bit is a 0, we've
             // reached the end of the varint
             if byte & 0x80 == 0 {
+                // a trailing 0 byte could have been left out
+                if byte == 0 && i > 0 {
+                    return Err("Varint not minimally encoded");
+                }
                 return Ok(len);
             }
         }
@@ -173,7 +177,7 @@ mod tests {
         let mut rng = XorShiftRng::seed_from_u64(5);
 
         for _ in 0..100 {
-            let lengths: Vec<_> = std::iter::repeat_with(|| rng.gen_range(1, 200))
+            let lengths: Vec<_> = core::iter::repeat_with(|| rng.gen_range(1, 200))
                 .take(100)
                 .collect();
 
@@ -1,6 +1,8 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use alloc::vec::Vec;
+
 #[derive(Default, Clone, Debug)]
 /// A `BitWriter` allows for efficiently writing bits to a byte buffer, up to a byte at a time.
 pub struct BitWriter {
@@ -18,6 +20,14 @@ impl BitWriter {
         Default::default()
     }
 
+    /// Creates a new `BitWriter` which appends to the given buffer, reusing its allocation.
+    pub fn with_buffer(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            ..Default::default()
+        }
+    }
+
     /// Writes a given number of bits from `byte` to the buffer.
     pub fn write(&mut self, byte: u8, num_bits: u32) {
         debug_assert!(num_bits <= 8);
@@ -48,12 +58,38 @@ impl BitWriter {
         } else {
             // Block Long (prefix 00)
             self.write(0, 2);
+            self.write_varint(len);
+        }
+    }
+
+    /// Returns the number of bits `write_len` writes for a given length.
+    pub fn len_bits(len: usize) -> usize {
+        debug_assert!(len > 0);
 
-            let mut buffer = unsigned_varint::encode::usize_buffer();
-            for &byte in unsigned_varint::encode::usize(len, &mut buffer) {
-                self.write(byte, 8);
+        if len == 1 {
+            1
+        } else if len < 16 {
+            2 + 4
+        } else {
+            // 7 bits of the length per varint byte
+            let mut varint_bytes = 1;
+            let mut rest = len >> 7;
+            while rest > 0 {
+                varint_bytes += 1;
+                rest >>= 7;
             }
+            2 + 8 * varint_bytes
+        }
+    }
+
+    /// Writes a length to the buffer as an unsigned varint.
+    fn write_varint(&mut self, mut len: usize) {
+        while len >= 0x80 {
+            // the lower 7 bits, with the most significant bit set as more bytes follow
+            self.write(len as u8 | 0x80, 8);
+            len >>= 7;
         }
+        self.write(len as u8, 8);
     }
 
     /// Writes any remaining bits to the buffer and returns it.
@@ -0,0 +1,71 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::{rleplus::MAX_ENCODED_SIZE, BitField, Result};
+use alloc::vec::Vec;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A bit field which is decoded from RLE+ only when it is first used. Deserializing keeps the
+/// encoded bytes, so that parameters holding a bit field can be decoded before the caller of a
+/// method is validated, without paying for decoding the bit field on invalid calls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnvalidatedBitField {
+    Validated(BitField),
+    Unvalidated(Vec<u8>),
+}
+
+impl UnvalidatedBitField {
+    /// Decodes the bit field if it isn't decoded yet, and returns a mutable reference to it.
+    /// Fails if the bytes aren't a valid RLE+ encoding.
+    pub fn validate_mut(&mut self) -> Result<&mut BitField> {
+        if let Self::Unvalidated(bytes) = self {
+            *self = Self::Validated(BitField::from_bytes(bytes)?);
+        }
+
+        match self {
+            Self::Validated(bf) => Ok(bf),
+            Self::Unvalidated(_) => unreachable!("bit field was just validated"),
+        }
+    }
+
+    /// Decodes the bit field if it isn't decoded yet, and returns it.
+    pub fn validate(self) -> Result<BitField> {
+        match self {
+            Self::Validated(bf) => Ok(bf),
+            Self::Unvalidated(bytes) => BitField::from_bytes(&bytes),
+        }
+    }
+}
+
+impl From<BitField> for UnvalidatedBitField {
+    fn from(bf: BitField) -> Self {
+        Self::Validated(bf)
+    }
+}
+
+impl Serialize for UnvalidatedBitField {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Validated(bf) => bf.serialize(serializer),
+            Self::Unvalidated(bytes) => serde_bytes::serialize(bytes, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for UnvalidatedBitField {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes: Vec<u8> = serde_bytes::deserialize(deserializer)?;
+        if bytes.len() > MAX_ENCODED_SIZE {
+            return Err(serde::de::Error::custom(
+                "encoded bit field exceeds the maximum length",
+            ));
+        }
+        Ok(Self::Unvalidated(bytes))
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+#![cfg(feature = "arbitrary")]
+
+use bitfield::{arbitrary::alternating, BitField};
+use proptest::prelude::*;
+use quickcheck::quickcheck;
+
+quickcheck! {
+    fn rleplus_round_trip(bf: BitField) -> bool {
+        BitField::from_bytes(&bf.to_bytes().unwrap()).unwrap() == bf
+    }
+
+    fn union_contains_both(a: BitField, b: BitField) -> bool {
+        let union = &a | &b;
+        union.contains_all(&a) && union.contains_all(&b) && union.len() <= a.len() + b.len()
+    }
+}
+
+proptest! {
+    #[test]
+    fn rleplus_serde_round_trip(bf in any::<BitField>()) {
+        let cbor = encoding::to_vec(&bf).unwrap();
+        prop_assert_eq!(encoding::from_slice::<BitField>(&cbor).unwrap(), bf);
+    }
+
+    #[test]
+    fn alternating_ranges(bf in alternating(1 << 10)) {
+        prop_assert!(bf.ranges().all(|range| range.len() == 1));
+        prop_assert_eq!(bf.ranges().count(), bf.len());
+    }
+
+    #[test]
+    fn difference_and_intersection_partition(a in any::<BitField>(), b in any::<BitField>()) {
+        let difference = &a - &b;
+        let intersection = &a & &b;
+        prop_assert!(!difference.contains_any(&b));
+        prop_assert_eq!(difference.len() + intersection.len(), a.len());
+    }
+}
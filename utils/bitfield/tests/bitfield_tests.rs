@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use ahash::AHashSet;
-use bitfield::{bitfield, BitField};
+use bitfield::{
+    bitfield, rleplus::MAX_ENCODED_SIZE, BitField, BoundedBitField, UnvalidatedBitField,
+};
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use std::iter::FromIterator;
@@ -50,6 +52,22 @@ fn bitfield_slice_small() {
     }
 }
 
+#[test]
+fn bitfield_try_slice_iter() {
+    let bf: BitField = bitfield![0, 1, 0, 0, 1, 0, 0, 1, 0, 1, 1, 1, 0, 0];
+
+    let ranges: Vec<_> = bf.try_slice_iter(1, 4).unwrap().collect();
+    assert_eq!(ranges, vec![4..5, 7..8, 9..11]);
+    assert_eq!(bf.try_slice_iter(6, 0).unwrap().count(), 0);
+    assert_eq!(bf.try_slice_iter(2, 4).unwrap().count(), 2);
+
+    // Fails before iterating when the slice ends past the last set bit
+    assert!(bf.try_slice_iter(2, 5).is_err());
+    assert!(bf.try_slice_iter(7, 0).is_err());
+    assert!(bf.try_slice_iter(1, usize::MAX).is_err());
+    assert!(BitField::new().try_slice_iter(0, 0).is_ok());
+}
+
 fn set_up_test_bitfields() -> (Vec<usize>, Vec<usize>, BitField, BitField) {
     let a = random_indices(100, 1);
     let b = random_indices(100, 2);
@@ -80,6 +98,7 @@ fn bitfield_intersection() {
     let expected: AHashSet<_> = hs_a.intersection(&hs_b).copied().collect();
 
     let merged = &bf_a & &bf_b;
+    assert_eq!(bf_a.intersection_len(&bf_b), expected.len());
     assert_eq!(expected, merged.iter().collect());
 }
 
@@ -93,9 +112,72 @@ fn bitfield_difference() {
     }
 
     let merged = &bf_a - &bf_b;
+    assert_eq!(bf_a.difference_len(&bf_b), expected.len());
     assert_eq!(expected, merged.iter().collect());
 }
 
+#[test]
+fn bitfield_symmetric_difference() {
+    let (a, b, bf_a, bf_b) = set_up_test_bitfields();
+
+    let hs_a: AHashSet<_> = a.into_iter().collect();
+    let hs_b: AHashSet<_> = b.into_iter().collect();
+    let expected: AHashSet<_> = hs_a.symmetric_difference(&hs_b).copied().collect();
+
+    let xor = &bf_a ^ &bf_b;
+    assert_eq!(expected, xor.iter().collect());
+    assert_eq!(xor, &(&bf_a - &bf_b) | &(&bf_b - &bf_a));
+
+    let mut bf = bf_a.clone();
+    bf ^= &bf_b;
+    assert_eq!(bf, xor);
+    bf ^= &bf_b;
+    assert_eq!(bf, bf_a);
+}
+
+#[test]
+fn bitfield_cut() {
+    // Same semantics as `Cut` of go-bitfield: each bit of `a` that isn't in `b` is moved down
+    // by the number of bits of `b` below it
+    for seed in 0..50 {
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        let a: Vec<usize> = (0..200).filter(|_| rng.gen_bool(0.5)).collect();
+        let b: Vec<usize> = (0..200).filter(|_| rng.gen_bool(0.3)).collect();
+        let bf_a: BitField = a.iter().copied().collect();
+        let bf_b: BitField = b.iter().copied().collect();
+
+        let expected: Vec<usize> = a
+            .iter()
+            .filter(|i| !b.contains(i))
+            .map(|&i| i - b.iter().filter(|&&j| j < i).count())
+            .collect();
+        assert_eq!(bf_a.cut(&bf_b).iter().collect::<Vec<_>>(), expected);
+    }
+
+    assert_eq!(
+        bitfield![1, 1, 0, 1, 0, 1].cut(&bitfield![0, 1, 1, 0, 0, 1]),
+        bitfield![1, 1]
+    );
+    assert_eq!(bitfield![1, 1, 1].cut(&BitField::new()), bitfield![1, 1, 1]);
+}
+
+#[test]
+fn intersection_difference_len_with_unflushed_bits() {
+    let mut a: BitField = (0..10).chain(20..30).collect();
+    a.set(15);
+    a.unset(25);
+    let mut b: BitField = (5..25).collect();
+    b.unset(8);
+
+    // a: 0..10, 15, 20..25, 26..30
+    // b: 5..8, 9..25
+    assert_eq!(a.intersection_len(&b), 10);
+    assert_eq!(a.difference_len(&b), 10);
+    assert_eq!(b.difference_len(&a), 9);
+    assert_eq!(a.intersection_len(&BitField::new()), 0);
+    assert_eq!(a.difference_len(&BitField::new()), a.len());
+}
+
 // Ported test from go impl (specs-actors)
 #[test]
 fn subtract_more() {
@@ -203,6 +285,25 @@ fn bit_vec_unset_vector() {
     assert_eq!(bf.get(3), false);
 }
 
+#[test]
+fn decode_limits() {
+    let bf: BitField = (0..20usize).map(|i| i * 2).collect();
+    let bytes = bf.to_bytes().unwrap();
+
+    assert_eq!(
+        BitField::from_bytes_with_limits(&bytes, 20, bytes.len()).unwrap(),
+        bf
+    );
+    assert!(BitField::from_bytes_with_limits(&bytes, 19, bytes.len()).is_err());
+    assert!(BitField::from_bytes_with_limits(&bytes, 20, bytes.len() - 1).is_err());
+
+    // Deserializing rejects encodings over the maximum size
+    let huge: BitField = (0..MAX_ENCODED_SIZE * 8).map(|i| i * 2).collect();
+    assert!(huge.to_bytes().unwrap().len() > MAX_ENCODED_SIZE);
+    let cbor = encoding::to_vec(&huge).unwrap();
+    assert!(encoding::from_slice::<BitField>(&cbor).is_err());
+}
+
 #[test]
 fn padding() {
     // bits: 0 1 0 1
@@ -218,3 +319,200 @@ fn padding() {
     let deserialized: BitField = encoding::from_slice(&cbor).unwrap();
     assert_eq!(deserialized, bf);
 }
+
+#[test]
+fn first_last() {
+    let mut bf = bitfield![0, 1, 1, 0, 1, 0, 0, 0, 1, 1];
+    assert_eq!(bf.first(), Some(1));
+    assert_eq!(bf.last(), Some(9));
+
+    bf.unset(9);
+    bf.set(12);
+    assert_eq!(bf.last(), Some(12));
+    assert_eq!(BitField::new().last(), None);
+}
+
+#[test]
+fn range_queries() {
+    let mut bf = bitfield![0, 1, 1, 0, 1, 0, 0, 0, 1, 1];
+    bf.set(20);
+    bf.unset(9);
+    assert_eq!(bf.count_in_range(0..10), 4);
+    assert_eq!(bf.count_in_range(2..9), 3);
+    assert_eq!(bf.count_in_range(5..5), 0);
+    assert_eq!(bf.count_in_range(10..100), 1);
+    assert!(bf.is_subset_of_range(1..21));
+    assert!(!bf.is_subset_of_range(1..20));
+    assert!(!bf.is_subset_of_range(2..21));
+    assert!(BitField::new().is_subset_of_range(0..0));
+
+    let vals = random_indices(1000, 4);
+    let bf: BitField = vals.iter().copied().collect();
+    assert_eq!(bf.last(), vals.last().copied());
+    for &(start, end) in &[(0, 1000), (13, 500), (250, 251), (999, 2000)] {
+        let expected = vals.iter().filter(|&&i| start <= i && i < end).count();
+        assert_eq!(bf.count_in_range(start..end), expected);
+    }
+}
+
+#[test]
+fn rank_select() {
+    let mut bf = bitfield![0, 1, 1, 0, 1, 0, 0, 0, 1, 1];
+    bf.set(5);
+    bf.unset(8);
+    let index = bf.rank_select();
+
+    let bits: Vec<_> = bf.iter().collect();
+    assert_eq!(bits, &[1, 2, 4, 5, 9]);
+    assert_eq!(index.len(), bits.len());
+
+    for (n, &bit) in bits.iter().enumerate() {
+        assert_eq!(bf.select(n), Some(bit));
+        assert_eq!(index.select(n), Some(bit));
+        assert_eq!(bf.rank(bit), n);
+        assert_eq!(index.rank(bit), n);
+    }
+    assert_eq!(bf.select(bits.len()), None);
+    assert_eq!(index.select(bits.len()), None);
+    assert_eq!(index.rank(0), 0);
+    assert_eq!(index.rank(3), 2);
+    assert_eq!(bf.rank(100), bits.len());
+    assert_eq!(index.rank(100), bits.len());
+}
+
+#[test]
+fn rank_select_random() {
+    let vals = random_indices(10000, 3);
+    let bf: BitField = vals.iter().copied().collect();
+    let index = bf.rank_select();
+
+    for (n, &bit) in vals.iter().enumerate().step_by(97) {
+        assert_eq!(index.select(n), Some(bit));
+        assert_eq!(index.rank(bit), n);
+        assert_eq!(index.rank(bit + 1), n + 1);
+    }
+}
+
+#[test]
+fn from_unsorted_bits() {
+    let bf: BitField = vec![5, 1, 2, 2, 1, 6].into_iter().collect();
+    assert_eq!(bf.ranges().collect::<Vec<_>>(), vec![1..3, 5..7]);
+
+    assert_eq!(BitField::try_from_bits(vec![1, 2, 5, 6]).unwrap(), bf);
+    assert!(BitField::try_from_bits(vec![1, 2, 2, 5, 6]).is_err());
+    assert!(BitField::try_from_bits(vec![5, 6, 1, 2]).is_err());
+}
+
+#[test]
+fn normalize() {
+    let mut bf = BitField::from_iter(vec![1, 2, 3, 10, 11]);
+    bf.set(5);
+    bf.unset(2);
+    let ranges: Vec<_> = bf.ranges().collect();
+
+    bf.normalize();
+    assert_eq!(bf.ranges().collect::<Vec<_>>(), ranges);
+    assert_eq!(ranges, vec![1..2, 3..4, 5..6, 10..12]);
+
+    // Buffered bits are folded into the ranges as they accumulate, instead of growing
+    // the buffer by a bit per call
+    let mut bf = BitField::new();
+    for i in 0..20_000 {
+        bf.set(i);
+    }
+    assert!(bf.allocated_bytes() < 64 * 1024);
+    assert_eq!(bf.ranges().collect::<Vec<_>>(), vec![0..20_000]);
+
+    let mut rng = XorShiftRng::seed_from_u64(6);
+    let mut expected: AHashSet<_> = bf.iter().collect();
+    for _ in 0..20_000 {
+        let bit = rng.gen_range(0, 30_000);
+        if rng.gen::<bool>() {
+            bf.set(bit);
+            expected.insert(bit);
+        } else {
+            bf.unset(bit);
+            expected.remove(&bit);
+        }
+    }
+    assert_eq!(bf.iter().collect::<AHashSet<_>>(), expected);
+}
+
+#[test]
+fn compact() {
+    let mut bf = BitField::from_iter(vec![1, 2, 3, 10, 11]);
+    for i in 20..200 {
+        bf.set(i);
+    }
+    bf.unset(2);
+    bf.unset(50);
+    let ranges: Vec<_> = bf.ranges().collect();
+    let before = bf.allocated_bytes();
+
+    bf.compact();
+    assert_eq!(bf.ranges().collect::<Vec<_>>(), ranges);
+    assert_eq!(ranges, vec![1..2, 3..4, 10..12, 20..50, 51..200]);
+    assert!(bf.allocated_bytes() < before);
+    assert_eq!(
+        bf.allocated_bytes(),
+        ranges.len() * std::mem::size_of::<std::ops::Range<usize>>()
+    );
+
+    // Bits can still be changed after compacting
+    bf.set(50);
+    assert!(bf.get(50));
+    bf.compact();
+    assert_eq!(bf.ranges().nth(3), Some(20..200));
+    assert_eq!(BitField::new().allocated_bytes(), 0);
+}
+
+#[test]
+fn bounded_bit_field() {
+    let bf: BitField = vec![1usize, 2, 3, 10].into_iter().collect();
+    let cbor = encoding::to_vec(&bf).unwrap();
+
+    let bounded: BoundedBitField<4> = encoding::from_slice(&cbor).unwrap();
+    assert_eq!(*bounded, bf);
+    assert_eq!(encoding::to_vec(&bounded).unwrap(), cbor);
+    assert!(encoding::from_slice::<BoundedBitField<3>>(&cbor).is_err());
+
+    // The bound is on the number of set bits, not the number of runs
+    assert!(encoding::from_slice::<BoundedBitField<2>>(&cbor).is_err());
+    let run = encoding::to_vec(&bitfield![0, 1, 1, 1, 1]).unwrap();
+    assert!(encoding::from_slice::<BoundedBitField<3>>(&run).is_err());
+
+    assert!(BoundedBitField::<3>::new(bf.clone()).is_err());
+    assert_eq!(
+        BoundedBitField::<4>::new(bf.clone()).unwrap().into_inner(),
+        bf
+    );
+}
+
+#[test]
+fn unvalidated_bitfield() {
+    let bf: BitField = vec![1usize, 2, 3, 10].into_iter().collect();
+    let cbor = encoding::to_vec(&bf).unwrap();
+
+    // Decoding keeps the bytes until the bit field is validated
+    let mut unvalidated: UnvalidatedBitField = encoding::from_slice(&cbor).unwrap();
+    assert_eq!(
+        unvalidated,
+        UnvalidatedBitField::Unvalidated(bf.to_bytes().unwrap())
+    );
+    assert_eq!(encoding::to_vec(&unvalidated).unwrap(), cbor);
+    assert_eq!(unvalidated.validate_mut().unwrap(), &bf);
+    assert_eq!(unvalidated, UnvalidatedBitField::Validated(bf.clone()));
+    assert_eq!(encoding::to_vec(&unvalidated).unwrap(), cbor);
+    assert_eq!(unvalidated.validate().unwrap(), bf);
+
+    // Invalid encodings are only rejected on validation
+    let invalid = encoding::to_vec(&UnvalidatedBitField::Unvalidated(vec![0xff])).unwrap();
+    let mut unvalidated: UnvalidatedBitField = encoding::from_slice(&invalid).unwrap();
+    assert!(unvalidated.validate_mut().is_err());
+    assert!(unvalidated.validate().is_err());
+
+    assert_eq!(
+        UnvalidatedBitField::from(bf.clone()).validate().unwrap(),
+        bf
+    );
+}
@@ -0,0 +1,60 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+#![cfg(feature = "json")]
+
+use bitfield::{
+    bitfield,
+    json::{self, BitFieldJson, BitFieldJsonRef},
+    BitField,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string};
+
+#[test]
+fn symmetric_json_serialization() {
+    for (bf, bf_json) in vec![
+        (BitField::new(), "[]"),
+        (bitfield![1], "[0,1]"),
+        (bitfield![0, 1, 1, 0, 0, 0, 1], "[1,2,3,1]"),
+        (bitfield![1, 1, 0, 1], "[0,2,1,1]"),
+    ] {
+        // Deserialize
+        let BitFieldJson(bf_d) = from_str(bf_json).unwrap();
+        assert_eq!(bf_d, bf);
+
+        // Serialize
+        assert_eq!(to_string(&BitFieldJsonRef(&bf)).unwrap(), bf_json);
+    }
+
+    // A trailing run of 0s doesn't set any bits
+    let BitFieldJson(bf) = from_str("[1,2,3]").unwrap();
+    assert_eq!(bf, bitfield![0, 1, 1]);
+}
+
+#[test]
+fn invalid_json() {
+    assert!(from_str::<BitFieldJson>("[1,0,2]").is_err());
+    assert!(from_str::<BitFieldJson>("[0,0]").is_err());
+    assert!(from_str::<BitFieldJson>("[0,-1]").is_err());
+    assert!(from_str::<BitFieldJson>(&format!("[{},1]", u64::MAX)).is_err());
+}
+
+#[test]
+fn annotating_struct_json() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct TestStruct {
+        #[serde(with = "json")]
+        sectors: BitField,
+        other: String,
+    }
+    let test_json = r#"{"sectors":[2,1,3,2],"other":"Some data"}"#;
+    let expected = TestStruct {
+        sectors: bitfield![0, 0, 1, 0, 0, 0, 1, 1],
+        other: "Some data".to_owned(),
+    };
+
+    let test: TestStruct = from_str(test_json).unwrap();
+    assert_eq!(test, expected);
+    assert_eq!(to_string(&test).unwrap(), test_json);
+}
@@ -0,0 +1,107 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use bitfield::{iter::Ranges, rleplus::MAX_RUN_LEN, BitField};
+use encoding::serde_bytes::ByteBuf;
+use std::ops::Range;
+
+struct Vector {
+    name: &'static str,
+    bytes: Vec<u8>,
+    /// Ranges of the set bits, or `None` if decoding must fail.
+    ranges: Option<Vec<Range<usize>>>,
+}
+
+fn parse_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn parse_range(s: &str) -> Range<usize> {
+    let mut bounds = s.split("..").map(|b| b.parse().unwrap());
+    bounds.next().unwrap()..bounds.next().unwrap()
+}
+
+fn vectors() -> Vec<Vector> {
+    include_str!("rleplus_vectors.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 3, "malformed vector: {}", line);
+            let ranges = match fields[2] {
+                "error" => None,
+                ranges => Some(ranges.split(',').map(parse_range).collect()),
+            };
+            Vector {
+                name: fields[0],
+                bytes: parse_hex(fields[1]),
+                ranges,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn golden_vectors() {
+    for vector in vectors() {
+        let ranges = match vector.ranges {
+            Some(ranges) => ranges,
+            None => {
+                assert!(
+                    BitField::from_bytes(&vector.bytes).is_err(),
+                    "{} was decoded",
+                    vector.name
+                );
+                continue;
+            }
+        };
+
+        let bf = BitField::from_bytes(&vector.bytes).unwrap();
+        assert_eq!(bf.ranges().collect::<Vec<_>>(), ranges, "{}", vector.name);
+        assert_eq!(
+            BitField::from_ranges(Ranges::new(ranges))
+                .to_bytes()
+                .unwrap(),
+            vector.bytes,
+            "{}",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn golden_vectors_serde() {
+    for vector in vectors() {
+        let cbor = encoding::to_vec(&ByteBuf::from(vector.bytes.clone())).unwrap();
+        let decoded = encoding::from_slice::<BitField>(&cbor);
+        if vector.ranges.is_none() {
+            assert!(decoded.is_err(), "{} was decoded", vector.name);
+            continue;
+        }
+        let bf = decoded.unwrap();
+        assert_eq!(encoding::to_vec(&bf).unwrap(), cbor, "{}", vector.name);
+    }
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn encode_max_run_len() {
+    let max = MAX_RUN_LEN as usize;
+
+    // The longest runs of 1s and 0s can be encoded
+    let bf = BitField::from_ranges(Ranges::new(vec![0..max]));
+    assert!(bf.to_bytes().is_ok());
+    let bf = BitField::from_ranges(Ranges::new(vec![max..max + 1]));
+    assert!(bf.to_bytes().is_ok());
+
+    // Longer runs are rejected, when encoding to bytes and when serializing
+    let bf = BitField::from_ranges(Ranges::new(vec![0..max + 1]));
+    assert!(bf.to_bytes().is_err());
+    assert!(encoding::to_vec(&bf).is_err());
+    let bf = BitField::from_ranges(Ranges::new(vec![max + 1..max + 2]));
+    assert!(bf.to_bytes().is_err());
+    assert!(encoding::to_vec(&bf).is_err());
+}
@@ -0,0 +1,28 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Generates typed client helpers for the builtin actors, along with a JSON description of
+//! their methods.
+//!
+//! Usage: `cargo run -p actor --example actor_clients -- <output dir>`
+
+use actor::BUILTIN_ACTOR_METHODS;
+use std::error::Error;
+use std::{env, fs, path::Path};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let out = env::args().nth(1).unwrap_or_else(|| ".".to_owned());
+    let out = Path::new(&out);
+    fs::create_dir_all(out)?;
+
+    for actor in BUILTIN_ACTOR_METHODS {
+        let path = out.join(format!("{}_client.rs", actor.actor));
+        fs::write(&path, actor.client_source())?;
+        println!("wrote {}", path.display());
+    }
+
+    let path = out.join("actor_methods.json");
+    fs::write(&path, serde_json::to_string_pretty(BUILTIN_ACTOR_METHODS)?)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
@@ -18,10 +18,12 @@ use vm::{actor_error, ActorError, ExitCode, MethodNum, Serialized, METHOD_CONSTR
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     PubkeyAddress = 2,
 }
 
+impl_method_num!(Method);
+
 /// Account Actor
 pub struct Actor;
 impl Actor {
@@ -66,7 +68,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
@@ -3,11 +3,13 @@
 
 use address::Address;
 use encoding::{tuple::*, Cbor};
+use runtime::StateObject;
 
 /// State includes the address for the actor
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     pub address: Address,
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
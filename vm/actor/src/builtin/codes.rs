@@ -19,6 +19,15 @@ lazy_static! {
     // Set of actor code types that can represent external signing parties.
     pub static ref CALLER_TYPES_SIGNABLE: [Cid; 2] =
         [ACCOUNT_ACTOR_CODE_ID.clone(), MULTISIG_ACTOR_CODE_ID.clone()];
+
+    // Actor code types which can be exec'd through the init actor, with the caller types
+    // permitted to exec them. `None` permits callers of any type.
+    // Singletons and account actors are not in the table, so they can never be exec'd.
+    static ref EXEC_PERMISSIONS: [(Cid, Option<Vec<Cid>>); 3] = [
+        (MINER_ACTOR_CODE_ID.clone(), Some(vec![POWER_ACTOR_CODE_ID.clone()])),
+        (MULTISIG_ACTOR_CODE_ID.clone(), None),
+        (PAYCH_ACTOR_CODE_ID.clone(), None),
+    ];
 }
 
 fn make_builtin(bz: &[u8]) -> Cid {
@@ -30,3 +39,14 @@ fn make_builtin(bz: &[u8]) -> Cid {
 pub fn is_principal(code: &Cid) -> bool {
     CALLER_TYPES_SIGNABLE.iter().any(|c| c == code)
 }
+
+// Tests whether an actor of the caller code type is permitted to exec an actor of the given code.
+pub fn can_exec(caller: &Cid, exec: &Cid) -> bool {
+    EXEC_PERMISSIONS
+        .iter()
+        .find(|(code, _)| code == exec)
+        .map_or(false, |(_, callers)| match callers {
+            Some(callers) => callers.contains(caller),
+            None => true,
+        })
+}
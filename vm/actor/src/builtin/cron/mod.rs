@@ -4,7 +4,10 @@
 mod state;
 
 pub use self::state::{Entry, State};
-use crate::{check_empty_params, SYSTEM_ACTOR_ADDR};
+use crate::{
+    check_empty_params, market, power, FIRST_NON_SINGLETON_ADDR, STORAGE_MARKET_ACTOR_ADDR,
+    STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+};
 use ipld_blockstore::BlockStore;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
@@ -20,10 +23,12 @@ use vm::{
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     EpochTick = 2,
 }
 
+impl_method_num!(Method);
+
 /// Constructor parameters for Cron actor, contains entries
 /// of actors and methods to call on each epoch
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -33,6 +38,25 @@ pub struct ConstructorParams {
     pub entries: Vec<Entry>,
 }
 
+impl ConstructorParams {
+    /// Entries of the builtin actors which are called at the end of every epoch, which the
+    /// genesis state is constructed with.
+    pub fn builtin() -> Self {
+        Self {
+            entries: vec![
+                Entry {
+                    receiver: *STORAGE_POWER_ACTOR_ADDR,
+                    method_num: power::Method::OnEpochTickEnd.into(),
+                },
+                Entry {
+                    receiver: *STORAGE_MARKET_ACTOR_ADDR,
+                    method_num: market::Method::CronTick.into(),
+                },
+            ],
+        }
+    }
+}
+
 /// Cron actor
 pub struct Actor;
 impl Actor {
@@ -43,6 +67,18 @@ impl Actor {
         RT: Runtime<BS>,
     {
         rt.validate_immediate_caller_is(std::iter::once(&*SYSTEM_ACTOR_ADDR))?;
+
+        // Only singleton actors are called, as any other actor could be deleted
+        for entry in &params.entries {
+            match entry.receiver.id() {
+                Ok(id) if id < FIRST_NON_SINGLETON_ADDR.0 => (),
+                _ => {
+                    return Err(actor_error!(ErrIllegalArgument;
+                        "cron entry receiver {} is not a singleton actor", entry.receiver))
+                }
+            }
+        }
+
         rt.create(&State {
             entries: params.entries,
         })?;
@@ -83,7 +119,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
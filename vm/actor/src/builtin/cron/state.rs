@@ -3,13 +3,12 @@
 
 use address::Address;
 use encoding::{tuple::*, Cbor};
+use runtime::StateObject;
 use serde::{Deserialize, Serialize};
 use vm::MethodNum;
 
-// TODO setup builtin entries for genesis initialization
-
 /// Cron actor state which holds entries to call during epoch tick
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct State {
     /// Entries is a set of actors (and corresponding methods) to call during EpochTick.
@@ -25,3 +24,4 @@ pub struct Entry {
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
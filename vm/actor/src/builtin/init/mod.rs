@@ -6,12 +6,8 @@ mod types;
 
 pub use self::state::State;
 pub use self::types::*;
-use crate::{
-    make_map, MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID,
-    POWER_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR,
-};
+use crate::{can_exec, make_map, SYSTEM_ACTOR_ADDR};
 use address::Address;
-use cid::Cid;
 use ipld_blockstore::BlockStore;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
@@ -24,10 +20,12 @@ use vm::{actor_error, ActorError, ExitCode, MethodNum, Serialized, METHOD_CONSTR
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     Exec = 2,
 }
 
+impl_method_num!(Method);
+
 /// Init actor
 pub struct Actor;
 impl Actor {
@@ -39,6 +37,9 @@ impl Actor {
     {
         let sys_ref: &Address = &SYSTEM_ACTOR_ADDR;
         rt.validate_immediate_caller_is(std::iter::once(sys_ref))?;
+        if params.network_name.is_empty() {
+            return Err(actor_error!(ErrIllegalArgument; "network name must not be empty"));
+        }
         let mut empty_map = make_map(rt.store());
         let root = empty_map
             .flush()
@@ -109,7 +110,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
@@ -122,9 +123,3 @@ impl ActorCode for Actor {
         }
     }
 }
-
-fn can_exec(caller: &Cid, exec: &Cid) -> bool {
-    (exec == &*MINER_ACTOR_CODE_ID && caller == &*POWER_ACTOR_CODE_ID)
-        || exec == &*MULTISIG_ACTOR_CODE_ID
-        || exec == &*PAYCH_ACTOR_CODE_ID
-}
@@ -7,11 +7,11 @@ use cid::Cid;
 use encoding::tuple::*;
 use encoding::Cbor;
 use ipld_blockstore::BlockStore;
-use ipld_hamt::Error as HamtError;
+use runtime::StateObject;
 use vm::ActorID;
 
 /// State is reponsible for creating
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     pub address_map: Cid,
     pub next_id: ActorID,
@@ -29,19 +29,37 @@ impl State {
 
     /// Allocates a new ID address and stores a mapping of the argument address to it.
     /// Returns the newly-allocated address.
+    ///
+    /// Fails if the IDs are exhausted, or if the next ID is in the range reserved for singletons.
     pub fn map_address_to_new_id<BS: BlockStore>(
         &mut self,
         store: &BS,
         addr: &Address,
-    ) -> Result<Address, HamtError> {
-        let id = self.next_id;
-        self.next_id += 1;
+    ) -> Result<Address, String> {
+        let id = self.allocate_id()?;
 
         let mut map = make_map_with_root(&self.address_map, store)?;
         map.set(addr.to_bytes().into(), id)?;
         self.address_map = map.flush()?;
 
-        Ok(Address::new_id(id))
+        Ok(id.to_address())
+    }
+
+    /// Returns the next ID address to be allocated, without allocating it.
+    pub fn next_id_address(&self) -> Address {
+        self.next_id.to_address()
+    }
+
+    /// Takes the next actor ID, advancing the counter.
+    fn allocate_id(&mut self) -> Result<ActorID, String> {
+        let id = self.next_id;
+        if id < FIRST_NON_SINGLETON_ADDR {
+            return Err(format!("next actor ID {} is reserved for singletons", id));
+        }
+        self.next_id = id
+            .checked_next()
+            .ok_or_else(|| "actor IDs exhausted".to_owned())?;
+        Ok(id)
     }
 
     /// ResolveAddress resolves an address to an ID-address, if possible.
@@ -64,8 +82,9 @@ impl State {
 
         Ok(map
             .get::<_, ActorID>(&addr.to_bytes())?
-            .map(Address::new_id))
+            .map(ActorID::to_address))
     }
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
@@ -0,0 +1,105 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Helpers for storage clients to build, sign and publish deal proposals.
+
+use super::policy::*;
+use super::{ClientDealProposal, DealProposal, Method, PublishStorageDealsParams};
+use crate::STORAGE_MARKET_ACTOR_ADDR;
+use address::Address;
+use clock::ChainEpoch;
+use crypto::Signer;
+use encoding::Cbor;
+use fil_types::{PieceInfo, RegisteredSealProof, UnpaddedPieceSize};
+use message::UnsignedMessage;
+use std::io::Read;
+use vm::Serialized;
+
+/// Terms of a storage deal agreed between a client and a provider.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DealTerms {
+    pub client: Address,
+    pub provider: Address,
+    pub start_epoch: ChainEpoch,
+    pub end_epoch: ChainEpoch,
+    pub verified_deal: bool,
+}
+
+/// Builds a deal proposal for the piece with the given terms. The storage price and
+/// collaterals are set to the minimum values allowed by the market policy.
+pub fn new_proposal(piece: &PieceInfo, terms: DealTerms) -> Result<DealProposal, String> {
+    piece.size.validate()?;
+    if terms.end_epoch <= terms.start_epoch {
+        return Err("deal end epoch must be after the start epoch".to_owned());
+    }
+
+    let duration = terms.end_epoch - terms.start_epoch;
+    let (min_dur, max_dur) = deal_duration_bounds(piece.size);
+    if duration < min_dur || duration > max_dur {
+        return Err(format!(
+            "deal duration {} out of bounds [{}, {}]",
+            duration, min_dur, max_dur
+        ));
+    }
+
+    let (min_price, _) = deal_price_per_epoch_bounds(piece.size, duration);
+    let (min_provider_collateral, _) = deal_provider_collateral_bounds(piece.size, duration);
+    let (min_client_collateral, _) = deal_client_collateral_bounds(piece.size, duration);
+
+    Ok(DealProposal {
+        piece_cid: piece.cid.clone(),
+        piece_size: piece.size,
+        verified_deal: terms.verified_deal,
+        client: terms.client,
+        provider: terms.provider,
+        start_epoch: terms.start_epoch,
+        end_epoch: terms.end_epoch,
+        storage_price_per_epoch: min_price,
+        provider_collateral: min_provider_collateral,
+        client_collateral: min_client_collateral,
+    })
+}
+
+/// Signs the proposal with the key of the deal's client.
+pub fn sign_proposal<S: Signer>(
+    proposal: DealProposal,
+    signer: &S,
+) -> Result<ClientDealProposal, String> {
+    let bz = proposal.marshal_cbor().map_err(|e| e.to_string())?;
+    let client_signature = signer
+        .sign_bytes(bz, &proposal.client)
+        .map_err(|e| e.to_string())?;
+    Ok(ClientDealProposal {
+        proposal,
+        client_signature,
+    })
+}
+
+/// Computes the piece commitment of the data read from the source, and returns the signed
+/// proposal of a deal for the piece with the given terms.
+pub fn propose_deal<R: Read, S: Signer>(
+    proof_type: RegisteredSealProof,
+    source: R,
+    size: UnpaddedPieceSize,
+    terms: DealTerms,
+    signer: &S,
+) -> Result<ClientDealProposal, String> {
+    let piece = PieceInfo::generate(proof_type, source, size)?;
+    let proposal = new_proposal(&piece, terms)?;
+    sign_proposal(proposal, signer)
+}
+
+/// Builds the message publishing the signed deals to the market actor. The message must be
+/// sent from the worker address of the deals' provider.
+pub fn publish_storage_deals_message(
+    from: Address,
+    deals: Vec<ClientDealProposal>,
+) -> Result<UnsignedMessage, String> {
+    let params = PublishStorageDealsParams { deals };
+    UnsignedMessage::builder()
+        .from(from)
+        .to(*STORAGE_MARKET_ACTOR_ADDR)
+        .method_num(Method::PublishStorageDeals.into())
+        .params(Serialized::serialize(&params).map_err(|e| e.to_string())?)
+        .build()
+}
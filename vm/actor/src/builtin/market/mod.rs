@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod deal;
+pub mod deal_client;
 mod policy;
 mod state;
 mod types;
@@ -13,17 +14,18 @@ pub use self::types::*;
 use crate::{
     make_map, request_miner_control_addrs,
     verifreg::{BytesParams, Method as VerifregMethod},
-    BalanceTable, DealID, SetMultimap, BURNT_FUNDS_ACTOR_ADDR, CALLER_TYPES_SIGNABLE,
-    CRON_ACTOR_ADDR, MINER_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
+    BalanceTable, BytesKey, DealID, Set, SetMultimap, BURNT_FUNDS_ACTOR_ADDR,
+    CALLER_TYPES_SIGNABLE, CRON_ACTOR_ADDR, MINER_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 use address::Address;
 use cid::Cid;
 use clock::{ChainEpoch, EPOCH_UNDEFINED};
-use encoding::to_vec;
-use fil_types::PieceInfo;
+use encoding::{to_vec, Cbor};
+use fil_types::{NetworkVersion, PieceInfo};
 use ipld_amt::Amt;
 use ipld_blockstore::BlockStore;
-use num_bigint::{BigInt, BigUint};
+use num_bigint::BigInt;
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Zero};
 use runtime::{ActorCode, Runtime};
@@ -36,7 +38,7 @@ use vm::{
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     AddBalance = 2,
     WithdrawBalance = 3,
     PublishStorageDeals = 4,
@@ -44,7 +46,12 @@ pub enum Method {
     OnMinerSectorsTerminate = 6,
     ComputeDataCommitment = 7,
     CronTick = 8,
+    VerifyDealsForActivation = 9,
+    DealsForProvider = 10,
 }
+
+impl_method_num!(Method);
+
 /// Market Actor
 pub struct Actor;
 impl Actor {
@@ -81,8 +88,12 @@ impl Actor {
         Ok(())
     }
 
-    /// Deposits the received value into the balance held in escrow.
-    fn add_balance<BS, RT>(rt: &mut RT, provider_or_client: Address) -> Result<(), ActorError>
+    /// Deposits the received value into the balance held in escrow. From network version 1
+    /// the updated escrow balance is returned.
+    fn add_balance<BS, RT>(
+        rt: &mut RT,
+        provider_or_client: Address,
+    ) -> Result<Option<AddBalanceReturn>, ActorError>
     where
         BS: BlockStore,
         RT: Runtime<BS>,
@@ -90,7 +101,8 @@ impl Actor {
         let (nominal, _) = escrow_address(rt, &provider_or_client)?;
 
         let msg_value = rt.message().value_received().clone();
-        rt.transaction::<State, Result<(), ActorError>, _>(|st, rt| {
+        let return_balance = rt.network_version() >= NetworkVersion::V1;
+        rt.transaction::<State, Result<_, ActorError>, _>(|st, rt| {
             st.add_escrow_balance(rt.store(), &nominal, msg_value)
                 .map_err(|e| {
                     ActorError::new(
@@ -107,10 +119,13 @@ impl Actor {
                         format!("adding to locked table: {}", e),
                     )
                 })?;
-            Ok(())
-        })??;
 
-        Ok(())
+            if !return_balance {
+                return Ok(None);
+            }
+            let balance = st.get_escrow_balance(rt.store(), &nominal)?;
+            Ok(Some(AddBalanceReturn { balance }))
+        })?
     }
 
     /// Attempt to withdraw the specified amount from the balance held in escrow.
@@ -153,7 +168,7 @@ impl Actor {
             })??;
 
         // TODO this will never be hit
-        if amount_slashed_total > BigInt::zero() {
+        if amount_slashed_total > TokenAmount::zero() {
             rt.send(
                 *BURNT_FUNDS_ACTOR_ADDR,
                 METHOD_SEND,
@@ -195,7 +210,7 @@ impl Actor {
             || actor_error!(ErrNotFound; "failed to resolve provider address {}", provider_raw),
         )?;
 
-        let (_, worker) = request_miner_control_addrs(rt, provider)?;
+        let (_, worker, _) = request_miner_control_addrs(rt, provider)?;
         if &worker != rt.message().caller() {
             return Err(ActorError::new(
                 ExitCode::ErrForbidden,
@@ -214,7 +229,7 @@ impl Actor {
                 })?;
                 rt.send(
                     *VERIFIED_REGISTRY_ACTOR_ADDR,
-                    VerifregMethod::UseBytes as u64,
+                    VerifregMethod::UseBytes.into(),
                     ser_params,
                     TokenAmount::zero(),
                 )?;
@@ -227,6 +242,18 @@ impl Actor {
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
             let mut deal_ops = SetMultimap::from_root(rt.store(), &st.deal_ops_by_epoch)
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+            let mut pending = st
+                .pending_proposals
+                .as_ref()
+                .map(|root| Set::from_root(rt.store(), root))
+                .transpose()
+                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+            let mut by_provider = st
+                .deals_by_provider
+                .as_ref()
+                .map(|root| SetMultimap::from_root(rt.store(), root))
+                .transpose()
+                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
 
             for mut deal in params.deals {
                 validate_deal(rt, &deal)?;
@@ -247,6 +274,22 @@ impl Actor {
                 deal.proposal.provider = provider;
                 deal.proposal.client = client;
 
+                if let Some(pending) = pending.as_mut() {
+                    let pcid = deal.proposal.cid().map_err(
+                        |e| actor_error!(ErrIllegalArgument; "failed to get proposal cid: {}", e),
+                    )?;
+                    let has = pending
+                        .has(&pcid.to_bytes())
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))?;
+                    if has {
+                        return Err(actor_error!(ErrIllegalArgument;
+                            "cannot publish duplicate deal proposal {}", pcid));
+                    }
+                    pending
+                        .put(BytesKey(pcid.to_bytes()))
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))?;
+                }
+
                 st.lock_balance_or_abort(
                     rt.store(),
                     &client,
@@ -263,6 +306,11 @@ impl Actor {
                 deal_ops
                     .put(deal.proposal.start_epoch, id)
                     .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))?;
+                if let Some(by_provider) = by_provider.as_mut() {
+                    by_provider
+                        .put_address(&provider, id)
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))?;
+                }
 
                 prop.set(id, deal.proposal)
                     .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
@@ -275,6 +323,20 @@ impl Actor {
             st.deal_ops_by_epoch = deal_ops
                 .root()
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+            if let Some(mut pending) = pending {
+                st.pending_proposals = Some(
+                    pending
+                        .root()
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?,
+                );
+            }
+            if let Some(mut by_provider) = by_provider {
+                st.deals_by_provider = Some(
+                    by_provider
+                        .root()
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?,
+                );
+            }
 
             Ok(())
         })??;
@@ -287,7 +349,10 @@ impl Actor {
     /// Note: in the case of a capacity-commitment sector (one with zero deals), this function should succeed vacuously.
     /// The weight is defined as the sum, over all deals in the set, of the product of its size
     /// with its duration. This quantity may be an input into the functions specifying block reward,
-    /// sector power, collateral, and/or other parameters.    
+    /// sector power, collateral, and/or other parameters.
+    ///
+    /// Superseded by `verify_deals_for_activation` from `NetworkVersion::V7`. Each deal's
+    /// space-time is counted twice here, which is kept for the sectors proven before then.
     fn verify_deals_on_sector_prove_commit<BS, RT>(
         rt: &mut RT,
         params: VerifyDealsOnSectorProveCommitParams,
@@ -297,84 +362,36 @@ impl Actor {
         RT: Runtime<BS>,
     {
         rt.validate_immediate_caller_type(std::iter::once(&*MINER_ACTOR_CODE_ID))?;
-        let miner_addr = *rt.message().caller();
-        let mut total_deal_space_time = BigUint::zero();
-        let mut total_verified_deal_space_time = BigUint::zero();
-        rt.transaction::<State, Result<(), ActorError>, _>(|st, rt| {
-            // if there are no dealIDs, it is a CommittedCapacity sector
-            // and the totalDealSpaceTime should be zero
-            let mut states = Amt::load(&st.states, rt.store())
-                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
-            let proposals = Amt::load(&st.proposals, rt.store())
-                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
-
-            for id in &params.deal_ids {
-                let deal = states
-                    .get(*id)
-                    .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
-
-                if deal.is_some() {
-                    // Sector is currently precommitted but still not proven.
-                    return Err(ActorError::new(
-                        ExitCode::ErrIllegalArgument,
-                        format!("given deal already included in another sector: {}", id),
-                    ));
-                };
-
-                let proposal: DealProposal = proposals
-                    .get(*id)
-                    .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?
-                    .ok_or_else(|| {
-                        ActorError::new(
-                            ExitCode::ErrIllegalState,
-                            "Failed to retrieve the DealProposal".to_owned(),
-                        )
-                    })?;
-
-                validate_deal_can_activate(
-                    rt.curr_epoch(),
-                    &miner_addr,
-                    params.sector_expiry,
-                    &proposal,
-                )?;
-
-                states
-                    .set(
-                        *id,
-                        DealState {
-                            sector_start_epoch: rt.curr_epoch(),
-                            last_updated_epoch: EPOCH_UNDEFINED,
-                            slash_epoch: EPOCH_UNDEFINED,
-                        },
-                    )
-                    .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
-
-                // compute deal weight
-                let deal_space_time = proposal.duration() as u64 * proposal.piece_size.0;
-                if proposal.verified_deal {
-                    total_verified_deal_space_time += deal_space_time;
-                } else {
-                    total_deal_space_time += deal_space_time;
-                }
-
-                if proposal.verified_deal {
-                    total_verified_deal_space_time += deal_space_time;
-                } else {
-                    total_deal_space_time += deal_space_time;
-                }
-            }
-            st.states = states
-                .flush()
-                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
-            Ok(())
-        })??;
+        let sector = SectorDeals {
+            sector_expiry: params.sector_expiry,
+            deal_ids: params.deal_ids,
+        };
+        let weights = activate_deals(rt, std::slice::from_ref(&sector))?
+            .pop()
+            .unwrap_or_default();
 
         Ok(VerifyDealsOnSectorProveCommitReturn {
-            deal_weight: total_deal_space_time,
-            verified_deal_weight: total_verified_deal_space_time,
+            deal_weight: weights.deal_weight * 2u32,
+            verified_deal_weight: weights.verified_deal_weight * 2u32,
         })
     }
 
+    /// Verifies and activates the deals of multiple sectors being ProveCommitted, returning the
+    /// weights of the deals of each sector. Fails if any deal is invalid or included in more
+    /// than one sector.
+    fn verify_deals_for_activation<BS, RT>(
+        rt: &mut RT,
+        params: VerifyDealsForActivationParams,
+    ) -> Result<VerifyDealsForActivationReturn, ActorError>
+    where
+        BS: BlockStore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(std::iter::once(&*MINER_ACTOR_CODE_ID))?;
+        let sectors = activate_deals(rt, &params.sectors)?;
+        Ok(VerifyDealsForActivationReturn { sectors })
+    }
+
     /// Terminate a set of deals in response to their containing sector being terminated.
     /// Slash provider collateral, refund client collateral, and refund partial unpaid escrow
     /// amount to client.    
@@ -394,6 +411,12 @@ impl Actor {
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
             let mut states = Amt::load(&st.states, rt.store())
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+            let mut by_provider = st
+                .deals_by_provider
+                .as_ref()
+                .map(|root| SetMultimap::from_root(rt.store(), root))
+                .transpose()
+                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
 
             for id in params.deal_ids {
                 let deal: DealProposal = prop
@@ -425,11 +448,29 @@ impl Actor {
                 states.set(id, state).map_err(|e| {
                     ActorError::new(ExitCode::ErrIllegalState, format!("Set deal error: {}", e))
                 })?;
+
+                // Terminated deals are no longer listed for the provider, though their
+                // proposals are only deleted once cron processes the termination
+                if let Some(by_provider) = by_provider.as_mut() {
+                    by_provider
+                        .remove_address(&deal.provider, id)
+                        .map_err(|e| {
+                            actor_error!(ErrIllegalState;
+                            "failed to remove deal {} of provider: {}", id, e)
+                        })?;
+                }
             }
 
             st.states = states
                 .flush()
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+            if let Some(mut by_provider) = by_provider {
+                st.deals_by_provider = Some(
+                    by_provider
+                        .root()
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?,
+                );
+            }
             Ok(())
         })??;
         Ok(())
@@ -470,6 +511,27 @@ impl Actor {
         Ok(commd)
     }
 
+    /// Returns the IDs of the deals of a provider which have not been deleted or terminated.
+    fn deals_for_provider<BS, RT>(
+        rt: &mut RT,
+        params: DealsForProviderParams,
+    ) -> Result<DealsForProviderReturn, ActorError>
+    where
+        BS: BlockStore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let provider = rt.resolve_address(&params.provider)?.ok_or_else(
+            || actor_error!(ErrNotFound; "failed to resolve provider address {}", params.provider),
+        )?;
+        let st: State = rt.state()?;
+        let ids = st
+            .deals_for_provider(rt.store(), &provider)
+            .map_err(|e| actor_error!(ErrIllegalState; "failed to load provider deals: {}", e))?;
+        Ok(DealsForProviderReturn { ids })
+    }
+
     fn cron_tick<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
     where
         BS: BlockStore,
@@ -477,7 +539,7 @@ impl Actor {
     {
         rt.validate_immediate_caller_is(std::iter::once(&*CRON_ACTOR_ADDR))?;
 
-        let mut amount_slashed = BigInt::zero();
+        let mut amount_slashed = TokenAmount::zero();
         let mut timed_out_verified_deals: Vec<DealProposal> = Vec::new();
 
         rt.transaction::<State, Result<(), ActorError>, _>(|st, rt| {
@@ -490,6 +552,7 @@ impl Actor {
                 })?;
 
             let mut updates_needed: Vec<(ChainEpoch, DealID)> = Vec::new();
+            let mut deleted_deals: Vec<(Address, DealID)> = Vec::new();
 
             let mut states = Amt::load(&st.states, rt.store())
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
@@ -500,6 +563,13 @@ impl Actor {
             let mut lt = BalanceTable::from_root(rt.store(), &st.locked_table)
                 .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
 
+            let mut pending = st
+                .pending_proposals
+                .as_ref()
+                .map(|root| Set::from_root(rt.store(), root))
+                .transpose()
+                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+
             let mut i = st.last_cron + 1;
             while i <= rt.curr_epoch() {
                 dbe.for_each(i, |id| {
@@ -514,6 +584,20 @@ impl Actor {
                         })?;
 
                     let deal = st.must_get_deal(rt.store(), id)?;
+
+                    // The proposal is no longer pending once first processed, either activated
+                    // or timed out.
+                    if state.last_updated_epoch == EPOCH_UNDEFINED {
+                        if let Some(pending) = pending.as_mut() {
+                            let pcid = deal.cid().map_err(|e| {
+                                actor_error!(ErrIllegalState; "failed to get proposal cid: {}", e)
+                            })?;
+                            pending
+                                .delete(&pcid.to_bytes())
+                                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))?;
+                        }
+                    }
+
                     // Not yet appeared in proven sector; check for timeout.
                     if state.sector_start_epoch == EPOCH_UNDEFINED {
                         assert!(
@@ -536,6 +620,7 @@ impl Actor {
                         }
                     }
 
+                    let provider = deal.provider;
                     let (slash_amount, next_epoch) = st.update_pending_deal_state(
                         rt.store(),
                         state,
@@ -560,6 +645,8 @@ impl Actor {
                             )
                         })?;
                         updates_needed.push((next_epoch, id));
+                    } else {
+                        deleted_deals.push((provider, id));
                     }
                     Ok(())
                 })
@@ -603,9 +690,51 @@ impl Actor {
 
             st.locked_table = ltc;
             st.escrow_table = etc;
-
             st.deal_ops_by_epoch = nd_bec;
 
+            match &st.deals_by_provider {
+                Some(root) => {
+                    let mut by_provider = SetMultimap::from_root(rt.store(), root)
+                        .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+                    for (provider, id) in deleted_deals {
+                        by_provider.remove_address(&provider, id).map_err(|e| {
+                            actor_error!(ErrIllegalState;
+                                "failed to remove deal {} of provider: {}", id, e)
+                        })?;
+                    }
+                    st.deals_by_provider = Some(
+                        by_provider
+                            .root()
+                            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?,
+                    );
+                }
+                // Deals are indexed by provider from the first cron tick of the network version
+                None if rt.network_version() >= NetworkVersion::V3 => {
+                    st.migrate_deals_by_provider(rt.store()).map_err(|e| {
+                        actor_error!(ErrIllegalState; "failed to index deals by provider: {}", e)
+                    })?;
+                }
+                None => {}
+            }
+
+            match pending {
+                Some(mut pending) => {
+                    st.pending_proposals = Some(
+                        pending
+                            .root()
+                            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?,
+                    );
+                }
+                // Proposals are tracked from the first cron tick of the network version, which
+                // adds those of the deals not yet processed
+                None if rt.network_version() >= NetworkVersion::V5 => {
+                    st.migrate_pending_proposals(rt.store()).map_err(|e| {
+                        actor_error!(ErrIllegalState; "failed to track pending proposals: {}", e)
+                    })?;
+                }
+                None => {}
+            }
+
             st.last_cron = rt.curr_epoch();
 
             Ok(())
@@ -618,7 +747,7 @@ impl Actor {
             })?;
             rt.send(
                 *VERIFIED_REGISTRY_ACTOR_ADDR,
-                VerifregMethod::RestoreBytes as u64,
+                VerifregMethod::RestoreBytes.into(),
                 ser_params,
                 TokenAmount::zero(),
             )?;
@@ -636,6 +765,128 @@ impl Actor {
 ////////////////////////////////////////////////////////////////////////////////
 // Checks
 ////////////////////////////////////////////////////////////////////////////////
+/// Validates and activates the deals of a sector, returning the weights of the deals.
+/// If there are no deals, it is a committed capacity sector and the weights are zero.
+/// Proposals must be pending once the pending proposals are tracked.
+/// Activates the deals of each of the calling miner's sectors in one state transaction,
+/// returning the deal weights of each sector in order.
+fn activate_deals<BS, RT>(
+    rt: &mut RT,
+    sectors: &[SectorDeals],
+) -> Result<Vec<SectorWeights>, ActorError>
+where
+    BS: BlockStore,
+    RT: Runtime<BS>,
+{
+    let miner_addr = *rt.message().caller();
+    let curr_epoch = rt.curr_epoch();
+
+    rt.transaction::<State, Result<_, ActorError>, _>(|st, rt| {
+        let mut states = Amt::load(&st.states, rt.store())
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+        let proposals = Amt::load(&st.proposals, rt.store())
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+        let pending = st
+            .pending_proposals
+            .as_ref()
+            .map(|root| Set::from_root(rt.store(), root))
+            .transpose()
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+
+        let weights = sectors
+            .iter()
+            .map(|sector| {
+                activate_sector_deals(
+                    &mut states,
+                    &proposals,
+                    pending.as_ref(),
+                    &miner_addr,
+                    curr_epoch,
+                    sector,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        st.states = states
+            .flush()
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+        Ok(weights)
+    })?
+}
+
+fn activate_sector_deals<BS>(
+    states: &mut Amt<DealState, BS>,
+    proposals: &Amt<DealProposal, BS>,
+    pending: Option<&Set<BS>>,
+    miner_addr: &Address,
+    curr_epoch: ChainEpoch,
+    sector: &SectorDeals,
+) -> Result<SectorWeights, ActorError>
+where
+    BS: BlockStore,
+{
+    let mut weights = SectorWeights::default();
+    for id in &sector.deal_ids {
+        let deal = states
+            .get(*id)
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+
+        if deal.is_some() {
+            // Deal was already activated, for this or another sector.
+            return Err(ActorError::new(
+                ExitCode::ErrIllegalArgument,
+                format!("given deal already included in another sector: {}", id),
+            ));
+        };
+
+        let proposal: DealProposal = proposals
+            .get(*id)
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?
+            .ok_or_else(|| {
+                ActorError::new(
+                    ExitCode::ErrIllegalState,
+                    "Failed to retrieve the DealProposal".to_owned(),
+                )
+            })?;
+
+        validate_deal_can_activate(curr_epoch, miner_addr, sector.sector_expiry, &proposal)?;
+
+        if let Some(pending) = pending {
+            let pcid = proposal
+                .cid()
+                .map_err(|e| actor_error!(ErrIllegalState; "failed to get proposal cid: {}", e))?;
+            let has = pending
+                .has(&pcid.to_bytes())
+                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))?;
+            if !has {
+                return Err(actor_error!(ErrIllegalState;
+                    "tried to activate deal {} that was not in the pending set", id));
+            }
+        }
+
+        states
+            .set(
+                *id,
+                DealState {
+                    sector_start_epoch: curr_epoch,
+                    last_updated_epoch: EPOCH_UNDEFINED,
+                    slash_epoch: EPOCH_UNDEFINED,
+                },
+            )
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e.into()))?;
+
+        // compute deal weight
+        let deal_space_time = proposal.duration() as u64 * proposal.piece_size.0;
+        weights.deal_space += proposal.piece_size.0;
+        if proposal.verified_deal {
+            weights.verified_deal_weight += deal_space_time;
+        } else {
+            weights.deal_weight += deal_space_time;
+        }
+    }
+    Ok(weights)
+}
+
 fn validate_deal_can_activate(
     curr_epoch: ChainEpoch,
     miner_addr: &Address,
@@ -680,48 +931,8 @@ where
         ));
     };
 
-    let (min_dur, max_dur) = deal_duration_bounds(deal.proposal.piece_size);
-    if deal.proposal.duration() < min_dur || deal.proposal.duration() > max_dur {
-        return Err(ActorError::new(
-            ExitCode::ErrIllegalArgument,
-            "Deal duration out of bounds.".to_owned(),
-        ));
-    };
-
-    let (min_price, max_price) =
-        deal_price_per_epoch_bounds(deal.proposal.piece_size, deal.proposal.duration());
-    if deal.proposal.storage_price_per_epoch < min_price
-        || deal.proposal.storage_price_per_epoch > max_price
-    {
-        return Err(ActorError::new(
-            ExitCode::ErrIllegalArgument,
-            "Storage price out of bounds.".to_owned(),
-        ));
-    };
-
-    let (min_provider_collateral, max_provider_collateral) =
-        deal_provider_collateral_bounds(deal.proposal.piece_size, deal.proposal.duration());
-    if deal.proposal.provider_collateral < min_provider_collateral
-        || deal.proposal.provider_collateral > max_provider_collateral
-    {
-        return Err(ActorError::new(
-            ExitCode::ErrIllegalArgument,
-            "Provider collateral out of bounds.".to_owned(),
-        ));
-    };
-
-    let (min_client_collateral, max_client_collateral) =
-        deal_client_collateral_bounds(deal.proposal.piece_size, deal.proposal.duration());
-    if deal.proposal.provider_collateral < min_client_collateral
-        || deal.proposal.provider_collateral > max_client_collateral
-    {
-        return Err(ActorError::new(
-            ExitCode::ErrIllegalArgument,
-            "Client collateral out of bounds.".to_owned(),
-        ));
-    };
-
-    Ok(())
+    validate_deal_bounds(&Policy::default(), &deal.proposal)
+        .map_err(|e| ActorError::new(ExitCode::ErrIllegalArgument, e))
 }
 
 fn deal_proposal_is_internally_valid<BS, RT>(
@@ -746,18 +957,12 @@ where
         )
     })?;
 
-    rt.syscalls()
-        .verify_signature(
-            &proposal.client_signature,
-            &proposal.proposal.client,
-            &sv_bz,
-        )
-        .map_err(|e| {
-            ActorError::new(
-                ExitCode::ErrIllegalArgument,
-                format!("signature proposal invalid: {}", e),
-            )
-        })?;
+    rt.verify_signature(
+        &proposal.client_signature,
+        &proposal.proposal.client,
+        &sv_bz,
+    )?
+    .map_err(|e| actor_error!(ErrIllegalArgument; "signature proposal invalid: {}", e))?;
 
     Ok(())
 }
@@ -785,8 +990,11 @@ where
     }
 
     // Storage miner actor entry; implied funds recipient is the associated owner address.
-    let (owner_addr, worker_addr) = request_miner_control_addrs(rt, nominal)?;
-    rt.validate_immediate_caller_is([owner_addr, worker_addr].iter())?;
+    let (owner_addr, worker_addr, control_addrs) = request_miner_control_addrs(rt, nominal)?;
+    // Any of the miner's control addresses may manage its escrow.
+    let mut callers = vec![owner_addr, worker_addr];
+    callers.extend(control_addrs);
+    rt.validate_immediate_caller_is(callers.iter())?;
     Ok((nominal, owner_addr))
 }
 
@@ -801,15 +1009,15 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt)?;
                 Ok(Serialized::default())
             }
-            Some(Method::AddBalance) => {
-                Self::add_balance(rt, params.deserialize()?)?;
-                Ok(Serialized::default())
-            }
+            Some(Method::AddBalance) => match Self::add_balance(rt, params.deserialize()?)? {
+                Some(ret) => Ok(Serialized::serialize(ret)?),
+                None => Ok(Serialized::default()),
+            },
             Some(Method::WithdrawBalance) => {
                 Self::withdraw_balance(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
@@ -834,6 +1042,14 @@ impl ActorCode for Actor {
                 Self::cron_tick(rt)?;
                 Ok(Serialized::default())
             }
+            Some(Method::VerifyDealsForActivation) => {
+                let res = Self::verify_deals_for_activation(rt, params.deserialize()?)?;
+                Ok(Serialized::serialize(res)?)
+            }
+            Some(Method::DealsForProvider) if rt.network_version() >= NetworkVersion::V3 => {
+                let res = Self::deals_for_provider(rt, params.deserialize()?)?;
+                Ok(Serialized::serialize(res)?)
+            }
             _ => Err(rt.abort(ExitCode::SysErrInvalidMethod, "Invalid method")),
         }
     }
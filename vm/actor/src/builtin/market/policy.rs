@@ -1,6 +1,7 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::DealProposal;
 use crate::network::EPOCHS_IN_YEAR;
 use clock::ChainEpoch;
 use fil_types::PaddedPieceSize;
@@ -9,7 +10,6 @@ use vm::TokenAmount;
 
 // The maximum supply of Filecoin that will ever exist (in token units)
 const TOTAL_FILECOIN: u64 = 2_000_000_000;
-const TOKEN_PRECISION: u64 = 1_000_000_000_000_000_000;
 
 /// DealUpdatesInterval is the number of blocks between payouts for deals
 pub const DEAL_UPDATED_INTERVAL: i64 = 100;
@@ -22,7 +22,7 @@ pub(super) fn deal_price_per_epoch_bounds(
     _size: PaddedPieceSize,
     _duration: ChainEpoch,
 ) -> (TokenAmount, TokenAmount) {
-    let v = TokenAmount::from(TOTAL_FILECOIN) * TokenAmount::from(TOKEN_PRECISION);
+    let v = TokenAmount::from_whole(TOTAL_FILECOIN);
     (TokenAmount::zero(), v) // PARAM_FINISH
 }
 
@@ -30,7 +30,7 @@ pub(super) fn deal_provider_collateral_bounds(
     _piece_size: PaddedPieceSize,
     _duration: ChainEpoch,
 ) -> (TokenAmount, TokenAmount) {
-    let v = TokenAmount::from(TOTAL_FILECOIN) * TokenAmount::from(TOKEN_PRECISION);
+    let v = TokenAmount::from_whole(TOTAL_FILECOIN);
     (TokenAmount::zero(), v) // PARAM_FINISH
 }
 
@@ -38,12 +38,174 @@ pub(super) fn deal_client_collateral_bounds(
     _piece_size: PaddedPieceSize,
     _duration: ChainEpoch,
 ) -> (TokenAmount, TokenAmount) {
-    let v = TokenAmount::from(TOTAL_FILECOIN) * TokenAmount::from(TOKEN_PRECISION);
+    let v = TokenAmount::from_whole(TOTAL_FILECOIN);
     (TokenAmount::zero(), v) // PARAM_FINISH
 }
 
+/// Bounds of a token amount of a deal, given the piece size and duration of the deal.
+type TokenBounds = fn(PaddedPieceSize, ChainEpoch) -> (TokenAmount, TokenAmount);
+
+/// Bounds of the fields of deal proposals accepted by the market.
+#[derive(Clone, Copy)]
+pub(super) struct Policy {
+    pub duration_bounds: fn(PaddedPieceSize) -> (ChainEpoch, ChainEpoch),
+    pub price_per_epoch_bounds: TokenBounds,
+    pub provider_collateral_bounds: TokenBounds,
+    pub client_collateral_bounds: TokenBounds,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            duration_bounds: deal_duration_bounds,
+            price_per_epoch_bounds: deal_price_per_epoch_bounds,
+            provider_collateral_bounds: deal_provider_collateral_bounds,
+            client_collateral_bounds: deal_client_collateral_bounds,
+        }
+    }
+}
+
+/// Checks that the duration, price and collaterals of a deal proposal are within the bounds
+/// of the policy. The bounds are inclusive.
+pub(super) fn validate_deal_bounds(policy: &Policy, proposal: &DealProposal) -> Result<(), String> {
+    let duration = proposal.duration();
+    let (min_duration, max_duration) = (policy.duration_bounds)(proposal.piece_size);
+    if duration < min_duration || duration > max_duration {
+        return Err("Deal duration out of bounds.".to_owned());
+    }
+
+    let token_bounds: [(&str, &TokenAmount, TokenBounds); 3] = [
+        (
+            "Storage price",
+            &proposal.storage_price_per_epoch,
+            policy.price_per_epoch_bounds,
+        ),
+        (
+            "Provider collateral",
+            &proposal.provider_collateral,
+            policy.provider_collateral_bounds,
+        ),
+        (
+            "Client collateral",
+            &proposal.client_collateral,
+            policy.client_collateral_bounds,
+        ),
+    ];
+    for (name, amount, bounds) in token_bounds.iter() {
+        let (min, max) = bounds(proposal.piece_size, duration);
+        if *amount < &min || *amount > &max {
+            return Err(format!("{} out of bounds.", name));
+        }
+    }
+
+    Ok(())
+}
+
 pub(super) fn collateral_penalty_for_deal_activation_missed(
     provider_collateral: TokenAmount,
 ) -> TokenAmount {
     provider_collateral
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::Address;
+    use cid::Cid;
+
+    fn proposal() -> DealProposal {
+        DealProposal {
+            piece_cid: Cid::default(),
+            piece_size: PaddedPieceSize(2048),
+            verified_deal: false,
+            client: Address::new_id(100),
+            provider: Address::new_id(101),
+            start_epoch: 10,
+            end_epoch: 110,
+            storage_price_per_epoch: TokenAmount::from(5u8),
+            provider_collateral: TokenAmount::from(5u8),
+            client_collateral: TokenAmount::from(5u8),
+        }
+    }
+
+    fn token_bounds(min: u8, max: u8) -> (TokenAmount, TokenAmount) {
+        (TokenAmount::from(min), TokenAmount::from(max))
+    }
+
+    /// Policy with the bounds of each field set to exactly the values of `proposal`, so that
+    /// changing any single field of the proposal puts it out of bounds.
+    fn tight_policy() -> Policy {
+        Policy {
+            duration_bounds: |_| (100, 100),
+            price_per_epoch_bounds: |_, _| token_bounds(5, 5),
+            provider_collateral_bounds: |_, _| token_bounds(5, 5),
+            client_collateral_bounds: |_, _| token_bounds(5, 5),
+        }
+    }
+
+    #[test]
+    fn default_policy_accepts_deal() {
+        assert_eq!(
+            validate_deal_bounds(&Policy::default(), &proposal()),
+            Ok(())
+        );
+        assert_eq!(validate_deal_bounds(&tight_policy(), &proposal()), Ok(()));
+    }
+
+    #[test]
+    fn each_bound_checks_its_field() {
+        let cases: [(&str, fn(&mut DealProposal, u8)); 4] = [
+            ("Deal duration", |p, v| {
+                p.end_epoch = p.start_epoch + 95 + v as ChainEpoch
+            }),
+            ("Storage price", |p, v| {
+                p.storage_price_per_epoch = TokenAmount::from(v)
+            }),
+            ("Provider collateral", |p, v| {
+                p.provider_collateral = TokenAmount::from(v)
+            }),
+            ("Client collateral", |p, v| {
+                p.client_collateral = TokenAmount::from(v)
+            }),
+        ];
+        for (name, set) in cases.iter() {
+            for &(value, valid) in &[(4, false), (5, true), (6, false)] {
+                let mut p = proposal();
+                set(&mut p, value);
+                let res = validate_deal_bounds(&tight_policy(), &p);
+                if valid {
+                    assert_eq!(res, Ok(()), "{} {}", name, value);
+                } else {
+                    assert_eq!(res, Err(format!("{} out of bounds.", name)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn collateral_bounds_are_independent() {
+        // A provider collateral outside of the client collateral bounds is valid, and the other
+        // way around
+        let policy = Policy {
+            provider_collateral_bounds: |_, _| token_bounds(0, 10),
+            client_collateral_bounds: |_, _| token_bounds(20, 30),
+            ..tight_policy()
+        };
+        let mut p = proposal();
+        p.provider_collateral = TokenAmount::from(10u8);
+        p.client_collateral = TokenAmount::from(20u8);
+        assert_eq!(validate_deal_bounds(&policy, &p), Ok(()));
+
+        p.client_collateral = TokenAmount::from(10u8);
+        assert_eq!(
+            validate_deal_bounds(&policy, &p),
+            Err("Client collateral out of bounds.".to_owned())
+        );
+        p.client_collateral = TokenAmount::from(20u8);
+        p.provider_collateral = TokenAmount::from(20u8);
+        assert_eq!(
+            validate_deal_bounds(&policy, &p),
+            Err("Provider collateral out of bounds.".to_owned())
+        );
+    }
+}
@@ -4,19 +4,21 @@
 use super::{
     collateral_penalty_for_deal_activation_missed, DealProposal, DealState, DEAL_UPDATED_INTERVAL,
 };
-use crate::{BalanceTable, DealID};
+use crate::{BalanceTable, BytesKey, DealID, Set, SetMultimap};
 use address::Address;
 use cid::Cid;
 use clock::{ChainEpoch, EPOCH_UNDEFINED};
-use encoding::tuple::*;
 use encoding::Cbor;
 use ipld_amt::Amt;
 use ipld_blockstore::BlockStore;
 use num_traits::Zero;
+use runtime::StateObject;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use vm::{ActorError, ExitCode, TokenAmount};
 
 /// Market actor state
-#[derive(Default, Serialize_tuple, Deserialize_tuple)]
+#[derive(Default, Clone, PartialEq)]
 pub struct State {
     /// Amt<DealID, DealProposal>
     pub proposals: Cid,
@@ -34,6 +36,16 @@ pub struct State {
     /// SetMultimap<Address>
     pub deal_ops_by_epoch: Cid,
     pub last_cron: ChainEpoch,
+    /// IDs of the deals of each provider which have not been deleted or terminated, indexed
+    /// from `NetworkVersion::V3`. States without the index are serialized as the seven field
+    /// tuple of earlier network versions, so that their state roots are unchanged.
+    /// SetMultimap<Address>
+    pub deals_by_provider: Option<Cid>,
+    /// CIDs of the proposals published which have not yet been processed by cron, used to
+    /// reject duplicate proposals from `NetworkVersion::V5`. States without the set keep the
+    /// encoding of earlier network versions.
+    /// Set<Cid>
+    pub pending_proposals: Option<Cid>,
 }
 
 impl State {
@@ -46,9 +58,88 @@ impl State {
             next_id: 0,
             deal_ops_by_epoch: empty_mset,
             last_cron: EPOCH_UNDEFINED,
+            deals_by_provider: None,
+            pending_proposals: None,
         }
     }
 
+    /// Rebuilds the set of pending proposals from the deals in state, to migrate state from
+    /// before the set was kept. A proposal is pending until its deal is first processed by cron.
+    /// Run by the first cron tick from `NetworkVersion::V5`.
+    pub fn migrate_pending_proposals<BS>(&mut self, store: &BS) -> Result<(), String>
+    where
+        BS: BlockStore,
+    {
+        let proposals: Amt<DealProposal, _> = Amt::load(&self.proposals, store)?;
+        let states: Amt<DealState, _> = Amt::load(&self.states, store)?;
+        let mut pending = Set::new(store);
+
+        proposals.for_each(|id, proposal| {
+            let processed = states
+                .get(id)?
+                .map_or(false, |state| state.last_updated_epoch != EPOCH_UNDEFINED);
+            if !processed {
+                let pcid = proposal.cid().map_err(|e| e.to_string())?;
+                pending.put(BytesKey(pcid.to_bytes()))?;
+            }
+            Ok(())
+        })?;
+
+        self.pending_proposals = Some(pending.root()?);
+        Ok(())
+    }
+
+    /// Builds the index of deals by provider from the deals in state, to migrate state from
+    /// before the index was kept. Terminated deals are not indexed.
+    pub fn migrate_deals_by_provider<BS>(&mut self, store: &BS) -> Result<(), String>
+    where
+        BS: BlockStore,
+    {
+        let proposals: Amt<DealProposal, _> = Amt::load(&self.proposals, store)?;
+        let states: Amt<DealState, _> = Amt::load(&self.states, store)?;
+        let mut by_provider = SetMultimap::new(store);
+
+        proposals.for_each(|id, proposal| {
+            let terminated = states
+                .get(id)?
+                .map_or(false, |state| state.slash_epoch != EPOCH_UNDEFINED);
+            if !terminated {
+                by_provider.put_address(&proposal.provider, id)?;
+            }
+            Ok(())
+        })?;
+
+        self.deals_by_provider = Some(by_provider.root()?);
+        Ok(())
+    }
+
+    /// Returns the IDs of the deals of the provider which have not been deleted or terminated,
+    /// in ascending order. The provider must be an ID address. Returns an error if deals are
+    /// not yet indexed by provider.
+    pub fn deals_for_provider<BS>(
+        &self,
+        store: &BS,
+        provider: &Address,
+    ) -> Result<Vec<DealID>, String>
+    where
+        BS: BlockStore,
+    {
+        let root = self
+            .deals_by_provider
+            .as_ref()
+            .ok_or("deals are not indexed by provider")?;
+        let by_provider = SetMultimap::from_root(store, root)?;
+        let mut ids = Vec::new();
+        by_provider
+            .for_each_address(provider, |id| {
+                ids.push(id);
+                Ok(())
+            })
+            .map_err(|e| e.to_string())?;
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
     ////////////////////////////////////////////////////////////////////////////////
     // Deal state operations
     ////////////////////////////////////////////////////////////////////////////////
@@ -141,7 +232,7 @@ impl State {
     }
     fn mutate_deal_proposals<BS, F>(&mut self, store: &BS, f: F) -> Result<(), ActorError>
     where
-        F: FnOnce(&mut Amt<Cid, BS>) -> Result<(), ActorError>,
+        F: FnOnce(&mut Amt<DealProposal, BS>) -> Result<(), ActorError>,
         BS: BlockStore,
     {
         let mut prop = Amt::load(&self.proposals, store)
@@ -164,7 +255,7 @@ impl State {
     where
         BS: BlockStore,
     {
-        self.mutate_deal_proposals(store, |props: &mut Amt<Cid, BS>| {
+        self.mutate_deal_proposals(store, |props: &mut Amt<DealProposal, BS>| {
             props.delete(deal_id).map_err(|e| {
                 ActorError::new(
                     ExitCode::ErrPlaceholder,
@@ -492,3 +583,120 @@ fn deal_get_payment_remaining(deal: &DealProposal, epoch: ChainEpoch) -> TokenAm
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
+
+impl Serialize for State {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let Self {
+            proposals,
+            states,
+            escrow_table,
+            locked_table,
+            next_id,
+            deal_ops_by_epoch,
+            last_cron,
+            deals_by_provider,
+            pending_proposals,
+        } = self;
+        match (deals_by_provider, pending_proposals) {
+            (by_provider, Some(pending)) => (
+                proposals,
+                states,
+                escrow_table,
+                locked_table,
+                next_id,
+                deal_ops_by_epoch,
+                last_cron,
+                by_provider,
+                pending,
+            )
+                .serialize(s),
+            (Some(by_provider), None) => (
+                proposals,
+                states,
+                escrow_table,
+                locked_table,
+                next_id,
+                deal_ops_by_epoch,
+                last_cron,
+                by_provider,
+            )
+                .serialize(s),
+            (None, None) => (
+                proposals,
+                states,
+                escrow_table,
+                locked_table,
+                next_id,
+                deal_ops_by_epoch,
+                last_cron,
+            )
+                .serialize(s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StateVisitor;
+
+        impl<'de> de::Visitor<'de> for StateVisitor {
+            type Value = State;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a tuple of 7 to 9 market state fields")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<State, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let proposals = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let states = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let escrow_table = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let locked_table = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let next_id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(4, &self))?;
+                let deal_ops_by_epoch = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(5, &self))?;
+                let last_cron = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(6, &self))?;
+                let deals_by_provider = seq.next_element::<Option<_>>()?.flatten();
+                let pending_proposals = seq.next_element()?;
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(10, &self));
+                }
+                Ok(State {
+                    proposals,
+                    states,
+                    escrow_table,
+                    locked_table,
+                    next_id,
+                    deal_ops_by_epoch,
+                    last_cron,
+                    deals_by_provider,
+                    pending_proposals,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(StateVisitor)
+    }
+}
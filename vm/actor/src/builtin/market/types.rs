@@ -7,17 +7,23 @@ use address::Address;
 use clock::ChainEpoch;
 use encoding::tuple::*;
 use fil_types::RegisteredSealProof;
-use num_bigint::bigint_ser;
 use num_bigint::biguint_ser;
+use serde::{Deserialize, Serialize};
 use vm::{DealID, TokenAmount};
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct WithdrawBalanceParams {
     pub provider_or_client: Address,
-    #[serde(with = "bigint_ser")]
     pub amount: TokenAmount,
 }
 
+/// Escrow balance of the address after an `AddBalance` deposit.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AddBalanceReturn {
+    pub balance: TokenAmount,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct OnMinerSectorsTerminateParams {
     pub deal_ids: Vec<DealID>,
@@ -52,6 +58,46 @@ pub struct VerifyDealsOnSectorProveCommitReturn {
     pub verified_deal_weight: DealWeight,
 }
 
+/// Deals to activate for a single sector.
+#[derive(Clone, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorDeals {
+    pub sector_expiry: ChainEpoch,
+    pub deal_ids: Vec<DealID>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct VerifyDealsForActivationParams {
+    pub sectors: Vec<SectorDeals>,
+}
+
+/// Weights of the deals activated for a single sector.
+#[derive(Debug, Default, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorWeights {
+    /// Total space of the deals in the sector
+    pub deal_space: u64,
+    #[serde(with = "biguint_ser")]
+    pub deal_weight: DealWeight,
+    #[serde(with = "biguint_ser")]
+    pub verified_deal_weight: DealWeight,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct VerifyDealsForActivationReturn {
+    /// Weights of each sector, in the order of the sectors in the params
+    pub sectors: Vec<SectorWeights>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct DealsForProviderParams {
+    pub provider: Address,
+}
+
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct DealsForProviderReturn {
+    /// IDs of the provider's deals, in ascending order
+    pub ids: Vec<DealID>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ComputeDataCommitmentParams {
     pub deal_ids: Vec<DealID>,
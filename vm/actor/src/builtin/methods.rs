@@ -0,0 +1,644 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::{account, cron, init, market, miner, multisig, paych, power, reward, verifreg};
+use serde::Serialize;
+use vm::{MethodNum, METHOD_CONSTRUCTOR};
+
+/// Description of a method exported by a builtin actor.
+///
+/// Parameter and return types are given as Rust paths, relative to the imports of the
+/// generated client source.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MethodInfo {
+    pub num: MethodNum,
+    /// Name of the method, as in the actor's `Method` enum.
+    pub name: &'static str,
+    /// Type of the parameters, `None` if the method takes no parameters.
+    pub params: Option<&'static str>,
+    /// Type of the return value, `None` if nothing is returned.
+    pub ret: Option<&'static str>,
+}
+
+impl MethodInfo {
+    pub const fn new(
+        num: MethodNum,
+        name: &'static str,
+        params: Option<&'static str>,
+        ret: Option<&'static str>,
+    ) -> Self {
+        Self {
+            num,
+            name,
+            params,
+            ret,
+        }
+    }
+}
+
+/// Description of the methods exported by a builtin actor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ActorMethods {
+    /// Name of the actor module within this crate.
+    pub actor: &'static str,
+    /// Name of the address constant for singleton actors.
+    pub address: Option<&'static str>,
+    pub methods: &'static [MethodInfo],
+}
+
+/// Methods of all builtin actors, with their parameter and return types.
+pub const BUILTIN_ACTOR_METHODS: &[ActorMethods] = &[
+    ActorMethods {
+        actor: "account",
+        address: None,
+        methods: &[
+            MethodInfo::new(METHOD_CONSTRUCTOR, "Constructor", Some("Address"), None),
+            MethodInfo::new(
+                MethodNum::new(account::Method::PubkeyAddress as u64),
+                "PubkeyAddress",
+                None,
+                Some("Address"),
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "cron",
+        address: Some("CRON_ACTOR_ADDR"),
+        methods: &[
+            MethodInfo::new(
+                METHOD_CONSTRUCTOR,
+                "Constructor",
+                Some("cron::ConstructorParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(cron::Method::EpochTick as u64),
+                "EpochTick",
+                None,
+                None,
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "init",
+        address: Some("INIT_ACTOR_ADDR"),
+        methods: &[
+            MethodInfo::new(
+                METHOD_CONSTRUCTOR,
+                "Constructor",
+                Some("init::ConstructorParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(init::Method::Exec as u64),
+                "Exec",
+                Some("init::ExecParams"),
+                Some("init::ExecReturn"),
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "market",
+        address: Some("STORAGE_MARKET_ACTOR_ADDR"),
+        methods: &[
+            MethodInfo::new(METHOD_CONSTRUCTOR, "Constructor", None, None),
+            MethodInfo::new(
+                MethodNum::new(market::Method::AddBalance as u64),
+                "AddBalance",
+                Some("Address"),
+                Some("market::AddBalanceReturn"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::WithdrawBalance as u64),
+                "WithdrawBalance",
+                Some("market::WithdrawBalanceParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::PublishStorageDeals as u64),
+                "PublishStorageDeals",
+                Some("market::PublishStorageDealsParams"),
+                Some("market::PublishStorageDealsReturn"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::VerifyDealsOnSectorProveCommit as u64),
+                "VerifyDealsOnSectorProveCommit",
+                Some("market::VerifyDealsOnSectorProveCommitParams"),
+                Some("market::VerifyDealsOnSectorProveCommitReturn"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::OnMinerSectorsTerminate as u64),
+                "OnMinerSectorsTerminate",
+                Some("market::OnMinerSectorsTerminateParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::ComputeDataCommitment as u64),
+                "ComputeDataCommitment",
+                Some("market::ComputeDataCommitmentParams"),
+                Some("Cid"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::CronTick as u64),
+                "CronTick",
+                None,
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::VerifyDealsForActivation as u64),
+                "VerifyDealsForActivation",
+                Some("market::VerifyDealsForActivationParams"),
+                Some("market::VerifyDealsForActivationReturn"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(market::Method::DealsForProvider as u64),
+                "DealsForProvider",
+                Some("market::DealsForProviderParams"),
+                Some("market::DealsForProviderReturn"),
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "miner",
+        address: None,
+        methods: &[
+            MethodInfo::new(
+                METHOD_CONSTRUCTOR,
+                "Constructor",
+                Some("miner::MinerConstructorParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ControlAddresses as u64),
+                "ControlAddresses",
+                None,
+                Some("miner::GetControlAddressesReturn"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ChangeWorkerAddress as u64),
+                "ChangeWorkerAddress",
+                Some("miner::ChangeWorkerAddressParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ChangePeerID as u64),
+                "ChangePeerID",
+                Some("miner::ChangePeerIDParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::SubmitWindowedPoSt as u64),
+                "SubmitWindowedPoSt",
+                Some("miner::SubmitWindowedPoStParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::PreCommitSector as u64),
+                "PreCommitSector",
+                Some("miner::SectorPreCommitInfo"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ProveCommitSector as u64),
+                "ProveCommitSector",
+                Some("miner::ProveCommitSectorParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ExtendSectorExpiration as u64),
+                "ExtendSectorExpiration",
+                Some("miner::ExtendSectorExpirationParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::TerminateSectors as u64),
+                "TerminateSectors",
+                Some("miner::TerminateSectorsParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::DeclareFaults as u64),
+                "DeclareFaults",
+                Some("miner::DeclareFaultsParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::DeclareFaultsRecovered as u64),
+                "DeclareFaultsRecovered",
+                Some("miner::DeclareFaultsRecoveredParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::OnDeferredCronEvent as u64),
+                "OnDeferredCronEvent",
+                Some("miner::CronEventPayload"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::CheckSectorProven as u64),
+                "CheckSectorProven",
+                Some("miner::CheckSectorProvenParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::AddLockedFund as u64),
+                "AddLockedFund",
+                Some("TokenAmount"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ReportConsensusFault as u64),
+                "ReportConsensusFault",
+                Some("miner::ReportConsensusFaultParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::WithdrawBalance as u64),
+                "WithdrawBalance",
+                Some("miner::WithdrawBalanceParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ConfirmSectorProofsValid as u64),
+                "ConfirmSectorProofsValid",
+                Some("miner::ConfirmSectorProofsParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(miner::Method::ChangeMultiaddrs as u64),
+                "ChangeMultiaddrs",
+                Some("miner::ChangeMultiaddrsParams"),
+                None,
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "multisig",
+        address: None,
+        methods: &[
+            MethodInfo::new(
+                METHOD_CONSTRUCTOR,
+                "Constructor",
+                Some("multisig::ConstructorParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::Propose as u64),
+                "Propose",
+                Some("multisig::ProposeParams"),
+                Some("multisig::TxnID"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::Approve as u64),
+                "Approve",
+                Some("multisig::TxnIDParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::Cancel as u64),
+                "Cancel",
+                Some("multisig::TxnIDParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::AddSigner as u64),
+                "AddSigner",
+                Some("multisig::AddSignerParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::RemoveSigner as u64),
+                "RemoveSigner",
+                Some("multisig::RemoveSignerParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::SwapSigner as u64),
+                "SwapSigner",
+                Some("multisig::SwapSignerParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::ChangeNumApprovalsThreshold as u64),
+                "ChangeNumApprovalsThreshold",
+                Some("multisig::ChangeNumApprovalsThresholdParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(multisig::Method::ConfirmSwapSigner as u64),
+                "ConfirmSwapSigner",
+                None,
+                None,
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "paych",
+        address: None,
+        methods: &[
+            MethodInfo::new(
+                METHOD_CONSTRUCTOR,
+                "Constructor",
+                Some("paych::ConstructorParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(paych::Method::UpdateChannelState as u64),
+                "UpdateChannelState",
+                Some("paych::UpdateChannelStateParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(paych::Method::Settle as u64),
+                "Settle",
+                None,
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(paych::Method::Collect as u64),
+                "Collect",
+                None,
+                None,
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "power",
+        address: Some("STORAGE_POWER_ACTOR_ADDR"),
+        methods: &[
+            MethodInfo::new(METHOD_CONSTRUCTOR, "Constructor", None, None),
+            MethodInfo::new(
+                MethodNum::new(power::Method::CreateMiner as u64),
+                "CreateMiner",
+                Some("power::CreateMinerParams"),
+                Some("power::CreateMinerReturn"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::DeleteMiner as u64),
+                "DeleteMiner",
+                Some("power::DeleteMinerParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnSectorProveCommit as u64),
+                "OnSectorProveCommit",
+                Some("power::OnSectorProveCommitParams"),
+                Some("TokenAmount"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnSectorTerminate as u64),
+                "OnSectorTerminate",
+                Some("power::OnSectorTerminateParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnFaultBegin as u64),
+                "OnFaultBegin",
+                Some("power::OnFaultBeginParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnFaultEnd as u64),
+                "OnFaultEnd",
+                Some("power::OnFaultEndParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnSectorModifyWeightDesc as u64),
+                "OnSectorModifyWeightDesc",
+                Some("power::OnSectorModifyWeightDescParams"),
+                Some("TokenAmount"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::EnrollCronEvent as u64),
+                "EnrollCronEvent",
+                Some("power::EnrollCronEventParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnEpochTickEnd as u64),
+                "OnEpochTickEnd",
+                None,
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::UpdatePledgeTotal as u64),
+                "UpdatePledgeTotal",
+                Some("TokenAmount"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::OnConsensusFault as u64),
+                "OnConsensusFault",
+                Some("TokenAmount"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::SubmitPoRepForBulkVerify as u64),
+                "SubmitPoRepForBulkVerify",
+                Some("fil_types::SealVerifyInfo"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(power::Method::CurrentTotalPower as u64),
+                "CurrentTotalPower",
+                None,
+                Some("power::CurrentTotalPowerReturn"),
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "reward",
+        address: Some("REWARD_ACTOR_ADDR"),
+        methods: &[
+            MethodInfo::new(METHOD_CONSTRUCTOR, "Constructor", None, None),
+            MethodInfo::new(
+                MethodNum::new(reward::Method::AwardBlockReward as u64),
+                "AwardBlockReward",
+                Some("reward::AwardBlockRewardParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(reward::Method::ThisEpochReward as u64),
+                "ThisEpochReward",
+                None,
+                Some("TokenAmount"),
+            ),
+            MethodInfo::new(
+                MethodNum::new(reward::Method::UpdateNetworkKPI as u64),
+                "UpdateNetworkKPI",
+                Some("fil_types::StoragePower"),
+                None,
+            ),
+        ],
+    },
+    ActorMethods {
+        actor: "system",
+        address: Some("SYSTEM_ACTOR_ADDR"),
+        methods: &[MethodInfo::new(
+            METHOD_CONSTRUCTOR,
+            "Constructor",
+            None,
+            None,
+        )],
+    },
+    ActorMethods {
+        actor: "verifreg",
+        address: Some("VERIFIED_REGISTRY_ACTOR_ADDR"),
+        methods: &[
+            MethodInfo::new(METHOD_CONSTRUCTOR, "Constructor", Some("Address"), None),
+            MethodInfo::new(
+                MethodNum::new(verifreg::Method::AddVerifier as u64),
+                "AddVerifier",
+                Some("verifreg::AddVerifierParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(verifreg::Method::RemoveVerifier as u64),
+                "RemoveVerifier",
+                Some("verifreg::AddVerifierParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(verifreg::Method::AddVerifiedClient as u64),
+                "AddVerifiedClient",
+                Some("verifreg::AddVerifierClientParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(verifreg::Method::UseBytes as u64),
+                "UseBytes",
+                Some("verifreg::UseBytesParams"),
+                None,
+            ),
+            MethodInfo::new(
+                MethodNum::new(verifreg::Method::RestoreBytes as u64),
+                "RestoreBytes",
+                Some("verifreg::RestoreBytesParams"),
+                None,
+            ),
+        ],
+    },
+];
+
+/// Imports used by the generated client source.
+const CLIENT_IMPORTS: &str = "use actor::*;
+use address::Address;
+use cid::Cid;
+use encoding::Error as EncodingError;
+use message::UnsignedMessage;
+use num_bigint::bigint_ser::{BigIntDe, BigIntSer};
+use vm::{MethodNum, Serialized, TokenAmount};
+";
+
+/// Returns the method descriptions of a builtin actor, by actor module name.
+pub fn actor_methods(actor: &str) -> Option<&'static ActorMethods> {
+    BUILTIN_ACTOR_METHODS.iter().find(|a| a.actor == actor)
+}
+
+impl ActorMethods {
+    /// Returns the description of a method, by method number.
+    pub fn method(&self, num: MethodNum) -> Option<&'static MethodInfo> {
+        self.methods.iter().find(|m| m.num == num)
+    }
+
+    /// Generates Rust source for typed client helpers of the actor. Each exported method,
+    /// other than the constructor, gets a function which builds the `UnsignedMessage`
+    /// calling it, and methods with a return value get a function to decode it.
+    pub fn client_source(&self) -> String {
+        let mut src = format!(
+            "// Client helpers for the {} actor, generated from `actor::BUILTIN_ACTOR_METHODS`.\n\n{}",
+            self.actor, CLIENT_IMPORTS
+        );
+        for m in self.methods.iter().filter(|m| m.num != METHOD_CONSTRUCTOR) {
+            let fn_name = snake_case(m.name);
+
+            let mut args = String::from("from: Address, ");
+            if self.address.is_none() {
+                args.push_str("to: Address, ");
+            }
+            args.push_str("value: TokenAmount");
+            let params = match m.params {
+                Some(ty) => {
+                    args.push_str(&format!(", params: &{}", ty));
+                    if is_bigint(ty) {
+                        "Serialized::serialize(BigIntSer(params)).map_err(|e| e.to_string())?"
+                    } else {
+                        "Serialized::serialize(params).map_err(|e| e.to_string())?"
+                    }
+                }
+                None => "Serialized::default()",
+            };
+            let to = match self.address {
+                Some(addr) => format!("*{}", addr),
+                None => "to".to_owned(),
+            };
+
+            src.push_str(&format!(
+                "
+/// Builds a message calling `{name}` on the {actor} actor.
+pub fn {fn_name}({args}) -> Result<UnsignedMessage, String> {{
+    UnsignedMessage::builder()
+        .from(from)
+        .to({to})
+        .value(value)
+        .method_num(MethodNum::new({num}))
+        .params({params})
+        .build()
+}}
+",
+                name = m.name,
+                actor = self.actor,
+                fn_name = fn_name,
+                args = args,
+                to = to,
+                num = m.num,
+                params = params,
+            ));
+
+            if let Some(ty) = m.ret {
+                let body = if is_bigint(ty) {
+                    "let BigIntDe(ret) = ret.deserialize()?;\n    Ok(ret)"
+                } else {
+                    "ret.deserialize()"
+                };
+                src.push_str(&format!(
+                    "
+/// Decodes the return value of `{name}`.
+pub fn decode_{fn_name}_return(ret: &Serialized) -> Result<{ty}, EncodingError> {{
+    {body}
+}}
+",
+                    name = m.name,
+                    fn_name = fn_name,
+                    ty = ty,
+                    body = body,
+                ));
+            }
+        }
+        src
+    }
+}
+
+/// Types which are encoded as big integers, and must be wrapped when serialized.
+fn is_bigint(ty: &str) -> bool {
+    ty == "fil_types::StoragePower"
+}
+
+/// Converts a method name to the name of its client function, keeping acronyms together.
+fn snake_case(name: &str) -> String {
+    let name = name.replace("PoSt", "Post").replace("PoRep", "Porep");
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev_lower = chars[i - 1].is_ascii_lowercase();
+            let next_lower = chars.get(i + 1).map_or(false, |n| n.is_ascii_lowercase());
+            if prev_lower || (chars[i - 1].is_ascii_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
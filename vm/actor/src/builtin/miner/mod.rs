@@ -15,6 +15,7 @@ pub use self::types::*;
 use crate::account::Method as AccountMethod;
 use crate::market::{
     ComputeDataCommitmentParams, Method as MarketMethod, OnMinerSectorsTerminateParams,
+    SectorDeals, VerifyDealsForActivationParams, VerifyDealsForActivationReturn,
     VerifyDealsOnSectorProveCommitParams, VerifyDealsOnSectorProveCommitReturn,
 };
 use crate::power::{
@@ -24,29 +25,27 @@ use crate::power::{
     SECTOR_TERMINATION_FAULTY, SECTOR_TERMINATION_MANUAL,
 };
 use crate::{
-    check_empty_params, is_principal, make_map, ACCOUNT_ACTOR_CODE_ID, BURNT_FUNDS_ACTOR_ADDR,
-    CALLER_TYPES_SIGNABLE, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR,
-    STORAGE_POWER_ACTOR_ADDR,
+    check_empty_params, is_principal, make_map, DealWeight, PendingChange, ACCOUNT_ACTOR_CODE_ID,
+    BURNT_FUNDS_ACTOR_ADDR, CALLER_TYPES_SIGNABLE, INIT_ACTOR_ADDR, REWARD_ACTOR_ADDR,
+    STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
 };
-use address::{Address, Payload, Protocol};
+use address::{Address, Protocol};
 use ahash::AHashSet;
-use bitfield::BitField;
+use bitfield::{iter::RangeIterator, BitField};
 use byteorder::{BigEndian, ByteOrder};
-use cid::{multihash::Blake2b256, Cid};
+use cid::Cid;
 use clock::ChainEpoch;
 use crypto::DomainSeparationTag::{
     InteractiveSealChallengeSeed, SealRandomness, WindowedPoStChallengeSeed,
 };
 use encoding::Cbor;
 use fil_types::{
-    InteractiveSealRandomness, PoStProof, PoStRandomness, RegisteredSealProof,
+    InteractiveSealRandomness, NetworkVersion, PoStProof, PoStRandomness, RegisteredSealProof,
     SealRandomness as SealRandom, SealVerifyInfo, SealVerifyParams, SectorID, SectorNumber,
     SectorSize, WindowPoStVerifyInfo,
 };
 use ipld_amt::Amt;
 use ipld_blockstore::BlockStore;
-use num_bigint::bigint_ser::{BigIntDe, BigIntSer};
-use num_bigint::BigInt;
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Zero};
 use runtime::{ActorCode, Runtime};
@@ -54,7 +53,7 @@ use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::ops::Neg;
 use vm::{
-    actor_error, ActorError, DealID, ExitCode, MethodNum, Serialized, TokenAmount,
+    actor_error, ActorError, ActorID, DealID, ExitCode, MethodNum, Serialized, TokenAmount,
     METHOD_CONSTRUCTOR, METHOD_SEND,
 };
 
@@ -64,7 +63,7 @@ use vm::{
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     ControlAddresses = 2,
     ChangeWorkerAddress = 3,
     ChangePeerID = 4,
@@ -84,6 +83,8 @@ pub enum Method {
     ChangeMultiaddrs = 18,
 }
 
+impl_method_num!(Method);
+
 /// Miner Actor
 pub struct Actor;
 
@@ -108,8 +109,16 @@ impl Actor {
             ));
         }
 
+        check_peer_info(&params.peer_id, &params.multi_address)
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalArgument, e))?;
+
         let owner = resolve_owner_address(rt, params.owner)?;
         let worker = resolve_worker_address(rt, params.worker)?;
+        let control_addresses = params
+            .control_addresses
+            .into_iter()
+            .map(|addr| resolve_control_address(rt, addr))
+            .collect::<Result<_, _>>()?;
 
         let empty_map = make_map(rt.store()).flush().map_err(|e| {
             ActorError::new(
@@ -125,7 +134,7 @@ impl Actor {
             )
         })?;
 
-        let empty_deadlines_cid = rt.store().put(&Deadlines::new(), Blake2b256).unwrap();
+        let empty_deadlines_cid = rt.store_put(&Deadlines::new())?;
 
         let current_epoch = rt.curr_epoch();
         let blake2b = |b: &[u8]| rt.syscalls().hash_blake2b(b);
@@ -146,6 +155,7 @@ impl Actor {
             empty_deadlines_cid,
             owner,
             worker,
+            control_addresses,
             params.peer_id,
             params.multi_address,
             params.seal_proof_type,
@@ -177,6 +187,7 @@ impl Actor {
         Ok(GetControlAddressesReturn {
             owner: st.info.owner,
             worker: st.info.worker,
+            control_addresses: st.info.control_addresses,
         })
     }
 
@@ -196,10 +207,7 @@ impl Actor {
             effective_epoch = rt.curr_epoch() + WORKER_KEY_CHANGE_DELAY;
 
             // This may replace another pending key change.
-            st.info.pending_worker_key = Some(WorkerKeyChange {
-                new_worker: worker,
-                effective_at: effective_epoch,
-            });
+            st.info.pending_worker_key = Some(WorkerKeyChange::new(worker, effective_epoch));
             Ok(())
         })??;
 
@@ -216,6 +224,9 @@ impl Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
+        check_peer_info(&params.new_id, &[])
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalArgument, e))?;
+
         rt.transaction::<State, Result<(), ActorError>, _>(|st, rt| {
             rt.validate_immediate_caller_is(std::iter::once(&st.info.worker))?;
             st.info.peer_id = params.new_id;
@@ -232,6 +243,9 @@ impl Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
+        check_peer_info(&[], &params.new_multi_addrs)
+            .map_err(|e| ActorError::new(ExitCode::ErrIllegalArgument, e))?;
+
         rt.transaction::<State, Result<(), ActorError>, _>(|st, rt| {
             rt.validate_immediate_caller_is(std::iter::once(&st.info.worker))?;
             st.info.multi_address = params.new_multi_addrs;
@@ -316,11 +330,12 @@ impl Actor {
                 // TODO WPOST (follow-up): process Skipped as faults
 
                 // Work out which sectors are due in the declared partitions at this deadline.
+                let partitions: Vec<u64> = params.partitions.iter().map(|&p| p.into()).collect();
                 let partitions_sectors = compute_partitions_sector(
                     &mut deadlines,
                     partition_size,
                     deadline.index,
-                    &params.partitions,
+                    &partitions,
                 )
                 .map_err(|_| {
                     ActorError::new(
@@ -348,8 +363,7 @@ impl Actor {
                 verify_windowed_post(rt, deadline.challenge, &sector_infos, params.proofs.clone())?;
 
                 // Record the successful submission
-                let posted_partitions: BitField =
-                    params.partitions.iter().map(|&i| i as usize).collect();
+                let posted_partitions: BitField = partitions.iter().map(|&i| i as usize).collect();
                 let contains = st.post_submissions.contains_any(&posted_partitions);
                 if contains {
                     return Err(ActorError::new(
@@ -387,10 +401,9 @@ impl Actor {
                 if declared_recoveries.is_empty() {
                     Ok(st.info.sector_size)
                 } else {
-                    let mut sectors_by_number: HashMap<SectorNumber, SectorOnChainInfo> =
-                        HashMap::new();
+                    let mut sectors_by_number: HashMap<u64, SectorOnChainInfo> = HashMap::new();
                     for sec in sector_infos {
-                        sectors_by_number.insert(sec.info.sector_number, sec);
+                        sectors_by_number.insert(sec.info.sector_number.into(), sec);
                     }
                     declared_recoveries.iter().for_each(|i| {
                         let key = i as u64;
@@ -550,7 +563,7 @@ impl Actor {
 
         notify_pledge_change(rt, &newly_vested_amount.neg())?;
         let mut bf = BitField::new();
-        bf.set(params.sector_number as usize);
+        bf.set(u64::from(params.sector_number) as usize);
 
         // Request deferred Cron check for PreCommit expiry check.
         let cron_payload = CronEventPayload {
@@ -642,9 +655,9 @@ impl Actor {
 
         rt.send(
             *STORAGE_POWER_ACTOR_ADDR,
-            PowerMethod::SubmitPoRepForBulkVerify as u64,
+            PowerMethod::SubmitPoRepForBulkVerify.into(),
             Serialized::serialize(&svi)?,
-            BigInt::zero(),
+            TokenAmount::zero(),
         )?;
 
         Ok(())
@@ -680,37 +693,29 @@ impl Actor {
 
             // Check (and activate) storage deals associated to sector. Abort if checks failed.
             // return DealWeight for the deal set in the sector
-            let ser_params = Serialized::serialize(VerifyDealsOnSectorProveCommitParams {
-                deal_ids: precommit.info.deal_ids.clone(),
-                sector_expiry: precommit.info.expiration,
-            })?;
-
-            // TODO revisit spec TODOs
-            let mut ret = rt.send(
-                *STORAGE_MARKET_ACTOR_ADDR,
-                MarketMethod::VerifyDealsOnSectorProveCommit as u64,
-                ser_params,
-                TokenAmount::zero(),
+            let (deal_weight, verified_deal_weight) = activate_sector_deals(
+                rt,
+                precommit.info.deal_ids.clone(),
+                precommit.info.expiration,
             )?;
-            let deal_weights: VerifyDealsOnSectorProveCommitReturn = ret.deserialize()?;
 
             // Request power for activated sector.
             // Return initial pledge requirement.
             let param = Serialized::serialize(OnSectorProveCommitParams {
                 weight: SectorStorageWeightDesc {
                     sector_size: st.info.sector_size,
-                    deal_weight: deal_weights.deal_weight.clone(),
-                    verified_deal_weight: deal_weights.verified_deal_weight.clone(),
+                    deal_weight: deal_weight.clone(),
+                    verified_deal_weight: verified_deal_weight.clone(),
                     duration: precommit.info.expiration - rt.curr_epoch(),
                 },
             })?;
-            ret = rt.send(
+            let ret = rt.send(
                 *STORAGE_POWER_ACTOR_ADDR,
-                PowerMethod::OnSectorProveCommit as u64,
+                PowerMethod::OnSectorProveCommit.into(),
                 param,
                 TokenAmount::zero(),
             )?;
-            let BigIntDe(initial_pledge) = ret.deserialize()?;
+            let initial_pledge: TokenAmount = ret.deserialize()?;
 
             // Add sector and pledge lock-up to miner state
             let current_epoch = rt.curr_epoch();
@@ -745,8 +750,8 @@ impl Actor {
                 let new_sector_info = SectorOnChainInfo{
                     info,
                     activation_epoch: current_epoch,
-                    deal_weight: deal_weights.deal_weight,
-                    verified_deal_weight: deal_weights.verified_deal_weight
+                    deal_weight,
+                    verified_deal_weight
                 };
 
                 st.put_sector(rt.store(), new_sector_info).map_err(|e| {
@@ -757,7 +762,7 @@ impl Actor {
                     ActorError::new(ExitCode::ErrIllegalState, format!("failed to delete precommit for sector {}: {}", num, e))
                 })?;
 
-                st.add_sector_expirations(rt.store(), expired_epoch, &[num]).map_err(|e| {
+                st.add_sector_expirations(rt.store(), expired_epoch, &[u64::from(num)]).map_err(|e| {
                     ActorError::new(ExitCode::ErrIllegalState, format!("failed to add new sector {} expiration: {}", num, e))
                 })?;
 
@@ -846,9 +851,9 @@ impl Actor {
 
         rt.send(
             *STORAGE_POWER_ACTOR_ADDR,
-            PowerMethod::OnSectorModifyWeightDesc as u64,
+            PowerMethod::OnSectorModifyWeightDesc.into(),
             ser_params,
-            BigInt::zero(),
+            TokenAmount::zero(),
         )?;
 
         // store new sector expiry
@@ -862,26 +867,34 @@ impl Actor {
             })?;
 
             // move expiration from old epoch to new
-            st.remove_sector_expirations(rt.store(), old_expiration, &[params.sector_number])
-                .map_err(|e| {
-                    ActorError::new(
-                        ExitCode::ErrIllegalState,
-                        format!(
-                            "failed to update sector expiration: {:?}, {}",
-                            sector_number, e
-                        ),
-                    )
-                })?;
-            st.add_sector_expirations(rt.store(), params.new_expiration, &[params.sector_number])
-                .map_err(|e| {
-                    ActorError::new(
-                        ExitCode::ErrIllegalState,
-                        format!(
-                            "failed to update sector expiration: {:?}, {}",
-                            sector_number, e
-                        ),
-                    )
-                })?;
+            st.remove_sector_expirations(
+                rt.store(),
+                old_expiration,
+                &[u64::from(params.sector_number)],
+            )
+            .map_err(|e| {
+                ActorError::new(
+                    ExitCode::ErrIllegalState,
+                    format!(
+                        "failed to update sector expiration: {:?}, {}",
+                        sector_number, e
+                    ),
+                )
+            })?;
+            st.add_sector_expirations(
+                rt.store(),
+                params.new_expiration,
+                &[u64::from(params.sector_number)],
+            )
+            .map_err(|e| {
+                ActorError::new(
+                    ExitCode::ErrIllegalState,
+                    format!(
+                        "failed to update sector expiration: {:?}, {}",
+                        sector_number, e
+                    ),
+                )
+            })?;
 
             Ok(())
         })?
@@ -889,7 +902,7 @@ impl Actor {
 
     fn terminate_sectors<BS, RT>(
         rt: &mut RT,
-        params: TerminateSectorsParams,
+        mut params: TerminateSectorsParams,
     ) -> Result<(), ActorError>
     where
         BS: BlockStore,
@@ -898,9 +911,27 @@ impl Actor {
         let st: State = rt.state()?;
         rt.validate_immediate_caller_is(std::iter::once(&st.info.worker))?;
 
+        // The sectors are only decoded once the caller is known to be the worker
+        let sectors = params.sectors.validate_mut().map_err(|e| {
+            ActorError::new(
+                ExitCode::ErrIllegalArgument,
+                format!("failed to decode sectors bitfield: {}", e),
+            )
+        })?;
+        if sectors.len() > SECTORS_MAX {
+            return Err(ActorError::new(
+                ExitCode::ErrIllegalArgument,
+                format!(
+                    "too many sectors to terminate {}, max {}",
+                    sectors.len(),
+                    SECTORS_MAX
+                ),
+            ));
+        }
+
         // Note: this cannot terminate pre-committed but un-proven sectors.
         // They must be allowed to expire (and deposit burnt).
-        terminate_sectors(rt, &params.sectors, SECTOR_TERMINATION_MANUAL)?;
+        terminate_sectors(rt, sectors, SECTOR_TERMINATION_MANUAL)?;
         Ok(())
     }
 
@@ -963,7 +994,7 @@ impl Actor {
                                 format!("invalid fault declaration: {}", e),
                             )
                         })?;
-                    Ok(decl.sectors)
+                    Ok(decl.sectors.into_inner())
                 })
                 .collect::<Result<Vec<BitField>, ActorError>>()?;
 
@@ -1118,7 +1149,7 @@ impl Actor {
                                 format!("invalid recovery declaration: {}", e),
                             )
                         })?;
-                    Ok(decl.sectors)
+                    Ok(decl.sectors.into_inner())
                 })
                 .collect::<Result<Vec<BitField>, ActorError>>()?;
 
@@ -1241,9 +1272,9 @@ impl Actor {
 
         rt.send(
             *STORAGE_POWER_ACTOR_ADDR,
-            PowerMethod::OnConsensusFault as u64,
-            Serialized::serialize(BigIntSer(&st.locked_funds))?,
-            BigInt::zero(),
+            PowerMethod::OnConsensusFault.into(),
+            Serialized::serialize(&st.locked_funds)?,
+            TokenAmount::zero(),
         )?;
 
         // TODO: terminate deals with market actor, https://github.com/filecoin-project/specs-actors/issues/279
@@ -1702,14 +1733,17 @@ fn compute_faults_from_missing_posts(
                 );
 
                 let partition_sectors =
-                    deadline_sectors.slice(part_first_sector_idx, part_sector_count)?;
+                    || deadline_sectors.try_slice_iter(part_first_sector_idx, part_sector_count);
 
                 // record newly-faulty sectors
-                let new_faults = &st.faults - &partition_sectors;
+                let new_faults =
+                    BitField::from_ranges(st.faults.ranges().difference(partition_sectors()?));
                 f_groups.push(new_faults);
 
                 // record failed recoveries
-                let failed_recovery = &st.recoveries & &partition_sectors;
+                let failed_recovery = BitField::from_ranges(
+                    st.recoveries.ranges().intersection(partition_sectors()?),
+                );
                 r_groups.push(failed_recovery);
             }
         }
@@ -1806,7 +1840,7 @@ where
             sectors
                 .iter()
                 .try_for_each(|i| {
-                    let sec_num = i as u64;
+                    let sec_num = SectorNumber::new(i as u64)?;
                     let sector = match st.get_precommitted_sector(rt.store(), sec_num)? {
                         Some(sec) => sec,
                         // Already committed/deleted
@@ -1884,7 +1918,7 @@ where
             .try_for_each(|i| {
                 let i = i as u64;
                 let sector = st
-                    .get_sector(rt.store(), i)?
+                    .get_sector(rt.store(), SectorNumber::new(i)?)?
                     .ok_or_else(|| format!("no sector found: {}", i))?;
 
                 deal_ids.extend_from_slice(&sector.info.deal_ids);
@@ -1980,7 +2014,7 @@ where
     })?;
     rt.send(
         *STORAGE_POWER_ACTOR_ADDR,
-        PowerMethod::EnrollCronEvent as u64,
+        PowerMethod::EnrollCronEvent.into(),
         ser_params,
         TokenAmount::zero(),
     )?;
@@ -2009,7 +2043,7 @@ where
 
     rt.send(
         *STORAGE_POWER_ACTOR_ADDR,
-        PowerMethod::OnFaultBegin as u64,
+        PowerMethod::OnFaultBegin.into(),
         ser_params,
         TokenAmount::zero(),
     )?;
@@ -2037,13 +2071,64 @@ where
 
     rt.send(
         *STORAGE_POWER_ACTOR_ADDR,
-        PowerMethod::OnFaultEnd as u64,
+        PowerMethod::OnFaultEnd.into(),
         ser_params,
         TokenAmount::zero(),
     )?;
     Ok(())
 }
 
+/// Activates the deals of a sector being proven with the market actor, returning the
+/// sector's deal weight and verified deal weight. Before `NetworkVersion::V7` this goes
+/// through the single-sector `VerifyDealsOnSectorProveCommit`, which keeps its own weights.
+fn activate_sector_deals<BS, RT>(
+    rt: &mut RT,
+    deal_ids: Vec<DealID>,
+    sector_expiry: ChainEpoch,
+) -> Result<(DealWeight, DealWeight), ActorError>
+where
+    BS: BlockStore,
+    RT: Runtime<BS>,
+{
+    if rt.network_version() < NetworkVersion::V7 {
+        let ret: VerifyDealsOnSectorProveCommitReturn = rt
+            .send(
+                *STORAGE_MARKET_ACTOR_ADDR,
+                MarketMethod::VerifyDealsOnSectorProveCommit.into(),
+                Serialized::serialize(VerifyDealsOnSectorProveCommitParams {
+                    deal_ids,
+                    sector_expiry,
+                })?,
+                TokenAmount::zero(),
+            )?
+            .deserialize()?;
+        return Ok((ret.deal_weight, ret.verified_deal_weight));
+    }
+
+    let weights = rt
+        .send(
+            *STORAGE_MARKET_ACTOR_ADDR,
+            MarketMethod::VerifyDealsForActivation.into(),
+            Serialized::serialize(VerifyDealsForActivationParams {
+                sectors: vec![SectorDeals {
+                    deal_ids,
+                    sector_expiry,
+                }],
+            })?,
+            TokenAmount::zero(),
+        )?
+        .deserialize::<VerifyDealsForActivationReturn>()?
+        .sectors
+        .pop()
+        .ok_or_else(|| {
+            ActorError::new(
+                ExitCode::ErrIllegalState,
+                "no deal weights returned for sector".to_owned(),
+            )
+        })?;
+    Ok((weights.deal_weight, weights.verified_deal_weight))
+}
+
 fn request_terminate_deals<BS, RT>(rt: &mut RT, deal_ids: Vec<DealID>) -> Result<(), ActorError>
 where
     BS: BlockStore,
@@ -2055,7 +2140,7 @@ where
 
     rt.send(
         *STORAGE_MARKET_ACTOR_ADDR,
-        MarketMethod::OnMinerSectorsTerminate as u64,
+        MarketMethod::OnMinerSectorsTerminate.into(),
         Serialized::serialize(OnMinerSectorsTerminateParams { deal_ids })?,
         TokenAmount::zero(),
     )?;
@@ -2086,7 +2171,7 @@ where
 
     rt.send(
         *STORAGE_POWER_ACTOR_ADDR,
-        PowerMethod::OnSectorTerminate as u64,
+        PowerMethod::OnSectorTerminate.into(),
         ser_params,
         TokenAmount::zero(),
     )?;
@@ -2103,16 +2188,16 @@ where
     BS: BlockStore,
     RT: Runtime<BS>,
 {
-    let miner_actor_id: u64 = if let Payload::ID(i) = rt.message().receiver().payload() {
-        *i
-    } else {
-        panic!("could not provide ID address");
-    };
+    let miner_actor_id = rt
+        .message()
+        .receiver()
+        .id()
+        .expect("could not provide ID address");
 
     // Regenerate challenge randomness, which must match that generated for the proof.
     let entropy = rt.message().receiver().marshal_cbor().unwrap();
     let randomness: PoStRandomness =
-        rt.get_randomness(WindowedPoStChallengeSeed, challenge_epoch, &entropy)?;
+        rt.get_randomness_from_beacon(WindowedPoStChallengeSeed, challenge_epoch, &entropy)?;
 
     let challenged_sectors = sectors.iter().map(|s| s.to_sector_info()).collect();
 
@@ -2121,7 +2206,7 @@ where
         randomness,
         proofs,
         challenged_sectors,
-        prover: miner_actor_id,
+        prover: ActorID(miner_actor_id),
     };
 
     // verify the post proof
@@ -2162,15 +2247,15 @@ where
 
     let commd = request_unsealed_sector_cid(rt, params.registered_proof, params.deal_ids.clone())?;
 
-    let miner_actor_id: u64 = if let Payload::ID(i) = rt.message().receiver().payload() {
-        *i
-    } else {
-        panic!("could not provide ID address");
-    };
+    let miner_actor_id = rt
+        .message()
+        .receiver()
+        .id()
+        .expect("could not provide ID address");
     let entropy = rt.message().receiver().marshal_cbor().unwrap();
     let randomness: SealRandom =
-        rt.get_randomness(SealRandomness, params.seal_rand_epoch, &entropy)?;
-    let interactive_randomness: InteractiveSealRandomness = rt.get_randomness(
+        rt.get_randomness_from_tickets(SealRandomness, params.seal_rand_epoch, &entropy)?;
+    let interactive_randomness: InteractiveSealRandomness = rt.get_randomness_from_beacon(
         InteractiveSealChallengeSeed,
         params.interactive_epoch,
         &entropy,
@@ -2179,7 +2264,7 @@ where
     Ok(SealVerifyInfo {
         registered_proof: params.registered_proof,
         sector_id: SectorID {
-            miner: miner_actor_id,
+            miner: ActorID(miner_actor_id),
             number: params.sector_num,
         },
         deal_ids: params.deal_ids,
@@ -2202,7 +2287,7 @@ where
 {
     let ret = rt.send(
         *STORAGE_MARKET_ACTOR_ADDR,
-        MarketMethod::ComputeDataCommitment as u64,
+        MarketMethod::ComputeDataCommitment.into(),
         Serialized::serialize(ComputeDataCommitmentParams {
             sector_type,
             deal_ids,
@@ -2218,28 +2303,8 @@ where
     RT: Runtime<BS>,
 {
     rt.transaction(|st: &mut State, rt| {
-        if st.info.pending_worker_key.is_none() {
-            return Err(ActorError::new(
-                ExitCode::ErrIllegalState,
-                "No pending key change.".to_string(),
-            ));
-        }
-        if let Some(worker_key) = &st.info.pending_worker_key {
-            if worker_key.effective_at > rt.curr_epoch() {
-                return Err(ActorError::new(
-                    ExitCode::ErrIllegalState,
-                    format!(
-                        "Too early for key change. Current: {}, Change: {}",
-                        rt.curr_epoch(),
-                        worker_key.effective_at
-                    ),
-                ));
-            }
-
-            st.info.worker = worker_key.new_worker;
-            st.info.pending_worker_key = None;
-        }
-
+        st.info.worker =
+            PendingChange::confirm(&mut st.info.pending_worker_key, rt.curr_epoch(), None)?;
         Ok(())
     })?
 }
@@ -2277,6 +2342,29 @@ where
     Ok(resolved)
 }
 
+/// Resolves a control address to an ID address and verifies that it is the address of a principal actor.
+fn resolve_control_address<BS, RT>(rt: &RT, raw: Address) -> Result<Address, ActorError>
+where
+    BS: BlockStore,
+    RT: Runtime<BS>,
+{
+    let resolved = rt
+        .resolve_address(&raw)?
+        .ok_or_else(|| actor_error!(ErrIllegalArgument; "unable to resolve address: {}", raw))?;
+    assert!(resolved.protocol() == Protocol::ID);
+
+    let control_code = rt
+        .get_actor_code_cid(&resolved)?
+        .ok_or_else(|| actor_error!(ErrIllegalArgument; "no code for address: {}", resolved))?;
+    if !is_principal(&control_code) {
+        return Err(actor_error!(ErrIllegalArgument;
+            "control actor type must be a principal, was {}", control_code
+        ));
+    }
+
+    Ok(resolved)
+}
+
 /// Resolves an address to an ID address and verifies that it is address of an account actor with an associated BLS key.
 /// The worker must be BLS since the worker key will be used alongside a BLS-VRF.
 fn resolve_worker_address<BS, RT>(rt: &mut RT, raw: Address) -> Result<Address, ActorError>
@@ -2300,7 +2388,7 @@ where
     if raw.protocol() != Protocol::BLS {
         let ret = rt.send(
             resolved,
-            AccountMethod::PubkeyAddress as u64,
+            AccountMethod::PubkeyAddress.into(),
             Serialized::default(),
             TokenAmount::zero(),
         )?;
@@ -2335,7 +2423,7 @@ where
     BS: BlockStore,
     RT: Runtime<BS>,
 {
-    if amount > BigInt::zero() {
+    if amount > TokenAmount::zero() {
         rt.send(
             *BURNT_FUNDS_ACTOR_ADDR,
             METHOD_SEND,
@@ -2345,7 +2433,7 @@ where
     }
     Ok(())
 }
-fn notify_pledge_change<BS, RT>(rt: &mut RT, pledge_delta: &BigInt) -> Result<(), ActorError>
+fn notify_pledge_change<BS, RT>(rt: &mut RT, pledge_delta: &TokenAmount) -> Result<(), ActorError>
 where
     BS: BlockStore,
     RT: Runtime<BS>,
@@ -2353,8 +2441,8 @@ where
     if !pledge_delta.is_zero() {
         rt.send(
             *STORAGE_POWER_ACTOR_ADDR,
-            PowerMethod::UpdatePledgeTotal as u64,
-            Serialized::serialize(BigIntSer(pledge_delta))?,
+            PowerMethod::UpdatePledgeTotal.into(),
+            Serialized::serialize(pledge_delta)?,
             TokenAmount::zero(),
         )?;
     }
@@ -2455,7 +2543,7 @@ fn unlock_penalty<BS>(
 where
     BS: BlockStore,
 {
-    let mut fee = BigInt::zero();
+    let mut fee = TokenAmount::zero();
     for s in sectors {
         fee += f(s)
     }
@@ -2479,7 +2567,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 check_empty_params(params)?;
                 Self::constructor(rt, params.deserialize()?)?;
@@ -2535,8 +2623,7 @@ impl ActorCode for Actor {
                 Ok(Serialized::default())
             }
             Some(Method::AddLockedFund) => {
-                let BigIntDe(param) = params.deserialize()?;
-                Self::add_locked_fund(rt, param)?;
+                Self::add_locked_fund(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
             }
             Some(Method::ReportConsensusFault) => {
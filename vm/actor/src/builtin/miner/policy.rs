@@ -85,9 +85,15 @@ pub const FAULT_MAX_AGE: ChainEpoch = WPOST_PROVING_PERIOD * 14 - 1;
 /// Staging period for a miner worker key change.
 pub const WORKER_KEY_CHANGE_DELAY: ChainEpoch = 2 * ELECTION_LOOKBACK; // PARAM_FINISH
 
+/// Maximum number of bytes of a miner's libp2p peer ID.
+pub const MAX_PEER_ID_LENGTH: usize = 128;
+
+/// Maximum number of bytes of all of a miner's multiaddrs together.
+pub const MAX_MULTIADDR_DATA: usize = 1024;
+
 /// Deposit per sector required at pre-commitment, refunded after the commitment is proven (else burned).
 pub fn precommit_deposit(sector_size: SectorSize, _duration: ChainEpoch) -> TokenAmount {
-    let deposit_per_byte = BigInt::zero(); // PARAM_FINISH
+    let deposit_per_byte = TokenAmount::zero(); // PARAM_FINISH
     deposit_per_byte * BigInt::from(sector_size as u64)
 }
 
@@ -97,15 +103,15 @@ struct BigFrac {
 }
 
 pub fn pledge_penalty_for_sector_termination(_sector: &SectorOnChainInfo) -> TokenAmount {
-    BigInt::zero() // PARAM_FINISH
+    TokenAmount::zero() // PARAM_FINISH
 }
 /// Penalty to locked pledge collateral for a "skipped" sector or missing PoSt fault.
 pub fn pledge_penalty_for_sector_undeclared_fault(_sector: &SectorOnChainInfo) -> TokenAmount {
-    BigInt::zero() // PARAM_FINISH
+    TokenAmount::zero() // PARAM_FINISH
 }
 /// Penalty to locked pledge collateral for a declared or on-going sector fault.
 pub fn pledge_penalty_for_sector_declared_fault(_sector: &SectorOnChainInfo) -> TokenAmount {
-    BigInt::zero() // PARAM_FINISH
+    TokenAmount::zero() // PARAM_FINISH
 }
 /// Specification for a linear vesting schedule.
 pub struct VestSpec {
@@ -154,7 +160,7 @@ pub fn reward_for_consensus_slash_report(
         .denominator
         .pow(&elapsed);
     let num =
-        (slasher_share_numerator * consensus_fault_reporter_initial_share.numerator) * &collateral;
+        &collateral * (slasher_share_numerator * consensus_fault_reporter_initial_share.numerator);
     let denom = slasher_share_denominator * consensus_fault_reporter_initial_share.denominator;
 
     std::cmp::min(
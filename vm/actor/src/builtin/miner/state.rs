@@ -4,39 +4,37 @@
 use super::deadlines::{compute_proving_period_deadline, DeadlineInfo};
 use super::policy::*;
 use super::types::*;
-use crate::{power, u64_key, BytesKey, HAMT_BIT_WIDTH};
+use crate::{power, BytesKey, MapKey, UintKey, HAMT_BIT_WIDTH};
 use address::Address;
 use ahash::AHashSet;
 use bitfield::BitField;
 use cid::{multihash::Blake2b256, Cid};
 use clock::ChainEpoch;
 use encoding::{serde_bytes, tuple::*, Cbor};
-use fil_types::{RegisteredSealProof, SectorInfo, SectorNumber, SectorSize};
+use fil_types::{RegisteredPoStProof, RegisteredSealProof, SectorInfo, SectorNumber, SectorSize};
 use ipld_amt::{Amt, Error as AmtError};
 use ipld_blockstore::BlockStore;
 use ipld_hamt::{Error as HamtError, Hamt};
-use num_bigint::bigint_ser::{self, BigIntDe};
 use num_bigint::BigInt;
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
 use num_traits::Zero;
+use runtime::StateObject;
 use vm::TokenAmount;
 
 // Balance of Miner Actor should be greater than or equal to
 // the sum of PreCommitDeposits and LockedFunds.
 // Excess balance as computed by st.GetAvailableBalance will be
 // withdrawable or usable for pre-commit deposit or pledge lock-up.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     /// Contains static info about this miner
     // TODO revisit as will likely change to Cid in future
     pub info: MinerInfo,
 
     /// Total funds locked as pre_commit_deposit
-    #[serde(with = "bigint_ser")]
     pub pre_commit_deposit: TokenAmount,
     /// Total unvested funds locked as pledge collateral
-    #[serde(with = "bigint_ser")]
     pub locked_funds: TokenAmount,
     /// Array, AMT[ChainEpoch]TokenAmount
     vesting_funds: Cid,
@@ -99,6 +97,7 @@ pub struct State {
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
 
 impl State {
     #[allow(clippy::too_many_arguments)]
@@ -108,21 +107,26 @@ impl State {
         empty_deadlines: Cid,
         owner: Address,
         worker: Address,
+        control_addresses: Vec<Address>,
         peer_id: Vec<u8>,
-        multi_address: Vec<u8>,
+        multi_address: Vec<Multiaddr>,
         seal_proof_type: RegisteredSealProof,
         period_start: ChainEpoch,
     ) -> Result<Self, String> {
+        check_peer_info(&peer_id, &multi_address)?;
+        let window_post_proof_type = seal_proof_type.registered_window_post_proof()?;
         let sector_size = seal_proof_type.sector_size()?;
         let window_post_partition_sectors = seal_proof_type.window_post_partitions_sector()?;
         Ok(Self {
             info: MinerInfo {
                 owner,
                 worker,
+                control_addresses,
                 pending_worker_key: None,
                 peer_id,
                 multi_address,
                 seal_proof_type,
+                window_post_proof_type,
                 sector_size,
                 window_post_partition_sectors,
             },
@@ -170,7 +174,7 @@ impl State {
     ) -> Result<(), HamtError> {
         let mut precommitted =
             Hamt::load_with_bit_width(&self.pre_committed_sectors, store, HAMT_BIT_WIDTH)?;
-        precommitted.set(u64_key(info.info.sector_number), info)?;
+        precommitted.set(UintKey(info.info.sector_number.into()).key(), info)?;
 
         self.pre_committed_sectors = precommitted.flush()?;
         Ok(())
@@ -185,7 +189,7 @@ impl State {
             store,
             HAMT_BIT_WIDTH,
         )?;
-        precommitted.get(&u64_key(sector_num))
+        precommitted.get(&UintKey(sector_num.into()).key())
     }
     pub fn delete_precommitted_sector<BS: BlockStore>(
         &mut self,
@@ -197,7 +201,7 @@ impl State {
             store,
             HAMT_BIT_WIDTH,
         )?;
-        precommitted.delete(&u64_key(sector_num))?;
+        precommitted.delete(&UintKey(sector_num.into()).key())?;
 
         self.pre_committed_sectors = precommitted.flush()?;
         Ok(())
@@ -208,7 +212,7 @@ impl State {
         sector_num: SectorNumber,
     ) -> Result<bool, AmtError> {
         let sectors = Amt::<SectorOnChainInfo, _>::load(&self.sectors, store)?;
-        Ok(sectors.get(sector_num)?.is_some())
+        Ok(sectors.get(sector_num.into())?.is_some())
     }
     pub fn put_sector<BS: BlockStore>(
         &mut self,
@@ -216,7 +220,7 @@ impl State {
         sector: SectorOnChainInfo,
     ) -> Result<(), AmtError> {
         let mut sectors = Amt::load(&self.sectors, store)?;
-        sectors.set(sector.info.sector_number, sector)?;
+        sectors.set(sector.info.sector_number.into(), sector)?;
 
         self.sectors = sectors.flush()?;
         Ok(())
@@ -227,7 +231,7 @@ impl State {
         sector_num: SectorNumber,
     ) -> Result<Option<SectorOnChainInfo>, AmtError> {
         let sectors = Amt::<SectorOnChainInfo, _>::load(&self.sectors, store)?;
-        sectors.get(sector_num)
+        sectors.get(sector_num.into())
     }
     pub fn delete_sector<BS: BlockStore>(
         &mut self,
@@ -256,7 +260,7 @@ impl State {
     pub fn add_new_sectors(&mut self, sector_nos: &[SectorNumber]) -> Result<(), String> {
         let mut ns = BitField::new();
         for &sector in sector_nos {
-            ns.set(sector as usize)
+            ns.set(u64::from(sector) as usize)
         }
         self.new_sectors |= &ns;
 
@@ -488,7 +492,7 @@ impl State {
     ) -> Result<Vec<SectorOnChainInfo>, String> {
         let mut sector_infos: Vec<SectorOnChainInfo> = Vec::new();
         for i in sectors.iter() {
-            let key = i as u64;
+            let key = SectorNumber::new(i as u64)?;
             let sector_on_chain = self
                 .get_sector(store, key)?
                 .ok_or(format!("sector not found: {}", i))?;
@@ -521,7 +525,7 @@ impl State {
             store,
             &proven_sectors,
             &expected_faults,
-            good_sector_no as u64,
+            SectorNumber::new(good_sector_no as u64)?,
         )?;
 
         Ok((sector_infos, recoveries))
@@ -616,7 +620,7 @@ impl State {
         let vest_begin = current_epoch + spec.initial_delay;
         let vest_period = BigInt::from(spec.vest_period as u64);
         let mut e = vest_begin + spec.step_duration;
-        let mut vested_so_far = BigInt::zero();
+        let mut vested_so_far = TokenAmount::zero();
 
         while &vested_so_far < vesting_sum {
             let vest_epoch = quantize_up(e, spec.quantization);
@@ -634,10 +638,11 @@ impl State {
 
             // Load existing entry, else set a new one
             if let Some(locked_fund_entry) = vesting_funds.get(vest_epoch as u64)? {
-                let mut locked_funds = BigInt::from(locked_fund_entry);
+                let mut locked_funds = TokenAmount::from(locked_fund_entry);
                 locked_funds += vest_this_time;
 
-                let num = ToPrimitive::to_u64(&locked_funds)
+                let num = locked_funds
+                    .to_u64()
                     .ok_or("unable to convert to u64")
                     .unwrap();
                 vesting_funds.set(vest_epoch as u64, num)?;
@@ -659,16 +664,16 @@ impl State {
         current_epoch: ChainEpoch,
         target: TokenAmount,
     ) -> Result<TokenAmount, String> {
-        let mut vesting_funds: Amt<BigIntDe, _> = Amt::load(&self.vesting_funds, store)?;
+        let mut vesting_funds: Amt<TokenAmount, _> = Amt::load(&self.vesting_funds, store)?;
 
         let mut amount_unlocked = TokenAmount::default();
         let mut to_del: Vec<u64> = Vec::new();
 
-        let mut set: Vec<(u64, BigIntDe)> = Vec::new();
+        let mut set: Vec<(u64, TokenAmount)> = Vec::new();
         vesting_funds.for_each(|k, v| {
             if amount_unlocked > target {
                 if k >= current_epoch as u64 {
-                    let BigIntDe(mut locked_entry) = v.clone();
+                    let mut locked_entry = v.clone();
                     let unlock_amount =
                         std::cmp::min(target.clone() - &amount_unlocked, locked_entry.clone());
                     amount_unlocked += &unlock_amount;
@@ -677,7 +682,7 @@ impl State {
                     if locked_entry.is_zero() {
                         to_del.push(k);
                     } else {
-                        set.push((k, BigIntDe(locked_entry)));
+                        set.push((k, locked_entry));
                     }
                 }
             } else {
@@ -706,15 +711,14 @@ impl State {
         store: &BS,
         current_epoch: ChainEpoch,
     ) -> Result<TokenAmount, String> {
-        let mut vesting_funds: Amt<BigIntDe, _> = Amt::load(&self.vesting_funds, store)?;
+        let mut vesting_funds: Amt<TokenAmount, _> = Amt::load(&self.vesting_funds, store)?;
 
         let mut amount_unlocked = TokenAmount::default();
         let mut to_del: Vec<u64> = Vec::new();
 
         vesting_funds.for_each(|k, v| {
             if k < current_epoch as u64 {
-                let BigIntDe(locked_entry) = v;
-                amount_unlocked += locked_entry;
+                amount_unlocked += v;
                 to_del.push(k);
             } else {
                 // stop iterating
@@ -737,13 +741,12 @@ impl State {
         store: &BS,
         current_epoch: ChainEpoch,
     ) -> Result<TokenAmount, String> {
-        let vesting_funds: Amt<BigIntDe, _> = Amt::load(&self.vesting_funds, store)?;
+        let vesting_funds: Amt<TokenAmount, _> = Amt::load(&self.vesting_funds, store)?;
 
         let mut amount_unlocked = TokenAmount::default();
         vesting_funds.for_each(|k, v| {
             if k < current_epoch as u64 {
-                let BigIntDe(locked_entry) = v.clone();
-                amount_unlocked += locked_entry;
+                amount_unlocked += v;
             } else {
                 // stop iterating
                 return Err("finished".to_string());
@@ -764,7 +767,7 @@ impl State {
 }
 
 /// Static information about miner
-#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct MinerInfo {
     /// Account that owns this miner
     /// - Income and returned collateral are paid to this address
@@ -777,6 +780,10 @@ pub struct MinerInfo {
     /// other day to day miner activities
     pub worker: Address,
 
+    /// Additional addresses which are permitted to submit messages controlling actions
+    /// on behalf of this miner
+    pub control_addresses: Vec<Address>,
+
     /// Optional worker key to update at an epoch
     pub pending_worker_key: Option<WorkerKeyChange>,
 
@@ -784,12 +791,14 @@ pub struct MinerInfo {
     #[serde(with = "serde_bytes")]
     pub peer_id: Vec<u8>,
     /// Slice of byte arrays representing Libp2p multi-addresses used for establishing a connection with this miner.
-    #[serde(with = "serde_bytes")]
-    pub multi_address: Vec<u8>,
+    pub multi_address: Vec<Multiaddr>,
 
     /// The proof type used by this miner for sealing sectors.
     pub seal_proof_type: RegisteredSealProof,
 
+    /// The proof type used by this miner for Window PoSt, which matches the seal proof type.
+    pub window_post_proof_type: RegisteredPoStProof,
+
     /// Amount of space in each sector committed to the network by this miner
     pub sector_size: SectorSize,
 
@@ -798,6 +807,34 @@ pub struct MinerInfo {
     pub window_post_partition_sectors: u64,
 }
 
+/// Checks that the peer ID and multiaddrs of a miner are within the size limits, and that
+/// none of the multiaddrs are empty.
+pub fn check_peer_info(peer_id: &[u8], multiaddrs: &[Multiaddr]) -> Result<(), String> {
+    if peer_id.len() > MAX_PEER_ID_LENGTH {
+        return Err(format!(
+            "peer ID size of {} exceeds maximum size of {}",
+            peer_id.len(),
+            MAX_PEER_ID_LENGTH
+        ));
+    }
+
+    let mut total_size = 0;
+    for ma in multiaddrs {
+        if ma.0.is_empty() {
+            return Err("invalid empty multiaddr".to_owned());
+        }
+        total_size += ma.0.len();
+    }
+    if total_size > MAX_MULTIADDR_DATA {
+        return Err(format!(
+            "multiaddr size of {} exceeds maximum of {}",
+            total_size, MAX_MULTIADDR_DATA
+        ));
+    }
+
+    Ok(())
+}
+
 impl SectorOnChainInfo {
     pub fn new(
         info: SectorPreCommitInfo,
@@ -843,6 +880,8 @@ pub struct Deadlines {
     pub due: Vec<BitField>,
 }
 
+impl Cbor for Deadlines {}
+
 impl Default for Deadlines {
     fn default() -> Self {
         Self::new()
@@ -884,7 +923,10 @@ impl Deadlines {
 // Misc helpers
 //
 
-fn delete_many<BS: BlockStore>(amt: &mut Amt<BigIntDe, BS>, keys: &[u64]) -> Result<(), AmtError> {
+fn delete_many<BS: BlockStore>(
+    amt: &mut Amt<TokenAmount, BS>,
+    keys: &[u64],
+) -> Result<(), AmtError> {
     for &i in keys {
         amt.delete(i)?;
     }
@@ -907,19 +949,41 @@ mod tests {
     use encoding::{from_slice, to_vec};
     use libp2p::PeerId;
 
+    fn multiaddr(s: &str) -> Multiaddr {
+        s.parse::<libp2p::Multiaddr>().unwrap().to_vec().into()
+    }
+
     #[test]
     fn miner_info_serialize() {
+        let seal_proof_type = RegisteredSealProof::StackedDRG2KiBV1;
         let info = MinerInfo {
             owner: Address::new_id(2),
             worker: Address::new_id(3),
+            control_addresses: vec![Address::new_id(4)],
             pending_worker_key: None,
             peer_id: PeerId::random().into_bytes(),
-            multi_address: PeerId::random().into_bytes(),
-            sector_size: SectorSize::_2KiB,
-            seal_proof_type: RegisteredSealProof::from(1),
-            window_post_partition_sectors: 0,
+            multi_address: vec![
+                multiaddr("/ip4/127.0.0.1/tcp/2345"),
+                multiaddr("/ip6/::1/tcp/2345"),
+            ],
+            sector_size: seal_proof_type.sector_size().unwrap(),
+            seal_proof_type,
+            window_post_proof_type: seal_proof_type.registered_window_post_proof().unwrap(),
+            window_post_partition_sectors: seal_proof_type.window_post_partitions_sector().unwrap(),
         };
         let bz = to_vec(&info).unwrap();
         assert_eq!(from_slice::<MinerInfo>(&bz).unwrap(), info);
     }
+
+    #[test]
+    fn peer_info_limits() {
+        let peer_id = PeerId::random().into_bytes();
+        let ma = multiaddr("/ip4/127.0.0.1/tcp/2465");
+        assert!(check_peer_info(&peer_id, &[ma.clone(), ma.clone()]).is_ok());
+        assert!(check_peer_info(&[], &[]).is_ok());
+
+        assert!(check_peer_info(&[0; MAX_PEER_ID_LENGTH + 1], &[]).is_err());
+        assert!(check_peer_info(&peer_id, &[ma.clone(), Vec::new().into()]).is_err());
+        assert!(check_peer_info(&peer_id, &[ma, vec![1; MAX_MULTIADDR_DATA].into()]).is_err());
+    }
 }
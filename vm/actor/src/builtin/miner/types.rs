@@ -1,14 +1,16 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::SECTORS_MAX;
+use crate::PendingChange;
 use address::Address;
-use bitfield::BitField;
+use bitfield::{BitField, BoundedBitField, UnvalidatedBitField};
 use cid::Cid;
 use clock::ChainEpoch;
 use encoding::{serde_bytes, tuple::*};
-use fil_types::{PoStProof, RegisteredSealProof, SectorNumber};
-use num_bigint::bigint_ser;
+use fil_types::{PartitionNumber, PoStProof, RegisteredSealProof, SectorNumber};
 use num_bigint::{biguint_ser, BigUint};
+use serde::{Deserialize, Serialize};
 use vm::{DealID, TokenAmount};
 
 pub type CronEvent = i64;
@@ -16,17 +18,29 @@ pub const CRON_EVENT_WORKER_KEY_CHANGE: CronEvent = 1;
 pub const CRON_EVENT_PRE_COMMIT_EXPIRY: CronEvent = 2;
 pub const CRON_EVENT_PROVING_PERIOD: CronEvent = 3;
 
+/// Bytes of a libp2p multiaddr of a miner. Multiaddrs are only checked for their size on chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Multiaddr(#[serde(with = "serde_bytes")] pub Vec<u8>);
+
+impl From<Vec<u8>> for Multiaddr {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
 /// Storage miner actor constructor params are defined here so the power actor can send them to the init actor
 /// to instantiate miners.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct MinerConstructorParams {
     pub owner: Address,
     pub worker: Address,
+    #[serde(with = "address::cbor::vec")]
+    pub control_addresses: Vec<Address>,
     pub seal_proof_type: RegisteredSealProof,
     #[serde(with = "serde_bytes")]
     pub peer_id: Vec<u8>,
-    #[serde(with = "serde_bytes")]
-    pub multi_address: Vec<u8>,
+    pub multi_address: Vec<Multiaddr>,
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct CronEventPayload {
@@ -37,6 +51,8 @@ pub struct CronEventPayload {
 pub struct GetControlAddressesReturn {
     pub owner: Address,
     pub worker: Address,
+    #[serde(with = "address::cbor::vec")]
+    pub control_addresses: Vec<Address>,
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ChangeWorkerAddressParams {
@@ -49,8 +65,7 @@ pub struct ChangePeerIDParams {
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ChangeMultiaddrsParams {
-    #[serde(with = "serde_bytes")]
-    pub new_multi_addrs: Vec<u8>,
+    pub new_multi_addrs: Vec<Multiaddr>,
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConfirmSectorProofsParams {
@@ -64,7 +79,7 @@ pub struct SubmitWindowedPoStParams {
     /// The partition indices being proven.
     /// Partitions are counted across all deadlines, such that all partition indices in the second deadline are greater
     /// than the partition numbers in the first deadlines.
-    pub partitions: Vec<u64>,
+    pub partitions: Vec<PartitionNumber>,
     /// Array of proofs, one per distinct registered proof type present in the sectors being proven.
     /// In the usual case of a single proof type, this array will always have a single element (independent of number of partitions).
     pub proofs: Vec<PoStProof>,
@@ -88,7 +103,7 @@ pub struct ExtendSectorExpirationParams {
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct TerminateSectorsParams {
-    pub sectors: BitField,
+    pub sectors: UnvalidatedBitField,
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct DeclareFaultsParams {
@@ -97,7 +112,7 @@ pub struct DeclareFaultsParams {
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct FaultDeclaration {
     pub deadline: u64, // In range [0..WPoStPeriodDeadlines)
-    pub sectors: BitField,
+    pub sectors: BoundedBitField<SECTORS_MAX>,
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct DeclareFaultsRecoveredParams {
@@ -106,7 +121,7 @@ pub struct DeclareFaultsRecoveredParams {
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct RecoveryDeclaration {
     pub deadline: u64, // In range [0..WPoStPeriodDeadlines)
-    pub sectors: BitField,
+    pub sectors: BoundedBitField<SECTORS_MAX>,
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ReportConsensusFaultParams {
@@ -119,15 +134,10 @@ pub struct ReportConsensusFaultParams {
 }
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct WithdrawBalanceParams {
-    #[serde(with = "bigint_ser")]
     pub amount_requested: TokenAmount,
 }
-#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
-pub struct WorkerKeyChange {
-    /// Must be an ID address
-    pub new_worker: Address,
-    pub effective_at: ChainEpoch,
-}
+/// Pending change of the miner worker key, the new worker must be an ID address.
+pub type WorkerKeyChange = PendingChange<Address>;
 #[derive(Debug, PartialEq, Clone, Serialize_tuple, Deserialize_tuple)]
 pub struct SectorPreCommitInfo {
     pub registered_proof: RegisteredSealProof,
@@ -142,7 +152,6 @@ pub struct SectorPreCommitInfo {
 #[derive(Debug, PartialEq, Clone, Serialize_tuple, Deserialize_tuple)]
 pub struct SectorPreCommitOnChainInfo {
     pub info: SectorPreCommitInfo,
-    #[serde(with = "bigint_ser")]
     pub pre_commit_deposit: TokenAmount,
     pub pre_commit_epoch: ChainEpoch,
 }
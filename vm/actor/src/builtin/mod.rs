@@ -6,6 +6,7 @@ mod codes;
 pub mod cron;
 pub mod init;
 pub mod market;
+mod methods;
 pub mod miner;
 pub mod multisig;
 pub mod network;
@@ -18,5 +19,6 @@ pub mod system;
 pub mod verifreg;
 
 pub use self::codes::*;
+pub use self::methods::*;
 pub(crate) use self::shared::*;
 pub use self::singletons::*;
@@ -6,8 +6,11 @@ mod types;
 
 pub use self::state::State;
 pub use self::types::*;
-use crate::{make_map, CALLER_TYPES_SIGNABLE, INIT_ACTOR_ADDR};
+use crate::network::EPOCHS_IN_DAY;
+use crate::{check_empty_params, make_map, PendingChange, CALLER_TYPES_SIGNABLE, INIT_ACTOR_ADDR};
 use address::Address;
+use clock::ChainEpoch;
+use fil_types::NetworkVersion;
 use ipld_blockstore::BlockStore;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
@@ -18,7 +21,7 @@ use vm::{ActorError, ExitCode, MethodNum, Serialized, TokenAmount, METHOD_CONSTR
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     Propose = 2,
     Approve = 3,
     Cancel = 4,
@@ -28,8 +31,14 @@ pub enum Method {
     RemoveSigner = 7,
     SwapSigner = 8,
     ChangeNumApprovalsThreshold = 9,
+    ConfirmSwapSigner = 10,
 }
 
+impl_method_num!(Method);
+
+/// Number of epochs a signer swap can be confirmed by the new signer before it lapses.
+pub const SIGNER_SWAP_EXPIRY: ChainEpoch = EPOCHS_IN_DAY;
+
 /// Multisig Actor
 pub struct Actor;
 impl Actor {
@@ -48,6 +57,12 @@ impl Actor {
             ));
         }
 
+        let signer_weights = if cfg!(feature = "weighted-multisig") {
+            Self::validate_signer_weights(rt, &params.signers, params.signer_weights)?
+        } else {
+            Vec::new()
+        };
+
         let empty_root = make_map(rt.store()).flush().map_err(|err| {
             rt.abort(
                 ExitCode::ErrIllegalState,
@@ -63,6 +78,8 @@ impl Actor {
             next_tx_id: Default::default(),
             start_epoch: Default::default(),
             unlock_duration: Default::default(),
+            pending_swap: None,
+            signer_weights,
         };
 
         if params.unlock_duration != 0 {
@@ -198,10 +215,22 @@ impl Actor {
                 ));
             }
 
-            // Add signer and increase threshold if set
-            st.signers.push(params.signer);
+            let weight = if cfg!(feature = "weighted-multisig") {
+                params.weight
+            } else {
+                1
+            };
+            if weight == 0 {
+                return Err(ActorError::new(
+                    ExitCode::ErrIllegalArgument,
+                    "Signer weight must be positive".to_owned(),
+                ));
+            }
+
+            // Add signer and increase threshold by its weight if set
+            st.add_signer(params.signer, weight);
             if params.increase {
-                st.num_approvals_threshold += 1;
+                st.num_approvals_threshold += weight as i64;
             }
 
             Ok(())
@@ -234,10 +263,17 @@ impl Actor {
             }
 
             // Remove signer from state
-            st.signers.retain(|s| s != &params.signer);
+            let weight = st.remove_signer(&params.signer);
 
-            // Decrease approvals threshold if decrease param or below threshold
-            if params.decrease || st.signers.len() - 1 < st.num_approvals_threshold as usize {
+            if cfg!(feature = "weighted-multisig") {
+                // Decrease approvals threshold by the signer's weight if decrease param or the
+                // remaining signers can no longer meet it
+                if params.decrease || (st.total_weight() as i64) < st.num_approvals_threshold {
+                    st.num_approvals_threshold -= weight as i64;
+                }
+            } else if params.decrease || st.signers.len() - 1 < st.num_approvals_threshold as usize
+            {
+                // Decrease approvals threshold if decrease param or below threshold
                 st.num_approvals_threshold -= 1;
             }
             Ok(())
@@ -253,7 +289,7 @@ impl Actor {
         let receiver = *rt.message().receiver();
         rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
 
-        rt.transaction::<State, _, _>(|st, _| {
+        rt.transaction::<State, _, _>(|st, rt| {
             // Check that signer to remove exists
             if !st.is_signer(&params.from) {
                 return Err(ActorError::new(
@@ -270,11 +306,70 @@ impl Actor {
                 ));
             }
 
-            // Remove signer from state
-            st.signers.retain(|s| s != &params.from);
+            if rt.network_version() < NetworkVersion::V8 {
+                // Replace the signer, the new signer takes over its weight
+                let weight = st.remove_signer(&params.from);
+                st.add_signer(params.to, weight);
+                return Ok(());
+            }
 
-            // Add new signer
-            st.signers.push(params.to);
+            // Swap is applied once the new signer confirms, this may replace another pending swap
+            st.pending_swap = Some(PendingChange::new(params, rt.curr_epoch()));
+
+            Ok(())
+        })?
+    }
+
+    /// Multisig actor function for the new signer to confirm a pending signer swap.
+    /// Swaps are only pending from `NetworkVersion::V8`.
+    pub fn confirm_swap_signer<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
+    where
+        BS: BlockStore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_type(CALLER_TYPES_SIGNABLE.iter())?;
+        let caller_addr: Address = *rt.message().caller();
+
+        rt.transaction::<State, _, _>(|st, rt| {
+            // Only the party being swapped in can confirm the swap
+            match &st.pending_swap {
+                Some(swap) if swap.value.to == caller_addr => (),
+                Some(_) => {
+                    return Err(ActorError::new(
+                        ExitCode::ErrForbidden,
+                        "Caller is not the pending signer".to_owned(),
+                    ))
+                }
+                None => {
+                    return Err(ActorError::new(
+                        ExitCode::ErrNotFound,
+                        "No pending signer swap".to_owned(),
+                    ))
+                }
+            }
+            let swap = PendingChange::confirm(
+                &mut st.pending_swap,
+                rt.curr_epoch(),
+                Some(SIGNER_SWAP_EXPIRY),
+            )?;
+
+            // Signers may have changed since the swap was proposed
+            if !st.is_signer(&swap.from) {
+                return Err(ActorError::new(
+                    ExitCode::ErrNotFound,
+                    "Party not found".to_owned(),
+                ));
+            }
+            if st.is_signer(&swap.to) {
+                return Err(ActorError::new(
+                    ExitCode::ErrIllegalArgument,
+                    "Party already present".to_owned(),
+                ));
+            }
+
+            // Replace the signer, the new signer takes over its weight
+            let weight = st.remove_signer(&swap.from);
+            st.add_signer(swap.to, weight);
 
             Ok(())
         })?
@@ -294,7 +389,7 @@ impl Actor {
 
         rt.transaction::<State, _, _>(|st, _| {
             // Check if valid threshold value
-            if params.new_threshold <= 0 || params.new_threshold as usize > st.signers.len() {
+            if params.new_threshold <= 0 || params.new_threshold as u64 > st.total_weight() {
                 return Err(ActorError::new(
                     ExitCode::ErrIllegalArgument,
                     "New threshold value not supported".to_owned(),
@@ -348,8 +443,8 @@ impl Actor {
                     ));
                 }
 
-                // Check if number approvals is met
-                if txn.approved.len() >= st.num_approvals_threshold as usize {
+                // Check if weight of approvals is met
+                if st.approval_weight(&txn.approved) >= st.num_approvals_threshold as u64 {
                     // Ensure sufficient funds
                     if let Err(e) = st.check_available(curr_bal, txn.value.clone(), curr_epoch) {
                         return Err(ActorError::new(
@@ -381,6 +476,39 @@ impl Actor {
         Ok(())
     }
 
+    /// Validates the weights given for the initial signers, each signer has a weight of
+    /// one if no weights are given.
+    fn validate_signer_weights<BS, RT>(
+        rt: &RT,
+        signers: &[Address],
+        weights: Vec<u64>,
+    ) -> Result<Vec<u64>, ActorError>
+    where
+        BS: BlockStore,
+        RT: Runtime<BS>,
+    {
+        if weights.is_empty() {
+            return Ok(vec![1; signers.len()]);
+        }
+        if weights.len() != signers.len() {
+            return Err(rt.abort(
+                ExitCode::ErrIllegalArgument,
+                format!(
+                    "Signer weights length {} does not match signers length {}",
+                    weights.len(),
+                    signers.len()
+                ),
+            ));
+        }
+        if weights.iter().any(|&w| w == 0) {
+            return Err(rt.abort(
+                ExitCode::ErrIllegalArgument,
+                "Signer weight must be positive".to_owned(),
+            ));
+        }
+        Ok(weights)
+    }
+
     fn validate_signer<BS, RT>(rt: &RT, st: &State, address: &Address) -> Result<(), ActorError>
     where
         BS: BlockStore,
@@ -405,7 +533,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
@@ -438,6 +566,11 @@ impl ActorCode for Actor {
                 Self::change_num_approvals_threshold(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
             }
+            Some(Method::ConfirmSwapSigner) if rt.network_version() >= NetworkVersion::V8 => {
+                check_empty_params(params)?;
+                Self::confirm_swap_signer(rt)?;
+                Ok(Serialized::default())
+            }
             _ => Err(rt.abort(ExitCode::SysErrInvalidMethod, "Invalid method".to_owned())),
         }
     }
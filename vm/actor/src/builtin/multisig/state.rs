@@ -1,31 +1,41 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{Transaction, TxnID};
-use crate::BytesKey;
+use super::{SwapSignerParams, Transaction, TxnID};
+use crate::{BytesKey, PendingChange};
 use address::Address;
 use cid::Cid;
 use clock::ChainEpoch;
-use encoding::{tuple::*, Cbor};
+use encoding::Cbor;
 use ipld_blockstore::BlockStore;
 use ipld_hamt::Hamt;
-use num_bigint::bigint_ser;
+use runtime::StateObject;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use vm::TokenAmount;
 
 /// Multisig actor state
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, PartialEq)]
 pub struct State {
     pub signers: Vec<Address>,
     pub num_approvals_threshold: i64,
     pub next_tx_id: TxnID,
 
     // Linear unlock
-    #[serde(with = "bigint_ser")]
     pub initial_balance: TokenAmount,
     pub start_epoch: ChainEpoch,
     pub unlock_duration: ChainEpoch,
 
     pub pending_txs: Cid,
+
+    /// Signer swap awaiting confirmation from the new signer. States without a pending swap
+    /// are serialized as the seven field tuple of the original layout, so that their state
+    /// roots are unchanged.
+    pub pending_swap: Option<PendingChange<SwapSignerParams>>,
+
+    /// Approval weight of each signer, in the same order as `signers`. Only encoded
+    /// and populated with the `weighted-multisig` feature.
+    pub signer_weights: Vec<u64>,
 }
 
 impl State {
@@ -47,6 +57,50 @@ impl State {
         false
     }
 
+    /// Returns the approval weight of a signer, zero if the address is not a signer.
+    /// Signers have a weight of one unless weighted multisig is enabled.
+    pub fn signer_weight(&self, addr: &Address) -> u64 {
+        match self.signers.iter().position(|s| s == addr) {
+            Some(i) => self.signer_weights.get(i).copied().unwrap_or(1),
+            None => 0,
+        }
+    }
+
+    /// Returns the combined weight of all signers.
+    pub fn total_weight(&self) -> u64 {
+        self.signers.iter().map(|s| self.signer_weight(s)).sum()
+    }
+
+    /// Returns the combined weight of the approvals of a transaction, which is compared
+    /// against the approvals threshold.
+    pub fn approval_weight(&self, approved: &[Address]) -> u64 {
+        if cfg!(feature = "weighted-multisig") {
+            approved.iter().map(|a| self.signer_weight(a)).sum()
+        } else {
+            approved.len() as u64
+        }
+    }
+
+    /// Adds a signer with the given approval weight.
+    pub(crate) fn add_signer(&mut self, addr: Address, weight: u64) {
+        self.signers.push(addr);
+        if cfg!(feature = "weighted-multisig") {
+            self.signer_weights.push(weight);
+        }
+    }
+
+    /// Removes a signer and returns the approval weight it had.
+    pub(crate) fn remove_signer(&mut self, addr: &Address) -> u64 {
+        let weight = self.signer_weight(addr);
+        if let Some(i) = self.signers.iter().position(|s| s == addr) {
+            self.signers.remove(i);
+            if i < self.signer_weights.len() {
+                self.signer_weights.remove(i);
+            }
+        }
+        weight
+    }
+
     pub(crate) fn check_available(
         &self,
         balance: TokenAmount,
@@ -113,3 +167,126 @@ impl State {
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
+
+impl Serialize for State {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let Self {
+            signers,
+            num_approvals_threshold,
+            next_tx_id,
+            initial_balance,
+            start_epoch,
+            unlock_duration,
+            pending_txs,
+            pending_swap,
+            signer_weights,
+        } = self;
+        if cfg!(feature = "weighted-multisig") {
+            (
+                signers,
+                num_approvals_threshold,
+                next_tx_id,
+                initial_balance,
+                start_epoch,
+                unlock_duration,
+                pending_txs,
+                pending_swap,
+                signer_weights,
+            )
+                .serialize(s)
+        } else if let Some(swap) = pending_swap {
+            (
+                signers,
+                num_approvals_threshold,
+                next_tx_id,
+                initial_balance,
+                start_epoch,
+                unlock_duration,
+                pending_txs,
+                swap,
+            )
+                .serialize(s)
+        } else {
+            (
+                signers,
+                num_approvals_threshold,
+                next_tx_id,
+                initial_balance,
+                start_epoch,
+                unlock_duration,
+                pending_txs,
+            )
+                .serialize(s)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StateVisitor;
+
+        impl<'de> de::Visitor<'de> for StateVisitor {
+            type Value = State;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a tuple of 7 to 9 multisig state fields")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<State, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let signers = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let num_approvals_threshold = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let next_tx_id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let initial_balance: TokenAmount = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let start_epoch = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(4, &self))?;
+                let unlock_duration = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(5, &self))?;
+                let pending_txs = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(6, &self))?;
+                let pending_swap = seq.next_element::<Option<_>>()?.flatten();
+                let signer_weights = if cfg!(feature = "weighted-multisig") {
+                    seq.next_element()?.unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(10, &self));
+                }
+                Ok(State {
+                    signers,
+                    num_approvals_threshold,
+                    next_tx_id,
+                    initial_balance,
+                    start_epoch,
+                    unlock_duration,
+                    pending_txs,
+                    pending_swap,
+                    signer_weights,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(StateVisitor)
+    }
+}
@@ -5,13 +5,12 @@ use crate::BytesKey;
 use address::Address;
 use clock::ChainEpoch;
 use encoding::tuple::*;
-use num_bigint::bigint_ser;
 use serde::{Deserialize, Serialize};
 use vm::{MethodNum, Serialized, TokenAmount};
 
 /// Transaction ID type
 // TODO change to uvarint encoding
-#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct TxnID(pub i64);
 
@@ -26,7 +25,6 @@ impl TxnID {
 #[derive(Clone, PartialEq, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct Transaction {
     pub to: Address,
-    #[serde(with = "bigint_ser")]
     pub value: TokenAmount,
     pub method: MethodNum,
     pub params: Serialized,
@@ -37,16 +35,20 @@ pub struct Transaction {
 /// Constructor parameters for multisig actor
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
+    #[serde(with = "address::cbor::vec")]
     pub signers: Vec<Address>,
     pub num_approvals_threshold: i64,
     pub unlock_duration: ChainEpoch,
+    /// Approval weight of each signer, defaults to one for every signer if empty.
+    /// Only encoded with the `weighted-multisig` feature.
+    #[cfg_attr(not(feature = "weighted-multisig"), serde(skip))]
+    pub signer_weights: Vec<u64>,
 }
 
 /// Propose method call parameters
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ProposeParams {
     pub to: Address,
-    #[serde(with = "bigint_ser")]
     pub value: TokenAmount,
     pub method: MethodNum,
     pub params: Serialized,
@@ -63,6 +65,10 @@ pub struct TxnIDParams {
 pub struct AddSignerParams {
     pub signer: Address,
     pub increase: bool,
+    /// Approval weight of the new signer, only encoded with the `weighted-multisig`
+    /// feature.
+    #[cfg_attr(not(feature = "weighted-multisig"), serde(skip))]
+    pub weight: u64,
 }
 
 /// Remove signer params
@@ -73,7 +79,7 @@ pub struct RemoveSignerParams {
 }
 
 /// Swap signer multisig method params
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct SwapSignerParams {
     pub from: Address,
     pub to: Address,
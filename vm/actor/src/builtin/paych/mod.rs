@@ -4,13 +4,15 @@
 mod state;
 mod types;
 
+#[cfg(feature = "json")]
+pub use self::state::json;
 pub use self::state::{LaneState, Merge, State};
 pub use self::types::*;
 use crate::{check_empty_params, ACCOUNT_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID};
 use address::Address;
+use crypto::constant_time_eq;
 use encoding::to_vec;
 use ipld_blockstore::BlockStore;
-use num_bigint::BigInt;
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Zero};
 use runtime::{ActorCode, Runtime};
@@ -23,12 +25,14 @@ use vm::{
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     UpdateChannelState = 2,
     Settle = 3,
     Collect = 4,
 }
 
+impl_method_num!(Method);
+
 /// Payment Channel actor
 pub struct Actor;
 impl Actor {
@@ -115,14 +119,8 @@ impl Actor {
         })?;
 
         // Validate signature
-        rt.syscalls()
-            .verify_signature(&sig, &signer, &sv_bz)
-            .map_err(|e| {
-                ActorError::new(
-                    ExitCode::ErrIllegalArgument,
-                    format!("voucher signature invalid: {}", e),
-                )
-            })?;
+        rt.verify_signature(&sig, &signer, &sv_bz)?
+            .map_err(|e| actor_error!(ErrIllegalArgument; "voucher signature invalid: {}", e))?;
 
         if rt.curr_epoch() < sv.time_lock_min {
             return Err(rt.abort(ExitCode::ErrIllegalArgument, "cannot use this voucher yet"));
@@ -132,12 +130,27 @@ impl Actor {
             return Err(rt.abort(ExitCode::ErrIllegalArgument, "this voucher has expired"));
         }
 
-        if !sv.secret_pre_image.is_empty() {
-            let hashed_secret: &[u8] = &rt
-                .syscalls()
-                .hash_blake2b(&params.secret)
-                .map_err(|e| *e.downcast::<ActorError>().unwrap())?;
-            if hashed_secret != sv.secret_pre_image.as_slice() {
+        let policy = policy(rt.network_version());
+        // Before prefixed secret hashes, the blake2b hash of the secret is compared with a pre
+        // image of any length, so pre images of other lengths are only rejected after hashing
+        let secret_hash = if policy.prefixed_secret_hash {
+            sv.secret_hash(&policy)
+                .map_err(|e| rt.abort(ExitCode::ErrIllegalArgument, e))?
+        } else if sv.secret_pre_image.is_empty() {
+            None
+        } else {
+            Some((
+                SecretHashFunction::Blake2b256,
+                sv.secret_pre_image.as_slice(),
+            ))
+        };
+        if let Some((hash_fn, digest)) = secret_hash {
+            let hashed_secret = match hash_fn {
+                SecretHashFunction::Blake2b256 => rt.syscalls().hash_blake2b(&params.secret),
+                SecretHashFunction::Sha256 => rt.syscalls().hash_sha256(&params.secret),
+            }
+            .map_err(|e| *e.downcast::<ActorError>().unwrap())?;
+            if !constant_time_eq(&hashed_secret, digest) {
                 return Err(ActorError::new(
                     ExitCode::ErrIllegalArgument,
                     "incorrect secret".to_owned(),
@@ -145,6 +158,12 @@ impl Actor {
             }
         }
 
+        // Validate the voucher against a copy of the current state before any side effects
+        let curr_bal = rt.current_balance()?;
+        let mut st = st;
+        Self::apply_voucher(&mut st, &sv, &curr_bal, &policy)?;
+
+        // Only call out to the extra actor once all voucher validations have passed
         if let Some(extra) = &sv.extra {
             rt.send(
                 extra.actor,
@@ -157,101 +176,112 @@ impl Actor {
             )?;
         }
 
+        // State is reloaded and the voucher is applied again after the send, as the state and
+        // balance of the channel could have been modified by the extra actor calling back into it.
         let curr_bal = rt.current_balance()?;
-        rt.transaction(|st: &mut State, _| {
-            // Find the voucher lane, create and insert it in sorted order if necessary.
-            let (idx, exists) = find_lane(&st.lane_states, sv.lane);
-            if !exists {
-                if st.lane_states.len() >= LANE_LIMIT {
-                    return Err(ActorError::new(
-                        ExitCode::ErrIllegalArgument,
-                        "lane limit exceeded".to_owned(),
-                    ));
-                }
-                let tmp_ls = LaneState {
-                    id: sv.lane,
-                    redeemed: BigInt::zero(),
-                    nonce: 0,
-                };
-                st.lane_states.insert(idx, tmp_ls);
-            };
-            // let mut ls = st.lane_states[idx].clone();
+        rt.transaction(|st: &mut State, _| Self::apply_voucher(st, &sv, &curr_bal, &policy))?
+    }
 
-            if st.lane_states[idx].nonce > sv.nonce {
+    /// Applies a validated voucher to the channel state, checking the voucher against the
+    /// lane states and the balance of the channel.
+    fn apply_voucher(
+        st: &mut State,
+        sv: &SignedVoucher,
+        curr_bal: &TokenAmount,
+        policy: &Policy,
+    ) -> Result<(), ActorError> {
+        // Find the voucher lane, create and insert it in sorted order if necessary.
+        let (idx, exists) = find_lane(&st.lane_states, sv.lane);
+        if !exists {
+            if st.lane_states.len() >= policy.lane_limit {
                 return Err(ActorError::new(
                     ExitCode::ErrIllegalArgument,
-                    "voucher has an outdated nonce, cannot redeem".to_owned(),
+                    "lane limit exceeded".to_owned(),
                 ));
             }
+            let tmp_ls = LaneState {
+                id: sv.lane,
+                redeemed: TokenAmount::zero(),
+                nonce: 0,
+            };
+            st.lane_states.insert(idx, tmp_ls);
+        };
+        // let mut ls = st.lane_states[idx].clone();
 
-            // The next section actually calculates the payment amounts to update the payment channel state
-            // 1. (optional) sum already redeemed value of all merging lanes
-            let mut redeemed = BigInt::default();
-            for merge in sv.merges {
-                if merge.lane == sv.lane {
-                    return Err(ActorError::new(
-                        ExitCode::ErrIllegalArgument,
-                        "voucher cannot merge lanes into it's own lane".to_owned(),
-                    ));
-                }
-                let (idx, exists) = find_lane(&st.lane_states, merge.lane);
-                if exists {
-                    if st.lane_states[idx].nonce >= merge.nonce {
-                        return Err(ActorError::new(
-                            ExitCode::ErrIllegalArgument,
-                            "merged lane in voucher has outdated nonce, cannot redeem".to_owned(),
-                        ));
-                    }
-
-                    redeemed += &st.lane_states[idx].redeemed;
-                    st.lane_states[idx].nonce = merge.nonce;
-                } else {
+        if st.lane_states[idx].nonce > sv.nonce {
+            return Err(ActorError::new(
+                ExitCode::ErrIllegalArgument,
+                "voucher has an outdated nonce, cannot redeem".to_owned(),
+            ));
+        }
+
+        // The next section actually calculates the payment amounts to update the payment channel state
+        // 1. (optional) sum already redeemed value of all merging lanes
+        let mut redeemed = TokenAmount::default();
+        for merge in sv.merges.iter() {
+            if merge.lane == sv.lane {
+                return Err(ActorError::new(
+                    ExitCode::ErrIllegalArgument,
+                    "voucher cannot merge lanes into it's own lane".to_owned(),
+                ));
+            }
+            let (idx, exists) = find_lane(&st.lane_states, merge.lane);
+            if exists {
+                if st.lane_states[idx].nonce >= merge.nonce {
                     return Err(ActorError::new(
                         ExitCode::ErrIllegalArgument,
-                        format!("voucher specifies invalid merge lane {}", merge.lane),
+                        "merged lane in voucher has outdated nonce, cannot redeem".to_owned(),
                     ));
                 }
+
+                redeemed += &st.lane_states[idx].redeemed;
+                st.lane_states[idx].nonce = merge.nonce;
+            } else {
+                return Err(ActorError::new(
+                    ExitCode::ErrIllegalArgument,
+                    format!("voucher specifies invalid merge lane {}", merge.lane),
+                ));
             }
+        }
 
-            // 2. To prevent double counting, remove already redeemed amounts (from
-            // voucher or other lanes) from the voucher amount
-            st.lane_states[idx].nonce = sv.nonce;
-            let balance_delta = &sv.amount - (redeemed + &st.lane_states[idx].redeemed);
+        // 2. To prevent double counting, remove already redeemed amounts (from
+        // voucher or other lanes) from the voucher amount
+        st.lane_states[idx].nonce = sv.nonce;
+        let balance_delta = &sv.amount - (redeemed + &st.lane_states[idx].redeemed);
 
-            // 3. set new redeemed value for merged-into lane
-            st.lane_states[idx].redeemed = sv.amount;
+        // 3. set new redeemed value for merged-into lane
+        st.lane_states[idx].redeemed = sv.amount.clone();
 
-            // 4. check operation validity
-            let new_send_balance = st.to_send.clone() + balance_delta;
+        // 4. check operation validity
+        let new_send_balance = st.to_send.clone() + balance_delta;
 
-            if new_send_balance < TokenAmount::from(0u8) {
-                return Err(ActorError::new(
-                    ExitCode::ErrIllegalState,
-                    "voucher would leave channel balance negative".to_owned(),
-                ));
-            }
+        if new_send_balance < TokenAmount::from(0u8) {
+            return Err(ActorError::new(
+                ExitCode::ErrIllegalState,
+                "voucher would leave channel balance negative".to_owned(),
+            ));
+        }
 
-            if new_send_balance > curr_bal {
-                return Err(ActorError::new(
-                    ExitCode::ErrIllegalState,
-                    "not enough funds in channel to cover voucher".to_owned(),
-                ));
-            }
+        if &new_send_balance > curr_bal {
+            return Err(ActorError::new(
+                ExitCode::ErrIllegalState,
+                "not enough funds in channel to cover voucher".to_owned(),
+            ));
+        }
 
-            // 5. add new redemption ToSend
-            st.to_send = new_send_balance;
+        // 5. add new redemption ToSend
+        st.to_send = new_send_balance;
 
-            // update channel settlingAt and MinSettleHeight if delayed by voucher
-            if sv.min_settle_height != 0 {
-                if st.settling_at != 0 && st.settling_at < sv.min_settle_height {
-                    st.settling_at = sv.min_settle_height;
-                }
-                if st.min_settle_height < sv.min_settle_height {
-                    st.min_settle_height = sv.min_settle_height;
-                }
+        // update channel settlingAt and MinSettleHeight if delayed by voucher
+        if sv.min_settle_height != 0 {
+            if st.settling_at != 0 && st.settling_at < sv.min_settle_height {
+                st.settling_at = sv.min_settle_height;
             }
-            Ok(())
-        })?
+            if st.min_settle_height < sv.min_settle_height {
+                st.min_settle_height = sv.min_settle_height;
+            }
+        }
+        Ok(())
     }
 
     pub fn settle<BS, RT>(rt: &mut RT) -> Result<(), ActorError>
@@ -260,6 +290,7 @@ impl Actor {
         RT: Runtime<BS>,
     {
         let epoch = rt.curr_epoch();
+        let policy = policy(rt.network_version());
         let st: State = rt.state()?;
         rt.validate_immediate_caller_is([st.from, st.to].iter())?;
 
@@ -271,7 +302,7 @@ impl Actor {
                 ));
             }
 
-            st.settling_at = epoch + SETTLE_DELAY;
+            st.settling_at = epoch + policy.settle_delay;
             if st.settling_at < st.min_settle_height {
                 st.settling_at = st.min_settle_height;
             }
@@ -296,15 +327,7 @@ impl Actor {
         }
 
         // TODO revisit: Spec doesn't check this, could be possible balance is below to_send?
-        let rem_bal = rt
-            .current_balance()?
-            .checked_sub(&st.to_send)
-            .ok_or_else(|| {
-                rt.abort(
-                    ExitCode::ErrInsufficientFunds,
-                    "Cannot send more than remaining balance",
-                )
-            })?;
+        let rem_bal = rt.current_balance()? - &st.to_send;
 
         // send remaining balance to `from`
         rt.send(st.from, METHOD_SEND, Serialized::default(), rem_bal)?;
@@ -339,7 +362,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt, params.deserialize().unwrap())?;
                 Ok(Serialized::default())
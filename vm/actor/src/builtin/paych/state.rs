@@ -5,20 +5,19 @@ use address::Address;
 use clock::ChainEpoch;
 use encoding::tuple::*;
 use encoding::Cbor;
-use num_bigint::{bigint_ser, BigInt};
+use runtime::StateObject;
 use vm::TokenAmount;
 
 /// A given payment channel actor is established by `from`
 /// to enable off-chain microtransactions to `to` address
 /// to be reconciled and tallied on chain.
-#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     /// Channel owner, who has funded the actor.
     pub from: Address,
     /// Recipient of payouts from channel.
     pub to: Address,
     /// Amount successfully redeemed through the payment channel, paid out on `Collect`.
-    #[serde(with = "bigint_ser")]
     pub to_send: TokenAmount,
     /// Height at which the channel can be collected.
     pub settling_at: ChainEpoch,
@@ -39,17 +38,33 @@ impl State {
             lane_states: Vec::new(),
         }
     }
+
+    /// Returns the number of lanes which have been redeemed from.
+    pub fn lane_count(&self) -> usize {
+        self.lane_states.len()
+    }
+
+    /// Returns the state of the lane with the given ID, if a voucher has been redeemed on it.
+    pub fn lane_state(&self, id: u64) -> Option<&LaneState> {
+        self.lane_states
+            .binary_search_by_key(&id, |ls| ls.id)
+            .ok()
+            .map(|idx| &self.lane_states[idx])
+    }
+
+    /// Returns true if the channel is settling, and can be collected after `settling_at`.
+    pub fn is_settling(&self) -> bool {
+        self.settling_at != 0
+    }
 }
 
 /// The Lane state tracks the latest (highest) voucher nonce used to merge the lane
 /// as well as the amount it has already redeemed.
-#[derive(Default, PartialEq, Debug, Serialize_tuple, Deserialize_tuple)]
+#[derive(Default, Clone, PartialEq, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct LaneState {
     /// Identifier unique to this channel
     pub id: u64,
-    // TODO this could possibly be a BigUint, but won't affect serialization
-    #[serde(with = "bigint_ser")]
-    pub redeemed: BigInt,
+    pub redeemed: TokenAmount,
     pub nonce: u64,
 }
 
@@ -61,5 +76,59 @@ pub struct Merge {
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
 impl Cbor for LaneState {}
 impl Cbor for Merge {}
+
+#[cfg(feature = "json")]
+pub mod json {
+    use super::*;
+    use serde::{Serialize, Serializer};
+
+    /// Wrapper for serializing a payment channel state reference to JSON.
+    #[derive(Serialize)]
+    #[serde(transparent)]
+    pub struct StateJsonRef<'a>(#[serde(with = "self")] pub &'a State);
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct LaneStateSer {
+        #[serde(rename = "ID")]
+        id: u64,
+        redeemed: String,
+        nonce: u64,
+    }
+
+    pub fn serialize<S>(m: &State, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct StateSer {
+            from: String,
+            to: String,
+            to_send: String,
+            settling_at: ChainEpoch,
+            min_settle_height: ChainEpoch,
+            lane_states: Vec<LaneStateSer>,
+        }
+        StateSer {
+            from: m.from.to_string(),
+            to: m.to.to_string(),
+            to_send: m.to_send.to_str_radix(10),
+            settling_at: m.settling_at,
+            min_settle_height: m.min_settle_height,
+            lane_states: m
+                .lane_states
+                .iter()
+                .map(|ls| LaneStateSer {
+                    id: ls.id,
+                    redeemed: ls.redeemed.to_str_radix(10),
+                    nonce: ls.nonce,
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
@@ -6,14 +6,63 @@ use address::Address;
 use clock::ChainEpoch;
 use crypto::Signature;
 use encoding::{serde_bytes, tuple::*};
-use num_bigint::{bigint_ser, BigInt};
-use vm::{MethodNum, Serialized};
+use fil_types::NetworkVersion;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use vm::{MethodNum, Serialized, TokenAmount};
 
-/// Maximum number of lanes in a channel
-pub const LANE_LIMIT: usize = 256;
+/// Parameters of the payment channel actor, which can change between network versions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Policy {
+    /// Maximum number of lanes in a channel
+    pub lane_limit: usize,
+    /// Number of epochs after settling before the channel can be collected
+    pub settle_delay: ChainEpoch,
+    /// Whether the secret pre image of a voucher can be a digest prefixed with its hash
+    /// function, otherwise it is a blake2b digest alone
+    pub prefixed_secret_hash: bool,
+}
+
+/// Returns the payment channel policy in effect for a network version.
+pub fn policy(version: NetworkVersion) -> Policy {
+    // TODO replace placeholder settle delay when params finished
+    match version {
+        NetworkVersion::V0 | NetworkVersion::V1 | NetworkVersion::V2 | NetworkVersion::V3 => {
+            Policy {
+                lane_limit: 256,
+                settle_delay: 1,
+                prefixed_secret_hash: false,
+            }
+        }
+        _ => Policy {
+            lane_limit: 256,
+            settle_delay: 1,
+            prefixed_secret_hash: true,
+        },
+    }
+}
+
+/// Length of the digest a voucher secret hashes to.
+pub const SECRET_HASH_LEN: usize = 32;
+
+/// Hash function a voucher secret is hashed with. A `secret_pre_image` of a digest alone is
+/// hashed with blake2b, otherwise the digest is prefixed with the byte of its hash function,
+/// when the policy allows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+pub enum SecretHashFunction {
+    Blake2b256 = 0,
+    Sha256 = 1,
+}
 
-// TODO replace placeholder when params finished
-pub const SETTLE_DELAY: ChainEpoch = 1;
+impl SecretHashFunction {
+    /// Encodes the digest of a secret as the `secret_pre_image` of a voucher.
+    pub fn encode(self, digest: &[u8; SECRET_HASH_LEN]) -> Vec<u8> {
+        let mut bz = Vec::with_capacity(SECRET_HASH_LEN + 1);
+        bz.push(self as u8);
+        bz.extend_from_slice(digest);
+        bz
+    }
+}
 
 /// Constructor parameters for payment channel actor
 #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -42,8 +91,7 @@ pub struct SignedVoucher {
     /// Set by `from` to prevent redemption of stale vouchers on a lane
     pub nonce: u64,
     /// Amount voucher can be redeemed for
-    #[serde(with = "bigint_ser")]
-    pub amount: BigInt,
+    pub amount: TokenAmount,
     /// (optional) Can extend channel min_settle_height if needed
     pub min_settle_height: ChainEpoch,
 
@@ -80,6 +128,28 @@ pub struct UpdateChannelStateParams {
     pub proof: Vec<u8>,
 }
 
+impl SignedVoucher {
+    /// Returns the hash function and the digest the secret of the voucher must hash to, or
+    /// `None` if the voucher has no secret. Fails if the secret pre image isn't a digest,
+    /// prefixed with a known hash function if the policy allows it.
+    pub fn secret_hash(
+        &self,
+        policy: &Policy,
+    ) -> Result<Option<(SecretHashFunction, &[u8])>, String> {
+        let bz = self.secret_pre_image.as_slice();
+        match bz.len() {
+            0 => Ok(None),
+            SECRET_HASH_LEN => Ok(Some((SecretHashFunction::Blake2b256, bz))),
+            len if len == SECRET_HASH_LEN + 1 && policy.prefixed_secret_hash => {
+                let hash_fn = SecretHashFunction::from_u8(bz[0])
+                    .ok_or_else(|| format!("unknown secret hash function {}", bz[0]))?;
+                Ok(Some((hash_fn, &bz[1..])))
+            }
+            len => Err(format!("invalid secret pre image length {}", len)),
+        }
+    }
+}
+
 impl From<SignedVoucher> for UpdateChannelStateParams {
     fn from(sv: SignedVoucher) -> Self {
         UpdateChannelStateParams {
@@ -102,7 +172,7 @@ mod tests {
             time_lock_max: 2,
             lane: 3,
             nonce: 4,
-            amount: BigInt::from(5),
+            amount: TokenAmount::from(5),
             signature: Some(Signature::new_bls(b"doesn't matter".to_vec())),
             ..Default::default()
         };
@@ -115,4 +185,41 @@ mod tests {
         );
         assert_eq!(from_slice::<SignedVoucher>(&bz).unwrap(), v);
     }
+
+    #[test]
+    fn secret_hash() {
+        let voucher = |secret_pre_image| SignedVoucher {
+            secret_pre_image,
+            ..Default::default()
+        };
+        let digest = [7u8; SECRET_HASH_LEN];
+        let (v3, v4) = (policy(NetworkVersion::V3), policy(NetworkVersion::V4));
+
+        assert_eq!(voucher(vec![]).secret_hash(&v4), Ok(None));
+        assert_eq!(
+            voucher(digest.to_vec()).secret_hash(&v4),
+            Ok(Some((SecretHashFunction::Blake2b256, &digest[..])))
+        );
+        for &hash_fn in &[SecretHashFunction::Blake2b256, SecretHashFunction::Sha256] {
+            assert_eq!(
+                voucher(hash_fn.encode(&digest)).secret_hash(&v4),
+                Ok(Some((hash_fn, &digest[..])))
+            );
+        }
+
+        // Unknown hash functions and digests of other lengths are rejected
+        let mut unknown = SecretHashFunction::Sha256.encode(&digest);
+        unknown[0] = 2;
+        assert!(voucher(unknown).secret_hash(&v4).is_err());
+        assert!(voucher(vec![7; 31]).secret_hash(&v4).is_err());
+        assert!(voucher(vec![7; 34]).secret_hash(&v4).is_err());
+
+        // Prefixed digests are rejected before the policy allows them
+        let prefixed = voucher(SecretHashFunction::Blake2b256.encode(&digest));
+        assert!(prefixed.secret_hash(&v3).is_err());
+        assert_eq!(
+            voucher(digest.to_vec()).secret_hash(&v3),
+            Ok(Some((SecretHashFunction::Blake2b256, &digest[..])))
+        );
+    }
 }
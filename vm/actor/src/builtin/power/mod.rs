@@ -15,9 +15,8 @@ use crate::{
     REWARD_ACTOR_ADDR,
 };
 use address::Address;
-use fil_types::{SealVerifyInfo, StoragePower};
+use fil_types::{NetworkVersion, SealVerifyInfo, StoragePower};
 use ipld_blockstore::BlockStore;
-use num_bigint::bigint_ser::{BigIntDe, BigIntSer};
 use num_bigint::BigInt;
 use num_derive::FromPrimitive;
 use num_traits::{FromPrimitive, Zero};
@@ -29,7 +28,7 @@ use vm::{ActorError, ExitCode, MethodNum, Serialized, TokenAmount, METHOD_CONSTR
 #[repr(u64)]
 pub enum Method {
     /// Constructor for Storage Power Actor
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     CreateMiner = 2,
     DeleteMiner = 3,
     OnSectorProveCommit = 4,
@@ -42,8 +41,11 @@ pub enum Method {
     UpdatePledgeTotal = 11,
     OnConsensusFault = 12,
     SubmitPoRepForBulkVerify = 13,
+    CurrentTotalPower = 14,
 }
 
+impl_method_num!(Method);
+
 /// Storage Power Actor
 pub struct Actor;
 impl Actor {
@@ -85,7 +87,7 @@ impl Actor {
         let addresses: init::ExecReturn = rt
             .send(
                 *INIT_ACTOR_ADDR,
-                init::Method::Exec as u64,
+                init::Method::Exec.into(),
                 params.clone(),
                 value,
             )?
@@ -120,7 +122,7 @@ impl Actor {
 
         let st: State = rt.state()?;
 
-        let (owner_addr, worker_addr) = request_miner_control_addrs(rt, nominal)?;
+        let (owner_addr, worker_addr, _) = request_miner_control_addrs(rt, nominal)?;
         rt.validate_immediate_caller_is(&[owner_addr, worker_addr])?;
 
         let claim = st
@@ -159,9 +161,8 @@ impl Actor {
                 ));
             }
 
-            st.total_quality_adj_power -= claim.quality_adj_power;
-            st.total_raw_byte_power -= claim.raw_byte_power;
-            Ok(())
+            st.remove_claim_power(&claim)
+                .map_err(|e| ActorError::new(ExitCode::ErrIllegalState, e))
         })??;
 
         Self::delete_miner_actor(rt, &nominal)?;
@@ -351,6 +352,10 @@ impl Actor {
                     }
                 }
                 st.last_epoch_tick = rt_epoch;
+                if st.total_bytes_committed.is_none() && rt.network_version() >= NetworkVersion::V9
+                {
+                    st.migrate_committed_power(rt.store())?;
+                }
                 Ok(events)
             })?
             .map_err(|e| {
@@ -364,7 +369,7 @@ impl Actor {
             // TODO switch 12 to OnDeferredCronEvent on miner actor impl
             rt.send(
                 event.miner_addr,
-                12,
+                MethodNum::new(12),
                 event.callback_payload,
                 TokenAmount::from(0u8),
             )?;
@@ -411,11 +416,16 @@ impl Actor {
             })?;
 
         rt.transaction(|st: &mut State, _| {
-            st.total_quality_adj_power -= claim.quality_adj_power;
-            st.total_raw_byte_power -= claim.raw_byte_power;
+            st.remove_claim_power(&claim).map_err(|e| {
+                ActorError::new(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to remove power for {}: {}", miner_addr, e),
+                )
+            })?;
 
             st.add_pledge_total(pledge_amount);
-        })?;
+            Ok(())
+        })??;
 
         Self::delete_miner_actor(rt, &miner_addr)?;
 
@@ -439,6 +449,33 @@ impl Actor {
         Ok(())
     }
 
+    /// Returns the network wide power totals and miner counts.
+    fn current_total_power<BS, RT>(rt: &mut RT) -> Result<CurrentTotalPowerReturn, ActorError>
+    where
+        BS: BlockStore,
+        RT: Runtime<BS>,
+    {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let (raw_byte_committed, quality_adj_committed) =
+            st.committed_power(rt.store()).map_err(|e| {
+                ActorError::new(
+                    ExitCode::ErrIllegalState,
+                    format!("failed to load committed power: {}", e),
+                )
+            })?;
+
+        Ok(CurrentTotalPowerReturn {
+            raw_byte_power: st.total_raw_byte_power,
+            quality_adj_power: st.total_quality_adj_power,
+            raw_byte_committed,
+            quality_adj_committed,
+            pledge_collateral: st.total_pledge_collateral,
+            miner_count: st.miner_count,
+            miner_above_min_power_count: st.num_miners_meeting_min_power,
+        })
+    }
+
     fn submit_porep_for_bulk_verify<BS, RT>(
         rt: &mut RT,
         seal_info: SealVerifyInfo,
@@ -497,11 +534,11 @@ where
     let st: State = rt.state()?;
     let ret = rt.send(
         *REWARD_ACTOR_ADDR,
-        RewardMethod::LastPerEpochReward as u64,
+        RewardMethod::ThisEpochReward.into(),
         Serialized::default(),
         TokenAmount::zero(),
     )?;
-    let BigIntDe(epoch_reward) = ret.deserialize()?;
+    let epoch_reward: TokenAmount = ret.deserialize()?;
 
     let qa_power = qa_power_for_weight(&desc);
     Ok(initial_pledge_for_weight(
@@ -537,7 +574,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 check_empty_params(params)?;
                 Self::constructor(rt)?;
@@ -553,7 +590,7 @@ impl ActorCode for Actor {
             }
             Some(Method::OnSectorProveCommit) => {
                 let res = Self::on_sector_prove_commit(rt, params.deserialize()?)?;
-                Ok(Serialized::serialize(BigIntSer(&res))?)
+                Ok(Serialized::serialize(&res)?)
             }
             Some(Method::OnSectorTerminate) => {
                 Self::on_sector_terminate(rt, params.deserialize()?)?;
@@ -569,7 +606,7 @@ impl ActorCode for Actor {
             }
             Some(Method::OnSectorModifyWeightDesc) => {
                 let res = Self::on_sector_modify_weight_desc(rt, params.deserialize()?)?;
-                Ok(Serialized::serialize(BigIntSer(&res))?)
+                Ok(Serialized::serialize(&res)?)
             }
             Some(Method::EnrollCronEvent) => {
                 Self::enroll_cron_event(rt, params.deserialize()?)?;
@@ -581,19 +618,22 @@ impl ActorCode for Actor {
                 Ok(Serialized::default())
             }
             Some(Method::UpdatePledgeTotal) => {
-                let BigIntDe(param) = params.deserialize()?;
-                Self::update_pledge_total(rt, param)?;
+                Self::update_pledge_total(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
             }
             Some(Method::OnConsensusFault) => {
-                let BigIntDe(param) = params.deserialize()?;
-                Self::on_consensus_fault(rt, param)?;
+                Self::on_consensus_fault(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
             }
             Some(Method::SubmitPoRepForBulkVerify) => {
                 Self::submit_porep_for_bulk_verify(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
             }
+            Some(Method::CurrentTotalPower) => {
+                check_empty_params(params)?;
+                let res = Self::current_total_power(rt)?;
+                Ok(Serialized::serialize(res)?)
+            }
             _ => Err(rt.abort(ExitCode::SysErrInvalidMethod, "Invalid method")),
         }
     }
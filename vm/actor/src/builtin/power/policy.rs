@@ -59,5 +59,5 @@ pub fn initial_pledge_for_weight(
     let _ = circ_supply; // TODO: ce use this
     let _ = total_pledge; // TODO: ce use this
 
-    (qa_power * per_epoch_reward) / tot_qa_power
+    (per_epoch_reward * qa_power) / tot_qa_power
 }
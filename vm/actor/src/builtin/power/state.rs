@@ -2,26 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::CONSENSUS_MINER_MIN_POWER;
-use crate::{BytesKey, Multimap, HAMT_BIT_WIDTH};
+use crate::{BytesKey, IntKey, MapKey, Multimap, HAMT_BIT_WIDTH};
 use address::Address;
 use cid::Cid;
 use clock::ChainEpoch;
 use encoding::{tuple::*, Cbor};
 use fil_types::StoragePower;
-use integer_encoding::VarInt;
 use ipld_blockstore::BlockStore;
 use ipld_hamt::Hamt;
-use num_bigint::bigint_ser;
+use num_bigint::bigint_ser::{self, BigIntDe, BigIntSer};
+use num_traits::Zero;
+use runtime::StateObject;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use vm::{Serialized, TokenAmount};
 
 /// Storage power actor state
-#[derive(Default, Serialize_tuple, Deserialize_tuple)]
+#[derive(Default, Clone, PartialEq)]
 pub struct State {
-    #[serde(with = "bigint_ser")]
+    /// Sum of the raw byte power of the miners meeting the consensus minimum power.
     pub total_raw_byte_power: StoragePower,
-    #[serde(with = "bigint_ser")]
+    /// Sum of the quality adjusted power of the miners meeting the consensus minimum power.
     pub total_quality_adj_power: StoragePower,
-    #[serde(with = "bigint_ser")]
     pub total_pledge_collateral: TokenAmount,
     pub miner_count: i64,
 
@@ -39,6 +41,14 @@ pub struct State {
     pub num_miners_meeting_min_power: i64,
 
     pub proof_validation_batch: Option<Cid>,
+
+    /// Sum of the raw byte power of all miners, including those below the consensus minimum.
+    /// Tracked from `NetworkVersion::V9`, along with `total_qa_bytes_committed`. States without
+    /// them are serialized in the original nine field layout.
+    pub total_bytes_committed: Option<StoragePower>,
+    /// Sum of the quality adjusted power of all miners, including those below the consensus
+    /// minimum.
+    pub total_qa_bytes_committed: Option<StoragePower>,
 }
 
 impl State {
@@ -69,6 +79,8 @@ impl State {
         claim.raw_byte_power += power;
         claim.quality_adj_power += qa_power;
 
+        self.add_committed_power(power, qa_power)?;
+
         let new_nominal_power = &claim.quality_adj_power;
 
         let min_power_ref: &StoragePower = &*CONSENSUS_MINER_MIN_POWER;
@@ -107,6 +119,84 @@ impl State {
         self.set_claim(store, miner, claim)
     }
 
+    /// Removes the power of a miner's claim from the totals, when the miner is removed.
+    pub(super) fn remove_claim_power(&mut self, claim: &Claim) -> Result<(), String> {
+        self.add_committed_power(&-&claim.raw_byte_power, &-&claim.quality_adj_power)?;
+
+        if claim.quality_adj_power >= *CONSENSUS_MINER_MIN_POWER {
+            self.num_miners_meeting_min_power -= 1;
+            self.total_quality_adj_power -= &claim.quality_adj_power;
+            self.total_raw_byte_power -= &claim.raw_byte_power;
+        }
+
+        if self.num_miners_meeting_min_power < 0 {
+            return Err(format!(
+                "negative number of miners: {}",
+                self.num_miners_meeting_min_power
+            ));
+        }
+        if self.total_raw_byte_power < Zero::zero() || self.total_quality_adj_power < Zero::zero() {
+            return Err(format!(
+                "negative total power: raw {}, quality adjusted {}",
+                self.total_raw_byte_power, self.total_quality_adj_power
+            ));
+        }
+        Ok(())
+    }
+
+    /// Adds to the committed power totals, if they are tracked.
+    fn add_committed_power(
+        &mut self,
+        power: &StoragePower,
+        qa_power: &StoragePower,
+    ) -> Result<(), String> {
+        if let (Some(raw), Some(qa)) = (
+            self.total_bytes_committed.as_mut(),
+            self.total_qa_bytes_committed.as_mut(),
+        ) {
+            *raw += power;
+            *qa += qa_power;
+            if *raw < Zero::zero() || *qa < Zero::zero() {
+                return Err(format!(
+                    "negative total committed power: raw {}, quality adjusted {}",
+                    raw, qa
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the raw byte and quality adjusted power committed by all miners. Sums the
+    /// claims when the totals are not tracked in state.
+    pub fn committed_power<BS: BlockStore>(
+        &self,
+        store: &BS,
+    ) -> Result<(StoragePower, StoragePower), String> {
+        if let (Some(raw), Some(qa)) = (&self.total_bytes_committed, &self.total_qa_bytes_committed)
+        {
+            return Ok((raw.clone(), qa.clone()));
+        }
+
+        let map: Hamt<BytesKey, _> =
+            Hamt::load_with_bit_width(&self.claims, store, HAMT_BIT_WIDTH)?;
+        let (mut raw, mut qa) = (StoragePower::zero(), StoragePower::zero());
+        map.for_each(|_, claim: Claim| {
+            raw += claim.raw_byte_power;
+            qa += claim.quality_adj_power;
+            Ok(())
+        })?;
+        Ok((raw, qa))
+    }
+
+    /// Starts tracking the committed power totals, from the claims of state from before they
+    /// were kept. Run by the first cron tick from `NetworkVersion::V9`.
+    pub fn migrate_committed_power<BS: BlockStore>(&mut self, store: &BS) -> Result<(), String> {
+        let (raw, qa) = self.committed_power(store)?;
+        self.total_bytes_committed = Some(raw);
+        self.total_qa_bytes_committed = Some(qa);
+        Ok(())
+    }
+
     pub(super) fn add_pledge_total(&mut self, amount: TokenAmount) {
         self.total_pledge_collateral += amount;
     }
@@ -199,12 +289,140 @@ impl State {
     }
 }
 
+/// Cron events are keyed by signed varint epochs.
 fn epoch_key(e: ChainEpoch) -> BytesKey {
-    let bz = e.encode_var_vec();
-    bz.into()
+    IntKey(e).key()
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
+
+impl Serialize for State {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let Self {
+            total_raw_byte_power,
+            total_quality_adj_power,
+            total_pledge_collateral,
+            miner_count,
+            cron_event_queue,
+            last_epoch_tick,
+            claims,
+            num_miners_meeting_min_power,
+            proof_validation_batch,
+            total_bytes_committed,
+            total_qa_bytes_committed,
+        } = self;
+        match (total_bytes_committed, total_qa_bytes_committed) {
+            (Some(raw_committed), Some(qa_committed)) => (
+                BigIntSer(total_raw_byte_power),
+                BigIntSer(total_quality_adj_power),
+                total_pledge_collateral,
+                miner_count,
+                cron_event_queue,
+                last_epoch_tick,
+                claims,
+                num_miners_meeting_min_power,
+                proof_validation_batch,
+                BigIntSer(raw_committed),
+                BigIntSer(qa_committed),
+            )
+                .serialize(s),
+            _ => (
+                BigIntSer(total_raw_byte_power),
+                BigIntSer(total_quality_adj_power),
+                total_pledge_collateral,
+                miner_count,
+                cron_event_queue,
+                last_epoch_tick,
+                claims,
+                num_miners_meeting_min_power,
+                proof_validation_batch,
+            )
+                .serialize(s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StateVisitor;
+
+        impl<'de> de::Visitor<'de> for StateVisitor {
+            type Value = State;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a tuple of 9 or 11 power state fields")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<State, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let BigIntDe(total_raw_byte_power) = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let BigIntDe(total_quality_adj_power) = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let total_pledge_collateral = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let miner_count = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let cron_event_queue = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(4, &self))?;
+                let last_epoch_tick = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(5, &self))?;
+                let claims = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(6, &self))?;
+                let num_miners_meeting_min_power = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(7, &self))?;
+                let proof_validation_batch = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(8, &self))?;
+                let (total_bytes_committed, total_qa_bytes_committed) =
+                    match seq.next_element::<BigIntDe>()? {
+                        Some(BigIntDe(raw)) => {
+                            let BigIntDe(qa) = seq
+                                .next_element()?
+                                .ok_or_else(|| de::Error::invalid_length(10, &self))?;
+                            (Some(raw), Some(qa))
+                        }
+                        None => (None, None),
+                    };
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(12, &self));
+                }
+                Ok(State {
+                    total_raw_byte_power,
+                    total_quality_adj_power,
+                    total_pledge_collateral,
+                    miner_count,
+                    cron_event_queue,
+                    last_epoch_tick,
+                    claims,
+                    num_miners_meeting_min_power,
+                    proof_validation_batch,
+                    total_bytes_committed,
+                    total_qa_bytes_committed,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(StateVisitor)
+    }
+}
 
 #[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct Claim {
@@ -246,4 +464,93 @@ mod test {
         assert_eq!(b3, epoch_key(e3));
         assert_eq!(b4, epoch_key(e4));
     }
+
+    #[test]
+    fn committed_power_and_min_power_miners() {
+        let store = db::MemoryDB::default();
+        let empty_map = Hamt::<BytesKey, _>::new_with_bit_width(&store, HAMT_BIT_WIDTH)
+            .flush()
+            .unwrap();
+        let mut st = State::new(empty_map.clone(), empty_map);
+        let miner = Address::new_id(100);
+        st.set_claim(&store, &miner, Claim::default()).unwrap();
+
+        let min = CONSENSUS_MINER_MIN_POWER.clone();
+        let half = &min / 2;
+
+        // Committed power is summed from the claims until it is tracked
+        st.add_to_claim(&store, &miner, &half, &half).unwrap();
+        assert_eq!(st.total_bytes_committed, None);
+        assert_eq!(
+            st.committed_power(&store).unwrap(),
+            (half.clone(), half.clone())
+        );
+        st.migrate_committed_power(&store).unwrap();
+        st.add_to_claim(&store, &miner, &-&half, &-&half).unwrap();
+
+        // Power below the minimum is only committed
+        st.add_to_claim(&store, &miner, &half, &half).unwrap();
+        assert_eq!(st.total_bytes_committed, Some(half.clone()));
+        assert_eq!(st.total_qa_bytes_committed, Some(half.clone()));
+        assert_eq!(st.total_raw_byte_power, StoragePower::zero());
+        assert_eq!(st.total_quality_adj_power, StoragePower::zero());
+        assert_eq!(st.num_miners_meeting_min_power, 0);
+
+        // Reaching the minimum counts all of the miner's power
+        st.add_to_claim(&store, &miner, &min, &min).unwrap();
+        let total = &half + &min;
+        assert_eq!(st.total_bytes_committed, Some(total.clone()));
+        assert_eq!(st.total_raw_byte_power, total);
+        assert_eq!(st.total_quality_adj_power, total);
+        assert_eq!(st.num_miners_meeting_min_power, 1);
+
+        // Dropping below the minimum keeps the power committed
+        st.add_to_claim(&store, &miner, &-&min, &-&min).unwrap();
+        assert_eq!(st.total_qa_bytes_committed, Some(half));
+        assert_eq!(st.total_raw_byte_power, StoragePower::zero());
+        assert_eq!(st.num_miners_meeting_min_power, 0);
+
+        st.add_to_claim(&store, &miner, &min, &min).unwrap();
+        let claim = st.get_claim(&store, &miner).unwrap().unwrap();
+        st.remove_claim_power(&claim).unwrap();
+        assert_eq!(st.committed_power(&store).unwrap(), Default::default());
+        assert_eq!(st.total_raw_byte_power, StoragePower::zero());
+        assert_eq!(st.total_quality_adj_power, StoragePower::zero());
+        assert_eq!(st.num_miners_meeting_min_power, 0);
+
+        // Removing more power than committed fails
+        assert!(st.add_to_claim(&store, &miner, &-&total, &-&total).is_err());
+    }
+
+    #[test]
+    fn committed_power_encoding() {
+        let mut st = State::new(Cid::default(), Cid::default());
+
+        // Without the committed power the state keeps its original nine field layout
+        let bz = encoding::to_vec(&st).unwrap();
+        let zero = StoragePower::zero();
+        let original = (
+            BigIntSer(&zero),
+            BigIntSer(&zero),
+            BigIntSer(&zero),
+            0i64,
+            Cid::default(),
+            0i64,
+            Cid::default(),
+            0i64,
+            None::<Cid>,
+        );
+        assert_eq!(bz, encoding::to_vec(&original).unwrap());
+        let decoded: State = encoding::from_slice(&bz).unwrap();
+        assert_eq!(decoded.total_bytes_committed, None);
+
+        st.total_bytes_committed = Some(StoragePower::from(5));
+        st.total_qa_bytes_committed = Some(StoragePower::from(7));
+        let decoded: State = encoding::from_slice(&encoding::to_vec(&st).unwrap()).unwrap();
+        assert_eq!(decoded.total_bytes_committed, Some(StoragePower::from(5)));
+        assert_eq!(
+            decoded.total_qa_bytes_committed,
+            Some(StoragePower::from(7))
+        );
+    }
 }
@@ -5,7 +5,7 @@ use crate::DealWeight;
 use address::Address;
 use clock::ChainEpoch;
 use encoding::{serde_bytes, tuple::*, Cbor};
-use fil_types::{RegisteredSealProof, SectorSize};
+use fil_types::{RegisteredSealProof, SectorSize, StoragePower};
 use num_bigint::bigint_ser;
 use num_bigint::biguint_ser;
 use vm::{Serialized, TokenAmount};
@@ -68,7 +68,6 @@ pub struct OnSectorTerminateParams {
 pub struct OnSectorTemporaryFaultEffectiveBeginParams {
     // TODO revisit todo for replacing with power
     pub weights: Vec<SectorStorageWeightDesc>,
-    #[serde(with = "bigint_ser")]
     pub pledge: TokenAmount,
 }
 
@@ -76,7 +75,6 @@ pub struct OnSectorTemporaryFaultEffectiveBeginParams {
 pub struct OnSectorTemporaryFaultEffectiveEndParams {
     // TODO revisit todo for replacing with power
     pub weights: Vec<SectorStorageWeightDesc>,
-    #[serde(with = "bigint_ser")]
     pub pledge: TokenAmount,
 }
 
@@ -113,3 +111,24 @@ pub struct OnFaultBeginParams {
 pub struct OnFaultEndParams {
     pub weights: Vec<SectorStorageWeightDesc>, // TODO: replace with power if it can be computed by miner
 }
+
+/// Network wide power totals and miner counts.
+#[derive(Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct CurrentTotalPowerReturn {
+    /// Raw byte power of the miners meeting the consensus minimum power.
+    #[serde(with = "bigint_ser")]
+    pub raw_byte_power: StoragePower,
+    /// Quality adjusted power of the miners meeting the consensus minimum power.
+    #[serde(with = "bigint_ser")]
+    pub quality_adj_power: StoragePower,
+    /// Raw byte power committed by all miners.
+    #[serde(with = "bigint_ser")]
+    pub raw_byte_committed: StoragePower,
+    /// Quality adjusted power committed by all miners.
+    #[serde(with = "bigint_ser")]
+    pub quality_adj_committed: StoragePower,
+    pub pledge_collateral: TokenAmount,
+    pub miner_count: i64,
+    /// Number of miners meeting the consensus minimum power.
+    pub miner_above_min_power_count: i64,
+}
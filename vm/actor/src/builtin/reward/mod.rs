@@ -10,12 +10,10 @@ use crate::network::EXPECTED_LEADERS_PER_EPOCH;
 use crate::{
     check_empty_params, miner, BURNT_FUNDS_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
-use clock::ChainEpoch;
-use fil_types::StoragePower;
+use fil_types::{NetworkVersion, StoragePower};
 use ipld_blockstore::BlockStore;
-use num_bigint::bigint_ser::{BigIntDe, BigIntSer};
-use num_bigint::BigInt;
-use num_bigint::BigUint;
+use num_bigint::bigint_ser::BigIntDe;
+use num_bigint::Sign;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use runtime::{ActorCode, Runtime};
@@ -30,12 +28,14 @@ use vm::{
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     AwardBlockReward = 2,
-    LastPerEpochReward = 3,
+    ThisEpochReward = 3,
     UpdateNetworkKPI = 4,
 }
 
+impl_method_num!(Method);
+
 /// Reward Actor
 pub struct Actor;
 impl Actor {
@@ -49,7 +49,7 @@ impl Actor {
 
         // TODO revisit based on issue: https://github.com/filecoin-project/specs-actors/issues/317
 
-        rt.create(&State::new())?;
+        rt.create(&State::new(rt.network_version()))?;
         Ok(())
     }
 
@@ -58,7 +58,8 @@ impl Actor {
     /// The system actor thus computes the parameters and attached value.
     ///
     /// The reward includes two components:
-    /// - the epoch block reward, computed and paid from the reward actor's balance,
+    /// - the epoch block reward, a share of the epoch reward for each win of the block producer,
+    ///   paid from the reward actor's balance,
     /// - the block gas reward, expected to be transferred to the reward actor with this invocation.
     ///
     /// The reward is reduced before the residual is credited to the block producer, by:
@@ -80,10 +81,20 @@ impl Actor {
             params.gas_reward
         );
 
-        assert!(
-            params.ticket_count > 0,
-            "cannot give block reward for zero tickets"
-        );
+        // Each win is paid a share of the reward expected for a leader of the epoch
+        let shares = if rt.network_version() >= NetworkVersion::V2 {
+            if params.win_count == 0 {
+                return Err(actor_error!(ErrIllegalArgument;
+                    "invalid win count {}", params.win_count));
+            }
+            params.win_count
+        } else {
+            assert!(
+                params.win_count > 0,
+                "cannot give block reward for zero tickets"
+            );
+            1
+        };
 
         let miner_addr = rt.resolve_address(&params.miner)?.ok_or_else(
             || actor_error!(ErrIllegalState; "failed to resolve given owner address"),
@@ -92,7 +103,7 @@ impl Actor {
         let prior_balance = rt.current_balance()?;
 
         let state: State = rt.state()?;
-        let block_reward = state.last_per_epoch_reward / EXPECTED_LEADERS_PER_EPOCH;
+        let block_reward = state.this_epoch_reward * shares / EXPECTED_LEADERS_PER_EPOCH;
         let total_reward = block_reward + params.gas_reward;
 
         // Cap the penalty at the total reward value.
@@ -108,8 +119,8 @@ impl Actor {
 
         rt.send(
             miner_addr,
-            miner::Method::AddLockedFund as u64,
-            Serialized::serialize(&BigIntSer(&reward_payable))?,
+            miner::Method::AddLockedFund.into(),
+            Serialized::serialize(&reward_payable)?,
             reward_payable,
         )?;
 
@@ -124,43 +135,15 @@ impl Actor {
         Ok(())
     }
 
-    fn last_per_epoch_reward<BS, RT>(rt: &mut RT) -> Result<TokenAmount, ActorError>
+    /// Returns the reward to be paid in total to the block producers of the current epoch.
+    fn this_epoch_reward<BS, RT>(rt: &mut RT) -> Result<TokenAmount, ActorError>
     where
         BS: BlockStore,
         RT: Runtime<BS>,
     {
         rt.validate_immediate_caller_accept_any()?;
         let st: State = rt.state()?;
-        Ok(st.last_per_epoch_reward)
-    }
-
-    /// Withdraw available funds from reward map
-    fn compute_per_epoch_reward(st: &mut State, _ticket_count: u64) -> TokenAmount {
-        // TODO update when finished in specs
-        let new_simple_supply = minting_function(
-            &SIMPLE_TOTAL,
-            &(BigUint::from(st.reward_epochs_paid as u64) << MINTING_INPUT_FIXED_POINT),
-        );
-        let new_baseline_supply = minting_function(&*BASELINE_TOTAL, &st.effective_network_time);
-
-        let new_simple_minted = new_simple_supply
-            .checked_sub(&st.simple_supply)
-            .unwrap_or_default();
-        let new_baseline_minted = new_baseline_supply
-            .checked_sub(&st.baseline_supply)
-            .unwrap_or_default();
-
-        st.simple_supply = new_simple_supply;
-        st.baseline_supply = new_baseline_supply;
-
-        let per_epoch_reward = new_simple_minted + new_baseline_minted;
-        st.last_per_epoch_reward = per_epoch_reward.clone();
-        per_epoch_reward
-    }
-
-    fn new_baseline_power(_st: &State, _reward_epochs_paid: ChainEpoch) -> StoragePower {
-        // TODO: this is not the final baseline function or value, PARAM_FINISH
-        BigInt::from(BASELINE_POWER)
+        Ok(st.this_epoch_reward)
     }
 
     // Called at the end of each epoch by the power actor (in turn by its cron hook).
@@ -177,31 +160,15 @@ impl Actor {
     {
         rt.validate_immediate_caller_is(std::iter::once(&*STORAGE_POWER_ACTOR_ADDR))?;
 
-        rt.transaction::<State, Result<(), ActorError>, _>(|st: &mut State, _| {
+        let network_version = rt.network_version();
+        if network_version >= NetworkVersion::V10 && curr_realized_power.sign() == Sign::Minus {
+            return Err(actor_error!(ErrIllegalArgument;
+                "realized power {} must not be negative", curr_realized_power));
+        }
+
+        rt.transaction(|st: &mut State, _| {
             // By the time this is called, the rewards for this epoch have been paid to miners.
-            st.reward_epochs_paid += 1;
-            st.realized_power = curr_realized_power;
-
-            st.baseline_power = Self::new_baseline_power(st, st.reward_epochs_paid);
-            st.cumsum_baseline += &st.baseline_power.to_biguint().ok_or_else(|| {
-                ActorError::new(
-                    ExitCode::ErrIllegalState,
-                    "Negative Baseline Power".to_string(),
-                )
-            })?;
-
-            // Cap realized power in computing CumsumRealized so that progress is only relative to the current epoch.
-            let capped_realized_power = std::cmp::min(&st.baseline_power, &st.realized_power);
-            st.cumsum_realized += capped_realized_power.to_biguint().ok_or_else(|| {
-                ActorError::new(
-                    ExitCode::ErrIllegalState,
-                    "Negative Realized Power".to_string(),
-                )
-            })?;
-            st.effective_network_time =
-                st.get_effective_network_time(&st.cumsum_baseline, &st.cumsum_realized);
-            Self::compute_per_epoch_reward(st, 1);
-            Ok(())
+            st.update_to_next_epoch_with_reward(curr_realized_power, network_version)
         })??;
         Ok(())
     }
@@ -218,7 +185,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 check_empty_params(params)?;
                 Self::constructor(rt)?;
@@ -228,9 +195,9 @@ impl ActorCode for Actor {
                 Self::award_block_reward(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
             }
-            Some(Method::LastPerEpochReward) => {
-                let res = Self::last_per_epoch_reward(rt)?;
-                Ok(Serialized::serialize(BigIntSer(&res))?)
+            Some(Method::ThisEpochReward) => {
+                let res = Self::this_epoch_reward(rt)?;
+                Ok(Serialized::serialize(&res)?)
             }
             Some(Method::UpdateNetworkKPI) => {
                 let BigIntDe(param) = params.deserialize()?;
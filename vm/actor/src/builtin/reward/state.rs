@@ -4,14 +4,16 @@
 use super::types::*;
 use clock::ChainEpoch;
 use encoding::{repr::*, tuple::*, Cbor};
-use fil_types::{Spacetime, StoragePower};
+use fil_types::{NetworkVersion, Spacetime, StoragePower};
 use num_bigint::bigint_ser;
 use num_bigint::biguint_ser;
+use num_bigint::{BigInt, BigUint};
 use num_derive::FromPrimitive;
-use vm::TokenAmount;
+use runtime::StateObject;
+use vm::{actor_error, ActorError, ExitCode, TokenAmount};
 
 /// Reward actor state
-#[derive(Serialize_tuple, Deserialize_tuple, Default)]
+#[derive(Serialize_tuple, Deserialize_tuple, Default, Clone, PartialEq)]
 pub struct State {
     #[serde(with = "bigint_ser")]
     pub baseline_power: StoragePower,
@@ -24,16 +26,13 @@ pub struct State {
     #[serde(with = "biguint_ser")]
     pub effective_network_time: NetworkTime,
 
-    #[serde(with = "bigint_ser")]
     pub simple_supply: TokenAmount,
-    #[serde(with = "bigint_ser")]
     pub baseline_supply: TokenAmount,
 
     /// The reward to be paid in total to block producers, if exactly the expected number of them produce a block.
     /// The actual reward total paid out depends on the number of winners in any round.
-    /// This is computed at the end of the previous epoch, and should really be called ThisEpochReward.
-    #[serde(with = "bigint_ser")]
-    pub last_per_epoch_reward: TokenAmount,
+    /// This is computed at the end of the previous epoch.
+    pub this_epoch_reward: TokenAmount,
 
     /// The count of epochs for which a reward has been paid.
     /// This should equal the number of non-empty tipsets after the genesis, aka "chain height".
@@ -41,24 +40,87 @@ pub struct State {
 }
 
 impl State {
-    pub fn new() -> Self {
-        Self::default()
+    /// Constructs the genesis state. From `NetworkVersion::V10` this includes the reward to be
+    /// paid for the first epoch, before that no reward is paid until the first update.
+    pub fn new(network_version: NetworkVersion) -> Self {
+        if network_version < NetworkVersion::V10 {
+            return Self::default();
+        }
+        let mut st = Self {
+            baseline_power: BigInt::from(BASELINE_POWER),
+            ..Default::default()
+        };
+        st.update_this_epoch_reward(network_version);
+        st
     }
 
-    pub(super) fn get_effective_network_time(
-        &self,
-        _cumsum_baseline: &Spacetime,
-        cumsum_realized: &Spacetime,
-    ) -> NetworkTime {
+    /// Moves the state past an epoch for which rewards have been paid, accumulating the
+    /// realized power of the network in that epoch, and computes the reward for the next one.
+    pub(super) fn update_to_next_epoch_with_reward(
+        &mut self,
+        curr_realized_power: StoragePower,
+        network_version: NetworkVersion,
+    ) -> Result<(), ActorError> {
+        self.reward_epochs_paid += 1;
+        self.realized_power = curr_realized_power;
+
+        // TODO: this is not the final baseline function or value, PARAM_FINISH
+        self.baseline_power = BigInt::from(BASELINE_POWER);
+        self.cumsum_baseline += self
+            .baseline_power
+            .to_biguint()
+            .ok_or_else(|| actor_error!(ErrIllegalState; "Negative Baseline Power"))?;
+
+        // Cap realized power in computing CumsumRealized so that progress is only relative to the current epoch.
+        let capped_realized_power = std::cmp::min(&self.baseline_power, &self.realized_power);
+        self.cumsum_realized += capped_realized_power
+            .to_biguint()
+            .ok_or_else(|| actor_error!(ErrIllegalState; "Negative Realized Power"))?;
+        self.effective_network_time = self.get_effective_network_time();
+
+        self.update_this_epoch_reward(network_version);
+        Ok(())
+    }
+
+    /// Computes the reward for the next epoch, as the simple supply to be minted by the end of
+    /// the epoch plus the baseline supply unlocked by the realized power so far, less the supply
+    /// minted for previous epochs. Before `NetworkVersion::V10` the simple supply is only
+    /// minted up to the start of the epoch.
+    fn update_this_epoch_reward(&mut self, network_version: NetworkVersion) {
+        let simple_epochs = if network_version >= NetworkVersion::V10 {
+            self.reward_epochs_paid + 1
+        } else {
+            self.reward_epochs_paid
+        };
+        let new_simple_supply = TokenAmount::from(minting_function(
+            &SIMPLE_TOTAL,
+            &(BigUint::from(simple_epochs as u64) << MINTING_INPUT_FIXED_POINT),
+        ));
+        let new_baseline_supply = TokenAmount::from(minting_function(
+            &BASELINE_TOTAL,
+            &self.effective_network_time,
+        ));
+
+        let new_simple_minted = &new_simple_supply - &self.simple_supply;
+        let new_baseline_minted = &new_baseline_supply - &self.baseline_supply;
+
+        self.simple_supply = new_simple_supply;
+        self.baseline_supply = new_baseline_supply;
+        self.this_epoch_reward = new_simple_minted + new_baseline_minted;
+    }
+
+    fn get_effective_network_time(&self) -> NetworkTime {
         // TODO: this function depends on the final baseline
         // EffectiveNetworkTime is a fractional input with an implicit denominator of (2^MintingInputFixedPoint).
         // realizedCumsum is thus left shifted by MintingInputFixedPoint before converted into a FixedPoint fraction
         // through division (which is an inverse function for the integral of the baseline).
-        (cumsum_realized << MINTING_INPUT_FIXED_POINT) / BASELINE_POWER
+        // Since realized power is capped by the baseline each epoch, this never exceeds the epochs paid.
+        (&self.cumsum_realized << MINTING_INPUT_FIXED_POINT) / BASELINE_POWER
     }
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
 
 /// Defines vestion function type for reward actor
 #[derive(Clone, Debug, PartialEq, Copy, FromPrimitive, Serialize_repr, Deserialize_repr)]
@@ -73,9 +135,7 @@ pub struct Reward {
     pub vesting_function: VestingFunction,
     pub start_epoch: ChainEpoch,
     pub end_epoch: ChainEpoch,
-    #[serde(with = "bigint_ser")]
     pub value: TokenAmount,
-    #[serde(with = "bigint_ser")]
     pub amount_withdrawn: TokenAmount,
 }
 
@@ -4,20 +4,16 @@
 use crate::network::*;
 use address::Address;
 use encoding::tuple::*;
-use num_bigint::{bigint_ser, BigInt, BigUint, ToBigInt};
-use num_traits::{Pow, Zero};
+use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_traits::Zero;
 use std::ops::Neg;
 use vm::TokenAmount;
+pub use vm::TOKEN_PRECISION;
 
 pub type NetworkTime = BigUint;
 
-/// Number of token units in an abstract "FIL" token.
-/// The network works purely in the indivisible token amounts. This constant converts to a fixed decimal with more
-/// human-friendly scale.
-pub const TOKEN_PRECISION: u64 = 1_000_000_000_000_000_000;
-
 /// Baseline power for the network
-pub(super) const BASELINE_POWER: u64 = 1 << 50; // 1PiB for testnet, PARAM_FINISH
+pub const BASELINE_POWER: u64 = 1 << 50; // 1PiB for testnet, PARAM_FINISH
 
 /// Fixed-point precision (in bits) used for minting function's input "t"
 pub(super) const MINTING_INPUT_FIXED_POINT: usize = 30;
@@ -34,9 +30,9 @@ lazy_static! {
 
     // These numbers are placeholders, but should be in units of attoFIL, 10^-18 FIL
     /// 100M for testnet, PARAM_FINISH
-    pub static ref SIMPLE_TOTAL: BigInt = BigInt::from(100).pow(6u8) * BigInt::from(1).pow(18u8);
+    pub static ref SIMPLE_TOTAL: BigInt = BigInt::from(100_000_000) * TOKEN_PRECISION;
     /// 900M for testnet, PARAM_FINISH
-    pub static ref BASELINE_TOTAL: BigInt = BigInt::from(900).pow(6u8) * BigInt::from(1).pow(18u8);
+    pub static ref BASELINE_TOTAL: BigInt = BigInt::from(900_000_000) * TOKEN_PRECISION;
 
     // The following are the numerator and denominator of -ln(1/2)=ln(2),
     // represented as a rational with sufficient precision.
@@ -44,14 +40,17 @@ lazy_static! {
     pub static ref LN_TWO_DEN: BigInt = BigInt::from(10_000_000_000_000_000_000_000_000_000u128);
 }
 
+/// Parameters of a block reward. The win count takes the place of the ticket count in the
+/// encoding, so the params encode the same from before multi-win blocks. From
+/// `NetworkVersion::V2` each win is paid a share of the epoch reward, before that a block is
+/// paid a single share regardless of its win count.
 #[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct AwardBlockRewardParams {
     pub miner: Address,
-    #[serde(with = "bigint_ser")]
     pub penalty: TokenAmount,
-    #[serde(with = "bigint_ser")]
     pub gas_reward: TokenAmount,
-    pub ticket_count: u64,
+    /// Number of wins of the block producer in the epoch's election.
+    pub win_count: u64,
 }
 
 /// Minting Function: Taylor series expansion
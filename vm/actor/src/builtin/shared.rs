@@ -9,27 +9,30 @@ use num_traits::Zero;
 use runtime::Runtime;
 use vm::{ActorError, Serialized, TokenAmount};
 
+/// Requests the owner, worker and control addresses of a miner.
 pub(crate) fn request_miner_control_addrs<BS, RT>(
     rt: &mut RT,
     miner_addr: Address,
-) -> Result<(Address, Address), ActorError>
+) -> Result<(Address, Address, Vec<Address>), ActorError>
 where
     BS: BlockStore,
     RT: Runtime<BS>,
 {
     let ret = rt.send(
         miner_addr,
-        Method::ControlAddresses as u64,
+        Method::ControlAddresses.into(),
         Serialized::default(),
         TokenAmount::zero(),
     )?;
     let addrs: MinerAddrs = ret.deserialize()?;
 
-    Ok((addrs.owner, addrs.worker))
+    Ok((addrs.owner, addrs.worker, addrs.control_addrs))
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
 struct MinerAddrs {
     owner: Address,
     worker: Address,
+    #[serde(with = "address::cbor::vec")]
+    control_addrs: Vec<Address>,
 }
@@ -20,7 +20,7 @@ use vm::{ActorError, ExitCode, MethodNum, Serialized, METHOD_CONSTRUCTOR};
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
-    Constructor = METHOD_CONSTRUCTOR,
+    Constructor = METHOD_CONSTRUCTOR.value(),
     AddVerifier = 2,
     RemoveVerifier = 3,
     AddVerifiedClient = 4,
@@ -28,6 +28,8 @@ pub enum Method {
     RestoreBytes = 6,
 }
 
+impl_method_num!(Method);
+
 pub struct Actor;
 impl Actor {
     /// Constructor for Registry Actor
@@ -317,7 +319,7 @@ impl ActorCode for Actor {
         BS: BlockStore,
         RT: Runtime<BS>,
     {
-        match FromPrimitive::from_u64(method) {
+        match FromPrimitive::from_u64(method.value()) {
             Some(Method::Constructor) => {
                 Self::constructor(rt, params.deserialize()?)?;
                 Ok(Serialized::default())
@@ -9,10 +9,11 @@ use encoding::{tuple::*, Cbor};
 use ipld_blockstore::BlockStore;
 use ipld_hamt::{BytesKey, Hamt};
 use num_bigint::bigint_ser::{BigIntDe, BigIntSer};
+use runtime::StateObject;
 
 use crate::builtin::verifreg::types::Datacap;
 
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct State {
     pub root_key: Address,
     pub verifiers: Cid,
@@ -124,3 +125,4 @@ impl State {
 }
 
 impl Cbor for State {}
+impl StateObject for State {}
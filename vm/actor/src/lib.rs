@@ -7,6 +7,17 @@ extern crate lazy_static;
 // workaround for a compiler bug, see https://github.com/rust-lang/rust/issues/55779
 extern crate serde;
 
+/// Implements the conversion of an actor's method enum into its method number.
+macro_rules! impl_method_num {
+    ($method:ty) => {
+        impl From<$method> for ::vm::MethodNum {
+            fn from(method: $method) -> Self {
+                ::vm::MethodNum::new(method as u64)
+            }
+        }
+    };
+}
+
 mod builtin;
 mod util;
 
@@ -18,6 +29,8 @@ use cid::Cid;
 use ipld_blockstore::BlockStore;
 use ipld_hamt::{BytesKey, Error as HamtError, Hamt};
 use num_bigint::BigUint;
+use std::array::TryFromSliceError;
+use std::convert::TryInto;
 use unsigned_varint::decode::Error as UVarintError;
 
 const HAMT_BIT_WIDTH: u8 = 5;
@@ -50,13 +63,37 @@ fn make_map_with_root<'bs, BS: BlockStore>(
     Hamt::load_with_bit_width(root, store, HAMT_BIT_WIDTH)
 }
 
+/// Encodes the key as an unsigned varint.
 pub fn u64_key(k: u64) -> BytesKey {
     let mut bz = unsigned_varint::encode::u64_buffer();
     unsigned_varint::encode::u64(k, &mut bz);
     bz.to_vec().into()
 }
 
+/// Decodes a key encoded with `u64_key`.
 pub fn parse_uint_key(s: &[u8]) -> Result<u64, UVarintError> {
     let (v, _) = unsigned_varint::decode::u64(s)?;
     Ok(v)
 }
+
+/// Encodes the key as a zigzag signed varint, which keeps small negative keys short.
+pub fn i64_key(k: i64) -> BytesKey {
+    u64_key(((k << 1) ^ (k >> 63)) as u64)
+}
+
+/// Decodes a key encoded with `i64_key`.
+pub fn parse_int_key(s: &[u8]) -> Result<i64, UVarintError> {
+    let v = parse_uint_key(s)?;
+    Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+}
+
+/// Encodes the key as 8 big-endian bytes, so that the byte order of keys is their numeric
+/// order.
+pub fn u64_be_key(k: u64) -> BytesKey {
+    k.to_be_bytes().to_vec().into()
+}
+
+/// Decodes a key encoded with `u64_be_key`, which must be exactly 8 bytes.
+pub fn parse_u64_be_key(s: &[u8]) -> Result<u64, TryFromSliceError> {
+    s.try_into().map(u64::from_be_bytes)
+}
@@ -5,9 +5,9 @@ use crate::{BytesKey, HAMT_BIT_WIDTH};
 use address::Address;
 use cid::Cid;
 use ipld_blockstore::BlockStore;
-use ipld_hamt::{Error, Hamt};
-use num_bigint::bigint_ser::BigIntDe;
-use vm::TokenAmount;
+use ipld_hamt::{Entry, Error, Hamt};
+use std::convert::TryFrom;
+use vm::{NonNegTokenAmount, TokenAmount};
 
 /// Balance table which handles getting and updating token balances specifically
 pub struct BalanceTable<'a, BS>(Hamt<'a, BytesKey, BS>);
@@ -34,42 +34,68 @@ where
     /// Gets token amount for given address in balance table
     #[inline]
     pub fn get(&self, key: &Address) -> Result<TokenAmount, String> {
+        Ok(self.get_amount(key)?.into())
+    }
+
+    fn get_amount(&self, key: &Address) -> Result<NonNegTokenAmount, String> {
         Ok(self
             .0
-            .get::<_, BigIntDe>(&key.to_bytes())?
+            .get::<_, NonNegTokenAmount>(&key.to_bytes())?
             // TODO investigate whether it's worth it to cache root to give better error details
-            .ok_or("no key {} in map root")?
-            .0)
+            .ok_or("no key {} in map root")?)
     }
 
     /// Checks if a balance for an address exists
     #[inline]
     pub fn has(&self, key: &Address) -> Result<bool, Error> {
-        match self.0.get::<_, BigIntDe>(&key.to_bytes())? {
+        match self.0.get::<_, NonNegTokenAmount>(&key.to_bytes())? {
             Some(_) => Ok(true),
             None => Ok(false),
         }
     }
 
-    /// Sets the balance for the address, overwriting previous value
+    /// Sets the balance for the address, overwriting previous value.
+    /// Balances can't be set to a negative value.
     #[inline]
     pub fn set(&mut self, key: &Address, value: TokenAmount) -> Result<(), Error> {
-        self.0.set(key.to_bytes().into(), BigIntDe(value))
+        let value = NonNegTokenAmount::try_from(value).map_err(Error::Other)?;
+        self.set_amount(key, value)
+    }
+
+    fn set_amount(&mut self, key: &Address, value: NonNegTokenAmount) -> Result<(), Error> {
+        self.0.set(key.to_bytes().into(), value)
     }
 
     /// Adds token amount to previously initialized account.
     pub fn add(&mut self, key: &Address, value: &TokenAmount) -> Result<(), String> {
-        let prev = self.get(key)?;
-        Ok(self.0.set(key.to_bytes().into(), BigIntDe(prev + value))?)
+        self.add_amount(key, value.clone(), false)
     }
 
     /// Adds an amount to a balance. Creates entry if not exists
     pub fn add_create(&mut self, key: &Address, value: TokenAmount) -> Result<(), String> {
-        let new_val = match self.0.get::<_, BigIntDe>(&key.to_bytes())? {
-            Some(v) => v.0 + value,
-            None => value,
-        };
-        Ok(self.0.set(key.to_bytes().into(), BigIntDe(new_val))?)
+        self.add_amount(key, value, true)
+    }
+
+    /// Adds an amount to a balance with a single lookup in the table. A missing balance is
+    /// created if `create` is set, and is an error otherwise.
+    fn add_amount(
+        &mut self,
+        key: &Address,
+        value: TokenAmount,
+        create: bool,
+    ) -> Result<(), String> {
+        match self.0.entry(key.to_bytes().into())? {
+            Entry::Occupied(mut entry) => {
+                let prev: NonNegTokenAmount = entry.get()?;
+                let new_val = NonNegTokenAmount::try_from(prev.into_token_amount() + value)?;
+                entry.insert(new_val)?;
+            }
+            Entry::Vacant(entry) if create => {
+                entry.insert(NonNegTokenAmount::try_from(value)?)?;
+            }
+            Entry::Vacant(_) => return Err(format!("no key {} in map root", key)),
+        }
+        Ok(())
     }
 
     /// Subtracts up to the specified amount from a balance, without reducing the balance
@@ -81,20 +107,19 @@ where
         req: &TokenAmount,
         floor: &TokenAmount,
     ) -> Result<TokenAmount, String> {
-        let prev = self.get(key)?;
-        let res = prev
-            .checked_sub(req)
-            .unwrap_or_else(|| TokenAmount::from(0u8));
-        let new_val: &TokenAmount = std::cmp::max(&res, floor);
-
-        if &prev > new_val {
-            // Subtraction needed, set new value and return change
-            self.0
-                .set(key.to_bytes().into(), BigIntDe(new_val.clone()))?;
-            Ok(prev - new_val)
-        } else {
+        let prev = self.get_amount(key)?;
+        let req = NonNegTokenAmount::try_from(req.clone())?;
+        let res = prev.checked_sub(&req).unwrap_or_default();
+        let new_val = std::cmp::max(res, NonNegTokenAmount::new_saturating(floor.clone()));
+
+        match prev.checked_sub(&new_val) {
+            Some(sub_amt) if sub_amt != NonNegTokenAmount::default() => {
+                // Subtraction needed, set new value and return change
+                self.set_amount(key, new_val)?;
+                Ok(sub_amt.into())
+            }
             // New value is same as previous, no change needed
-            Ok(TokenAmount::default())
+            _ => Ok(TokenAmount::default()),
         }
     }
 
@@ -122,15 +147,26 @@ where
         Ok(prev)
     }
 
+    /// Iterates over the balances in the table, in no particular order.
+    pub fn for_each<F>(&self, mut f: F) -> Result<(), String>
+    where
+        F: FnMut(&Address, TokenAmount) -> Result<(), String>,
+    {
+        self.0.for_each(|k: &BytesKey, v: NonNegTokenAmount| {
+            let addr = Address::from_bytes(&k.0).map_err(|e| e.to_string())?;
+            f(&addr, v.into())
+        })
+    }
+
     /// Returns total balance held by this balance table
     pub fn total(&self) -> Result<TokenAmount, String> {
-        let mut total = TokenAmount::default();
+        let mut total = NonNegTokenAmount::default();
 
-        self.0.for_each(|_, v: BigIntDe| {
-            total += v.0;
+        self.0.for_each(|_, v: NonNegTokenAmount| {
+            total += &v;
             Ok(())
         })?;
 
-        Ok(total)
+        Ok(total.into())
     }
 }
@@ -0,0 +1,62 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::{
+    i64_key, parse_int_key, parse_u64_be_key, parse_uint_key, u64_be_key, u64_key, BytesKey,
+};
+
+/// Integer key of a hamt. Each map is keyed through one of the key types, which fixes the
+/// encoding of its keys, as keys encoded differently are different entries of the map.
+pub trait MapKey: Sized {
+    /// Returns the encoded key.
+    fn key(&self) -> BytesKey;
+
+    /// Decodes an encoded key.
+    fn parse(bz: &[u8]) -> Result<Self, String>;
+}
+
+/// Unsigned varint key. Used by the maps keyed by sector number or deal ID, and by the deal
+/// ops of the market actor, whose epoch keys are cast to unsigned as in the spec actors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UintKey(pub u64);
+
+impl MapKey for UintKey {
+    fn key(&self) -> BytesKey {
+        u64_key(self.0)
+    }
+
+    fn parse(bz: &[u8]) -> Result<Self, String> {
+        parse_uint_key(bz).map(Self).map_err(|e| e.to_string())
+    }
+}
+
+/// Signed varint key, for maps keyed by epochs which may be negative. Used by the cron
+/// events of the power actor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntKey(pub i64);
+
+impl MapKey for IntKey {
+    fn key(&self) -> BytesKey {
+        i64_key(self.0)
+    }
+
+    fn parse(bz: &[u8]) -> Result<Self, String> {
+        parse_int_key(bz).map(Self).map_err(|e| e.to_string())
+    }
+}
+
+/// Fixed width big-endian key, for maps whose keys are iterated in numeric order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BigEndianKey(pub u64);
+
+impl MapKey for BigEndianKey {
+    fn key(&self) -> BytesKey {
+        u64_be_key(self.0)
+    }
+
+    fn parse(bz: &[u8]) -> Result<Self, String> {
+        parse_u64_be_key(bz)
+            .map(Self)
+            .map_err(|_| format!("big-endian key must be 8 bytes, was {}", bz.len()))
+    }
+}
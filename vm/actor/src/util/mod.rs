@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod balance_table;
+mod map_key;
 mod multimap;
+mod pending_change;
 mod set;
 mod set_multimap;
 
 pub use self::balance_table::BalanceTable;
+pub use self::map_key::{BigEndianKey, IntKey, MapKey, UintKey};
 pub use self::multimap::*;
+pub use self::pending_change::PendingChange;
 pub use self::set::Set;
 pub use self::set_multimap::SetMultimap;
@@ -0,0 +1,92 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use clock::ChainEpoch;
+use serde::{de, ser, Deserialize, Serialize};
+use vm::{actor_error, ActorError, ExitCode};
+
+/// Change to a value which is proposed by one party and only applied once confirmed after
+/// the effective epoch. Used for two-phase changes such as miner worker key changes and
+/// multisig signer swaps.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PendingChange<T> {
+    /// Value to apply when the change is confirmed
+    pub value: T,
+    /// Epoch at which the change can be confirmed
+    pub effective_at: ChainEpoch,
+}
+
+impl<T> PendingChange<T> {
+    /// Creates a pending change which can be confirmed starting at `effective_at`.
+    pub fn new(value: T, effective_at: ChainEpoch) -> Self {
+        Self {
+            value,
+            effective_at,
+        }
+    }
+
+    /// Returns true if the change can be confirmed at the given epoch.
+    pub fn is_effective(&self, curr_epoch: ChainEpoch) -> bool {
+        curr_epoch >= self.effective_at
+    }
+
+    /// Returns true if the change was not confirmed within `lifetime` epochs of
+    /// becoming effective.
+    pub fn is_expired(&self, curr_epoch: ChainEpoch, lifetime: ChainEpoch) -> bool {
+        curr_epoch >= self.effective_at + lifetime
+    }
+
+    /// Takes the pending change and returns the new value if it can be confirmed at the
+    /// current epoch. Changes with a `lifetime` expire if left unconfirmed for that many
+    /// epochs after becoming effective.
+    pub fn confirm(
+        pending: &mut Option<Self>,
+        curr_epoch: ChainEpoch,
+        lifetime: Option<ChainEpoch>,
+    ) -> Result<T, ActorError> {
+        let change = pending
+            .as_ref()
+            .ok_or_else(|| actor_error!(ErrIllegalState; "no pending change"))?;
+
+        if !change.is_effective(curr_epoch) {
+            return Err(actor_error!(ErrIllegalState;
+                "too early for change. Current: {}, Change: {}", curr_epoch, change.effective_at));
+        }
+        if let Some(lifetime) = lifetime {
+            if change.is_expired(curr_epoch, lifetime) {
+                return Err(actor_error!(ErrIllegalState;
+                    "pending change expired at {}", change.effective_at + lifetime));
+            }
+        }
+
+        Ok(pending.take().expect("pending change checked above").value)
+    }
+}
+
+impl<T> Serialize for PendingChange<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        (&self.value, &self.effective_at).serialize(s)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for PendingChange<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let (value, effective_at) = Deserialize::deserialize(deserializer)?;
+        Ok(Self {
+            value,
+            effective_at,
+        })
+    }
+}
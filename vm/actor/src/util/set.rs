@@ -50,6 +50,12 @@ where
         Ok(self.0.get::<_, ()>(key)?.is_some())
     }
 
+    /// Returns true if the set has no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Deletes key from set.
     #[inline]
     pub fn delete(&mut self, key: &[u8]) -> Result<(), String> {
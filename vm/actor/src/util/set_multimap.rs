@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::Set;
-use crate::{parse_uint_key, u64_key, BytesKey, DealID, HAMT_BIT_WIDTH};
+use crate::{BytesKey, DealID, MapKey, UintKey, HAMT_BIT_WIDTH};
+use address::Address;
 use cid::Cid;
 use clock::ChainEpoch;
 use ipld_blockstore::BlockStore;
@@ -11,7 +12,7 @@ use std::borrow::Borrow;
 use std::error::Error as StdError;
 
 /// SetMultimap is a hamt with values that are also a hamt but are of the set variant.
-/// This allows hash sets to be indexable by an address.
+/// This allows hash sets to be indexable by an epoch or an address.
 pub struct SetMultimap<'a, BS>(Hamt<'a, BytesKey, BS>);
 impl<'a, BS> SetMultimap<'a, BS>
 where
@@ -35,70 +36,140 @@ where
 
     /// Puts the DealID in the hash set of the key.
     pub fn put(&mut self, key: ChainEpoch, value: DealID) -> Result<(), String> {
+        self.put_key(UintKey(key as u64).key(), value)
+    }
+
+    /// Puts the DealID in the hash set of the address.
+    pub fn put_address(&mut self, key: &Address, value: DealID) -> Result<(), String> {
+        self.put_key(BytesKey(key.to_bytes()), value)
+    }
+
+    fn put_key(&mut self, key: BytesKey, value: DealID) -> Result<(), String> {
         // Get construct amt from retrieved cid or create new
-        let mut set = self.get(key)?.unwrap_or_else(|| Set::new(self.0.store()));
+        let mut set = self
+            .get_key(&key)?
+            .unwrap_or_else(|| Set::new(self.0.store()));
 
-        set.put(u64_key(value))?;
+        set.put(UintKey(value).key())?;
 
         // Save and calculate new root
         let new_root = set.root()?;
 
         // Set hamt node to set new root
-        Ok(self.0.set(u64_key(key as u64), &new_root)?)
+        Ok(self.0.set(key, &new_root)?)
     }
 
     /// Gets the set at the given index of the `SetMultimap`
     #[inline]
     pub fn get(&self, key: ChainEpoch) -> Result<Option<Set<'a, BS>>, String> {
-        match self.0.get(&u64_key(key as u64))? {
+        self.get_key(&UintKey(key as u64).key())
+    }
+
+    fn get_key(&self, key: &[u8]) -> Result<Option<Set<'a, BS>>, String> {
+        match self.0.get(key)? {
             Some(cid) => Ok(Some(Set::from_root(self.0.store(), &cid)?)),
             None => Ok(None),
         }
     }
 
-    /// Removes a DealID from a key hash set.
+    /// Removes a DealID from a key hash set. The set is kept when it becomes empty, as in the
+    /// spec.
     #[inline]
     pub fn remove(&mut self, key: ChainEpoch, v: DealID) -> Result<(), String> {
+        self.remove_key(UintKey(key as u64).key(), v, false)
+    }
+
+    /// Removes a DealID from the hash set of the address, deleting the set once it is empty.
+    pub fn remove_address(&mut self, key: &Address, v: DealID) -> Result<(), String> {
+        self.remove_key(BytesKey(key.to_bytes()), v, true)
+    }
+
+    fn remove_key(&mut self, key: BytesKey, v: DealID, delete_empty: bool) -> Result<(), String> {
         // Get construct amt from retrieved cid and return if no set exists
-        let mut set = match self.get(key)? {
+        let mut set = match self.get_key(&key)? {
             Some(s) => s,
             None => return Ok(()),
         };
 
-        set.delete(u64_key(v).borrow())?;
+        set.delete(UintKey(v).key().borrow())?;
+
+        if delete_empty && set.is_empty() {
+            self.0.delete(&key)?;
+            return Ok(());
+        }
 
         // Save and calculate new root
         let new_root = set.root()?;
 
-        Ok(self.0.set(u64_key(key as u64), &new_root)?)
+        Ok(self.0.set(key, &new_root)?)
     }
 
     /// Removes set at index.
     #[inline]
     pub fn remove_all(&mut self, key: ChainEpoch) -> Result<(), String> {
         // Remove entry from table
-        self.0.delete(&u64_key(key as u64))?;
+        self.0.delete(&UintKey(key as u64).key())?;
 
         Ok(())
     }
 
     /// Iterates through keys and converts them to a DealID to call a function on each.
-    pub fn for_each<F>(&self, key: ChainEpoch, mut f: F) -> Result<(), Box<dyn StdError>>
+    pub fn for_each<F>(&self, key: ChainEpoch, f: F) -> Result<(), Box<dyn StdError>>
+    where
+        F: FnMut(DealID) -> Result<(), Box<dyn StdError>>,
+    {
+        self.for_each_key(&UintKey(key as u64).key(), f)
+    }
+
+    /// Calls a function on each DealID in the hash set of the address.
+    pub fn for_each_address<F>(&self, key: &Address, f: F) -> Result<(), Box<dyn StdError>>
+    where
+        F: FnMut(DealID) -> Result<(), Box<dyn StdError>>,
+    {
+        self.for_each_key(&key.to_bytes(), f)
+    }
+
+    fn for_each_key<F>(&self, key: &[u8], mut f: F) -> Result<(), Box<dyn StdError>>
     where
         F: FnMut(DealID) -> Result<(), Box<dyn StdError>>,
     {
         // Get construct amt from retrieved cid and return if no set exists
-        let set = match self.get(key)? {
+        let set = match self.get_key(key)? {
             Some(s) => s,
             None => return Ok(()),
         };
 
         set.for_each(|k| {
-            let v = parse_uint_key(&k)
+            let UintKey(v) = UintKey::parse(&k)
                 .map_err(|e| format!("Could not parse key: {:?}, ({})", &k.0, e))?;
 
             // Run function on all parsed keys
             f(v)
         })
     }
+
+    /// Returns the keys of all sets in a `SetMultimap` indexed by epoch, in the order of the
+    /// hamt.
+    pub fn keys(&self) -> Result<Vec<ChainEpoch>, Box<dyn StdError>> {
+        let mut keys = Vec::new();
+        self.0.for_each(|k, _: Cid| {
+            let UintKey(epoch) = UintKey::parse(&k)
+                .map_err(|e| format!("Could not parse key: {:?}, ({})", &k.0, e))?;
+            keys.push(epoch as ChainEpoch);
+            Ok(())
+        })?;
+        Ok(keys)
+    }
+
+    /// Iterates through all sets of the `SetMultimap` and calls a function with the key of
+    /// the set and each DealID in it.
+    pub fn for_each_all<F>(&self, mut f: F) -> Result<(), Box<dyn StdError>>
+    where
+        F: FnMut(ChainEpoch, DealID) -> Result<(), Box<dyn StdError>>,
+    {
+        for key in self.keys()? {
+            self.for_each(key, |v| f(key, v))?;
+        }
+        Ok(())
+    }
 }
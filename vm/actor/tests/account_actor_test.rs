@@ -6,7 +6,7 @@ mod common;
 use actor::{account::State, ACCOUNT_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID};
 use address::Address;
 use common::*;
-use vm::{ExitCode, Serialized};
+use vm::{ExitCode, MethodNum, Serialized};
 
 macro_rules! account_tests {
     ($($name:ident: $value:expr,)*) => {
@@ -27,7 +27,7 @@ macro_rules! account_tests {
                     rt
                     .call(
                         &*ACCOUNT_ACTOR_CODE_ID,
-                        1,
+                        MethodNum::new(1),
                         &Serialized::serialize(addr).unwrap(),
                     )
                     .unwrap();
@@ -38,7 +38,7 @@ macro_rules! account_tests {
                     rt.expect_validate_caller_any();
 
                     let pk: Address = rt
-                        .call(&*ACCOUNT_ACTOR_CODE_ID, 2, &Serialized::default())
+                        .call(&*ACCOUNT_ACTOR_CODE_ID, MethodNum::new(2), &Serialized::default())
                         .unwrap()
                         .deserialize()
                         .unwrap();
@@ -46,7 +46,7 @@ macro_rules! account_tests {
                 } else {
                     let res = rt.call(
                         &*ACCOUNT_ACTOR_CODE_ID,
-                        1,
+                        MethodNum::new(1),
                         &Serialized::serialize(addr).unwrap(),
                     ).map_err(|e| e.exit_code());
                     assert_eq!(res, Err(exit_code))
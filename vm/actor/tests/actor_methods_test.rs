@@ -0,0 +1,55 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::{actor_methods, market, miner, BUILTIN_ACTOR_METHODS};
+use std::collections::HashSet;
+use vm::{MethodNum, METHOD_CONSTRUCTOR};
+
+#[test]
+fn method_tables() {
+    for actor in BUILTIN_ACTOR_METHODS {
+        let mut nums = HashSet::new();
+        for m in actor.methods {
+            assert!(
+                nums.insert(m.num),
+                "duplicate method {} in {}",
+                m.num,
+                actor.actor
+            );
+        }
+        assert!(actor.method(METHOD_CONSTRUCTOR).is_some());
+    }
+
+    let market = actor_methods("market").unwrap();
+    let publish = market
+        .method(market::Method::PublishStorageDeals.into())
+        .unwrap();
+    assert_eq!(publish.name, "PublishStorageDeals");
+    assert_eq!(publish.ret, Some("market::PublishStorageDealsReturn"));
+    assert!(actor_methods("unknown").is_none());
+}
+
+#[test]
+fn generated_client_source() {
+    let src = actor_methods("market").unwrap().client_source();
+    // Singleton actors don't take the receiver address
+    assert!(src.contains(
+        "pub fn add_balance(from: Address, value: TokenAmount, params: &Address) -> Result<UnsignedMessage, String>"
+    ));
+    assert!(src.contains(".to(*STORAGE_MARKET_ACTOR_ADDR)"));
+    assert!(src.contains("pub fn decode_publish_storage_deals_return(ret: &Serialized)"));
+    // Constructors are only called by the system
+    assert!(!src.contains("pub fn constructor("));
+
+    let src = actor_methods("miner").unwrap().client_source();
+    assert!(src.contains("pub fn submit_windowed_post(from: Address, to: Address,"));
+    assert!(src.contains(&format!(
+        ".method_num(MethodNum::new({}))",
+        MethodNum::from(miner::Method::AddLockedFund)
+    )));
+    assert!(src.contains("Serialized::serialize(params)"));
+
+    let src = actor_methods("reward").unwrap().client_source();
+    assert!(src.contains("Serialized::serialize(BigIntSer(params))"));
+    assert!(src.contains("-> Result<TokenAmount, EncodingError> {\n    ret.deserialize()"));
+}
@@ -0,0 +1,241 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Model tests of balance tables, which apply random sequences of operations to the Hamt
+//! backed tables and to plain maps, and check that the balances never diverge.
+
+use actor::BalanceTable;
+use address::Address;
+use db::MemoryDB;
+use num_traits::ToPrimitive;
+use proptest::prelude::*;
+use std::collections::HashMap;
+use vm::TokenAmount;
+
+/// Number of distinct addresses operations are applied to, kept small so that operations
+/// frequently hit existing balances.
+const NUM_KEYS: u64 = 4;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Add(u64, u64),
+    AddCreate(u64, u64),
+    SubtractWithMinimum(u64, u64, u64),
+    MustSubtract(u64, u64),
+    Remove(u64),
+    Set(u64, u64),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let key = 0..NUM_KEYS;
+    let amount = 0..1000u64;
+    prop_oneof![
+        (key.clone(), amount.clone()).prop_map(|(k, v)| Op::Add(k, v)),
+        (key.clone(), amount.clone()).prop_map(|(k, v)| Op::AddCreate(k, v)),
+        (key.clone(), amount.clone(), amount.clone())
+            .prop_map(|(k, v, floor)| Op::SubtractWithMinimum(k, v, floor)),
+        (key.clone(), amount.clone()).prop_map(|(k, v)| Op::MustSubtract(k, v)),
+        key.clone().prop_map(Op::Remove),
+        (key, amount).prop_map(|(k, v)| Op::Set(k, v)),
+    ]
+}
+
+/// Plain map implementation of the balance table operations.
+#[derive(Default)]
+struct Model(HashMap<u64, u64>);
+
+impl Model {
+    /// Applies the operation, returning the amount the table returns for it or `None` if the
+    /// operation fails.
+    fn apply(&mut self, op: &Op) -> Option<u64> {
+        match *op {
+            Op::Add(k, v) => {
+                *self.0.get_mut(&k)? += v;
+                Some(0)
+            }
+            Op::AddCreate(k, v) => {
+                *self.0.entry(k).or_default() += v;
+                Some(0)
+            }
+            Op::SubtractWithMinimum(k, v, floor) => self.subtract_with_minimum(k, v, floor),
+            Op::MustSubtract(k, v) => {
+                // The balance is reduced to zero before the shortfall is reported
+                let subtracted = self.subtract_with_minimum(k, v, 0)?;
+                if subtracted == v {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            Op::Remove(k) => self.0.remove(&k),
+            Op::Set(k, v) => {
+                self.0.insert(k, v);
+                Some(0)
+            }
+        }
+    }
+
+    fn subtract_with_minimum(&mut self, k: u64, v: u64, floor: u64) -> Option<u64> {
+        let prev = self.0.get_mut(&k)?;
+        let new_val = prev.saturating_sub(v).max(floor);
+        if new_val >= *prev {
+            return Some(0);
+        }
+        let subtracted = *prev - new_val;
+        *prev = new_val;
+        Some(subtracted)
+    }
+}
+
+fn apply(bt: &mut BalanceTable<MemoryDB>, op: &Op) -> Option<u64> {
+    let addr = |k: u64| Address::new_id(k);
+    let amount = |v: u64| TokenAmount::from(v);
+    let res = match *op {
+        Op::Add(k, v) => bt.add(&addr(k), &amount(v)).map(|_| TokenAmount::default()),
+        Op::AddCreate(k, v) => bt
+            .add_create(&addr(k), amount(v))
+            .map(|_| TokenAmount::default()),
+        Op::SubtractWithMinimum(k, v, floor) => {
+            bt.subtract_with_minimum(&addr(k), &amount(v), &amount(floor))
+        }
+        Op::MustSubtract(k, v) => bt
+            .must_subtract(&addr(k), &amount(v))
+            .map(|_| TokenAmount::default()),
+        Op::Remove(k) => bt.remove(&addr(k)),
+        Op::Set(k, v) => bt.set(&addr(k), amount(v)).map(|_| TokenAmount::default()),
+    };
+    res.ok().map(|v| v.to_u64().unwrap())
+}
+
+/// Returns the balances of the table by actor ID.
+fn balances(bt: &BalanceTable<MemoryDB>) -> HashMap<u64, u64> {
+    let mut balances = HashMap::new();
+    bt.for_each(|addr, v| {
+        balances.insert(addr.id().unwrap(), v.to_u64().unwrap());
+        Ok(())
+    })
+    .unwrap();
+    balances
+}
+
+proptest! {
+    #[test]
+    fn balance_table_matches_model(ops in prop::collection::vec(op(), 1..64)) {
+        let store = MemoryDB::default();
+        let mut bt = BalanceTable::new(&store);
+        let mut model = Model::default();
+
+        for op in &ops {
+            prop_assert_eq!(apply(&mut bt, op), model.apply(op), "{:?}", op);
+            prop_assert_eq!(&balances(&bt), &model.0);
+            prop_assert_eq!(
+                bt.total().unwrap(),
+                TokenAmount::from(model.0.values().sum::<u64>())
+            );
+        }
+
+        // Balances are the same after a round trip through the store
+        let root = bt.root().unwrap();
+        prop_assert_eq!(&balances(&BalanceTable::from_root(&store, &root).unwrap()), &model.0);
+    }
+}
+
+#[derive(Debug, Clone)]
+enum EscrowOp {
+    Deposit(u64, u64),
+    Lock(u64, u64),
+    Unlock(u64, u64),
+    Withdraw(u64, u64),
+}
+
+fn escrow_op() -> impl Strategy<Value = EscrowOp> {
+    let key = 0..NUM_KEYS;
+    let amount = 0..1000u64;
+    prop_oneof![
+        (key.clone(), amount.clone()).prop_map(|(k, v)| EscrowOp::Deposit(k, v)),
+        (key.clone(), amount.clone()).prop_map(|(k, v)| EscrowOp::Lock(k, v)),
+        (key.clone(), amount.clone()).prop_map(|(k, v)| EscrowOp::Unlock(k, v)),
+        (key, amount).prop_map(|(k, v)| EscrowOp::Withdraw(k, v)),
+    ]
+}
+
+proptest! {
+    /// Applies the escrow and locked table operations of the market actor, reverting the
+    /// tables when an operation fails as the actor would, and checks that locked balances
+    /// never exceed escrow balances.
+    #[test]
+    fn escrow_covers_locked(ops in prop::collection::vec(escrow_op(), 1..64)) {
+        let store = MemoryDB::default();
+        let mut escrow_root = BalanceTable::new(&store).root().unwrap();
+        let mut locked_root = escrow_root.clone();
+        let mut escrow = HashMap::<u64, u64>::new();
+        let mut locked = HashMap::<u64, u64>::new();
+
+        for op in &ops {
+            let mut et = BalanceTable::from_root(&store, &escrow_root).unwrap();
+            let mut lt = BalanceTable::from_root(&store, &locked_root).unwrap();
+            let res = match *op {
+                EscrowOp::Deposit(k, v) => {
+                    *escrow.entry(k).or_default() += v;
+                    locked.entry(k).or_default();
+                    et.add_create(&Address::new_id(k), TokenAmount::from(v)).and_then(|_| {
+                        lt.add_create(&Address::new_id(k), TokenAmount::default())
+                    })
+                }
+                EscrowOp::Lock(k, v) => {
+                    let addr = Address::new_id(k);
+                    let available = escrow.get(&k).copied();
+                    let prev = locked.get(&k).copied();
+                    match (available, prev) {
+                        (Some(available), Some(prev)) if prev + v <= available => {
+                            locked.insert(k, prev + v);
+                            lt.add(&addr, &TokenAmount::from(v))
+                        }
+                        _ => Err("not enough balance to lock".to_owned()),
+                    }
+                }
+                EscrowOp::Unlock(k, v) => {
+                    let expected = locked.get(&k).copied().filter(|&prev| prev >= v);
+                    let res = lt.must_subtract(&Address::new_id(k), &TokenAmount::from(v));
+                    prop_assert_eq!(res.is_ok(), expected.is_some(), "{:?}", op);
+                    if let Some(prev) = expected {
+                        locked.insert(k, prev - v);
+                    }
+                    res
+                }
+                EscrowOp::Withdraw(k, v) => {
+                    let min = locked.get(&k).copied().unwrap_or_default();
+                    let res = et.subtract_with_minimum(
+                        &Address::new_id(k),
+                        &TokenAmount::from(v),
+                        &TokenAmount::from(min),
+                    );
+                    prop_assert_eq!(res.is_ok(), escrow.contains_key(&k), "{:?}", op);
+                    if let Some(prev) = escrow.get_mut(&k) {
+                        let new_val = prev.saturating_sub(v).max(min).min(*prev);
+                        prop_assert_eq!(
+                            res.as_ref().unwrap(),
+                            &TokenAmount::from(*prev - new_val)
+                        );
+                        *prev = new_val;
+                    }
+                    res.map(|_| ())
+                }
+            };
+
+            // Failed operations are reverted
+            if res.is_ok() {
+                escrow_root = et.root().unwrap();
+                locked_root = lt.root().unwrap();
+            }
+
+            let et = BalanceTable::from_root(&store, &escrow_root).unwrap();
+            let lt = BalanceTable::from_root(&store, &locked_root).unwrap();
+            prop_assert_eq!(&balances(&et), &escrow);
+            prop_assert_eq!(&balances(&lt), &locked);
+            for (k, locked) in &locked {
+                prop_assert!(locked <= &escrow[k], "locked exceeds escrow for {}", k);
+            }
+        }
+    }
+}
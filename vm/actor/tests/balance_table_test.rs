@@ -3,12 +3,14 @@
 
 use actor::BalanceTable;
 use address::Address;
-use vm::TokenAmount;
+use rand::Rng;
+use std::collections::HashMap;
+use vm::{testing::TestGen, TokenAmount};
 
 // Ported test from specs-actors
 #[test]
 fn add_create() {
-    let addr = Address::new_id(100);
+    let addr = TestGen::new(0).address();
     let store = db::MemoryDB::default();
     let mut bt = BalanceTable::new(&store);
 
@@ -24,8 +26,9 @@ fn add_create() {
 // Ported test from specs-actors
 #[test]
 fn total() {
-    let addr1 = Address::new_id(100);
-    let addr2 = Address::new_id(101);
+    let mut gen = TestGen::new(0);
+    let addr1 = gen.address();
+    let addr2 = gen.address();
     let store = db::MemoryDB::default();
     let mut bt = BalanceTable::new(&store);
 
@@ -68,7 +71,7 @@ fn total() {
 
 #[test]
 fn balance_subtracts() {
-    let addr = Address::new_id(100);
+    let addr = TestGen::new(0).address();
     let store = db::MemoryDB::default();
     let mut bt = BalanceTable::new(&store);
 
@@ -98,7 +101,7 @@ fn balance_subtracts() {
 
 #[test]
 fn remove() {
-    let addr = Address::new_id(100);
+    let addr = TestGen::new(0).address();
     let store = db::MemoryDB::default();
     let mut bt = BalanceTable::new(&store);
 
@@ -107,3 +110,45 @@ fn remove() {
     bt.remove(&addr).unwrap();
     assert!(bt.get(&addr).is_err());
 }
+
+#[test]
+fn rejects_negative_balances() {
+    let addr = TestGen::new(0).address();
+    let store = db::MemoryDB::default();
+    let mut bt = BalanceTable::new(&store);
+
+    assert!(bt.set(&addr, TokenAmount::from(-1)).is_err());
+    assert_eq!(bt.has(&addr), Ok(false));
+
+    bt.add_create(&addr, TokenAmount::from(10u8)).unwrap();
+    assert!(bt.add(&addr, &TokenAmount::from(-11)).is_err());
+    assert!(bt.add_create(&addr, TokenAmount::from(-11)).is_err());
+    assert!(bt
+        .subtract_with_minimum(&addr, &TokenAmount::from(-5), &TokenAmount::from(0u8))
+        .is_err());
+    assert_eq!(bt.get(&addr), Ok(TokenAmount::from(10u8)));
+}
+
+#[test]
+fn random_balances() {
+    for seed in 0..20 {
+        let mut gen = TestGen::new(seed);
+        let store = db::MemoryDB::default();
+        let mut bt = BalanceTable::new(&store);
+
+        let addrs: Vec<Address> = (0..10).map(|_| gen.address()).collect();
+        let mut expected: HashMap<Address, TokenAmount> = HashMap::new();
+        for _ in 0..50 {
+            let addr = addrs[gen.rng().gen_range(0, addrs.len())];
+            let amount = gen.token_amount(1_000);
+            bt.add_create(&addr, amount.clone()).unwrap();
+            *expected.entry(addr).or_default() += amount;
+        }
+
+        for (addr, balance) in &expected {
+            assert_eq!(bt.get(addr).as_ref(), Ok(balance), "seed {}", seed);
+        }
+        let total: TokenAmount = expected.values().sum();
+        assert_eq!(bt.total(), Ok(total), "seed {}", seed);
+    }
+}
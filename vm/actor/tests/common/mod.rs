@@ -2,19 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use actor::{
-    self, ACCOUNT_ACTOR_CODE_ID, CRON_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID, MARKET_ACTOR_CODE_ID,
-    MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID, POWER_ACTOR_CODE_ID,
-    REWARD_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID, VERIFREG_ACTOR_CODE_ID,
+    self, ACCOUNT_ACTOR_CODE_ID, CRON_ACTOR_ADDR, CRON_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID,
+    MARKET_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID,
+    POWER_ACTOR_CODE_ID, REWARD_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID, VERIFREG_ACTOR_CODE_ID,
 };
 use address::Address;
 use cid::{multihash::Blake2b256, Cid};
 use clock::ChainEpoch;
-use crypto::{DomainSeparationTag, Signature};
+use crypto::{DomainSeparationTag, Signature, SignatureError};
 use db::MemoryDB;
 use encoding::{blake2b_256, de::DeserializeOwned, Cbor};
-use fil_types::{PieceInfo, RegisteredSealProof, SealVerifyInfo, WindowPoStVerifyInfo};
+use fil_types::{
+    NetworkVersion, PieceInfo, RegisteredSealProof, SealVerifyInfo, WindowPoStVerifyInfo,
+};
 use ipld_blockstore::BlockStore;
-use runtime::{ActorCode, ConsensusFault, MessageInfo, Runtime, Syscalls};
+use runtime::{ActorCode, ConsensusFault, LogLevel, MessageInfo, Runtime, StateObject, Syscalls};
 use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, VecDeque};
 use std::error::Error as StdError;
@@ -22,6 +24,7 @@ use vm::{actor_error, ActorError, ExitCode, MethodNum, Randomness, Serialized, T
 
 pub struct MockRuntime {
     pub epoch: ChainEpoch,
+    pub network_version: NetworkVersion,
     pub miner: Address,
     pub id_addresses: HashMap<Address, Address>,
     pub actor_code_cids: HashMap<Address, Cid>,
@@ -54,12 +57,21 @@ pub struct MockRuntime {
     pub expect_verify_post: RefCell<Option<ExpectVerifyPoSt>>,
     pub expect_compute_unsealed_sector_cid: RefCell<Option<ExpectComputeUnsealedSectorCid>>,
     pub expect_verify_consensus_fault: RefCell<Option<ExpectVerifyConsensusFault>>,
+    pub expect_get_randomness_tickets: RefCell<VecDeque<ExpectRandomness>>,
+    pub expect_get_randomness_beacon: RefCell<VecDeque<ExpectRandomness>>,
+
+    // Called when the next expected message is sent, used to simulate the receiver calling back
+    pub on_send: Option<Box<dyn FnOnce(&mut MockRuntime)>>,
+
+    // Methods of the actor under test called by the cron actor at the end of every epoch
+    pub cron_calls: Vec<(Cid, MethodNum)>,
 }
 
 impl Default for MockRuntime {
     fn default() -> Self {
         Self {
             epoch: Default::default(),
+            network_version: NetworkVersion::V0,
             miner: Address::new_id(0),
             id_addresses: Default::default(),
             actor_code_cids: Default::default(),
@@ -84,6 +96,10 @@ impl Default for MockRuntime {
             expect_verify_post: Default::default(),
             expect_compute_unsealed_sector_cid: Default::default(),
             expect_verify_consensus_fault: Default::default(),
+            expect_get_randomness_tickets: Default::default(),
+            expect_get_randomness_beacon: Default::default(),
+            on_send: None,
+            cron_calls: Default::default(),
         }
     }
 }
@@ -110,7 +126,7 @@ pub struct ExpectedVerifySig {
     pub sig: Signature,
     pub signer: Address,
     pub plaintext: Vec<u8>,
-    pub result: ExitCode,
+    pub result: Result<(), SignatureError>,
 }
 
 #[derive(Clone, Debug)]
@@ -135,6 +151,14 @@ pub struct ExpectVerifyConsensusFault {
     exit_code: ExitCode,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExpectRandomness {
+    tag: DomainSeparationTag,
+    epoch: ChainEpoch,
+    entropy: Vec<u8>,
+    out: Randomness,
+}
+
 #[derive(Clone)]
 pub struct ExpectComputeUnsealedSectorCid {
     reg: RegisteredSealProof,
@@ -156,9 +180,6 @@ impl MockRuntime {
         }
         Ok(())
     }
-    fn put<C: Cbor>(&self, o: &C) -> Result<Cid, ActorError> {
-        Ok(self.store.put(&o, Blake2b256).unwrap())
-    }
     fn _get<T: DeserializeOwned>(&self, cid: Cid) -> Result<T, ActorError> {
         Ok(self.store.get(&cid).unwrap().unwrap())
     }
@@ -172,6 +193,12 @@ impl MockRuntime {
             .unwrap();
         Ok(data)
     }
+
+    /// Replaces the state of the actor, as if it had been stored by a previous call.
+    #[allow(dead_code)]
+    pub fn replace_state<C: Cbor>(&mut self, obj: &C) {
+        self.state = Some(self.store.put(obj, Blake2b256).unwrap());
+    }
     pub fn expect_validate_caller_addr(&mut self, addr: Vec<Address>) {
         assert!(addr.len() > 0, "addrs must be non-empty");
         self.expect_validate_caller_addr = Some(addr);
@@ -201,6 +228,67 @@ impl MockRuntime {
         });
     }
 
+    #[allow(dead_code)]
+    pub fn expect_get_randomness_from_tickets(
+        &self,
+        tag: DomainSeparationTag,
+        epoch: ChainEpoch,
+        entropy: Vec<u8>,
+        out: Randomness,
+    ) {
+        self.expect_get_randomness_tickets
+            .borrow_mut()
+            .push_back(ExpectRandomness {
+                tag,
+                epoch,
+                entropy,
+                out,
+            });
+    }
+
+    #[allow(dead_code)]
+    pub fn expect_get_randomness_from_beacon(
+        &self,
+        tag: DomainSeparationTag,
+        epoch: ChainEpoch,
+        entropy: Vec<u8>,
+        out: Randomness,
+    ) {
+        self.expect_get_randomness_beacon
+            .borrow_mut()
+            .push_back(ExpectRandomness {
+                tag,
+                epoch,
+                entropy,
+                out,
+            });
+    }
+
+    /// Checks a randomness request against the next expectation of the source, and returns
+    /// the expected randomness.
+    fn expected_randomness(
+        &self,
+        expectations: &RefCell<VecDeque<ExpectRandomness>>,
+        tag: DomainSeparationTag,
+        epoch: ChainEpoch,
+        entropy: &[u8],
+    ) -> Result<Randomness, ActorError> {
+        self.require_in_call();
+        if epoch > self.epoch {
+            return Err(actor_error!(ErrIllegalArgument;
+                "cannot draw randomness from future epoch {}, current epoch is {}",
+                epoch, self.epoch));
+        }
+        let exp = expectations
+            .borrow_mut()
+            .pop_front()
+            .expect("unexpected call to get randomness");
+        assert_eq!(exp.tag, tag, "unexpected domain separation tag");
+        assert_eq!(exp.epoch, epoch, "unexpected randomness epoch");
+        assert_eq!(exp.entropy, entropy, "unexpected randomness entropy");
+        Ok(exp.out)
+    }
+
     #[allow(dead_code)]
     pub fn expect_compute_unsealed_sector_cid(&self, exp: ExpectComputeUnsealedSectorCid) {
         *self.expect_compute_unsealed_sector_cid.borrow_mut() = Some(exp);
@@ -313,6 +401,16 @@ impl MockRuntime {
                 .is_none(),
             "expect_verify_consensus_fault not received",
         );
+        assert!(
+            self.expect_get_randomness_tickets.borrow().is_empty(),
+            "expect_get_randomness_from_tickets {:?}, not received",
+            self.expect_get_randomness_tickets.borrow()
+        );
+        assert!(
+            self.expect_get_randomness_beacon.borrow().is_empty(),
+            "expect_get_randomness_from_beacon {:?}, not received",
+            self.expect_get_randomness_beacon.borrow()
+        );
 
         self.reset();
     }
@@ -326,6 +424,8 @@ impl MockRuntime {
         *self.expect_verify_post.borrow_mut() = None;
         *self.expect_compute_unsealed_sector_cid.borrow_mut() = None;
         *self.expect_verify_consensus_fault.borrow_mut() = None;
+        self.expect_get_randomness_tickets.borrow_mut().clear();
+        self.expect_get_randomness_beacon.borrow_mut().clear();
     }
 
     #[allow(dead_code)]
@@ -377,6 +477,44 @@ impl MockRuntime {
     pub fn set_value(&mut self, value: TokenAmount) {
         self.value_received = value;
     }
+
+    /// Advances the current epoch by `n` epochs.
+    #[allow(dead_code)]
+    pub fn advance_epoch(&mut self, n: ChainEpoch) {
+        self.epoch += n;
+    }
+
+    /// Registers a method of the actor with the given code to be called by the cron actor at the
+    /// end of every epoch, such as the market CronTick or power OnEpochTickEnd.
+    #[allow(dead_code)]
+    pub fn register_cron_call(&mut self, code: Cid, method: MethodNum) {
+        self.cron_calls.push((code, method));
+    }
+
+    /// Advances the current epoch by `n` epochs one at a time, making the registered cron calls
+    /// at the end of each epoch. `expect` is called before each cron call to set up the
+    /// expectations of the call, with the epoch being ended.
+    #[allow(dead_code)]
+    pub fn advance_epoch_with_cron<F>(&mut self, n: ChainEpoch, mut expect: F)
+    where
+        F: FnMut(&mut MockRuntime, ChainEpoch),
+    {
+        let (caller, caller_type) = (self.caller, self.caller_type.clone());
+        for _ in 0..n {
+            self.epoch += 1;
+            for (code, method) in self.cron_calls.clone() {
+                self.set_caller(CRON_ACTOR_CODE_ID.clone(), *CRON_ACTOR_ADDR);
+                self.expect_validate_caller_addr(vec![*CRON_ACTOR_ADDR]);
+                expect(self, self.epoch);
+                if let Err(e) = self.call(&code, method, &Serialized::default()) {
+                    panic!("cron call {} failed at epoch {}: {}", method, self.epoch, e);
+                }
+                self.verify();
+            }
+        }
+        self.caller = caller;
+        self.caller_type = caller_type;
+    }
 }
 
 impl MessageInfo for MockRuntime {
@@ -402,6 +540,11 @@ impl Runtime<MemoryDB> for MockRuntime {
         self.epoch
     }
 
+    fn network_version(&self) -> NetworkVersion {
+        self.require_in_call();
+        self.network_version
+    }
+
     fn validate_immediate_caller_accept_any(&mut self) -> Result<(), ActorError> {
         self.require_in_call();
         assert!(
@@ -500,13 +643,32 @@ impl Runtime<MemoryDB> for MockRuntime {
         Ok(self.actor_code_cids.get(&addr).cloned())
     }
 
-    fn get_randomness(
+    fn get_randomness_from_tickets(
+        &self,
+        personalization: DomainSeparationTag,
+        rand_epoch: ChainEpoch,
+        entropy: &[u8],
+    ) -> Result<Randomness, ActorError> {
+        self.expected_randomness(
+            &self.expect_get_randomness_tickets,
+            personalization,
+            rand_epoch,
+            entropy,
+        )
+    }
+
+    fn get_randomness_from_beacon(
         &self,
-        _personalization: DomainSeparationTag,
-        _rand_epoch: ChainEpoch,
-        _entropy: &[u8],
+        personalization: DomainSeparationTag,
+        rand_epoch: ChainEpoch,
+        entropy: &[u8],
     ) -> Result<Randomness, ActorError> {
-        unimplemented!()
+        self.expected_randomness(
+            &self.expect_get_randomness_beacon,
+            personalization,
+            rand_epoch,
+            entropy,
+        )
     }
 
     fn create<C: Cbor>(&mut self, obj: &C) -> Result<(), ActorError> {
@@ -525,17 +687,19 @@ impl Runtime<MemoryDB> for MockRuntime {
             .unwrap())
     }
 
-    fn transaction<C: Cbor, R, F>(&mut self, f: F) -> Result<R, ActorError>
+    fn transaction<C: StateObject, R, F>(&mut self, f: F) -> Result<R, ActorError>
     where
         F: FnOnce(&mut C, &mut Self) -> R,
     {
         if self.in_transaction {
             return Err(self.abort(ExitCode::SysErrorIllegalActor, "nested transaction"));
         }
-        let mut read_only = self.state()?;
+        let loaded: C = self.state()?;
+        let mut read_only = loaded.clone();
         self.in_transaction = true;
         let ret = f(&mut read_only, self);
-        self.state = Some(self.put(&read_only).unwrap());
+        let head = self.state.clone().unwrap();
+        self.state = Some(read_only.flush(&self.store, &loaded, &head).unwrap());
         self.in_transaction = false;
         Ok(ret)
     }
@@ -544,6 +708,14 @@ impl Runtime<MemoryDB> for MockRuntime {
         &self.store
     }
 
+    fn store_get<T: Cbor>(&self, cid: &Cid) -> Result<Option<T>, ActorError> {
+        Ok(self.store.get(cid).unwrap())
+    }
+
+    fn store_put<T: Cbor>(&self, obj: &T) -> Result<Cid, ActorError> {
+        Ok(self.store.put(obj, Blake2b256).unwrap())
+    }
+
     fn send(
         &mut self,
         to: Address,
@@ -577,6 +749,10 @@ impl Runtime<MemoryDB> for MockRuntime {
         }
         self.balance -= value;
 
+        if let Some(on_send) = self.on_send.take() {
+            on_send(self);
+        }
+
         match expected_msg.exit_code {
             ExitCode::Ok => return Ok(expected_msg.send_return),
             x => {
@@ -632,36 +808,25 @@ impl Runtime<MemoryDB> for MockRuntime {
 }
 
 impl Syscalls for MockRuntime {
+    fn log(&self, level: LogLevel, msg: &str) {
+        println!("[{}] {}: {}", self.receiver, level, msg);
+    }
     fn verify_signature(
         &self,
         signature: &Signature,
         signer: &Address,
         plaintext: &[u8],
-    ) -> Result<(), Box<dyn StdError>> {
-        if self.expect_verify_sigs.borrow().len() == 0 {
-            return Err(Box::new(
-                actor_error!(ErrIllegalState; "Unexpected signature verification"),
-            ));
-        }
+    ) -> Result<(), SignatureError> {
         let exp = self
             .expect_verify_sigs
             .borrow_mut()
             .pop()
-            .ok_or(actor_error!(ErrIllegalState; "Unexpected signature verification"))?;
-        if exp.sig == *signature && exp.signer == *signer && &exp.plaintext[..] == plaintext {
-            if exp.result == ExitCode::Ok {
-                return Ok(());
-            } else {
-                return Err(Box::new(ActorError::new(
-                    exp.result,
-                    "Expected failure".to_string(),
-                )));
-            }
-        } else {
-            return Err(Box::new(
-                actor_error!(ErrIllegalState; "Signatures did not match"),
-            ));
-        }
+            .expect("Unexpected signature verification");
+        assert!(
+            exp.sig == *signature && exp.signer == *signer && &exp.plaintext[..] == plaintext,
+            "Signatures did not match"
+        );
+        exp.result
     }
 
     fn hash_blake2b(&self, data: &[u8]) -> Result<[u8; 32], Box<dyn StdError>> {
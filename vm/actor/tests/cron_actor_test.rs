@@ -4,11 +4,12 @@
 mod common;
 use actor::{
     cron::{ConstructorParams, Entry, State},
-    CRON_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID,
+    market, power, CRON_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID,
 };
 use address::Address;
 use common::*;
-use vm::{ExitCode, Serialized};
+use vm::{ExitCode, MethodNum, Serialized};
 
 fn construct_runtime() -> MockRuntime {
     MockRuntime {
@@ -33,20 +34,20 @@ fn construct_with_entries() {
     let mut rt = construct_runtime();
 
     let entry1 = Entry {
-        receiver: Address::new_id(1001),
-        method_num: 1001,
+        receiver: Address::new_id(1),
+        method_num: MethodNum::new(1001),
     };
     let entry2 = Entry {
-        receiver: Address::new_id(1002),
-        method_num: 1002,
+        receiver: Address::new_id(2),
+        method_num: MethodNum::new(1002),
     };
     let entry3 = Entry {
-        receiver: Address::new_id(1003),
-        method_num: 1003,
+        receiver: Address::new_id(4),
+        method_num: MethodNum::new(1003),
     };
     let entry4 = Entry {
-        receiver: Address::new_id(1004),
-        method_num: 1004,
+        receiver: Address::new_id(5),
+        method_num: MethodNum::new(1004),
     };
 
     let params = ConstructorParams {
@@ -72,20 +73,20 @@ fn epoch_tick_with_entries() {
     let mut rt = construct_runtime();
 
     let entry1 = Entry {
-        receiver: Address::new_id(1001),
-        method_num: 1001,
+        receiver: Address::new_id(1),
+        method_num: MethodNum::new(1001),
     };
     let entry2 = Entry {
-        receiver: Address::new_id(1002),
-        method_num: 1002,
+        receiver: Address::new_id(2),
+        method_num: MethodNum::new(1002),
     };
     let entry3 = Entry {
-        receiver: Address::new_id(1003),
-        method_num: 1003,
+        receiver: Address::new_id(4),
+        method_num: MethodNum::new(1003),
     };
     let entry4 = Entry {
-        receiver: Address::new_id(1004),
-        method_num: 1004,
+        receiver: Address::new_id(5),
+        method_num: MethodNum::new(1004),
     };
 
     let params = ConstructorParams {
@@ -136,12 +137,60 @@ fn epoch_tick_with_entries() {
     epoch_tick_and_verify(&mut rt);
 }
 
+#[test]
+fn construct_rejects_non_singleton_receivers() {
+    let mut rt = construct_runtime();
+
+    for receiver in &[Address::new_id(100), Address::new_bls(&[3; 48]).unwrap()] {
+        let params = ConstructorParams {
+            entries: vec![Entry {
+                receiver: *receiver,
+                method_num: MethodNum::new(2),
+            }],
+        };
+        rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+        let err = rt
+            .call(
+                &*CRON_ACTOR_CODE_ID,
+                MethodNum::new(1),
+                &Serialized::serialize(&params).unwrap(),
+            )
+            .unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+        rt.verify();
+    }
+}
+
+#[test]
+fn construct_with_builtin_entries() {
+    let mut rt = construct_runtime();
+
+    let params = ConstructorParams::builtin();
+    assert_eq!(
+        params.entries,
+        vec![
+            Entry {
+                receiver: *STORAGE_POWER_ACTOR_ADDR,
+                method_num: power::Method::OnEpochTickEnd.into(),
+            },
+            Entry {
+                receiver: *STORAGE_MARKET_ACTOR_ADDR,
+                method_num: market::Method::CronTick.into(),
+            },
+        ]
+    );
+    construct_and_verify(&mut rt, &params);
+
+    let state: State = rt.get_state().unwrap();
+    assert_eq!(state.entries, params.entries);
+}
+
 fn construct_and_verify(rt: &mut MockRuntime, params: &ConstructorParams) {
     rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
     let ret = rt
         .call(
             &*CRON_ACTOR_CODE_ID,
-            1,
+            MethodNum::new(1),
             &Serialized::serialize(&params).unwrap(),
         )
         .unwrap();
@@ -152,7 +201,11 @@ fn construct_and_verify(rt: &mut MockRuntime, params: &ConstructorParams) {
 fn epoch_tick_and_verify(rt: &mut MockRuntime) {
     rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
     let ret = rt
-        .call(&*CRON_ACTOR_CODE_ID, 2, &Serialized::default())
+        .call(
+            &*CRON_ACTOR_CODE_ID,
+            MethodNum::new(2),
+            &Serialized::default(),
+        )
         .unwrap();
     assert_eq!(Serialized::default(), ret);
     rt.verify();
@@ -0,0 +1,94 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::{
+    market::{
+        deal_client::{new_proposal, publish_storage_deals_message, sign_proposal, DealTerms},
+        Method, PublishStorageDealsParams,
+    },
+    STORAGE_MARKET_ACTOR_ADDR,
+};
+use address::Address;
+use commcid::piece_commitment_v1_to_cid;
+use crypto::{Signature, Signer};
+use encoding::Cbor;
+use fil_types::{PaddedPieceSize, PieceInfo};
+use message::Message;
+use num_traits::Zero;
+use std::error::Error;
+use vm::{MethodNum, TokenAmount};
+
+/// Signs data by using it as the signature bytes, so the signed data can be checked.
+struct EchoSigner;
+impl Signer for EchoSigner {
+    fn sign_bytes(&self, data: Vec<u8>, _: &Address) -> Result<Signature, Box<dyn Error>> {
+        Ok(Signature::new_secp256k1(data))
+    }
+}
+
+fn piece() -> PieceInfo {
+    PieceInfo {
+        size: PaddedPieceSize(2048),
+        cid: piece_commitment_v1_to_cid(&[1; 32]),
+    }
+}
+
+fn terms() -> DealTerms {
+    DealTerms {
+        client: Address::new_id(101),
+        provider: Address::new_id(102),
+        start_epoch: 10,
+        end_epoch: 200,
+        verified_deal: false,
+    }
+}
+
+#[test]
+fn proposal_defaults() {
+    let proposal = new_proposal(&piece(), terms()).unwrap();
+    assert_eq!(proposal.piece_size, PaddedPieceSize(2048));
+    assert_eq!(proposal.client, Address::new_id(101));
+    assert_eq!(proposal.provider, Address::new_id(102));
+    assert_eq!(proposal.duration(), 190);
+    assert_eq!(proposal.storage_price_per_epoch, TokenAmount::zero());
+    assert_eq!(proposal.provider_collateral, TokenAmount::zero());
+    assert_eq!(proposal.client_collateral, TokenAmount::zero());
+}
+
+#[test]
+fn invalid_proposals() {
+    let mut bad_size = piece();
+    bad_size.size = PaddedPieceSize(2000);
+    assert!(new_proposal(&bad_size, terms()).is_err());
+
+    let mut ends_before_start = terms();
+    ends_before_start.end_epoch = ends_before_start.start_epoch;
+    assert!(new_proposal(&piece(), ends_before_start).is_err());
+
+    let mut too_long = terms();
+    too_long.end_epoch = i64::MAX;
+    assert!(new_proposal(&piece(), too_long).is_err());
+}
+
+#[test]
+fn sign_and_publish() {
+    let proposal = new_proposal(&piece(), terms()).unwrap();
+    let signed = sign_proposal(proposal.clone(), &EchoSigner).unwrap();
+    assert_eq!(signed.proposal, proposal);
+    assert_eq!(
+        signed.client_signature.bytes(),
+        &proposal.marshal_cbor().unwrap()[..]
+    );
+
+    let worker = Address::new_id(103);
+    let msg = publish_storage_deals_message(worker, vec![signed.clone()]).unwrap();
+    assert_eq!(msg.from(), &worker);
+    assert_eq!(msg.to(), &*STORAGE_MARKET_ACTOR_ADDR);
+    assert_eq!(
+        msg.method_num(),
+        MethodNum::from(Method::PublishStorageDeals)
+    );
+
+    let params: PublishStorageDealsParams = msg.params().deserialize().unwrap();
+    assert_eq!(params.deals, vec![signed]);
+}
@@ -4,16 +4,18 @@
 mod common;
 
 use actor::{
+    can_exec,
     init::{ConstructorParams, ExecParams, ExecReturn, Method, State},
-    Multimap, ACCOUNT_ACTOR_CODE_ID, FIRST_NON_SINGLETON_ADDR, INIT_ACTOR_CODE_ID,
-    MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID, POWER_ACTOR_CODE_ID,
-    STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID,
+    Multimap, ACCOUNT_ACTOR_CODE_ID, CRON_ACTOR_CODE_ID, FIRST_NON_SINGLETON_ADDR,
+    INIT_ACTOR_CODE_ID, MARKET_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID,
+    PAYCH_ACTOR_CODE_ID, POWER_ACTOR_CODE_ID, REWARD_ACTOR_CODE_ID, STORAGE_POWER_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID, VERIFREG_ACTOR_CODE_ID,
 };
 use address::Address;
 use cid::Cid;
 use common::*;
 use serde::Serialize;
-use vm::{ActorError, ExitCode, Serialized, TokenAmount, METHOD_CONSTRUCTOR};
+use vm::{ActorError, ActorID, ExitCode, Serialized, TokenAmount, METHOD_CONSTRUCTOR};
 
 fn construct_runtime() -> MockRuntime {
     MockRuntime {
@@ -38,6 +40,78 @@ fn abort_cant_call_exec() {
     assert_eq!(err.exit_code(), ExitCode::ErrForbidden);
 }
 
+#[test]
+fn abort_exec_forbidden_types() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+    let anne = Address::new_id(1001);
+
+    // Only the power actor can exec miners
+    for caller in &[
+        ACCOUNT_ACTOR_CODE_ID.clone(),
+        MULTISIG_ACTOR_CODE_ID.clone(),
+        MINER_ACTOR_CODE_ID.clone(),
+    ] {
+        rt.set_caller(caller.clone(), anne);
+        let err = exec_and_verify(&mut rt, MINER_ACTOR_CODE_ID.clone(), &"")
+            .expect_err("Exec should have failed");
+        assert_eq!(err.exit_code(), ExitCode::ErrForbidden);
+    }
+
+    // Singletons and accounts can't be exec'd by any caller, including the power actor
+    rt.set_caller(
+        POWER_ACTOR_CODE_ID.clone(),
+        STORAGE_POWER_ACTOR_ADDR.clone(),
+    );
+    for code in &[
+        SYSTEM_ACTOR_CODE_ID.clone(),
+        INIT_ACTOR_CODE_ID.clone(),
+        CRON_ACTOR_CODE_ID.clone(),
+        POWER_ACTOR_CODE_ID.clone(),
+        MARKET_ACTOR_CODE_ID.clone(),
+        REWARD_ACTOR_CODE_ID.clone(),
+        VERIFREG_ACTOR_CODE_ID.clone(),
+        ACCOUNT_ACTOR_CODE_ID.clone(),
+    ] {
+        let err = exec_and_verify(&mut rt, code.clone(), &"").expect_err("Exec should have failed");
+        assert_eq!(err.exit_code(), ExitCode::ErrForbidden);
+    }
+}
+
+#[test]
+fn exec_permissions() {
+    assert!(can_exec(&POWER_ACTOR_CODE_ID, &MINER_ACTOR_CODE_ID));
+    assert!(!can_exec(&ACCOUNT_ACTOR_CODE_ID, &MINER_ACTOR_CODE_ID));
+    for caller in &[
+        ACCOUNT_ACTOR_CODE_ID.clone(),
+        MULTISIG_ACTOR_CODE_ID.clone(),
+        POWER_ACTOR_CODE_ID.clone(),
+    ] {
+        assert!(can_exec(caller, &MULTISIG_ACTOR_CODE_ID));
+        assert!(can_exec(caller, &PAYCH_ACTOR_CODE_ID));
+        assert!(!can_exec(caller, &MARKET_ACTOR_CODE_ID));
+        assert!(!can_exec(caller, &ACCOUNT_ACTOR_CODE_ID));
+    }
+}
+
+#[test]
+fn abort_empty_network_name() {
+    let mut rt = construct_runtime();
+    rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR.clone()]);
+    let params = ConstructorParams {
+        network_name: String::new(),
+    };
+    let err = rt
+        .call(
+            &*INIT_ACTOR_CODE_ID,
+            METHOD_CONSTRUCTOR,
+            &Serialized::serialize(&params).unwrap(),
+        )
+        .expect_err("Constructor should have failed");
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+    rt.verify();
+}
+
 #[test]
 fn create_2_payment_channels() {
     let mut rt = construct_runtime();
@@ -244,6 +318,36 @@ fn sending_constructor_failure() {
     );
 }
 
+#[test]
+fn allocate_ids() {
+    let store = db::MemoryDB::default();
+    let empty_map = Multimap::new(&store).root().unwrap();
+    let mut st = State::new(empty_map, "mock".to_owned());
+    let addr = Address::new_actor(b"actor");
+
+    assert_eq!(st.next_id_address(), Address::new_id(100));
+    assert_eq!(
+        st.map_address_to_new_id(&store, &addr).unwrap(),
+        Address::new_id(100)
+    );
+    assert_eq!(st.next_id, ActorID(101));
+    assert_eq!(
+        st.resolve_address(&store, &addr).unwrap(),
+        Some(Address::new_id(100))
+    );
+
+    // IDs in the range reserved for singletons can't be allocated
+    st.next_id = ActorID(5);
+    assert!(st.map_address_to_new_id(&store, &addr).is_err());
+    assert_eq!(st.next_id, ActorID(5));
+
+    // The last ID can't be allocated without wrapping around
+    st.next_id = ActorID(u64::MAX - 1);
+    st.map_address_to_new_id(&store, &addr).unwrap();
+    assert!(st.map_address_to_new_id(&store, &addr).is_err());
+    assert_eq!(st.next_id, ActorID(u64::MAX));
+}
+
 fn construct_and_verify(rt: &mut MockRuntime) {
     rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR.clone()]);
     let params = ConstructorParams {
@@ -288,7 +392,7 @@ where
 
     let ret = rt.call(
         &*INIT_ACTOR_CODE_ID,
-        Method::Exec as u64,
+        Method::Exec.into(),
         &Serialized::serialize(&exec_params).unwrap(),
     );
 
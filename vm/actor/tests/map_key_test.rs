@@ -0,0 +1,52 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::{i64_key, parse_int_key, u64_key, BigEndianKey, IntKey, MapKey, UintKey};
+
+#[test]
+fn int_keys() {
+    for &k in &[0, 1, -1, 63, -64, 64, 101, std::i64::MAX, std::i64::MIN] {
+        assert_eq!(parse_int_key(&i64_key(k)).unwrap(), k);
+        assert_eq!(IntKey::parse(&IntKey(k).key()), Ok(IntKey(k)));
+    }
+
+    // Small negative keys are as short as small positive keys
+    assert_eq!(i64_key(-1).0, vec![0x1]);
+    assert_eq!(i64_key(1).0, vec![0x2]);
+    assert_eq!(i64_key(-64).0.len(), 1);
+
+    // Negative keys don't collide with the unsigned keys of their cast
+    assert_ne!(i64_key(-1), u64_key(-1i64 as u64));
+}
+
+#[test]
+fn uint_keys() {
+    for &k in &[0, 127, 128, 300, std::u64::MAX] {
+        assert_eq!(UintKey(k).key(), u64_key(k));
+        assert_eq!(UintKey::parse(&UintKey(k).key()), Ok(UintKey(k)));
+    }
+    assert!(UintKey::parse(&[0x80]).is_err());
+}
+
+#[test]
+fn big_endian_keys() {
+    let keys = [0, 1, 255, 256, 300, 1 << 32, std::u64::MAX];
+    for &k in &keys {
+        assert_eq!(BigEndianKey(k).key().len(), 8);
+        assert_eq!(
+            BigEndianKey::parse(&BigEndianKey(k).key()),
+            Ok(BigEndianKey(k))
+        );
+    }
+    assert!(BigEndianKey::parse(&[1, 2, 3]).is_err());
+
+    // Keys are ordered as the integers, unlike varint keys
+    let mut encoded: Vec<_> = keys.iter().map(|&k| BigEndianKey(k).key().0).collect();
+    encoded.sort();
+    let decoded: Vec<_> = encoded
+        .iter()
+        .map(|bz| BigEndianKey::parse(bz).unwrap().0)
+        .collect();
+    assert_eq!(decoded, keys);
+    assert!(u64_key(256).0 < u64_key(255).0);
+}
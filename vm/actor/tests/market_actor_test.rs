@@ -4,23 +4,37 @@
 mod common;
 
 use actor::{
-    market::{Method, State, WithdrawBalanceParams},
+    market::{
+        AddBalanceReturn, ClientDealProposal, DealProposal, DealState, DealsForProviderParams,
+        DealsForProviderReturn, Method, OnMinerSectorsTerminateParams, PublishStorageDealsParams,
+        PublishStorageDealsReturn, SectorDeals, SectorWeights, State,
+        VerifyDealsForActivationParams, VerifyDealsForActivationReturn,
+        VerifyDealsOnSectorProveCommitParams, VerifyDealsOnSectorProveCommitReturn,
+        WithdrawBalanceParams,
+    },
     miner::{GetControlAddressesReturn, Method as MinerMethod},
-    Multimap, SetMultimap, ACCOUNT_ACTOR_CODE_ID, CALLER_TYPES_SIGNABLE, INIT_ACTOR_CODE_ID,
-    MARKET_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR,
-    SYSTEM_ACTOR_ADDR,
+    DealID, Multimap, Set, SetMultimap, ACCOUNT_ACTOR_CODE_ID, BURNT_FUNDS_ACTOR_ADDR,
+    CALLER_TYPES_SIGNABLE, INIT_ACTOR_CODE_ID, MARKET_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID,
+    MULTISIG_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 use address::Address;
-use clock::EPOCH_UNDEFINED;
+use clock::{ChainEpoch, EPOCH_UNDEFINED};
+use commcid::piece_commitment_v1_to_cid;
 use common::*;
+use crypto::Signature;
+use encoding::{to_vec, Cbor};
+use fil_types::{NetworkVersion, PaddedPieceSize};
 use ipld_amt::Amt;
+use num_bigint::BigUint;
+use num_traits::Zero;
 use std::collections::HashMap;
-use vm::{ExitCode, Serialized, TokenAmount, METHOD_CONSTRUCTOR, METHOD_SEND};
+use vm::{ActorError, ExitCode, Serialized, TokenAmount, METHOD_CONSTRUCTOR, METHOD_SEND};
 
 const OWNER_ID: u64 = 101;
 const PROVIDER_ID: u64 = 102;
 const WORKER_ID: u64 = 103;
 const CLIENT_ID: u64 = 104;
+const CONTROL_ID: u64 = 105;
 
 fn setup() -> MockRuntime {
     let mut actor_code_cids = HashMap::default();
@@ -78,6 +92,8 @@ fn simple_construction() {
     assert_eq!(empty_map, state_data.locked_table);
     assert_eq!(empty_set, state_data.deal_ops_by_epoch);
     assert_eq!(state_data.last_cron, EPOCH_UNDEFINED);
+    assert_eq!(state_data.deals_by_provider, None);
+    assert_eq!(state_data.pending_proposals, None);
 }
 
 #[test]
@@ -104,7 +120,7 @@ fn add_provider_escrow_funds() {
             assert!(rt
                 .call(
                     &MARKET_ACTOR_CODE_ID.clone(),
-                    Method::AddBalance as u64,
+                    Method::AddBalance.into(),
                     &Serialized::serialize(provider_addr.clone()).unwrap(),
                 )
                 .is_ok());
@@ -121,6 +137,43 @@ fn add_provider_escrow_funds() {
     }
 }
 
+#[test]
+fn add_provider_escrow_funds_from_control_address() {
+    let mut rt = setup();
+
+    let owner_addr = Address::new_id(OWNER_ID);
+    let worker_addr = Address::new_id(WORKER_ID);
+    let provider_addr = Address::new_id(PROVIDER_ID);
+    let control_addr = Address::new_id(CONTROL_ID);
+
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), control_addr);
+    rt.set_value(TokenAmount::from(10u8));
+    expect_provider_control_addresses(
+        &mut rt,
+        provider_addr,
+        owner_addr,
+        worker_addr,
+        vec![control_addr],
+    );
+
+    assert!(rt
+        .call(
+            &MARKET_ACTOR_CODE_ID.clone(),
+            Method::AddBalance.into(),
+            &Serialized::serialize(provider_addr.clone()).unwrap(),
+        )
+        .is_ok());
+    rt.verify();
+
+    let state_data: State = rt.get_state().unwrap();
+    assert_eq!(
+        state_data
+            .get_escrow_balance(&rt.store, &provider_addr)
+            .unwrap(),
+        TokenAmount::from(10u8)
+    );
+}
+
 #[test]
 fn account_actor_check() {
     let mut rt = setup();
@@ -139,7 +192,7 @@ fn account_actor_check() {
         ExitCode::ErrForbidden,
         rt.call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::AddBalance as u64,
+            Method::AddBalance.into(),
             &Serialized::serialize(provider_addr).unwrap(),
         )
         .unwrap_err()
@@ -170,7 +223,7 @@ fn add_non_provider_funds() {
             assert!(rt
                 .call(
                     &MARKET_ACTOR_CODE_ID.clone(),
-                    Method::AddBalance as u64,
+                    Method::AddBalance.into(),
                     &Serialized::serialize(caller_addr.clone()).unwrap(),
                 )
                 .is_ok());
@@ -188,6 +241,42 @@ fn add_non_provider_funds() {
     }
 }
 
+#[test]
+fn add_balance_returns_escrow_balance() {
+    let client_addr = Address::new_id(CLIENT_ID);
+    let mut rt = setup();
+
+    // No return value before network version 1
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), client_addr);
+    rt.set_value(TokenAmount::from(10u8));
+    rt.expect_validate_caller_type(CALLER_TYPES_SIGNABLE.to_vec());
+    let ret = rt
+        .call(
+            &MARKET_ACTOR_CODE_ID.clone(),
+            Method::AddBalance.into(),
+            &Serialized::serialize(client_addr).unwrap(),
+        )
+        .unwrap();
+    rt.verify();
+    assert!(ret.bytes().is_empty());
+
+    rt.network_version = NetworkVersion::V1;
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), client_addr);
+    rt.set_value(TokenAmount::from(5u8));
+    rt.expect_validate_caller_type(CALLER_TYPES_SIGNABLE.to_vec());
+    let ret: AddBalanceReturn = rt
+        .call(
+            &MARKET_ACTOR_CODE_ID.clone(),
+            Method::AddBalance.into(),
+            &Serialized::serialize(client_addr).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(ret.balance, TokenAmount::from(15u8));
+}
+
 #[test]
 fn withdraw_provider_to_owner() {
     let mut rt = setup();
@@ -235,7 +324,7 @@ fn withdraw_provider_to_owner() {
     assert!(rt
         .call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::WithdrawBalance as u64,
+            Method::WithdrawBalance.into(),
             &Serialized::serialize(params).unwrap(),
         )
         .is_ok());
@@ -295,7 +384,7 @@ fn withdraw_non_provider() {
     assert!(rt
         .call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::WithdrawBalance as u64,
+            Method::WithdrawBalance.into(),
             &Serialized::serialize(params).unwrap(),
         )
         .is_ok());
@@ -346,7 +435,7 @@ fn client_withdraw_more_than_available() {
     assert!(rt
         .call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::WithdrawBalance as u64,
+            Method::WithdrawBalance.into(),
             &Serialized::serialize(params).unwrap(),
         )
         .is_ok());
@@ -410,7 +499,7 @@ fn worker_withdraw_more_than_available() {
     assert!(rt
         .call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::WithdrawBalance as u64,
+            Method::WithdrawBalance.into(),
             &Serialized::serialize(params).unwrap(),
         )
         .is_ok());
@@ -427,22 +516,586 @@ fn worker_withdraw_more_than_available() {
     );
 }
 
+#[test]
+fn verify_deals_for_committed_capacity_sectors() {
+    let mut rt = setup();
+    let provider_addr = Address::new_id(PROVIDER_ID);
+
+    rt.set_caller(MINER_ACTOR_CODE_ID.clone(), provider_addr);
+    rt.expect_validate_caller_type(vec![MINER_ACTOR_CODE_ID.clone()]);
+    let params = VerifyDealsForActivationParams {
+        sectors: vec![
+            SectorDeals {
+                sector_expiry: 100,
+                deal_ids: Vec::new(),
+            },
+            SectorDeals {
+                sector_expiry: 200,
+                deal_ids: Vec::new(),
+            },
+        ],
+    };
+    let ret: VerifyDealsForActivationReturn = rt
+        .call(
+            &*MARKET_ACTOR_CODE_ID,
+            Method::VerifyDealsForActivation.into(),
+            &Serialized::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    // Sectors without deals have no weight
+    assert_eq!(
+        ret.sectors,
+        vec![SectorWeights::default(), SectorWeights::default()]
+    );
+}
+
+#[test]
+fn verify_deals_rejects_non_miner_caller() {
+    let mut rt = setup();
+    let client_addr = Address::new_id(CLIENT_ID);
+
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), client_addr);
+    rt.expect_validate_caller_type(vec![MINER_ACTOR_CODE_ID.clone()]);
+    let params = VerifyDealsForActivationParams {
+        sectors: vec![SectorDeals {
+            sector_expiry: 100,
+            deal_ids: Vec::new(),
+        }],
+    };
+    let err = rt
+        .call(
+            &*MARKET_ACTOR_CODE_ID,
+            Method::VerifyDealsForActivation.into(),
+            &Serialized::serialize(params).unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrForbidden);
+    rt.verify();
+}
+
+#[test]
+fn verify_deals_for_published_deals() {
+    let mut rt = setup();
+    fund_deal_parties(&mut rt);
+    let ids = vec![
+        publish_deal(&mut rt, 10, 200),
+        publish_deal(&mut rt, 11, 300),
+    ];
+
+    let ret: VerifyDealsForActivationReturn =
+        verify_deals_for_activation(&mut rt, 400, vec![vec![ids[0]], vec![ids[1]]])
+            .unwrap()
+            .deserialize()
+            .unwrap();
+    let weights = |duration: u64| SectorWeights {
+        deal_space: 2048,
+        deal_weight: BigUint::from(duration * 2048),
+        verified_deal_weight: BigUint::zero(),
+    };
+    assert_eq!(ret.sectors, vec![weights(190), weights(289)]);
+
+    let st: State = rt.get_state().unwrap();
+    let states = Amt::<DealState, _>::load(&st.states, &rt.store).unwrap();
+    for id in &ids {
+        let state = states.get(*id).unwrap().unwrap();
+        assert_eq!(state.sector_start_epoch, rt.epoch);
+        assert_eq!(state.slash_epoch, EPOCH_UNDEFINED);
+    }
+}
+
+#[test]
+fn verify_deals_rejects_duplicate_deals() {
+    let mut rt = setup();
+    fund_deal_parties(&mut rt);
+    let id = publish_deal(&mut rt, 10, 200);
+
+    // A deal can't be included in two sectors
+    let err = verify_deals_for_activation(&mut rt, 400, vec![vec![id], vec![id]]).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+    let st: State = rt.get_state().unwrap();
+    let states = Amt::<DealState, _>::load(&st.states, &rt.store).unwrap();
+    assert_eq!(states.get(id).unwrap(), None);
+
+    // Nor be activated again once in a sector
+    activate_deals(&mut rt, 400, vec![id]);
+    let err = verify_deals_for_activation(&mut rt, 400, vec![vec![id]]).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+}
+
+#[test]
+fn verify_deals_on_sector_prove_commit_keeps_weights() {
+    let mut rt = setup();
+    fund_deal_parties(&mut rt);
+    let id = publish_deal(&mut rt, 10, 200);
+
+    rt.set_caller(MINER_ACTOR_CODE_ID.clone(), Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![MINER_ACTOR_CODE_ID.clone()]);
+    let params = VerifyDealsOnSectorProveCommitParams {
+        deal_ids: vec![id],
+        sector_expiry: 400,
+    };
+    let ret: VerifyDealsOnSectorProveCommitReturn = rt
+        .call(
+            &*MARKET_ACTOR_CODE_ID,
+            Method::VerifyDealsOnSectorProveCommit.into(),
+            &Serialized::serialize(params).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    // Each deal's space-time is counted twice by the single-sector method
+    assert_eq!(ret.deal_weight, BigUint::from(2 * 190 * 2048u64));
+    assert_eq!(ret.verified_deal_weight, BigUint::zero());
+    let st: State = rt.get_state().unwrap();
+    let states = Amt::<DealState, _>::load(&st.states, &rt.store).unwrap();
+    assert_eq!(
+        states.get(id).unwrap().unwrap().sector_start_epoch,
+        rt.epoch
+    );
+}
+
+#[test]
+fn publish_duplicate_deal() {
+    let mut rt = setup();
+    rt.network_version = NetworkVersion::V5;
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+
+    // Proposals are tracked from the first cron tick of the network version
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    fund_deal_parties(&mut rt);
+    publish_deal(&mut rt, 10, 200);
+
+    let err = try_publish_deal(&mut rt, 10, 200).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.next_id, 1);
+}
+
+#[test]
+fn duplicate_deals_rejected_from_network_version_5() {
+    let mut rt = setup();
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    fund_deal_parties(&mut rt);
+
+    // Duplicate proposals are accepted before the upgrade, and no set is kept in state
+    publish_deal(&mut rt, 10, 200);
+    publish_deal(&mut rt, 10, 200);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.pending_proposals, None);
+    assert_eq!(st.next_id, 2);
+
+    // The first cron tick after the upgrade tracks the proposals not yet processed
+    rt.network_version = NetworkVersion::V5;
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    let err = try_publish_deal(&mut rt, 10, 200).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.deals_by_provider.is_some());
+    let decoded = State::unmarshal_cbor(&st.marshal_cbor().unwrap()).unwrap();
+    assert_eq!(decoded.deals_by_provider, st.deals_by_provider);
+    assert_eq!(decoded.pending_proposals, st.pending_proposals);
+}
+
+#[test]
+fn cron_deletes_slashed_deal() {
+    let mut rt = setup();
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+    fund_deal_parties(&mut rt);
+    let id = publish_deal(&mut rt, 10, 200);
+    activate_deals(&mut rt, 400, vec![id]);
+
+    rt.advance_epoch(20);
+    terminate_deals(&mut rt, vec![id]);
+
+    // The provider collateral is burnt and the deal removed when cron processes the deal
+    rt.advance_epoch_with_cron(1, |rt, _| {
+        rt.expect_send(
+            *BURNT_FUNDS_ACTOR_ADDR,
+            METHOD_SEND,
+            Serialized::default(),
+            TokenAmount::from(10u8),
+            Serialized::default(),
+            ExitCode::Ok,
+        );
+    });
+    let st: State = rt.get_state().unwrap();
+    let proposals = Amt::<DealProposal, _>::load(&st.proposals, &rt.store).unwrap();
+    assert_eq!(proposals.get(id).unwrap(), None);
+}
+
+#[test]
+fn cron_tick_tracks_epochs() {
+    let mut rt = setup();
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+
+    rt.advance_epoch_with_cron(3, expect_burn_nothing);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.last_cron, 3);
+
+    // Epochs skipped without cron are processed by the next tick
+    rt.advance_epoch(5);
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.last_cron, 9);
+}
+
+#[test]
+fn deals_for_provider_through_publish_and_termination() {
+    let mut rt = setup();
+    rt.network_version = NetworkVersion::V3;
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+
+    // Deals are indexed from the first cron tick of the network version
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    assert!(deals_for_provider(&mut rt).is_empty());
+
+    fund_deal_parties(&mut rt);
+    let ids = vec![
+        publish_deal(&mut rt, 10, 200),
+        publish_deal(&mut rt, 11, 200),
+    ];
+    assert_eq!(deals_for_provider(&mut rt), ids);
+
+    activate_deals(&mut rt, 300, ids.clone());
+    terminate_deals(&mut rt, vec![ids[0]]);
+    assert_eq!(deals_for_provider(&mut rt), vec![ids[1]]);
+
+    // The set of the provider is deleted with its last deal
+    terminate_deals(&mut rt, vec![ids[1]]);
+    assert!(deals_for_provider(&mut rt).is_empty());
+    let st: State = rt.get_state().unwrap();
+    let empty_set = SetMultimap::new(&rt.store).root().unwrap();
+    assert_eq!(st.deals_by_provider, Some(empty_set));
+}
+
+#[test]
+fn deals_indexed_by_provider_from_network_version_3() {
+    let mut rt = setup();
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+
+    fund_deal_parties(&mut rt);
+    let ids = vec![
+        publish_deal(&mut rt, 10, 200),
+        publish_deal(&mut rt, 11, 200),
+    ];
+    activate_deals(&mut rt, 300, ids.clone());
+    terminate_deals(&mut rt, vec![ids[0]]);
+
+    // Deals are not indexed before the upgrade, and the state keeps the layout of earlier
+    // network versions
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.deals_by_provider, None);
+    let fields = (
+        &st.proposals,
+        &st.states,
+        &st.escrow_table,
+        &st.locked_table,
+        &st.next_id,
+        &st.deal_ops_by_epoch,
+        &st.last_cron,
+    );
+    assert_eq!(st.marshal_cbor().unwrap(), to_vec(&fields).unwrap());
+    let decoded = State::unmarshal_cbor(&to_vec(&fields).unwrap()).unwrap();
+    assert_eq!(decoded.deals_by_provider, None);
+    assert_eq!(decoded.pending_proposals, None);
+
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), Address::new_id(CLIENT_ID));
+    let err = rt
+        .call(
+            &*MARKET_ACTOR_CODE_ID,
+            Method::DealsForProvider.into(),
+            &Serialized::serialize(DealsForProviderParams {
+                provider: Address::new_id(PROVIDER_ID),
+            })
+            .unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::SysErrInvalidMethod);
+
+    // The first cron tick after the upgrade indexes the deals which are not terminated
+    rt.network_version = NetworkVersion::V3;
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    assert_eq!(deals_for_provider(&mut rt), vec![ids[1]]);
+
+    let st: State = rt.get_state().unwrap();
+    let decoded = State::unmarshal_cbor(&st.marshal_cbor().unwrap()).unwrap();
+    assert_eq!(decoded.deals_by_provider, st.deals_by_provider);
+}
+
+#[test]
+fn deals_published_before_network_version_5_activate() {
+    let mut rt = setup();
+    rt.register_cron_call(MARKET_ACTOR_CODE_ID.clone(), Method::CronTick.into());
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    fund_deal_parties(&mut rt);
+    let ids = vec![
+        publish_deal(&mut rt, 10, 200),
+        publish_deal(&mut rt, 11, 200),
+    ];
+
+    // Deals are activated without a pending set before the upgrade
+    activate_deals(&mut rt, 300, vec![ids[0]]);
+
+    // The first cron tick after the upgrade tracks the proposals of both deals, as neither has
+    // been processed by cron yet
+    rt.network_version = NetworkVersion::V5;
+    rt.advance_epoch_with_cron(1, expect_burn_nothing);
+    {
+        let st: State = rt.get_state().unwrap();
+        let pending = Set::from_root(&rt.store, st.pending_proposals.as_ref().unwrap()).unwrap();
+        assert_eq!(pending.collect_keys().unwrap().len(), 2);
+    }
+
+    // So the deal published before the upgrade can still be activated after it
+    activate_deals(&mut rt, 300, vec![ids[1]]);
+}
+
+#[test]
+fn migrate_pending_proposals() {
+    let store = db::MemoryDB::default();
+    let empty_array = Amt::<u64, _>::new(&store).flush().unwrap();
+    let empty_map = Multimap::new(&store).root().unwrap();
+    let empty_set = SetMultimap::new(&store).root().unwrap();
+    let mut st = State::new(empty_array, empty_map, empty_set);
+
+    let proposals: Vec<DealProposal> = (0..3)
+        .map(|i| DealProposal {
+            piece_cid: piece_commitment_v1_to_cid(&[1; 32]),
+            piece_size: PaddedPieceSize(128),
+            verified_deal: false,
+            client: Address::new_id(CLIENT_ID),
+            provider: Address::new_id(PROVIDER_ID),
+            start_epoch: 10 + i,
+            end_epoch: 100,
+            storage_price_per_epoch: TokenAmount::from(1u8),
+            provider_collateral: TokenAmount::from(1u8),
+            client_collateral: TokenAmount::from(1u8),
+        })
+        .collect();
+
+    let mut prop_amt = Amt::load(&st.proposals, &store).unwrap();
+    prop_amt.batch_set(&proposals).unwrap();
+    st.proposals = prop_amt.flush().unwrap();
+
+    // Deal 1 is activated but not yet processed by cron, deal 2 has been processed
+    let mut states = Amt::load(&st.states, &store).unwrap();
+    for (id, last_updated_epoch) in &[(1, EPOCH_UNDEFINED), (2, 15)] {
+        states
+            .set(
+                *id,
+                DealState {
+                    sector_start_epoch: 5,
+                    last_updated_epoch: *last_updated_epoch,
+                    slash_epoch: EPOCH_UNDEFINED,
+                },
+            )
+            .unwrap();
+    }
+    st.states = states.flush().unwrap();
+
+    st.migrate_pending_proposals(&store).unwrap();
+
+    let pending = Set::from_root(&store, st.pending_proposals.as_ref().unwrap()).unwrap();
+    let is_pending = |p: &DealProposal| pending.has(&p.cid().unwrap().to_bytes()).unwrap();
+    assert!(is_pending(&proposals[0]));
+    assert!(is_pending(&proposals[1]));
+    assert!(!is_pending(&proposals[2]));
+    assert_eq!(pending.collect_keys().unwrap().len(), 2);
+}
+
+/// Adds escrow funds for the provider and the client of the deals published by `publish_deal`.
+fn fund_deal_parties(rt: &mut MockRuntime) {
+    add_provider_funds(
+        rt,
+        Address::new_id(PROVIDER_ID),
+        Address::new_id(OWNER_ID),
+        Address::new_id(WORKER_ID),
+        TokenAmount::from(1000u16),
+    );
+    add_participant_funds(rt, Address::new_id(CLIENT_ID), TokenAmount::from(1000u16));
+}
+
+/// Publishes a deal of the client with the provider, sent by the worker of the provider.
+fn publish_deal(rt: &mut MockRuntime, start_epoch: ChainEpoch, end_epoch: ChainEpoch) -> DealID {
+    let ret: PublishStorageDealsReturn = try_publish_deal(rt, start_epoch, end_epoch)
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    ret.ids[0]
+}
+
+/// Calls PublishStorageDeals with the deal built by `publish_deal`, returning the result.
+fn try_publish_deal(
+    rt: &mut MockRuntime,
+    start_epoch: ChainEpoch,
+    end_epoch: ChainEpoch,
+) -> Result<Serialized, ActorError> {
+    let (provider, client) = (Address::new_id(PROVIDER_ID), Address::new_id(CLIENT_ID));
+    let proposal = DealProposal {
+        piece_cid: piece_commitment_v1_to_cid(&[1; 32]),
+        piece_size: PaddedPieceSize(2048),
+        verified_deal: false,
+        client,
+        provider,
+        start_epoch,
+        end_epoch,
+        storage_price_per_epoch: TokenAmount::from(1u8),
+        provider_collateral: TokenAmount::from(10u8),
+        client_collateral: TokenAmount::from(10u8),
+    };
+    let client_signature = Signature::new_bls(b"client signature".to_vec());
+
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), Address::new_id(WORKER_ID));
+    rt.expect_validate_caller_type(CALLER_TYPES_SIGNABLE.to_vec());
+    rt.expect_send(
+        provider,
+        MinerMethod::ControlAddresses.into(),
+        Serialized::default(),
+        TokenAmount::from(0u8),
+        Serialized::serialize(GetControlAddressesReturn {
+            owner: Address::new_id(OWNER_ID),
+            worker: Address::new_id(WORKER_ID),
+            control_addresses: Vec::new(),
+        })
+        .unwrap(),
+        ExitCode::Ok,
+    );
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: client_signature.clone(),
+        signer: client,
+        plaintext: proposal.marshal_cbor().unwrap(),
+        result: Ok(()),
+    });
+
+    let params = PublishStorageDealsParams {
+        deals: vec![ClientDealProposal {
+            proposal,
+            client_signature,
+        }],
+    };
+    let ret = rt.call(
+        &*MARKET_ACTOR_CODE_ID,
+        Method::PublishStorageDeals.into(),
+        &Serialized::serialize(params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+/// Calls VerifyDealsForActivation from the provider for sectors with the given deals.
+fn verify_deals_for_activation(
+    rt: &mut MockRuntime,
+    sector_expiry: ChainEpoch,
+    sectors: Vec<Vec<DealID>>,
+) -> Result<Serialized, ActorError> {
+    rt.set_caller(MINER_ACTOR_CODE_ID.clone(), Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![MINER_ACTOR_CODE_ID.clone()]);
+    let params = VerifyDealsForActivationParams {
+        sectors: sectors
+            .into_iter()
+            .map(|deal_ids| SectorDeals {
+                sector_expiry,
+                deal_ids,
+            })
+            .collect(),
+    };
+    let ret = rt.call(
+        &*MARKET_ACTOR_CODE_ID,
+        Method::VerifyDealsForActivation.into(),
+        &Serialized::serialize(params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+/// Activates the deals in a sector of the provider.
+fn activate_deals(rt: &mut MockRuntime, sector_expiry: ChainEpoch, deal_ids: Vec<DealID>) {
+    verify_deals_for_activation(rt, sector_expiry, vec![deal_ids]).unwrap();
+}
+
+/// Terminates deals of the provider, as when their sector is terminated.
+fn terminate_deals(rt: &mut MockRuntime, deal_ids: Vec<DealID>) {
+    rt.set_caller(MINER_ACTOR_CODE_ID.clone(), Address::new_id(PROVIDER_ID));
+    rt.expect_validate_caller_type(vec![MINER_ACTOR_CODE_ID.clone()]);
+    rt.call(
+        &*MARKET_ACTOR_CODE_ID,
+        Method::OnMinerSectorsTerminate.into(),
+        &Serialized::serialize(OnMinerSectorsTerminateParams { deal_ids }).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+}
+
+/// Returns the deals of the provider listed by the `DealsForProvider` method.
+fn deals_for_provider(rt: &mut MockRuntime) -> Vec<DealID> {
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), Address::new_id(CLIENT_ID));
+    rt.expect_validate_caller_any();
+    let ret: DealsForProviderReturn = rt
+        .call(
+            &*MARKET_ACTOR_CODE_ID,
+            Method::DealsForProvider.into(),
+            &Serialized::serialize(DealsForProviderParams {
+                provider: Address::new_id(PROVIDER_ID),
+            })
+            .unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.ids
+}
+
+/// Expects the cron tick to burn no slashed funds.
+fn expect_burn_nothing(rt: &mut MockRuntime, _epoch: ChainEpoch) {
+    rt.expect_send(
+        *BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        Serialized::default(),
+        TokenAmount::from(0u8),
+        Serialized::default(),
+        ExitCode::Ok,
+    );
+}
+
 fn expect_provider_control_address(
     rt: &mut MockRuntime,
     provider: Address,
     owner: Address,
     worker: Address,
 ) {
-    rt.expect_validate_caller_addr(vec![owner.clone(), worker.clone()]);
+    expect_provider_control_addresses(rt, provider, owner, worker, Vec::new())
+}
+
+fn expect_provider_control_addresses(
+    rt: &mut MockRuntime,
+    provider: Address,
+    owner: Address,
+    worker: Address,
+    control_addresses: Vec<Address>,
+) {
+    let mut callers = vec![owner.clone(), worker.clone()];
+    callers.extend(control_addresses.iter().cloned());
+    rt.expect_validate_caller_addr(callers);
 
     let return_value = GetControlAddressesReturn {
         owner: owner.clone(),
         worker: worker.clone(),
+        control_addresses,
     };
 
     rt.expect_send(
         provider.clone(),
-        MinerMethod::ControlAddresses as u64,
+        MinerMethod::ControlAddresses.into(),
         Serialized::default(),
         TokenAmount::from(0u8),
         Serialized::serialize(return_value).unwrap(),
@@ -465,7 +1118,7 @@ fn add_provider_funds(
     assert!(rt
         .call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::AddBalance as u64,
+            Method::AddBalance.into(),
             &Serialized::serialize(provider.clone()).unwrap(),
         )
         .is_ok());
@@ -488,7 +1141,7 @@ fn add_participant_funds(rt: &mut MockRuntime, addr: Address, amount: TokenAmoun
     assert!(rt
         .call(
             &MARKET_ACTOR_CODE_ID.clone(),
-            Method::AddBalance as u64,
+            Method::AddBalance.into(),
             &Serialized::serialize(addr.clone()).unwrap(),
         )
         .is_ok());
@@ -0,0 +1,138 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+mod common;
+
+use actor::{
+    multisig::{ConstructorParams, Method, State, SwapSignerParams, SIGNER_SWAP_EXPIRY},
+    ACCOUNT_ACTOR_CODE_ID, CALLER_TYPES_SIGNABLE, INIT_ACTOR_ADDR, INIT_ACTOR_CODE_ID,
+    MULTISIG_ACTOR_CODE_ID,
+};
+use address::Address;
+use common::*;
+use fil_types::NetworkVersion;
+use vm::{ActorError, ExitCode, Serialized, METHOD_CONSTRUCTOR};
+
+const MSIG_ADDR: u64 = 100;
+
+fn construct(signers: Vec<Address>, network_version: NetworkVersion) -> MockRuntime {
+    let mut rt = MockRuntime {
+        receiver: Address::new_id(MSIG_ADDR),
+        network_version,
+        ..Default::default()
+    };
+    rt.set_caller(INIT_ACTOR_CODE_ID.clone(), *INIT_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*INIT_ACTOR_ADDR]);
+    let params = ConstructorParams {
+        signers,
+        num_approvals_threshold: 1,
+        unlock_duration: 0,
+        signer_weights: Vec::new(),
+    };
+    rt.call(
+        &*MULTISIG_ACTOR_CODE_ID,
+        METHOD_CONSTRUCTOR,
+        &Serialized::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+    rt
+}
+
+fn swap_signer(rt: &mut MockRuntime, from: Address, to: Address) {
+    let receiver = rt.receiver;
+    rt.set_caller(MULTISIG_ACTOR_CODE_ID.clone(), receiver);
+    rt.expect_validate_caller_addr(vec![receiver]);
+    rt.call(
+        &*MULTISIG_ACTOR_CODE_ID,
+        Method::SwapSigner.into(),
+        &Serialized::serialize(SwapSignerParams { from, to }).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+}
+
+fn confirm_swap_signer(rt: &mut MockRuntime, caller: Address) -> Result<Serialized, ActorError> {
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), caller);
+    rt.expect_validate_caller_type(CALLER_TYPES_SIGNABLE.to_vec());
+    let ret = rt.call(
+        &*MULTISIG_ACTOR_CODE_ID,
+        Method::ConfirmSwapSigner.into(),
+        &Serialized::default(),
+    );
+    rt.verify();
+    ret
+}
+
+#[test]
+fn swap_signer_before_network_version_8() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let mut rt = construct(vec![a, b], NetworkVersion::V7);
+
+    // The swap is applied right away
+    swap_signer(&mut rt, a, c);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![b, c]);
+    assert!(st.pending_swap.is_none());
+
+    // And there is nothing to confirm
+    rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), c);
+    let err = rt
+        .call(
+            &*MULTISIG_ACTOR_CODE_ID,
+            Method::ConfirmSwapSigner.into(),
+            &Serialized::default(),
+        )
+        .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::SysErrInvalidMethod);
+}
+
+#[test]
+fn confirm_swap_signer() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let mut rt = construct(vec![a, b], NetworkVersion::V8);
+
+    swap_signer(&mut rt, a, c);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![a, b]);
+    assert!(st.pending_swap.is_some());
+
+    // Only the new signer can confirm the swap
+    let err = confirm_swap_signer(&mut rt, b).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrForbidden);
+
+    rt.epoch = 5;
+    confirm_swap_signer(&mut rt, c).unwrap();
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![b, c]);
+    assert!(st.pending_swap.is_none());
+
+    // The swap can't be confirmed twice
+    let err = confirm_swap_signer(&mut rt, c).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrNotFound);
+}
+
+#[test]
+fn stale_swap_signer_confirmation() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let mut rt = construct(vec![a, b], NetworkVersion::V8);
+    swap_signer(&mut rt, a, c);
+
+    // The swap lapses when not confirmed in time
+    rt.epoch = SIGNER_SWAP_EXPIRY;
+    let err = confirm_swap_signer(&mut rt, c).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalState);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![a, b]);
+
+    // Nor is it applied if the replaced signer was removed in the meantime
+    rt.epoch = 0;
+    swap_signer(&mut rt, a, c);
+    let mut st: State = rt.get_state().unwrap();
+    st.signers.retain(|s| s != &a);
+    rt.replace_state(&st);
+    let err = confirm_swap_signer(&mut rt, c).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrNotFound);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![b]);
+}
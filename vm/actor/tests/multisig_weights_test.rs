@@ -0,0 +1,228 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+mod common;
+
+use actor::{
+    multisig::{
+        AddSignerParams, ConstructorParams, Method, RemoveSignerParams, State, SwapSignerParams,
+        TxnID,
+    },
+    PendingChange, INIT_ACTOR_ADDR, INIT_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID,
+};
+use address::Address;
+use cid::{multihash::Blake2b256, Cid};
+use common::*;
+use encoding::{from_slice, to_vec};
+use num_bigint::bigint_ser::BigIntSer;
+use vm::{Serialized, TokenAmount, METHOD_CONSTRUCTOR};
+
+const MSIG_ADDR: u64 = 100;
+
+fn state(signers: Vec<Address>, signer_weights: Vec<u64>) -> State {
+    State {
+        signers,
+        num_approvals_threshold: 2,
+        next_tx_id: TxnID(0),
+        initial_balance: TokenAmount::from(0u8),
+        start_epoch: 0,
+        unlock_duration: 0,
+        pending_txs: Cid::new_from_cbor(&[], Blake2b256),
+        pending_swap: None,
+        signer_weights,
+    }
+}
+
+fn construct(signers: Vec<Address>, signer_weights: Vec<u64>, threshold: i64) -> MockRuntime {
+    let mut rt = MockRuntime {
+        receiver: Address::new_id(MSIG_ADDR),
+        ..Default::default()
+    };
+    rt.set_caller(INIT_ACTOR_CODE_ID.clone(), *INIT_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*INIT_ACTOR_ADDR]);
+    let params = ConstructorParams {
+        signers,
+        num_approvals_threshold: threshold,
+        unlock_duration: 0,
+        signer_weights,
+    };
+    rt.call(
+        &*MULTISIG_ACTOR_CODE_ID,
+        METHOD_CONSTRUCTOR,
+        &Serialized::serialize(params).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+    rt
+}
+
+fn remove_signer(rt: &mut MockRuntime, signer: Address, decrease: bool) {
+    let receiver = rt.receiver;
+    rt.set_caller(MULTISIG_ACTOR_CODE_ID.clone(), receiver);
+    rt.expect_validate_caller_addr(vec![receiver]);
+    rt.call(
+        &*MULTISIG_ACTOR_CODE_ID,
+        Method::RemoveSigner as u64,
+        &Serialized::serialize(RemoveSignerParams { signer, decrease }).unwrap(),
+    )
+    .unwrap();
+    rt.verify();
+}
+
+#[test]
+fn unweighted_signers() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let st = state(vec![a, b], Vec::new());
+
+    assert_eq!(st.signer_weight(&a), 1);
+    assert_eq!(st.signer_weight(&c), 0);
+    assert_eq!(st.total_weight(), 2);
+    assert_eq!(st.approval_weight(&[a, b]), 2);
+}
+
+#[cfg(not(feature = "weighted-multisig"))]
+#[test]
+fn unweighted_encoding() {
+    let params = AddSignerParams {
+        signer: Address::new_id(1),
+        increase: true,
+        weight: 5,
+    };
+    // Weight is not part of the encoding without the feature
+    let bz = to_vec(&params).unwrap();
+    assert_eq!(bz, to_vec(&(Address::new_id(1), true)).unwrap());
+    assert_eq!(from_slice::<AddSignerParams>(&bz).unwrap().weight, 0);
+
+    let st = state(vec![Address::new_id(1)], Vec::new());
+    let decoded: State = from_slice(&to_vec(&st).unwrap()).unwrap();
+    assert!(decoded.signer_weights.is_empty());
+}
+
+#[cfg(not(feature = "weighted-multisig"))]
+#[test]
+fn pending_swap_encoding() {
+    let (a, b) = (Address::new_id(1), Address::new_id(2));
+    let mut st = state(vec![a], Vec::new());
+
+    // Without a pending swap the state keeps its original seven field layout
+    let bz = to_vec(&st).unwrap();
+    let balance = TokenAmount::from(0u8);
+    let original = (
+        vec![a],
+        2i64,
+        TxnID(0),
+        BigIntSer(&balance),
+        0i64,
+        0i64,
+        st.pending_txs.clone(),
+    );
+    assert_eq!(bz, to_vec(&original).unwrap());
+    assert!(from_slice::<State>(&bz).unwrap().pending_swap.is_none());
+
+    st.pending_swap = Some(PendingChange::new(SwapSignerParams { from: a, to: b }, 10));
+    let decoded: State = from_slice(&to_vec(&st).unwrap()).unwrap();
+    let swap = decoded.pending_swap.unwrap();
+    assert_eq!((swap.value.from, swap.value.to), (a, b));
+    assert_eq!(swap.effective_at, 10);
+}
+
+#[cfg(not(feature = "weighted-multisig"))]
+#[test]
+fn remove_unweighted_signer() {
+    let (a, b, c, d) = (
+        Address::new_id(1),
+        Address::new_id(2),
+        Address::new_id(3),
+        Address::new_id(4),
+    );
+    let mut rt = construct(vec![a, b, c, d], Vec::new(), 2);
+
+    // The threshold is kept while at least one signer more than it remains
+    remove_signer(&mut rt, d, false);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![a, b, c]);
+    assert_eq!(st.num_approvals_threshold, 2);
+
+    // Otherwise it is lowered by one
+    remove_signer(&mut rt, c, false);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.num_approvals_threshold, 1);
+
+    // As it is when decreasing
+    let mut rt = construct(vec![a, b, c, d], Vec::new(), 2);
+    remove_signer(&mut rt, d, true);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.num_approvals_threshold, 1);
+}
+
+#[cfg(feature = "weighted-multisig")]
+#[test]
+fn remove_signer_keeps_reachable_threshold() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let mut rt = construct(vec![a, b, c], Vec::new(), 2);
+
+    // The two remaining signers still meet the threshold
+    remove_signer(&mut rt, c, false);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![a, b]);
+    assert_eq!(st.num_approvals_threshold, 2);
+
+    // A single signer can't, so the threshold is lowered
+    remove_signer(&mut rt, b, false);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.num_approvals_threshold, 1);
+}
+
+#[cfg(feature = "weighted-multisig")]
+#[test]
+fn weighted_signers() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let st = state(vec![a, b, c], vec![3, 1, 2]);
+
+    assert_eq!(st.signer_weight(&a), 3);
+    assert_eq!(st.total_weight(), 6);
+    assert_eq!(st.approval_weight(&[b, c]), 3);
+    // Approvals of parties which are no longer signers carry no weight
+    assert_eq!(st.approval_weight(&[b, Address::new_id(4)]), 1);
+
+    let decoded: State = from_slice(&to_vec(&st).unwrap()).unwrap();
+    assert_eq!(decoded.signer_weights, vec![3, 1, 2]);
+}
+
+#[cfg(feature = "weighted-multisig")]
+#[test]
+fn weighted_encoding() {
+    let params = AddSignerParams {
+        signer: Address::new_id(1),
+        increase: true,
+        weight: 5,
+    };
+    let bz = to_vec(&params).unwrap();
+    assert_eq!(bz, to_vec(&(Address::new_id(1), true, 5)).unwrap());
+    assert_eq!(from_slice::<AddSignerParams>(&bz).unwrap().weight, 5);
+}
+
+#[cfg(feature = "weighted-multisig")]
+#[test]
+fn remove_weighted_signer() {
+    let (a, b, c) = (Address::new_id(1), Address::new_id(2), Address::new_id(3));
+    let mut rt = construct(vec![a, b, c], vec![3, 1, 2], 4);
+
+    // The remaining weight of 4 still meets the threshold, even though only two signers remain
+    remove_signer(&mut rt, c, false);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signer_weights, vec![3, 1]);
+    assert_eq!(st.num_approvals_threshold, 4);
+
+    // The remaining weight of 3 doesn't, so the threshold drops by the removed weight
+    remove_signer(&mut rt, b, false);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.signers, vec![a]);
+    assert_eq!(st.num_approvals_threshold, 3);
+
+    // Decreasing lowers the threshold by the weight of the removed signer
+    let mut rt = construct(vec![a, b, c], vec![3, 1, 2], 5);
+    remove_signer(&mut rt, c, true);
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.num_approvals_threshold, 3);
+}
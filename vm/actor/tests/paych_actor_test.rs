@@ -4,22 +4,22 @@
 mod common;
 use actor::{
     paych::{
-        ConstructorParams, LaneState, Merge, Method, ModVerifyParams, PaymentVerifyParams,
-        SignedVoucher, State as PState, UpdateChannelStateParams, LANE_LIMIT, SETTLE_DELAY,
+        policy, ConstructorParams, LaneState, Merge, Method, ModVerifyParams, PaymentVerifyParams,
+        SecretHashFunction, SignedVoucher, State as PState, UpdateChannelStateParams,
     },
     ACCOUNT_ACTOR_CODE_ID, INIT_ACTOR_ADDR, INIT_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID,
     PAYCH_ACTOR_CODE_ID,
 };
 use address::Address;
-use cid::Cid;
+use cid::{multihash::Sha2_256, Cid};
 use clock::ChainEpoch;
 use common::*;
-use crypto::Signature;
+use crypto::{Signature, SignatureError};
 use derive_builder::Builder;
 use encoding::to_vec;
-use num_bigint::BigInt;
+use fil_types::NetworkVersion;
 use std::collections::HashMap;
-use vm::{ExitCode, Serialized, TokenAmount, METHOD_CONSTRUCTOR, METHOD_SEND};
+use vm::{ExitCode, MethodNum, Serialized, TokenAmount, METHOD_CONSTRUCTOR, METHOD_SEND};
 
 const R_PAYEE_ADDR: u64 = 103;
 const R_PAYER_ADDR: u64 = 102;
@@ -32,11 +32,15 @@ struct LaneParams {
     nonce: u64,
 }
 
-fn is_ok(rt: &mut MockRuntime, method_num: u64, ser: &Serialized) {
+fn settle_delay() -> ChainEpoch {
+    policy(NetworkVersion::V0).settle_delay
+}
+
+fn is_ok(rt: &mut MockRuntime, method_num: MethodNum, ser: &Serialized) {
     assert!(rt.call(&*PAYCH_ACTOR_CODE_ID, method_num, ser).is_ok());
 }
 
-fn expect_error(rt: &mut MockRuntime, method_num: u64, ser: &Serialized, exp: ExitCode) {
+fn expect_error(rt: &mut MockRuntime, method_num: MethodNum, ser: &Serialized, exp: ExitCode) {
     assert_eq!(
         exp,
         rt.call(&*PAYCH_ACTOR_CODE_ID, method_num, ser,)
@@ -185,8 +189,8 @@ mod create_lane_tests {
         secret_preimage: Vec<u8>,
         #[builder(default)]
         sig: Option<Signature>,
-        #[builder(default = "true")]
-        verify_sig: bool,
+        #[builder(default = "Ok(())")]
+        verify_sig: Result<(), SignatureError>,
         #[builder(default = "ExitCode::ErrIllegalArgument")]
         exp_exit_code: ExitCode,
     }
@@ -247,7 +251,15 @@ mod create_lane_tests {
             TestCase::builder()
                 .desc("fails if signature is not verified".to_string())
                 .sig(sig.clone())
-                .verify_sig(false)
+                .verify_sig(Err(SignatureError::WrongSigner))
+                .build()
+                .unwrap(),
+            TestCase::builder()
+                .desc("fails if signature can't be decoded".to_string())
+                .sig(sig.clone())
+                .verify_sig(Err(SignatureError::InvalidSignatureBytes(
+                    "bad bytes".to_string(),
+                )))
                 .build()
                 .unwrap(),
             TestCase::builder()
@@ -283,7 +295,7 @@ mod create_lane_tests {
                 secret_pre_image: test_case.secret_preimage.clone(),
                 lane: test_case.lane,
                 nonce: test_case.nonce,
-                amount: BigInt::from(test_case.amt),
+                amount: TokenAmount::from(test_case.amt),
                 signature: test_case.sig.clone(),
                 ..SignedVoucher::default()
             };
@@ -293,23 +305,18 @@ mod create_lane_tests {
             rt.expect_validate_caller_addr(vec![payer_addr, payee_addr]);
 
             if test_case.sig.is_some() && test_case.secret_preimage.len() == 0 {
-                let exp_exit_code = if !test_case.verify_sig {
-                    ExitCode::ErrIllegalState
-                } else {
-                    ExitCode::Ok
-                };
                 rt.expect_verify_signature(ExpectedVerifySig {
                     sig: sv.clone().signature.unwrap(),
                     signer: payer_addr,
                     plaintext: to_vec(&sv).unwrap(),
-                    result: exp_exit_code,
+                    result: test_case.verify_sig.clone(),
                 });
             }
 
             if test_case.exp_exit_code == ExitCode::Ok {
                 is_ok(
                     &mut rt,
-                    Method::UpdateChannelState as u64,
+                    Method::UpdateChannelState.into(),
                     &Serialized::serialize(ucp).unwrap(),
                 );
 
@@ -322,7 +329,7 @@ mod create_lane_tests {
             } else {
                 expect_error(
                     &mut rt,
-                    Method::UpdateChannelState as u64,
+                    Method::UpdateChannelState.into(),
                     &Serialized::serialize(ucp).unwrap(),
                     test_case.exp_exit_code,
                 );
@@ -345,7 +352,7 @@ mod update_channel_state_redeem {
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), payee_addr);
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
 
-        sv.amount = BigInt::from(9);
+        sv.amount = TokenAmount::from(9);
 
         let payer_addr = Address::new_id(R_PAYER_ADDR);
 
@@ -353,19 +360,19 @@ mod update_channel_state_redeem {
             sig: sv.clone().signature.unwrap(),
             signer: payer_addr,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
 
         is_ok(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
         );
 
         rt.verify();
         let exp_ls = LaneState {
             id: 0,
-            redeemed: BigInt::from(9),
+            redeemed: TokenAmount::from(9),
             nonce: 1,
         };
         let exp_state = PState {
@@ -389,7 +396,7 @@ mod update_channel_state_redeem {
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
 
         let initial_amount = state.to_send;
-        sv.amount = BigInt::from(9);
+        sv.amount = TokenAmount::from(9);
         sv.lane = 1;
         let ls_to_update: &LaneState = &state.lane_states[1];
         sv.nonce = ls_to_update.nonce + 1;
@@ -399,12 +406,12 @@ mod update_channel_state_redeem {
             sig: sv.clone().signature.unwrap(),
             signer: payer_addr,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
 
         is_ok(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
         );
 
@@ -438,11 +445,11 @@ mod merge_tests {
             sig: sv.clone().signature.unwrap(),
             signer: payee_addr,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
         expect_error(
             rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
             exp_exit_code,
         );
@@ -468,12 +475,12 @@ mod merge_tests {
             sig: sv.clone().signature.unwrap(),
             signer: payee_addr,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
 
         is_ok(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
         );
         rt.verify();
@@ -566,7 +573,7 @@ mod merge_tests {
         let merge_from = LaneState {
             id: 999,
             nonce: sv.nonce,
-            redeemed: BigInt::from(0),
+            redeemed: TokenAmount::from(0),
         };
 
         sv.lane = merge_to.id;
@@ -580,12 +587,12 @@ mod merge_tests {
 
     #[test]
     fn lane_limit_exceeded() {
-        let num_lanes = LANE_LIMIT as u64;
+        let num_lanes = policy(NetworkVersion::V0).lane_limit as u64;
         let (mut rt, mut sv, _) = construct_runtime(num_lanes);
 
         sv.lane += 1;
         sv.nonce += 1;
-        sv.amount = BigInt::from(100);
+        sv.amount = TokenAmount::from(100);
         failure_end(&mut rt, sv, ExitCode::ErrIllegalArgument);
     }
 }
@@ -604,14 +611,14 @@ mod update_channel_state_extra {
 
         sv.extra = Some(ModVerifyParams {
             actor: other_addr,
-            method: Method::UpdateChannelState as u64,
+            method: Method::UpdateChannelState.into(),
             data: Serialized::serialize(fake_params.clone()).unwrap(),
         });
         rt.expect_verify_signature(ExpectedVerifySig {
             sig: sv.clone().signature.unwrap(),
             signer: state.to,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
         let exp_send_params = PaymentVerifyParams {
             extra: Serialized::serialize(fake_params.to_vec()).unwrap(),
@@ -620,7 +627,7 @@ mod update_channel_state_extra {
 
         rt.expect_send(
             other_addr,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             Serialized::serialize(exp_send_params).unwrap(),
             TokenAmount::from(0u8),
             Serialized::default(),
@@ -633,7 +640,7 @@ mod update_channel_state_extra {
         let (mut rt, sv) = construct_runtime(ExitCode::Ok);
         is_ok(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
         );
         rt.verify();
@@ -644,12 +651,105 @@ mod update_channel_state_extra {
         let (mut rt, sv) = construct_runtime(ExitCode::ErrPlaceholder);
         expect_error(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
             ExitCode::ErrPlaceholder,
         );
         rt.verify();
     }
+
+    #[test]
+    fn extra_call_not_sent_for_invalid_voucher() {
+        let (mut rt, mut sv) = require_create_cannel_with_lanes(1);
+        let state: PState = rt.get_state().unwrap();
+        rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+
+        // Voucher nonce is outdated, extra actor must not be called
+        sv.nonce = 0;
+        sv.extra = Some(ModVerifyParams {
+            actor: Address::new_id(OTHER_ADDR),
+            method: Method::UpdateChannelState.into(),
+            data: Serialized::default(),
+        });
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: sv.clone().signature.unwrap(),
+            signer: state.to,
+            plaintext: to_vec(&sv).unwrap(),
+            result: Ok(()),
+        });
+        expect_error(
+            &mut rt,
+            Method::UpdateChannelState.into(),
+            &Serialized::serialize(UpdateChannelStateParams::from(sv)).unwrap(),
+            ExitCode::ErrIllegalArgument,
+        );
+        rt.verify();
+    }
+
+    #[test]
+    fn extra_call_reentrant_redeem() {
+        let (mut rt, mut sv) = require_create_cannel_with_lanes(1);
+        let state: PState = rt.get_state().unwrap();
+        let other_addr = Address::new_id(OTHER_ADDR);
+        rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+
+        sv.nonce = 2;
+        sv.amount = TokenAmount::from(10);
+        sv.extra = Some(ModVerifyParams {
+            actor: other_addr,
+            method: Method::UpdateChannelState.into(),
+            data: Serialized::default(),
+        });
+        let exp_sig = ExpectedVerifySig {
+            sig: sv.clone().signature.unwrap(),
+            signer: state.to,
+            plaintext: to_vec(&sv).unwrap(),
+            result: Ok(()),
+        };
+        let exp_send_params = Serialized::serialize(PaymentVerifyParams {
+            extra: Serialized::default(),
+            proof: vec![],
+        })
+        .unwrap();
+        let params = Serialized::serialize(UpdateChannelStateParams::from(sv)).unwrap();
+
+        rt.expect_verify_signature(exp_sig.clone());
+        rt.expect_send(
+            other_addr,
+            Method::UpdateChannelState.into(),
+            exp_send_params.clone(),
+            TokenAmount::from(0u8),
+            Serialized::default(),
+            ExitCode::Ok,
+        );
+
+        // Extra actor calls back into the channel to redeem the same voucher
+        let inner_params = params.clone();
+        rt.on_send = Some(Box::new(move |rt: &mut MockRuntime| {
+            rt.expect_validate_caller_addr(vec![state.from, state.to]);
+            rt.expect_verify_signature(exp_sig);
+            rt.expect_send(
+                other_addr,
+                Method::UpdateChannelState.into(),
+                exp_send_params,
+                TokenAmount::from(0u8),
+                Serialized::default(),
+                ExitCode::Ok,
+            );
+            is_ok(rt, Method::UpdateChannelState.into(), &inner_params);
+            rt.in_call = true;
+        }));
+
+        is_ok(&mut rt, Method::UpdateChannelState.into(), &params);
+        rt.verify();
+
+        // Voucher amount is only counted once
+        let state: PState = rt.get_state().unwrap();
+        assert_eq!(state.to_send, TokenAmount::from(10));
+        assert_eq!(state.lane_states[0].nonce, 2);
+    }
 }
 
 mod update_channel_state_settling {
@@ -661,9 +761,9 @@ mod update_channel_state_settling {
         let state: PState = rt.get_state().unwrap();
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
-        is_ok(&mut rt, Method::Settle as u64, &Serialized::default());
+        is_ok(&mut rt, Method::Settle.into(), &Serialized::default());
 
-        let exp_settling_at = SETTLE_DELAY + 10;
+        let exp_settling_at = settle_delay() + 10;
         let state: PState = rt.get_state().unwrap();
         assert_eq!(exp_settling_at, state.settling_at);
         assert_eq!(state.min_settle_height, 0);
@@ -698,11 +798,11 @@ mod update_channel_state_settling {
                 sig: sv.clone().signature.unwrap(),
                 signer: state.to,
                 plaintext: to_vec(&ucp.sv).unwrap(),
-                result: ExitCode::Ok,
+                result: Ok(()),
             });
             is_ok(
                 &mut rt,
-                Method::UpdateChannelState as u64,
+                Method::UpdateChannelState.into(),
                 &Serialized::serialize(ucp).unwrap(),
             );
             let new_state: PState = rt.get_state().unwrap();
@@ -725,12 +825,12 @@ mod secret_preimage {
             sig: sv.clone().signature.unwrap(),
             signer: state.to,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
 
         is_ok(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(ucp).unwrap(),
         );
 
@@ -757,17 +857,127 @@ mod secret_preimage {
             sig: sv.clone().signature.unwrap(),
             signer: state.to,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
         expect_error(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(ucp).unwrap(),
             ExitCode::ErrIllegalArgument,
         );
 
         rt.verify();
     }
+
+    /// Applies a voucher with the secret pre image and the secret at the network version, and
+    /// returns the exit code.
+    fn update_with_secret(
+        version: NetworkVersion,
+        secret_pre_image: Vec<u8>,
+        secret: &[u8],
+    ) -> ExitCode {
+        let (mut rt, mut sv) = require_create_cannel_with_lanes(1);
+        rt.network_version = version;
+        let state: PState = rt.get_state().unwrap();
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+
+        sv.secret_pre_image = secret_pre_image;
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: sv.clone().signature.unwrap(),
+            signer: state.to,
+            plaintext: to_vec(&sv).unwrap(),
+            result: Ok(()),
+        });
+        let ucp = UpdateChannelStateParams {
+            proof: vec![],
+            secret: secret.to_vec(),
+            sv,
+        };
+        let exit_code = match rt.call(
+            &*PAYCH_ACTOR_CODE_ID,
+            Method::UpdateChannelState.into(),
+            &Serialized::serialize(ucp).unwrap(),
+        ) {
+            Ok(_) => ExitCode::Ok,
+            Err(e) => e.exit_code(),
+        };
+        rt.verify();
+        exit_code
+    }
+
+    #[test]
+    fn secret_hash_functions() {
+        let secret = b"Magneto";
+        let mut sha256 = [0u8; 32];
+        sha256.copy_from_slice(Sha2_256::digest(secret).digest());
+        let blake2b = encoding::blake2b_256(secret);
+
+        // A digest without a hash function is hashed with blake2b
+        assert_eq!(
+            update_with_secret(NetworkVersion::V4, blake2b.to_vec(), secret),
+            ExitCode::Ok
+        );
+        assert_eq!(
+            update_with_secret(
+                NetworkVersion::V4,
+                SecretHashFunction::Blake2b256.encode(&blake2b),
+                secret
+            ),
+            ExitCode::Ok
+        );
+        assert_eq!(
+            update_with_secret(
+                NetworkVersion::V4,
+                SecretHashFunction::Sha256.encode(&sha256),
+                secret
+            ),
+            ExitCode::Ok
+        );
+
+        // The secret must hash to the digest with the given hash function
+        assert_eq!(
+            update_with_secret(NetworkVersion::V4, sha256.to_vec(), secret),
+            ExitCode::ErrIllegalArgument
+        );
+        assert_eq!(
+            update_with_secret(
+                NetworkVersion::V4,
+                SecretHashFunction::Sha256.encode(&blake2b),
+                secret
+            ),
+            ExitCode::ErrIllegalArgument
+        );
+        assert_eq!(
+            update_with_secret(
+                NetworkVersion::V4,
+                SecretHashFunction::Sha256.encode(&sha256),
+                b"Profesr"
+            ),
+            ExitCode::ErrIllegalArgument
+        );
+
+        // Unknown hash functions are rejected
+        let mut unknown = SecretHashFunction::Sha256.encode(&sha256);
+        unknown[0] = 9;
+        assert_eq!(
+            update_with_secret(NetworkVersion::V4, unknown, secret),
+            ExitCode::ErrIllegalArgument
+        );
+
+        // Prefixed digests are rejected before network version 4
+        assert_eq!(
+            update_with_secret(NetworkVersion::V3, blake2b.to_vec(), secret),
+            ExitCode::Ok
+        );
+        assert_eq!(
+            update_with_secret(
+                NetworkVersion::V3,
+                SecretHashFunction::Sha256.encode(&sha256),
+                secret
+            ),
+            ExitCode::ErrIllegalArgument
+        );
+    }
 }
 
 mod actor_settle {
@@ -781,9 +991,9 @@ mod actor_settle {
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
 
-        is_ok(&mut rt, Method::Settle as u64, &Serialized::default());
+        is_ok(&mut rt, Method::Settle.into(), &Serialized::default());
 
-        let exp_settling_at = EP + SETTLE_DELAY;
+        let exp_settling_at = EP + settle_delay();
         state = rt.get_state().unwrap();
         assert_eq!(state.settling_at, exp_settling_at);
         assert_eq!(state.min_settle_height, 0);
@@ -796,12 +1006,12 @@ mod actor_settle {
         let state: PState = rt.get_state().unwrap();
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
-        is_ok(&mut rt, Method::Settle as u64, &Serialized::default());
+        is_ok(&mut rt, Method::Settle.into(), &Serialized::default());
 
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
         expect_error(
             &mut rt,
-            Method::Settle as u64,
+            Method::Settle.into(),
             &Serialized::default(),
             ExitCode::ErrIllegalState,
         );
@@ -813,7 +1023,7 @@ mod actor_settle {
         rt.epoch = EP;
         let mut state: PState = rt.get_state().unwrap();
 
-        sv.min_settle_height = (EP + SETTLE_DELAY) + 1;
+        sv.min_settle_height = (EP + settle_delay()) + 1;
         let ucp = UpdateChannelStateParams::from(sv.clone());
 
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
@@ -821,11 +1031,11 @@ mod actor_settle {
             sig: ucp.sv.clone().signature.unwrap(),
             signer: state.to,
             plaintext: to_vec(&sv).unwrap(),
-            result: ExitCode::Ok,
+            result: Ok(()),
         });
         is_ok(
             &mut rt,
-            Method::UpdateChannelState as u64,
+            Method::UpdateChannelState.into(),
             &Serialized::serialize(&ucp).unwrap(),
         );
         state = rt.get_state().unwrap();
@@ -833,7 +1043,7 @@ mod actor_settle {
         assert_eq!(state.min_settle_height, ucp.sv.min_settle_height);
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
-        is_ok(&mut rt, Method::Settle as u64, &Serialized::default());
+        is_ok(&mut rt, Method::Settle.into(), &Serialized::default());
         state = rt.get_state().unwrap();
         assert_eq!(state.settling_at, ucp.sv.min_settle_height);
     }
@@ -871,7 +1081,7 @@ mod actor_collect {
         let mut state: PState = rt.get_state().unwrap();
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
         rt.expect_validate_caller_addr(vec![state.from, state.to]);
-        is_ok(&mut rt, Method::Settle as u64, &Serialized::default());
+        is_ok(&mut rt, Method::Settle.into(), &Serialized::default());
         state = rt.get_state().unwrap();
 
         assert_eq!(state.settling_at, 11);
@@ -881,7 +1091,7 @@ mod actor_collect {
 
         rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.to);
 
-        is_ok(&mut rt, Method::Collect as u64, &Serialized::default());
+        is_ok(&mut rt, Method::Collect.into(), &Serialized::default());
         state = rt.get_state().unwrap();
         assert_eq!(state.to_send, TokenAmount::from(0u8));
     }
@@ -924,7 +1134,7 @@ mod actor_collect {
             if !tc.dont_settle {
                 rt.set_caller(ACCOUNT_ACTOR_CODE_ID.clone(), state.from);
                 rt.expect_validate_caller_addr(vec![state.from, state.to]);
-                is_ok(&mut rt, Method::Settle as u64, &Serialized::default());
+                is_ok(&mut rt, Method::Settle.into(), &Serialized::default());
                 state = rt.get_state().unwrap();
                 assert_eq!(state.settling_at, 11);
             }
@@ -935,7 +1145,7 @@ mod actor_collect {
 
             expect_error(
                 &mut rt,
-                Method::Collect as u64,
+                Method::Collect.into(),
                 &Serialized::default(),
                 tc.exp_send_collect,
             );
@@ -993,7 +1203,7 @@ fn require_add_new_lane(rt: &mut MockRuntime, param: LaneParams) -> SignedVouche
         time_lock_max: i64::MAX,
         lane: param.lane,
         nonce: param.nonce,
-        amount: BigInt::from(param.amt),
+        amount: TokenAmount::from(param.amt),
         signature: Some(sig.clone()),
         ..SignedVoucher::default()
     };
@@ -1003,11 +1213,11 @@ fn require_add_new_lane(rt: &mut MockRuntime, param: LaneParams) -> SignedVouche
         sig: sig.clone(),
         signer: payee_addr,
         plaintext: to_vec(&sv).unwrap(),
-        result: ExitCode::Ok,
+        result: Ok(()),
     });
     is_ok(
         rt,
-        Method::UpdateChannelState as u64,
+        Method::UpdateChannelState.into(),
         &Serialized::serialize(UpdateChannelStateParams::from(sv.clone())).unwrap(),
     );
     rt.verify();
@@ -1016,7 +1226,7 @@ fn require_add_new_lane(rt: &mut MockRuntime, param: LaneParams) -> SignedVouche
         time_lock_max: i64::MAX,
         lane: param.lane,
         nonce: param.nonce,
-        amount: BigInt::from(param.amt),
+        amount: TokenAmount::from(param.amt),
         signature: Some(sig.clone()),
         ..SignedVoucher::default()
     }
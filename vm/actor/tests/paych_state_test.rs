@@ -0,0 +1,62 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::paych::{LaneState, State};
+use address::Address;
+use vm::TokenAmount;
+
+fn channel_state() -> State {
+    let mut st = State::new(Address::new_id(101), Address::new_id(102));
+    st.to_send = TokenAmount::from(25);
+    st.lane_states = vec![
+        LaneState {
+            id: 1,
+            redeemed: TokenAmount::from(10),
+            nonce: 2,
+        },
+        LaneState {
+            id: 4,
+            redeemed: TokenAmount::from(15),
+            nonce: 1,
+        },
+    ];
+    st
+}
+
+#[test]
+fn lane_getters() {
+    let mut st = channel_state();
+    assert_eq!(st.lane_count(), 2);
+    assert_eq!(st.lane_state(4).unwrap().redeemed, TokenAmount::from(15));
+    assert_eq!(st.lane_state(1).unwrap().nonce, 2);
+    assert!(st.lane_state(2).is_none());
+
+    assert!(!st.is_settling());
+    st.settling_at = 50;
+    assert!(st.is_settling());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn state_json() {
+    use actor::paych::json::StateJsonRef;
+
+    let mut st = channel_state();
+    st.settling_at = 50;
+    st.min_settle_height = 40;
+    let json = serde_json::to_value(StateJsonRef(&st)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "From": "t0101",
+            "To": "t0102",
+            "ToSend": "25",
+            "SettlingAt": 50,
+            "MinSettleHeight": 40,
+            "LaneStates": [
+                { "ID": 1, "Redeemed": "10", "Nonce": 2 },
+                { "ID": 4, "Redeemed": "15", "Nonce": 1 },
+            ],
+        })
+    );
+}
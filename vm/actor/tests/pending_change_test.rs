@@ -0,0 +1,44 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::PendingChange;
+use address::Address;
+use encoding::{from_slice, to_vec};
+use vm::ExitCode;
+
+#[test]
+fn confirm_after_effective() {
+    let mut pending = Some(PendingChange::new(Address::new_id(1), 10));
+
+    let err = PendingChange::confirm(&mut pending, 9, None).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalState);
+    assert!(pending.is_some());
+
+    assert_eq!(
+        PendingChange::confirm(&mut pending, 10, None).unwrap(),
+        Address::new_id(1)
+    );
+    assert!(pending.is_none());
+
+    // Change can only be confirmed once
+    assert!(PendingChange::confirm(&mut pending, 10, None).is_err());
+}
+
+#[test]
+fn expired_change() {
+    let mut pending = Some(PendingChange::new(Address::new_id(1), 10));
+    assert!(!pending.as_ref().unwrap().is_expired(14, 5));
+    assert!(pending.as_ref().unwrap().is_expired(15, 5));
+
+    assert!(PendingChange::confirm(&mut pending, 15, Some(5)).is_err());
+    assert!(PendingChange::confirm(&mut pending, 15, None).is_ok());
+}
+
+#[test]
+fn serialize_symmetric() {
+    let change = PendingChange::new(Address::new_id(3), 100);
+    let bz = to_vec(&change).unwrap();
+    // Encoded as a tuple of the value and effective epoch
+    assert_eq!(bz, to_vec(&(Address::new_id(3), 100)).unwrap());
+    assert_eq!(from_slice::<PendingChange<Address>>(&bz).unwrap(), change);
+}
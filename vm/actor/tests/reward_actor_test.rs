@@ -4,12 +4,18 @@
 mod common;
 
 use actor::{
-    reward::{AwardBlockRewardParams, Method},
-    REWARD_ACTOR_ADDR, REWARD_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID,
+    miner,
+    network::{EPOCHS_IN_YEAR, EXPECTED_LEADERS_PER_EPOCH},
+    reward::{AwardBlockRewardParams, Method, State, BASELINE_POWER},
+    BURNT_FUNDS_ACTOR_ADDR, POWER_ACTOR_CODE_ID, REWARD_ACTOR_ADDR, REWARD_ACTOR_CODE_ID,
+    STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID,
 };
 use address::Address;
 use common::*;
-use vm::{Serialized, TokenAmount, METHOD_CONSTRUCTOR};
+use fil_types::{NetworkVersion, StoragePower};
+use num_bigint::BigUint;
+use num_bigint::{bigint_ser::BigIntSer, BigInt};
+use vm::{ActorError, ExitCode, Serialized, TokenAmount, METHOD_CONSTRUCTOR, METHOD_SEND};
 
 fn construct_runtime() -> MockRuntime {
     MockRuntime {
@@ -20,6 +26,16 @@ fn construct_runtime() -> MockRuntime {
     }
 }
 
+/// Runtime of the reward actor with the minting of `NetworkVersion::V10`, constructed.
+fn construct_minting_runtime() -> MockRuntime {
+    let mut rt = MockRuntime {
+        network_version: NetworkVersion::V10,
+        ..construct_runtime()
+    };
+    construct_and_verify(&mut rt);
+    rt
+}
+
 #[test]
 #[should_panic(expected = "actor current balance 0 insufficient to pay gas reward 10")]
 fn balance_less_than_reward() {
@@ -35,19 +51,228 @@ fn balance_less_than_reward() {
         miner: miner,
         penalty: TokenAmount::from(0u8),
         gas_reward: gas_reward,
-        ticket_count: 0,
+        win_count: 0,
     };
 
     // Expect call to fail because actor doesnt have enough tokens to reward
     let _res = rt.call(
         &*REWARD_ACTOR_CODE_ID,
-        Method::AwardBlockReward as u64,
+        Method::AwardBlockReward.into(),
         &Serialized::serialize(&params).unwrap(),
     );
 
     rt.verify()
 }
 
+#[test]
+fn award_block_reward_per_win() {
+    for &(version, win_count, shares) in &[
+        (NetworkVersion::V0, 1, 1u64),
+        (NetworkVersion::V0, 3, 1),
+        (NetworkVersion::V2, 1, 1),
+        (NetworkVersion::V2, 3, 3),
+    ] {
+        let mut rt = construct_runtime();
+        construct_and_verify(&mut rt);
+        rt.network_version = version;
+        rt.balance = TokenAmount::from(10u128.pow(27));
+
+        let st: State = rt.get_state().unwrap();
+        let miner = Address::new_id(1000);
+        let gas_reward = TokenAmount::from(10u8);
+        let reward = st.this_epoch_reward * shares / EXPECTED_LEADERS_PER_EPOCH + &gas_reward;
+
+        rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+        rt.expect_send(
+            miner,
+            miner::Method::AddLockedFund.into(),
+            Serialized::serialize(&reward).unwrap(),
+            reward.clone(),
+            Serialized::default(),
+            ExitCode::Ok,
+        );
+        rt.expect_send(
+            *BURNT_FUNDS_ACTOR_ADDR,
+            METHOD_SEND,
+            Serialized::default(),
+            TokenAmount::from(0u8),
+            Serialized::default(),
+            ExitCode::Ok,
+        );
+        let params = AwardBlockRewardParams {
+            miner,
+            penalty: TokenAmount::from(0u8),
+            gas_reward,
+            win_count,
+        };
+        rt.call(
+            &*REWARD_ACTOR_CODE_ID,
+            Method::AwardBlockReward.into(),
+            &Serialized::serialize(&params).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+    }
+}
+
+#[test]
+fn award_block_reward_rejects_no_wins() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+    rt.network_version = NetworkVersion::V2;
+    rt.balance = TokenAmount::from(10u128.pow(27));
+
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    let params = AwardBlockRewardParams {
+        miner: Address::new_id(1000),
+        penalty: TokenAmount::from(0u8),
+        gas_reward: TokenAmount::from(0u8),
+        win_count: 0,
+    };
+    let err = rt
+        .call(
+            &*REWARD_ACTOR_CODE_ID,
+            Method::AwardBlockReward.into(),
+            &Serialized::serialize(&params).unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+    rt.verify();
+}
+
+#[test]
+fn genesis_reward() {
+    let mut rt = construct_minting_runtime();
+
+    let st: State = rt.get_state().unwrap();
+    assert!(st.this_epoch_reward > TokenAmount::from(0u8));
+    assert_eq!(st.simple_supply, st.this_epoch_reward);
+    assert_eq!(st.baseline_supply, TokenAmount::from(0u8));
+    assert_eq!(st.baseline_power, StoragePower::from(BASELINE_POWER));
+
+    rt.expect_validate_caller_any();
+    let reward: TokenAmount = rt
+        .call(
+            &*REWARD_ACTOR_CODE_ID,
+            Method::ThisEpochReward.into(),
+            &Serialized::default(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(reward, st.this_epoch_reward);
+}
+
+#[test]
+fn simple_minting_decays() {
+    let mut rt = construct_minting_runtime();
+
+    let mut total = rt.get_state::<State>().unwrap().this_epoch_reward;
+    let mut prev = total.clone();
+    for epoch in 1..10 {
+        update_network_kpi(&mut rt, StoragePower::from(0u8)).unwrap();
+        let st: State = rt.get_state().unwrap();
+        assert_eq!(st.reward_epochs_paid, epoch);
+        // Without realized power, only the simple supply is minted
+        assert_eq!(st.effective_network_time, BigUint::from(0u8));
+        assert_eq!(st.baseline_supply, TokenAmount::from(0u8));
+        assert!(st.this_epoch_reward <= prev);
+        total += &st.this_epoch_reward;
+        prev = st.this_epoch_reward;
+    }
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.simple_supply, total);
+}
+
+#[test]
+fn simple_supply_halves_every_six_years() {
+    // The unminted simple supply decays with a half-life of 6 years, so the supply minted by
+    // the end of year y is SIMPLE_TOTAL * (1 - 2^(-y/6)) attoFIL, rounded down.
+    let vectors: &[(i64, &str)] = &[
+        (1, "10910128185966069525977379"),
+        (2, "20629947401590026262414718"),
+        (3, "29289321881345247559915563"),
+        (4, "37003947505256341761639469"),
+        (5, "43876897584531350928323347"),
+        (6, "50000000000000000000000000"),
+    ];
+    for &(years, expected) in vectors {
+        let mut rt = construct_minting_runtime();
+
+        // The update paying the second to last epoch of the year computes the supply minted
+        // by the end of the year
+        let mut st: State = rt.get_state().unwrap();
+        st.reward_epochs_paid = years * EPOCHS_IN_YEAR - 2;
+        rt.replace_state(&st);
+        update_network_kpi(&mut rt, StoragePower::from(0u8)).unwrap();
+
+        let st: State = rt.get_state().unwrap();
+        let expected = TokenAmount::from_atto(expected.parse::<BigInt>().unwrap());
+        assert_eq!(st.simple_supply, expected, "after {} years", years);
+    }
+}
+
+#[test]
+fn baseline_minting_capped_by_realized_power() {
+    let mut rt = construct_minting_runtime();
+    let mut rt_zero = construct_minting_runtime();
+
+    let mut prev_baseline_supply = TokenAmount::from(0u8);
+    for epoch in 1..5u64 {
+        // Power beyond the baseline doesn't advance the network time more than an epoch
+        update_network_kpi(&mut rt, StoragePower::from(BASELINE_POWER) * 2).unwrap();
+        update_network_kpi(&mut rt_zero, StoragePower::from(0u8)).unwrap();
+
+        let st: State = rt.get_state().unwrap();
+        let st_zero: State = rt_zero.get_state().unwrap();
+        assert_eq!(st.effective_network_time, BigUint::from(epoch) << 30);
+        assert_eq!(st.cumsum_realized, st.cumsum_baseline);
+        assert!(st.baseline_supply > TokenAmount::from(0u8));
+        assert_eq!(st.simple_supply, st_zero.simple_supply);
+        // Reward includes the newly unlocked baseline supply on top of the simple minting
+        assert_eq!(
+            st.this_epoch_reward,
+            st_zero.this_epoch_reward + &st.baseline_supply - &prev_baseline_supply
+        );
+        prev_baseline_supply = st.baseline_supply;
+    }
+}
+
+#[test]
+fn negative_realized_power() {
+    let mut rt = construct_minting_runtime();
+
+    let err = update_network_kpi(&mut rt, StoragePower::from(-1)).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalArgument);
+
+    // Before network version 10 it fails in the state update instead
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+    let err = update_network_kpi(&mut rt, StoragePower::from(-1)).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalState);
+}
+
+#[test]
+fn minting_before_network_version_10() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+
+    // No reward is paid for the genesis epoch
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.this_epoch_reward, TokenAmount::from(0u8));
+    assert_eq!(st.baseline_power, StoragePower::from(0u8));
+
+    // The simple supply is minted up to the start of the epoch, one epoch behind the minting
+    // from network version 10
+    let genesis: State = construct_minting_runtime().get_state().unwrap();
+    update_network_kpi(&mut rt, StoragePower::from(0u8)).unwrap();
+    let st: State = rt.get_state().unwrap();
+    assert_eq!(st.reward_epochs_paid, 1);
+    assert_eq!(st.simple_supply, genesis.simple_supply);
+    assert_eq!(st.this_epoch_reward, genesis.this_epoch_reward);
+}
+
 fn construct_and_verify(rt: &mut MockRuntime) {
     rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR.clone()]);
     let ret = rt
@@ -61,3 +286,39 @@ fn construct_and_verify(rt: &mut MockRuntime) {
     assert_eq!(Serialized::default(), ret);
     rt.verify();
 }
+
+fn update_network_kpi(
+    rt: &mut MockRuntime,
+    realized_power: StoragePower,
+) -> Result<Serialized, ActorError> {
+    rt.set_caller(POWER_ACTOR_CODE_ID.clone(), *STORAGE_POWER_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![*STORAGE_POWER_ACTOR_ADDR]);
+    let ret = rt.call(
+        &*REWARD_ACTOR_CODE_ID,
+        Method::UpdateNetworkKPI.into(),
+        &Serialized::serialize(BigIntSer(&realized_power)).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+#[test]
+#[should_panic(expected = "cannot give block reward for zero tickets")]
+fn award_block_reward_zero_tickets_before_network_version_2() {
+    let mut rt = construct_runtime();
+    construct_and_verify(&mut rt);
+    rt.balance = TokenAmount::from(10u128.pow(27));
+
+    rt.expect_validate_caller_addr(vec![*SYSTEM_ACTOR_ADDR]);
+    let params = AwardBlockRewardParams {
+        miner: Address::new_id(1000),
+        penalty: TokenAmount::from(0u8),
+        gas_reward: TokenAmount::from(0u8),
+        win_count: 0,
+    };
+    let _res = rt.call(
+        &*REWARD_ACTOR_CODE_ID,
+        Method::AwardBlockReward.into(),
+        &Serialized::serialize(&params).unwrap(),
+    );
+}
@@ -46,3 +46,34 @@ fn for_each() {
 
     assert_eq!(vals.len(), 3);
 }
+
+#[test]
+fn for_each_all() {
+    let store = db::MemoryDB::default();
+    let mut smm = SetMultimap::new(&store);
+
+    smm.put(100, 8).unwrap();
+    smm.put(100, 3).unwrap();
+    smm.put(101, 8).unwrap();
+    smm.put(250, 1).unwrap();
+    smm.put(250, 1).unwrap();
+
+    let mut keys = smm.keys().unwrap();
+    keys.sort_unstable();
+    assert_eq!(keys, vec![100, 101, 250]);
+
+    let mut vals: Vec<(ChainEpoch, u64)> = Vec::new();
+    smm.for_each_all(|epoch, id| {
+        vals.push((epoch, id));
+        Ok(())
+    })
+    .unwrap();
+    vals.sort();
+    assert_eq!(vals, vec![(100, 3), (100, 8), (101, 8), (250, 1)]);
+
+    // Removed sets are no longer iterated
+    smm.remove_all(100).unwrap();
+    let mut keys = smm.keys().unwrap();
+    keys.sort_unstable();
+    assert_eq!(keys, vec![101, 250]);
+}
@@ -1,7 +1,7 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use actor::Set;
+use actor::{Multimap, Set};
 
 #[test]
 fn put() {
@@ -47,3 +47,19 @@ fn delete() {
     // Test delete when doesn't exist doesn't error
     set.delete(key).unwrap();
 }
+
+#[test]
+fn from_root() {
+    let store = db::MemoryDB::default();
+    let mut set = Set::new(&store);
+
+    // An empty set shares its root with an empty map
+    assert_eq!(set.root().unwrap(), Multimap::new(&store).root().unwrap());
+
+    set.put("0".into()).unwrap();
+    let root = set.root().unwrap();
+
+    let set = Set::from_root(&store, &root).unwrap();
+    assert_eq!(set.has(b"0"), Ok(true));
+    assert_eq!(set.has(b"1"), Ok(false));
+}
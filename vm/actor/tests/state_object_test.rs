@@ -0,0 +1,35 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::account::State;
+use address::Address;
+use db::MemoryDB;
+use encoding::Cbor;
+use ipld_blockstore::{BlockStore, TrackingBlockStore};
+use runtime::StateObject;
+
+#[test]
+fn flush_skips_unchanged_state() {
+    let db = MemoryDB::default();
+    let store = TrackingBlockStore::new(&db);
+
+    let loaded = State {
+        address: Address::new_id(100),
+    };
+    let head = loaded.cid().unwrap();
+    let mut state = loaded.clone();
+
+    // Unchanged state keeps its head without writing
+    assert_eq!(state.flush(&store, &loaded, &head).unwrap(), head);
+    assert_eq!(store.stats().writes, 0);
+
+    // Changed state is written under a new head
+    state.address = Address::new_id(101);
+    let new_head = state.flush(&store, &loaded, &head).unwrap();
+    assert_ne!(new_head, head);
+    assert_eq!(store.stats().writes, 1);
+    assert_eq!(
+        store.get::<State>(&new_head).unwrap().unwrap().address,
+        Address::new_id(101)
+    );
+}
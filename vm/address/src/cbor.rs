@@ -0,0 +1,67 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Serde helpers for CBOR encoded collections of addresses.
+
+/// Serializes and deserializes a `Vec<Address>` as an array of address bytes, for use with
+/// `#[serde(with = "address::cbor::vec")]`. Deserializing reports the index of the first
+/// invalid address of the array.
+pub mod vec {
+    use crate::Address;
+    use alloc::vec::Vec;
+    use core::fmt;
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::ser::{SerializeSeq, Serializer};
+    use serde::Deserializer;
+    use serde_bytes::ByteBuf;
+
+    /// Upper bound on the capacity preallocated from the length of the encoded array.
+    const MAX_PREALLOCATED: usize = 256;
+
+    pub fn serialize<S>(addrs: &[Address], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(addrs.len()))?;
+        for addr in addrs {
+            seq.serialize_element(addr)?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Address>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct AddressesVisitor;
+
+        impl<'de> Visitor<'de> for AddressesVisitor {
+            type Value = Vec<Address>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an array of addresses")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let capacity = seq.size_hint().unwrap_or(0).min(MAX_PREALLOCATED);
+                let mut addrs = Vec::with_capacity(capacity);
+                while let Some(bz) = seq.next_element::<ByteBuf>()? {
+                    let addr = Address::from_bytes(&bz).map_err(|e| {
+                        de::Error::custom(format_args!(
+                            "invalid address at index {}: {}",
+                            addrs.len(),
+                            e
+                        ))
+                    })?;
+                    addrs.push(addr);
+                }
+                Ok(addrs)
+            }
+        }
+
+        deserializer.deserialize_seq(AddressesVisitor)
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::{encode, Address};
+use alloc::string::String;
+use core::fmt;
+use core::ops::Deref;
+
+/// An Address with its precomputed string encoding, for code paths which display the same
+/// addresses many times. Formatting an Address recomputes the checksum of secp256k1, actor
+/// and BLS addresses, while a DisplayAddress only computes it once when created.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DisplayAddress {
+    addr: Address,
+    encoded: String,
+}
+
+impl DisplayAddress {
+    /// Creates a DisplayAddress, encoding the address with its network.
+    pub fn new(addr: Address) -> Self {
+        Self {
+            encoded: encode(&addr, addr.network()),
+            addr,
+        }
+    }
+
+    /// Returns the Address.
+    pub fn address(&self) -> &Address {
+        &self.addr
+    }
+
+    /// Returns the string encoding of the Address.
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl From<Address> for DisplayAddress {
+    fn from(addr: Address) -> Self {
+        Self::new(addr)
+    }
+}
+
+impl From<DisplayAddress> for Address {
+    fn from(addr: DisplayAddress) -> Self {
+        addr.addr
+    }
+}
+
+impl Deref for DisplayAddress {
+    type Target = Address;
+
+    fn deref(&self) -> &Address {
+        &self.addr
+    }
+}
+
+impl AsRef<str> for DisplayAddress {
+    fn as_ref(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl fmt::Display for DisplayAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encoded)
+    }
+}
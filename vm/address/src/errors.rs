@@ -1,34 +1,100 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{BLS_PUB_LEN, PAYLOAD_HASH_LEN, SECP_PUB_LEN};
+use super::{BLS_PUB_LEN, MAX_SUBADDRESS_LEN, PAYLOAD_HASH_LEN, SECP_PUB_LEN};
+use alloc::vec::Vec;
+use core::{fmt, num};
 use data_encoding::DecodeError;
+#[cfg(feature = "std")]
 use encoding::{CodecProtocol, Error as EncodingError};
-use leb128::read::Error as Leb128Error;
-use std::{io, num};
-use thiserror::Error;
+#[cfg(feature = "std")]
+use std::io;
 
 /// Address error
-#[derive(Debug, PartialEq, Error)]
+#[derive(Debug, PartialEq)]
 pub enum Error {
-    #[error("Unknown address network")]
     UnknownNetwork,
-    #[error("Unknown address protocol")]
     UnknownProtocol,
-    #[error("Invalid address payload")]
     InvalidPayload,
-    #[error("Invalid address length")]
     InvalidLength,
-    #[error("Invalid payload length, wanted: {} got: {0}", PAYLOAD_HASH_LEN)]
     InvalidPayloadLength(usize),
-    #[error("Invalid BLS pub key length, wanted: {} got: {0}", BLS_PUB_LEN)]
     InvalidBLSLength(usize),
-    #[error("Invalid SECP pub key length, wanted: {} got: {0}", SECP_PUB_LEN)]
     InvalidSECPLength(usize),
-    #[error("Invalid address checksum")]
+    InvalidSubaddressLength(usize),
     InvalidChecksum,
-    #[error("Decoding for address failed: {0}")]
-    Base32Decoding(#[from] DecodeError),
+    NonIDAddress,
+    Base32Decoding(DecodeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownNetwork => write!(f, "Unknown address network"),
+            Error::UnknownProtocol => write!(f, "Unknown address protocol"),
+            Error::InvalidPayload => write!(f, "Invalid address payload"),
+            Error::InvalidLength => write!(f, "Invalid address length"),
+            Error::InvalidPayloadLength(len) => write!(
+                f,
+                "Invalid payload length, wanted: {} got: {}",
+                PAYLOAD_HASH_LEN, len
+            ),
+            Error::InvalidBLSLength(len) => write!(
+                f,
+                "Invalid BLS pub key length, wanted: {} got: {}",
+                BLS_PUB_LEN, len
+            ),
+            Error::InvalidSECPLength(len) => write!(
+                f,
+                "Invalid SECP pub key length, wanted: {} got: {}",
+                SECP_PUB_LEN, len
+            ),
+            Error::InvalidSubaddressLength(len) => write!(
+                f,
+                "Invalid delegated subaddress length, wanted at most: {} got: {}",
+                MAX_SUBADDRESS_LEN, len
+            ),
+            Error::InvalidChecksum => write!(f, "Invalid address checksum"),
+            Error::NonIDAddress => write!(f, "Address is not an ID address"),
+            Error::Base32Decoding(err) => write!(f, "Decoding for address failed: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Base32Decoding(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Error from parsing a list of addresses, with the error of every address which failed to
+/// parse and its index in the list.
+#[derive(Debug, PartialEq)]
+pub struct ParseAddressesError {
+    pub errors: Vec<(usize, Error)>,
+}
+
+impl fmt::Display for ParseAddressesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to parse {} addresses", self.errors.len())?;
+        for (i, (index, err)) in self.errors.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { ", " };
+            write!(f, "{}address {}: {}", sep, index, err)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseAddressesError {}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Error {
+        Error::Base32Decoding(err)
+    }
 }
 
 impl From<num::ParseIntError> for Error {
@@ -37,17 +103,14 @@ impl From<num::ParseIntError> for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(_: io::Error) -> Error {
         Error::InvalidPayload
     }
 }
-impl From<Leb128Error> for Error {
-    fn from(_: Leb128Error) -> Error {
-        Error::InvalidPayload
-    }
-}
 
+#[cfg(feature = "std")]
 impl From<Error> for EncodingError {
     fn from(err: Error) -> EncodingError {
         EncodingError::Marshalling {
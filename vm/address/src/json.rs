@@ -0,0 +1,64 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::Address;
+use alloc::string::{String, ToString};
+use core::str::FromStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Wrapper for serializing and deserializing an Address from JSON, as its string encoding.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(transparent)]
+pub struct AddressJson(#[serde(with = "self")] pub Address);
+
+/// Wrapper for serializing an Address reference to JSON.
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct AddressJsonRef<'a>(#[serde(with = "self")] pub &'a Address);
+
+impl From<AddressJson> for Address {
+    fn from(wrapper: AddressJson) -> Self {
+        wrapper.0
+    }
+}
+
+impl From<Address> for AddressJson {
+    fn from(address: Address) -> Self {
+        Self(address)
+    }
+}
+
+pub fn serialize<S>(m: &Address, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&m.to_string())
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Address, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    Address::from_str(&s).map_err(de::Error::custom)
+}
+
+pub mod opt {
+    use super::{Address, AddressJson, AddressJsonRef};
+    use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(v: &Option<Address>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        v.as_ref().map(AddressJsonRef).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Address>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: Option<AddressJson> = Deserialize::deserialize(deserializer)?;
+        Ok(s.map(|v| v.0))
+    }
+}
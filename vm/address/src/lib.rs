@@ -1,21 +1,43 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+//! Filecoin addresses, with their byte and string encodings.
+//!
+//! The crate supports `no_std` with `alloc` when built without the default `std` feature, so
+//! the same address derivation and checksums can be used on embedded and wasm targets. Errors
+//! only implement `std::error::Error`, and addresses are only `Cbor` encodable, with `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod cbor;
+mod display;
 mod errors;
+#[cfg(feature = "json")]
+pub mod json;
 mod network;
 mod payload;
 mod protocol;
-pub use self::errors::Error;
-pub use self::network::Network;
-pub use self::payload::{BLSPublicKey, Payload};
+pub use self::display::DisplayAddress;
+pub use self::errors::{Error, ParseAddressesError};
+pub use self::network::{current_network, set_current_network, Network};
+pub use self::payload::{BLSPublicKey, DelegatedAddress, Payload};
 pub use self::protocol::Protocol;
 
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use blake2b_simd::Params;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::Hash;
+use core::str::FromStr;
 use data_encoding::Encoding;
 use data_encoding_macro::{internal_new_encoding, new_encoding};
-use encoding::{blake2b_variable, de, ser, serde_bytes, Cbor};
-use std::fmt;
-use std::hash::Hash;
-use std::str::FromStr;
+#[cfg(feature = "std")]
+use encoding::Cbor;
+use serde::{de, ser};
 
 /// defines the encoder for base32 encoding with the provided string with no padding
 const ADDRESS_ENCODER: Encoding = new_encoding! {
@@ -35,16 +57,27 @@ pub const BLS_PUB_LEN: usize = 48;
 /// Length of the checksum hash for string encodings.
 pub const CHECKSUM_HASH_LEN: usize = 4;
 
-const MAX_ADDRESS_LEN: usize = 84 + 2;
+/// Max length of the subaddress of a delegated address.
+pub const MAX_SUBADDRESS_LEN: usize = 54;
+
+/// Max length of a u64 in leb128 format, which bounds the payload of an ID address.
+pub const MAX_U64_LEN: usize = 10;
+
+/// Max length of the encoded bytes of an address, including the protocol byte. Delegated
+/// addresses are the longest, with a leb128 encoded namespace and the subaddress.
+pub const MAX_ADDRESS_BYTES_LEN: usize = 1 + MAX_U64_LEN + MAX_SUBADDRESS_LEN;
+
+/// Max length of the decimal string of a u64.
+const MAX_U64_STR_LEN: usize = 20;
+
+/// Max length of the string encoding of an address, which is a delegated address with a 20
+/// digit namespace and the base32 encoded subaddress and checksum.
+const MAX_ADDRESS_LEN: usize = 2 + MAX_U64_STR_LEN + 1 + 93;
 const MAINNET_PREFIX: &str = "f";
 const TESTNET_PREFIX: &str = "t";
 
-// TODO pull network from config (probably)
-const NETWORK_DEFAULT: Network = Network::Testnet;
-
 /// Address is the struct that defines the protocol and data payload conversion from either
 /// a public key or value
-/// TODO add Address JSON implementation
 #[derive(PartialEq, Eq, Clone, Debug, Hash, Copy)]
 pub struct Address {
     network: Network,
@@ -52,6 +85,12 @@ pub struct Address {
 }
 
 impl Address {
+    /// Max length of the encoded bytes of an address.
+    pub const MAX_BYTES: usize = MAX_ADDRESS_BYTES_LEN;
+
+    /// Max length of the string encoding of an address.
+    pub const MAX_STRING_LEN: usize = MAX_ADDRESS_LEN;
+
     /// Address constructor
     fn new(network: Network, protocol: Protocol, bz: &[u8]) -> Result<Self, Error> {
         Ok(Self {
@@ -66,25 +105,31 @@ impl Address {
             Err(Error::InvalidLength)
         } else {
             let protocol = Protocol::from_byte(bz[0]).ok_or(Error::UnknownProtocol)?;
-            Self::new(NETWORK_DEFAULT, protocol, &bz[1..])
+            Self::new(current_network(), protocol, &bz[1..])
         }
     }
 
     /// Generates new address using ID protocol
     pub fn new_id(id: u64) -> Self {
         Self {
-            network: NETWORK_DEFAULT,
+            network: current_network(),
             payload: Payload::ID(id),
         }
     }
 
+    /// Creates an ID address from the leb128 encoded actor ID, which is the payload of the
+    /// encoded bytes of an ID address without the protocol byte.
+    pub fn from_id_bytes(bz: &[u8]) -> Result<Self, Error> {
+        Ok(Self::new_id(from_leb_bytes(bz)?))
+    }
+
     /// Generates new address using Secp256k1 pubkey
     pub fn new_secp256k1(pubkey: &[u8]) -> Result<Self, Error> {
         if pubkey.len() != 65 {
             return Err(Error::InvalidSECPLength(pubkey.len()));
         }
         Ok(Self {
-            network: NETWORK_DEFAULT,
+            network: current_network(),
             payload: Payload::Secp256k1(address_hash(pubkey)),
         })
     }
@@ -92,7 +137,7 @@ impl Address {
     /// Generates new address using the Actor protocol
     pub fn new_actor(data: &[u8]) -> Self {
         Self {
-            network: NETWORK_DEFAULT,
+            network: current_network(),
             payload: Payload::Actor(address_hash(data)),
         }
     }
@@ -105,11 +150,20 @@ impl Address {
         let mut key = [0u8; BLS_PUB_LEN];
         key.copy_from_slice(pubkey);
         Ok(Self {
-            network: NETWORK_DEFAULT,
+            network: current_network(),
             payload: Payload::BLS(key.into()),
         })
     }
 
+    /// Generates new address using the Delegated protocol, with the actor ID of the namespace
+    /// actor and a subaddress of at most `MAX_SUBADDRESS_LEN` bytes.
+    pub fn new_delegated(namespace: u64, subaddress: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            network: current_network(),
+            payload: Payload::Delegated(DelegatedAddress::new(namespace, subaddress)?),
+        })
+    }
+
     /// Returns protocol for Address
     pub fn protocol(&self) -> Protocol {
         Protocol::from(self.payload)
@@ -127,6 +181,14 @@ impl Address {
         self.payload
     }
 
+    /// Returns the actor ID of an ID address, failing for addresses of other protocols.
+    pub fn id(&self) -> Result<u64, Error> {
+        match self.payload {
+            Payload::ID(id) => Ok(id),
+            _ => Err(Error::NonIDAddress),
+        }
+    }
+
     /// Returns the raw bytes data payload of the Address
     pub fn payload_bytes(&self) -> Vec<u8> {
         self.payload.to_raw_bytes()
@@ -147,14 +209,91 @@ impl Address {
     pub fn to_bytes(&self) -> Vec<u8> {
         self.payload.to_bytes()
     }
+
+    /// Returns the length of the encoded payload, so the encoded bytes of the Address are one
+    /// byte longer with the protocol byte.
+    pub const fn payload_len(&self) -> usize {
+        self.payload.raw_len()
+    }
+
+    /// Writes the encoded bytes of the Address, without allocating.
+    #[cfg(feature = "std")]
+    pub fn write_bytes<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let mut buf = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let len = self.payload.encode_into(&mut buf);
+        w.write_all(&buf[..len])
+    }
+
+    /// Returns the string encoding of the Address with the prefix of the given network, rather
+    /// than the Address's own network.
+    pub fn to_string_with_network(&self, network: Network) -> String {
+        encode(self, network)
+    }
+
+    /// Parses an Address from its string encoding with either network prefix, and creates it on
+    /// the given network rather than the network of the prefix.
+    pub fn from_str_with_network(addr: &str, network: Network) -> Result<Self, Error> {
+        let mut addr = Address::from_str(addr)?;
+        addr.network = network;
+        Ok(addr)
+    }
+}
+
+/// Addresses are ordered by their encoded bytes, so by protocol and then by payload, which is
+/// the order of their keys in the state tree. Addresses which only differ by network are
+/// ordered by network.
+impl Ord for Address {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mut a = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let mut b = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let a_len = self.payload.encode_into(&mut a);
+        let b_len = other.payload.encode_into(&mut b);
+        a[..a_len]
+            .cmp(&b[..b_len])
+            .then_with(|| self.network.cmp(&other.network))
+    }
+}
+
+impl PartialOrd for Address {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl fmt::Display for Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", encode(self))
+        write!(f, "{}", encode(self, self.network))
+    }
+}
+
+/// Parses the string encodings of a list of addresses. Every address is parsed, so if any of
+/// them fail the error holds the errors of all of the invalid addresses.
+pub fn parse_addresses<S: AsRef<str>>(addrs: &[S]) -> Result<Vec<Address>, ParseAddressesError> {
+    let mut parsed = Vec::with_capacity(addrs.len());
+    let mut errors = Vec::new();
+    for (index, addr) in addrs.iter().enumerate() {
+        match Address::from_str(addr.as_ref()) {
+            Ok(addr) => parsed.push(addr),
+            Err(err) => errors.push((index, err)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(ParseAddressesError { errors })
     }
 }
 
+/// Parses the string encodings of a list of addresses, stopping at the first address which fails
+/// to parse and returning its index in the list with the error.
+pub fn parse_many<S: AsRef<str>>(addrs: &[S]) -> Result<Vec<Address>, (usize, Error)> {
+    addrs
+        .iter()
+        .enumerate()
+        .map(|(index, addr)| Address::from_str(addr.as_ref()).map_err(|err| (index, err)))
+        .collect()
+}
+
 impl FromStr for Address {
     type Err = Error;
     fn from_str(addr: &str) -> Result<Self, Error> {
@@ -176,6 +315,7 @@ impl FromStr for Address {
             "1" => Protocol::Secp256k1,
             "2" => Protocol::Actor,
             "3" => Protocol::BLS,
+            "4" => Protocol::Delegated,
             _ => {
                 return Err(Error::UnknownProtocol);
             }
@@ -184,28 +324,49 @@ impl FromStr for Address {
         // bytes after the protocol character is the data payload of the address
         let raw = addr.get(2..).ok_or(Error::InvalidPayload)?;
         if protocol == Protocol::ID {
-            if raw.len() > 20 {
-                // 20 is max u64 as string
+            if raw.len() > MAX_U64_STR_LEN {
                 return Err(Error::InvalidLength);
             }
             let id = raw.parse::<u64>()?;
-            return Ok(Address::new_id(id));
+            // ID addresses keep the network of their prefix, not the current network
+            return Ok(Address {
+                network,
+                payload: Payload::ID(id),
+            });
         }
 
+        // delegated addresses have the decimal namespace before the encoded subaddress
+        let (namespace, raw) = if protocol == Protocol::Delegated {
+            let (namespace, raw) = raw.split_at(raw.find('f').ok_or(Error::InvalidPayload)?);
+            if namespace.is_empty() || namespace.len() > MAX_U64_STR_LEN {
+                return Err(Error::InvalidPayload);
+            }
+            (Some(namespace.parse::<u64>()?), &raw[1..])
+        } else {
+            (None, raw)
+        };
+
         // decode using byte32 encoding
         let mut payload = ADDRESS_ENCODER.decode(raw.as_bytes())?;
+        if payload.len() < CHECKSUM_HASH_LEN {
+            return Err(Error::InvalidPayload);
+        }
         // payload includes checksum at end, so split after decoding
         let cksm = payload.split_off(payload.len() - CHECKSUM_HASH_LEN);
 
-        // sanity check to make sure address hash values are correct length
-        if (protocol == Protocol::Secp256k1 || protocol == Protocol::Actor)
-            && payload.len() != PAYLOAD_HASH_LEN
-        {
-            return Err(Error::InvalidPayload);
+        if let Some(namespace) = namespace {
+            let addr = Address {
+                network,
+                payload: Payload::Delegated(DelegatedAddress::new(namespace, &payload)?),
+            };
+            if !validate_checksum(&addr.to_bytes(), cksm) {
+                return Err(Error::InvalidChecksum);
+            }
+            return Ok(addr);
         }
 
-        // sanity check to make sure bls pub key is correct length
-        if protocol == Protocol::BLS && payload.len() != BLS_PUB_LEN {
+        // sanity check to make sure hashes and bls pub keys are the correct length
+        if protocol.payload_len() != Some(payload.len()) {
             return Err(Error::InvalidPayload);
         }
 
@@ -225,8 +386,9 @@ impl ser::Serialize for Address {
     where
         S: ser::Serializer,
     {
-        let address_bytes = self.to_bytes();
-        serde_bytes::Serialize::serialize(&address_bytes, s)
+        let mut buf = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let len = self.payload.encode_into(&mut buf);
+        serde_bytes::Serialize::serialize(&buf[..len], s)
     }
 }
 
@@ -242,10 +404,11 @@ impl<'de> de::Deserialize<'de> for Address {
     }
 }
 
+#[cfg(feature = "std")]
 impl Cbor for Address {}
 
 /// encode converts the address into a string
-fn encode(addr: &Address) -> String {
+fn encode(addr: &Address, network: Network) -> String {
     match addr.protocol() {
         Protocol::Secp256k1 | Protocol::Actor | Protocol::BLS => {
             let ingest = addr.to_bytes();
@@ -255,35 +418,118 @@ fn encode(addr: &Address) -> String {
             bz.extend(checksum(&ingest));
             format!(
                 "{}{}{}",
-                addr.network.to_prefix(),
+                network.to_prefix(),
                 addr.protocol().to_string(),
                 ADDRESS_ENCODER.encode(bz.as_mut()),
             )
         }
         Protocol::ID => format!(
             "{}{}{}",
-            addr.network.to_prefix(),
+            network.to_prefix(),
             addr.protocol().to_string(),
             from_leb_bytes(&addr.payload_bytes()).expect("should read encoded bytes"),
         ),
+        Protocol::Delegated => {
+            let delegated = match addr.payload() {
+                Payload::Delegated(delegated) => delegated,
+                _ => unreachable!("protocol matches the payload"),
+            };
+            let mut bz = delegated.subaddress().to_vec();
+            bz.extend(checksum(&addr.to_bytes()));
+            format!(
+                "{}{}{}f{}",
+                network.to_prefix(),
+                addr.protocol().to_string(),
+                delegated.namespace(),
+                ADDRESS_ENCODER.encode(&bz),
+            )
+        }
     }
 }
 
-pub(crate) fn to_leb_bytes(id: u64) -> Result<Vec<u8>, Error> {
-    let mut buf = Vec::new();
+/// Writes the id to the start of the buffer in leb128 format, and returns the number of bytes
+/// written. The buffer must fit `leb_len(id)` bytes.
+pub(crate) fn write_leb_bytes(mut id: u64, buf: &mut [u8]) -> usize {
+    // write id to buffer in leb128 format, 7 bits at a time
+    let mut i = 0;
+    loop {
+        let byte = (id & 0x7f) as u8;
+        id >>= 7;
+        if id == 0 {
+            buf[i] = byte;
+            return i + 1;
+        }
+        buf[i] = byte | 0x80;
+        i += 1;
+    }
+}
 
-    // write id to buffer in leb128 format
-    leb128::write::unsigned(&mut buf, id)?;
+/// Returns the length of the id in leb128 format.
+pub(crate) const fn leb_len(mut id: u64) -> usize {
+    let mut len = 1;
+    while id >= 0x80 {
+        id >>= 7;
+        len += 1;
+    }
+    len
+}
 
-    // Create byte vector from buffer
-    Ok(buf)
+/// Returns the id in leb128 format.
+pub fn to_leb_bytes(id: u64) -> Vec<u8> {
+    let mut buf = [0u8; MAX_U64_LEN];
+    let len = write_leb_bytes(id, &mut buf);
+    buf[..len].to_vec()
 }
 
-pub(crate) fn from_leb_bytes(bz: &[u8]) -> Result<u64, Error> {
-    let mut readable = &bz[..];
+/// Reads an id in leb128 format from the whole buffer. Only the minimal encoding of an id is
+/// accepted, and buffers longer than `MAX_U64_LEN` fail with `Error::InvalidLength`.
+pub fn from_leb_bytes(bz: &[u8]) -> Result<u64, Error> {
+    if bz.len() > MAX_U64_LEN {
+        return Err(Error::InvalidLength);
+    }
+    let (id, len) = read_leb_prefix(bz)?;
+    if len != bz.len() {
+        // trailing bytes after the encoded id
+        return Err(Error::InvalidPayload);
+    }
+    Ok(id)
+}
+
+/// Reads an id in leb128 format from the start of the buffer, and returns it with the number of
+/// bytes it was encoded in.
+pub(crate) fn read_leb_prefix(bz: &[u8]) -> Result<(u64, usize), Error> {
+    let mut id = 0u64;
+    let mut shift = 0;
+
+    // read id from buffer in leb128 format, only the minimal encoding of the id is accepted
+    // so each id has a single byte representation
+    for (i, &byte) in bz.iter().enumerate() {
+        if shift == 63 && byte > 1 {
+            // value does not fit in a u64
+            return Err(Error::InvalidPayload);
+        }
+        id |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                // last byte only pads the encoding with zeros
+                return Err(Error::InvalidPayload);
+            }
+            return Ok((id, i + 1));
+        }
+        shift += 7;
+    }
+
+    // ran out of bytes before the end of the encoded id
+    Err(Error::InvalidPayload)
+}
 
-    // write id to buffer in leb128 format
-    Ok(leb128::read::unsigned(&mut readable)?)
+/// Generates a blake2b hash of the ingest with the given digest size
+fn blake2b_variable(ingest: &[u8], size: usize) -> Vec<u8> {
+    Params::new()
+        .hash_length(size)
+        .hash(ingest)
+        .as_bytes()
+        .to_vec()
 }
 
 /// Checksum calculates the 4 byte checksum hash
@@ -298,9 +544,9 @@ pub fn validate_checksum(ingest: &[u8], expect: Vec<u8>) -> bool {
 }
 
 /// Returns an address hash for given data
-fn address_hash(ingest: &[u8]) -> [u8; 20] {
+fn address_hash(ingest: &[u8]) -> [u8; PAYLOAD_HASH_LEN] {
     let digest = blake2b_variable(ingest, PAYLOAD_HASH_LEN);
-    let mut hash = [0u8; 20];
+    let mut hash = [0u8; PAYLOAD_HASH_LEN];
     hash.clone_from_slice(&digest);
     hash
 }
@@ -2,17 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::{MAINNET_PREFIX, TESTNET_PREFIX};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Network of the addresses created without an explicit network, stored as the discriminant
+/// of the `Network`.
+static CURRENT_NETWORK: AtomicU8 = AtomicU8::new(Network::Testnet as u8);
 
 /// Network defines the preconfigured networks to use with address encoding
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
 pub enum Network {
-    Mainnet,
-    Testnet,
+    Mainnet = 0,
+    Testnet = 1,
 }
 
 impl Default for Network {
+    /// Returns the current network, set with `set_current_network`.
     fn default() -> Self {
-        Network::Testnet
+        current_network()
     }
 }
 
@@ -26,3 +33,19 @@ impl Network {
         }
     }
 }
+
+/// Returns the network used for addresses which are created or decoded from bytes, which is
+/// testnet unless set with `set_current_network`.
+pub fn current_network() -> Network {
+    match CURRENT_NETWORK.load(Ordering::Relaxed) {
+        0 => Network::Mainnet,
+        _ => Network::Testnet,
+    }
+}
+
+/// Sets the network used for addresses which are created or decoded from bytes. This should be
+/// set once on startup, before any addresses are created, as addresses on different networks
+/// are not equal.
+pub fn set_current_network(network: Network) {
+    CURRENT_NETWORK.store(network as u8, Ordering::Relaxed);
+}
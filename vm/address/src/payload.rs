@@ -1,12 +1,15 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{from_leb_bytes, to_leb_bytes, Error, Protocol, BLS_PUB_LEN, PAYLOAD_HASH_LEN};
-use std::convert::TryInto;
-use std::fmt;
-use std::hash::{Hash, Hasher};
-use std::ops::Deref;
-use std::u64;
+use super::{
+    from_leb_bytes, leb_len, read_leb_prefix, write_leb_bytes, Error, Protocol, BLS_PUB_LEN,
+    MAX_ADDRESS_BYTES_LEN, MAX_SUBADDRESS_LEN, PAYLOAD_HASH_LEN,
+};
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::u64;
 
 /// Public key struct used as BLS Address data.
 /// This type is only needed to be able to implement traits on it due to limitations on
@@ -46,6 +49,65 @@ impl Deref for BLSPublicKey {
     }
 }
 
+/// Data of a delegated address: the actor ID of the namespace actor which manages the address,
+/// and a subaddress of at most `MAX_SUBADDRESS_LEN` bytes which the namespace actor interprets.
+#[derive(Copy, Clone)]
+pub struct DelegatedAddress {
+    namespace: u64,
+    length: usize,
+    buffer: [u8; MAX_SUBADDRESS_LEN],
+}
+
+impl DelegatedAddress {
+    /// Creates a delegated address payload, failing if the subaddress is too long.
+    pub fn new(namespace: u64, subaddress: &[u8]) -> Result<Self, Error> {
+        if subaddress.len() > MAX_SUBADDRESS_LEN {
+            return Err(Error::InvalidSubaddressLength(subaddress.len()));
+        }
+        let mut buffer = [0u8; MAX_SUBADDRESS_LEN];
+        buffer[..subaddress.len()].copy_from_slice(subaddress);
+        Ok(Self {
+            namespace,
+            length: subaddress.len(),
+            buffer,
+        })
+    }
+
+    /// Returns the actor ID of the namespace actor.
+    pub fn namespace(&self) -> u64 {
+        self.namespace
+    }
+
+    /// Returns the subaddress bytes.
+    pub fn subaddress(&self) -> &[u8] {
+        &self.buffer[..self.length]
+    }
+}
+
+impl Hash for DelegatedAddress {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.namespace.hash(state);
+        self.subaddress().hash(state);
+    }
+}
+
+impl Eq for DelegatedAddress {}
+impl PartialEq for DelegatedAddress {
+    fn eq(&self, other: &Self) -> bool {
+        self.namespace == other.namespace && self.subaddress() == other.subaddress()
+    }
+}
+
+impl fmt::Debug for DelegatedAddress {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("DelegatedAddress")
+            .field("namespace", &self.namespace)
+            .field("subaddress", &self.subaddress())
+            .finish()
+    }
+}
+
 /// Payload is the data of the Address. Variants are the supported Address protocols.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Payload {
@@ -57,61 +119,91 @@ pub enum Payload {
     Actor([u8; PAYLOAD_HASH_LEN]),
     /// BLS key address, full 48 byte public key
     BLS(BLSPublicKey),
+    /// Delegated address, a namespace actor ID and a subaddress of up to 54 bytes
+    Delegated(DelegatedAddress),
 }
 
 impl Payload {
     /// Returns encoded bytes of Address without the protocol byte.
     pub fn to_raw_bytes(&self) -> Vec<u8> {
-        use Payload::*;
-        match self {
-            ID(i) => to_leb_bytes(*i).unwrap(),
-            Secp256k1(arr) => arr.to_vec(),
-            Actor(arr) => arr.to_vec(),
-            BLS(arr) => arr.to_vec(),
-        }
+        let mut buf = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let len = self.encode_into(&mut buf);
+        buf[1..len].to_vec()
     }
 
     /// Returns encoded bytes of Address including the protocol byte.
     pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let len = self.encode_into(&mut buf);
+        buf[..len].to_vec()
+    }
+
+    /// Returns the length of the encoded bytes of the payload, without the protocol byte.
+    pub const fn raw_len(&self) -> usize {
+        match self {
+            Payload::ID(i) => leb_len(*i),
+            Payload::Secp256k1(_) | Payload::Actor(_) => PAYLOAD_HASH_LEN,
+            Payload::BLS(_) => BLS_PUB_LEN,
+            Payload::Delegated(addr) => leb_len(addr.namespace) + addr.length,
+        }
+    }
+
+    /// Writes the encoded bytes including the protocol byte to the start of the buffer, and
+    /// returns the number of bytes written.
+    pub(crate) fn encode_into(&self, buf: &mut [u8; MAX_ADDRESS_BYTES_LEN]) -> usize {
         use Payload::*;
-        let mut bz = match self {
-            ID(i) => to_leb_bytes(*i).unwrap(),
-            Secp256k1(arr) => arr.to_vec(),
-            Actor(arr) => arr.to_vec(),
-            BLS(arr) => arr.to_vec(),
+        buf[0] = Protocol::from(self) as u8;
+        let raw: &[u8] = match self {
+            ID(i) => return 1 + write_leb_bytes(*i, &mut buf[1..]),
+            Secp256k1(arr) => arr,
+            Actor(arr) => arr,
+            BLS(arr) => &arr.0,
+            Delegated(addr) => {
+                let len = 1 + write_leb_bytes(addr.namespace, &mut buf[1..]);
+                buf[len..len + addr.length].copy_from_slice(addr.subaddress());
+                return len + addr.length;
+            }
         };
-
-        bz.insert(0, Protocol::from(self) as u8);
-        bz
+        buf[1..=raw.len()].copy_from_slice(raw);
+        1 + raw.len()
     }
 
     /// Generates payload from raw bytes and protocol.
     pub fn new(protocol: Protocol, payload: &[u8]) -> Result<Self, Error> {
+        match protocol.payload_len() {
+            Some(len) if len != payload.len() && protocol == Protocol::BLS => {
+                return Err(Error::InvalidBLSLength(payload.len()));
+            }
+            Some(len) if len != payload.len() => {
+                return Err(Error::InvalidPayloadLength(payload.len()));
+            }
+            _ => (),
+        }
         let payload = match protocol {
             Protocol::ID => Self::ID(from_leb_bytes(payload)?),
-            Protocol::Secp256k1 => Self::Secp256k1(
-                payload
-                    .try_into()
-                    .map_err(|_| Error::InvalidPayloadLength(payload.len()))?,
-            ),
-            Protocol::Actor => Self::Actor(
-                payload
-                    .try_into()
-                    .map_err(|_| Error::InvalidPayloadLength(payload.len()))?,
-            ),
+            Protocol::Secp256k1 => Self::Secp256k1(hash_payload(payload)),
+            Protocol::Actor => Self::Actor(hash_payload(payload)),
             Protocol::BLS => {
-                if payload.len() != BLS_PUB_LEN {
-                    return Err(Error::InvalidBLSLength(payload.len()));
-                }
                 let mut pk = [0u8; BLS_PUB_LEN];
                 pk.copy_from_slice(payload);
                 Self::BLS(pk.into())
             }
+            Protocol::Delegated => {
+                let (namespace, len) = read_leb_prefix(payload)?;
+                Self::Delegated(DelegatedAddress::new(namespace, &payload[len..])?)
+            }
         };
         Ok(payload)
     }
 }
 
+/// Copies a payload which has been checked to be `PAYLOAD_HASH_LEN` bytes.
+fn hash_payload(payload: &[u8]) -> [u8; PAYLOAD_HASH_LEN] {
+    let mut hash = [0u8; PAYLOAD_HASH_LEN];
+    hash.copy_from_slice(payload);
+    hash
+}
+
 impl From<Payload> for Protocol {
     fn from(pl: Payload) -> Self {
         match pl {
@@ -119,6 +211,7 @@ impl From<Payload> for Protocol {
             Payload::Secp256k1(_) => Self::Secp256k1,
             Payload::Actor(_) => Self::Actor,
             Payload::BLS(_) => Self::BLS,
+            Payload::Delegated(_) => Self::Delegated,
         }
     }
 }
@@ -130,6 +223,7 @@ impl From<&Payload> for Protocol {
             Payload::Secp256k1(_) => Self::Secp256k1,
             Payload::Actor(_) => Self::Actor,
             Payload::BLS(_) => Self::BLS,
+            Payload::Delegated(_) => Self::Delegated,
         }
     }
 }
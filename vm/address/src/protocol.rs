@@ -1,11 +1,12 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::{BLS_PUB_LEN, PAYLOAD_HASH_LEN};
+use core::fmt;
+use core::hash::Hash;
+use core::u64;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
-use std::fmt;
-use std::hash::Hash;
-use std::u64;
 
 /// Protocol defines the addressing protocol used to derive data to an address
 #[derive(PartialEq, Eq, Copy, Clone, FromPrimitive, Debug, Hash)]
@@ -19,6 +20,8 @@ pub enum Protocol {
     Actor = 2,
     /// BLS key addressing
     BLS = 3,
+    /// Delegated addressing, with a subaddress interpreted by a namespace actor
+    Delegated = 4,
 }
 
 impl Protocol {
@@ -26,6 +29,16 @@ impl Protocol {
     pub(super) fn from_byte(b: u8) -> Option<Protocol> {
         FromPrimitive::from_u8(b)
     }
+
+    /// Returns the length of the payload of addresses of the protocol, or `None` for
+    /// protocols with variable length payloads.
+    pub const fn payload_len(&self) -> Option<usize> {
+        match self {
+            Protocol::Secp256k1 | Protocol::Actor => Some(PAYLOAD_HASH_LEN),
+            Protocol::BLS => Some(BLS_PUB_LEN),
+            Protocol::ID | Protocol::Delegated => None,
+        }
+    }
 }
 
 /// allows conversion of Protocol value to string
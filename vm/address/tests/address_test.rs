@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use data_encoding::{DecodeError, DecodeKind};
+use encoding::serde_bytes::ByteBuf;
+use encoding::tuple::*;
 use encoding::{from_slice, Cbor};
 use forest_address::{
-    checksum, validate_checksum, Address, Error, Network, Protocol, BLS_PUB_LEN, PAYLOAD_HASH_LEN,
-    SECP_PUB_LEN,
+    checksum, from_leb_bytes, parse_addresses, parse_many, to_leb_bytes, validate_checksum,
+    Address, DisplayAddress, Error, Network, ParseAddressesError, Payload, Protocol, BLS_PUB_LEN,
+    MAX_ADDRESS_BYTES_LEN, MAX_SUBADDRESS_LEN, MAX_U64_LEN, PAYLOAD_HASH_LEN, SECP_PUB_LEN,
 };
+use std::cmp::Ordering;
 use std::str::FromStr;
 
 #[test]
@@ -290,7 +294,7 @@ fn invalid_string_addresses() {
             expected: Error::UnknownNetwork,
         },
         StringAddrVec {
-            input: "t4gfvuyh7v2sx3patm5k23wdzmhyhtmqctasbr23y",
+            input: "t5gfvuyh7v2sx3patm5k23wdzmhyhtmqctasbr23y",
             expected: Error::UnknownProtocol,
         },
         StringAddrVec {
@@ -362,7 +366,7 @@ fn invalid_byte_addresses() {
     let test_vectors = &[
         // Unknown Protocol
         StringAddrVec {
-            input: vec![4, 4, 4],
+            input: vec![5, 4, 4],
             expected: Error::UnknownProtocol,
         },
         // ID protocol
@@ -408,6 +412,85 @@ fn invalid_byte_addresses() {
     }
 }
 
+#[test]
+fn non_canonical_id_bytes() {
+    let test_vectors: &[&[u8]] = &[
+        // Padded with zero continuation bytes
+        &[0, 0x80, 0],
+        &[0, 0x81, 0],
+        &[0, 0x81, 0x80, 0],
+        // Trailing bytes after the id
+        &[0, 1, 0],
+        &[0, 0x81, 1, 2],
+        // Unterminated encoding
+        &[0, 0x81],
+        // Overflows a u64
+        &[0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 2],
+    ];
+    for bz in test_vectors {
+        assert_eq!(
+            Address::from_bytes(bz),
+            Err(Error::InvalidPayload),
+            "{:?}",
+            bz
+        );
+    }
+
+    // Longer than the encoding of any u64
+    let mut too_long = vec![0];
+    too_long.extend(&[0x80; 10]);
+    too_long.push(0);
+    assert_eq!(Address::from_bytes(&too_long), Err(Error::InvalidLength));
+
+    // Minimal encodings round trip
+    for &id in &[0, 1, 127, 128, 16383, 16384, std::u64::MAX] {
+        let addr = Address::new_id(id);
+        let bz = addr.to_bytes();
+        assert!(bz.len() - 1 <= 10);
+        assert_eq!(Address::from_bytes(&bz).unwrap(), addr);
+    }
+}
+
+#[test]
+fn leb_id_bytes() {
+    for &id in &[0, 1, 127, 128, 16383, 16384, std::u64::MAX] {
+        let bz = to_leb_bytes(id);
+        assert!(bz.len() <= MAX_U64_LEN);
+        assert_eq!(bz, Address::new_id(id).payload_bytes());
+        assert_eq!(from_leb_bytes(&bz), Ok(id));
+        assert_eq!(Address::from_id_bytes(&bz), Ok(Address::new_id(id)));
+    }
+    assert_eq!(to_leb_bytes(300), vec![0xac, 0x02]);
+
+    assert_eq!(from_leb_bytes(&[]), Err(Error::InvalidPayload));
+    assert_eq!(from_leb_bytes(&[0x81, 0]), Err(Error::InvalidPayload));
+    assert_eq!(
+        from_leb_bytes(&[0x80; MAX_U64_LEN + 1]),
+        Err(Error::InvalidLength)
+    );
+    assert_eq!(Address::from_id_bytes(&[0x81]), Err(Error::InvalidPayload));
+}
+
+#[test]
+fn protocol_payload_len() {
+    assert_eq!(Protocol::ID.payload_len(), None);
+    assert_eq!(Protocol::Secp256k1.payload_len(), Some(PAYLOAD_HASH_LEN));
+    assert_eq!(Protocol::Actor.payload_len(), Some(PAYLOAD_HASH_LEN));
+    assert_eq!(Protocol::BLS.payload_len(), Some(BLS_PUB_LEN));
+    assert_eq!(Protocol::Delegated.payload_len(), None);
+
+    for &protocol in &[Protocol::Secp256k1, Protocol::Actor, Protocol::BLS] {
+        let len = protocol.payload_len().unwrap();
+        assert!(Payload::new(protocol, &vec![0; len]).is_ok());
+        assert!(Payload::new(protocol, &vec![0; len - 1]).is_err());
+        assert!(Payload::new(protocol, &vec![0; len + 1]).is_err());
+    }
+
+    let addr = Address::new_delegated(std::u64::MAX, &[1; MAX_SUBADDRESS_LEN]).unwrap();
+    assert_eq!(addr.to_bytes().len(), Address::MAX_BYTES);
+    assert_eq!(addr.to_string().len(), Address::MAX_STRING_LEN);
+}
+
 #[test]
 fn cbor_encoding() {
     struct StringAddrVec<'a> {
@@ -559,3 +642,266 @@ fn set_network() {
     assert_eq!(addr.network(), Network::Mainnet);
     assert_eq!(addr.to_string(), "f01");
 }
+
+#[test]
+fn write_bytes() {
+    let addrs = vec![
+        Address::new_id(0),
+        Address::new_id(127),
+        Address::new_id(128),
+        Address::new_id(u64::MAX),
+        Address::new_secp256k1(&[4; SECP_PUB_LEN]).unwrap(),
+        Address::new_actor(b"actor"),
+        Address::new_bls(&[1; BLS_PUB_LEN]).unwrap(),
+        Address::new_delegated(u64::MAX, &[2; MAX_SUBADDRESS_LEN]).unwrap(),
+    ];
+    for addr in addrs {
+        let bz = addr.to_bytes();
+        assert_eq!(addr.payload_len() + 1, bz.len());
+        assert!(bz.len() <= MAX_ADDRESS_BYTES_LEN);
+        assert_eq!(addr.payload_bytes(), &bz[1..]);
+
+        let mut written = Vec::new();
+        addr.write_bytes(&mut written).unwrap();
+        assert_eq!(written, bz);
+
+        // Serialization doesn't go through the allocated bytes
+        assert_eq!(
+            from_slice::<Address>(&addr.marshal_cbor().unwrap()).unwrap(),
+            addr
+        );
+    }
+
+    // Writing fails if the writer doesn't fit the address
+    let mut buf = [0u8; 2];
+    assert!(Address::new_id(u64::MAX)
+        .write_bytes(&mut &mut buf[..])
+        .is_err());
+}
+
+#[test]
+fn address_id() {
+    assert_eq!(Address::new_id(0).id(), Ok(0));
+    assert_eq!(Address::new_id(u64::MAX).id(), Ok(u64::MAX));
+    assert_eq!(Address::new_actor(b"actor").id(), Err(Error::NonIDAddress));
+    assert_eq!(
+        Address::new_bls(&[1; BLS_PUB_LEN]).unwrap().id(),
+        Err(Error::NonIDAddress)
+    );
+}
+
+#[test]
+fn network_override() {
+    let addr = Address::new_actor(b"actor");
+    let testnet = addr.to_string_with_network(Network::Testnet);
+    let mainnet = addr.to_string_with_network(Network::Mainnet);
+    assert!(testnet.starts_with('t'));
+    assert_eq!(&mainnet[1..], &testnet[1..]);
+    assert_eq!(
+        mainnet,
+        addr.clone().set_network(Network::Mainnet).to_string()
+    );
+    // The address itself is not modified
+    assert_eq!(addr.to_string(), testnet);
+
+    let parsed = Address::from_str_with_network(&testnet, Network::Mainnet).unwrap();
+    assert_eq!(parsed.network(), Network::Mainnet);
+    assert_eq!(parsed.to_string(), mainnet);
+    let parsed = Address::from_str_with_network("f0100", Network::Testnet).unwrap();
+    assert_eq!(parsed, Address::new_id(100));
+    assert_eq!(
+        Address::from_str_with_network("x0100", Network::Testnet),
+        Err(Error::UnknownNetwork)
+    );
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, PartialEq)]
+struct Signers {
+    #[serde(with = "forest_address::cbor::vec")]
+    signers: Vec<Address>,
+}
+
+#[test]
+fn cbor_address_vec() {
+    let signers = Signers {
+        signers: vec![
+            Address::new_id(1),
+            Address::new_actor(b"actor"),
+            Address::new_bls(&[1; BLS_PUB_LEN]).unwrap(),
+        ],
+    };
+    let bz = encoding::to_vec(&signers).unwrap();
+    // Encoded the same as the default serialization of the addresses
+    assert_eq!(bz, encoding::to_vec(&(&signers.signers,)).unwrap());
+    assert_eq!(from_slice::<Signers>(&bz).unwrap(), signers);
+
+    let invalid = (vec![
+        ByteBuf::from(Address::new_id(1).to_bytes()),
+        ByteBuf::from(vec![9, 9]),
+    ],);
+    let err = from_slice::<Signers>(&encoding::to_vec(&invalid).unwrap()).unwrap_err();
+    assert!(err.to_string().contains("invalid address at index 1"));
+}
+
+#[test]
+fn parse_address_list() {
+    assert_eq!(
+        parse_addresses(&["t01".to_owned(), "f02".to_owned()]),
+        Ok(vec![
+            Address::new_id(1),
+            *Address::new_id(2).set_network(Network::Mainnet)
+        ])
+    );
+    assert_eq!(parse_addresses::<String>(&[]), Ok(vec![]));
+
+    let err = parse_addresses(&["x01", "t01", "t9"]).unwrap_err();
+    assert_eq!(
+        err,
+        ParseAddressesError {
+            errors: vec![(0, Error::UnknownNetwork), (2, Error::InvalidLength)]
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse 2 addresses: address 0: Unknown address network, \
+         address 2: Invalid address length"
+    );
+}
+
+#[test]
+fn parse_many_addresses() {
+    assert_eq!(
+        parse_many(&["t01", "f02"]),
+        Ok(vec![
+            Address::new_id(1),
+            *Address::new_id(2).set_network(Network::Mainnet)
+        ])
+    );
+    assert_eq!(parse_many::<&str>(&[]), Ok(vec![]));
+
+    // Only the first failure is reported
+    assert_eq!(
+        parse_many(&["t01", "t9", "x01"]),
+        Err((1, Error::InvalidLength))
+    );
+}
+
+#[test]
+fn display_address() {
+    let addrs = vec![
+        Address::new_id(1),
+        Address::new_secp256k1(&[4; SECP_PUB_LEN]).unwrap(),
+        *Address::new_actor(b"actor").set_network(Network::Mainnet),
+        Address::new_bls(&[1; BLS_PUB_LEN]).unwrap(),
+    ];
+    for addr in addrs {
+        let display = DisplayAddress::new(addr);
+        assert_eq!(display.as_str(), addr.to_string());
+        assert_eq!(display.to_string(), addr.to_string());
+        assert_eq!(display.address(), &addr);
+        assert_eq!(display.protocol(), addr.protocol());
+        assert_eq!(Address::from(display), addr);
+    }
+}
+
+#[test]
+fn delegated_address() {
+    let addr = Address::new_delegated(10, &[0xab; 20]).unwrap();
+    assert_eq!(addr.protocol(), Protocol::Delegated);
+    match addr.payload() {
+        Payload::Delegated(delegated) => {
+            assert_eq!(delegated.namespace(), 10);
+            assert_eq!(delegated.subaddress(), &[0xab; 20]);
+        }
+        _ => panic!("payload should be delegated"),
+    }
+
+    // Bytes are the protocol byte, the leb128 namespace and the subaddress
+    let bz = addr.to_bytes();
+    assert_eq!(&bz[..2], &[4, 10]);
+    assert_eq!(&bz[2..], &[0xab; 20]);
+    assert_eq!(Address::from_bytes(&bz).unwrap(), addr);
+    assert_eq!(
+        from_slice::<Address>(&addr.marshal_cbor().unwrap()).unwrap(),
+        addr
+    );
+
+    // Strings have the decimal namespace before the encoded subaddress and checksum
+    let encoded = addr.to_string();
+    assert!(encoded.starts_with("t410f"));
+    assert_eq!(Address::from_str(&encoded).unwrap(), addr);
+
+    let longest = Address::new_delegated(u64::MAX, &[0xff; MAX_SUBADDRESS_LEN]).unwrap();
+    assert_eq!(Address::from_str(&longest.to_string()).unwrap(), longest);
+    let empty = Address::new_delegated(0, &[]).unwrap();
+    assert_eq!(Address::from_str(&empty.to_string()).unwrap(), empty);
+
+    // Subaddresses are compared by their used bytes
+    assert_ne!(empty, Address::new_delegated(0, &[0]).unwrap());
+
+    assert_eq!(
+        Address::new_delegated(10, &[0; MAX_SUBADDRESS_LEN + 1]),
+        Err(Error::InvalidSubaddressLength(MAX_SUBADDRESS_LEN + 1))
+    );
+    let mut too_long = vec![4, 10];
+    too_long.extend(&[0; MAX_SUBADDRESS_LEN + 1]);
+    assert_eq!(
+        Address::from_bytes(&too_long),
+        Err(Error::InvalidSubaddressLength(MAX_SUBADDRESS_LEN + 1))
+    );
+    // Namespace must be terminated
+    assert_eq!(Address::from_bytes(&[4, 0x80]), Err(Error::InvalidPayload));
+
+    // Tampering with the namespace invalidates the checksum
+    let tampered = encoded.replacen("t410", "t411", 1);
+    assert_eq!(Address::from_str(&tampered), Err(Error::InvalidChecksum));
+    // Namespace must be separated from the subaddress
+    assert_eq!(Address::from_str("t410"), Err(Error::InvalidPayload));
+    assert_eq!(Address::from_str("t4f"), Err(Error::InvalidPayload));
+}
+
+#[test]
+fn address_order() {
+    let mut addrs = vec![
+        Address::new_bls(&[1; BLS_PUB_LEN]).unwrap(),
+        Address::new_actor(b"actor"),
+        Address::new_id(256),
+        Address::new_delegated(10, &[1]).unwrap(),
+        Address::new_id(255),
+        Address::new_secp256k1(&[4; SECP_PUB_LEN]).unwrap(),
+        Address::new_id(1),
+    ];
+    addrs.sort();
+
+    // Sorted by protocol and then by the encoded payload, not by the string encoding
+    let protocols: Vec<_> = addrs.iter().map(Address::protocol).collect();
+    assert_eq!(
+        protocols,
+        vec![
+            Protocol::ID,
+            Protocol::ID,
+            Protocol::ID,
+            Protocol::Secp256k1,
+            Protocol::Actor,
+            Protocol::BLS,
+            Protocol::Delegated,
+        ]
+    );
+    // IDs are ordered by their leb128 encoding rather than numerically
+    assert_eq!(
+        &addrs[..3],
+        &[
+            Address::new_id(1),
+            Address::new_id(256),
+            Address::new_id(255)
+        ]
+    );
+    for pair in addrs.windows(2) {
+        assert!(pair[0].to_bytes() < pair[1].to_bytes());
+    }
+
+    // Ordering is consistent with equality across networks
+    let mainnet = *Address::new_id(1).set_network(Network::Mainnet);
+    assert!(mainnet < Address::new_id(1));
+    assert_eq!(Address::new_id(1).cmp(&Address::new_id(1)), Ordering::Equal);
+}
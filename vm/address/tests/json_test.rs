@@ -0,0 +1,61 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+#![cfg(feature = "json")]
+
+use forest_address::{
+    json::{self, AddressJson, AddressJsonRef},
+    Address,
+};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn address_json_round_trip() {
+    let addr = Address::new_id(1234);
+    let json = serde_json::to_string(&AddressJsonRef(&addr)).unwrap();
+    assert_eq!(json, "\"t01234\"");
+    let AddressJson(decoded) = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, addr);
+
+    let addr = Address::new_actor(b"actor");
+    let json = serde_json::to_string(&AddressJson(addr)).unwrap();
+    assert_eq!(json, format!("\"{}\"", addr));
+    assert_eq!(
+        Address::from(serde_json::from_str::<AddressJson>(&json).unwrap()),
+        addr
+    );
+
+    assert!(serde_json::from_str::<AddressJson>("\"x01234\"").is_err());
+    assert!(serde_json::from_str::<AddressJson>("1234").is_err());
+}
+
+#[test]
+fn optional_address_json() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Fields {
+        #[serde(with = "json")]
+        address: Address,
+        #[serde(default, with = "json::opt")]
+        optional: Option<Address>,
+    }
+
+    let fields = Fields {
+        address: Address::new_id(1),
+        optional: Some(Address::new_id(2)),
+    };
+    let json = serde_json::to_string(&fields).unwrap();
+    assert_eq!(json, r#"{"address":"t01","optional":"t02"}"#);
+    assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+
+    let fields = Fields {
+        address: Address::new_id(1),
+        optional: None,
+    };
+    let json = serde_json::to_string(&fields).unwrap();
+    assert_eq!(json, r#"{"address":"t01","optional":null}"#);
+    assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+    assert_eq!(
+        serde_json::from_str::<Fields>(r#"{"address":"t01"}"#).unwrap(),
+        fields
+    );
+}
@@ -0,0 +1,30 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_address::{current_network, set_current_network, Address, Network};
+
+// The current network is global, so it is only changed by this test in its own binary
+#[test]
+fn current_network_is_used_for_new_addresses() {
+    assert_eq!(current_network(), Network::Testnet);
+    assert_eq!(Address::new_id(1).to_string(), "t01");
+
+    set_current_network(Network::Mainnet);
+    assert_eq!(current_network(), Network::Mainnet);
+    let addr = Address::new_id(1);
+    assert_eq!(addr.network(), Network::Mainnet);
+    assert_eq!(addr.to_string(), "f01");
+    let addr = Address::from_bytes(&[0, 1]).unwrap();
+    assert_eq!(addr.to_string(), "f01");
+    assert_eq!(Address::new_actor(b"actor").network(), Network::Mainnet);
+
+    // Parsing a string keeps the network of its prefix
+    let addr: Address = "t01".parse().unwrap();
+    assert_eq!(addr.network(), Network::Testnet);
+
+    set_current_network(Network::Testnet);
+    assert_eq!(Address::new_id(1).to_string(), "t01");
+    let addr: Address = "f0100".parse().unwrap();
+    assert_eq!(addr.network(), Network::Mainnet);
+    assert_eq!(addr.to_string(), "f0100");
+}
@@ -0,0 +1,387 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Applies builtin actor methods with representative params through the interpreter, and
+//! prints the gas used by each method with its gas charges broken down by operation. Comparing
+//! the output before and after changing the price list shows the effect of the change on actor
+//! workloads:
+//!
+//! `cargo bench -p interpreter --bench actor_gas`
+
+#[allow(dead_code)]
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use actor::{
+    account, init,
+    market::{
+        self,
+        deal_client::{self, DealTerms},
+        ClientDealProposal, OnMinerSectorsTerminateParams, PublishStorageDealsParams,
+        PublishStorageDealsReturn, SectorDeals, VerifyDealsForActivationParams,
+        VerifyDealsOnSectorProveCommitParams, WithdrawBalanceParams,
+    },
+    miner::{self, ChangeMultiaddrsParams, ChangePeerIDParams, Multiaddr},
+    paych::{self, SignedVoucher, UpdateChannelStateParams},
+    CRON_ACTOR_ADDR, INIT_ACTOR_ADDR, PAYCH_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR,
+};
+use cid::Cid;
+use common::*;
+use crypto::Signature;
+use fil_types::{PaddedPieceSize, PieceInfo};
+use interpreter::{ApplyRet, GasCharge};
+use std::collections::BTreeMap;
+use vm::{ExitCode, Serialized, TokenAmount, METHOD_SEND};
+
+const INITIAL_BALANCE: u64 = 1_000_000_000_000;
+
+/// Value sent with messages which don't transfer funds, as the interpreter only applies
+/// messages with a value.
+fn dust() -> TokenAmount {
+    TokenAmount::from(1u8)
+}
+
+/// Gas used by an applied method.
+struct MethodGas {
+    method: &'static str,
+    exit_code: ExitCode,
+    /// Gas used in the receipt, which is zero for implicit messages.
+    gas_used: i64,
+    /// Number of charges and total gas charged, by operation.
+    charges: BTreeMap<&'static str, (usize, i64)>,
+}
+
+#[derive(Default)]
+struct Report {
+    methods: Vec<MethodGas>,
+}
+
+impl Report {
+    /// Records the gas of an applied method and returns the result of applying it.
+    fn record(
+        &mut self,
+        method: &'static str,
+        (ret, trace): (ApplyRet, Vec<GasCharge>),
+    ) -> ApplyRet {
+        let mut charges = BTreeMap::new();
+        for charge in trace {
+            let entry = charges.entry(charge.name).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += charge.gas;
+        }
+        self.methods.push(MethodGas {
+            method,
+            exit_code: ret.msg_receipt.exit_code,
+            gas_used: ret.msg_receipt.gas_used,
+            charges,
+        });
+        ret
+    }
+
+    fn print(&self) {
+        println!(
+            "{:<40} {:<24} {:>12} {:>12}",
+            "method", "exit code", "gas used", "traced"
+        );
+        for m in &self.methods {
+            let exit_code = format!("{:?}", m.exit_code);
+            let traced: i64 = m.charges.values().map(|&(_, gas)| gas).sum();
+            println!(
+                "{:<40} {:<24} {:>12} {:>12}",
+                m.method, exit_code, m.gas_used, traced
+            );
+            for (name, (count, gas)) in &m.charges {
+                let count = format!("x{}", count);
+                println!("    {:<36} {:>24} {:>12}", name, count, gas);
+            }
+        }
+    }
+}
+
+fn account_methods(report: &mut Report) {
+    let mut h = Harness::new();
+    let from = h.add_account(1001, INITIAL_BALANCE.into());
+    let to = h.add_account(1002, INITIAL_BALANCE.into());
+
+    report.record(
+        "account::Send",
+        h.send_traced(
+            from,
+            to,
+            METHOD_SEND,
+            Serialized::default(),
+            TokenAmount::from(100u8),
+        ),
+    );
+    report.record(
+        "account::PubkeyAddress",
+        h.send_traced(
+            from,
+            to,
+            account::Method::PubkeyAddress.into(),
+            Serialized::default(),
+            dust(),
+        ),
+    );
+}
+
+fn paych_methods(report: &mut Report) {
+    let mut h = Harness::new();
+    let client = h.add_account(1001, INITIAL_BALANCE.into());
+    let payee = h.add_account(1002, INITIAL_BALANCE.into());
+
+    let params = Serialized::serialize(init::ExecParams {
+        code_cid: PAYCH_ACTOR_CODE_ID.clone(),
+        constructor_params: Serialized::serialize(paych::ConstructorParams {
+            from: client,
+            to: payee,
+        })
+        .unwrap(),
+    })
+    .unwrap();
+    let ret = report.record(
+        "init::Exec (paych)",
+        h.send_traced(
+            client,
+            *INIT_ACTOR_ADDR,
+            init::Method::Exec.into(),
+            params,
+            TokenAmount::from(100u8),
+        ),
+    );
+    let ch = ret
+        .msg_receipt
+        .return_data
+        .deserialize::<init::ExecReturn>()
+        .unwrap()
+        .id_address;
+
+    for &(lane, nonce, amount) in &[(0, 1, 10u8), (1, 1, 15), (0, 2, 25)] {
+        let params = Serialized::serialize(UpdateChannelStateParams {
+            sv: SignedVoucher {
+                lane,
+                nonce,
+                amount: TokenAmount::from(amount),
+                signature: Some(Signature::new_secp256k1(vec![0])),
+                ..Default::default()
+            },
+            secret: Vec::new(),
+            proof: Vec::new(),
+        })
+        .unwrap();
+        report.record(
+            "paych::UpdateChannelState",
+            h.send_traced(
+                payee,
+                ch,
+                paych::Method::UpdateChannelState.into(),
+                params,
+                dust(),
+            ),
+        );
+    }
+
+    h.epoch = 10;
+    let ret = report.record(
+        "paych::Settle",
+        h.send_traced(
+            client,
+            ch,
+            paych::Method::Settle.into(),
+            Serialized::default(),
+            dust(),
+        ),
+    );
+    if ret.msg_receipt.exit_code.is_success() {
+        h.epoch = h.state::<paych::State>(&ch).settling_at;
+    }
+    report.record(
+        "paych::Collect",
+        h.send_traced(
+            client,
+            ch,
+            paych::Method::Collect.into(),
+            Serialized::default(),
+            dust(),
+        ),
+    );
+}
+
+fn market_methods(report: &mut Report) {
+    let mut h = Harness::new();
+    let client = h.add_account(1001, INITIAL_BALANCE.into());
+    let owner = h.add_account(1003, INITIAL_BALANCE.into());
+    let worker = h.add_account(1004, INITIAL_BALANCE.into());
+    let provider = h.add_miner(1005, owner, worker);
+
+    for &(from, escrow) in &[(client, client), (worker, provider)] {
+        report.record(
+            "market::AddBalance",
+            h.send_traced(
+                from,
+                *STORAGE_MARKET_ACTOR_ADDR,
+                market::Method::AddBalance.into(),
+                Serialized::serialize(escrow).unwrap(),
+                TokenAmount::from(1000u16),
+            ),
+        );
+    }
+
+    let deals = (0..4u64)
+        .map(|i| {
+            let mut proposal = deal_client::new_proposal(
+                &PieceInfo {
+                    size: PaddedPieceSize(2048),
+                    cid: Cid::default(),
+                },
+                DealTerms {
+                    client,
+                    provider,
+                    start_epoch: 10 + i as i64,
+                    end_epoch: 200,
+                    verified_deal: false,
+                },
+            )
+            .unwrap();
+            proposal.provider_collateral = TokenAmount::from(50u8);
+            ClientDealProposal {
+                proposal,
+                client_signature: Signature::new_secp256k1(vec![0]),
+            }
+        })
+        .collect();
+    h.epoch = 1;
+    let ret = report.record(
+        "market::PublishStorageDeals (4 deals)",
+        h.send_traced(
+            worker,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::PublishStorageDeals.into(),
+            Serialized::serialize(PublishStorageDealsParams { deals }).unwrap(),
+            dust(),
+        ),
+    );
+    let deal_ids = ret
+        .msg_receipt
+        .return_data
+        .deserialize::<PublishStorageDealsReturn>()
+        .map(|ret| ret.ids)
+        .unwrap_or_default();
+
+    h.epoch = 2;
+    report.record(
+        "market::VerifyDealsForActivation",
+        h.apply_implicit_traced(
+            provider,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::VerifyDealsForActivation.into(),
+            Serialized::serialize(VerifyDealsForActivationParams {
+                sectors: vec![SectorDeals {
+                    sector_expiry: 300,
+                    deal_ids: deal_ids.clone(),
+                }],
+            })
+            .unwrap(),
+        ),
+    );
+    report.record(
+        "market::VerifyDealsOnSectorProveCommit",
+        h.apply_implicit_traced(
+            provider,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::VerifyDealsOnSectorProveCommit.into(),
+            Serialized::serialize(VerifyDealsOnSectorProveCommitParams {
+                deal_ids: deal_ids.clone(),
+                sector_expiry: 300,
+            })
+            .unwrap(),
+        ),
+    );
+
+    h.epoch = 20;
+    report.record(
+        "market::CronTick",
+        h.apply_implicit_traced(
+            *CRON_ACTOR_ADDR,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::CronTick.into(),
+            Serialized::default(),
+        ),
+    );
+    report.record(
+        "market::OnMinerSectorsTerminate",
+        h.apply_implicit_traced(
+            provider,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::OnMinerSectorsTerminate.into(),
+            Serialized::serialize(OnMinerSectorsTerminateParams { deal_ids }).unwrap(),
+        ),
+    );
+    report.record(
+        "market::WithdrawBalance",
+        h.send_traced(
+            client,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::WithdrawBalance.into(),
+            Serialized::serialize(WithdrawBalanceParams {
+                provider_or_client: client,
+                amount: TokenAmount::from(100u8),
+            })
+            .unwrap(),
+            dust(),
+        ),
+    );
+}
+
+fn miner_methods(report: &mut Report) {
+    let mut h = Harness::new();
+    let owner = h.add_account(1003, INITIAL_BALANCE.into());
+    let worker = h.add_account(1004, INITIAL_BALANCE.into());
+    let miner = h.add_miner(1005, owner, worker);
+
+    report.record(
+        "miner::ControlAddresses",
+        h.send_traced(
+            owner,
+            miner,
+            miner::Method::ControlAddresses.into(),
+            Serialized::default(),
+            dust(),
+        ),
+    );
+    report.record(
+        "miner::ChangePeerID",
+        h.send_traced(
+            worker,
+            miner,
+            miner::Method::ChangePeerID.into(),
+            Serialized::serialize(ChangePeerIDParams {
+                new_id: vec![7; 38],
+            })
+            .unwrap(),
+            dust(),
+        ),
+    );
+    report.record(
+        "miner::ChangeMultiaddrs",
+        h.send_traced(
+            worker,
+            miner,
+            miner::Method::ChangeMultiaddrs.into(),
+            Serialized::serialize(ChangeMultiaddrsParams {
+                new_multi_addrs: vec![Multiaddr(vec![4, 127, 0, 0, 1, 6, 0x04, 0xd2])],
+            })
+            .unwrap(),
+            dust(),
+        ),
+    );
+}
+
+fn main() {
+    let mut report = Report::default();
+    account_methods(&mut report);
+    paych_methods(&mut report);
+    market_methods(&mut report);
+    miner_methods(&mut report);
+    report.print();
+}
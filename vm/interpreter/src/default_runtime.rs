@@ -3,26 +3,26 @@
 
 use super::gas_block_store::GasBlockStore;
 use super::gas_syscalls::GasSyscalls;
-use super::gas_tracker::{price_list_by_epoch, GasTracker, PriceList};
+use super::gas_tracker::{price_list_by_epoch, GasCharge, GasTracker, PriceList};
 use super::ChainRand;
 use actor::*;
 use address::{Address, Protocol};
 use byteorder::{BigEndian, WriteBytesExt};
 use cid::{multihash::Blake2b256, Cid};
 use clock::ChainEpoch;
-use crypto::DomainSeparationTag;
-use fil_types::NetworkParams;
+use crypto::{DomainSeparationTag, Signature, SignatureError};
+use fil_types::{NetworkParams, NetworkVersion};
 use forest_encoding::Cbor;
-use forest_encoding::{error::Error as EncodingError, to_vec};
+use forest_encoding::{error::Error as EncodingError, from_slice, to_vec};
 use ipld_blockstore::BlockStore;
 use log::warn;
 use message::{Message, UnsignedMessage};
-use num_bigint::BigInt;
-use runtime::{ActorCode, MessageInfo, Runtime, Syscalls};
+use runtime::{ActorCode, MessageInfo, Runtime, StateObject, Syscalls};
 use state_tree::StateTree;
 use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::rc::Rc;
+use std::time::Instant;
 use vm::{
     actor_error, ActorError, ActorState, ExitCode, MethodNum, Randomness, Serialized, TokenAmount,
     EMPTY_ARR_CID, METHOD_SEND,
@@ -50,12 +50,11 @@ impl MessageInfo for VMMsg {
 }
 
 /// Implementation of the Runtime trait.
-pub struct DefaultRuntime<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P> {
+pub struct DefaultRuntime<'db, 'st, 'sys, 'r, BS, SYS, P> {
     state: &'st mut StateTree<'db, BS>,
     store: GasBlockStore<'db, BS>,
     syscalls: GasSyscalls<'sys, SYS>,
     gas_tracker: Rc<RefCell<GasTracker>>,
-    message: &'msg UnsignedMessage,
     vm_msg: VMMsg,
     epoch: ChainEpoch,
     origin: Address,
@@ -65,10 +64,11 @@ pub struct DefaultRuntime<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P> {
     rand: &'r ChainRand,
     caller_validated: bool,
     allow_internal: bool,
+    max_state_size: Option<usize>,
     params: PhantomData<P>,
 }
 
-impl<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P> DefaultRuntime<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P>
+impl<'db, 'st, 'sys, 'r, BS, SYS, P> DefaultRuntime<'db, 'st, 'sys, 'r, BS, SYS, P>
 where
     BS: BlockStore,
     SYS: Syscalls,
@@ -81,7 +81,7 @@ where
         store: &'db BS,
         syscalls: &'sys SYS,
         gas_used: i64,
-        message: &'msg UnsignedMessage,
+        message: &UnsignedMessage,
         epoch: ChainEpoch,
         origin: Address,
         origin_nonce: u64,
@@ -99,6 +99,7 @@ where
             price_list,
             gas: Rc::clone(&gas_tracker),
             syscalls,
+            actor_debugging: false,
         };
 
         let caller_id = state
@@ -119,7 +120,6 @@ where
             store: gas_block_store,
             syscalls: gas_syscalls,
             gas_tracker,
-            message,
             vm_msg,
             epoch,
             origin,
@@ -129,14 +129,50 @@ where
             rand,
             allow_internal: true,
             caller_validated: false,
+            max_state_size: None,
             params: PhantomData,
         })
     }
 
-    /// Adds to amount of used
+    /// Sets the wall clock deadline after which charging gas aborts execution with a
+    /// fatal error.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.gas_tracker.borrow_mut().set_deadline(deadline);
+    }
+
+    /// Sets the maximum serialized size of an actor's head state, committing a larger state
+    /// aborts with `ErrIllegalState`. No limit is enforced if `None`.
+    pub fn set_max_state_size(&mut self, max_state_size: Option<usize>) {
+        self.max_state_size = max_state_size;
+    }
+
+    /// Starts recording the gas charges made by the runtime, including the charges of
+    /// internal sends and of the store and syscalls.
+    pub fn enable_gas_tracing(&mut self) {
+        self.gas_tracker.borrow_mut().enable_tracing();
+    }
+
+    /// Emits the logs of the actors invoked by the runtime, which are ignored by default.
+    pub fn enable_actor_debugging(&mut self) {
+        self.syscalls.actor_debugging = true;
+    }
+
+    /// Returns the gas charges recorded since gas tracing was enabled, and stops recording.
+    pub fn take_gas_trace(&mut self) -> Vec<GasCharge> {
+        self.gas_tracker.borrow_mut().take_trace()
+    }
+
+    /// Adds to amount of used, naming the charge in the gas trace
+    /// * Will borrow gas tracker RefCell, do not call if any reference to this exists
+    pub fn charge_gas(&mut self, name: &'static str, to_use: i64) -> Result<(), ActorError> {
+        self.gas_tracker.borrow_mut().charge_gas(name, to_use)
+    }
+
+    /// Charges gas for the return data of an invocation, which is stored on chain in the
+    /// message receipt.
     /// * Will borrow gas tracker RefCell, do not call if any reference to this exists
-    pub fn charge_gas(&mut self, to_use: i64) -> Result<(), ActorError> {
-        self.gas_tracker.borrow_mut().charge_gas(to_use)
+    pub fn charge_return_value(&mut self, data_size: usize) -> Result<(), ActorError> {
+        self.syscalls.on_chain_return_value(data_size)
     }
 
     /// Returns gas used by runtime
@@ -155,7 +191,7 @@ where
     }
 
     /// Get the balance of a particular Actor from their Address
-    fn get_balance(&self, addr: &Address) -> Result<BigInt, ActorError> {
+    fn get_balance(&self, addr: &Address) -> Result<TokenAmount, ActorError> {
         Ok(self
             .state
             .get_actor(&addr)
@@ -186,6 +222,34 @@ where
         Ok(())
     }
 
+    /// Checks the serialized size of a new head state of the receiver against the maximum.
+    fn check_state_size<C: Cbor>(&self, state: &C) -> Result<(), ActorError> {
+        let max = match self.max_state_size {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        let size = state
+            .marshal_cbor()
+            .map_err(|e| actor_error!(ErrSerialization; "failed to marshal state: {}", e))?
+            .len();
+        if size > max {
+            return Err(actor_error!(ErrIllegalState;
+                "state of actor {} is {} bytes, exceeding the maximum of {}",
+                self.message().receiver(), size, max));
+        }
+        Ok(())
+    }
+
+    /// Checks that randomness is not requested for an epoch after the current one.
+    fn check_randomness_epoch(&self, rand_epoch: ChainEpoch) -> Result<(), ActorError> {
+        if rand_epoch > self.epoch {
+            return Err(actor_error!(ErrIllegalArgument;
+                "cannot draw randomness from future epoch {}, current epoch is {}",
+                rand_epoch, self.epoch));
+        }
+        Ok(())
+    }
+
     fn abort_if_already_validated(&mut self) -> Result<(), ActorError> {
         if self.caller_validated {
             Err(actor_error!(SysErrorIllegalActor;
@@ -196,34 +260,6 @@ where
         }
     }
 
-    /// Helper function for inserting into blockstore.
-    fn put<T>(&self, obj: &T) -> Result<Cid, ActorError>
-    where
-        T: Cbor,
-    {
-        self.store
-            .put(obj, Blake2b256)
-            .map_err(|e| match e.downcast::<EncodingError>() {
-                Ok(ser_error) => actor_error!(ErrSerialization;
-                        "failed to marshal cbor object {}", ser_error),
-                Err(other) => actor_error!(fatal("failed to put cbor object: {}", other)),
-            })
-    }
-
-    /// Helper function for getting deserializable objects from blockstore.
-    fn get<T>(&self, cid: &Cid) -> Result<Option<T>, ActorError>
-    where
-        T: Cbor,
-    {
-        self.store
-            .get(cid)
-            .map_err(|e| match e.downcast::<EncodingError>() {
-                Ok(ser_error) => actor_error!(ErrSerialization;
-                "failed to unmarshal cbor object {}", ser_error),
-                Err(other) => actor_error!(fatal("failed to get cbor object: {}", other)),
-            })
-    }
-
     fn internal_send(
         &mut self,
         from: Address,
@@ -236,7 +272,7 @@ where
             .from(from)
             .to(to)
             .method_num(method)
-            .value(value)
+            .value(value.clone())
             .params(params)
             .gas_limit(self.gas_available())
             .build()
@@ -248,7 +284,23 @@ where
             .snapshot()
             .map_err(|e| actor_error!(fatal("failed to create snapshot {}", e)))?;
 
+        // The receiver is invoked in the context of the internal message, which has to
+        // validate its caller independently of the sending actor.
+        let sender_msg = std::mem::replace(
+            &mut self.vm_msg,
+            VMMsg {
+                caller: from,
+                receiver: to,
+                value_received: value,
+            },
+        );
+        let sender_validated = std::mem::replace(&mut self.caller_validated, false);
+
         let send_res = vm_send::<BS, SYS, P>(self, &msg, None);
+
+        self.vm_msg = sender_msg;
+        self.caller_validated = sender_validated;
+
         send_res.map_err(|e| {
             if let Err(e) = self.state.revert_to_snapshot(&snapshot) {
                 actor_error!(fatal("failed to revert snapshot: {}", e))
@@ -258,9 +310,31 @@ where
         })
     }
 
-    /// creates account actors from only BLS/SECP256K1 addresses.
-    pub fn try_create_account_actor(&mut self, addr: &Address) -> Result<ActorState, ActorError> {
-        self.charge_gas(self.price_list().on_create_actor())?;
+    /// Resolves the receiver of a message to its ID address and actor. Account actors are
+    /// created for BLS/SECP256K1 addresses without an actor, while other addresses which
+    /// can't be resolved are an invalid receiver.
+    fn resolve_receiver(&mut self, to: &Address) -> Result<(Address, ActorState), ActorError> {
+        if let Some(id) = self.state.lookup_id(to).map_err(ActorError::new_fatal)? {
+            if let Some(act) = self.state.get_actor(&id).map_err(ActorError::new_fatal)? {
+                return Ok((id, act));
+            }
+        }
+
+        match to.protocol() {
+            Protocol::BLS | Protocol::Secp256k1 => self.try_create_account_actor(to),
+            Protocol::ID | Protocol::Actor | Protocol::Delegated => {
+                Err(actor_error!(SysErrInvalidReceiver; "no such actor: {}", to))
+            }
+        }
+    }
+
+    /// creates account actors from only BLS/SECP256K1 addresses, and returns the ID address
+    /// and state of the created actor.
+    pub fn try_create_account_actor(
+        &mut self,
+        addr: &Address,
+    ) -> Result<(Address, ActorState), ActorError> {
+        self.charge_gas("OnCreateActor", self.price_list().on_create_actor())?;
 
         let addr_id = self
             .state
@@ -283,7 +357,7 @@ where
         self.internal_send(
             *SYSTEM_ACTOR_ADDR,
             addr_id,
-            account::Method::Constructor as u64,
+            account::Method::Constructor.into(),
             TokenAmount::from(0),
             p,
         )?;
@@ -294,11 +368,12 @@ where
             .map_err(ActorError::new_fatal)?
             .ok_or_else(|| actor_error!(fatal("failed to retrieve created actor state")))?;
 
-        Ok(act)
+        Ok((addr_id, act))
     }
 }
 
-impl<BS, SYS, P> Runtime<BS> for DefaultRuntime<'_, '_, '_, '_, '_, BS, SYS, P>
+impl<'db, BS, SYS, P> Runtime<GasBlockStore<'db, BS>>
+    for DefaultRuntime<'db, '_, '_, '_, BS, SYS, P>
 where
     BS: BlockStore,
     SYS: Syscalls,
@@ -310,6 +385,9 @@ where
     fn curr_epoch(&self) -> ChainEpoch {
         self.epoch
     }
+    fn network_version(&self) -> NetworkVersion {
+        P::network_version(self.epoch)
+    }
     fn validate_immediate_caller_accept_any(&mut self) -> Result<(), ActorError> {
         self.abort_if_already_validated()
     }
@@ -347,7 +425,7 @@ where
     }
 
     fn current_balance(&self) -> Result<TokenAmount, ActorError> {
-        self.get_balance(self.message.to())
+        self.get_balance(self.message().receiver())
     }
 
     fn resolve_address(&self, address: &Address) -> Result<Option<Address>, ActorError> {
@@ -364,22 +442,39 @@ where
             .map(|act| act.code))
     }
 
-    fn get_randomness(
+    fn get_randomness_from_tickets(
+        &self,
+        personalization: DomainSeparationTag,
+        rand_epoch: ChainEpoch,
+        entropy: &[u8],
+    ) -> Result<Randomness, ActorError> {
+        self.check_randomness_epoch(rand_epoch)?;
+        let r = self
+            .rand
+            .get_chain_randomness(&self.store, personalization, rand_epoch, entropy)
+            .map_err(|e| actor_error!(fatal("could not get randomness: {}", e.to_string())))?;
+
+        Ok(Randomness(r))
+    }
+
+    fn get_randomness_from_beacon(
         &self,
         personalization: DomainSeparationTag,
         rand_epoch: ChainEpoch,
         entropy: &[u8],
     ) -> Result<Randomness, ActorError> {
+        self.check_randomness_epoch(rand_epoch)?;
         let r = self
             .rand
-            .get_randomness(&self.store, personalization, rand_epoch, entropy)
+            .get_beacon_randomness(&self.store, personalization, rand_epoch, entropy)
             .map_err(|e| actor_error!(fatal("could not get randomness: {}", e.to_string())))?;
 
         Ok(Randomness(r))
     }
 
     fn create<C: Cbor>(&mut self, obj: &C) -> Result<(), ActorError> {
-        let c = self.put(obj)?;
+        self.check_state_size(obj)?;
+        let c = self.store_put(obj)?;
 
         self.state_commit(&EMPTY_ARR_CID, c)
     }
@@ -397,7 +492,7 @@ where
             )?;
 
         // TODO revisit as the go impl doesn't handle not exists and nil cases
-        self.get(&actor.state)?.ok_or_else(|| {
+        self.store_get(&actor.state)?.ok_or_else(|| {
             actor_error!(fatal(
                 "State does not exist for actor state cid: {}",
                 actor.state
@@ -407,7 +502,7 @@ where
 
     fn transaction<C, R, F>(&mut self, f: F) -> Result<R, ActorError>
     where
-        C: Cbor,
+        C: StateObject,
         F: FnOnce(&mut C, &mut Self) -> R,
     {
         // get actor
@@ -416,26 +511,62 @@ where
             .ok_or_else(|| actor_error!(SysErrorIllegalActor;
                 "actor state for transaction doesn't exist"))?;
 
-        // get state for actor based on generic C
+        // get state for actor based on generic C, keeping the head's size and the state as
+        // loaded to tell whether the transaction changed it
         // TODO Lotus is not handling the not exist case, revisit
-        let mut state: C = self
-            .get(&act.state)?
+        let head_bz = self
+            .store
+            .get_bytes(&act.state)
+            .map_err(|e| actor_error!(fatal("failed to get cbor object: {}", e)))?
             .ok_or_else(|| actor_error!(fatal("Actor state does not exist: {}", act.state)))?;
+        let loaded: C = from_slice(&head_bz)
+            .map_err(|e| actor_error!(ErrSerialization; "failed to unmarshal cbor object {}", e))?;
+        let mut state = loaded.clone();
 
         // Update the state
         self.allow_internal = false;
         let r = f(&mut state, self);
         self.allow_internal = true;
 
-        let c = self.put(&state)?;
+        // Unchanged state is left as the actor's head without encoding it. The put is still
+        // charged, for the size of the head, so the gas used doesn't depend on whether the
+        // state is written
+        if state == loaded {
+            self.charge_gas("OnIpldPut", self.price_list.on_ipld_put(head_bz.len()))?;
+            return Ok(r);
+        }
 
         // Committing that change
-        self.state_commit(&act.state, c)?;
+        self.check_state_size(&state)?;
+        let c = self.store_put(&state)?;
+        if c != act.state {
+            self.state_commit(&act.state, c)?;
+        }
         Ok(r)
     }
 
-    fn store(&self) -> &BS {
-        self.store.store
+    fn store(&self) -> &GasBlockStore<'db, BS> {
+        &self.store
+    }
+
+    fn store_get<T: Cbor>(&self, cid: &Cid) -> Result<Option<T>, ActorError> {
+        self.store
+            .get(cid)
+            .map_err(|e| match e.downcast::<EncodingError>() {
+                Ok(ser_error) => actor_error!(ErrSerialization;
+                "failed to unmarshal cbor object {}", ser_error),
+                Err(other) => actor_error!(fatal("failed to get cbor object: {}", other)),
+            })
+    }
+
+    fn store_put<T: Cbor>(&self, obj: &T) -> Result<Cid, ActorError> {
+        self.store
+            .put(obj, Blake2b256)
+            .map_err(|e| match e.downcast::<EncodingError>() {
+                Ok(ser_error) => actor_error!(ErrSerialization;
+                        "failed to marshal cbor object {}", ser_error),
+                Err(other) => actor_error!(fatal("failed to put cbor object: {}", other)),
+            })
     }
 
     fn send(
@@ -450,7 +581,7 @@ where
         }
 
         let ret = self
-            .internal_send(*self.message.receiver(), to, method, value, params)
+            .internal_send(*self.message().receiver(), to, method, value, params)
             .map_err(|e| {
                 warn!(
                     "internal send failed: (to: {}) (method: {}) {}",
@@ -458,7 +589,7 @@ where
                 );
                 e
             })?;
-        self.charge_gas(ACTOR_EXEC_GAS)?;
+        self.charge_gas("OnActorExec", ACTOR_EXEC_GAS)?;
 
         Ok(ret)
     }
@@ -495,11 +626,11 @@ where
         Ok(addr)
     }
     fn create_actor(&mut self, code_id: &Cid, address: &Address) -> Result<(), ActorError> {
-        self.charge_gas(self.price_list.on_create_actor())?;
+        self.charge_gas("OnCreateActor", self.price_list.on_create_actor())?;
         self.state
             .set_actor(
                 &address,
-                ActorState::new(code_id.clone(), Cid::default(), 0u64.into(), 0),
+                ActorState::new(code_id.clone(), EMPTY_ARR_CID.clone(), 0u64.into(), 0),
             )
             .map_err(|e| {
                 self.abort(
@@ -509,11 +640,12 @@ where
             })
     }
     fn delete_actor(&mut self, _beneficiary: &Address) -> Result<(), ActorError> {
-        self.charge_gas(self.price_list.on_delete_actor())?;
+        self.charge_gas("OnDeleteActor", self.price_list.on_delete_actor())?;
+        let receiver = *self.message().receiver();
         let balance = self
             .state
-            .get_actor(self.message.to())
-            .map_err(|e| actor_error!(fatal("failed to get actor {}, {}", self.message.to(), e)))?
+            .get_actor(&receiver)
+            .map_err(|e| actor_error!(fatal("failed to get actor {}, {}", receiver, e)))?
             .ok_or_else(
                 || actor_error!(SysErrorIllegalActor; "failed to load actor in delete actor"),
             )
@@ -524,7 +656,7 @@ where
                 "cannot delete actor with non-zero balance",
             ));
         }
-        self.state.delete_actor(self.message.to()).map_err(|e| {
+        self.state.delete_actor(&receiver).map_err(|e| {
             self.abort(
                 ExitCode::SysErrInternal,
                 format!("failed to delete actor: {}", e),
@@ -534,6 +666,16 @@ where
     fn syscalls(&self) -> &dyn Syscalls {
         &self.syscalls
     }
+    fn verify_signature(
+        &self,
+        signature: &Signature,
+        signer: &Address,
+        plaintext: &[u8],
+    ) -> Result<Result<(), SignatureError>, ActorError> {
+        self.syscalls
+            .on_verify_signature(signature.signature_type(), plaintext.len())?;
+        Ok(self.syscalls.verify_signature(signature, signer, plaintext))
+    }
     fn total_fil_circ_supply(&self) -> Result<TokenAmount, ActorError> {
         let get_actor_state = |addr: &Address| -> Result<ActorState, ActorError> {
             self.state
@@ -572,8 +714,8 @@ where
 
 /// Shared logic between the DefaultRuntime and the Interpreter.
 /// It invokes methods on different Actors based on the Message.
-pub fn vm_send<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P>(
-    rt: &mut DefaultRuntime<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P>,
+pub fn vm_send<'db, 'st, 'sys, 'r, BS, SYS, P>(
+    rt: &mut DefaultRuntime<'db, 'st, 'sys, 'r, BS, SYS, P>,
     msg: &UnsignedMessage,
     gas_cost: Option<i64>,
 ) -> Result<Serialized, ActorError>
@@ -583,11 +725,12 @@ where
     P: NetworkParams,
 {
     if let Some(cost) = gas_cost {
-        rt.charge_gas(cost)?;
+        rt.charge_gas("OnChainMessage", cost)?;
     }
 
     // TODO maybe move this
     rt.charge_gas(
+        "OnMethodInvocation",
         rt.price_list()
             .on_method_invocation(msg.value(), msg.method_num()),
     )?;
@@ -595,34 +738,25 @@ where
     {
         // On get actor gas charge
         // TODO this value shouldn't be final
-        rt.charge_gas(0)?;
+        rt.charge_gas("OnGetActor", 0)?;
 
-        // TODO: we need to try to recover here and try to create account actor
-        // TODO: actually fix this and don't leave as unwrap for PR
-        let to_actor = match rt
-            .state
-            .get_actor(msg.to())
-            .map_err(ActorError::new_fatal)?
-        {
-            Some(act) => act,
-            None => {
-                // Try to create actor if not exist
-                rt.try_create_account_actor(msg.to())?
-            }
-        };
+        // The receiver may be sent to by any address, and is invoked with its ID address
+        let (to, to_actor) = rt.resolve_receiver(msg.to())?;
+        rt.vm_msg.receiver = to;
 
         rt.charge_gas(
+            "OnMethodInvocation",
             rt.price_list()
                 .on_method_invocation(msg.value(), msg.method_num()),
         )?;
 
         if msg.value() > &TokenAmount::from(0) {
-            transfer(rt.state, &msg.from(), &msg.to(), &msg.value())?;
+            transfer(rt.state, &msg.from(), &to, &msg.value())?;
         }
 
         if msg.method_num() != METHOD_SEND {
-            rt.charge_gas(ACTOR_EXEC_GAS)?;
-            return invoke(rt, to_actor.code, msg.method_num(), msg.params(), msg.to());
+            rt.charge_gas("OnActorExec", ACTOR_EXEC_GAS)?;
+            return invoke(rt, to_actor.code, msg.method_num(), msg.params(), &to);
         }
     }
 
@@ -689,8 +823,8 @@ fn transfer<BS: BlockStore>(
 }
 
 /// Calls actor code with method and parameters.
-fn invoke<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P>(
-    rt: &mut DefaultRuntime<'db, 'msg, 'st, 'sys, 'r, BS, SYS, P>,
+fn invoke<'db, 'st, 'sys, 'r, BS, SYS, P>(
+    rt: &mut DefaultRuntime<'db, 'st, 'sys, 'r, BS, SYS, P>,
     code: Cid,
     method_num: MethodNum,
     params: &Serialized,
@@ -769,7 +903,9 @@ fn make_actor(addr: &Address) -> Result<ActorState, ActorError> {
         Protocol::ID => {
             Err(actor_error!(SysErrInvalidReceiver; "no actor with given id: {}", addr))
         }
-        Protocol::Actor => Err(actor_error!(SysErrInvalidReceiver; "no such actor: {}", addr)),
+        Protocol::Actor | Protocol::Delegated => {
+            Err(actor_error!(SysErrInvalidReceiver; "no such actor: {}", addr))
+        }
     }
 }
 
@@ -779,6 +915,7 @@ fn new_bls_account_actor() -> ActorState {
         balance: TokenAmount::from(0),
         state: EMPTY_ARR_CID.clone(),
         sequence: 0,
+        delegated_address: None,
     }
 }
 
@@ -788,5 +925,6 @@ fn new_secp256k1_account_actor() -> ActorState {
         balance: TokenAmount::from(0),
         state: EMPTY_ARR_CID.clone(),
         sequence: 0,
+        delegated_address: None,
     }
 }
@@ -11,11 +11,11 @@ use std::error::Error as StdError;
 use std::rc::Rc;
 use vm::{actor_error, ActorError};
 
-/// Blockstore wrapper to charge gas on reads and writes
-pub(crate) struct GasBlockStore<'bs, BS> {
-    pub price_list: PriceList,
-    pub gas: Rc<RefCell<GasTracker>>,
-    pub store: &'bs BS,
+/// Blockstore wrapper to charge gas on reads and writes, given to actors as the runtime's store
+pub struct GasBlockStore<'bs, BS> {
+    pub(crate) price_list: PriceList,
+    pub(crate) gas: Rc<RefCell<GasTracker>>,
+    pub(crate) store: &'bs BS,
 }
 
 impl<BS> BlockStore for GasBlockStore<'_, BS>
@@ -31,7 +31,7 @@ where
         if let Some(bz) = &ret {
             self.gas
                 .borrow_mut()
-                .charge_gas(self.price_list.on_ipld_get(bz.len()))?;
+                .charge_gas("OnIpldGet", self.price_list.on_ipld_get(bz.len()))?;
         }
         Ok(ret)
     }
@@ -53,9 +53,10 @@ where
         S: Serialize,
         T: MultihashDigest,
     {
-        self.gas
-            .borrow_mut()
-            .charge_gas(self.price_list.on_ipld_put(to_vec(obj).unwrap().len()))?;
+        self.gas.borrow_mut().charge_gas(
+            "OnIpldPut",
+            self.price_list.on_ipld_put(to_vec(obj).unwrap().len()),
+        )?;
 
         Ok(self
             .store
@@ -4,63 +4,98 @@
 use super::gas_tracker::{GasTracker, PriceList};
 use address::Address;
 use cid::Cid;
-use crypto::Signature;
+use crypto::{Signature, SignatureError, SignatureType};
 use fil_types::{PieceInfo, RegisteredSealProof, SealVerifyInfo, WindowPoStVerifyInfo};
-use runtime::{ConsensusFault, Syscalls};
+use runtime::{ConsensusFault, LogLevel, Syscalls};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::rc::Rc;
+use vm::ActorError;
 
 /// Syscall wrapper to charge gas on syscalls
 pub(crate) struct GasSyscalls<'sys, S> {
     pub price_list: PriceList,
     pub gas: Rc<RefCell<GasTracker>>,
     pub syscalls: &'sys S,
+    /// Forwards actor logs to the wrapped syscalls, which are dropped otherwise.
+    pub actor_debugging: bool,
+}
+
+impl<S> GasSyscalls<'_, S> {
+    /// Charges gas for storing return data of the given length on chain.
+    pub fn on_chain_return_value(&self, data_size: usize) -> Result<(), ActorError> {
+        self.gas.borrow_mut().charge_gas(
+            "OnChainReturnValue",
+            self.price_list.on_chain_return_value(data_size),
+        )
+    }
+
+    /// Charges gas for verifying a signature of the given type over a plaintext of the given
+    /// length.
+    pub fn on_verify_signature(
+        &self,
+        sig_type: SignatureType,
+        plaintext_len: usize,
+    ) -> Result<(), ActorError> {
+        self.gas.borrow_mut().charge_gas(
+            "OnVerifySignature",
+            self.price_list.on_verify_signature(sig_type, plaintext_len),
+        )
+    }
 }
 
 impl<'sys, S> Syscalls for GasSyscalls<'sys, S>
 where
     S: Syscalls,
 {
+    fn log(&self, level: LogLevel, msg: &str) {
+        if self.actor_debugging {
+            self.syscalls.log(level, msg);
+        }
+    }
     fn verify_signature(
         &self,
         signature: &Signature,
         signer: &Address,
         plaintext: &[u8],
-    ) -> Result<(), Box<dyn StdError>> {
-        self.gas.borrow_mut().charge_gas(
-            self.price_list
-                .on_verify_signature(signature.signature_type(), plaintext.len()),
-        )?;
+    ) -> Result<(), SignatureError> {
+        // Charged by the runtime, so that running out of gas aborts the actor
         self.syscalls.verify_signature(signature, signer, plaintext)
     }
     fn hash_blake2b(&self, data: &[u8]) -> Result<[u8; 32], Box<dyn StdError>> {
         self.gas
             .borrow_mut()
-            .charge_gas(self.price_list.on_hashing(data.len()))?;
+            .charge_gas("OnHashing", self.price_list.on_hashing(data.len()))?;
         self.syscalls.hash_blake2b(data)
     }
+    fn hash_sha256(&self, data: &[u8]) -> Result<[u8; 32], Box<dyn StdError>> {
+        self.gas
+            .borrow_mut()
+            .charge_gas("OnHashing", self.price_list.on_hashing(data.len()))?;
+        self.syscalls.hash_sha256(data)
+    }
     fn compute_unsealed_sector_cid(
         &self,
         reg: RegisteredSealProof,
         pieces: &[PieceInfo],
     ) -> Result<Cid, Box<dyn StdError>> {
-        self.gas
-            .borrow_mut()
-            .charge_gas(self.price_list.on_compute_unsealed_sector_cid(reg, pieces))?;
+        self.gas.borrow_mut().charge_gas(
+            "OnComputeUnsealedSectorCid",
+            self.price_list.on_compute_unsealed_sector_cid(reg, pieces),
+        )?;
         self.syscalls.compute_unsealed_sector_cid(reg, pieces)
     }
     fn verify_seal(&self, vi: &SealVerifyInfo) -> Result<(), Box<dyn StdError>> {
         self.gas
             .borrow_mut()
-            .charge_gas(self.price_list.on_verify_seal(vi))?;
+            .charge_gas("OnVerifySeal", self.price_list.on_verify_seal(vi))?;
         self.syscalls.verify_seal(vi)
     }
     fn verify_post(&self, vi: &WindowPoStVerifyInfo) -> Result<(), Box<dyn StdError>> {
         self.gas
             .borrow_mut()
-            .charge_gas(self.price_list.on_verify_post(vi))?;
+            .charge_gas("OnVerifyPost", self.price_list.on_verify_post(vi))?;
         self.syscalls.verify_post(vi)
     }
     fn verify_consensus_fault(
@@ -69,9 +104,10 @@ where
         h2: &[u8],
         extra: &[u8],
     ) -> Result<Option<ConsensusFault>, Box<dyn StdError>> {
-        self.gas
-            .borrow_mut()
-            .charge_gas(self.price_list.on_verify_consensus_fault())?;
+        self.gas.borrow_mut().charge_gas(
+            "OnVerifyConsensusFault",
+            self.price_list.on_verify_consensus_fault(),
+        )?;
         self.syscalls.verify_consensus_fault(h1, h2, extra)
     }
 
@@ -87,6 +123,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gas_tracker::{price_list_by_epoch, ScalingCost, UPGRADE_CALICO_HEIGHT};
+    use fil_types::{PoStProof, RegisteredPoStProof, SectorInfo};
     use runtime::{ConsensusFault, ConsensusFaultType, Syscalls};
 
     #[derive(Copy, Debug, Clone)]
@@ -97,7 +135,7 @@ mod tests {
             _signature: &Signature,
             _signer: &Address,
             _plaintext: &[u8],
-        ) -> Result<(), Box<dyn StdError>> {
+        ) -> Result<(), SignatureError> {
             Ok(())
         }
         fn hash_blake2b(&self, _data: &[u8]) -> Result<[u8; 32], Box<dyn StdError>> {
@@ -147,15 +185,21 @@ mod tests {
                 hashing_per_byte: 1,
                 compute_unsealed_sector_cid_base: 1,
                 verify_seal_base: 1,
-                verify_post_base: 1,
+                verify_post_small_sectors: ScalingCost { flat: 1, scale: 0 },
                 verify_consensus_fault: 1,
                 ..Default::default()
             },
-            gas: Rc::new(RefCell::new(GasTracker::new(20, 0))),
+            gas: Rc::new(RefCell::new(GasTracker::new(22, 0))),
             syscalls: &TestSyscalls,
+            actor_debugging: false,
         };
 
         assert_eq!(gsys.gas.borrow().gas_used(), 0);
+        gsys.on_verify_signature(Signature::default().signature_type(), 1)
+            .unwrap();
+        assert_eq!(gsys.gas.borrow().gas_used(), 5);
+
+        // Verification itself is charged by the runtime
         gsys.verify_signature(&Default::default(), &Address::new_id(0), &[0u8])
             .unwrap();
         assert_eq!(gsys.gas.borrow().gas_used(), 5);
@@ -163,9 +207,12 @@ mod tests {
         gsys.hash_blake2b(&[0u8]).unwrap();
         assert_eq!(gsys.gas.borrow().gas_used(), 7);
 
+        gsys.hash_sha256(&[0u8]).unwrap();
+        assert_eq!(gsys.gas.borrow().gas_used(), 9);
+
         gsys.compute_unsealed_sector_cid(RegisteredSealProof::from(0), &[])
             .unwrap();
-        assert_eq!(gsys.gas.borrow().gas_used(), 8);
+        assert_eq!(gsys.gas.borrow().gas_used(), 10);
 
         gsys.verify_seal(&SealVerifyInfo {
             registered_proof: RegisteredSealProof::from(1),
@@ -178,12 +225,116 @@ mod tests {
             unsealed_cid: Default::default(),
         })
         .unwrap();
-        assert_eq!(gsys.gas.borrow().gas_used(), 9);
+        assert_eq!(gsys.gas.borrow().gas_used(), 11);
 
         gsys.verify_post(&Default::default()).unwrap();
-        assert_eq!(gsys.gas.borrow().gas_used(), 10);
+        assert_eq!(gsys.gas.borrow().gas_used(), 12);
 
         gsys.verify_consensus_fault(&[], &[], &[]).unwrap();
-        assert_eq!(gsys.gas.borrow().gas_used(), 11);
+        assert_eq!(gsys.gas.borrow().gas_used(), 13);
+
+        gsys.on_chain_return_value(4).unwrap();
+        assert_eq!(gsys.gas.borrow().gas_used(), 17);
+
+        // Return data which doesn't fit in the remaining gas
+        assert!(gsys.on_chain_return_value(6).is_err());
+        assert_eq!(gsys.gas.borrow().gas_used(), 22);
+    }
+
+    #[test]
+    fn verify_post_gas() {
+        let gsys = GasSyscalls {
+            price_list: PriceList {
+                verify_post_small_sectors: ScalingCost {
+                    flat: 100,
+                    scale: 10,
+                },
+                verify_post_large_sectors: ScalingCost {
+                    flat: 200,
+                    scale: 1,
+                },
+                ..Default::default()
+            },
+            gas: Rc::new(RefCell::new(GasTracker::new(100_000, 0))),
+            syscalls: &TestSyscalls,
+            actor_debugging: false,
+        };
+        let info = |proofs: &[RegisteredPoStProof], sectors| WindowPoStVerifyInfo {
+            proofs: proofs
+                .iter()
+                .map(|&registered_proof| PoStProof {
+                    registered_proof,
+                    proof_bytes: Vec::new(),
+                })
+                .collect(),
+            challenged_sectors: vec![
+                SectorInfo {
+                    proof: RegisteredSealProof::from(1),
+                    sector_number: 0,
+                    sealed_cid: Default::default(),
+                };
+                sectors
+            ],
+            ..Default::default()
+        };
+        let charge = |vi: &WindowPoStVerifyInfo| {
+            let before = gsys.gas.borrow().gas_used();
+            gsys.verify_post(vi).unwrap();
+            gsys.gas.borrow().gas_used() - before
+        };
+
+        let small = RegisteredPoStProof::StackedDRGWindow2KiBV1;
+        let large = RegisteredPoStProof::StackedDRGWindow32GiBV1;
+
+        // Without proofs, or with proofs of unknown types, sectors are priced as small sectors
+        assert_eq!(charge(&info(&[], 4)), 140);
+        assert_eq!(charge(&info(&[RegisteredPoStProof::Invalid(-1)], 3)), 130);
+        assert_eq!(charge(&info(&[small], 4)), 140);
+        assert_eq!(charge(&info(&[large, large], 4)), 204);
+
+        // The most expensive proof type is charged, regardless of the order of the proofs
+        assert_eq!(charge(&info(&[small, large], 4)), 204);
+        assert_eq!(charge(&info(&[large, small], 4)), 204);
+        assert_eq!(charge(&info(&[small, large], 20)), 300);
+    }
+
+    #[test]
+    fn verify_post_prices_by_epoch() {
+        let info = WindowPoStVerifyInfo {
+            proofs: vec![PoStProof {
+                registered_proof: RegisteredPoStProof::StackedDRGWindow32GiBV1,
+                proof_bytes: Vec::new(),
+            }],
+            challenged_sectors: vec![
+                SectorInfo {
+                    proof: RegisteredSealProof::from(3),
+                    sector_number: 0,
+                    sealed_cid: Default::default(),
+                };
+                2
+            ],
+            ..Default::default()
+        };
+
+        // Genesis prices are flat
+        let pl = price_list_by_epoch(UPGRADE_CALICO_HEIGHT - 1);
+        assert_eq!(pl.on_verify_post(&info), 700);
+        assert_eq!(pl.on_verify_post(&Default::default()), 700);
+
+        let pl = price_list_by_epoch(UPGRADE_CALICO_HEIGHT);
+        assert_eq!(pl.on_verify_post(&info), 748_593_537 + 2 * 85_639);
+        assert_eq!(pl.on_verify_post(&Default::default()), 123_861_062);
+    }
+
+    #[test]
+    fn log_not_charged() {
+        let gsys = GasSyscalls {
+            price_list: PriceList::default(),
+            gas: Rc::new(RefCell::new(GasTracker::new(10, 0))),
+            syscalls: &TestSyscalls,
+            actor_debugging: true,
+        };
+        gsys.log(LogLevel::Info, "actor log");
+        assert_eq!(gsys.gas.borrow().gas_used(), 0);
     }
 }
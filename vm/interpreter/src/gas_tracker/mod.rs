@@ -3,12 +3,25 @@
 
 mod price_list;
 
-pub use self::price_list::{price_list_by_epoch, PriceList};
+pub use self::price_list::{price_list_by_epoch, PriceList, ScalingCost, UPGRADE_CALICO_HEIGHT};
+use std::time::Instant;
 use vm::{actor_error, ActorError, ExitCode};
 
+/// Gas charged for an operation during execution, recorded when gas tracing is enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasCharge {
+    /// Name of the operation, matching the `PriceList` method which priced it.
+    pub name: &'static str,
+    pub gas: i64,
+}
+
 pub struct GasTracker {
     gas_available: i64,
     gas_used: i64,
+    /// Wall clock deadline after which charging gas fails, aborting execution.
+    deadline: Option<Instant>,
+    /// Charges made since tracing was enabled, `None` if tracing is disabled.
+    trace: Option<Vec<GasCharge>>,
 }
 
 impl GasTracker {
@@ -16,11 +29,36 @@ impl GasTracker {
         Self {
             gas_available,
             gas_used,
+            deadline: None,
+            trace: None,
         }
     }
 
-    /// Safely consumes gas
-    pub fn charge_gas(&mut self, to_use: i64) -> Result<(), ActorError> {
+    /// Sets the wall clock deadline for execution.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// Starts recording the gas charges made.
+    pub fn enable_tracing(&mut self) {
+        self.trace = Some(Vec::new());
+    }
+
+    /// Returns the gas charges recorded since tracing was enabled, and stops recording.
+    pub fn take_trace(&mut self) -> Vec<GasCharge> {
+        self.trace.take().unwrap_or_default()
+    }
+
+    /// Safely consumes gas, recording the charge under the given name if tracing is enabled.
+    pub fn charge_gas(&mut self, name: &'static str, to_use: i64) -> Result<(), ActorError> {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(actor_error!(fatal("execution deadline exceeded")));
+            }
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.push(GasCharge { name, gas: to_use });
+        }
         if self.gas_used + to_use > self.gas_available {
             self.gas_used = self.gas_available;
             Err(actor_error!(SysErrOutOfGas;
@@ -51,10 +89,38 @@ mod tests {
     #[test]
     fn basic_gas_tracker() {
         let mut t = GasTracker::new(20, 10);
-        t.charge_gas(5).unwrap();
+        t.charge_gas("a", 5).unwrap();
         assert_eq!(t.gas_used(), 15);
-        t.charge_gas(5).unwrap();
+        t.charge_gas("a", 5).unwrap();
         assert_eq!(t.gas_used(), 20);
-        assert!(t.charge_gas(1).is_err())
+        assert!(t.charge_gas("a", 1).is_err())
+    }
+
+    #[test]
+    fn gas_tracker_trace() {
+        let mut t = GasTracker::new(20, 0);
+        t.charge_gas("a", 1).unwrap();
+        t.enable_tracing();
+        t.charge_gas("b", 2).unwrap();
+        t.charge_gas("c", 3).unwrap();
+        assert_eq!(
+            t.take_trace(),
+            vec![
+                GasCharge { name: "b", gas: 2 },
+                GasCharge { name: "c", gas: 3 }
+            ]
+        );
+
+        // Recording stops once the trace is taken
+        t.charge_gas("d", 4).unwrap();
+        assert!(t.take_trace().is_empty());
+    }
+
+    #[test]
+    fn gas_tracker_deadline() {
+        let mut t = GasTracker::new(20, 0);
+        t.set_deadline(Some(Instant::now()));
+        assert!(t.charge_gas("a", 1).unwrap_err().is_fatal());
+        assert_eq!(t.gas_used(), 0);
     }
 }
@@ -3,10 +3,25 @@
 
 use clock::ChainEpoch;
 use crypto::SignatureType;
-use fil_types::{PieceInfo, RegisteredSealProof, SealVerifyInfo, WindowPoStVerifyInfo};
+use fil_types::{PieceInfo, RegisteredSealProof, SealVerifyInfo, SectorSize, WindowPoStVerifyInfo};
 use num_traits::Zero;
 use vm::{MethodNum, TokenAmount, METHOD_SEND};
 
+/// Gas cost of an operation which scales with the number of items it operates on, given by:
+///   Flat + n*Scale
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ScalingCost {
+    pub flat: i64,
+    pub scale: i64,
+}
+
+impl ScalingCost {
+    #[inline]
+    fn apply(&self, n: usize) -> i64 {
+        self.flat + n as i64 * self.scale
+    }
+}
+
 /// Provides prices for operations in the VM
 #[derive(Copy, Clone, Debug)]
 pub struct PriceList {
@@ -70,7 +85,12 @@ pub struct PriceList {
 
     pub compute_unsealed_sector_cid_base: i64,
     pub verify_seal_base: i64,
-    pub verify_post_base: i64,
+
+    /// Gas cost for verifying a Window PoSt, scaling with the number of challenged sectors.
+    /// Proofs of 32GiB and 64GiB sectors have a separate cost from proofs of smaller sectors.
+    pub verify_post_small_sectors: ScalingCost,
+    pub verify_post_large_sectors: ScalingCost,
+
     pub verify_consensus_fault: i64,
 }
 
@@ -144,10 +164,24 @@ impl PriceList {
     pub fn on_verify_seal(&self, _info: &SealVerifyInfo) -> i64 {
         self.verify_seal_base
     }
-    /// Returns gas required for PoSt verification
+    /// Returns gas required for PoSt verification, which depends on the proof types and the
+    /// number of challenged sectors. A PoSt with proofs of several types is priced as the most
+    /// expensive of them, proofs of unknown types and PoSts without proofs are priced as small
+    /// sectors.
     #[inline]
-    pub fn on_verify_post(&self, _info: &WindowPoStVerifyInfo) -> i64 {
-        self.verify_post_base
+    pub fn on_verify_post(&self, info: &WindowPoStVerifyInfo) -> i64 {
+        let sectors = info.challenged_sectors.len();
+        let small = self.verify_post_small_sectors.apply(sectors);
+        info.proofs
+            .iter()
+            .map(|p| match p.registered_proof.sector_size() {
+                Ok(SectorSize::_32GiB) | Ok(SectorSize::_64GiB) => {
+                    self.verify_post_large_sectors.apply(sectors)
+                }
+                _ => small,
+            })
+            .max()
+            .unwrap_or(small)
     }
     /// Returns gas required for verifying consensus fault
     #[inline]
@@ -180,12 +214,39 @@ const BASE_PRICES: PriceList = PriceList {
     hashing_per_byte: 2,
     compute_unsealed_sector_cid_base: 100,
     verify_seal_base: 2000,
-    verify_post_base: 700,
+    verify_post_small_sectors: ScalingCost {
+        flat: 700,
+        scale: 0,
+    },
+    verify_post_large_sectors: ScalingCost {
+        flat: 700,
+        scale: 0,
+    },
     verify_consensus_fault: 10,
 };
 
+/// Epoch of the Calico upgrade of mainnet, from which Window PoSt verification is priced by
+/// proof type and challenged sectors.
+pub const UPGRADE_CALICO_HEIGHT: ChainEpoch = 265_200;
+
+/// Prices from the Calico upgrade, Window PoSt verification uses the costs of Lotus.
+const CALICO_PRICES: PriceList = PriceList {
+    verify_post_small_sectors: ScalingCost {
+        flat: 123_861_062,
+        scale: 9_226_981,
+    },
+    verify_post_large_sectors: ScalingCost {
+        flat: 748_593_537,
+        scale: 85_639,
+    },
+    ..BASE_PRICES
+};
+
 /// Returns gas price list by Epoch for gas consumption
-pub fn price_list_by_epoch(_epoch: ChainEpoch) -> PriceList {
-    // In future will match on epoch and select matching price lists when config options allowed
-    BASE_PRICES
+pub fn price_list_by_epoch(epoch: ChainEpoch) -> PriceList {
+    if epoch < UPGRADE_CALICO_HEIGHT {
+        BASE_PRICES
+    } else {
+        CALICO_PRICES
+    }
 }
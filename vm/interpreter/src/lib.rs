@@ -6,9 +6,13 @@ mod default_syscalls;
 mod gas_block_store;
 mod gas_syscalls;
 mod gas_tracker;
+mod penalty;
 mod rand;
 mod vm;
 pub use self::default_runtime::*;
 pub use self::default_syscalls::DefaultSyscalls;
+pub use self::gas_block_store::GasBlockStore;
+pub use self::gas_tracker::GasCharge;
+pub use self::penalty::*;
 pub use self::rand::*;
 pub use self::vm::*;
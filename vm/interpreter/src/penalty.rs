@@ -0,0 +1,224 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use message::{Message, UnsignedMessage};
+use vm::{ExitCode, TokenAmount};
+
+/// Reason a message is rejected before it is executed. Nothing is charged to the sender of a
+/// rejected message, instead the miner who included it is penalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFailure {
+    /// The gas cost of including the message on chain exceeds its gas limit.
+    OutOfGas,
+    /// The sender doesn't exist in the state tree.
+    SenderMissing,
+    /// The sender is not an account actor.
+    SenderNotAccount,
+    /// The sequence of the message is lower than the sequence of the sender.
+    SequenceTooLow,
+    /// The sequence of the message is higher than the sequence of the sender.
+    SequenceTooHigh,
+    /// The balance of the sender doesn't cover the value and the gas limit of the message.
+    InsufficientFunds,
+}
+
+impl MessageFailure {
+    /// Returns the exit code of the receipt of a message rejected for this failure.
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            MessageFailure::OutOfGas => ExitCode::SysErrOutOfGas,
+            MessageFailure::SenderMissing | MessageFailure::SenderNotAccount => {
+                ExitCode::SysErrSenderInvalid
+            }
+            MessageFailure::SequenceTooLow
+            | MessageFailure::SequenceTooHigh
+            | MessageFailure::InsufficientFunds => ExitCode::SysErrSenderStateInvalid,
+        }
+    }
+}
+
+/// Numerator of the fraction of the gas used that a message can overestimate its gas limit by
+/// without any gas being burned.
+const GAS_OVERUSE_NUM: i64 = 11;
+/// Denominator of the fraction of the gas used that a message can overestimate its gas limit by
+/// without any gas being burned.
+const GAS_OVERUSE_DENOM: i64 = 10;
+
+/// Computes the penalty to the miner who included a message rejected for the given failure,
+/// where `msg_gas_cost` is the gas cost of including the message on chain. The miner pays for
+/// the inclusion of the message at its gas price, as it should have been checked before the
+/// message was packed. Nothing is charged to the sender, since the state of the sender is not
+/// modified.
+///
+/// As in the spec, the inclusion cost is charged for every failure.
+pub fn compute_penalty(
+    failure: MessageFailure,
+    msg: &UnsignedMessage,
+    msg_gas_cost: i64,
+) -> TokenAmount {
+    match failure {
+        MessageFailure::OutOfGas
+        | MessageFailure::SenderMissing
+        | MessageFailure::SenderNotAccount
+        | MessageFailure::SequenceTooLow
+        | MessageFailure::SequenceTooHigh
+        | MessageFailure::InsufficientFunds => msg.gas_price() * msg_gas_cost,
+    }
+}
+
+/// Splits the unused gas of an executed message into the gas refunded to the sender and the
+/// gas burned, returned in that order. Gas limits within 10% of the gas used are refunded in
+/// full, larger overestimations burn a share of the unused gas growing with the overestimation,
+/// up to all of it once the gas limit is over twice the gas used.
+pub fn compute_gas_overestimation_burn(gas_used: i64, gas_limit: i64) -> (i64, i64) {
+    if gas_used == 0 {
+        return (0, gas_limit);
+    }
+    let over = gas_limit - (GAS_OVERUSE_NUM * gas_used) / GAS_OVERUSE_DENOM;
+    if over < 0 {
+        return (gas_limit - gas_used, 0);
+    }
+    let over = over.min(gas_used);
+    // The product overflows an i64 for gas limits of a few billion
+    let gas_to_burn = ((gas_limit - gas_used) as i128 * over as i128 / gas_used as i128) as i64;
+    (gas_limit - gas_used - gas_to_burn, gas_to_burn)
+}
+
+/// Distribution of the gas funds deducted from the sender of an executed message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasOutputs {
+    /// Paid to the reward actor for the gas used, which is awarded to the block miner.
+    pub miner_reward: TokenAmount,
+    /// Burned for the gas limit overestimating the gas used.
+    pub over_estimation_burn: TokenAmount,
+    /// Returned to the sender.
+    pub refund: TokenAmount,
+    /// Amount of gas burned.
+    pub gas_burned: i64,
+}
+
+/// Computes how the gas funds deducted from the sender of an executed message, which are its
+/// gas limit at its gas price, are distributed once the message used `gas_used`. Applies from
+/// `NetworkVersion::V6`, before which all of the unused gas is refunded.
+pub fn compute_gas_outputs(gas_used: i64, gas_limit: i64, gas_price: &TokenAmount) -> GasOutputs {
+    let (gas_refund, gas_burned) = compute_gas_overestimation_burn(gas_used, gas_limit);
+    GasOutputs {
+        miner_reward: gas_price * gas_used,
+        over_estimation_burn: gas_price * gas_burned,
+        refund: gas_price * gas_refund,
+        gas_burned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use address::Address;
+    use num_traits::Zero;
+
+    fn message(gas_price: u64, gas_limit: i64) -> UnsignedMessage {
+        UnsignedMessage::builder()
+            .from(Address::new_id(100))
+            .to(Address::new_id(200))
+            .gas_price(gas_price.into())
+            .gas_limit(gas_limit)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn miner_pays_inclusion_cost() {
+        let msg = message(3, 1000);
+        for &failure in &[
+            MessageFailure::OutOfGas,
+            MessageFailure::SenderMissing,
+            MessageFailure::SenderNotAccount,
+            MessageFailure::SequenceTooLow,
+            MessageFailure::SequenceTooHigh,
+            MessageFailure::InsufficientFunds,
+        ] {
+            assert_eq!(
+                compute_penalty(failure, &msg, 120),
+                TokenAmount::from(360u16),
+                "{:?}",
+                failure
+            );
+        }
+    }
+
+    #[test]
+    fn penalty_independent_of_gas_limit() {
+        // The out of gas penalty is priced on the inclusion cost, which exceeds the gas limit
+        let penalty = compute_penalty(MessageFailure::OutOfGas, &message(2, 10), 50);
+        assert_eq!(penalty, TokenAmount::from(100u8));
+
+        let penalty = compute_penalty(MessageFailure::SequenceTooHigh, &message(0, 1000), 50);
+        assert!(penalty.is_zero());
+    }
+
+    #[test]
+    fn gas_overestimation_burn() {
+        // Vectors from the gas burn tests of Lotus, as (used, limit, refund, burn)
+        let cases: &[(i64, i64, i64, i64)] = &[
+            (100, 200, 10, 90),
+            (100, 150, 30, 20),
+            (1_000, 1_300, 240, 60),
+            (500, 700, 140, 60),
+            (200, 200, 0, 0),
+            (20_000, 21_000, 1_000, 0),
+            (0, 2_000, 0, 2_000),
+            (500, 651, 121, 30),
+            (500, 5_000, 0, 4_500),
+            (7_499_000_000, 7_500_000_000, 1_000_000, 0),
+            (3_750_000_000, 7_500_000_000, 375_000_000, 3_375_000_000),
+            (1, 7_500_000_000, 0, 7_499_999_999),
+        ];
+        for &(used, limit, refund, burn) in cases {
+            assert_eq!(
+                compute_gas_overestimation_burn(used, limit),
+                (refund, burn),
+                "used: {}, limit: {}",
+                used,
+                limit
+            );
+        }
+    }
+
+    #[test]
+    fn gas_outputs_cover_gas_limit() {
+        let gas_price = TokenAmount::from(3u8);
+        for &(used, limit) in &[(0, 100), (100, 105), (100, 150), (100, 1000), (100, 100)] {
+            let out = compute_gas_outputs(used, limit, &gas_price);
+            assert_eq!(
+                &out.miner_reward + &out.over_estimation_burn + &out.refund,
+                &gas_price * limit
+            );
+            assert_eq!(out.over_estimation_burn, &gas_price * out.gas_burned);
+        }
+
+        let out = compute_gas_outputs(100, 150, &gas_price);
+        assert_eq!(out.miner_reward, TokenAmount::from(300u16));
+        assert_eq!(out.over_estimation_burn, TokenAmount::from(60u8));
+        assert_eq!(out.refund, TokenAmount::from(90u8));
+    }
+
+    #[test]
+    fn failure_exit_codes() {
+        assert_eq!(
+            MessageFailure::OutOfGas.exit_code(),
+            ExitCode::SysErrOutOfGas
+        );
+        assert_eq!(
+            MessageFailure::SenderMissing.exit_code(),
+            ExitCode::SysErrSenderInvalid
+        );
+        assert_eq!(
+            MessageFailure::SequenceTooLow.exit_code(),
+            ExitCode::SysErrSenderStateInvalid
+        );
+        assert_eq!(
+            MessageFailure::InsufficientFunds.exit_code(),
+            ExitCode::SysErrSenderStateInvalid
+        );
+    }
+}
@@ -1,26 +1,33 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{gas_tracker::price_list_by_epoch, vm_send, ChainRand, DefaultRuntime};
+use super::{
+    compute_gas_outputs, compute_penalty, gas_tracker::price_list_by_epoch, vm_send, ChainRand,
+    DefaultRuntime, DefaultSyscalls, GasCharge, GasOutputs, MessageFailure,
+};
 use actor::{
-    cron, reward, ACCOUNT_ACTOR_CODE_ID, CRON_ACTOR_ADDR, REWARD_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    cron, reward, ACCOUNT_ACTOR_CODE_ID, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR,
+    REWARD_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
+use address::Address;
 use blocks::FullTipset;
 use cid::Cid;
 use clock::ChainEpoch;
-use fil_types::NetworkParams;
-use forest_encoding::Cbor;
-use ipld_blockstore::BlockStore;
+use fil_types::{NetworkParams, NetworkVersion};
+use forest_encoding::{Cbor, Error as EncodingError};
+use ipld_amt::Amt;
+use ipld_blockstore::{BlockStore, BufferedBlockStore, StoreStats, TrackingBlockStore};
 use log::warn;
-use message::{Message, MessageReceipt, UnsignedMessage};
-use num_bigint::BigInt;
+use message::{Message, MessageReceipt, SignedMessage, UnsignedMessage};
 use num_traits::Zero;
 use runtime::Syscalls;
 use state_tree::StateTree;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::error::Error as StdError;
+use std::fmt;
 use std::marker::PhantomData;
-use vm::{actor_error, ActorError, ExitCode, Serialized};
+use std::time::{Duration, Instant};
+use vm::{ActorError, ExitCode, Serialized, TokenAmount};
 
 /// Interpreter which handles execution of state transitioning messages and returns receipts
 /// from the vm execution.
@@ -30,6 +37,12 @@ pub struct VM<'db, 'r, DB, SYS, P> {
     epoch: ChainEpoch,
     syscalls: SYS,
     rand: &'r ChainRand,
+    deadline: ExecutionDeadline,
+    tipset_deadline: Option<Instant>,
+    max_state_size: Option<usize>,
+    config: VMConfig,
+    /// Gas charges of the last applied message, if gas tracing is enabled.
+    gas_trace: Vec<GasCharge>,
     params: PhantomData<P>,
 }
 
@@ -45,6 +58,7 @@ where
         epoch: ChainEpoch,
         syscalls: SYS,
         rand: &'r ChainRand,
+        config: VMConfig,
     ) -> Result<Self, String> {
         let state = StateTree::new_from_root(store, root)?;
         Ok(VM {
@@ -53,10 +67,51 @@ where
             epoch,
             syscalls,
             rand,
+            deadline: ExecutionDeadline::default(),
+            tipset_deadline: None,
+            max_state_size: None,
+            config,
+            gas_trace: Vec::new(),
             params: PhantomData,
         })
     }
 
+    /// Sets the wall clock budget for executing messages, no deadline is set by default.
+    pub fn set_execution_deadline(&mut self, deadline: ExecutionDeadline) {
+        self.deadline = deadline;
+    }
+
+    /// Sets the maximum serialized size of each actor's head state. Messages which commit a
+    /// larger state fail with `ErrIllegalState`, no maximum is enforced by default.
+    pub fn set_max_state_size(&mut self, max_state_size: Option<usize>) {
+        self.max_state_size = max_state_size;
+    }
+
+    /// Returns the configuration of the VM.
+    pub fn config(&self) -> &VMConfig {
+        &self.config
+    }
+
+    /// Enables recording the gas charges made when applying each message, disabled by default.
+    pub fn set_gas_tracing(&mut self, enabled: bool) {
+        self.config.tracing = enabled;
+    }
+
+    /// Returns the gas charges made when applying the last message, which are only recorded
+    /// if gas tracing is enabled.
+    pub fn take_gas_trace(&mut self) -> Vec<GasCharge> {
+        std::mem::take(&mut self.gas_trace)
+    }
+
+    /// Returns the deadline for a message starting execution now.
+    fn message_deadline(&self) -> Option<Instant> {
+        let msg_deadline = self.deadline.per_message.map(|d| Instant::now() + d);
+        match (msg_deadline, self.tipset_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
     /// Flush stores in VM and return state root.
     pub fn flush(&mut self) -> Result<Cid, String> {
         self.state.flush()
@@ -75,46 +130,42 @@ where
         mut callback: Option<impl FnMut(Cid, UnsignedMessage, ApplyRet) -> Result<(), String>>,
     ) -> Result<Vec<MessageReceipt>, Box<dyn StdError>> {
         let mut receipts = Vec::new();
-        let mut processed = HashSet::<Cid>::default();
+        self.tipset_deadline = self.deadline.per_tipset.map(|d| Instant::now() + d);
 
-        for block in tipset.blocks() {
-            let mut penalty = BigInt::zero();
-            let mut gas_reward = BigInt::zero();
+        for block in tipset_block_messages(tipset)? {
+            let mut penalty = TokenAmount::zero();
+            let mut gas_reward = TokenAmount::zero();
 
-            let mut process_msg = |msg: &UnsignedMessage| -> Result<(), Box<dyn StdError>> {
-                let cid = msg.cid()?;
-                // Ensure no duplicate processing of a message
-                if processed.contains(&cid) {
-                    return Ok(());
+            for (cid, msg) in block.messages {
+                if deadline_passed(self.tipset_deadline) {
+                    return Err(Box::new(DeadlineExceeded));
                 }
                 let ret = self.apply_message(msg)?;
 
                 // Update totals
                 gas_reward += msg.gas_price() * ret.msg_receipt.gas_used;
-                penalty += ret.penalty;
-                receipts.push(ret.msg_receipt);
+                penalty += &ret.penalty;
+                receipts.push(ret.msg_receipt.clone());
 
-                // Add callback here if needed in future
+                if let Some(callback) = &mut callback {
+                    callback(cid, msg.clone(), ret)?;
+                }
+            }
 
-                // Add processed Cid to set of processed messages
-                processed.insert(cid);
-                Ok(())
+            // Blocks are paid for each of their wins from the network version, before which
+            // every block is paid a single share regardless of its election proof
+            let win_count = if P::network_version(self.epoch) >= NetworkVersion::V2 {
+                block.win_count
+            } else {
+                1
             };
 
-            for msg in block.bls_msgs() {
-                process_msg(msg)?;
-            }
-            for msg in block.secp_msgs() {
-                process_msg(msg.message())?;
-            }
-
             // Generate reward transaction for the miner of the block
             let params = Serialized::serialize(reward::AwardBlockRewardParams {
-                miner: *block.header().miner_address(),
+                miner: block.miner,
                 penalty,
                 gas_reward,
-                // TODO revisit this if/when removed from go clients
-                ticket_count: 1,
+                win_count,
             })?;
 
             // TODO change this just just one get and update sequence in memory after interop
@@ -127,11 +178,11 @@ where
                 .from(*SYSTEM_ACTOR_ADDR)
                 .to(*REWARD_ACTOR_ADDR)
                 .sequence(sys_act.sequence)
-                .value(BigInt::zero())
-                .gas_price(BigInt::zero())
+                .value(TokenAmount::zero())
+                .gas_price(TokenAmount::zero())
                 .gas_limit(1 << 30)
                 .params(params)
-                .method_num(reward::Method::AwardBlockReward as u64)
+                .method_num(reward::Method::AwardBlockReward.into())
                 .build()?;
 
             // TODO revisit this ApplyRet structure, doesn't match go logic 1:1 and can be cleaner
@@ -139,8 +190,7 @@ where
             if let Some(err) = ret.act_error {
                 return Err(format!(
                     "failed to apply reward message for miner {}: {}",
-                    block.header().miner_address(),
-                    err
+                    block.miner, err
                 )
                 .into());
             }
@@ -160,10 +210,10 @@ where
             .from(*SYSTEM_ACTOR_ADDR)
             .to(*CRON_ACTOR_ADDR)
             .sequence(sys_act.sequence)
-            .value(BigInt::zero())
-            .gas_price(BigInt::zero())
+            .value(TokenAmount::zero())
+            .gas_price(TokenAmount::zero())
             .gas_limit(1 << 30)
-            .method_num(cron::Method::EpochTick as u64)
+            .method_num(cron::Method::EpochTick.into())
             .params(Serialized::default())
             .build()?;
 
@@ -175,11 +225,14 @@ where
         if let Some(mut callback) = callback {
             callback(cron_msg.cid()?, cron_msg, ret)?;
         }
+        self.tipset_deadline = None;
         Ok(receipts)
     }
 
     pub fn apply_implicit_message(&mut self, msg: &UnsignedMessage) -> ApplyRet {
-        let (return_data, _, act_err) = self.send(msg, None);
+        let (return_data, rt, act_err) = self.send(msg, None, None);
+        let gas_trace = rt.map(|mut rt| rt.take_gas_trace()).unwrap_or_default();
+        self.gas_trace = gas_trace;
 
         ApplyRet {
             msg_receipt: MessageReceipt {
@@ -192,98 +245,108 @@ where
                 gas_used: 0,
             },
             act_error: None,
-            penalty: BigInt::zero(),
+            penalty: TokenAmount::zero(),
         }
     }
 
     /// Applies the state transition for a single message
     /// Returns ApplyRet structure which contains the message receipt and some meta data.
-    fn apply_message(&mut self, msg: &UnsignedMessage) -> Result<ApplyRet, String> {
+    ///
+    /// Returns `DeadlineExceeded` if the execution deadline passes while applying the message,
+    /// regardless of the outcome of the execution.
+    pub fn apply_message(&mut self, msg: &UnsignedMessage) -> Result<ApplyRet, Box<dyn StdError>> {
         check_message(msg)?;
+        self.gas_trace.clear();
 
         let pl = price_list_by_epoch(self.epoch());
         let ser_msg = &msg.marshal_cbor().map_err(|e| e.to_string())?;
         let msg_gas_cost = pl.on_chain_message(ser_msg.len());
 
+        // Rejected messages are not executed, and the miner is penalized for including them
+        let reject = |failure: MessageFailure, msg_err: String| ApplyRet {
+            msg_receipt: MessageReceipt {
+                return_data: Serialized::default(),
+                exit_code: failure.exit_code(),
+                gas_used: 0,
+            },
+            act_error: Some(ActorError::new(failure.exit_code(), msg_err)),
+            penalty: compute_penalty(failure, msg, msg_gas_cost),
+        };
+
         if msg_gas_cost > msg.gas_limit() {
-            return Ok(ApplyRet {
-                msg_receipt: MessageReceipt {
-                    return_data: Serialized::default(),
-                    exit_code: ExitCode::SysErrOutOfGas,
-                    gas_used: 0,
-                },
-                act_error: Some(actor_error!(SysErrOutOfGas;
-                    "Out of gas ({} > {})", msg_gas_cost, msg.gas_limit())),
-                penalty: msg.gas_price() * msg_gas_cost,
-            });
+            return Ok(reject(
+                MessageFailure::OutOfGas,
+                format!("Out of gas ({} > {})", msg_gas_cost, msg.gas_limit()),
+            ));
         }
 
-        let miner_penalty_amount = msg.gas_price() * msg_gas_cost;
-        let mut from_act = match self.state.get_actor(msg.from()) {
-            Ok(from_act) => from_act.ok_or("Failed to retrieve actor state")?,
-            Err(_) => {
-                return Ok(ApplyRet {
-                    msg_receipt: MessageReceipt {
-                        return_data: Serialized::default(),
-                        exit_code: ExitCode::SysErrSenderInvalid,
-                        gas_used: 0,
-                    },
-                    penalty: msg.gas_price() * msg_gas_cost,
-                    act_error: Some(actor_error!(SysErrSenderInvalid; "Sender invalid")),
-                });
+        let from_act = match self.state.get_actor(msg.from()) {
+            Ok(Some(from_act)) => from_act,
+            Ok(None) => {
+                return Ok(reject(
+                    MessageFailure::SenderMissing,
+                    format!("sender {} not found", msg.from()),
+                ));
+            }
+            Err(e) => {
+                return Ok(reject(
+                    MessageFailure::SenderMissing,
+                    format!("failed to load sender {}: {}", msg.from(), e),
+                ));
             }
         };
 
         if from_act.code != *ACCOUNT_ACTOR_CODE_ID {
-            return Ok(ApplyRet {
-                msg_receipt: MessageReceipt {
-                    return_data: Serialized::default(),
-                    exit_code: ExitCode::SysErrSenderInvalid,
-                    gas_used: 0,
-                },
-                penalty: miner_penalty_amount,
-                act_error: Some(actor_error!(SysErrSenderInvalid; "send not from account actor")),
-            });
+            return Ok(reject(
+                MessageFailure::SenderNotAccount,
+                "send not from account actor".to_owned(),
+            ));
         };
 
         // TODO revisit if this is removed in future
         if msg.sequence() != from_act.sequence {
-            return Ok(ApplyRet {
-                msg_receipt: MessageReceipt {
-                    return_data: Serialized::default(),
-                    exit_code: ExitCode::SysErrSenderStateInvalid,
-                    gas_used: 0,
-                },
-                penalty: miner_penalty_amount,
-                act_error: Some(actor_error!(SysErrSenderStateInvalid;
-                    "actor sequence invalid: {} != {}", msg.sequence(), from_act.sequence)),
-            });
+            let failure = if msg.sequence() < from_act.sequence {
+                MessageFailure::SequenceTooLow
+            } else {
+                MessageFailure::SequenceTooHigh
+            };
+            return Ok(reject(
+                failure,
+                format!(
+                    "actor sequence invalid: {} != {}",
+                    msg.sequence(),
+                    from_act.sequence
+                ),
+            ));
         };
 
         let gas_cost = msg.gas_price() * msg.gas_limit();
         let total_cost = &gas_cost + msg.value();
         if from_act.balance < total_cost {
-            return Ok(ApplyRet {
-                msg_receipt: MessageReceipt {
-                    return_data: Serialized::default(),
-                    exit_code: ExitCode::SysErrSenderStateInvalid,
-                    gas_used: 0,
-                },
-                penalty: miner_penalty_amount,
-                act_error: Some(actor_error!(SysErrSenderStateInvalid;
-                    "actor balance less than needed: {} < {}", from_act.balance, total_cost)),
-            });
+            return Ok(reject(
+                MessageFailure::InsufficientFunds,
+                format!(
+                    "actor balance less than needed: {} < {}",
+                    from_act.balance, total_cost
+                ),
+            ));
         };
 
         self.state.mutate_actor(msg.from(), |act| {
-            from_act.deduct_funds(&gas_cost)?;
+            act.deduct_funds(&gas_cost)?;
             act.sequence += 1;
             Ok(())
         })?;
 
         let snapshot = self.state.snapshot()?;
 
-        let (mut ret_data, rt, mut act_err) = self.send(msg, Some(msg_gas_cost));
+        let deadline = self.message_deadline();
+        let (mut ret_data, rt, mut act_err) = self.send(msg, Some(msg_gas_cost), deadline);
+        if deadline_passed(deadline) {
+            // Actors may have handled the abort as a regular error, so the result of the
+            // execution can't be trusted.
+            return Err(Box::new(DeadlineExceeded));
+        }
         if let Some(err) = &act_err {
             if err.is_fatal() {
                 return Err(format!(
@@ -294,7 +357,8 @@ where
                     msg.method_num(),
                     self.epoch,
                     err
-                ));
+                )
+                .into());
             } else {
                 warn!(
                     "[from={}, to={}, seq={}, m={}] send error: {}",
@@ -308,28 +372,27 @@ where
                     return Err(format!(
                         "message invocation errored, but had a return value anyway: {}",
                         err
-                    ));
+                    )
+                    .into());
                 }
             }
         }
 
-        let gas_used = if let Some(mut rt) = rt {
+        let (gas_used, gas_trace) = if let Some(mut rt) = rt {
             if !ret_data.is_empty() {
-                if let Err(e) = rt.charge_gas(rt.price_list().on_chain_return_value(ret_data.len()))
-                {
+                if let Err(e) = rt.charge_return_value(ret_data.len()) {
                     act_err = Some(e);
                     ret_data = Serialized::default();
                 }
             }
-            if rt.gas_used() < 0 {
-                0
-            } else {
-                rt.gas_used()
-            }
+            let gas_used = if rt.gas_used() < 0 { 0 } else { rt.gas_used() };
+            (gas_used, rt.take_gas_trace())
         } else {
-            return Err(format!("send returned None runtime: {:?}", act_err));
+            return Err(format!("send returned None runtime: {:?}", act_err).into());
         };
 
+        self.gas_trace = gas_trace;
+
         if let Some(err) = &act_err {
             if !err.is_ok() {
                 // Revert all state changes on error.
@@ -337,41 +400,60 @@ where
             }
         }
 
-        // refund unused gas
-        let refund = (msg.gas_limit() - gas_used) * msg.gas_price();
+        let gas_outputs = if P::network_version(self.epoch) >= NetworkVersion::V6 {
+            // Refund the unused gas, minus the burn for overestimating the gas limit
+            compute_gas_outputs(gas_used, msg.gas_limit(), msg.gas_price())
+        } else {
+            // Refund all of the unused gas
+            GasOutputs {
+                miner_reward: msg.gas_price() * gas_used,
+                over_estimation_burn: TokenAmount::zero(),
+                refund: msg.gas_price() * (msg.gas_limit() - gas_used),
+                gas_burned: 0,
+            }
+        };
         self.state.mutate_actor(msg.from(), |act| {
-            act.deposit_funds(&refund);
+            act.deposit_funds(&gas_outputs.refund);
             Ok(())
         })?;
 
-        let gas_reward = msg.gas_price() * BigInt::from(gas_used);
+        if !gas_outputs.over_estimation_burn.is_zero() {
+            self.state.mutate_actor(&*BURNT_FUNDS_ACTOR_ADDR, |act| {
+                act.deposit_funds(&gas_outputs.over_estimation_burn);
+                Ok(())
+            })?;
+        }
+
         self.state.mutate_actor(&*REWARD_ACTOR_ADDR, |act| {
-            act.deposit_funds(&gas_reward);
+            act.deposit_funds(&gas_outputs.miner_reward);
             Ok(())
         })?;
 
-        if refund + gas_reward != gas_cost {
-            return Err("Gas handling math is wrong".to_owned());
+        if gas_outputs.refund + gas_outputs.over_estimation_burn + gas_outputs.miner_reward
+            != gas_cost
+        {
+            return Err("Gas handling math is wrong".into());
         }
 
         Ok(ApplyRet {
             msg_receipt: MessageReceipt {
                 return_data: ret_data,
-                exit_code: ExitCode::Ok,
+                exit_code: act_err.as_ref().map_or(ExitCode::Ok, ActorError::exit_code),
                 gas_used,
             },
-            penalty: BigInt::zero(),
-            act_error: None,
+            penalty: TokenAmount::zero(),
+            act_error: act_err,
         })
     }
     /// Instantiates a new Runtime, and calls internal_send to do the execution.
-    fn send<'m>(
+    fn send(
         &mut self,
-        msg: &'m UnsignedMessage,
+        msg: &UnsignedMessage,
         gas_cost: Option<i64>,
+        deadline: Option<Instant>,
     ) -> (
         Serialized,
-        Option<DefaultRuntime<'db, 'm, '_, '_, '_, DB, SYS, P>>,
+        Option<DefaultRuntime<'db, '_, '_, '_, DB, SYS, P>>,
         Option<ActorError>,
     ) {
         let res = DefaultRuntime::new(
@@ -388,21 +470,174 @@ where
         );
 
         match res {
-            Ok(mut rt) => match vm_send(&mut rt, msg, gas_cost) {
-                Ok(ser) => (ser, Some(rt), None),
-                Err(actor_err) => (Serialized::default(), Some(rt), Some(actor_err)),
-            },
+            Ok(mut rt) => {
+                if self.config.tracing {
+                    rt.enable_gas_tracing();
+                }
+                if self.config.actor_debugging {
+                    rt.enable_actor_debugging();
+                }
+                rt.set_deadline(deadline);
+                rt.set_max_state_size(self.max_state_size);
+                match vm_send(&mut rt, msg, gas_cost) {
+                    Ok(ser) => (ser, Some(rt), None),
+                    Err(actor_err) => (Serialized::default(), Some(rt), Some(actor_err)),
+                }
+            }
             Err(e) => (Serialized::default(), None, Some(e)),
         }
     }
 }
 
+impl<'db, 'r, 'bs, BS, SYS, P> VM<'db, 'r, TrackingBlockStore<'bs, BS>, SYS, P>
+where
+    BS: BlockStore,
+    SYS: Syscalls,
+    P: NetworkParams,
+{
+    /// Applies a single message and returns the IO made to the store by its execution, used
+    /// to compare the gas charged for a message against the reads and writes it performs.
+    /// Writes to actors in the state tree are buffered until the state is flushed, so they
+    /// are not included in the stats of a message.
+    pub fn apply_message_tracked(
+        &mut self,
+        msg: &UnsignedMessage,
+    ) -> Result<(ApplyRet, StoreStats), Box<dyn StdError>> {
+        self.store.reset_stats();
+        let ret = self.apply_message(msg)?;
+        Ok((ret, self.store.stats()))
+    }
+}
+
+/// Configuration of the VM. The configuration only changes what is recorded while messages are
+/// applied, never the result of applying them, so it can differ between nodes.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct VMConfig {
+    /// Records the gas charges made when applying each message.
+    pub tracing: bool,
+    /// Emits the debug logs of actors, which are ignored otherwise.
+    pub actor_debugging: bool,
+}
+
+/// Wall clock budget for executing messages in the VM. Exceeding it aborts execution with
+/// `DeadlineExceeded`, which is local to the node and doesn't make the messages invalid.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ExecutionDeadline {
+    /// Budget for the execution of each message.
+    pub per_message: Option<Duration>,
+    /// Budget for applying all messages of a tipset.
+    pub per_tipset: Option<Duration>,
+}
+
+/// Error returned when execution is aborted because the `ExecutionDeadline` passed. The state
+/// of the VM is left partially applied and must be discarded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeadlineExceeded;
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "execution deadline exceeded")
+    }
+}
+
+impl StdError for DeadlineExceeded {}
+
+fn deadline_passed(deadline: Option<Instant>) -> bool {
+    deadline.map_or(false, |d| Instant::now() >= d)
+}
+
 /// Apply message return data
 #[derive(Clone)]
 pub struct ApplyRet {
     pub msg_receipt: MessageReceipt,
     pub act_error: Option<ActorError>,
-    pub penalty: BigInt,
+    pub penalty: TokenAmount,
+}
+
+/// Applies the messages of a tipset on top of its parent state, with the implicit reward and
+/// cron messages, and returns the new state root, the root of the receipts of the messages and
+/// the receipts.
+///
+/// The state is written to a buffered store, and only the blocks reachable from the new state
+/// root are flushed to the store. The receipts are written to the store directly.
+pub fn apply_block_messages<DB, P>(
+    store: &DB,
+    tipset: &FullTipset,
+    rand: &ChainRand,
+    callback: Option<impl FnMut(Cid, UnsignedMessage, ApplyRet) -> Result<(), String>>,
+) -> Result<(Cid, Cid, Vec<MessageReceipt>), Box<dyn StdError>>
+where
+    DB: BlockStore,
+    P: NetworkParams,
+{
+    let mut buf_store = BufferedBlockStore::new(store);
+    let mut vm = VM::<_, _, P>::new(
+        tipset.parent_state(),
+        &buf_store,
+        tipset.epoch(),
+        DefaultSyscalls::new(&buf_store),
+        rand,
+        VMConfig::default(),
+    )?;
+
+    let receipts = vm.apply_tipset_messages(tipset, callback)?;
+    let receipts_root = Amt::new_from_slice(store, &receipts)?;
+
+    let state_root = vm.flush()?;
+    buf_store.flush(&state_root)?;
+
+    Ok((state_root, receipts_root, receipts))
+}
+
+/// Messages to apply for a block of a tipset.
+pub struct BlockMessages<'a> {
+    /// Miner of the block, which is rewarded for including the messages.
+    pub miner: Address,
+    /// Messages paired with their Cid, BLS messages followed by secp messages.
+    pub messages: Vec<(Cid, &'a UnsignedMessage)>,
+    /// Number of wins of the miner in the epoch's election, zero if the block has no election
+    /// proof or its proof has no win count.
+    pub win_count: u64,
+}
+
+/// Returns the messages to apply for each block of the tipset, in block order.
+///
+/// The same message can be included by multiple blocks of a tipset, only the first inclusion
+/// is applied. A block is still returned if all of its messages were already included, so its
+/// miner receives the block reward.
+pub fn tipset_block_messages(tipset: &FullTipset) -> Result<Vec<BlockMessages<'_>>, EncodingError> {
+    // Messages are deduplicated by their quick hash, so the Cid is only computed for the
+    // messages applied. The hash isn't collision resistant, messages with the same hash are
+    // compared in full.
+    let mut processed = HashMap::<u64, Vec<&UnsignedMessage>>::default();
+    let mut blocks = Vec::with_capacity(tipset.blocks().len());
+
+    for block in tipset.blocks() {
+        let mut messages = Vec::new();
+        let msgs = block
+            .bls_msgs()
+            .iter()
+            .chain(block.secp_msgs().iter().map(SignedMessage::message));
+        for msg in msgs {
+            // Ensure no duplicate processing of a message
+            let same_hash = processed.entry(msg.quick_hash()?).or_default();
+            if !same_hash.contains(&msg) {
+                same_hash.push(msg);
+                messages.push((msg.cid()?, msg));
+            }
+        }
+        blocks.push(BlockMessages {
+            miner: *block.header().miner_address(),
+            messages,
+            win_count: block
+                .header()
+                .election_proof()
+                .as_ref()
+                .and_then(|proof| proof.win_count)
+                .unwrap_or(0),
+        });
+    }
+    Ok(blocks)
 }
 
 /// Does some basic checks on the Message to see if the fields are valid.
@@ -413,10 +648,10 @@ fn check_message(msg: &UnsignedMessage) -> Result<(), &'static str> {
     if msg.gas_limit() < 0 {
         return Err("Message has negative gas limit");
     }
-    if msg.value() == &BigInt::zero() {
+    if msg.value() == &TokenAmount::zero() {
         return Err("Message has no value set");
     }
-    if msg.gas_price() == &BigInt::zero() {
+    if msg.gas_price() == &TokenAmount::zero() {
         return Err("Message has no gas price set");
     }
 
@@ -0,0 +1,418 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use actor::{
+    account, cron, init, miner, reward, ACCOUNT_ACTOR_CODE_ID, BURNT_FUNDS_ACTOR_ADDR,
+    CRON_ACTOR_ADDR, CRON_ACTOR_CODE_ID, INIT_ACTOR_ADDR, INIT_ACTOR_CODE_ID, MARKET_ACTOR_CODE_ID,
+    MINER_ACTOR_CODE_ID, POWER_ACTOR_CODE_ID, REWARD_ACTOR_ADDR, REWARD_ACTOR_CODE_ID,
+    STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, SYSTEM_ACTOR_CODE_ID,
+};
+use address::{Address, SECP_PUB_LEN};
+use blocks::{Block, BlockHeader, FullTipset, TipsetKeys};
+use cid::{multihash::Blake2b256, Cid};
+use clock::ChainEpoch;
+use crypto::{Signature, SignatureError};
+use db::MemoryDB;
+use fil_types::{DevnetParams, NetworkParams, NetworkVersion, RegisteredSealProof, SealVerifyInfo};
+use forest_encoding::{de::DeserializeOwned, ser::Serialize};
+use interpreter::{
+    apply_block_messages, ApplyRet, ChainRand, DefaultRuntime, DefaultSyscalls, GasCharge,
+    VMConfig, VM,
+};
+use ipld_amt::Amt;
+use ipld_blockstore::BlockStore;
+use ipld_hamt::Hamt;
+use message::{Message, MessageReceipt, UnsignedMessage};
+use runtime::{ConsensusFault, LogLevel, Syscalls};
+use state_tree::StateTree;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use vm::{
+    ActorState, ExitCode, MethodNum, Serialized, TokenAmount, EMPTY_ARR_CID, METHOD_CONSTRUCTOR,
+};
+
+/// Gas price of the messages sent by the harness.
+pub const GAS_PRICE: u64 = 1;
+/// Gas limit of the messages sent by the harness.
+pub const GAS_LIMIT: i64 = 1_000_000_000;
+/// Balance of the reward actor, which covers the block rewards of a few epochs.
+const REWARD_BALANCE: u64 = 10_000_000_000_000_000_000;
+
+/// Network parameters of `DevnetParams` with every network upgrade in effect.
+#[allow(dead_code)]
+pub struct UpgradedParams;
+impl NetworkParams for UpgradedParams {
+    const TOTAL_FILECOIN: i64 = DevnetParams::TOTAL_FILECOIN;
+    const MINING_REWARD_TOTAL: i64 = DevnetParams::MINING_REWARD_TOTAL;
+
+    fn network_version(_epoch: ChainEpoch) -> NetworkVersion {
+        NetworkVersion::V6
+    }
+}
+
+/// Syscalls which accept all signatures, so vouchers and deal proposals don't have to be
+/// signed with real keys.
+pub struct TestSyscalls;
+impl Syscalls for TestSyscalls {
+    fn log(&self, level: LogLevel, msg: &str) {
+        println!("{}: {}", level, msg);
+    }
+    fn verify_signature(
+        &self,
+        _signature: &Signature,
+        _signer: &Address,
+        _plaintext: &[u8],
+    ) -> Result<(), SignatureError> {
+        Ok(())
+    }
+    fn verify_consensus_fault(
+        &self,
+        _h1: &[u8],
+        _h2: &[u8],
+        _extra: &[u8],
+    ) -> Result<Option<ConsensusFault>, Box<dyn StdError>> {
+        Ok(None)
+    }
+    fn batch_verify_seals(
+        &self,
+        _vis: &[(Address, Vec<SealVerifyInfo>)],
+    ) -> Result<HashMap<Address, Vec<bool>>, Box<dyn StdError>> {
+        Ok(HashMap::new())
+    }
+}
+
+/// Chain state which messages are applied to by the interpreter one at a time, at the
+/// current epoch of the harness.
+pub struct Harness {
+    pub store: MemoryDB,
+    root: Cid,
+    pub epoch: ChainEpoch,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    /// Sets up the singleton actors used by the payment channel and market actors and to apply
+    /// the implicit messages of blocks, and constructs the market and power actors.
+    pub fn new() -> Self {
+        let store = MemoryDB::default();
+        let root = StateTree::new(&store).flush().unwrap();
+        let mut h = Harness {
+            store,
+            root,
+            epoch: 0,
+        };
+
+        let address_map = Hamt::<String, _>::new_with_bit_width(&h.store, 5)
+            .flush()
+            .unwrap();
+        let init_state = init::State::new(address_map, "scenario".to_owned());
+        let init_head = h.put(&init_state);
+        h.set_actor(&INIT_ACTOR_ADDR, &INIT_ACTOR_CODE_ID, init_head, 0u8.into());
+
+        let burnt_head = h.put(&account::State {
+            address: *BURNT_FUNDS_ACTOR_ADDR,
+        });
+        h.set_actor(
+            &BURNT_FUNDS_ACTOR_ADDR,
+            &ACCOUNT_ACTOR_CODE_ID,
+            burnt_head,
+            0u8.into(),
+        );
+
+        // Only the balance of the system actor is used, its state is never loaded.
+        let empty = EMPTY_ARR_CID.clone();
+        h.set_actor(
+            &SYSTEM_ACTOR_ADDR,
+            &SYSTEM_ACTOR_CODE_ID,
+            empty.clone(),
+            0u8.into(),
+        );
+
+        let reward_head = h.put(&reward::State::new(NetworkVersion::V0));
+        h.set_actor(
+            &REWARD_ACTOR_ADDR,
+            &REWARD_ACTOR_CODE_ID,
+            reward_head,
+            REWARD_BALANCE.into(),
+        );
+        let cron_head = h.put(&cron::State {
+            entries: Vec::new(),
+        });
+        h.set_actor(&CRON_ACTOR_ADDR, &CRON_ACTOR_CODE_ID, cron_head, 0u8.into());
+
+        for (addr, code) in vec![
+            (*STORAGE_MARKET_ACTOR_ADDR, MARKET_ACTOR_CODE_ID.clone()),
+            (*STORAGE_POWER_ACTOR_ADDR, POWER_ACTOR_CODE_ID.clone()),
+        ] {
+            h.set_actor(&addr, &code, empty.clone(), 0u8.into());
+            let ret = h.apply_implicit(
+                *SYSTEM_ACTOR_ADDR,
+                addr,
+                METHOD_CONSTRUCTOR,
+                Serialized::default(),
+            );
+            assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+        }
+
+        h
+    }
+
+    /// Adds an account actor with a secp256k1 key address and returns its ID address.
+    pub fn add_account(&mut self, id: u64, balance: TokenAmount) -> Address {
+        let addr = Address::new_id(id);
+        let head = self.put(&account::State {
+            address: Address::new_secp256k1(&[id as u8; SECP_PUB_LEN]).unwrap(),
+        });
+        self.set_actor(&addr, &ACCOUNT_ACTOR_CODE_ID, head, balance);
+        addr
+    }
+
+    /// Adds a miner actor without any sectors and returns its ID address.
+    pub fn add_miner(&mut self, id: u64, owner: Address, worker: Address) -> Address {
+        let addr = Address::new_id(id);
+        let empty_arr = Amt::<u64, _>::new(&self.store).flush().unwrap();
+        let empty_map = Hamt::<String, _>::new_with_bit_width(&self.store, 5)
+            .flush()
+            .unwrap();
+        let deadlines = self.put(&miner::Deadlines::new());
+        let st = miner::State::new(
+            empty_arr,
+            empty_map,
+            deadlines,
+            owner,
+            worker,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            RegisteredSealProof::StackedDRG2KiBV1,
+            0,
+        )
+        .unwrap();
+        let head = self.put(&st);
+        self.set_actor(&addr, &MINER_ACTOR_CODE_ID, head, 0u8.into());
+        addr
+    }
+
+    /// Applies a message sent by an account actor, with the sequence of the account.
+    pub fn send(
+        &mut self,
+        from: Address,
+        to: Address,
+        method: MethodNum,
+        params: Serialized,
+        value: TokenAmount,
+    ) -> ApplyRet {
+        self.send_traced(from, to, method, params, value).0
+    }
+
+    /// Applies a message sent by an account actor like `send`, and returns the gas charges
+    /// made when applying it.
+    pub fn send_traced(
+        &mut self,
+        from: Address,
+        to: Address,
+        method: MethodNum,
+        params: Serialized,
+        value: TokenAmount,
+    ) -> (ApplyRet, Vec<GasCharge>) {
+        let msg = UnsignedMessage::builder()
+            .from(from)
+            .to(to)
+            .sequence(self.actor(&from).sequence)
+            .method_num(method)
+            .params(params)
+            .value(value)
+            .gas_price(GAS_PRICE.into())
+            .gas_limit(GAS_LIMIT)
+            .build()
+            .unwrap();
+        self.apply_traced(&msg)
+    }
+
+    /// Applies a message as is, which may be rejected before it is executed.
+    pub fn apply(&mut self, msg: &UnsignedMessage) -> ApplyRet {
+        self.apply_traced(msg).0
+    }
+
+    /// Applies a message as is like `apply`, with the given network parameters.
+    #[allow(dead_code)]
+    pub fn apply_with<P: NetworkParams>(&mut self, msg: &UnsignedMessage) -> ApplyRet {
+        self.apply_traced_with::<P>(msg).0
+    }
+
+    fn apply_traced(&mut self, msg: &UnsignedMessage) -> (ApplyRet, Vec<GasCharge>) {
+        self.apply_traced_with::<DevnetParams>(msg)
+    }
+
+    fn apply_traced_with<P: NetworkParams>(
+        &mut self,
+        msg: &UnsignedMessage,
+    ) -> (ApplyRet, Vec<GasCharge>) {
+        let rand = ChainRand::new(TipsetKeys::new(vec![]));
+        let mut vm = VM::<_, _, P>::new(
+            &self.root,
+            &self.store,
+            self.epoch,
+            TestSyscalls,
+            &rand,
+            VMConfig {
+                tracing: true,
+                actor_debugging: true,
+            },
+        )
+        .unwrap();
+        let ret = vm.apply_message(msg).unwrap();
+        self.root = vm.flush().unwrap();
+        (ret, vm.take_gas_trace())
+    }
+
+    /// Applies a message without value from any actor, as done for system messages such as
+    /// the cron tick, or to call an actor from a miner actor.
+    pub fn apply_implicit(
+        &mut self,
+        from: Address,
+        to: Address,
+        method: MethodNum,
+        params: Serialized,
+    ) -> ApplyRet {
+        self.apply_implicit_traced(from, to, method, params).0
+    }
+
+    /// Applies a message without value like `apply_implicit`, and returns the gas charges
+    /// made when applying it.
+    pub fn apply_implicit_traced(
+        &mut self,
+        from: Address,
+        to: Address,
+        method: MethodNum,
+        params: Serialized,
+    ) -> (ApplyRet, Vec<GasCharge>) {
+        let msg = UnsignedMessage::builder()
+            .from(from)
+            .to(to)
+            .method_num(method)
+            .params(params)
+            .gas_limit(1 << 30)
+            .build()
+            .unwrap();
+
+        let rand = ChainRand::new(TipsetKeys::new(vec![]));
+        let mut vm = VM::<_, _, DevnetParams>::new(
+            &self.root,
+            &self.store,
+            self.epoch,
+            TestSyscalls,
+            &rand,
+            VMConfig {
+                tracing: true,
+                actor_debugging: true,
+            },
+        )
+        .unwrap();
+        let ret = vm.apply_implicit_message(&msg);
+        self.root = vm.flush().unwrap();
+        (ret, vm.take_gas_trace())
+    }
+
+    /// Applies a tipset of blocks with the given miners and messages on top of the current
+    /// state with `apply_block_messages`, and returns the receipts of the messages applied.
+    /// Blocks have no election proof, as the network version of `DevnetParams` pays every block
+    /// a single share of the epoch reward.
+    pub fn apply_blocks(
+        &mut self,
+        blocks: Vec<(Address, Vec<UnsignedMessage>)>,
+    ) -> Vec<MessageReceipt> {
+        let blocks = blocks
+            .into_iter()
+            .map(|(miner, bls_messages)| Block {
+                header: BlockHeader::builder()
+                    .miner_address(miner)
+                    .state_root(self.root.clone())
+                    .epoch(self.epoch)
+                    .build()
+                    .unwrap(),
+                bls_messages,
+                secp_messages: Vec::new(),
+            })
+            .collect();
+        let tipset = FullTipset::new(blocks).unwrap();
+
+        let rand = ChainRand::new(TipsetKeys::new(vec![]));
+        let (state_root, _, receipts) = apply_block_messages::<_, DevnetParams>(
+            &self.store,
+            &tipset,
+            &rand,
+            None::<fn(Cid, UnsignedMessage, ApplyRet) -> Result<(), String>>,
+        )
+        .unwrap();
+        self.root = state_root;
+        receipts
+    }
+
+    pub fn get_actor(&self, addr: &Address) -> Option<ActorState> {
+        StateTree::new_from_root(&self.store, &self.root)
+            .unwrap()
+            .get_actor(addr)
+            .unwrap()
+    }
+
+    pub fn actor(&self, addr: &Address) -> ActorState {
+        self.get_actor(addr).unwrap()
+    }
+
+    pub fn balance(&self, addr: &Address) -> TokenAmount {
+        self.actor(addr).balance
+    }
+
+    /// Loads the head state of an actor.
+    pub fn state<T: DeserializeOwned>(&self, addr: &Address) -> T {
+        self.store.get(&self.actor(addr).state).unwrap().unwrap()
+    }
+
+    fn put<T: Serialize>(&self, obj: &T) -> Cid {
+        self.store.put(obj, Blake2b256).unwrap()
+    }
+
+    fn set_actor(&mut self, addr: &Address, code: &Cid, head: Cid, balance: TokenAmount) {
+        let mut state = StateTree::new_from_root(&self.store, &self.root).unwrap();
+        state
+            .set_actor(addr, ActorState::new(code.clone(), head, balance, 0))
+            .unwrap();
+        self.root = state.flush().unwrap();
+    }
+}
+
+/// Gas fees paid by the sender of an applied message.
+pub fn gas_cost(ret: &ApplyRet) -> TokenAmount {
+    TokenAmount::from(ret.msg_receipt.gas_used) * GAS_PRICE
+}
+
+/// Creates a runtime for applying `message` on top of `state` at epoch zero, originating from
+/// the sender of the message.
+#[allow(dead_code)]
+pub fn message_runtime<'db, 'st, 'sys, 'r>(
+    state: &'st mut StateTree<'db, MemoryDB>,
+    store: &'db MemoryDB,
+    syscalls: &'sys DefaultSyscalls<'db, MemoryDB>,
+    rand: &'r ChainRand,
+    message: &UnsignedMessage,
+) -> DefaultRuntime<'db, 'st, 'sys, 'r, MemoryDB, DefaultSyscalls<'db, MemoryDB>, DevnetParams> {
+    DefaultRuntime::new(
+        state,
+        store,
+        syscalls,
+        0,
+        message,
+        0,
+        *message.from(),
+        0,
+        0,
+        rand,
+    )
+    .unwrap()
+}
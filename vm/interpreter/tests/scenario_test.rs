@@ -0,0 +1,602 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+mod common;
+
+use actor::{
+    account, init,
+    market::{
+        self,
+        deal_client::{self, DealTerms},
+        ClientDealProposal, OnMinerSectorsTerminateParams, PublishStorageDealsParams,
+        PublishStorageDealsReturn, SectorDeals, VerifyDealsForActivationParams,
+    },
+    paych::{self, SignedVoucher, UpdateChannelStateParams},
+    ACCOUNT_ACTOR_CODE_ID, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, INIT_ACTOR_ADDR,
+    PAYCH_ACTOR_CODE_ID, REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR,
+};
+use address::{Address, SECP_PUB_LEN};
+use commcid::piece_commitment_v1_to_cid;
+use common::*;
+use crypto::Signature;
+use fil_types::{PaddedPieceSize, PieceInfo};
+use interpreter::{compute_gas_overestimation_burn, VMConfig};
+use message::{Message, UnsignedMessage};
+use num_traits::Zero;
+use vm::{ExitCode, MethodNum, Serialized, TokenAmount, METHOD_SEND};
+
+const CLIENT_ID: u64 = 1001;
+const PAYEE_ID: u64 = 1002;
+const OWNER_ID: u64 = 1003;
+const WORKER_ID: u64 = 1004;
+const MINER_ID: u64 = 1005;
+
+const INITIAL_BALANCE: u64 = 1_000_000_000_000;
+
+/// Value sent with messages which don't transfer funds, as the interpreter only applies
+/// messages with a value.
+fn dust() -> TokenAmount {
+    TokenAmount::from(1u8)
+}
+
+fn voucher_params(lane: u64, nonce: u64, amount: u64) -> Serialized {
+    Serialized::serialize(UpdateChannelStateParams {
+        sv: SignedVoucher {
+            lane,
+            nonce,
+            amount: TokenAmount::from(amount),
+            signature: Some(Signature::new_secp256k1(vec![0])),
+            ..Default::default()
+        },
+        secret: Vec::new(),
+        proof: Vec::new(),
+    })
+    .unwrap()
+}
+
+fn exec_paych_params(from: Address, to: Address) -> Serialized {
+    Serialized::serialize(init::ExecParams {
+        code_cid: PAYCH_ACTOR_CODE_ID.clone(),
+        constructor_params: Serialized::serialize(paych::ConstructorParams { from, to }).unwrap(),
+    })
+    .unwrap()
+}
+
+#[test]
+fn paych_redeem_settle_collect() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+
+    // The client creates and funds the channel through the init actor
+    let ret = h.send(
+        client,
+        *INIT_ACTOR_ADDR,
+        init::Method::Exec.into(),
+        exec_paych_params(client, payee),
+        TokenAmount::from(100u8),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    let exec_ret: init::ExecReturn = ret.msg_receipt.return_data.deserialize().unwrap();
+    let ch = exec_ret.id_address;
+    assert_eq!(h.actor(&ch).code, *PAYCH_ACTOR_CODE_ID);
+    assert_eq!(h.balance(&ch), TokenAmount::from(100u8));
+    let st: paych::State = h.state(&ch);
+    assert_eq!((st.from, st.to), (client, payee));
+
+    // The payee redeems vouchers across two lanes, later vouchers of a lane replace the
+    // amount redeemed by earlier ones
+    let payee_balance = h.balance(&payee);
+    let mut payee_paid = TokenAmount::zero();
+    for &(lane, nonce, amount) in &[(0, 1, 10), (1, 1, 15), (0, 2, 25)] {
+        let ret = h.send(
+            payee,
+            ch,
+            paych::Method::UpdateChannelState.into(),
+            voucher_params(lane, nonce, amount),
+            dust(),
+        );
+        assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+        payee_paid += gas_cost(&ret) + dust();
+    }
+    let st: paych::State = h.state(&ch);
+    assert_eq!(st.lane_states.len(), 2);
+    assert_eq!(st.to_send, TokenAmount::from(40u8));
+
+    // A voucher which the channel can't cover fails, and the value sent with it is returned
+    let ret = h.send(
+        payee,
+        ch,
+        paych::Method::UpdateChannelState.into(),
+        voucher_params(2, 1, 1000),
+        dust(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::ErrIllegalState);
+    payee_paid += gas_cost(&ret);
+    let st: paych::State = h.state(&ch);
+    assert_eq!(st.lane_states.len(), 2);
+
+    // The channel can only be collected once settled
+    h.epoch = 10;
+    let ret = h.send(
+        client,
+        ch,
+        paych::Method::Collect.into(),
+        Serialized::default(),
+        dust(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::ErrForbidden);
+
+    let ret = h.send(
+        client,
+        ch,
+        paych::Method::Settle.into(),
+        Serialized::default(),
+        dust(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    let st: paych::State = h.state(&ch);
+    assert_eq!(st.settling_at, 11);
+
+    h.epoch = st.settling_at;
+    let ret = h.send(
+        client,
+        ch,
+        paych::Method::Collect.into(),
+        Serialized::default(),
+        dust(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+
+    // The payee receives the redeemed amount and the client the rest of the channel funds
+    let st: paych::State = h.state(&ch);
+    assert!(st.to_send.is_zero());
+    assert!(h.balance(&ch).is_zero());
+    assert_eq!(
+        h.balance(&payee),
+        payee_balance - payee_paid + TokenAmount::from(40u8)
+    );
+}
+
+#[test]
+fn internal_send_runs_in_receiver_context() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+    let client_head = h.actor(&client).state;
+    let next_id = h.state::<init::State>(&INIT_ACTOR_ADDR).next_id;
+
+    // The init actor calls the constructor of the channel, which only accepts the init actor
+    // as its caller and sets the state of the channel, not of the message's receiver
+    let ret = h.send(
+        client,
+        *INIT_ACTOR_ADDR,
+        init::Method::Exec.into(),
+        exec_paych_params(client, payee),
+        TokenAmount::from(100u8),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    let exec_ret: init::ExecReturn = ret.msg_receipt.return_data.deserialize().unwrap();
+    let st: paych::State = h.state(&exec_ret.id_address);
+    assert_eq!((st.from, st.to), (client, payee));
+    assert_eq!(h.actor(&client).state, client_head);
+    assert_eq!(
+        h.state::<init::State>(&INIT_ACTOR_ADDR).next_id,
+        next_id + 1
+    );
+}
+
+#[test]
+fn paych_constructor_failure_reverts_exec() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let next_id = h.state::<init::State>(&INIT_ACTOR_ADDR).next_id;
+
+    // The payee of a channel must be an account, the error of the constructor called by the
+    // init actor is the exit code of the message
+    let ret = h.send(
+        client,
+        *INIT_ACTOR_ADDR,
+        init::Method::Exec.into(),
+        exec_paych_params(client, *INIT_ACTOR_ADDR),
+        TokenAmount::from(100u8),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::ErrIllegalArgument);
+    assert!(ret.msg_receipt.return_data.bytes().is_empty());
+
+    // No actor is created and only gas is charged to the client
+    assert_eq!(h.state::<init::State>(&INIT_ACTOR_ADDR).next_id, next_id);
+    assert!(h.get_actor(&Address::new_id(next_id)).is_none());
+    assert_eq!(
+        h.balance(&client),
+        TokenAmount::from(INITIAL_BALANCE) - gas_cost(&ret)
+    );
+    assert_eq!(h.actor(&client).sequence, 1);
+}
+
+#[test]
+fn market_slashes_provider_after_sector_termination() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let owner = h.add_account(OWNER_ID, INITIAL_BALANCE.into());
+    let worker = h.add_account(WORKER_ID, INITIAL_BALANCE.into());
+    let provider = h.add_miner(MINER_ID, owner, worker);
+
+    // Both parties deposit funds in escrow, the provider's worker is checked by calling the
+    // miner actor
+    for &(from, escrow) in &[(client, client), (worker, provider)] {
+        let ret = h.send(
+            from,
+            *STORAGE_MARKET_ACTOR_ADDR,
+            market::Method::AddBalance.into(),
+            Serialized::serialize(escrow).unwrap(),
+            TokenAmount::from(1000u16),
+        );
+        assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    }
+
+    let mut proposal = deal_client::new_proposal(
+        &PieceInfo {
+            size: PaddedPieceSize(2048),
+            cid: piece_commitment_v1_to_cid(&[1; 32]),
+        },
+        DealTerms {
+            client,
+            provider,
+            start_epoch: 10,
+            end_epoch: 200,
+            verified_deal: false,
+        },
+    )
+    .unwrap();
+    proposal.provider_collateral = TokenAmount::from(50u8);
+    let publish_params = Serialized::serialize(PublishStorageDealsParams {
+        deals: vec![ClientDealProposal {
+            proposal,
+            client_signature: Signature::new_secp256k1(vec![0]),
+        }],
+    })
+    .unwrap();
+
+    // Only the provider's worker can publish its deals
+    h.epoch = 1;
+    let ret = h.send(
+        client,
+        *STORAGE_MARKET_ACTOR_ADDR,
+        market::Method::PublishStorageDeals.into(),
+        publish_params.clone(),
+        dust(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::ErrForbidden);
+
+    let ret = h.send(
+        worker,
+        *STORAGE_MARKET_ACTOR_ADDR,
+        market::Method::PublishStorageDeals.into(),
+        publish_params,
+        dust(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    let deal_ids = ret
+        .msg_receipt
+        .return_data
+        .deserialize::<PublishStorageDealsReturn>()
+        .unwrap()
+        .ids;
+
+    let st: market::State = h.state(&STORAGE_MARKET_ACTOR_ADDR);
+    assert_eq!(
+        st.get_locked_balance(&h.store, &provider).unwrap(),
+        TokenAmount::from(50u8)
+    );
+
+    // The miner activates the deal in a sector, then terminates the sector
+    h.epoch = 2;
+    let ret = h.apply_implicit(
+        provider,
+        *STORAGE_MARKET_ACTOR_ADDR,
+        market::Method::VerifyDealsForActivation.into(),
+        Serialized::serialize(VerifyDealsForActivationParams {
+            sectors: vec![SectorDeals {
+                sector_expiry: 300,
+                deal_ids: deal_ids.clone(),
+            }],
+        })
+        .unwrap(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+
+    h.epoch = 20;
+    let ret = h.apply_implicit(
+        provider,
+        *STORAGE_MARKET_ACTOR_ADDR,
+        market::Method::OnMinerSectorsTerminate.into(),
+        Serialized::serialize(OnMinerSectorsTerminateParams { deal_ids }).unwrap(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+
+    // The provider collateral is burnt when cron processes the terminated deal
+    let market_balance = h.balance(&STORAGE_MARKET_ACTOR_ADDR);
+    let burnt_balance = h.balance(&BURNT_FUNDS_ACTOR_ADDR);
+    let ret = h.apply_implicit(
+        *CRON_ACTOR_ADDR,
+        *STORAGE_MARKET_ACTOR_ADDR,
+        market::Method::CronTick.into(),
+        Serialized::default(),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+
+    assert_eq!(
+        h.balance(&BURNT_FUNDS_ACTOR_ADDR),
+        burnt_balance + TokenAmount::from(50u8)
+    );
+    assert_eq!(
+        h.balance(&STORAGE_MARKET_ACTOR_ADDR),
+        market_balance - TokenAmount::from(50u8)
+    );
+    let st: market::State = h.state(&STORAGE_MARKET_ACTOR_ADDR);
+    assert_eq!(
+        st.get_escrow_balance(&h.store, &provider).unwrap(),
+        TokenAmount::from(950u16)
+    );
+    assert!(st
+        .get_locked_balance(&h.store, &provider)
+        .unwrap()
+        .is_zero());
+    assert!(st.get_locked_balance(&h.store, &client).unwrap().is_zero());
+}
+
+#[test]
+fn receipt_carries_actor_exit_code() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+
+    // The account actor has no such method, the value sent with the message is returned
+    let ret = h.send(
+        client,
+        payee,
+        MethodNum::new(99),
+        Serialized::default(),
+        TokenAmount::from(100u8),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::SysErrInvalidMethod);
+    assert_eq!(
+        ret.act_error.map(|e| e.exit_code()),
+        Some(ExitCode::SysErrInvalidMethod)
+    );
+    assert_eq!(h.balance(&payee), TokenAmount::from(INITIAL_BALANCE));
+}
+
+#[test]
+fn send_to_robust_addresses() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let next_id = h.state::<init::State>(&INIT_ACTOR_ADDR).next_id;
+
+    // Sending to a key address without an actor creates an account actor for it, and later
+    // sends resolve to the same actor
+    let key_addr = Address::new_secp256k1(&[7; SECP_PUB_LEN]).unwrap();
+    for _ in 0..2 {
+        let ret = h.send(
+            client,
+            key_addr,
+            account::Method::PubkeyAddress.into(),
+            Serialized::default(),
+            TokenAmount::from(100u8),
+        );
+        assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+        let ret_addr: Address = ret.msg_receipt.return_data.deserialize().unwrap();
+        assert_eq!(ret_addr, key_addr);
+    }
+    let id_addr = Address::new_id(next_id);
+    assert_eq!(h.actor(&id_addr).code, *ACCOUNT_ACTOR_CODE_ID);
+    assert_eq!(h.balance(&key_addr), TokenAmount::from(200u8));
+    assert_eq!(
+        h.state::<init::State>(&INIT_ACTOR_ADDR).next_id,
+        next_id + 1
+    );
+
+    // Actor and ID addresses which don't resolve to an actor are invalid receivers
+    for &to in &[Address::new_actor(b"missing"), Address::new_id(next_id + 1)] {
+        let ret = h.send(
+            client,
+            to,
+            account::Method::PubkeyAddress.into(),
+            Serialized::default(),
+            dust(),
+        );
+        assert_eq!(ret.msg_receipt.exit_code, ExitCode::SysErrInvalidReceiver);
+    }
+    assert_eq!(
+        h.state::<init::State>(&INIT_ACTOR_ADDR).next_id,
+        next_id + 1
+    );
+    assert!(h.get_actor(&Address::new_id(next_id + 1)).is_none());
+}
+
+#[test]
+fn gas_trace_records_charges() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+
+    let (ret, trace) = h.send_traced(
+        client,
+        *INIT_ACTOR_ADDR,
+        init::Method::Exec.into(),
+        exec_paych_params(client, payee),
+        TokenAmount::from(100u8),
+    );
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+
+    // The message size is charged first, and creating the channel charges for reading
+    // and writing state and for the actor
+    assert_eq!(trace[0].name, "OnChainMessage");
+    for name in &[
+        "OnMethodInvocation",
+        "OnIpldGet",
+        "OnIpldPut",
+        "OnCreateActor",
+    ] {
+        assert!(trace.iter().any(|c| &c.name == name), "no {} charge", name);
+    }
+    let traced: i64 = trace.iter().map(|c| c.gas).sum();
+    assert!(traced > 0 && traced <= ret.msg_receipt.gas_used);
+}
+
+#[test]
+fn sender_pays_for_gas_used() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+
+    // The gas limit is paid up front and the unused gas refunded, leaving the sender charged
+    // for the gas used
+    let ret = h.send(client, payee, METHOD_SEND, Serialized::default(), dust());
+    assert!(ret.msg_receipt.gas_used > 0);
+    assert_eq!(
+        h.balance(&client),
+        TokenAmount::from(INITIAL_BALANCE) - gas_cost(&ret) - dust()
+    );
+    assert_eq!(
+        h.balance(&payee),
+        TokenAmount::from(INITIAL_BALANCE) + dust()
+    );
+    assert_eq!(h.actor(&client).sequence, 1);
+}
+
+#[test]
+fn gas_burned_and_miner_penalized() {
+    let mut h = Harness::new();
+    let client = h.add_account(CLIENT_ID, INITIAL_BALANCE.into());
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+
+    // Before the upgrade all of the unused gas is refunded
+    let burnt = h.balance(&BURNT_FUNDS_ACTOR_ADDR);
+    let reward = h.balance(&REWARD_ACTOR_ADDR);
+    let ret = h.send(client, payee, METHOD_SEND, Serialized::default(), dust());
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    assert_eq!(h.balance(&BURNT_FUNDS_ACTOR_ADDR), burnt);
+    assert_eq!(h.balance(&REWARD_ACTOR_ADDR), &reward + gas_cost(&ret));
+    assert_eq!(
+        h.balance(&client),
+        TokenAmount::from(INITIAL_BALANCE) - gas_cost(&ret) - dust()
+    );
+
+    // From the upgrade the gas limit of the harness far exceeds the gas used, so most of the
+    // unused gas is burned instead of refunded
+    let balance = h.balance(&client);
+    let reward = h.balance(&REWARD_ACTOR_ADDR);
+    let msg = UnsignedMessage::builder()
+        .from(client)
+        .to(payee)
+        .sequence(h.actor(&client).sequence)
+        .value(dust())
+        .gas_price(GAS_PRICE.into())
+        .gas_limit(GAS_LIMIT)
+        .build()
+        .unwrap();
+    let ret = h.apply_with::<UpgradedParams>(&msg);
+    assert_eq!(ret.msg_receipt.exit_code, ExitCode::Ok);
+    let gas_used = ret.msg_receipt.gas_used;
+    let (refund, gas_burned) = compute_gas_overestimation_burn(gas_used, GAS_LIMIT);
+    assert!(gas_burned > refund);
+    assert_eq!(
+        h.balance(&BURNT_FUNDS_ACTOR_ADDR),
+        burnt + TokenAmount::from(gas_burned) * GAS_PRICE
+    );
+    assert_eq!(
+        h.balance(&REWARD_ACTOR_ADDR),
+        reward + TokenAmount::from(gas_used) * GAS_PRICE
+    );
+    assert_eq!(
+        h.balance(&client),
+        balance - TokenAmount::from(gas_used + gas_burned) * GAS_PRICE - dust()
+    );
+
+    // A message with a sequence the sender hasn't reached is rejected without charging the
+    // sender, the miner who included it is penalized instead
+    let balance = h.balance(&client);
+    let msg = UnsignedMessage::builder()
+        .from(client)
+        .to(payee)
+        .sequence(5)
+        .value(dust())
+        .gas_price(GAS_PRICE.into())
+        .gas_limit(GAS_LIMIT)
+        .build()
+        .unwrap();
+    let ret = h.apply(&msg);
+    assert_eq!(
+        ret.msg_receipt.exit_code,
+        ExitCode::SysErrSenderStateInvalid
+    );
+    assert_eq!(ret.msg_receipt.gas_used, 0);
+    assert!(!ret.penalty.is_zero());
+    assert_eq!(h.balance(&client), balance);
+    assert_eq!(h.actor(&client).sequence, 2);
+
+    // The penalty doesn't depend on the gas limit of the message
+    let mut msg = msg;
+    msg.set_gas_limit(GAS_LIMIT / 2);
+    assert_eq!(h.apply(&msg).penalty, ret.penalty);
+}
+
+#[test]
+fn vm_config_defaults_to_no_recording() {
+    let config = VMConfig::default();
+    assert!(!config.tracing);
+    assert!(!config.actor_debugging);
+}
+
+#[test]
+fn tipset_applies_duplicate_message_once() {
+    let mut h = Harness::new();
+    let senders: Vec<_> = (0..3)
+        .map(|i| h.add_account(2001 + i, INITIAL_BALANCE.into()))
+        .collect();
+    let payee = h.add_account(PAYEE_ID, INITIAL_BALANCE.into());
+    let owner = h.add_account(OWNER_ID, INITIAL_BALANCE.into());
+    let worker = h.add_account(WORKER_ID, INITIAL_BALANCE.into());
+    let miners = [
+        h.add_miner(MINER_ID, owner, worker),
+        h.add_miner(MINER_ID + 1, owner, worker),
+    ];
+
+    let msgs: Vec<_> = senders
+        .iter()
+        .map(|&from| {
+            UnsignedMessage::builder()
+                .from(from)
+                .to(payee)
+                .value(TokenAmount::from(100u8))
+                .gas_price(GAS_PRICE.into())
+                .gas_limit(GAS_LIMIT)
+                .build()
+                .unwrap()
+        })
+        .collect();
+
+    // Both blocks include the first message, which is only applied by the first block in the
+    // tipset. Applying it again would be rejected for its sequence and add a receipt.
+    let receipts = h.apply_blocks(vec![
+        (miners[0], vec![msgs[0].clone(), msgs[1].clone()]),
+        (miners[1], vec![msgs[0].clone(), msgs[2].clone()]),
+    ]);
+    assert_eq!(receipts.len(), 3);
+    assert!(receipts.iter().all(|r| r.exit_code == ExitCode::Ok));
+    assert_eq!(
+        h.balance(&payee),
+        TokenAmount::from(INITIAL_BALANCE) + TokenAmount::from(300u16)
+    );
+    for sender in &senders {
+        assert_eq!(h.actor(sender).sequence, 1);
+    }
+
+    // The miner of each block is rewarded, including the block whose shared message was
+    // already applied
+    for miner in &miners {
+        assert!(!h.balance(miner).is_zero());
+    }
+}
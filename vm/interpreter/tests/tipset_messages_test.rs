@@ -0,0 +1,114 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use address::Address;
+use blocks::{Block, BlockHeader, FullTipset};
+use crypto::{election_proof::ElectionProof, Signature, Signer};
+use forest_encoding::Cbor;
+use interpreter::tipset_block_messages;
+use message::{SignedMessage, UnsignedMessage};
+use std::error::Error;
+
+/// Test struct to generate one byte signature for testing
+struct DummySigner;
+impl Signer for DummySigner {
+    fn sign_bytes(&self, _: Vec<u8>, _: &Address) -> Result<Signature, Box<dyn Error>> {
+        Ok(Signature::new_secp256k1(vec![0u8]))
+    }
+}
+
+fn message(from: u64, sequence: u64) -> UnsignedMessage {
+    UnsignedMessage::builder()
+        .to(Address::new_id(1))
+        .from(Address::new_id(from))
+        .sequence(sequence)
+        .build()
+        .unwrap()
+}
+
+fn block(miner: u64, bls: Vec<UnsignedMessage>, secp: Vec<UnsignedMessage>) -> Block {
+    Block {
+        header: BlockHeader::builder()
+            .miner_address(Address::new_id(miner))
+            .build()
+            .unwrap(),
+        bls_messages: bls,
+        secp_messages: secp
+            .into_iter()
+            .map(|m| SignedMessage::new(m, &DummySigner).unwrap())
+            .collect(),
+    }
+}
+
+#[test]
+fn duplicate_messages_applied_once() {
+    let bls_dup = message(100, 0);
+    let secp_dup = message(101, 0);
+    let own_a = message(102, 0);
+    let own_b = message(103, 0);
+
+    let tipset = FullTipset::new(vec![
+        block(
+            1000,
+            vec![bls_dup.clone(), own_a.clone()],
+            vec![secp_dup.clone()],
+        ),
+        block(
+            1001,
+            vec![bls_dup.clone(), own_b.clone()],
+            vec![secp_dup.clone()],
+        ),
+    ])
+    .unwrap();
+
+    let blocks = tipset_block_messages(&tipset).unwrap();
+    assert_eq!(blocks.len(), 2);
+    for (msgs, block) in blocks.iter().zip(tipset.blocks()) {
+        assert_eq!(&msgs.miner, block.header().miner_address());
+    }
+
+    // The tipset orders the blocks, each miner included the message of its own sender
+    let own = |miner: &Address| {
+        if *miner == Address::new_id(1000) {
+            &own_a
+        } else {
+            &own_b
+        }
+    };
+
+    // The first block applies all of its messages, BLS messages before secp. Only the message
+    // unique to the second block is applied, the block is still included to reward its miner
+    let expected = vec![
+        vec![&bls_dup, own(&blocks[0].miner), &secp_dup],
+        vec![own(&blocks[1].miner)],
+    ];
+    for (msgs, expected) in blocks.iter().zip(expected) {
+        let applied: Vec<_> = msgs.messages.iter().map(|(_, m)| *m).collect();
+        assert_eq!(applied, expected);
+        for (cid, msg) in &msgs.messages {
+            assert_eq!(cid, &msg.cid().unwrap());
+        }
+    }
+}
+
+#[test]
+fn win_count_from_election_proof() {
+    let won = Block {
+        header: BlockHeader::builder()
+            .miner_address(Address::new_id(1000))
+            .election_proof(Some(ElectionProof {
+                win_count: Some(3),
+                ..Default::default()
+            }))
+            .build()
+            .unwrap(),
+        bls_messages: Vec::new(),
+        secp_messages: Vec::new(),
+    };
+    let tipset = FullTipset::new(vec![won]).unwrap();
+    assert_eq!(tipset_block_messages(&tipset).unwrap()[0].win_count, 3);
+
+    // A block without an election proof has no wins
+    let tipset = FullTipset::new(vec![block(1000, Vec::new(), Vec::new())]).unwrap();
+    assert_eq!(tipset_block_messages(&tipset).unwrap()[0].win_count, 0);
+}
@@ -1,25 +1,29 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+// Only the runtime factory of the shared test helpers is used here
+#[allow(dead_code)]
+mod common;
+
 use actor::{init, ACCOUNT_ACTOR_CODE_ID, INIT_ACTOR_ADDR};
-use address::Address;
+use address::{Address, SECP_PUB_LEN};
 use blocks::TipsetKeys;
 use cid::multihash::{Blake2b256, Identity};
+use common::message_runtime;
 use db::MemoryDB;
-use fil_types::DevnetParams;
-use interpreter::{vm_send, ChainRand, DefaultRuntime, DefaultSyscalls};
+use forest_encoding::Cbor;
+use interpreter::{vm_send, ChainRand, DefaultSyscalls};
 use ipld_blockstore::BlockStore;
 use ipld_hamt::Hamt;
 use message::UnsignedMessage;
+use runtime::Runtime;
 use state_tree::StateTree;
-use vm::{ActorState, Serialized};
-
-#[test]
-fn transfer_test() {
-    let store = MemoryDB::default();
-    let mut state = StateTree::new(&store);
+use std::time::Instant;
+use vm::{ActorError, ActorState, ExitCode, MethodNum, Serialized, EMPTY_ARR_CID, METHOD_SEND};
 
-    let e_cid = Hamt::<String, _>::new_with_bit_width(&store, 5)
+/// Sets up the init actor and two account actors, returns the addresses of the accounts.
+fn setup(state: &mut StateTree<MemoryDB>, store: &MemoryDB) -> (Address, Address) {
+    let e_cid = Hamt::<String, _>::new_with_bit_width(store, 5)
         .flush()
         .unwrap();
 
@@ -81,10 +85,19 @@ fn transfer_test() {
     state.set_actor(&actor_addr_1, actor_state_1).unwrap();
     state.set_actor(&actor_addr_2, actor_state_2).unwrap();
 
+    (actor_addr_1, actor_addr_2)
+}
+
+#[test]
+fn transfer_test() {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (actor_addr_1, actor_addr_2) = setup(&mut state, &store);
+
     let message = UnsignedMessage::builder()
         .to(actor_addr_1.clone())
         .from(actor_addr_2.clone())
-        .method_num(2)
+        .method_num(MethodNum::new(2))
         .value(1u8.into())
         .gas_limit(1000)
         .params(Serialized::default())
@@ -92,21 +105,8 @@ fn transfer_test() {
         .unwrap();
 
     let default_syscalls = DefaultSyscalls::new(&store);
-
     let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
-    let mut runtime = DefaultRuntime::<_, _, DevnetParams>::new(
-        &mut state,
-        &store,
-        &default_syscalls,
-        0,
-        &message,
-        0,
-        actor_addr_2.clone(),
-        0,
-        0,
-        &dummy_rand,
-    )
-    .unwrap();
+    let mut runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
     let _serialized = vm_send(&mut runtime, &message, None).unwrap();
 
     let actor_state_result_1 = state.get_actor(&actor_addr_1).unwrap().unwrap();
@@ -117,3 +117,206 @@ fn transfer_test() {
     assert_eq!(actor_state_result_1.sequence, 0);
     assert_eq!(actor_state_result_2.sequence, 0);
 }
+
+#[test]
+fn transfer_past_deadline() {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (actor_addr_1, actor_addr_2) = setup(&mut state, &store);
+
+    let message = UnsignedMessage::builder()
+        .to(actor_addr_1.clone())
+        .from(actor_addr_2.clone())
+        .method_num(MethodNum::new(2))
+        .value(1u8.into())
+        .gas_limit(1000)
+        .params(Serialized::default())
+        .build()
+        .unwrap();
+
+    let default_syscalls = DefaultSyscalls::new(&store);
+    let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
+    let mut runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
+    runtime.set_deadline(Some(Instant::now()));
+    let err = vm_send(&mut runtime, &message, None).unwrap_err();
+    assert!(err.is_fatal());
+
+    // No funds are transferred
+    let actor_state_result_1 = state.get_actor(&actor_addr_1).unwrap().unwrap();
+    assert_eq!(actor_state_result_1.balance, 10000u64.into());
+}
+
+/// Sends funds to a new key address, creating an account actor for it, with a maximum size
+/// of actor states.
+fn create_account(max_state_size: Option<usize>) -> Result<Serialized, ActorError> {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (_, actor_addr_2) = setup(&mut state, &store);
+
+    let message = UnsignedMessage::builder()
+        .to(Address::new_secp256k1(&[1; SECP_PUB_LEN]).unwrap())
+        .from(actor_addr_2.clone())
+        .method_num(METHOD_SEND)
+        .value(1u8.into())
+        .gas_limit(1_000_000)
+        .params(Serialized::default())
+        .build()
+        .unwrap();
+
+    let default_syscalls = DefaultSyscalls::new(&store);
+    let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
+    let mut runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
+    runtime.set_max_state_size(max_state_size);
+    vm_send(&mut runtime, &message, None)
+}
+
+#[test]
+fn created_actor_has_empty_head() {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (actor_addr_1, _) = setup(&mut state, &store);
+
+    let message = UnsignedMessage::builder()
+        .to(*INIT_ACTOR_ADDR)
+        .from(actor_addr_1)
+        .gas_limit(1_000_000)
+        .build()
+        .unwrap();
+    let default_syscalls = DefaultSyscalls::new(&store);
+    let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
+    let mut runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
+
+    // The head of a created actor is the empty array until its constructor sets a state
+    let new_addr = Address::new_id(300);
+    runtime
+        .create_actor(&ACCOUNT_ACTOR_CODE_ID, &new_addr)
+        .unwrap();
+    drop(runtime);
+    let act = state.get_actor(&new_addr).unwrap().unwrap();
+    assert_eq!(act.state, *EMPTY_ARR_CID);
+    assert_eq!(act.code, *ACCOUNT_ACTOR_CODE_ID);
+    assert_eq!(act.sequence, 0);
+}
+
+#[test]
+fn create_actor_exceeding_max_state_size() {
+    create_account(None).unwrap();
+    create_account(Some(1024)).unwrap();
+
+    // The account state, containing the key address, can't fit in the maximum
+    let err = create_account(Some(8)).unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::ErrIllegalState);
+}
+
+#[test]
+fn store_get_and_put_charge_gas() {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (actor_addr_1, actor_addr_2) = setup(&mut state, &store);
+
+    let message = UnsignedMessage::builder()
+        .to(actor_addr_1.clone())
+        .from(actor_addr_2.clone())
+        .gas_limit(1_000_000)
+        .build()
+        .unwrap();
+
+    let default_syscalls = DefaultSyscalls::new(&store);
+    let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
+    let runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
+
+    let obj = actor::account::State {
+        address: actor_addr_1,
+    };
+    let size = obj.marshal_cbor().unwrap().len();
+
+    let gas_before = runtime.gas_used();
+    let c = runtime.store_put(&obj).unwrap();
+    assert_eq!(
+        runtime.gas_used() - gas_before,
+        runtime.price_list().on_ipld_put(size)
+    );
+
+    let gas_before = runtime.gas_used();
+    let loaded: actor::account::State = runtime.store_get(&c).unwrap().unwrap();
+    assert_eq!(loaded.address, actor_addr_1);
+    assert_eq!(
+        runtime.gas_used() - gas_before,
+        runtime.price_list().on_ipld_get(size)
+    );
+}
+
+#[test]
+fn transaction_charges_put_for_unchanged_state() {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (actor_addr_1, actor_addr_2) = setup(&mut state, &store);
+
+    let message = UnsignedMessage::builder()
+        .to(actor_addr_1.clone())
+        .from(actor_addr_2.clone())
+        .gas_limit(1_000_000)
+        .build()
+        .unwrap();
+
+    let default_syscalls = DefaultSyscalls::new(&store);
+    let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
+    let head = state.get_actor(&actor_addr_1).unwrap().unwrap().state;
+    let mut runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
+
+    let obj = actor::account::State {
+        address: actor_addr_1,
+    };
+    let size = obj.marshal_cbor().unwrap().len();
+    let pl = runtime.price_list();
+
+    // Unchanged state is neither encoded nor rewritten, but the put is still charged
+    for _ in 0..2 {
+        let gas_before = runtime.gas_used();
+        runtime
+            .transaction(|_: &mut actor::account::State, _| ())
+            .unwrap();
+        assert_eq!(
+            runtime.gas_used() - gas_before,
+            pl.on_ipld_get(size) + pl.on_ipld_put(size)
+        );
+    }
+    drop(runtime);
+    assert_eq!(state.get_actor(&actor_addr_1).unwrap().unwrap().state, head);
+}
+
+#[test]
+fn actor_store_access_charges_gas() {
+    let store = MemoryDB::default();
+    let mut state = StateTree::new(&store);
+    let (actor_addr_1, actor_addr_2) = setup(&mut state, &store);
+
+    let message = UnsignedMessage::builder()
+        .to(actor_addr_1.clone())
+        .from(actor_addr_2.clone())
+        .gas_limit(1_000_000)
+        .build()
+        .unwrap();
+
+    let default_syscalls = DefaultSyscalls::new(&store);
+    let dummy_rand = ChainRand::new(TipsetKeys::new(vec![]));
+    let runtime = message_runtime(&mut state, &store, &default_syscalls, &dummy_rand, &message);
+
+    // Blocks written by actors through the store, such as HAMT nodes, are charged
+    let gas_before = runtime.gas_used();
+    let c = Hamt::<String, _>::new_with_bit_width(runtime.store(), 5)
+        .flush()
+        .unwrap();
+    let size = store.get_bytes(&c).unwrap().unwrap().len();
+    assert_eq!(
+        runtime.gas_used() - gas_before,
+        runtime.price_list().on_ipld_put(size)
+    );
+
+    let gas_before = runtime.gas_used();
+    Hamt::<String, _>::load_with_bit_width(&c, runtime.store(), 5).unwrap();
+    assert_eq!(
+        runtime.gas_used() - gas_before,
+        runtime.price_list().on_ipld_get(size)
+    );
+}
@@ -6,9 +6,9 @@ use crate::signed_message::SignedMessage;
 use crate::unsigned_message::UnsignedMessage;
 use address::Address;
 use cid::Cid;
-use encoding::{Cbor, Error};
+use encoding::{Cbor, CodecProtocol, Error};
 use serde::{Deserialize, Serialize};
-use vm::{MethodNum, Serialized, TokenAmount};
+use vm::{MethodNum, NonNegTokenAmount, Serialized, TokenAmount, TokenAmountError};
 
 /// Enum to encpasulate signed and unsigned messages. Useful when working with both types
 #[derive(Clone, Debug, Hash, Serialize, Deserialize)]
@@ -17,6 +17,32 @@ pub enum ChainMessage {
     Signed(SignedMessage),
 }
 
+/// Cbor header of an unsigned message, encoded as a tuple of 9 fields.
+const UNSIGNED_MESSAGE_HEADER: u8 = 0x89;
+/// Cbor header of a signed message, encoded as a tuple of the message and signature.
+const SIGNED_MESSAGE_HEADER: u8 = 0x82;
+
+impl ChainMessage {
+    /// Decodes a message from the raw cbor bytes of a block, using the length of the encoded
+    /// tuple to determine if it is an unsigned or a signed message.
+    pub fn unmarshal_block(bz: &[u8]) -> Result<Self, Error> {
+        match bz.first() {
+            Some(&UNSIGNED_MESSAGE_HEADER) => {
+                Ok(Self::Unsigned(UnsignedMessage::unmarshal_cbor(bz)?))
+            }
+            Some(&SIGNED_MESSAGE_HEADER) => Ok(Self::Signed(SignedMessage::unmarshal_cbor(bz)?)),
+            Some(header) => Err(Error::Unmarshalling {
+                description: format!("invalid message header: {:#x}", header),
+                protocol: CodecProtocol::Cbor,
+            }),
+            None => Err(Error::Unmarshalling {
+                description: "empty message block".to_owned(),
+                protocol: CodecProtocol::Cbor,
+            }),
+        }
+    }
+}
+
 impl Message for ChainMessage {
     fn from(&self) -> &Address {
         match self {
@@ -84,7 +110,7 @@ impl Message for ChainMessage {
             Self::Unsigned(t) => t.set_sequence(new_sequence),
         }
     }
-    fn required_funds(&self) -> TokenAmount {
+    fn required_funds(&self) -> Result<NonNegTokenAmount, TokenAmountError> {
         match self {
             Self::Signed(t) => t.required_funds(),
             Self::Unsigned(t) => t.required_funds(),
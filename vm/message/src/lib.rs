@@ -12,10 +12,10 @@ pub mod unsigned_message;
 pub use chain_message::ChainMessage;
 pub use message_receipt::*;
 pub use signed_message::SignedMessage;
-pub use unsigned_message::UnsignedMessage;
+pub use unsigned_message::{MessageError, UnsignedMessage, SUPPORTED_MESSAGE_VERSIONS};
 
 use address::Address;
-use vm::{MethodNum, Serialized, TokenAmount};
+use vm::{MethodNum, NonNegTokenAmount, Serialized, TokenAmount, TokenAmountError};
 
 pub trait Message {
     /// Returns the from address of the message
@@ -40,6 +40,7 @@ pub trait Message {
     fn set_sequence(&mut self, sequence: u64);
     /// Returns the gas limit for the message
     fn gas_limit(&self) -> i64;
-    /// Returns the required funds for the message
-    fn required_funds(&self) -> TokenAmount;
+    /// Returns the funds the sender needs to cover the value and gas of the message, or an
+    /// error if the gas price or the value of the message is negative.
+    fn required_funds(&self) -> Result<NonNegTokenAmount, TokenAmountError>;
 }
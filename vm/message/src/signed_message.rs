@@ -6,7 +6,7 @@ use address::Address;
 use crypto::{Error as CryptoError, Signature, SignatureType, Signer};
 use encoding::tuple::*;
 use encoding::{to_vec, Cbor, Error};
-use vm::{MethodNum, Serialized, TokenAmount};
+use vm::{MethodNum, NonNegTokenAmount, Serialized, TokenAmount, TokenAmountError};
 
 /// Represents a wrapped message with signature bytes
 #[derive(PartialEq, Clone, Debug, Serialize_tuple, Deserialize_tuple, Hash, Eq)]
@@ -25,15 +25,18 @@ impl SignedMessage {
         Ok(SignedMessage { message, signature })
     }
 
-    /// Generate a new signed message from fields
+    /// Generate a new signed message from fields, verifying the signature over the message.
+    #[cfg(feature = "verify")]
     pub fn new_from_parts(
         message: UnsignedMessage,
         signature: Signature,
     ) -> Result<SignedMessage, String> {
-        signature.verify(
-            &message.marshal_cbor().map_err(|err| err.to_string())?,
-            message.from(),
-        )?;
+        signature
+            .verify(
+                &message.marshal_cbor().map_err(|err| err.to_string())?,
+                message.from(),
+            )
+            .map_err(|err| err.to_string())?;
         Ok(SignedMessage { message, signature })
     }
 
@@ -97,7 +100,7 @@ impl Message for SignedMessage {
     fn set_sequence(&mut self, new_sequence: u64) {
         self.message.set_sequence(new_sequence)
     }
-    fn required_funds(&self) -> TokenAmount {
+    fn required_funds(&self) -> Result<NonNegTokenAmount, TokenAmountError> {
         self.message.required_funds()
     }
 }
@@ -116,8 +119,9 @@ impl Cbor for SignedMessage {
 pub mod json {
     use super::*;
     use crate::unsigned_message;
+    use cid::{json::CidJsonRef, Cid};
     use crypto::signature;
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
 
     /// Wrapper for serializing and deserializing a SignedMessage from JSON.
     #[derive(Deserialize, Serialize)]
@@ -129,6 +133,12 @@ pub mod json {
     #[serde(transparent)]
     pub struct SignedMessageJsonRef<'a>(#[serde(with = "self")] pub &'a SignedMessage);
 
+    /// Wrapper for serializing a SignedMessage reference to JSON, including the Cids of the
+    /// signed and unsigned message as done by Lotus RPC responses.
+    #[derive(Serialize)]
+    #[serde(transparent)]
+    pub struct SignedMessageJsonWithCidRef<'a>(#[serde(with = "with_cid")] pub &'a SignedMessage);
+
     impl From<SignedMessageJson> for SignedMessage {
         fn from(wrapper: SignedMessageJson) -> Self {
             wrapper.0
@@ -170,6 +180,42 @@ pub mod json {
         Ok(SignedMessage { message, signature })
     }
 
+    /// Serializes the signed message along with its Cid, and the Cid of the unsigned message.
+    /// Deserialization is the same as without the Cids, which are ignored if present.
+    pub mod with_cid {
+        use super::*;
+
+        pub fn serialize<S>(m: &SignedMessage, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            #[derive(Serialize)]
+            #[serde(rename_all = "PascalCase")]
+            struct SignedMessageSer<'a> {
+                #[serde(with = "unsigned_message::json::with_cid")]
+                message: &'a UnsignedMessage,
+                #[serde(with = "signature::json")]
+                signature: &'a Signature,
+                #[serde(rename = "CID")]
+                cid: CidJsonRef<'a>,
+            }
+            let cid: Cid = m.cid().map_err(ser::Error::custom)?;
+            SignedMessageSer {
+                message: &m.message,
+                signature: &m.signature,
+                cid: CidJsonRef(&cid),
+            }
+            .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<SignedMessage, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::deserialize(deserializer)
+        }
+    }
+
     pub mod vec {
         use super::*;
         use forest_json_utils::GoVecVisitor;
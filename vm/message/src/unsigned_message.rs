@@ -4,10 +4,26 @@
 use super::Message;
 use address::Address;
 use derive_builder::Builder;
-use encoding::Cbor;
-use num_bigint::bigint_ser::{BigIntDe, BigIntSer};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use vm::{MethodNum, Serialized, TokenAmount};
+use encoding::{Cbor, Error as EncodingError};
+use fnv::FnvHasher;
+use once_cell::sync::OnceCell;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::hash::{Hash, Hasher};
+use thiserror::Error;
+use vm::{MethodNum, NonNegTokenAmount, Serialized, TokenAmount, TokenAmountError};
+
+/// Message versions accepted when decoding messages from cbor.
+pub const SUPPORTED_MESSAGE_VERSIONS: &[i64] = &[0];
+
+/// Error for messages which can't be built or have a version which isn't supported.
+#[derive(Debug, PartialEq, Error)]
+pub enum MessageError {
+    #[error("Unsupported message version {0}")]
+    UnsupportedVersion(i64),
+    #[error("Failed to build message: {0}")]
+    Builder(String),
+}
+
 /// Default Unsigned VM message type which includes all data needed for a state transition
 ///
 /// Usage:
@@ -57,12 +73,68 @@ pub struct UnsignedMessage {
     gas_price: TokenAmount,
     #[builder(default)]
     gas_limit: i64,
+    #[builder(setter(skip))]
+    quick_hash: QuickHashCache,
 }
 
 impl UnsignedMessage {
     pub fn builder() -> MessageBuilder {
         MessageBuilder::default()
     }
+
+    /// Returns the version of the message.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Checks that the version of the message is one of the supported versions.
+    pub fn check_version(&self, supported: &[i64]) -> Result<(), MessageError> {
+        if supported.contains(&self.version) {
+            Ok(())
+        } else {
+            Err(MessageError::UnsupportedVersion(self.version))
+        }
+    }
+
+    /// Returns a cheap, non-cryptographic 64 bit hash of the message's cbor encoding.
+    ///
+    /// The hash is deterministic and cached after the first call, which makes it useful for
+    /// indexing and deduplicating messages where the full Cid is not needed.
+    pub fn quick_hash(&self) -> Result<u64, EncodingError> {
+        self.quick_hash
+            .0
+            .get_or_try_init(|| {
+                let mut hasher = FnvHasher::default();
+                hasher.write(&self.marshal_cbor()?);
+                Ok(hasher.finish())
+            })
+            .map(|h| *h)
+    }
+}
+
+impl MessageBuilder {
+    /// Builds the message and checks that its version is one of the supported versions.
+    pub fn build_checked(&self, supported: &[i64]) -> Result<UnsignedMessage, MessageError> {
+        let msg = self.build().map_err(MessageError::Builder)?;
+        msg.check_version(supported)?;
+        Ok(msg)
+    }
+}
+
+/// Cache of the message quick hash, which is ignored when comparing or hashing messages.
+#[derive(Clone, Debug, Default)]
+struct QuickHashCache(OnceCell<u64>);
+
+impl PartialEq for QuickHashCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for QuickHashCache {}
+
+impl Hash for QuickHashCache {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
 }
 
 impl Serialize for UnsignedMessage {
@@ -75,8 +147,8 @@ impl Serialize for UnsignedMessage {
             &self.to,
             &self.from,
             &self.sequence,
-            BigIntSer(&self.value),
-            BigIntSer(&self.gas_price),
+            &self.value,
+            &self.gas_price,
             &self.gas_limit,
             &self.method_num,
             &self.params,
@@ -90,17 +162,11 @@ impl<'de> Deserialize<'de> for UnsignedMessage {
     where
         D: Deserializer<'de>,
     {
-        let (
-            version,
-            to,
-            from,
-            sequence,
-            BigIntDe(value),
-            BigIntDe(gas_price),
-            gas_limit,
-            method_num,
-            params,
-        ) = Deserialize::deserialize(deserializer)?;
+        let (version, to, from, sequence, value, gas_price, gas_limit, method_num, params) =
+            Deserialize::deserialize(deserializer)?;
+        if !SUPPORTED_MESSAGE_VERSIONS.contains(&version) {
+            return Err(de::Error::custom(MessageError::UnsupportedVersion(version)));
+        }
         Ok(Self {
             version,
             to,
@@ -111,6 +177,7 @@ impl<'de> Deserialize<'de> for UnsignedMessage {
             gas_limit,
             method_num,
             params,
+            quick_hash: Default::default(),
         })
     }
 }
@@ -138,20 +205,24 @@ impl Message for UnsignedMessage {
         &self.gas_price
     }
     fn set_gas_price(&mut self, token_amount: TokenAmount) {
-        self.gas_price = token_amount
+        self.gas_price = token_amount;
+        self.quick_hash = Default::default();
     }
     fn set_sequence(&mut self, new_sequence: u64) {
-        self.sequence = new_sequence
+        self.sequence = new_sequence;
+        self.quick_hash = Default::default();
     }
     fn gas_limit(&self) -> i64 {
         self.gas_limit
     }
     fn set_gas_limit(&mut self, token_amount: i64) {
-        self.gas_limit = token_amount
+        self.gas_limit = token_amount;
+        self.quick_hash = Default::default();
     }
-    fn required_funds(&self) -> TokenAmount {
-        let total: TokenAmount = self.gas_price() * self.gas_limit();
-        total + self.value()
+    /// Returns an error if the gas cost or the value of the message is negative.
+    fn required_funds(&self) -> Result<NonNegTokenAmount, TokenAmountError> {
+        let gas_cost = NonNegTokenAmount::try_new(self.gas_price() * self.gas_limit())?;
+        Ok(gas_cost + NonNegTokenAmount::try_new(self.value().clone())?)
     }
 }
 
@@ -160,7 +231,12 @@ impl Cbor for UnsignedMessage {}
 #[cfg(feature = "json")]
 pub mod json {
     use super::*;
-    use serde::de;
+    use cid::{
+        json::{CidJson, CidJsonRef},
+        Cid,
+    };
+    use num_bigint::BigInt;
+    use serde::{de, ser};
 
     /// Wrapper for serializing and deserializing a UnsignedMessage from JSON.
     #[derive(Deserialize, Serialize, Debug)]
@@ -172,6 +248,14 @@ pub mod json {
     #[serde(transparent)]
     pub struct UnsignedMessageJsonRef<'a>(#[serde(with = "self")] pub &'a UnsignedMessage);
 
+    /// Wrapper for serializing a UnsignedMessage reference to JSON, including the message Cid
+    /// as done by Lotus RPC responses.
+    #[derive(Serialize)]
+    #[serde(transparent)]
+    pub struct UnsignedMessageJsonWithCidRef<'a>(
+        #[serde(with = "with_cid")] pub &'a UnsignedMessage,
+    );
+
     impl From<UnsignedMessageJson> for UnsignedMessage {
         fn from(wrapper: UnsignedMessageJson) -> Self {
             wrapper.0
@@ -182,34 +266,48 @@ pub mod json {
     #[serde(rename_all = "PascalCase")]
     struct JsonHelper {
         version: i64,
-        to: String,
-        from: String,
+        #[serde(with = "address::json")]
+        to: Address,
+        #[serde(with = "address::json")]
+        from: Address,
         #[serde(rename = "Nonce")]
         sequence: u64,
         value: String,
         gas_price: String,
         gas_limit: i64,
         #[serde(rename = "Method")]
-        method_num: u64,
+        method_num: MethodNum,
         params: Option<String>,
+        /// Cid of the message, accepted but not needed to decode the message.
+        #[serde(
+            rename = "CID",
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "option_cid"
+        )]
+        cid: Option<Cid>,
     }
 
     pub fn serialize<S>(m: &UnsignedMessage, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        json_helper(m, None).serialize(serializer)
+    }
+
+    fn json_helper(m: &UnsignedMessage, cid: Option<Cid>) -> JsonHelper {
         JsonHelper {
             version: m.version,
-            to: m.to.to_string(),
-            from: m.from.to_string(),
+            to: m.to,
+            from: m.from,
             sequence: m.sequence,
-            value: m.value.to_string(),
-            gas_price: m.gas_price.to_string(),
+            value: m.value.atto().to_string(),
+            gas_price: m.gas_price.atto().to_string(),
             gas_limit: m.gas_limit,
             method_num: m.method_num,
             params: Some(base64::encode(m.params.bytes())),
+            cid,
         }
-        .serialize(serializer)
     }
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<UnsignedMessage, D::Error>
@@ -219,20 +317,68 @@ pub mod json {
         let m: JsonHelper = Deserialize::deserialize(deserializer)?;
         Ok(UnsignedMessage {
             version: m.version,
-            to: m.to.parse().map_err(de::Error::custom)?,
-            from: m.from.parse().map_err(de::Error::custom)?,
+            to: m.to,
+            from: m.from,
             sequence: m.sequence,
-            value: m.value.parse().map_err(de::Error::custom)?,
-            gas_price: m.gas_price.parse().map_err(de::Error::custom)?,
+            value: parse_atto(&m.value)?,
+            gas_price: parse_atto(&m.gas_price)?,
             gas_limit: m.gas_limit,
             method_num: m.method_num,
             params: Serialized::new(
                 base64::decode(&m.params.unwrap_or_else(|| "".to_string()))
                     .map_err(de::Error::custom)?,
             ),
+            quick_hash: Default::default(),
         })
     }
 
+    /// Parses an amount in attoFIL, which is how Lotus encodes token amounts in JSON.
+    fn parse_atto<E: de::Error>(s: &str) -> Result<TokenAmount, E> {
+        s.parse::<BigInt>()
+            .map(TokenAmount::from)
+            .map_err(de::Error::custom)
+    }
+
+    /// Serializes the message along with its Cid. Deserialization is the same as without
+    /// the Cid, which is ignored if present.
+    pub mod with_cid {
+        use super::*;
+
+        pub fn serialize<S>(m: &UnsignedMessage, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let cid = m.cid().map_err(ser::Error::custom)?;
+            json_helper(m, Some(cid)).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<UnsignedMessage, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::deserialize(deserializer)
+        }
+    }
+
+    mod option_cid {
+        use super::*;
+
+        pub fn serialize<S>(v: &Option<Cid>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            v.as_ref().map(CidJsonRef).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Cid>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let v: Option<CidJson> = Deserialize::deserialize(deserializer)?;
+            Ok(v.map(|c| c.0))
+        }
+    }
+
     pub mod vec {
         use super::*;
         use forest_json_utils::GoVecVisitor;
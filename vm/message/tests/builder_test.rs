@@ -3,9 +3,12 @@
 
 use address::Address;
 use crypto::{Signature, Signer};
-use forest_message::{Message, SignedMessage, UnsignedMessage};
+use encoding::Cbor;
+use forest_message::{
+    Message, MessageError, SignedMessage, UnsignedMessage, SUPPORTED_MESSAGE_VERSIONS,
+};
 use std::error::Error;
-use vm::{MethodNum, Serialized, TokenAmount};
+use vm::{MethodNum, Serialized, TokenAmount, TokenAmountError};
 
 const DUMMY_SIG: [u8; 1] = [0u8];
 
@@ -71,3 +74,81 @@ fn generate_signed_message() {
         &Signature::new_secp256k1(DUMMY_SIG.to_vec())
     );
 }
+
+#[test]
+fn unsigned_message_quick_hash() {
+    let mut msg = UnsignedMessage::builder()
+        .to(Address::new_id(1))
+        .from(Address::new_id(2))
+        .build()
+        .unwrap();
+    let other = msg.clone();
+
+    let hash = msg.quick_hash().unwrap();
+    assert_eq!(msg.quick_hash().unwrap(), hash);
+    assert_eq!(other.quick_hash().unwrap(), hash);
+
+    // Cached hash is reset when the message is modified
+    msg.set_sequence(1);
+    assert_ne!(msg.quick_hash().unwrap(), hash);
+    assert_ne!(msg, other);
+}
+
+#[test]
+fn unsigned_message_version_checks() {
+    let mut mb = UnsignedMessage::builder();
+    mb.to(Address::new_id(1)).from(Address::new_id(2));
+
+    let msg = mb.build_checked(SUPPORTED_MESSAGE_VERSIONS).unwrap();
+    assert_eq!(msg.version(), 0);
+    assert_eq!(
+        UnsignedMessage::unmarshal_cbor(&msg.marshal_cbor().unwrap()).unwrap(),
+        msg
+    );
+
+    // Future versions are only accepted when they are in the supported set
+    mb.version(1);
+    assert_eq!(
+        mb.build_checked(SUPPORTED_MESSAGE_VERSIONS),
+        Err(MessageError::UnsupportedVersion(1))
+    );
+    let msg = mb.build_checked(&[0, 1]).unwrap();
+    assert_eq!(msg.version(), 1);
+
+    // Messages with unsupported versions can't be decoded
+    let bz = msg.marshal_cbor().unwrap();
+    assert!(UnsignedMessage::unmarshal_cbor(&bz).is_err());
+
+    // Missing fields are reported by the builder
+    assert!(matches!(
+        UnsignedMessage::builder().build_checked(SUPPORTED_MESSAGE_VERSIONS),
+        Err(MessageError::Builder(_))
+    ));
+}
+
+#[test]
+fn unsigned_message_required_funds() {
+    let mut mb = UnsignedMessage::builder();
+    mb.to(Address::new_id(1))
+        .from(Address::new_id(2))
+        .value(TokenAmount::from(10u8))
+        .gas_price(TokenAmount::from(2u8))
+        .gas_limit(100);
+    let msg = mb.build().unwrap();
+    assert_eq!(
+        msg.required_funds().unwrap().into_token_amount(),
+        TokenAmount::from(210u8)
+    );
+
+    // Negative values and gas prices are rejected instead of lowering the required funds
+    let msg = mb.clone().value(TokenAmount::from(-10)).build().unwrap();
+    assert_eq!(
+        msg.required_funds(),
+        Err(TokenAmountError::Negative(TokenAmount::from(-10)))
+    );
+    let msg = mb.gas_price(TokenAmount::from(-1)).build().unwrap();
+    assert_eq!(
+        msg.required_funds(),
+        Err(TokenAmountError::Negative(TokenAmount::from(-100)))
+    );
+}
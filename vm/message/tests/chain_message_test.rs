@@ -0,0 +1,42 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use address::Address;
+use crypto::{Signature, Signer};
+use encoding::Cbor;
+use forest_message::{ChainMessage, SignedMessage, UnsignedMessage};
+use std::error::Error;
+
+struct DummySigner;
+impl Signer for DummySigner {
+    fn sign_bytes(&self, _: Vec<u8>, _: &Address) -> Result<Signature, Box<dyn Error>> {
+        Ok(Signature::new_secp256k1(vec![0u8; 65]))
+    }
+}
+
+#[test]
+fn unmarshal_message_blocks() {
+    let unsigned = UnsignedMessage::builder()
+        .to(Address::new_id(1))
+        .from(Address::new_id(2))
+        .sequence(3)
+        .build()
+        .unwrap();
+    let signed = SignedMessage::new(unsigned.clone(), &DummySigner).unwrap();
+
+    match ChainMessage::unmarshal_block(&unsigned.marshal_cbor().unwrap()).unwrap() {
+        ChainMessage::Unsigned(msg) => assert_eq!(msg, unsigned),
+        msg => panic!("expected unsigned message, got {:?}", msg),
+    }
+    match ChainMessage::unmarshal_block(&signed.marshal_cbor().unwrap()).unwrap() {
+        ChainMessage::Signed(msg) => assert_eq!(msg, signed),
+        msg => panic!("expected signed message, got {:?}", msg),
+    }
+
+    // Blocks which are not messages can't be decoded
+    assert!(ChainMessage::unmarshal_block(&[]).is_err());
+    assert!(ChainMessage::unmarshal_block(&Address::new_id(1).marshal_cbor().unwrap()).is_err());
+    let mut truncated = signed.marshal_cbor().unwrap();
+    truncated.pop();
+    assert!(ChainMessage::unmarshal_block(&truncated).is_err());
+}
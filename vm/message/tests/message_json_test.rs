@@ -5,20 +5,21 @@
 
 use address::Address;
 use crypto::{Signature, Signer};
+use encoding::Cbor;
 use forest_message::signed_message::{
     self,
-    json::{SignedMessageJson, SignedMessageJsonRef},
+    json::{SignedMessageJson, SignedMessageJsonRef, SignedMessageJsonWithCidRef},
     SignedMessage,
 };
 use forest_message::unsigned_message::{
     self,
-    json::{UnsignedMessageJson, UnsignedMessageJsonRef},
+    json::{UnsignedMessageJson, UnsignedMessageJsonRef, UnsignedMessageJsonWithCidRef},
     UnsignedMessage,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string};
 use std::error::Error;
-use vm::Serialized;
+use vm::{MethodNum, Serialized};
 
 #[test]
 fn unsigned_symmetric_json() {
@@ -51,7 +52,7 @@ fn message_json_annotations() {
         .from(Address::new_id(34))
         .sequence(5)
         .value(6u8.into())
-        .method_num(7)
+        .method_num(MethodNum::new(7))
         .params(Serialized::default())
         .gas_limit(8)
         .gas_price(9u8.into())
@@ -109,3 +110,38 @@ fn message_json_annotations() {
     let expected = TestStruct { unsigned, signed };
     assert_eq!(from_str::<TestStruct>(test_json).unwrap(), expected);
 }
+
+#[test]
+fn message_json_with_cid() {
+    let message_json = r#"{"Version":9,"To":"t01234","From":"t01234","Nonce":42,"Value":"0","GasPrice":"0","GasLimit":9,"Method":1,"Params":"Ynl0ZSBhcnJheQ=="}"#;
+    let UnsignedMessageJson(unsigned) = from_str(message_json).unwrap();
+    let unsigned_cid = unsigned.cid().unwrap();
+
+    let with_cid = to_string(&UnsignedMessageJsonWithCidRef(&unsigned)).unwrap();
+    assert_eq!(
+        with_cid,
+        format!(
+            r#"{},"CID":{{"/":"{}"}}}}"#,
+            &message_json[..message_json.len() - 1],
+            unsigned_cid
+        )
+    );
+    // Cid is ignored when deserializing
+    let UnsignedMessageJson(decoded) = from_str(&with_cid).unwrap();
+    assert_eq!(decoded, unsigned);
+
+    struct SecpSigner;
+    impl Signer for SecpSigner {
+        fn sign_bytes(&self, _: Vec<u8>, _: &Address) -> Result<Signature, Box<dyn Error>> {
+            Ok(Signature::new_secp256k1(vec![0u8; 65]))
+        }
+    }
+    let signed = SignedMessage::new(unsigned, &SecpSigner).unwrap();
+    let with_cid = to_string(&SignedMessageJsonWithCidRef(&signed)).unwrap();
+    let value: serde_json::Value = from_str(&with_cid).unwrap();
+    assert_eq!(value["CID"]["/"], signed.cid().unwrap().to_string());
+    assert_eq!(value["Message"]["CID"]["/"], unsigned_cid.to_string());
+
+    let SignedMessageJson(decoded) = from_str(&with_cid).unwrap();
+    assert_eq!(decoded, signed);
+}
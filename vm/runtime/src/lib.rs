@@ -2,17 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod actor_code;
+mod state_object;
 
 pub use self::actor_code::*;
+pub use self::state_object::StateObject;
+pub use log::Level as LogLevel;
 
 use address::Address;
 use cid::Cid;
 use clock::ChainEpoch;
 use commcid::{cid_to_data_commitment_v1, cid_to_replica_commitment_v1, data_commitment_v1_to_cid};
-use crypto::{DomainSeparationTag, Signature};
+use crypto::{DomainSeparationTag, Signature, SignatureError};
 use fil_types::{
-    zero_piece_commitment, PaddedPieceSize, PieceInfo, RegisteredSealProof, SealVerifyInfo,
-    SectorInfo, WindowPoStVerifyInfo,
+    zero_piece_commitment, NetworkVersion, PaddedPieceSize, PieceInfo, RegisteredSealProof,
+    SealVerifyInfo, SectorInfo, WindowPoStVerifyInfo,
 };
 use filecoin_proofs_api::{self as proofs, ProverId, SectorId};
 use filecoin_proofs_api::{
@@ -22,9 +25,10 @@ use filecoin_proofs_api::{
 };
 use forest_encoding::{blake2b_256, Cbor};
 use ipld_blockstore::BlockStore;
-use log::warn;
+use log::{log, warn};
 use message::Message;
 use rayon::prelude::*;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
@@ -40,6 +44,9 @@ pub trait Runtime<BS: BlockStore> {
     /// The current chain epoch number. The genesis block has epoch zero.
     fn curr_epoch(&self) -> ChainEpoch;
 
+    /// The network version in effect at the current epoch.
+    fn network_version(&self) -> NetworkVersion;
+
     /// Validates the caller against some predicate.
     /// Exported actor methods must invoke at least one caller validation before returning.
     fn validate_immediate_caller_accept_any(&mut self) -> Result<(), ActorError>;
@@ -61,9 +68,22 @@ pub trait Runtime<BS: BlockStore> {
     /// Look up the code ID at an actor address.
     fn get_actor_code_cid(&self, addr: &Address) -> Result<Option<Cid>, ActorError>;
 
-    /// Randomness returns a (pseudo)random byte array drawing from a
-    /// random beacon at a given epoch and incorporating reequisite entropy
-    fn get_randomness(
+    /// Randomness returns a (pseudo)random byte array drawing from the
+    /// ticket chain at a given epoch and incorporating reequisite entropy.
+    /// Epochs after the current epoch are illegal, and epochs before genesis
+    /// draw from the genesis ticket.
+    fn get_randomness_from_tickets(
+        &self,
+        personalization: DomainSeparationTag,
+        rand_epoch: ChainEpoch,
+        entropy: &[u8],
+    ) -> Result<Randomness, ActorError>;
+
+    /// Randomness returns a (pseudo)random byte array drawing from the
+    /// random beacon at a given epoch and incorporating reequisite entropy.
+    /// Epochs after the current epoch are illegal, and epochs before genesis
+    /// draw from the genesis beacon entry.
+    fn get_randomness_from_beacon(
         &self,
         personalization: DomainSeparationTag,
         rand_epoch: ChainEpoch,
@@ -87,14 +107,21 @@ pub trait Runtime<BS: BlockStore> {
     ///
     /// If the state is modified after this function returns, execution will abort.
     ///
-    /// The gas cost of this method is that of a Store.Put of the mutated state object.
-    fn transaction<C: Cbor, R, F>(&mut self, f: F) -> Result<R, ActorError>
+    /// The gas cost of this method is that of a Store.Put of the mutated state object, state
+    /// which is left unchanged is not written again.
+    fn transaction<C: StateObject, R, F>(&mut self, f: F) -> Result<R, ActorError>
     where
         F: FnOnce(&mut C, &mut Self) -> R;
 
     /// Returns reference to blockstore
     fn store(&self) -> &BS;
 
+    /// Loads an object from the store by its Cid, charging gas for the read.
+    fn store_get<T: Cbor>(&self, cid: &Cid) -> Result<Option<T>, ActorError>;
+
+    /// Puts an object into the store, charging gas for the write, and returns its Cid.
+    fn store_put<T: Cbor>(&self, obj: &T) -> Result<Cid, ActorError>;
+
     /// Sends a message to another actor, returning the exit code and return value envelope.
     /// If the invoked method does not return successfully, its state changes
     /// (and that of any messages it sent in turn) will be rolled back.
@@ -130,7 +157,32 @@ pub trait Runtime<BS: BlockStore> {
     /// Provides the system call interface.
     fn syscalls(&self) -> &dyn Syscalls;
 
+    /// Verifies that a signature is valid for an address and plaintext, charging gas for the
+    /// verification. The outer error aborts the actor, such as when it runs out of gas, the inner
+    /// one is the reason the signature is invalid.
+    fn verify_signature(
+        &self,
+        signature: &Signature,
+        signer: &Address,
+        plaintext: &[u8],
+    ) -> Result<Result<(), SignatureError>, ActorError> {
+        Ok(self
+            .syscalls()
+            .verify_signature(signature, signer, plaintext))
+    }
+
     fn total_fil_circ_supply(&self) -> Result<TokenAmount, ActorError>;
+
+    /// Checks an invariant of the actor, logging an error if it doesn't hold. Execution
+    /// continues either way, so that debug and release builds compute the same state.
+    fn assert_invariant<S: AsRef<str>>(&self, cond: bool, msg: S) {
+        if !cond {
+            self.syscalls().log(
+                LogLevel::Error,
+                &format!("invariant violated: {}", msg.as_ref()),
+            );
+        }
+    }
 }
 
 /// Message information available to the actor about executing message.
@@ -163,19 +215,29 @@ where
 
 /// Pure functions implemented as primitives by the runtime.
 pub trait Syscalls {
-    /// Verifies that a signature is valid for an address and plaintext.
+    /// Logs a message from an actor for debugging. Logs don't affect execution and aren't
+    /// charged gas, and are only emitted if actor debugging is enabled in the VM.
+    fn log(&self, level: LogLevel, msg: &str) {
+        log!(target: "actor", level, "{}", msg);
+    }
+    /// Verifies that a signature is valid for an address and plaintext. Not charged gas, actors
+    /// verify signatures through `Runtime::verify_signature`.
     fn verify_signature(
         &self,
         signature: &Signature,
         signer: &Address,
         plaintext: &[u8],
-    ) -> Result<(), Box<dyn StdError>> {
-        Ok(signature.verify(plaintext, signer)?)
+    ) -> Result<(), SignatureError> {
+        signature.verify(plaintext, signer)
     }
     /// Hashes input data using blake2b with 256 bit output.
     fn hash_blake2b(&self, data: &[u8]) -> Result<[u8; 32], Box<dyn StdError>> {
         Ok(blake2b_256(data))
     }
+    /// Hashes input data using sha256.
+    fn hash_sha256(&self, data: &[u8]) -> Result<[u8; 32], Box<dyn StdError>> {
+        Ok(Sha256::digest(data).into())
+    }
     /// Computes an unsealed sector CID (CommD) from its constituent piece CIDs (CommPs) and sizes.
     fn compute_unsealed_sector_cid(
         &self,
@@ -247,13 +309,16 @@ pub trait Syscalls {
                         .try_into()?,
                     commr,
                 );
-                Ok((SectorId::from(sector_info.sector_number), replica))
+                Ok((
+                    SectorId::from(u64::from(sector_info.sector_number)),
+                    replica,
+                ))
             })
             .collect::<Result<BTreeMap<SectorId, PublicReplicaInfo>, _>>()?;
 
         // construct prover id
         let mut prover_id = ProverId::default();
-        let prover_bytes = verify_info.prover.to_be_bytes();
+        let prover_bytes = verify_info.prover.0.to_be_bytes();
         prover_id[..prover_bytes.len()].copy_from_slice(&prover_bytes);
 
         // verify
@@ -312,7 +377,7 @@ pub trait Syscalls {
 fn verify_seal(vi: &SealVerifyInfo) -> Result<(), Box<dyn StdError>> {
     let commd = cid_to_data_commitment_v1(&vi.unsealed_cid)?;
     let commr = cid_to_replica_commitment_v1(&vi.sealed_cid)?;
-    let miner_addr = Address::new_id(vi.sector_id.miner);
+    let miner_addr = vi.sector_id.miner.to_address();
     let miner_payload = miner_addr.payload_bytes();
     let mut prover_id = ProverId::default();
     prover_id[..miner_payload.len()].copy_from_slice(&miner_payload);
@@ -322,7 +387,7 @@ fn verify_seal(vi: &SealVerifyInfo) -> Result<(), Box<dyn StdError>> {
         commr,
         commd,
         prover_id,
-        SectorId::from(vi.sector_id.number),
+        SectorId::from(u64::from(vi.sector_id.number)),
         vi.randomness.0,
         vi.interactive_randomness.0,
         &vi.proof,
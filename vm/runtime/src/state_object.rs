@@ -0,0 +1,26 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::{multihash::Blake2b256, Cid};
+use forest_encoding::Cbor;
+use ipld_blockstore::BlockStore;
+use std::error::Error as StdError;
+
+/// State of an actor, stored as the actor's head in the state tree. States are compared with
+/// the state they were loaded as, so that unchanged states are neither encoded nor written.
+pub trait StateObject: Cbor + Clone + PartialEq {
+    /// Flushes the state to the store and returns its Cid. `loaded` is the state as loaded from
+    /// `head`, if the state is unchanged since then `head` is returned without encoding the
+    /// state or writing it to the store.
+    fn flush<BS: BlockStore>(
+        &self,
+        store: &BS,
+        loaded: &Self,
+        head: &Cid,
+    ) -> Result<Cid, Box<dyn StdError>> {
+        if self == loaded {
+            return Ok(head.clone());
+        }
+        store.put(self, Blake2b256)
+    }
+}
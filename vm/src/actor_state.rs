@@ -2,21 +2,68 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::TokenAmount;
+use address::Address;
 use cid::Cid;
-use encoding::tuple::*;
-use num_bigint::bigint_ser;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
 
 /// Identifier for Actors, includes builtin and initialized actors
-pub type ActorID = u64;
+#[derive(
+    PartialEq, Eq, PartialOrd, Ord, Hash, Default, Copy, Clone, Debug, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct ActorID(pub u64);
+
+impl ActorID {
+    /// Returns the ID following this one, or `None` if the ID space is exhausted.
+    pub fn checked_next(self) -> Option<Self> {
+        self.0.checked_add(1).map(Self)
+    }
+
+    /// Returns the ID address of the actor.
+    pub fn to_address(self) -> Address {
+        Address::new_id(self.0)
+    }
+}
+
+impl From<u64> for ActorID {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl TryFrom<&Address> for ActorID {
+    type Error = address::Error;
+
+    fn try_from(addr: &Address) -> Result<Self, Self::Error> {
+        addr.id().map(Self)
+    }
+}
+
+impl From<ActorID> for u64 {
+    fn from(id: ActorID) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for ActorID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
 /// State of all actor implementations
-#[derive(PartialEq, Eq, Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct ActorState {
     pub code: Cid,
     pub state: Cid,
     pub sequence: u64,
-    #[serde(with = "bigint_ser")]
     pub balance: TokenAmount,
+    /// Address the actor was assigned at creation in addition to its ID address, which can be
+    /// predicted before the actor is created. Actor states without one are serialized as the
+    /// four field tuple of earlier network versions, so that their state roots are unchanged.
+    pub delegated_address: Option<Address>,
 }
 
 impl ActorState {
@@ -27,6 +74,7 @@ impl ActorState {
             state,
             balance,
             sequence,
+            delegated_address: None,
         }
     }
     /// Safely deducts funds from an Actor
@@ -44,10 +92,72 @@ impl ActorState {
     }
 }
 
+impl Serialize for ActorState {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (code, state, sequence, balance) =
+            (&self.code, &self.state, &self.sequence, &self.balance);
+        match &self.delegated_address {
+            Some(addr) => (code, state, sequence, balance, addr).serialize(s),
+            None => (code, state, sequence, balance).serialize(s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ActorState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ActorStateVisitor;
+
+        impl<'de> de::Visitor<'de> for ActorStateVisitor {
+            type Value = ActorState;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a tuple of 4 or 5 actor state fields")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<ActorState, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let code = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let state = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let sequence = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let balance = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let delegated_address = seq.next_element()?;
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(6, &self));
+                }
+                Ok(ActorState {
+                    code,
+                    state,
+                    sequence,
+                    balance,
+                    delegated_address,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(ActorStateVisitor)
+    }
+}
+
 #[cfg(feature = "json")]
 pub mod json {
     use super::*;
-    use crate::TokenAmount;
+    use num_bigint::BigInt;
     use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
     use std::str::FromStr;
     /// Wrapper for serializing and deserializing a SignedMessage from JSON.
@@ -80,12 +190,19 @@ pub mod json {
             #[serde(rename = "Nonce")]
             pub sequence: u64,
             pub balance: String,
+            #[serde(
+                rename = "Address",
+                skip_serializing_if = "Option::is_none",
+                with = "address::json::opt"
+            )]
+            pub delegated_address: &'a Option<Address>,
         }
         ActorStateSer {
             code: &m.code,
             state: &m.state,
             sequence: m.sequence,
             balance: m.balance.to_str_radix(10),
+            delegated_address: &m.delegated_address,
         }
         .serialize(serializer)
     }
@@ -104,18 +221,24 @@ pub mod json {
             #[serde(rename = "Nonce")]
             pub sequence: u64,
             pub balance: String,
+            #[serde(rename = "Address", default, with = "address::json::opt")]
+            pub delegated_address: Option<Address>,
         }
         let ActorStateDe {
             code,
             state,
             sequence,
             balance,
+            delegated_address,
         } = Deserialize::deserialize(deserializer)?;
         Ok(ActorState {
             code,
             state,
             sequence,
-            balance: TokenAmount::from_str(&balance).map_err(de::Error::custom)?,
+            balance: BigInt::from_str(&balance)
+                .map_err(de::Error::custom)?
+                .into(),
+            delegated_address,
         })
     }
 }
@@ -12,6 +12,8 @@ mod exit_code;
 mod invoc;
 mod method;
 mod randomness;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod token;
 
 pub use self::actor_state::*;
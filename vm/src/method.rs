@@ -3,15 +3,78 @@
 
 use encoding::{de, from_slice, ser, serde_bytes, to_vec, Cbor, Error as EncodingError};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::{ParseIntError, TryFromIntError};
 use std::ops::Deref;
+use std::str::FromStr;
 
-/// Method number indicator for calling actor methods
-pub type MethodNum = u64;
+/// Method number indicator for calling actor methods, encoded as an unsigned integer.
+///
+/// Method numbers supplied by users, such as signed integers from JSON clients or strings from
+/// the command line, are validated to be unsigned 64 bit integers with `TryFrom<i64>` and
+/// `FromStr`.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct MethodNum(u64);
+
+impl MethodNum {
+    /// Base actor send method
+    pub const SEND: MethodNum = MethodNum(0);
+    /// Base actor constructor method
+    pub const CONSTRUCTOR: MethodNum = MethodNum(1);
+
+    /// Creates a method number.
+    pub const fn new(num: u64) -> Self {
+        Self(num)
+    }
+
+    /// Returns the method number as an integer.
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+}
 
 /// Base actor send method
-pub const METHOD_SEND: MethodNum = 0;
+pub const METHOD_SEND: MethodNum = MethodNum::SEND;
 /// Base actor constructor method
-pub const METHOD_CONSTRUCTOR: MethodNum = 1;
+pub const METHOD_CONSTRUCTOR: MethodNum = MethodNum::CONSTRUCTOR;
+
+impl From<u64> for MethodNum {
+    fn from(num: u64) -> Self {
+        Self(num)
+    }
+}
+
+impl From<MethodNum> for u64 {
+    fn from(num: MethodNum) -> Self {
+        num.0
+    }
+}
+
+impl TryFrom<i64> for MethodNum {
+    type Error = TryFromIntError;
+
+    fn try_from(num: i64) -> Result<Self, Self::Error> {
+        u64::try_from(num).map(Self)
+    }
+}
+
+impl FromStr for MethodNum {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl fmt::Display for MethodNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
 /// Serialized bytes to be used as parameters into actor methods
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Hash, Eq, Default)]
@@ -0,0 +1,93 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Deterministic generation of values for tests. Values are generated from a seeded RNG, so
+//! a failing test can be reproduced from its seed.
+
+use crate::TokenAmount;
+use address::{Address, BLS_PUB_LEN, SECP_PUB_LEN};
+use bitfield::BitField;
+use cid::{multihash::Blake2b256, Cid};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+/// ID of the first address generated by `TestGen::id_address`, above the IDs of the
+/// singleton actors.
+pub const FIRST_TEST_ACTOR_ID: u64 = 1000;
+
+/// Seeded generator of test values.
+pub struct TestGen {
+    rng: XorShiftRng,
+    next_id: u64,
+}
+
+impl TestGen {
+    /// Creates a generator, which generates the same values for the same seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: XorShiftRng::seed_from_u64(seed),
+            next_id: FIRST_TEST_ACTOR_ID,
+        }
+    }
+
+    /// Returns the underlying RNG, to generate other values from the same seed.
+    pub fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+
+    /// Returns a new ID address. IDs are sequential, so each generated ID address is unique.
+    pub fn id_address(&mut self) -> Address {
+        let id = self.next_id;
+        self.next_id += 1;
+        Address::new_id(id)
+    }
+
+    /// Returns a secp256k1 address of a random public key.
+    pub fn secp_address(&mut self) -> Address {
+        let mut key = [0u8; SECP_PUB_LEN];
+        self.rng.fill_bytes(&mut key);
+        Address::new_secp256k1(&key).unwrap()
+    }
+
+    /// Returns an actor address of random data.
+    pub fn actor_address(&mut self) -> Address {
+        let mut data = [0u8; 32];
+        self.rng.fill_bytes(&mut data);
+        Address::new_actor(&data)
+    }
+
+    /// Returns a BLS address of a random public key.
+    pub fn bls_address(&mut self) -> Address {
+        let mut key = [0u8; BLS_PUB_LEN];
+        self.rng.fill_bytes(&mut key);
+        Address::new_bls(&key).unwrap()
+    }
+
+    /// Returns an address of a random protocol.
+    pub fn address(&mut self) -> Address {
+        match self.rng.gen_range(0, 4) {
+            0 => self.id_address(),
+            1 => self.secp_address(),
+            2 => self.actor_address(),
+            _ => self.bls_address(),
+        }
+    }
+
+    /// Returns a Cid of random data.
+    pub fn cid(&mut self) -> Cid {
+        let mut data = [0u8; 32];
+        self.rng.fill_bytes(&mut data);
+        Cid::new_from_cbor(&data, Blake2b256)
+    }
+
+    /// Returns a token amount below `max`.
+    pub fn token_amount(&mut self, max: u64) -> TokenAmount {
+        TokenAmount::from(self.rng.gen_range(0, max))
+    }
+
+    /// Returns a bit field of bits below `len`, each set with probability `density`.
+    pub fn bit_field(&mut self, len: usize, density: f64) -> BitField {
+        let rng = &mut self.rng;
+        (0..len).filter(|_| rng.gen_bool(density)).collect()
+    }
+}
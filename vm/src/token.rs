@@ -1,6 +1,525 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use num_bigint::BigInt;
+use num_bigint::{bigint_ser, BigInt, BigUint, Sign};
+use num_traits::Zero;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Deref, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+use thiserror::Error;
 
-pub type TokenAmount = BigInt;
+/// Number of attoFIL in a FIL.
+pub const TOKEN_PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// Number of decimals of a FIL amount in attoFIL.
+const FIL_DECIMALS: usize = 18;
+
+/// Errors of operations on token amounts.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TokenAmountError {
+    #[error("insufficient amount: {available} is less than {required}")]
+    InsufficientAmount {
+        available: TokenAmount,
+        required: TokenAmount,
+    },
+    #[error("invalid FIL amount: {0:?}")]
+    InvalidFilString(String),
+    #[error("negative amount: {0}")]
+    Negative(TokenAmount),
+}
+
+/// Amount of tokens in attoFIL. Displays and parses as FIL, and serializes as the big endian
+/// bytes of the attoFIL amount with a sign byte, like a `BigInt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenAmount(BigInt);
+
+impl TokenAmount {
+    /// Creates a token amount from an amount in attoFIL.
+    pub fn from_atto(atto: impl Into<BigInt>) -> Self {
+        Self(atto.into())
+    }
+
+    /// Creates a token amount from an amount in whole FIL.
+    pub fn from_whole(fil: impl Into<BigInt>) -> Self {
+        Self(fil.into() * TOKEN_PRECISION)
+    }
+
+    /// Returns the amount in attoFIL.
+    pub fn atto(&self) -> &BigInt {
+        &self.0
+    }
+
+    /// Returns the amount in attoFIL.
+    pub fn into_atto(self) -> BigInt {
+        self.0
+    }
+
+    /// Formats the amount in FIL, such as `1.5 FIL`, with up to 18 decimals and without
+    /// trailing zeros.
+    pub fn to_fil_string(&self) -> String {
+        let precision = BigUint::from(TOKEN_PRECISION);
+        let int = self.0.magnitude() / &precision;
+        let frac = self.0.magnitude() % &precision;
+        let sign = if self.0.sign() == Sign::Minus {
+            "-"
+        } else {
+            ""
+        };
+        if frac.is_zero() {
+            return format!("{}{} FIL", sign, int);
+        }
+        let frac = format!("{:0>width$}", frac.to_string(), width = FIL_DECIMALS);
+        format!("{}{}.{} FIL", sign, int, frac.trim_end_matches('0'))
+    }
+
+    /// Parses a FIL amount. The amount is a decimal number with at most 18 decimals, optionally
+    /// followed by the `FIL` unit, as formatted by `to_fil_string`.
+    pub fn from_fil_str(s: &str) -> Result<Self, TokenAmountError> {
+        let invalid = || TokenAmountError::InvalidFilString(s.to_owned());
+
+        let s = s.trim();
+        let num = s
+            .strip_suffix("FIL")
+            .or_else(|| s.strip_suffix("fil"))
+            .unwrap_or(s)
+            .trim_end();
+        let (negative, num) = match num.strip_prefix('-') {
+            Some(num) => (true, num),
+            None => (false, num),
+        };
+        let (int, frac) = match num.find('.') {
+            Some(i) => (&num[..i], &num[i + 1..]),
+            None => (num, ""),
+        };
+        if (int.is_empty() && frac.is_empty())
+            || frac.len() > FIL_DECIMALS
+            || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let atto: BigInt = format!("{}{:0<width$}", int, frac, width = FIL_DECIMALS)
+            .parse()
+            .map_err(|_| invalid())?;
+        Ok(Self(if negative { -atto } else { atto }))
+    }
+
+    /// Subtracts the other amount, returning `None` if the result would be negative.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let res = self - other;
+        if res.0.sign() == Sign::Minus {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// Subtracts the other amount, returning an `InsufficientAmount` error if the result would
+    /// be negative.
+    pub fn try_sub(&self, other: &Self) -> Result<Self, TokenAmountError> {
+        self.checked_sub(other)
+            .ok_or_else(|| TokenAmountError::InsufficientAmount {
+                available: self.clone(),
+                required: other.clone(),
+            })
+    }
+}
+
+impl Deref for TokenAmount {
+    type Target = BigInt;
+
+    fn deref(&self) -> &BigInt {
+        &self.0
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_fil_string())
+    }
+}
+
+impl FromStr for TokenAmount {
+    type Err = TokenAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_fil_str(s)
+    }
+}
+
+impl Serialize for TokenAmount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        bigint_ser::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenAmount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        bigint_ser::deserialize(deserializer).map(Self)
+    }
+}
+
+macro_rules! impl_from_atto {
+    ($($t:ty),*) => {$(
+        impl From<$t> for TokenAmount {
+            fn from(atto: $t) -> Self {
+                Self(atto.into())
+            }
+        }
+    )*};
+}
+
+impl_from_atto!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, BigInt, BigUint);
+
+impl From<TokenAmount> for BigInt {
+    fn from(amount: TokenAmount) -> Self {
+        amount.0
+    }
+}
+
+impl Zero for TokenAmount {
+    fn zero() -> Self {
+        Self(BigInt::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+macro_rules! impl_amount_op {
+    ($($op:ident::$method:ident, $op_assign:ident::$method_assign:ident;)*) => {$(
+        impl $op for TokenAmount {
+            type Output = TokenAmount;
+
+            fn $method(self, other: TokenAmount) -> TokenAmount {
+                TokenAmount(self.0.$method(other.0))
+            }
+        }
+
+        impl<'a> $op<&'a TokenAmount> for TokenAmount {
+            type Output = TokenAmount;
+
+            fn $method(self, other: &TokenAmount) -> TokenAmount {
+                TokenAmount(self.0.$method(&other.0))
+            }
+        }
+
+        impl<'a> $op<TokenAmount> for &'a TokenAmount {
+            type Output = TokenAmount;
+
+            fn $method(self, other: TokenAmount) -> TokenAmount {
+                TokenAmount((&self.0).$method(other.0))
+            }
+        }
+
+        impl<'a, 'b> $op<&'b TokenAmount> for &'a TokenAmount {
+            type Output = TokenAmount;
+
+            fn $method(self, other: &TokenAmount) -> TokenAmount {
+                TokenAmount((&self.0).$method(&other.0))
+            }
+        }
+
+        impl $op_assign for TokenAmount {
+            fn $method_assign(&mut self, other: TokenAmount) {
+                self.0.$method_assign(other.0)
+            }
+        }
+
+        impl<'a> $op_assign<&'a TokenAmount> for TokenAmount {
+            fn $method_assign(&mut self, other: &TokenAmount) {
+                self.0.$method_assign(&other.0)
+            }
+        }
+    )*};
+}
+
+impl_amount_op! {
+    Add::add, AddAssign::add_assign;
+    Sub::sub, SubAssign::sub_assign;
+}
+
+/// Scaling of token amounts, such as by a power ratio or a number of epochs.
+macro_rules! impl_scale_op {
+    ($($t:ty),*) => {$(
+        impl Mul<$t> for TokenAmount {
+            type Output = TokenAmount;
+
+            fn mul(self, other: $t) -> TokenAmount {
+                TokenAmount(self.0 * other)
+            }
+        }
+
+        impl<'a> Mul<$t> for &'a TokenAmount {
+            type Output = TokenAmount;
+
+            fn mul(self, other: $t) -> TokenAmount {
+                TokenAmount(&self.0 * other)
+            }
+        }
+
+        impl Div<$t> for TokenAmount {
+            type Output = TokenAmount;
+
+            fn div(self, other: $t) -> TokenAmount {
+                TokenAmount(self.0 / other)
+            }
+        }
+
+        impl<'a> Div<$t> for &'a TokenAmount {
+            type Output = TokenAmount;
+
+            fn div(self, other: $t) -> TokenAmount {
+                TokenAmount(&self.0 / other)
+            }
+        }
+
+        impl MulAssign<$t> for TokenAmount {
+            fn mul_assign(&mut self, other: $t) {
+                self.0 *= other
+            }
+        }
+
+        impl DivAssign<$t> for TokenAmount {
+            fn div_assign(&mut self, other: $t) {
+                self.0 /= other
+            }
+        }
+    )*};
+}
+
+impl_scale_op!(u8, u32, u64, i32, i64, BigInt);
+
+impl<'a> Mul<&'a BigInt> for TokenAmount {
+    type Output = TokenAmount;
+
+    fn mul(self, other: &BigInt) -> TokenAmount {
+        TokenAmount(self.0 * other)
+    }
+}
+
+impl<'a, 'b> Mul<&'b BigInt> for &'a TokenAmount {
+    type Output = TokenAmount;
+
+    fn mul(self, other: &BigInt) -> TokenAmount {
+        TokenAmount(&self.0 * other)
+    }
+}
+
+impl<'a> Div<&'a BigInt> for TokenAmount {
+    type Output = TokenAmount;
+
+    fn div(self, other: &BigInt) -> TokenAmount {
+        TokenAmount(self.0 / other)
+    }
+}
+
+impl<'a, 'b> Div<&'b BigInt> for &'a TokenAmount {
+    type Output = TokenAmount;
+
+    fn div(self, other: &BigInt) -> TokenAmount {
+        TokenAmount(&self.0 / other)
+    }
+}
+
+/// Ratio of two token amounts.
+impl Div for TokenAmount {
+    type Output = BigInt;
+
+    fn div(self, other: TokenAmount) -> BigInt {
+        self.0 / other.0
+    }
+}
+
+impl<'a, 'b> Div<&'b TokenAmount> for &'a TokenAmount {
+    type Output = BigInt;
+
+    fn div(self, other: &TokenAmount) -> BigInt {
+        &self.0 / &other.0
+    }
+}
+
+impl<'a, 'b> Rem<&'b TokenAmount> for &'a TokenAmount {
+    type Output = TokenAmount;
+
+    fn rem(self, other: &TokenAmount) -> TokenAmount {
+        TokenAmount(&self.0 % &other.0)
+    }
+}
+
+impl Neg for TokenAmount {
+    type Output = TokenAmount;
+
+    fn neg(self) -> TokenAmount {
+        TokenAmount(-self.0)
+    }
+}
+
+impl<'a> Neg for &'a TokenAmount {
+    type Output = TokenAmount;
+
+    fn neg(self) -> TokenAmount {
+        TokenAmount(-&self.0)
+    }
+}
+
+impl Sum for TokenAmount {
+    fn sum<I: Iterator<Item = TokenAmount>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, amount| acc + amount)
+    }
+}
+
+impl<'a> Sum<&'a TokenAmount> for TokenAmount {
+    fn sum<I: Iterator<Item = &'a TokenAmount>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, amount| acc + amount)
+    }
+}
+
+/// Token amount which can never be negative, used for the balances and required funds checked
+/// by consensus. Serializes the same as a `TokenAmount`, as big endian bytes with a sign byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonNegTokenAmount(TokenAmount);
+
+impl NonNegTokenAmount {
+    /// Creates a non negative token amount, returning `None` if the amount is negative.
+    pub fn new(amount: TokenAmount) -> Option<Self> {
+        if amount.sign() == Sign::Minus {
+            None
+        } else {
+            Some(Self(amount))
+        }
+    }
+
+    /// Creates a non negative token amount, returning a `Negative` error if the amount is
+    /// negative.
+    pub fn try_new(amount: TokenAmount) -> Result<Self, TokenAmountError> {
+        if amount.sign() == Sign::Minus {
+            Err(TokenAmountError::Negative(amount))
+        } else {
+            Ok(Self(amount))
+        }
+    }
+
+    /// Creates a non negative token amount, clamping negative amounts to zero.
+    pub fn new_saturating(amount: TokenAmount) -> Self {
+        Self::new(amount).unwrap_or_default()
+    }
+
+    /// Subtracts the other amount, returning `None` if the result would be negative.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.0.checked_sub(&other.0).map(Self)
+    }
+
+    /// Subtracts the other amount, returning an `InsufficientAmount` error if the result would
+    /// be negative.
+    pub fn try_sub(&self, other: &Self) -> Result<Self, TokenAmountError> {
+        self.0.try_sub(&other.0).map(Self)
+    }
+
+    /// Formats the amount in FIL.
+    pub fn to_fil_string(&self) -> String {
+        self.0.to_fil_string()
+    }
+
+    /// Returns a reference to the amount as a signed `TokenAmount`.
+    pub fn as_token_amount(&self) -> &TokenAmount {
+        &self.0
+    }
+
+    /// Returns the amount as a signed `TokenAmount`.
+    pub fn into_token_amount(self) -> TokenAmount {
+        self.0
+    }
+}
+
+impl TryFrom<TokenAmount> for NonNegTokenAmount {
+    type Error = String;
+
+    fn try_from(amount: TokenAmount) -> Result<Self, Self::Error> {
+        if amount.sign() == Sign::Minus {
+            return Err(format!("token amount {} is negative", amount));
+        }
+        Ok(Self(amount))
+    }
+}
+
+impl From<u64> for NonNegTokenAmount {
+    fn from(amount: u64) -> Self {
+        Self(amount.into())
+    }
+}
+
+impl From<BigUint> for NonNegTokenAmount {
+    fn from(amount: BigUint) -> Self {
+        Self(amount.into())
+    }
+}
+
+impl From<NonNegTokenAmount> for TokenAmount {
+    fn from(amount: NonNegTokenAmount) -> Self {
+        amount.0
+    }
+}
+
+impl AsRef<TokenAmount> for NonNegTokenAmount {
+    fn as_ref(&self) -> &TokenAmount {
+        &self.0
+    }
+}
+
+impl Add for NonNegTokenAmount {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Add<&NonNegTokenAmount> for NonNegTokenAmount {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        Self(self.0 + &other.0)
+    }
+}
+
+impl AddAssign<&NonNegTokenAmount> for NonNegTokenAmount {
+    fn add_assign(&mut self, other: &Self) {
+        self.0 += &other.0;
+    }
+}
+
+impl fmt::Display for NonNegTokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Serialize for NonNegTokenAmount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NonNegTokenAmount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let amount = TokenAmount::deserialize(deserializer)?;
+        Self::try_from(amount).map_err(de::Error::custom)
+    }
+}
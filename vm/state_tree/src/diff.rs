@@ -0,0 +1,364 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::TREE_BIT_WIDTH;
+use actor::{
+    ACCOUNT_ACTOR_CODE_ID, CRON_ACTOR_CODE_ID, INIT_ACTOR_CODE_ID, MARKET_ACTOR_CODE_ID,
+    MINER_ACTOR_CODE_ID, MULTISIG_ACTOR_CODE_ID, PAYCH_ACTOR_CODE_ID, POWER_ACTOR_CODE_ID,
+    REWARD_ACTOR_CODE_ID, VERIFREG_ACTOR_CODE_ID,
+};
+use address::Address;
+use cid::Cid;
+use forest_ipld::Ipld;
+use ipld_blockstore::BlockStore;
+use ipld_hamt::{BytesKey, Hamt};
+use std::collections::BTreeMap;
+use std::fmt;
+use vm::ActorState;
+
+/// Differences between the actors of two state trees, ordered by actor address.
+#[derive(Debug, Default, PartialEq)]
+pub struct StateDiff {
+    pub actors: Vec<ActorDiff>,
+}
+
+impl StateDiff {
+    /// Returns true if both state roots contain the same actors.
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+}
+
+/// Change to a single actor between two state trees.
+#[derive(Debug, PartialEq)]
+pub struct ActorDiff {
+    pub address: Address,
+    pub change: ActorChange,
+}
+
+/// Kind of change made to an actor.
+#[derive(Debug, PartialEq)]
+pub enum ActorChange {
+    Added(ActorState),
+    Removed(ActorState),
+    Modified {
+        before: ActorState,
+        after: ActorState,
+        /// Field level changes of the actor state, only resolved when both heads are
+        /// available in the store and the code of the actor is unchanged.
+        fields: Vec<FieldDiff>,
+    },
+}
+
+/// Change of a single field within an actor's state. A field without a value before was
+/// added, one without a value after was removed.
+#[derive(Debug, PartialEq)]
+pub struct FieldDiff {
+    /// Path to the field, named for known builtin actor states.
+    pub path: String,
+    pub before: Option<Ipld>,
+    pub after: Option<Ipld>,
+}
+
+/// Computes the differences between the actors of two state roots.
+///
+/// For builtin actors, changes to the actor head are decoded to report the fields of the
+/// state which have changed.
+pub fn state_diff<BS>(store: &BS, root_a: &Cid, root_b: &Cid) -> Result<StateDiff, String>
+where
+    BS: BlockStore,
+{
+    if root_a == root_b {
+        return Ok(StateDiff::default());
+    }
+
+    let mut before = load_actors(store, root_a)?;
+    let after = load_actors(store, root_b)?;
+
+    let mut actors = Vec::new();
+    for (key, act_b) in after {
+        let address = Address::from_bytes(&key).map_err(|e| e.to_string())?;
+        let change = match before.remove(&key) {
+            Some(act_a) if act_a == act_b => continue,
+            Some(act_a) => {
+                let fields = if act_a.code == act_b.code {
+                    state_field_diffs(store, &act_a.code, &act_a.state, &act_b.state)?
+                } else {
+                    Vec::new()
+                };
+                ActorChange::Modified {
+                    before: act_a,
+                    after: act_b,
+                    fields,
+                }
+            }
+            None => ActorChange::Added(act_b),
+        };
+        actors.push(ActorDiff { address, change });
+    }
+    for (key, act_a) in before {
+        let address = Address::from_bytes(&key).map_err(|e| e.to_string())?;
+        actors.push(ActorDiff {
+            address,
+            change: ActorChange::Removed(act_a),
+        });
+    }
+    actors.sort_by_key(|diff| diff.address);
+
+    Ok(StateDiff { actors })
+}
+
+/// Loads all actors from a state root, keyed by address bytes.
+pub(crate) fn load_actors<BS>(
+    store: &BS,
+    root: &Cid,
+) -> Result<BTreeMap<Vec<u8>, ActorState>, String>
+where
+    BS: BlockStore,
+{
+    let hamt: Hamt<BytesKey, _> =
+        Hamt::load_with_bit_width(root, store, TREE_BIT_WIDTH).map_err(|e| e.to_string())?;
+    let mut actors = BTreeMap::new();
+    hamt.for_each(|k, act: ActorState| {
+        actors.insert(k.0.clone(), act);
+        Ok(())
+    })?;
+    Ok(actors)
+}
+
+/// Diffs two actor heads, returns no changes if either head can't be loaded from the store.
+fn state_field_diffs<BS>(
+    store: &BS,
+    code: &Cid,
+    head_a: &Cid,
+    head_b: &Cid,
+) -> Result<Vec<FieldDiff>, String>
+where
+    BS: BlockStore,
+{
+    let (a, b) = match (
+        store.get::<Ipld>(head_a).map_err(|e| e.to_string())?,
+        store.get::<Ipld>(head_b).map_err(|e| e.to_string())?,
+    ) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut diffs = Vec::new();
+    match (state_field_names(code), &a, &b) {
+        // States encoded with a different number of fields, such as before and after a field
+        // was added to the state, are named with the fields of the newest layout
+        (Some(names), Ipld::List(la), Ipld::List(lb)) => {
+            for i in 0..std::cmp::max(la.len(), lb.len()) {
+                let path = names
+                    .get(i)
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| i.to_string());
+                match (la.get(i), lb.get(i)) {
+                    (Some(fa), Some(fb)) => diff_ipld(path, fa, fb, &mut diffs),
+                    (fa, fb) => diffs.push(FieldDiff {
+                        path,
+                        before: fa.cloned(),
+                        after: fb.cloned(),
+                    }),
+                }
+            }
+        }
+        _ => diff_ipld(String::new(), &a, &b, &mut diffs),
+    }
+    Ok(diffs)
+}
+
+/// Recursively collects the differences between two Ipld values.
+fn diff_ipld(path: String, a: &Ipld, b: &Ipld, diffs: &mut Vec<FieldDiff>) {
+    if a == b {
+        return;
+    }
+    let join = |key: &dyn fmt::Display| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (a, b) {
+        (Ipld::List(la), Ipld::List(lb)) if la.len() == lb.len() => {
+            for (i, (ea, eb)) in la.iter().zip(lb.iter()).enumerate() {
+                diff_ipld(join(&i), ea, eb, diffs);
+            }
+        }
+        (Ipld::Map(ma), Ipld::Map(mb)) => {
+            for (k, va) in ma.iter() {
+                match mb.get(k) {
+                    Some(vb) => diff_ipld(join(k), va, vb, diffs),
+                    None => diffs.push(FieldDiff {
+                        path: join(k),
+                        before: Some(va.clone()),
+                        after: None,
+                    }),
+                }
+            }
+            for (k, vb) in mb.iter().filter(|(k, _)| !ma.contains_key(*k)) {
+                diffs.push(FieldDiff {
+                    path: join(k),
+                    before: None,
+                    after: Some(vb.clone()),
+                });
+            }
+        }
+        _ => diffs.push(FieldDiff {
+            path,
+            before: Some(a.clone()),
+            after: Some(b.clone()),
+        }),
+    }
+}
+
+/// Names of the serialized fields of builtin actor states, in encoding order.
+fn state_field_names(code: &Cid) -> Option<&'static [&'static str]> {
+    let names: &'static [&'static str] = if code == &*ACCOUNT_ACTOR_CODE_ID {
+        &["address"]
+    } else if code == &*CRON_ACTOR_CODE_ID {
+        &["entries"]
+    } else if code == &*INIT_ACTOR_CODE_ID {
+        &["address_map", "next_id", "network_name"]
+    } else if code == &*MARKET_ACTOR_CODE_ID {
+        &[
+            "proposals",
+            "states",
+            "escrow_table",
+            "locked_table",
+            "next_id",
+            "deal_ops_by_epoch",
+            "last_cron",
+            "deals_by_provider",
+            "pending_proposals",
+        ]
+    } else if code == &*MINER_ACTOR_CODE_ID {
+        &[
+            "info",
+            "pre_commit_deposit",
+            "locked_funds",
+            "pre_committed_sectors",
+            "sectors",
+            "proving_period_start",
+            "new_sectors",
+            "sector_expirations",
+            "deadlines",
+            "faults",
+            "fault_epochs",
+            "recoveries",
+            "post_submissions",
+            "next_deadline_to_process_faults",
+        ]
+    } else if code == &*MULTISIG_ACTOR_CODE_ID {
+        &[
+            "signers",
+            "num_approvals_threshold",
+            "next_tx_id",
+            "initial_balance",
+            "start_epoch",
+            "unlock_duration",
+            "pending_txs",
+            "pending_swap",
+            "signer_weights",
+        ]
+    } else if code == &*PAYCH_ACTOR_CODE_ID {
+        &[
+            "from",
+            "to",
+            "to_send",
+            "settling_at",
+            "min_settle_height",
+            "lane_states",
+        ]
+    } else if code == &*POWER_ACTOR_CODE_ID {
+        &[
+            "total_raw_byte_power",
+            "total_quality_adj_power",
+            "total_pledge_collateral",
+            "miner_count",
+            "cron_event_queue",
+            "last_epoch_tick",
+            "claims",
+            "num_miners_meeting_min_power",
+            "proof_validation_batch",
+            "total_bytes_committed",
+            "total_qa_bytes_committed",
+        ]
+    } else if code == &*REWARD_ACTOR_CODE_ID {
+        &[
+            "baseline_power",
+            "realized_power",
+            "cumsum_baseline",
+            "cumsum_realized",
+            "effective_network_time",
+            "simple_supply",
+            "baseline_supply",
+            "this_epoch_reward",
+            "reward_epochs_paid",
+        ]
+    } else if code == &*VERIFREG_ACTOR_CODE_ID {
+        &["root_key", "verifiers", "verified_clients"]
+    } else {
+        return None;
+    };
+    Some(names)
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diff in self.actors.iter() {
+            write!(f, "{}", diff)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ActorDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.change {
+            ActorChange::Added(act) => {
+                writeln!(f, "+ {} (code: {})", self.address, act.code)?;
+                writeln!(f, "    balance: {}", act.balance)?;
+                writeln!(f, "    sequence: {}", act.sequence)?;
+                writeln!(f, "    head: {}", act.state)
+            }
+            ActorChange::Removed(act) => writeln!(f, "- {} (code: {})", self.address, act.code),
+            ActorChange::Modified {
+                before,
+                after,
+                fields,
+            } => {
+                writeln!(f, "~ {}", self.address)?;
+                if before.code != after.code {
+                    writeln!(f, "    code: {} -> {}", before.code, after.code)?;
+                }
+                if before.balance != after.balance {
+                    writeln!(f, "    balance: {} -> {}", before.balance, after.balance)?;
+                }
+                if before.sequence != after.sequence {
+                    writeln!(f, "    sequence: {} -> {}", before.sequence, after.sequence)?;
+                }
+                if before.state != after.state {
+                    writeln!(f, "    head: {} -> {}", before.state, after.state)?;
+                }
+                for field in fields {
+                    writeln!(f, "      {}", field)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => write!(f, "{}: {:?} -> {:?}", self.path, before, after),
+            (None, Some(after)) => write!(f, "+ {}: {:?}", self.path, after),
+            (Some(before), None) => write!(f, "- {}: {:?}", self.path, before),
+            (None, None) => write!(f, "{}", self.path),
+        }
+    }
+}
@@ -1,8 +1,14 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod diff;
+mod metrics;
+
+pub use self::diff::*;
+pub use self::metrics::*;
+
 use actor::{init, INIT_ACTOR_ADDR};
-use address::{Address, Protocol};
+use address::{Address, Protocol, MAX_ADDRESS_BYTES_LEN};
 use cid::{multihash::Blake2b256, Cid};
 use fnv::FnvHashMap;
 use ipld_blockstore::BlockStore;
@@ -12,6 +18,15 @@ use vm::ActorState;
 
 const TREE_BIT_WIDTH: u8 = 5;
 
+/// Encodes the address into the buffer and returns the encoded bytes, which key the actor of
+/// the address in the tree, so that lookups don't allocate.
+fn address_key<'a>(addr: &Address, buf: &'a mut [u8; MAX_ADDRESS_BYTES_LEN]) -> &'a [u8] {
+    let len = 1 + addr.payload_len();
+    addr.write_bytes(&mut &mut buf[..])
+        .expect("buffer fits any address");
+    &buf[..len]
+}
+
 /// State tree implementation using hamt
 pub struct StateTree<'db, S> {
     hamt: Hamt<'db, BytesKey, S>,
@@ -59,7 +74,11 @@ where
         }
 
         // if state doesn't exist, find using hamt
-        let act: Option<ActorState> = self.hamt.get(&addr.to_bytes()).map_err(|e| e.to_string())?;
+        let mut key = [0u8; MAX_ADDRESS_BYTES_LEN];
+        let act: Option<ActorState> = self
+            .hamt
+            .get(address_key(&addr, &mut key))
+            .map_err(|e| e.to_string())?;
 
         // Update cache if state was found
         if let Some(act_s) = &act {
@@ -111,6 +130,35 @@ where
         state.resolve_address(self.store(), addr)
     }
 
+    /// Returns the delegated address of the actor at an address, if it was assigned one.
+    pub fn lookup_delegated_address(&self, addr: &Address) -> Result<Option<Address>, String> {
+        let act = self
+            .get_actor(addr)?
+            .ok_or_else(|| format!("Actor for address: {} does not exist", addr))?;
+        Ok(act.delegated_address)
+    }
+
+    /// Assigns a delegated address to the actor at an address. Fails if the actor already has
+    /// a different delegated address, or if the delegated address is an ID address.
+    pub fn set_delegated_address(
+        &mut self,
+        addr: &Address,
+        delegated: Address,
+    ) -> Result<(), String> {
+        if delegated.protocol() == Protocol::ID {
+            return Err(format!("Delegated address {} is an ID address", delegated));
+        }
+        self.mutate_actor(addr, |act| match act.delegated_address {
+            Some(existing) if existing != delegated => {
+                Err(format!("Actor already has delegated address {}", existing))
+            }
+            _ => {
+                act.delegated_address = Some(delegated);
+                Ok(())
+            }
+        })
+    }
+
     /// Delete actor for an address. Will resolve to ID address to delete.
     pub fn delete_actor(&mut self, addr: &Address) -> Result<(), String> {
         let addr = self
@@ -120,8 +168,9 @@ where
         // Remove value from cache
         self.actor_cache.write().remove(&addr);
 
+        let mut key = [0u8; MAX_ADDRESS_BYTES_LEN];
         self.hamt
-            .delete(&addr.to_bytes())
+            .delete(address_key(&addr, &mut key))
             .map_err(|e| e.to_string())?;
 
         Ok(())
@@ -158,9 +207,7 @@ where
             .ok_or("Failed to retrieve init actor state")?;
 
         // Create new address with init actor state
-        let new_addr = ias
-            .map_address_to_new_id(self.store(), addr)
-            .map_err(|e| e.to_string())?;
+        let new_addr = ias.map_address_to_new_id(self.store(), addr)?;
 
         // Set state for init actor in store and update root Cid
         init_act.state = self
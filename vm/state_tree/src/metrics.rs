@@ -0,0 +1,40 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::diff::load_actors;
+use address::Address;
+use cid::Cid;
+use ipld_blockstore::BlockStore;
+
+/// Serialized size of the head state of an actor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorStateSize {
+    pub address: Address,
+    pub code: Cid,
+    /// Size in bytes of the head block of the actor's state, not including the data it links
+    /// to. `None` if the head is not available in the store.
+    pub head_size: Option<usize>,
+}
+
+/// Returns the serialized size of the head state of each actor in a state root, ordered by
+/// actor address.
+pub fn state_sizes<BS>(store: &BS, root: &Cid) -> Result<Vec<ActorStateSize>, String>
+where
+    BS: BlockStore,
+{
+    load_actors(store, root)?
+        .into_iter()
+        .map(|(key, act)| {
+            let address = Address::from_bytes(&key).map_err(|e| e.to_string())?;
+            let head_size = store
+                .get_bytes(&act.state)
+                .map_err(|e| e.to_string())?
+                .map(|bz| bz.len());
+            Ok(ActorStateSize {
+                address,
+                code: act.code,
+                head_size,
+            })
+        })
+        .collect()
+}
@@ -1,9 +1,12 @@
 // Copyright 2020 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use actor::{init, ActorState, INIT_ACTOR_ADDR};
+use actor::{
+    account, init, ActorState, ACCOUNT_ACTOR_CODE_ID, INIT_ACTOR_ADDR, MULTISIG_ACTOR_CODE_ID,
+};
 use address::{Address, SECP_PUB_LEN};
 use cid::{multihash::Identity, Cid};
+use forest_ipld::Ipld;
 use ipld_blockstore::BlockStore;
 use ipld_hamt::Hamt;
 use state_tree::*;
@@ -82,7 +85,8 @@ fn get_set_non_id() {
             code: empty_cid(),
             state: state_cid,
             balance: Default::default(),
-            sequence: 2
+            sequence: 2,
+            delegated_address: None,
         })
     );
 
@@ -97,3 +101,234 @@ fn get_set_non_id() {
     assert_eq!(tree.snapshot().unwrap(), snapshot);
     assert_eq!(tree.get_actor(&INIT_ACTOR_ADDR).unwrap(), None);
 }
+
+#[test]
+fn state_diff_actors() {
+    let store = db::MemoryDB::default();
+    let mut tree = StateTree::new(&store);
+
+    let head_1 = store
+        .put(
+            &account::State {
+                address: Address::new_id(1),
+            },
+            Identity,
+        )
+        .unwrap();
+    let head_2 = store
+        .put(
+            &account::State {
+                address: Address::new_id(2),
+            },
+            Identity,
+        )
+        .unwrap();
+
+    let act_1 = ActorState::new(
+        ACCOUNT_ACTOR_CODE_ID.clone(),
+        head_1.clone(),
+        Default::default(),
+        0,
+    );
+    let act_2 = ActorState::new(empty_cid(), empty_cid(), Default::default(), 0);
+    tree.set_actor(&Address::new_id(1), act_1.clone()).unwrap();
+    tree.set_actor(&Address::new_id(2), act_2.clone()).unwrap();
+    let root_a = tree.flush().unwrap();
+    assert!(state_diff(&store, &root_a, &root_a).unwrap().is_empty());
+
+    let mut act_1_b = act_1.clone();
+    act_1_b.state = head_2;
+    act_1_b.sequence = 1;
+    tree.set_actor(&Address::new_id(1), act_1_b.clone())
+        .unwrap();
+    tree.delete_actor(&Address::new_id(2)).unwrap();
+    tree.set_actor(&Address::new_id(3), act_2.clone()).unwrap();
+    let root_b = tree.flush().unwrap();
+
+    let diff = state_diff(&store, &root_a, &root_b).unwrap();
+    assert_eq!(
+        diff.actors,
+        vec![
+            ActorDiff {
+                address: Address::new_id(1),
+                change: ActorChange::Modified {
+                    before: act_1,
+                    after: act_1_b,
+                    fields: vec![FieldDiff {
+                        path: "address".to_owned(),
+                        before: Some(Ipld::Bytes(Address::new_id(1).to_bytes())),
+                        after: Some(Ipld::Bytes(Address::new_id(2).to_bytes())),
+                    }],
+                },
+            },
+            ActorDiff {
+                address: Address::new_id(2),
+                change: ActorChange::Removed(act_2.clone()),
+            },
+            ActorDiff {
+                address: Address::new_id(3),
+                change: ActorChange::Added(act_2),
+            },
+        ]
+    );
+    assert!(diff.to_string().contains("address: "));
+}
+
+#[test]
+fn state_diff_multisig_signer_weights() {
+    let store = db::MemoryDB::default();
+    let mut tree = StateTree::new(&store);
+    let addr = Address::new_id(100);
+
+    // Fields of a weighted multisig state, only the signer weights are changed
+    let head = |weights: Vec<i128>| {
+        let mut fields = vec![Ipld::Null; 8];
+        fields.push(Ipld::List(weights.into_iter().map(Ipld::Integer).collect()));
+        store.put(&Ipld::List(fields), Identity).unwrap()
+    };
+    let act_a = ActorState::new(
+        MULTISIG_ACTOR_CODE_ID.clone(),
+        head(vec![1, 2]),
+        Default::default(),
+        0,
+    );
+    tree.set_actor(&addr, act_a.clone()).unwrap();
+    let root_a = tree.flush().unwrap();
+
+    let mut act_b = act_a.clone();
+    act_b.state = head(vec![1, 3]);
+    tree.set_actor(&addr, act_b.clone()).unwrap();
+    let root_b = tree.flush().unwrap();
+
+    let diff = state_diff(&store, &root_a, &root_b).unwrap();
+    assert_eq!(
+        diff.actors,
+        vec![ActorDiff {
+            address: addr,
+            change: ActorChange::Modified {
+                before: act_a,
+                after: act_b,
+                fields: vec![FieldDiff {
+                    path: "signer_weights.1".to_owned(),
+                    before: Some(Ipld::Integer(2)),
+                    after: Some(Ipld::Integer(3)),
+                }],
+            },
+        }]
+    );
+}
+
+#[test]
+fn state_diff_added_field() {
+    let store = db::MemoryDB::default();
+    let mut tree = StateTree::new(&store);
+    let addr = Address::new_id(100);
+
+    // A multisig state without signer weights, then with them
+    let act_a = ActorState::new(
+        MULTISIG_ACTOR_CODE_ID.clone(),
+        store
+            .put(&Ipld::List(vec![Ipld::Null; 8]), Identity)
+            .unwrap(),
+        Default::default(),
+        0,
+    );
+    tree.set_actor(&addr, act_a.clone()).unwrap();
+    let root_a = tree.flush().unwrap();
+
+    let mut fields = vec![Ipld::Null; 8];
+    fields.push(Ipld::List(vec![Ipld::Integer(1)]));
+    let mut act_b = act_a.clone();
+    act_b.state = store.put(&Ipld::List(fields), Identity).unwrap();
+    tree.set_actor(&addr, act_b.clone()).unwrap();
+    let root_b = tree.flush().unwrap();
+
+    let diff = state_diff(&store, &root_a, &root_b).unwrap();
+    assert_eq!(
+        diff.actors,
+        vec![ActorDiff {
+            address: addr,
+            change: ActorChange::Modified {
+                before: act_a,
+                after: act_b,
+                fields: vec![FieldDiff {
+                    path: "signer_weights".to_owned(),
+                    before: None,
+                    after: Some(Ipld::List(vec![Ipld::Integer(1)])),
+                }],
+            },
+        }]
+    );
+    assert!(diff.to_string().contains("+ signer_weights: "));
+
+    // And the other way around the field is removed
+    let diff = state_diff(&store, &root_b, &root_a).unwrap();
+    assert!(diff.to_string().contains("- signer_weights: "));
+}
+
+#[test]
+fn actor_state_sizes() {
+    let store = db::MemoryDB::default();
+    let mut tree = StateTree::new(&store);
+
+    let state = account::State {
+        address: Address::new_id(1),
+    };
+    let head = store.put(&state, Identity).unwrap();
+    let head_size = store.get_bytes(&head).unwrap().unwrap().len();
+    let act_1 = ActorState::new(ACCOUNT_ACTOR_CODE_ID.clone(), head, Default::default(), 0);
+    // Head of the second actor is not in the store
+    let act_2 = ActorState::new(empty_cid(), empty_cid(), Default::default(), 0);
+    tree.set_actor(&Address::new_id(2), act_2).unwrap();
+    tree.set_actor(&Address::new_id(1), act_1).unwrap();
+    let root = tree.flush().unwrap();
+
+    assert_eq!(
+        state_sizes(&store, &root).unwrap(),
+        vec![
+            ActorStateSize {
+                address: Address::new_id(1),
+                code: ACCOUNT_ACTOR_CODE_ID.clone(),
+                head_size: Some(head_size),
+            },
+            ActorStateSize {
+                address: Address::new_id(2),
+                code: empty_cid(),
+                head_size: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn delegated_address() {
+    let store = db::MemoryDB::default();
+    let mut tree = StateTree::new(&store);
+    let addr = Address::new_id(1);
+    let delegated = Address::new_actor(b"predictable");
+    tree.set_actor(
+        &addr,
+        ActorState::new(empty_cid(), empty_cid(), Default::default(), 1),
+    )
+    .unwrap();
+    assert_eq!(tree.lookup_delegated_address(&addr).unwrap(), None);
+
+    assert!(tree
+        .set_delegated_address(&addr, Address::new_id(2))
+        .is_err());
+    tree.set_delegated_address(&addr, delegated).unwrap();
+    // Setting the same address again is a no-op, but it can't be replaced
+    tree.set_delegated_address(&addr, delegated).unwrap();
+    assert!(tree
+        .set_delegated_address(&addr, Address::new_actor(b"other"))
+        .is_err());
+
+    // The delegated address is persisted with the actor state
+    let root = tree.flush().unwrap();
+    let tree = StateTree::new_from_root(&store, &root).unwrap();
+    assert_eq!(
+        tree.lookup_delegated_address(&addr).unwrap(),
+        Some(delegated)
+    );
+    assert!(tree.lookup_delegated_address(&Address::new_id(2)).is_err());
+}
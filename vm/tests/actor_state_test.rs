@@ -0,0 +1,61 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use address::Address;
+use cid::{multihash::Identity, Cid};
+use encoding::{from_slice, to_vec};
+use forest_vm::{ActorID, ActorState, TokenAmount};
+use num_bigint::bigint_ser::BigIntSer;
+use std::convert::TryFrom;
+
+fn actor() -> ActorState {
+    let cid = Cid::new_from_cbor(&[], Identity);
+    ActorState::new(cid.clone(), cid, TokenAmount::from(10u8), 3)
+}
+
+#[test]
+fn actor_state_without_delegated_address() {
+    let act = actor();
+    let bz = to_vec(&act).unwrap();
+
+    // Serialized as the four field tuple of earlier network versions
+    let legacy = to_vec(&(
+        &act.code,
+        &act.state,
+        &act.sequence,
+        BigIntSer(&act.balance),
+    ))
+    .unwrap();
+    assert_eq!(bz, legacy);
+    assert_eq!(from_slice::<ActorState>(&bz).unwrap(), act);
+}
+
+#[test]
+fn actor_state_with_delegated_address() {
+    let mut act = actor();
+    act.delegated_address = Some(Address::new_actor(b"predictable"));
+
+    let bz = to_vec(&act).unwrap();
+    assert_eq!(from_slice::<ActorState>(&bz).unwrap(), act);
+
+    // Tuples of other lengths are rejected
+    let short = to_vec(&(&act.code, &act.state, &act.sequence)).unwrap();
+    assert!(from_slice::<ActorState>(&short).is_err());
+    let long = to_vec(&(
+        &act.code,
+        &act.state,
+        &act.sequence,
+        BigIntSer(&act.balance),
+        act.delegated_address.unwrap(),
+        0u8,
+    ))
+    .unwrap();
+    assert!(from_slice::<ActorState>(&long).is_err());
+}
+
+#[test]
+fn actor_id_from_address() {
+    assert_eq!(ActorID::try_from(&Address::new_id(1234)), Ok(ActorID(1234)));
+    assert_eq!(ActorID(1234).to_address(), Address::new_id(1234));
+    assert!(ActorID::try_from(&Address::new_actor(b"actor")).is_err());
+}
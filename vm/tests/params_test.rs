@@ -3,7 +3,8 @@
 
 use address::Address;
 use encoding::{from_slice, to_vec};
-use forest_vm::Serialized;
+use forest_vm::{MethodNum, Serialized, METHOD_CONSTRUCTOR, METHOD_SEND};
+use std::convert::TryFrom;
 
 #[test]
 fn serialized_deserialize() {
@@ -38,3 +39,27 @@ fn cbor_params() {
     let params = Serialized::serialize(&addr).unwrap();
     assert_eq!(from_slice::<Address>(&params).unwrap(), addr);
 }
+
+#[test]
+fn method_num() {
+    assert_eq!(METHOD_SEND, MethodNum::new(0));
+    assert_eq!(METHOD_CONSTRUCTOR.value(), 1);
+    assert_eq!(MethodNum::from(7u64), MethodNum::new(7));
+    assert_eq!(MethodNum::new(7).to_string(), "7");
+
+    // Encoded as an unsigned integer
+    let bz = to_vec(&MethodNum::new(u64::MAX)).unwrap();
+    assert_eq!(bz, to_vec(&u64::MAX).unwrap());
+    assert_eq!(
+        from_slice::<MethodNum>(&bz).unwrap(),
+        MethodNum::new(u64::MAX)
+    );
+    assert!(from_slice::<MethodNum>(&to_vec(&-1i64).unwrap()).is_err());
+
+    // User supplied method numbers must be unsigned
+    assert_eq!(MethodNum::try_from(2i64), Ok(MethodNum::new(2)));
+    assert!(MethodNum::try_from(-1i64).is_err());
+    assert_eq!("2".parse::<MethodNum>(), Ok(MethodNum::new(2)));
+    assert!("-1".parse::<MethodNum>().is_err());
+    assert!("18446744073709551616".parse::<MethodNum>().is_err());
+}
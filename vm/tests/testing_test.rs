@@ -0,0 +1,43 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+#![cfg(feature = "testing")]
+
+use address::Protocol;
+use forest_vm::{
+    testing::{TestGen, FIRST_TEST_ACTOR_ID},
+    TokenAmount,
+};
+use std::collections::HashSet;
+
+#[test]
+fn same_seed_same_values() {
+    let mut a = TestGen::new(7);
+    let mut b = TestGen::new(7);
+    for _ in 0..20 {
+        assert_eq!(a.address(), b.address());
+        assert_eq!(a.cid(), b.cid());
+        assert_eq!(a.token_amount(100), b.token_amount(100));
+        assert_eq!(a.bit_field(100, 0.3), b.bit_field(100, 0.3));
+    }
+    assert_ne!(TestGen::new(8).cid(), TestGen::new(7).cid());
+}
+
+#[test]
+fn generated_values() {
+    let mut gen = TestGen::new(0);
+    assert_eq!(gen.id_address().id(), Ok(FIRST_TEST_ACTOR_ID));
+    assert_eq!(gen.id_address().id(), Ok(FIRST_TEST_ACTOR_ID + 1));
+    assert_eq!(gen.secp_address().protocol(), Protocol::Secp256k1);
+    assert_eq!(gen.actor_address().protocol(), Protocol::Actor);
+    assert_eq!(gen.bls_address().protocol(), Protocol::BLS);
+
+    let protocols: HashSet<_> = (0..100).map(|_| gen.address().protocol()).collect();
+    assert_eq!(protocols.len(), 4);
+
+    assert!(gen.token_amount(10) < TokenAmount::from(10u8));
+    let bf = gen.bit_field(64, 0.5);
+    assert!(bf.iter().all(|i| i < 64));
+    assert!(gen.bit_field(64, 0.0).is_empty());
+    assert_eq!(gen.bit_field(64, 1.0).len(), 64);
+}
@@ -0,0 +1,138 @@
+// Copyright 2020 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use encoding::{from_slice, to_vec};
+use forest_vm::{NonNegTokenAmount, TokenAmount, TokenAmountError, TOKEN_PRECISION};
+use num_bigint::{bigint_ser::BigIntSer, BigInt};
+use std::convert::TryFrom;
+
+#[test]
+fn non_negative_amounts() {
+    assert_eq!(NonNegTokenAmount::new(TokenAmount::from(-1)), None);
+    assert!(NonNegTokenAmount::try_from(TokenAmount::from(-1)).is_err());
+    assert_eq!(
+        NonNegTokenAmount::new_saturating(TokenAmount::from(-1)),
+        NonNegTokenAmount::default()
+    );
+    assert_eq!(
+        NonNegTokenAmount::new(TokenAmount::from(0u8)),
+        Some(NonNegTokenAmount::from(0))
+    );
+
+    let a = NonNegTokenAmount::from(5);
+    let b = NonNegTokenAmount::from(7);
+    assert_eq!(b.checked_sub(&a), Some(NonNegTokenAmount::from(2)));
+    assert_eq!(a.checked_sub(&b), None);
+    assert_eq!(a.checked_sub(&a), Some(NonNegTokenAmount::default()));
+    assert_eq!(a.clone() + &b, NonNegTokenAmount::from(12));
+    assert_eq!(TokenAmount::from(a), TokenAmount::from(5u8));
+}
+
+#[test]
+fn serializes_as_token_amount() {
+    for amount in &[0u64, 1, 255, 1 << 40] {
+        let token_amount = TokenAmount::from(*amount);
+        let bz = to_vec(&NonNegTokenAmount::from(*amount)).unwrap();
+        assert_eq!(bz, to_vec(&token_amount).unwrap());
+        assert_eq!(bz, to_vec(&BigIntSer(&BigInt::from(*amount))).unwrap());
+        assert_eq!(from_slice::<TokenAmount>(&bz).unwrap(), token_amount);
+        assert_eq!(
+            from_slice::<NonNegTokenAmount>(&bz).unwrap(),
+            NonNegTokenAmount::from(*amount)
+        );
+    }
+
+    // Negative amounts can't be decoded
+    let bz = to_vec(&TokenAmount::from(-3)).unwrap();
+    assert!(from_slice::<NonNegTokenAmount>(&bz).is_err());
+}
+
+#[test]
+fn fil_strings() {
+    let fil = TokenAmount::from(TOKEN_PRECISION);
+    for (amount, s) in &[
+        (TokenAmount::from(0u8), "0 FIL"),
+        (fil.clone(), "1 FIL"),
+        (&fil * 3 / 2, "1.5 FIL"),
+        (TokenAmount::from(1u8), "0.000000000000000001 FIL"),
+        (
+            &fil * 2_000_000_000u64 + TokenAmount::from(10u8),
+            "2000000000.00000000000000001 FIL",
+        ),
+        (-(&fil / 4), "-0.25 FIL"),
+    ] {
+        assert_eq!(amount.to_fil_string(), *s);
+        assert_eq!(amount.to_string(), *s);
+        assert_eq!(&s.parse::<TokenAmount>().unwrap(), amount);
+    }
+
+    assert_eq!(TokenAmount::from_fil_str("2").unwrap(), &fil * 2);
+    assert_eq!(TokenAmount::from_fil_str(" .5fil ").unwrap(), &fil / 2);
+    assert_eq!(TokenAmount::from_fil_str("3.").unwrap(), &fil * 3);
+    assert_eq!(TokenAmount::from_whole(3), &fil * 3);
+    for s in &[
+        "",
+        "FIL",
+        ".",
+        "-",
+        "1.2.3",
+        "1e18",
+        "+1",
+        "0.0000000000000000001",
+    ] {
+        assert_eq!(
+            TokenAmount::from_fil_str(s),
+            Err(TokenAmountError::InvalidFilString(s.to_string())),
+            "{}",
+            s
+        );
+    }
+}
+
+#[test]
+fn try_sub_errors_on_negative_result() {
+    let a = NonNegTokenAmount::from(5);
+    let b = NonNegTokenAmount::from(7);
+    assert_eq!(b.try_sub(&a), Ok(NonNegTokenAmount::from(2)));
+    assert_eq!(
+        a.try_sub(&b),
+        Err(TokenAmountError::InsufficientAmount {
+            available: TokenAmount::from(5u8),
+            required: TokenAmount::from(7u8),
+        })
+    );
+    assert_eq!(b.to_fil_string(), "0.000000000000000007 FIL");
+}
+
+#[test]
+fn token_amount_arithmetic() {
+    let a = TokenAmount::from(6u8);
+    let b = TokenAmount::from(4u8);
+    assert_eq!(&a + &b, TokenAmount::from(10u8));
+    assert_eq!(&b - &a, TokenAmount::from(-2));
+    assert_eq!(&a * 3, TokenAmount::from(18u8));
+    assert_eq!(&a * BigInt::from(3), TokenAmount::from(18u8));
+    assert_eq!(&a / 4, TokenAmount::from(1u8));
+    assert_eq!(&a / &b, BigInt::from(1));
+    assert_eq!(-&a, TokenAmount::from(-6));
+    assert_eq!(
+        vec![a.clone(), b.clone()].iter().sum::<TokenAmount>(),
+        TokenAmount::from(10u8)
+    );
+
+    let mut c = a.clone();
+    c += &b;
+    c -= TokenAmount::from(1u8);
+    assert_eq!(c, TokenAmount::from(9u8));
+    assert_eq!(c.atto(), &BigInt::from(9));
+
+    assert_eq!(a.checked_sub(&b), Some(TokenAmount::from(2u8)));
+    assert_eq!(b.checked_sub(&a), None);
+    assert_eq!(
+        b.try_sub(&a),
+        Err(TokenAmountError::InsufficientAmount {
+            available: b.clone(),
+            required: a.clone(),
+        })
+    );
+}